@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: MIT
+
+// The rows of the shown tasks are built on every reload and every filter change, run it with `cargo bench`
+
+use std::{any::Any, sync::Arc};
+
+use chrono::{Duration, Utc};
+use criterion::{BatchSize, Criterion, criterion_group, criterion_main};
+use tatuin::ui::{Marks, TaskRow};
+use tatuin_core::{
+    RichString,
+    project::Project as ProjectTrait,
+    task::{DateTimeUtc, Priority, State, Task as TaskTrait},
+    task_patch::{TaskPatch, ValuePatch},
+};
+
+const TASKS_COUNT: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct BenchTask {
+    id: String,
+    name: String,
+    description: String,
+    priority: Priority,
+    state: State,
+    due: Option<DateTimeUtc>,
+    labels: Vec<String>,
+}
+
+impl TaskTrait for BenchTask {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+    fn name(&self) -> RichString {
+        RichString::new(&self.name)
+    }
+    fn description(&self) -> Option<RichString> {
+        Some(RichString::new(&self.description))
+    }
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+    fn state(&self) -> State {
+        self.state
+    }
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+    fn labels(&self) -> Vec<String> {
+        self.labels.clone()
+    }
+    fn provider(&self) -> String {
+        "Todoist".to_string()
+    }
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        None
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+}
+
+fn tasks(count: usize) -> Vec<Arc<dyn TaskTrait>> {
+    let now = Utc::now();
+    let priorities = Priority::values();
+    (0..count)
+        .map(|i| {
+            Arc::new(BenchTask {
+                id: i.to_string(),
+                name: format!("Task {i} with [the link](https://example.com/{i}) and **more** text"),
+                description: format!("The description of the task {i}"),
+                priority: priorities[i % priorities.len()],
+                state: if i % 5 == 0 {
+                    State::Completed
+                } else {
+                    State::Uncompleted
+                },
+                due: (i % 3 != 0).then(|| now + Duration::days(i as i64 % 30 - 10)),
+                labels: vec![format!("tag{}", i % 20)],
+            }) as Arc<dyn TaskTrait>
+        })
+        .collect()
+}
+
+fn rows(tasks: &[Arc<dyn TaskTrait>], changed: &[TaskPatch]) -> Vec<TaskRow> {
+    tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| TaskRow::new(t, changed, i % 3, Marks::default()))
+        .collect()
+}
+
+fn task_rows(c: &mut Criterion) {
+    let tasks = tasks(TASKS_COUNT);
+    // every tenth task has the uncommitted change
+    let changed = tasks
+        .iter()
+        .step_by(10)
+        .map(|t| TaskPatch {
+            task: Some(t.clone()),
+            name: ValuePatch::Value(format!("{} renamed", t.name().raw())),
+            ..TaskPatch::default()
+        })
+        .collect::<Vec<TaskPatch>>();
+
+    c.bench_function(&format!("build {TASKS_COUNT} task rows"), |b| {
+        b.iter(|| rows(&tasks, &changed))
+    });
+
+    // the rows of the tasks that aren't changed by the reload are reused
+    c.bench_function(&format!("update {TASKS_COUNT} task rows"), |b| {
+        b.iter_batched(
+            || rows(&tasks, &changed),
+            |rows| {
+                rows.into_iter()
+                    .zip(&tasks)
+                    .enumerate()
+                    .map(|(i, (r, t))| r.update(t, &changed, i % 3, Marks::default()))
+                    .collect::<Vec<TaskRow>>()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // every iteration builds all the rows, so the default sample is too long
+    config = Criterion::default().sample_size(10);
+    targets = task_rows
+}
+criterion_main!(benches);
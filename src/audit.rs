@@ -0,0 +1,344 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tatuin_core::{
+    filter,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{Priority, Task as TaskTrait},
+    task_patch::{PatchError, TaskPatch, ValuePatch},
+};
+use tokio::sync::broadcast;
+
+use crate::settings::Audit;
+
+pub const AUDIT_FILE_NAME: &str = "audit.log";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Serialize)]
+struct Record<'a> {
+    time: String,
+    provider: &'a str,
+    provider_type: &'a str,
+    action: Action,
+    target: String,
+    task: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<&'static str, Option<String>>,
+    result: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// Append-only log of the mutating provider calls.
+// The file is rotated when it becomes bigger than the limit: audit.log -> audit.log.1 -> audit.log.2 ...
+pub struct AuditLog {
+    path: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    pub fn new(folder: &Path, s: &Audit) -> Self {
+        Self {
+            path: folder.join(AUDIT_FILE_NAME),
+            max_file_size: s.max_file_size_kb * 1024,
+            max_files: s.max_files,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn rotated_path(&self, idx: usize) -> PathBuf {
+        let mut p = self.path.clone().into_os_string();
+        p.push(format!(".{idx}"));
+        p.into()
+    }
+
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.max_files == 0 {
+            return std::fs::remove_file(&self.path);
+        }
+
+        for idx in (1..self.max_files).rev() {
+            let from = self.rotated_path(idx);
+            if from.exists() {
+                std::fs::rename(from, self.rotated_path(idx + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated_path(1))
+    }
+
+    fn write(&self, line: &str) -> std::io::Result<()> {
+        let _guard = self.lock.lock().unwrap();
+
+        if std::fs::metadata(&self.path).is_ok_and(|m| m.len() + line.len() as u64 > self.max_file_size) {
+            self.rotate()?;
+        }
+
+        let mut f = std::fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(f, "{line}")
+    }
+
+    fn record(&self, r: &Record) {
+        let result = serde_json::to_string(r)
+            .map_err(std::io::Error::other)
+            .and_then(|line| self.write(&line));
+        if let Err(e) = result {
+            tracing::error!(error=?e, path=?self.path, "Write the audit record");
+        }
+    }
+}
+
+fn value_to_str<T: std::fmt::Display>(v: &ValuePatch<T>) -> Option<String> {
+    match v {
+        ValuePatch::Value(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+fn patch_fields(tp: &TaskPatch) -> BTreeMap<&'static str, Option<String>> {
+    let mut fields = BTreeMap::new();
+    if tp.name.is_set() {
+        fields.insert("name", value_to_str(&tp.name));
+    }
+    if tp.description.is_set() {
+        fields.insert("description", value_to_str(&tp.description));
+    }
+    if tp.due.is_set() {
+        fields.insert("due", value_to_str(&tp.due));
+    }
+    if tp.scheduled.is_set() {
+        fields.insert("scheduled", value_to_str(&tp.scheduled));
+    }
+    if tp.priority.is_set() {
+        fields.insert("priority", value_to_str(&tp.priority));
+    }
+    if tp.state.is_set() {
+        fields.insert("state", tp.state.ref_value().map(|s| format!("{s:?}")));
+    }
+    if tp.labels.is_set() {
+        fields.insert("labels", tp.labels.ref_value().map(|l| l.join(",")));
+    }
+    fields
+}
+
+// Wraps the provider to write every mutating call into the audit log
+pub struct AuditedProvider {
+    inner: Box<dyn ProviderTrait>,
+    log: Arc<AuditLog>,
+}
+
+impl AuditedProvider {
+    pub fn new(inner: Box<dyn ProviderTrait>, log: Arc<AuditLog>) -> Self {
+        Self { inner, log }
+    }
+
+    fn record(
+        &self,
+        action: Action,
+        target: String,
+        task: Option<String>,
+        fields: BTreeMap<&'static str, Option<String>>,
+        error: Option<String>,
+    ) {
+        self.log.record(&Record {
+            time: chrono::Utc::now().to_rfc3339(),
+            provider: &self.inner.name(),
+            provider_type: &self.inner.type_name(),
+            action,
+            target,
+            task,
+            fields,
+            result: if error.is_some() { "error" } else { "ok" },
+            error,
+        });
+    }
+}
+
+impl std::fmt::Debug for AuditedProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for AuditedProvider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        ProjectProviderTrait::list(self.inner.as_mut()).await
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for AuditedProvider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        TaskProviderTrait::list(self.inner.as_mut(), project, f).await
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let result = self.inner.create(project_id, tp).await;
+        self.record(
+            Action::Create,
+            project_id.to_string(),
+            tp.name.value(),
+            patch_fields(tp),
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+        result
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let errors = self.inner.update(patches).await;
+        for tp in patches {
+            let task = tp.task.as_ref();
+            let id = task.map(|t| t.id()).unwrap_or_default();
+            let error = errors.iter().find(|e| e.task.id() == id).map(|e| e.error.to_string());
+            self.record(
+                Action::Update,
+                id,
+                task.map(|t| t.name().raw()),
+                patch_fields(tp),
+                error,
+            );
+        }
+        errors
+    }
+
+    async fn set_order(&mut self, ids: &[String]) -> Result<(), ProviderError> {
+        self.inner.set_order(ids).await
+    }
+
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        self.inner.query(query).await
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let result = self.inner.delete(t).await;
+        self.record(
+            Action::Delete,
+            t.id(),
+            Some(t.name().raw()),
+            BTreeMap::new(),
+            result.as_ref().err().map(|e| e.to_string()),
+        );
+        result
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for AuditedProvider {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn type_name(&self) -> String {
+        self.inner.type_name()
+    }
+
+    async fn reload(&mut self) {
+        self.inner.reload().await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn supported_priorities(&self) -> Vec<Priority> {
+        self.inner.supported_priorities()
+    }
+
+    fn sync_state(&self) -> SyncState {
+        self.inner.sync_state()
+    }
+
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.inner.clear_cache().await
+    }
+
+    fn subscribe_on_changes(&self) -> Option<broadcast::Receiver<()>> {
+        self.inner.subscribe_on_changes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::task::State;
+
+    fn record(action: Action, error: Option<&str>) -> Record<'static> {
+        Record {
+            time: "2025-01-02T03:04:05+00:00".to_string(),
+            provider: "Home",
+            provider_type: "Tatuin",
+            action,
+            target: "id".to_string(),
+            task: Some("Task".to_string()),
+            fields: BTreeMap::new(),
+            result: if error.is_some() { "error" } else { "ok" },
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn patch_fields_test() {
+        let tp = TaskPatch {
+            name: ValuePatch::Value("New name".to_string()),
+            description: ValuePatch::Empty,
+            priority: ValuePatch::Value(Priority::High),
+            state: ValuePatch::Value(State::Completed),
+            ..TaskPatch::default()
+        };
+        let mut r = record(Action::Update, None);
+        r.fields = patch_fields(&tp);
+        assert_eq!(
+            serde_json::to_string(&r).unwrap(),
+            r#"{"time":"2025-01-02T03:04:05+00:00","provider":"Home","provider_type":"Tatuin","action":"update","target":"id","task":"Task","fields":{"description":null,"name":"New name","priority":"High","state":"Completed"},"result":"ok"}"#
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn rotation_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let log = AuditLog::new(
+            temp_dir.path(),
+            &Audit {
+                enabled: true,
+                max_file_size_kb: 1,
+                max_files: 2,
+            },
+        );
+
+        let r = record(Action::Delete, Some("Not found"));
+        let line_len = serde_json::to_string(&r).unwrap().len() + 1;
+        let records_count = 1024 / line_len;
+        for _ in 0..records_count * 4 {
+            log.record(&r);
+        }
+
+        let lines = |p: &Path| std::fs::read_to_string(p).unwrap().lines().count();
+        assert_eq!(lines(&log.path), records_count);
+        assert_eq!(lines(&log.rotated_path(1)), records_count);
+        assert_eq!(lines(&log.rotated_path(2)), records_count);
+        assert!(!log.rotated_path(3).exists());
+    }
+}
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use color_eyre::owo_colors::OwoColorize;
+use tatuin_core::{
+    StringError,
+    task_patch::{TaskPatch, ValuePatch},
+};
+use tatuin_providers::{
+    capture::{Bridge, Message, SourceTrait, matrix, slack},
+    config::Config,
+};
+
+use crate::{
+    cli::{self, CliError},
+    import::find_destination,
+    provider::Provider,
+};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_SLACK_EMOJI: &str = "star";
+const DEFAULT_MATRIX_EMOJI: &str = "⭐";
+
+struct Capture {
+    bridge: Bridge,
+    into: String,
+    interval: Duration,
+    next_run: Instant,
+}
+
+fn new_source(name: &str, cfg: &HashMap<String, String>) -> Result<Box<dyn SourceTrait>, StringError> {
+    let value = |key: &str| -> Result<&str, StringError> {
+        cfg.get(key).map(String::as_str).ok_or(StringError::new(
+            format!("Capture '{name}': the key '{key}' is required").as_str(),
+        ))
+    };
+
+    match value("type")? {
+        slack::SOURCE_NAME => Ok(Box::new(slack::Source::new(
+            value("token")?,
+            value("channel")?,
+            value("emoji").unwrap_or(DEFAULT_SLACK_EMOJI),
+        )?)),
+        matrix::SOURCE_NAME => Ok(Box::new(matrix::Source::new(
+            value("homeserver")?,
+            value("token")?,
+            value("room")?,
+            value("emoji").unwrap_or(DEFAULT_MATRIX_EMOJI),
+        )?)),
+        t => Err(StringError::new(
+            format!("Capture '{name}': unknown type '{t}'").as_str(),
+        )),
+    }
+}
+
+fn load(app_name: &str, settings: &HashMap<String, HashMap<String, String>>) -> Result<Vec<Capture>, StringError> {
+    let mut result = Vec::new();
+    for (name, cfg) in settings {
+        let into = cfg.get("into").ok_or(StringError::new(
+            format!("Capture '{name}': the key 'into' is required").as_str(),
+        ))?;
+        let interval = match cfg.get("interval") {
+            Some(s) => Duration::from_secs(
+                s.parse::<u64>()
+                    .map_err(|e| StringError::new(format!("Capture '{name}': wrong interval '{s}': {e}").as_str()))?,
+            ),
+            None => DEFAULT_INTERVAL,
+        };
+
+        result.push(Capture {
+            bridge: Bridge::new(Config::new(app_name, name), new_source(name, cfg)?),
+            into: into.to_string(),
+            interval,
+            next_run: Instant::now(),
+        });
+    }
+    result.sort_by_key(|c| c.bridge.name());
+    Ok(result)
+}
+
+fn message_to_patch(m: &Message) -> TaskPatch {
+    let text = m.text.trim();
+    let (name, rest) = text.split_once('\n').unwrap_or((text, ""));
+    let name = if name.trim().is_empty() {
+        m.permalink.as_str()
+    } else {
+        name.trim()
+    };
+
+    let rest = rest.trim();
+    let description = if rest.is_empty() {
+        ValuePatch::NotSet
+    } else {
+        ValuePatch::Value(rest.to_string())
+    };
+
+    TaskPatch {
+        task: None,
+        name: ValuePatch::Value(name.to_string()),
+        description,
+        due: ValuePatch::NotSet,
+        scheduled: ValuePatch::NotSet,
+        priority: ValuePatch::NotSet,
+        state: ValuePatch::NotSet,
+        labels: ValuePatch::NotSet,
+        parent: ValuePatch::NotSet,
+        url: ValuePatch::Value(m.permalink.clone()),
+    }
+}
+
+// Returns the numbers of the captured messages and of the ones that haven't been captured
+async fn capture(providers: &[Provider], c: &mut Capture) -> Result<(usize, usize), Box<dyn std::error::Error>> {
+    let messages = c.bridge.new_messages().await?;
+    if messages.is_empty() {
+        return Ok((0, 0));
+    }
+
+    let (provider, project) = find_destination(providers, &c.into).await?;
+    let mut p = provider.provider.write().await;
+    let mut captured = 0;
+    let total = messages.len();
+    for m in messages {
+        let tp = if provider.capabilities.url {
+            message_to_patch(&m)
+        } else {
+            message_to_patch(&m).with_url_in_description()
+        };
+        match p.create(project.id().as_str(), &tp).await {
+            Ok(()) => {
+                c.bridge.mark_captured(&m)?;
+                captured += 1;
+            }
+            Err(e) => {
+                tracing::error!(name=c.bridge.name(), permalink=m.permalink, error=?e, "Capture the message");
+                eprintln!("Capture message '{}' error: {e}", m.permalink);
+            }
+        }
+    }
+    Ok((captured, total - captured))
+}
+
+pub async fn run(
+    app_name: &str,
+    providers: &[Provider],
+    settings: &HashMap<String, HashMap<String, String>>,
+    once: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut captures = load(app_name, settings).map_err(cli::config_error)?;
+    if captures.is_empty() {
+        return Err(cli::config_error(StringError::new(
+            "There is no capture configured in the settings",
+        )));
+    }
+
+    loop {
+        let mut failed = false;
+        for c in captures.iter_mut().filter(|c| c.next_run <= Instant::now()) {
+            match capture(providers, c).await {
+                Ok((captured, not_captured)) => {
+                    if captured > 0 {
+                        crate::status!(
+                            "{}: {captured} messages have been captured into {}",
+                            c.bridge.name().purple(),
+                            c.into
+                        );
+                    }
+                    failed |= not_captured > 0;
+                }
+                Err(e) => {
+                    tracing::error!(name=c.bridge.name(), error=?e, "Capture messages");
+                    eprintln!("{}: capture error: {e}", c.bridge.name().purple());
+                    failed = true;
+                }
+            }
+            c.next_run = Instant::now() + c.interval;
+        }
+
+        if once {
+            if failed {
+                return Err(CliError::provider("Some messages haven't been captured").into());
+            }
+            return Ok(());
+        }
+
+        if let Some(next_run) = captures.iter().map(|c| c.next_run).min() {
+            tokio::time::sleep_until(next_run.into()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn message_to_patch_test() {
+        struct Case<'a> {
+            name: &'a str,
+            text: &'a str,
+            expected_name: &'a str,
+            expected_description: Option<&'a str>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "single line",
+                text: "Fix the build",
+                expected_name: "Fix the build",
+                expected_description: None,
+            },
+            Case {
+                name: "multiline",
+                text: " Fix the build \nIt fails on CI\nsince yesterday\n",
+                expected_name: "Fix the build",
+                expected_description: Some("It fails on CI\nsince yesterday"),
+            },
+            Case {
+                name: "empty text",
+                text: "",
+                expected_name: "https://chat/1",
+                expected_description: None,
+            },
+        ];
+
+        for c in CASES {
+            let tp = message_to_patch(&Message {
+                id: "1".to_string(),
+                text: c.text.to_string(),
+                permalink: "https://chat/1".to_string(),
+            });
+            assert_eq!(
+                tp.name.value().unwrap(),
+                c.expected_name,
+                "Test '{}' was failed",
+                c.name
+            );
+            assert_eq!(
+                tp.description.ref_value().map(String::as_str),
+                c.expected_description,
+                "Test '{}' was failed",
+                c.name
+            );
+            assert_eq!(
+                tp.url.ref_value().map(String::as_str),
+                Some("https://chat/1"),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn load_test() {
+        let settings = HashMap::from([(
+            "team".to_string(),
+            HashMap::from([
+                ("type".to_string(), "Slack".to_string()),
+                ("token".to_string(), "xoxb-token".to_string()),
+                ("into".to_string(), "tatuin/Inbox".to_string()),
+            ]),
+        )]);
+        let err = load("test_app", &settings).err().unwrap();
+        assert_eq!(err.to_string(), "Capture 'team': the key 'channel' is required");
+    }
+}
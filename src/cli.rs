@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT
+
+// The subcommands are run by the scripts and the cron jobs, so their exit codes are stable
+// and the quiet mode leaves only the requested output and the errors.
+
+use std::{
+    error::Error,
+    fmt,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use tatuin_core::provider::ProviderError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExitCode {
+    Ok = 0,
+    // any other failure, e.g. the wrong arguments
+    Failure = 1,
+    ProviderError = 2,
+    // no task, provider or project matches the arguments
+    NoMatch = 3,
+    ConfigError = 4,
+}
+
+impl From<ExitCode> for std::process::ExitCode {
+    fn from(c: ExitCode) -> Self {
+        Self::from(c as u8)
+    }
+}
+
+// The error that knows its exit code
+#[derive(Debug)]
+pub struct CliError {
+    code: ExitCode,
+    message: String,
+}
+
+impl CliError {
+    pub fn new(code: ExitCode, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+
+    pub fn no_match(message: &str) -> Self {
+        Self::new(ExitCode::NoMatch, message)
+    }
+
+    pub fn provider(message: &str) -> Self {
+        Self::new(ExitCode::ProviderError, message)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for CliError {}
+
+// Marks the error of loading the settings or the providers, the already classified errors are kept
+pub fn config_error<E: Into<Box<dyn Error>>>(e: E) -> Box<dyn Error> {
+    let e = e.into();
+    if e.is::<CliError>() {
+        return e;
+    }
+    CliError::new(ExitCode::ConfigError, &e.to_string()).into()
+}
+
+pub fn exit_code(e: &(dyn Error + 'static)) -> ExitCode {
+    if let Some(e) = e.downcast_ref::<CliError>() {
+        return e.code;
+    }
+    if e.is::<ProviderError>() {
+        return ExitCode::ProviderError;
+    }
+    ExitCode::Failure
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+// Prints the progress and the result messages unless the quiet mode is on
+#[macro_export]
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::cli::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::StringError;
+
+    #[test]
+    fn exit_code_test() {
+        struct Case<'a> {
+            name: &'a str,
+            error: Box<dyn Error>,
+            expected: ExitCode,
+        }
+        let cases = [
+            Case {
+                name: "no match",
+                error: CliError::no_match("There is no task").into(),
+                expected: ExitCode::NoMatch,
+            },
+            Case {
+                name: "provider error",
+                error: ProviderError::Network("timeout".to_string()).into(),
+                expected: ExitCode::ProviderError,
+            },
+            Case {
+                name: "config error",
+                error: config_error(StringError::new("wrong settings")),
+                expected: ExitCode::ConfigError,
+            },
+            Case {
+                name: "classified error stays as it is",
+                error: config_error(CliError::provider("no connection")),
+                expected: ExitCode::ProviderError,
+            },
+            Case {
+                name: "other error",
+                error: StringError::new("something").into(),
+                expected: ExitCode::Failure,
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(exit_code(c.error.as_ref()), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+}
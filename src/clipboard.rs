@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: MIT
+
+use std::process::{Command, Stdio};
+
+// The clipboard is read by the tools of the system, the first one that works is used
+const READERS: &[(&str, &[&str])] = &[
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+    ("pbpaste", &[]),
+];
+
+pub fn read() -> Option<String> {
+    READERS.iter().find_map(|(program, args)| {
+        let output = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok()
+    })
+}
@@ -0,0 +1,393 @@
+// SPDX-License-Identifier: MIT
+
+// The background mode synchronizes the providers and sends the due notifications periodically.
+// It's run by the user service of systemd or launchd, the service is managed by the daemon subcommands.
+// While it's running, the TUI reads the tasks it keeps in the cache folder, so the reminders don't depend on the TUI.
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use tatuin_core::{StringError, folders};
+
+use crate::{
+    cli::{CliError, ExitCode},
+    provider::Provider,
+    settings::Settings,
+    sync,
+};
+
+const SYSTEMD_UNIT_NAME: &str = "tatuin.service";
+const LAUNCHD_LABEL: &str = "com.github.panter-dsd.tatuin";
+// The file is touched on every cycle and keeps the interval in seconds
+const HEARTBEAT_FILE_NAME: &str = "daemon.heartbeat";
+// The time the cycle can take in addition to the interval
+const HEARTBEAT_MARGIN: Duration = Duration::from_secs(60);
+
+fn heartbeat_path(app_name: &str) -> PathBuf {
+    folders::cache_folder(app_name).join(HEARTBEAT_FILE_NAME)
+}
+
+fn beat(path: &Path, interval: Duration) {
+    if let Err(e) = std::fs::write(path, interval.as_secs().to_string()) {
+        tracing::warn!(path=?path, error=?e, "Write the heartbeat");
+    }
+}
+
+// The daemon is considered stopped if it has missed two cycles
+fn is_alive(path: &Path) -> bool {
+    let Some(interval) = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+    else {
+        return false;
+    };
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.elapsed().ok())
+        .is_some_and(|elapsed| elapsed <= Duration::from_secs(interval) * 2 + HEARTBEAT_MARGIN)
+}
+
+// Whether the daemon keeps the providers synchronized, so the TUI can read the stored tasks
+pub fn is_running(app_name: &str) -> bool {
+    is_alive(&heartbeat_path(app_name))
+}
+
+pub async fn run(
+    app_name: &str,
+    providers: &[Provider],
+    cfg: &Settings,
+    interval: Duration,
+) -> Result<(), Box<dyn Error>> {
+    #[cfg(not(feature = "notify"))]
+    let _ = cfg;
+
+    let heartbeat = heartbeat_path(app_name);
+    loop {
+        beat(&heartbeat, interval);
+
+        // the tasks are kept in memory between the cycles, the providers drop them to load the fresh ones
+        for p in providers {
+            p.provider.write().await.reload().await;
+        }
+
+        if let Err(e) = sync::sync(providers, None, false).await {
+            tracing::error!(error=?e, "Synchronize the providers");
+        }
+
+        // the notifications are sent once in a cycle, so the interval of every notification is ignored
+        #[cfg(feature = "notify")]
+        if !cfg.notify.is_empty()
+            && let Err(e) = crate::notify::run(app_name, providers, &cfg.notify, &cfg.contexts, None, true).await
+        {
+            tracing::error!(error=?e, "Send the notifications");
+            eprintln!("Notification error: {e}");
+        }
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+// The command line of the background mode, the binary goes first
+fn command_line(exe: &Path, settings_file: Option<&Path>, interval: Duration) -> Vec<String> {
+    let mut args = vec![exe.to_string_lossy().to_string()];
+    if let Some(f) = settings_file {
+        args.push("--settings-file".to_string());
+        args.push(f.to_string_lossy().to_string());
+    }
+    args.extend([
+        "--quiet".to_string(),
+        "daemon".to_string(),
+        "run".to_string(),
+        "--interval".to_string(),
+        interval.as_secs().to_string(),
+    ]);
+    args
+}
+
+fn systemd_unit(args: &[String]) -> String {
+    // the specifiers start with % and the quoted words can contain the escaped quotes
+    let exec_start = args
+        .iter()
+        .map(|a| {
+            format!(
+                "\"{}\"",
+                a.replace('\\', "\\\\").replace('"', "\\\"").replace('%', "%%")
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    format!(
+        "[Unit]
+Description=Tatuin background sync and notifications
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=simple
+ExecStart={exec_start}
+Restart=on-failure
+RestartSec=30
+
+[Install]
+WantedBy=default.target
+"
+    )
+}
+
+fn launchd_plist(args: &[String]) -> String {
+    let xml_escape = |s: &str| s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    let args = args
+        .iter()
+        .map(|a| format!("        <string>{}</string>\n", xml_escape(a)))
+        .collect::<String>();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+{args}    </array>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+</dict>
+</plist>
+"#
+    )
+}
+
+enum ServiceManager {
+    Systemd,
+    Launchd,
+}
+
+impl ServiceManager {
+    fn current() -> Result<Self, CliError> {
+        if cfg!(target_os = "macos") {
+            Ok(Self::Launchd)
+        } else if cfg!(target_os = "linux") {
+            Ok(Self::Systemd)
+        } else {
+            Err(CliError::new(
+                ExitCode::Failure,
+                "The service can be installed only for systemd or launchd",
+            ))
+        }
+    }
+
+    fn unit_path(&self) -> Result<PathBuf, StringError> {
+        match self {
+            Self::Systemd => dirs::config_dir()
+                .map(|d| d.join("systemd").join("user").join(SYSTEMD_UNIT_NAME))
+                .ok_or_else(|| StringError::new("Can't detect the config dir")),
+            Self::Launchd => dirs::home_dir()
+                .map(|d| {
+                    d.join("Library")
+                        .join("LaunchAgents")
+                        .join(format!("{LAUNCHD_LABEL}.plist"))
+                })
+                .ok_or_else(|| StringError::new("Can't detect the home dir")),
+        }
+    }
+
+    fn unit(&self, args: &[String]) -> String {
+        match self {
+            Self::Systemd => systemd_unit(args),
+            Self::Launchd => launchd_plist(args),
+        }
+    }
+
+    fn start(&self, unit_path: &Path) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Systemd => {
+                run_command("systemctl", &["--user", "daemon-reload"])?;
+                run_command("systemctl", &["--user", "enable", "--now", SYSTEMD_UNIT_NAME])?;
+                // the running service keeps the old command line until it's restarted
+                run_command("systemctl", &["--user", "restart", SYSTEMD_UNIT_NAME])
+            }
+            Self::Launchd => {
+                let path = unit_path.to_string_lossy();
+                // the already loaded service isn't reloaded by the load command
+                let _ = run_command("launchctl", &["unload", &path]);
+                run_command("launchctl", &["load", "-w", &path])
+            }
+        }
+    }
+
+    fn stop(&self, unit_path: &Path) -> Result<(), Box<dyn Error>> {
+        match self {
+            Self::Systemd => run_command("systemctl", &["--user", "disable", "--now", SYSTEMD_UNIT_NAME]),
+            Self::Launchd => run_command("launchctl", &["unload", "-w", &unit_path.to_string_lossy()]),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        match self {
+            Self::Systemd => run_command("systemctl", &["--user", "is-active", "--quiet", SYSTEMD_UNIT_NAME]).is_ok(),
+            Self::Launchd => run_command("launchctl", &["list", LAUNCHD_LABEL]).is_ok(),
+        }
+    }
+}
+
+fn run_command(program: &str, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    let output = Command::new(program).args(args).output()?;
+    if !output.status.success() {
+        return Err(StringError::new(
+            format!(
+                "`{program} {}` failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+            .as_str(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+pub fn install(settings_file: Option<&Path>, interval: Duration) -> Result<(), Box<dyn Error>> {
+    let manager = ServiceManager::current()?;
+    let unit_path = manager.unit_path()?;
+    let settings_file = settings_file.map(std::path::absolute).transpose()?;
+    let args = command_line(&std::env::current_exe()?, settings_file.as_deref(), interval);
+
+    if let Some(dir) = unit_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&unit_path, manager.unit(&args))?;
+    crate::status!("The service has been written to {}", unit_path.display());
+
+    manager
+        .start(&unit_path)
+        .map_err(|e| CliError::new(ExitCode::Failure, format!("The service isn't started: {e}").as_str()))?;
+    crate::status!("The service has been started");
+    Ok(())
+}
+
+pub fn status() -> Result<(), Box<dyn Error>> {
+    let manager = ServiceManager::current()?;
+    let unit_path = manager.unit_path()?;
+    if !unit_path.exists() {
+        return Err(CliError::no_match("The service isn't installed").into());
+    }
+
+    println!("Service: {}", unit_path.display());
+    println!("Running: {}", if manager.is_running() { "yes" } else { "no" });
+    Ok(())
+}
+
+pub fn uninstall() -> Result<(), Box<dyn Error>> {
+    let manager = ServiceManager::current()?;
+    let unit_path = manager.unit_path()?;
+    if !unit_path.exists() {
+        return Err(CliError::no_match("The service isn't installed").into());
+    }
+
+    if let Err(e) = manager.stop(&unit_path) {
+        tracing::warn!(error=?e, "Stop the service");
+        eprintln!("The service isn't stopped: {e}");
+    }
+    std::fs::remove_file(&unit_path)?;
+    if let ServiceManager::Systemd = manager {
+        let _ = run_command("systemctl", &["--user", "daemon-reload"]);
+    }
+    crate::status!("The service has been removed");
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn command_line_test() {
+        struct Case<'a> {
+            name: &'a str,
+            settings_file: Option<&'a Path>,
+            expected: &'a [&'a str],
+        }
+        let cases = [
+            Case {
+                name: "default settings",
+                settings_file: None,
+                expected: &["/usr/bin/tatuin", "--quiet", "daemon", "run", "--interval", "300"],
+            },
+            Case {
+                name: "settings file",
+                settings_file: Some(Path::new("/home/user/tatuin.toml")),
+                expected: &[
+                    "/usr/bin/tatuin",
+                    "--settings-file",
+                    "/home/user/tatuin.toml",
+                    "--quiet",
+                    "daemon",
+                    "run",
+                    "--interval",
+                    "300",
+                ],
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                command_line(Path::new("/usr/bin/tatuin"), c.settings_file, Duration::from_secs(300)),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn heartbeat_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HEARTBEAT_FILE_NAME);
+        assert!(!is_alive(&path), "the daemon has never been run");
+
+        beat(&path, Duration::from_secs(300));
+        assert!(is_alive(&path));
+
+        // the daemon has been stopped long ago
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+        assert!(!is_alive(&path));
+
+        std::fs::write(&path, "").unwrap();
+        assert!(!is_alive(&path), "the broken heartbeat");
+    }
+
+    #[test]
+    fn systemd_unit_test() {
+        let unit = systemd_unit(&["/opt/my tatuin".to_string(), "100%\"done\"".to_string()]);
+        assert!(
+            unit.contains("\nExecStart=\"/opt/my tatuin\" \"100%%\\\"done\\\"\"\n"),
+            "{unit}"
+        );
+    }
+
+    #[test]
+    fn launchd_plist_test() {
+        let plist = launchd_plist(&["/Applications/Tasks & Co/tatuin".to_string(), "run".to_string()]);
+        assert!(
+            plist.contains(
+                "    <array>
+        <string>/Applications/Tasks &amp; Co/tatuin</string>
+        <string>run</string>
+    </array>"
+            ),
+            "{plist}"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT
+
+// The dependencies between the tasks are kept locally, so a task can be blocked by the task of another provider.
+// The blocked task isn't completed until the tasks blocking it are completed or it's confirmed.
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tatuin_core::StringError;
+
+use crate::starred::TaskRef;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Dependency {
+    #[serde(flatten)]
+    task: TaskRef,
+    blocked_by: Vec<TaskRef>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct DependenciesFile {
+    #[serde(default)]
+    tasks: Vec<Dependency>,
+}
+
+#[derive(Default)]
+pub struct Dependencies {
+    // None keeps the dependencies only in memory
+    file_name: Option<PathBuf>,
+    tasks: Vec<Dependency>,
+}
+
+impl Dependencies {
+    pub fn load(file_name: &Path) -> Result<Self, Box<dyn Error>> {
+        let tasks = match std::fs::read_to_string(file_name) {
+            Ok(s) => toml::from_str::<DependenciesFile>(&s)?.tasks,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            file_name: Some(file_name.to_path_buf()),
+            tasks,
+        })
+    }
+
+    // The tasks that block the task
+    pub fn blockers(&self, provider: &str, id: &str) -> &[TaskRef] {
+        let r = TaskRef::new(provider, id);
+        self.tasks
+            .iter()
+            .find(|d| d.task == r)
+            .map(|d| d.blocked_by.as_slice())
+            .unwrap_or_default()
+    }
+
+    // Whether the task is blocked by the other one directly or through the tasks blocking it
+    fn depends_on(&self, task: &TaskRef, other: &TaskRef) -> bool {
+        let mut visited = Vec::new();
+        let mut queue = vec![task.clone()];
+        while let Some(r) = queue.pop() {
+            if visited.contains(&r) {
+                continue;
+            }
+            for b in self.blockers(r.provider(), r.id()) {
+                if b == other {
+                    return true;
+                }
+                queue.push(b.clone());
+            }
+            visited.push(r);
+        }
+        false
+    }
+
+    // Returns whether the task is blocked by the blocker now, the links that make a cycle are refused
+    pub fn toggle(&mut self, task: &TaskRef, blocker: &TaskRef) -> Result<bool, Box<dyn Error>> {
+        let blocked = match self.tasks.iter().position(|d| d.task == *task) {
+            Some(i) if self.tasks[i].blocked_by.contains(blocker) => {
+                self.tasks[i].blocked_by.retain(|b| b != blocker);
+                if self.tasks[i].blocked_by.is_empty() {
+                    self.tasks.remove(i);
+                }
+                false
+            }
+            _ if task == blocker || self.depends_on(blocker, task) => {
+                return Err(StringError::new("The tasks can't block each other").into());
+            }
+            Some(i) => {
+                self.tasks[i].blocked_by.push(blocker.clone());
+                true
+            }
+            None => {
+                self.tasks.push(Dependency {
+                    task: task.clone(),
+                    blocked_by: vec![blocker.clone()],
+                });
+                true
+            }
+        };
+        self.save()?;
+        Ok(blocked)
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(f) = &self.file_name {
+            let s = toml::to_string(&DependenciesFile {
+                tasks: self.tasks.clone(),
+            })?;
+            std::fs::write(f, s)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn toggle_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_name = dir.path().join("dependencies.toml");
+        let (a, b, c) = (
+            TaskRef::new("Todoist", "1"),
+            TaskRef::new("GitHub", "1"),
+            TaskRef::new("Todoist", "2"),
+        );
+
+        let mut d = Dependencies::load(&file_name).unwrap();
+        assert!(d.toggle(&a, &b).unwrap());
+        assert!(d.toggle(&b, &c).unwrap());
+        assert!(d.toggle(&a, &a).is_err(), "the task can't block itself");
+        assert!(d.toggle(&c, &a).is_err(), "the cycle through the blocker");
+
+        let mut d = Dependencies::load(&file_name).unwrap();
+        assert_eq!(d.blockers("Todoist", "1"), std::slice::from_ref(&b));
+        assert_eq!(d.blockers("GitHub", "1"), std::slice::from_ref(&c));
+        assert!(d.blockers("Todoist", "2").is_empty());
+
+        assert!(!d.toggle(&a, &b).unwrap());
+        let d = Dependencies::load(&file_name).unwrap();
+        assert!(d.blockers("Todoist", "1").is_empty());
+        assert_eq!(d.blockers("GitHub", "1"), [c]);
+    }
+}
@@ -0,0 +1,311 @@
+// SPDX-License-Identifier: MIT
+
+// Completing and opening the task from the command line, the uncompleted task is found by the fuzzy match of its name
+
+use std::{
+    error::Error,
+    io::{self, IsTerminal, Write},
+    sync::Arc,
+};
+
+use color_eyre::owo_colors::OwoColorize;
+use tatuin_core::{
+    filter::{Due, Filter, FilterState},
+    provider::TaskProviderTrait,
+    task::{State, Task as TaskTrait},
+    task_patch::{TaskPatch, ValuePatch},
+};
+
+use crate::{
+    cli::{self, CliError},
+    provider::Provider,
+    ui::fuzzy,
+};
+
+// The number of the matched tasks to choose from
+const MAX_CHOICES: usize = 9;
+
+// The indexes of the names matched by the pattern, the best matches first.
+// The name equal to the pattern is the only match, so the exact name never needs the choice.
+fn matches(pattern: &str, names: &[String]) -> Vec<usize> {
+    let pattern = pattern.trim();
+    let exact = names
+        .iter()
+        .enumerate()
+        .filter(|(_, n)| n.trim().to_lowercase() == pattern.to_lowercase())
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+    if exact.len() == 1 {
+        return exact;
+    }
+
+    fuzzy::filter(pattern, names.iter().map(|n| n.as_str()))
+}
+
+// The action is shown in the question, e.g. "complete"
+fn choose(
+    tasks: &[(&Provider, Box<dyn TaskTrait>)],
+    matched: &[usize],
+    action: &str,
+) -> Result<Option<usize>, Box<dyn Error>> {
+    println!("Several tasks match:");
+    for (n, i) in matched.iter().take(MAX_CHOICES).enumerate() {
+        let (p, t) = &tasks[*i];
+        println!("{}. {} ({})", n + 1, t.name().plain(), p.name.purple());
+    }
+    let count = matched.len().min(MAX_CHOICES);
+
+    loop {
+        print!("Which one to {action} (1..{count} or q for quit)> ");
+        io::stdout().flush()?;
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+        let input = input.trim();
+        if input == "q" {
+            return Ok(None);
+        }
+        if let Ok(n) = input.parse::<usize>()
+            && (1..=count).contains(&n)
+        {
+            return Ok(Some(matched[n - 1]));
+        }
+        println!("Wrong choice. Write one more time");
+    }
+}
+
+// Whether the user can choose one of the matched tasks, the scripts can't answer the question
+pub fn can_choose() -> bool {
+    !cli::is_quiet() && io::stdin().is_terminal()
+}
+
+// The index of the task to complete, None if the user has to choose it
+fn pick(pattern: &str, matched: &[usize], first: bool, can_choose: bool) -> Result<Option<usize>, CliError> {
+    match matched {
+        [] => Err(CliError::no_match(
+            format!("There is no uncompleted task matching '{pattern}'").as_str(),
+        )),
+        [i] => Ok(Some(*i)),
+        [i, ..] if first => Ok(Some(*i)),
+        _ if !can_choose => Err(CliError::no_match(
+            format!("Several tasks match '{pattern}', make the pattern more exact or use --first").as_str(),
+        )),
+        _ => Ok(None),
+    }
+}
+
+// The uncompleted tasks that can be completed
+async fn uncompleted<'a>(
+    providers: &'a [Provider],
+    provider_name: Option<&str>,
+) -> Result<Vec<(&'a Provider, Box<dyn TaskTrait>)>, Box<dyn Error>> {
+    let f = Filter {
+        states: vec![FilterState::Todo, FilterState::InProgress],
+        due: Due::values(),
+    };
+
+    let mut tasks = Vec::new();
+    for p in providers {
+        if provider_name.is_some_and(|n| n != p.name) {
+            continue;
+        }
+        let mut provider = p.provider.write().await;
+        for t in TaskProviderTrait::list(provider.as_mut(), None, &f).await? {
+            if t.const_patch_policy().available_states.contains(&State::Completed) {
+                tasks.push((p, t));
+            }
+        }
+    }
+    Ok(tasks)
+}
+
+// Finds the task, the user chooses one of the matched tasks if needed.
+// None is returned if the user hasn't chosen any of them.
+pub async fn find<'a>(
+    providers: &'a [Provider],
+    pattern: &str,
+    provider_name: Option<&str>,
+    first: bool,
+    action: &str,
+) -> Result<Option<(&'a Provider, Box<dyn TaskTrait>)>, Box<dyn Error>> {
+    let mut tasks = uncompleted(providers, provider_name).await?;
+    let names = tasks.iter().map(|(_, t)| t.name().plain()).collect::<Vec<String>>();
+    let matched = matches(pattern, &names);
+    let idx = match pick(pattern, &matched, first, can_choose())? {
+        Some(i) => i,
+        None => match choose(&tasks, &matched, action)? {
+            Some(i) => i,
+            None => return Ok(None),
+        },
+    };
+    Ok(Some(tasks.swap_remove(idx)))
+}
+
+// Finds the task without asking.
+// None is returned if several tasks match and the user has to choose one of them.
+pub async fn find_unattended<'a>(
+    providers: &'a [Provider],
+    pattern: &str,
+    provider_name: Option<&str>,
+    first: bool,
+    can_choose: bool,
+) -> Result<Option<(&'a Provider, Box<dyn TaskTrait>)>, Box<dyn Error>> {
+    let mut tasks = uncompleted(providers, provider_name).await?;
+    let names = tasks.iter().map(|(_, t)| t.name().plain()).collect::<Vec<String>>();
+    Ok(pick(pattern, &matches(pattern, &names), first, can_choose)?.map(|i| tasks.swap_remove(i)))
+}
+
+pub async fn done(
+    providers: &[Provider],
+    pattern: &str,
+    provider_name: Option<&str>,
+    first: bool,
+) -> Result<(), Box<dyn Error>> {
+    if let Some((p, t)) = find(providers, pattern, provider_name, first, "complete").await? {
+        crate::status!("{}", complete(p, t.as_ref()).await?);
+    }
+    Ok(())
+}
+
+// Completes the task without asking, returns the provider of the task and the result message.
+// None is returned if several tasks match and the user has to choose one of them.
+pub async fn done_unattended<'a>(
+    providers: &'a [Provider],
+    pattern: &str,
+    provider_name: Option<&str>,
+    first: bool,
+    can_choose: bool,
+) -> Result<Option<(&'a Provider, String)>, Box<dyn Error>> {
+    let Some((p, t)) = find_unattended(providers, pattern, provider_name, first, can_choose).await? else {
+        return Ok(None);
+    };
+    Ok(Some((p, complete(p, t.as_ref()).await?)))
+}
+
+// Returns the result message
+async fn complete(p: &Provider, t: &dyn TaskTrait) -> Result<String, Box<dyn Error>> {
+    let task: Arc<dyn TaskTrait> = t.clone_boxed().into();
+    let tp = TaskPatch {
+        task: Some(task),
+        name: ValuePatch::NotSet,
+        description: ValuePatch::NotSet,
+        due: ValuePatch::NotSet,
+        scheduled: ValuePatch::NotSet,
+        priority: ValuePatch::NotSet,
+        state: ValuePatch::Value(State::Completed),
+        labels: ValuePatch::NotSet,
+        parent: ValuePatch::NotSet,
+        url: ValuePatch::NotSet,
+    };
+    if let Some(e) = p.provider.write().await.update(&[tp]).await.first() {
+        return Err(CliError::provider(format!("The task isn't completed: {}", e.error).as_str()).into());
+    }
+
+    Ok(format!(
+        "The task '{}' has been completed in {}",
+        t.name().plain(),
+        p.name.purple()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_test() {
+        struct Case<'a> {
+            name: &'a str,
+            pattern: &'a str,
+            expected: Vec<usize>,
+        }
+        let names = ["Pay rent", "Pay the rent for the garage", "Buy milk", "Review PR"]
+            .map(String::from)
+            .to_vec();
+        let cases = [
+            Case {
+                name: "exact name",
+                pattern: "pay rent",
+                expected: vec![0],
+            },
+            Case {
+                name: "fuzzy match, the best first",
+                pattern: "pay r",
+                expected: vec![0, 1],
+            },
+            Case {
+                name: "single fuzzy match",
+                pattern: "milk",
+                expected: vec![2],
+            },
+            Case {
+                name: "no match",
+                pattern: "walk the dog",
+                expected: vec![],
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(matches(c.pattern, &names), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn pick_test() {
+        struct Case<'a> {
+            name: &'a str,
+            matched: &'a [usize],
+            first: bool,
+            can_choose: bool,
+            expected: Result<Option<usize>, ()>,
+        }
+        let cases = [
+            Case {
+                name: "no match",
+                matched: &[],
+                first: true,
+                can_choose: true,
+                expected: Err(()),
+            },
+            Case {
+                name: "single match",
+                matched: &[2],
+                first: false,
+                can_choose: false,
+                expected: Ok(Some(2)),
+            },
+            Case {
+                name: "the best match",
+                matched: &[1, 0],
+                first: true,
+                can_choose: false,
+                expected: Ok(Some(1)),
+            },
+            Case {
+                name: "the user chooses",
+                matched: &[1, 0],
+                first: false,
+                can_choose: true,
+                expected: Ok(None),
+            },
+            Case {
+                name: "the script can't choose",
+                matched: &[1, 0],
+                first: false,
+                can_choose: false,
+                expected: Err(()),
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                pick("pay", c.matched, c.first, c.can_choose).map_err(|_| ()),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -0,0 +1,451 @@
+// SPDX-License-Identifier: MIT
+
+mod csv;
+mod markdown;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use color_eyre::owo_colors::OwoColorize;
+use tatuin_core::{
+    StringError,
+    project::Project as ProjectTrait,
+    provider::ProjectProviderTrait,
+    task::{DateTimeUtc, Priority},
+    task_patch::{DatePatchItem, TaskPatch, ValuePatch},
+};
+
+use crate::{cli::CliError, provider::Provider};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    TodoistCsv,
+    TicktickCsv,
+    ThingsCsv,
+    Markdown,
+}
+
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct Mapping {
+    #[arg(long, help("column with the task name"))]
+    pub name_column: Option<String>,
+
+    #[arg(long, help("column with the task description"))]
+    pub description_column: Option<String>,
+
+    #[arg(long, help("column with the task priority"))]
+    pub priority_column: Option<String>,
+
+    #[arg(long, help("column with the task due date"))]
+    pub due_column: Option<String>,
+
+    #[arg(long, help("format of the due column, e.g. %d/%m/%Y"))]
+    pub due_format: Option<String>,
+
+    #[arg(
+        long,
+        value_delimiter(','),
+        help("mapping of the priority column values, e.g. 1=highest,2=high")
+    )]
+    pub priority_map: Vec<String>,
+}
+
+struct Columns {
+    name: &'static str,
+    description: Option<&'static str>,
+    priority: Option<&'static str>,
+    due: Option<&'static str>,
+    priorities: &'static [(&'static str, Priority)],
+}
+
+impl Format {
+    fn columns(&self) -> Columns {
+        match self {
+            Format::TodoistCsv => Columns {
+                name: "CONTENT",
+                description: Some("DESCRIPTION"),
+                priority: Some("PRIORITY"),
+                due: Some("DATE"),
+                priorities: &[
+                    ("1", Priority::Highest),
+                    ("2", Priority::High),
+                    ("3", Priority::Medium),
+                    ("4", Priority::Normal),
+                ],
+            },
+            Format::TicktickCsv => Columns {
+                name: "Title",
+                description: Some("Content"),
+                priority: Some("Priority"),
+                due: Some("Due Date"),
+                priorities: &[
+                    ("0", Priority::Normal),
+                    ("1", Priority::Low),
+                    ("3", Priority::Medium),
+                    ("5", Priority::High),
+                ],
+            },
+            Format::ThingsCsv => Columns {
+                name: "Title",
+                description: Some("Notes"),
+                priority: None,
+                due: Some("Due Date"),
+                priorities: &[],
+            },
+            Format::Markdown => Columns {
+                name: "",
+                description: None,
+                priority: None,
+                due: None,
+                priorities: &[],
+            },
+        }
+    }
+}
+
+fn parse_priority(s: &str) -> Option<Priority> {
+    Priority::values()
+        .into_iter()
+        .find(|p| p.to_string().eq_ignore_ascii_case(s.trim()))
+}
+
+fn parse_due(s: &str, custom_format: Option<&str>) -> Option<DateTimeUtc> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+
+    if let Some(f) = custom_format {
+        return NaiveDateTime::parse_from_str(s, f)
+            .ok()
+            .or_else(|| {
+                NaiveDate::parse_from_str(s, f)
+                    .ok()
+                    .and_then(|d| d.and_hms_opt(0, 0, 0))
+            })
+            .map(|dt| dt.and_utc());
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.to_utc());
+    }
+
+    if let Ok(dt) = DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%z") {
+        return Some(dt.to_utc());
+    }
+
+    for f in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(dt) = NaiveDateTime::parse_from_str(s, f) {
+            return Some(dt.and_utc());
+        }
+    }
+
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+fn new_patch(name: &str, description: Option<&str>, priority: Option<Priority>, due: Option<DateTimeUtc>) -> TaskPatch {
+    TaskPatch {
+        task: None,
+        name: ValuePatch::Value(name.to_string()),
+        description: match description {
+            Some(d) if !d.is_empty() => ValuePatch::Value(d.to_string()),
+            _ => ValuePatch::Empty,
+        },
+        due: due.map_or(ValuePatch::NotSet, |d| ValuePatch::Value(DatePatchItem::Custom(d))),
+        scheduled: ValuePatch::NotSet,
+        priority: priority.map_or(ValuePatch::NotSet, ValuePatch::Value),
+        state: ValuePatch::NotSet,
+        labels: ValuePatch::NotSet,
+        parent: ValuePatch::NotSet,
+        url: ValuePatch::NotSet,
+    }
+}
+
+fn priorities_map(format: Format, mapping: &Mapping) -> Result<HashMap<String, Priority>, StringError> {
+    let mut result = format
+        .columns()
+        .priorities
+        .iter()
+        .map(|(k, v)| (k.to_string(), *v))
+        .collect::<HashMap<String, Priority>>();
+
+    for item in &mapping.priority_map {
+        let (value, priority) = item
+            .split_once('=')
+            .ok_or(StringError::new(format!("Wrong priority mapping '{item}'").as_str()))?;
+        let priority =
+            parse_priority(priority).ok_or(StringError::new(format!("Unknown priority '{priority}'").as_str()))?;
+        result.insert(value.trim().to_string(), priority);
+    }
+
+    Ok(result)
+}
+
+fn parse_csv(format: Format, content: &str, mapping: &Mapping) -> Result<Vec<TaskPatch>, StringError> {
+    let columns = format.columns();
+    let priorities = priorities_map(format, mapping)?;
+    let name_column = mapping.name_column.as_deref().unwrap_or(columns.name);
+
+    let rows = csv::parse(content);
+
+    // Some exports (e.g. TickTick) have a preamble before the header
+    let header_idx = rows
+        .iter()
+        .position(|r| r.iter().any(|c| c == name_column))
+        .ok_or(StringError::new(
+            format!("Column '{name_column}' is not found in the file").as_str(),
+        ))?;
+    let header = &rows[header_idx];
+
+    // The columns passed explicitly must exist, the format defaults are optional
+    let column_idx = |custom: &Option<String>, default: Option<&str>| -> Result<Option<usize>, StringError> {
+        match custom {
+            Some(name) => header.iter().position(|c| c == name).map(Some).ok_or(StringError::new(
+                format!("Column '{name}' is not found in the file").as_str(),
+            )),
+            None => Ok(default.and_then(|name| header.iter().position(|c| c == name))),
+        }
+    };
+    let name_idx = column_idx(&None, Some(name_column))?.unwrap();
+    let description_idx = column_idx(&mapping.description_column, columns.description)?;
+    let priority_idx = column_idx(&mapping.priority_column, columns.priority)?;
+    let due_idx = column_idx(&mapping.due_column, columns.due)?;
+    let type_idx = if format == Format::TodoistCsv {
+        column_idx(&None, Some("TYPE"))?
+    } else {
+        None
+    };
+
+    let mut result = Vec::new();
+
+    for row in &rows[header_idx + 1..] {
+        let value = |idx: Option<usize>| idx.and_then(|i| row.get(i)).map(String::as_str);
+
+        // Todoist exports sections and notes in the same file
+        if value(type_idx).is_some_and(|t| t != "task") {
+            continue;
+        }
+
+        let name = value(Some(name_idx)).unwrap_or_default().trim();
+        if name.is_empty() {
+            continue;
+        }
+
+        let priority = value(priority_idx).and_then(|p| priorities.get(p.trim()).copied().or(parse_priority(p)));
+        let due = value(due_idx).and_then(|d| parse_due(d, mapping.due_format.as_deref()));
+        result.push(new_patch(name, value(description_idx), priority, due));
+    }
+
+    Ok(result)
+}
+
+pub fn parse(format: Format, content: &str, mapping: &Mapping) -> Result<Vec<TaskPatch>, StringError> {
+    match format {
+        Format::Markdown => Ok(markdown::parse(content)
+            .iter()
+            .map(|i| new_patch(&i.name, i.description.as_deref(), None, None))
+            .collect()),
+        _ => parse_csv(format, content, mapping),
+    }
+}
+
+fn print_patch(tp: &TaskPatch) {
+    let mut s = tp.name.value().unwrap_or_default();
+    if let Some(p) = tp.priority.value() {
+        s.push_str(format!(" priority: {p}").as_str());
+    }
+    if let Some(DatePatchItem::Custom(d)) = tp.due.value() {
+        s.push_str(format!(" due: {}", d.format("%Y-%m-%d")).as_str());
+    }
+    println!("{s}");
+}
+
+pub async fn find_destination<'a>(
+    providers: &'a [Provider],
+    into: &str,
+) -> Result<(&'a Provider, Box<dyn ProjectTrait>), Box<dyn std::error::Error>> {
+    let (provider_name, project_name) = into.split_once('/').ok_or(StringError::new(
+        "The destination should be in format <provider>/<project>",
+    ))?;
+
+    find_project(providers, provider_name, project_name).await
+}
+
+pub async fn find_project<'a>(
+    providers: &'a [Provider],
+    provider_name: &str,
+    project_name: &str,
+) -> Result<(&'a Provider, Box<dyn ProjectTrait>), Box<dyn std::error::Error>> {
+    let provider = providers
+        .iter()
+        .find(|p| p.name == provider_name)
+        .ok_or(CliError::no_match(
+            format!("Provider '{provider_name}' is not found").as_str(),
+        ))?;
+    if !provider.capabilities.create_task {
+        return Err(StringError::new(format!("Provider '{provider_name}' can't create tasks").as_str()).into());
+    }
+
+    let mut p = provider.provider.write().await;
+    let project = ProjectProviderTrait::list(p.as_mut())
+        .await?
+        .into_iter()
+        .find(|p| p.name() == project_name || p.id() == project_name)
+        .ok_or(CliError::no_match(
+            format!("Project '{project_name}' is not found").as_str(),
+        ))?;
+
+    Ok((provider, project))
+}
+
+pub async fn import(
+    providers: &[Provider],
+    into: &str,
+    patches: &[TaskPatch],
+    dry_run: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (provider, project) = find_destination(providers, into).await?;
+
+    if dry_run {
+        println!(
+            "{} tasks would be imported into {}/{}:",
+            patches.len(),
+            provider.name.purple(),
+            project.name()
+        );
+        patches.iter().for_each(print_patch);
+        return Ok(());
+    }
+
+    let mut p = provider.provider.write().await;
+    let mut imported = 0;
+    for tp in patches {
+        match p.create(project.id().as_str(), tp).await {
+            Ok(()) => imported += 1,
+            Err(e) => eprintln!("Import task '{}' error: {e}", tp.name.value().unwrap_or_default()),
+        }
+    }
+    crate::status!("{imported} of {} tasks have been imported", patches.len());
+
+    if imported < patches.len() {
+        return Err(CliError::provider(
+            format!(
+                "{} of {} tasks haven't been imported",
+                patches.len() - imported,
+                patches.len()
+            )
+            .as_str(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+// Creates the single task, the provider and project are taken from the settings if they aren't set.
+// Returns the result message.
+pub async fn add(
+    providers: &[Provider],
+    defaults: (Option<&str>, Option<&str>),
+    destination: (Option<&str>, Option<&str>),
+    name: &str,
+    description: Option<&str>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let provider_name = destination.0.or(defaults.0).ok_or(StringError::new(
+        "The provider is set neither in the arguments nor as default_provider in the settings",
+    ))?;
+    let project_name = destination.1.or(defaults.1).ok_or(StringError::new(
+        "The project is set neither in the arguments nor as default_project in the settings",
+    ))?;
+    let (provider, project) = find_project(providers, provider_name, project_name).await?;
+
+    let tp = new_patch(name, description, None, None);
+    provider
+        .provider
+        .write()
+        .await
+        .create(project.id().as_str(), &tp)
+        .await?;
+    Ok(format!(
+        "The task has been added into {}/{}",
+        provider.name.purple(),
+        project.name()
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_todoist_csv_test() {
+        let content = "TYPE,CONTENT,DESCRIPTION,PRIORITY,INDENT,AUTHOR,RESPONSIBLE,DATE,DATE_LANG,TIMEZONE\n\
+            section,Section,,,,,,,,\n\
+            task,First,Some description,1,1,,,2025-01-02,en,\n\
+            task,Second,,4,1,,,every day,en,\n\
+            note,A note,,,,,,,,\n";
+        let patches = parse(Format::TodoistCsv, content, &Mapping::default()).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].name.value(), Some("First".to_string()));
+        assert_eq!(patches[0].description.value(), Some("Some description".to_string()));
+        assert_eq!(patches[0].priority.value(), Some(Priority::Highest));
+        assert_eq!(
+            patches[0].due.value(),
+            Some(DatePatchItem::Custom(parse_due("2025-01-02", None).unwrap()))
+        );
+        assert_eq!(patches[1].name.value(), Some("Second".to_string()));
+        assert!(matches!(patches[1].description, ValuePatch::Empty));
+        assert_eq!(patches[1].priority.value(), Some(Priority::Normal));
+        assert!(!patches[1].due.is_set());
+    }
+
+    #[test]
+    fn parse_ticktick_csv_with_preamble_test() {
+        let content = "\"Date: 2025-01-01\"\n\"Version: 7.1\"\n\
+            \"Folder Name\",\"List Name\",\"Title\",\"Content\",\"Due Date\",\"Priority\"\n\
+            \"\",\"Inbox\",\"Buy milk\",\"\",\"2025-01-05T10:00:00+0000\",\"5\"\n";
+        let patches = parse(Format::TicktickCsv, content, &Mapping::default()).unwrap();
+        assert_eq!(patches.len(), 1);
+        assert_eq!(patches[0].name.value(), Some("Buy milk".to_string()));
+        assert_eq!(patches[0].priority.value(), Some(Priority::High));
+        assert_eq!(
+            patches[0].due.value(),
+            Some(DatePatchItem::Custom(parse_due("2025-01-05T10:00:00Z", None).unwrap()))
+        );
+    }
+
+    #[test]
+    fn parse_csv_with_mapping_test() {
+        let content = "Task,Importance,Deadline\nWrite report,urgent,31/01/2025\nRelax,meh,\n";
+        let mapping = Mapping {
+            name_column: Some("Task".to_string()),
+            priority_column: Some("Importance".to_string()),
+            due_column: Some("Deadline".to_string()),
+            due_format: Some("%d/%m/%Y".to_string()),
+            priority_map: vec!["urgent=highest".to_string(), "meh=lowest".to_string()],
+            ..Mapping::default()
+        };
+        let patches = parse(Format::ThingsCsv, content, &mapping).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].priority.value(), Some(Priority::Highest));
+        assert_eq!(
+            patches[0].due.value(),
+            Some(DatePatchItem::Custom(parse_due("2025-01-31", None).unwrap()))
+        );
+        assert_eq!(patches[1].priority.value(), Some(Priority::Lowest));
+        assert!(!patches[1].due.is_set());
+    }
+
+    #[test]
+    fn parse_csv_errors_test() {
+        assert!(parse(Format::ThingsCsv, "Name\nTask\n", &Mapping::default()).is_err());
+
+        let mapping = Mapping {
+            priority_map: vec!["1=unknown".to_string()],
+            ..Mapping::default()
+        };
+        assert!(parse(Format::TodoistCsv, "CONTENT\nTask\n", &mapping).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+
+pub fn parse(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+
+        match c {
+            '"' => in_quotes = true,
+            ',' => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+
+    rows.retain(|r| r.iter().any(|f| !f.is_empty()));
+    rows
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+
+    #[test]
+    fn parse_test() {
+        struct Case<'a> {
+            name: &'a str,
+            content: &'a str,
+            expected: Vec<Vec<&'a str>>,
+        }
+        let cases = [
+            Case {
+                name: "empty",
+                content: "",
+                expected: vec![],
+            },
+            Case {
+                name: "simple",
+                content: "a,b,c\n1,2,3",
+                expected: vec![vec!["a", "b", "c"], vec!["1", "2", "3"]],
+            },
+            Case {
+                name: "quoted with comma and quotes",
+                content: "\"a, b\",\"say \"\"hi\"\"\"\r\n",
+                expected: vec![vec!["a, b", "say \"hi\""]],
+            },
+            Case {
+                name: "multiline field",
+                content: "name,notes\ntask,\"line 1\nline 2\"\n",
+                expected: vec![vec!["name", "notes"], vec!["task", "line 1\nline 2"]],
+            },
+            Case {
+                name: "empty lines and bom are skipped",
+                content: "\u{feff}a,b\n\n,\n1,\n",
+                expected: vec![vec!["a", "b"], vec!["1", ""]],
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(parse(c.content), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+}
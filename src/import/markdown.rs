@@ -0,0 +1,95 @@
+// SPDX-License-Identifier: MIT
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+static CHECKBOX_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*[-*+]\s+\[(.)\]\s+(.+)$").unwrap());
+
+pub struct Item {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+// Parses uncompleted checklist items. The indented non-checkbox lines under an item become its description.
+pub fn parse(content: &str) -> Vec<Item> {
+    let mut items: Vec<Item> = Vec::new();
+    let mut current_is_open = false;
+
+    for line in content.lines() {
+        if let Some(caps) = CHECKBOX_RE.captures(line) {
+            current_is_open = &caps[1] == " ";
+            if current_is_open {
+                items.push(Item {
+                    name: caps[2].trim().to_string(),
+                    description: None,
+                });
+            }
+            continue;
+        }
+
+        let is_indented = line.starts_with(' ') || line.starts_with('\t');
+        if !is_indented || line.trim().is_empty() {
+            current_is_open = false;
+            continue;
+        }
+
+        if current_is_open && let Some(item) = items.last_mut() {
+            let description = item.description.get_or_insert_default();
+            if !description.is_empty() {
+                description.push('\n');
+            }
+            description.push_str(line.trim());
+        }
+    }
+
+    items
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse;
+
+    #[test]
+    fn parse_test() {
+        struct Case<'a> {
+            name: &'a str,
+            content: &'a str,
+            expected: Vec<(&'a str, Option<&'a str>)>,
+        }
+        let cases = [
+            Case {
+                name: "empty",
+                content: "",
+                expected: vec![],
+            },
+            Case {
+                name: "only open items",
+                content: "# Title\n- [ ] first\n* [x] done\n+ [ ] second\n- not a task",
+                expected: vec![("first", None), ("second", None)],
+            },
+            Case {
+                name: "description",
+                content: "- [ ] task\n  some note\n\tanother note\n\n  not a note\n- [x] done\n  skipped note",
+                expected: vec![("task", Some("some note\nanother note"))],
+            },
+            Case {
+                name: "nested items",
+                content: "- [ ] parent\n    - [ ] child",
+                expected: vec![("parent", None), ("child", None)],
+            },
+        ];
+
+        for c in cases {
+            let items = parse(c.content)
+                .into_iter()
+                .map(|i| (i.name, i.description))
+                .collect::<Vec<_>>();
+            let expected = c
+                .expected
+                .iter()
+                .map(|(n, d)| (n.to_string(), d.map(|d| d.to_string())))
+                .collect::<Vec<_>>();
+            assert_eq!(items, expected, "Test '{}' was failed", c.name);
+        }
+    }
+}
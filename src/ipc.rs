@@ -0,0 +1,330 @@
+// SPDX-License-Identifier: MIT
+
+// The running TUI listens on the unix socket, so the add and done subcommands change the tasks through it:
+// the changes appear in the open session at once and the providers aren't loaded once more.
+// The open subcommand selects the task in the session.
+// The subcommands call the providers directly if no session is running.
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use tatuin_core::folders;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    task::JoinHandle,
+};
+
+use crate::{
+    cli::{self, CliError, ExitCode},
+    done, import,
+    provider::Provider,
+};
+
+const SOCKET_FILE_NAME: &str = "session.sock";
+// The suspended session accepts the connection but doesn't reply
+const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn socket_path(app_name: &str) -> PathBuf {
+    folders::cache_folder(app_name).join(SOCKET_FILE_NAME)
+}
+
+// Every request and response is the JSON object on a single line
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Request {
+    // the placeholders are expanded and the default destination is applied by the client
+    Add {
+        name: String,
+        description: Option<String>,
+        provider: Option<String>,
+        project: Option<String>,
+    },
+    Done {
+        pattern: String,
+        provider: Option<String>,
+        first: bool,
+        // whether the client asks the user to choose one of the matched tasks
+        can_choose: bool,
+    },
+    Open {
+        pattern: String,
+        provider: Option<String>,
+        first: bool,
+        can_choose: bool,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum Response {
+    Ok { message: String },
+    Failed { code: ExitCode, message: String },
+    // the command needs the terminal of the client, e.g. to choose one of the matched tasks
+    Refused,
+}
+
+// What the session shows after the command
+#[derive(Debug, PartialEq)]
+pub enum Notice {
+    // the tasks of the provider have been changed
+    Changed { provider: String },
+    Open { provider: String, task_id: String },
+}
+
+type OnDone = Box<dyn Fn(Notice) + Send + Sync>;
+
+// Runs the commands while the session is open, the socket is removed when it's dropped
+pub struct Server {
+    path: PathBuf,
+    handle: JoinHandle<()>,
+}
+
+impl Server {
+    // Returns None if another session listens on the socket already.
+    // The result of every command is passed to on_done, so the session shows it.
+    pub async fn start(
+        path: &Path,
+        providers: Vec<Provider>,
+        on_done: impl Fn(Notice) + Send + Sync + 'static,
+    ) -> Result<Option<Self>, Box<dyn Error>> {
+        if UnixStream::connect(path).await.is_ok() {
+            return Ok(None);
+        }
+        // the socket is left by the session that hasn't been finished properly
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        let listener = UnixListener::bind(path)?;
+        let providers = Arc::new(providers);
+        let on_done: Arc<OnDone> = Arc::new(Box::new(on_done));
+        let handle = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        tracing::error!(error=?e, "Accept the command connection");
+                        break;
+                    }
+                };
+                let providers = providers.clone();
+                let on_done = on_done.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, &providers, on_done.as_ref()).await {
+                        tracing::warn!(error=?e, "Serve the command");
+                    }
+                });
+            }
+        });
+
+        Ok(Some(Self {
+            path: path.to_path_buf(),
+            handle,
+        }))
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.handle.abort();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!(path=?self.path, error=?e, "Remove the command socket");
+        }
+    }
+}
+
+async fn serve(stream: UnixStream, providers: &[Provider], on_done: &OnDone) -> Result<(), Box<dyn Error>> {
+    let (reader, mut writer) = stream.into_split();
+    let mut line = String::new();
+    BufReader::new(reader).read_line(&mut line).await?;
+
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(r) => {
+            tracing::info!(request=?r, "Run the command of the client");
+            match run(providers, r).await {
+                Ok(Some((notice, message))) => {
+                    on_done(notice);
+                    Response::Ok { message }
+                }
+                Ok(None) => Response::Refused,
+                Err(e) => Response::Failed {
+                    code: cli::exit_code(e.as_ref()),
+                    message: e.to_string(),
+                },
+            }
+        }
+        Err(e) => Response::Failed {
+            code: ExitCode::Failure,
+            message: format!("Wrong request: {e}"),
+        },
+    };
+
+    let mut data = serde_json::to_vec(&response)?;
+    data.push(b'\n');
+    writer.write_all(&data).await?;
+    Ok(())
+}
+
+// Returns what the session shows and the result message
+async fn run(providers: &[Provider], r: Request) -> Result<Option<(Notice, String)>, Box<dyn Error>> {
+    match r {
+        Request::Add {
+            name,
+            description,
+            provider,
+            project,
+        } => {
+            let message = import::add(
+                providers,
+                (None, None),
+                (provider.as_deref(), project.as_deref()),
+                &name,
+                description.as_deref(),
+            )
+            .await?;
+            Ok(provider.map(|provider| (Notice::Changed { provider }, message)))
+        }
+        Request::Done {
+            pattern,
+            provider,
+            first,
+            can_choose,
+        } => Ok(
+            done::done_unattended(providers, &pattern, provider.as_deref(), first, can_choose)
+                .await?
+                .map(|(p, message)| {
+                    let provider = p.name.clone();
+                    (Notice::Changed { provider }, message)
+                }),
+        ),
+        Request::Open {
+            pattern,
+            provider,
+            first,
+            can_choose,
+        } => Ok(
+            done::find_unattended(providers, &pattern, provider.as_deref(), first, can_choose)
+                .await?
+                .map(|(_, t)| {
+                    let message = format!("The task '{}' has been opened in the running session", t.name().plain());
+                    let notice = Notice::Open {
+                        provider: t.provider(),
+                        task_id: t.id(),
+                    };
+                    (notice, message)
+                }),
+        ),
+    }
+}
+
+// Sends the command to the running session and returns the result message.
+// None is returned if no session is running or it can't run the command, so the client runs it itself.
+pub async fn send(path: &Path, r: &Request) -> Result<Option<String>, Box<dyn Error>> {
+    let stream = match UnixStream::connect(path).await {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::debug!(path=?path, error=?e, "No session to send the command to");
+            return Ok(None);
+        }
+    };
+    let (reader, mut writer) = stream.into_split();
+    let mut data = serde_json::to_vec(r)?;
+    data.push(b'\n');
+    writer.write_all(&data).await?;
+
+    let mut line = String::new();
+    tokio::time::timeout(REPLY_TIMEOUT, BufReader::new(reader).read_line(&mut line))
+        .await
+        .map_err(|_| {
+            CliError::new(
+                ExitCode::Failure,
+                "The running session doesn't reply, it may be suspended",
+            )
+        })??;
+    match serde_json::from_str::<Response>(&line)? {
+        Response::Ok { message } => Ok(Some(message)),
+        Response::Failed { code, message } => Err(CliError::new(code, &message).into()),
+        Response::Refused => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn request_test() {
+        let r = Request::Done {
+            pattern: "pay rent".to_string(),
+            provider: None,
+            first: false,
+            can_choose: true,
+        };
+        let s = serde_json::to_string(&r).unwrap();
+        assert_eq!(
+            s,
+            r#"{"command":"done","pattern":"pay rent","provider":null,"first":false,"can_choose":true}"#
+        );
+        assert_eq!(serde_json::from_str::<Request>(&s).unwrap(), r);
+
+        let r = Request::Open {
+            pattern: "pay rent".to_string(),
+            provider: Some("Todoist".to_string()),
+            first: true,
+            can_choose: false,
+        };
+        let s = serde_json::to_string(&r).unwrap();
+        assert_eq!(
+            s,
+            r#"{"command":"open","pattern":"pay rent","provider":"Todoist","first":true,"can_choose":false}"#
+        );
+        assert_eq!(serde_json::from_str::<Request>(&s).unwrap(), r);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn send_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SOCKET_FILE_NAME);
+        let add = Request::Add {
+            name: "Pay rent".to_string(),
+            description: None,
+            provider: Some("Todoist".to_string()),
+            project: Some("Inbox".to_string()),
+        };
+        assert!(send(&path, &add).await.unwrap().is_none(), "no session is running");
+
+        // the socket of the session that has been killed
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+        assert!(send(&path, &add).await.unwrap().is_none());
+
+        let server = Server::start(&path, Vec::new(), |_| {}).await.unwrap().unwrap();
+        assert!(
+            Server::start(&path, Vec::new(), |_| {}).await.unwrap().is_none(),
+            "the session listens already"
+        );
+        let e = send(&path, &add).await.unwrap_err();
+        assert_eq!(cli::exit_code(e.as_ref()), ExitCode::NoMatch);
+        assert_eq!(e.to_string(), "Provider 'Todoist' is not found");
+
+        let open = Request::Open {
+            pattern: "pay rent".to_string(),
+            provider: None,
+            first: false,
+            can_choose: false,
+        };
+        let e = send(&path, &open).await.unwrap_err();
+        assert_eq!(cli::exit_code(e.as_ref()), ExitCode::NoMatch);
+
+        drop(server);
+        assert!(!path.exists());
+        assert!(send(&path, &add).await.unwrap().is_none());
+    }
+}
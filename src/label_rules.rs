@@ -0,0 +1,437 @@
+// SPDX-License-Identifier: MIT
+
+use std::{any::Any, sync::Arc};
+
+use async_trait::async_trait;
+use tatuin_core::{
+    RichString, StringError, filter,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, Recurrence, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, TaskPatch},
+};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Inference {
+    Priority(Priority),
+    Due(DatePatchItem),
+}
+
+fn parse_inference(s: &str) -> Option<Inference> {
+    let s = s.to_lowercase();
+    if let Some(p) = Priority::values()
+        .into_iter()
+        .find(|p| p.to_string().to_lowercase() == s)
+    {
+        return Some(Inference::Priority(p));
+    }
+    match s.as_str() {
+        "today" => Some(Inference::Due(DatePatchItem::Today)),
+        "tomorrow" => Some(Inference::Due(DatePatchItem::Tomorrow)),
+        "weekend" => Some(Inference::Due(DatePatchItem::ThisWeekend)),
+        "next week" => Some(Inference::Due(DatePatchItem::NextWeek)),
+        _ => None,
+    }
+}
+
+// The priority and the due date of the tasks by their labels, e.g. "P1 = High; urgent = Today".
+// They fill the priority and the due date that the provider hasn't set only.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LabelRules {
+    rules: Vec<(String, Inference)>,
+}
+
+impl LabelRules {
+    pub fn parse(s: &str) -> Result<Self, StringError> {
+        let rules = s
+            .split(';')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(|r| {
+                r.split_once('=')
+                    .and_then(|(label, value)| {
+                        let label = label.trim();
+                        (!label.is_empty())
+                            .then_some(())
+                            .and_then(|_| parse_inference(value.trim()))
+                            .map(|i| (label.to_lowercase(), i))
+                    })
+                    .ok_or_else(|| {
+                        StringError::new(
+                            format!(
+                                "Wrong label rule '{r}', it should be in format <label> = <priority or Today, Tomorrow, Weekend, Next week>"
+                            )
+                            .as_str(),
+                        )
+                    })
+            })
+            .collect::<Result<Vec<_>, StringError>>()?;
+        Ok(Self { rules })
+    }
+
+    fn inferences(&self, labels: &[String]) -> impl Iterator<Item = Inference> {
+        let labels = labels.iter().map(|l| l.to_lowercase()).collect::<Vec<String>>();
+        self.rules
+            .iter()
+            .filter(move |(l, _)| labels.contains(l))
+            .map(|(_, i)| *i)
+    }
+
+    // The highest of the priorities of the labels
+    fn priority(&self, labels: &[String]) -> Option<Priority> {
+        self.inferences(labels)
+            .filter_map(|i| match i {
+                Inference::Priority(p) => Some(p),
+                Inference::Due(_) => None,
+            })
+            .max()
+    }
+
+    // The earliest of the due dates of the labels
+    fn due(&self, labels: &[String]) -> Option<DateTimeUtc> {
+        self.inferences(labels)
+            .filter_map(|i| match i {
+                Inference::Due(d) => d.into(),
+                Inference::Priority(_) => None,
+            })
+            .min()
+    }
+
+    fn has_due(&self) -> bool {
+        self.rules.iter().any(|(_, i)| matches!(i, Inference::Due(_)))
+    }
+
+    fn apply(&self, t: Box<dyn TaskTrait>) -> Box<dyn TaskTrait> {
+        let labels = t.labels();
+        let priority = self.priority(&labels).filter(|_| t.priority() == Priority::Normal);
+        let due = self.due(&labels).filter(|_| t.due().is_none());
+        if priority.is_none() && due.is_none() {
+            return t;
+        }
+
+        Box::new(InferredTask {
+            task: t.into(),
+            priority,
+            due,
+        })
+    }
+}
+
+// The task with the priority and the due date inferred from its labels,
+// the provider sees its own task through as_any, so the task can be changed as usual
+#[derive(Clone)]
+struct InferredTask {
+    task: Arc<dyn TaskTrait>,
+    priority: Option<Priority>,
+    due: Option<DateTimeUtc>,
+}
+
+impl TaskTrait for InferredTask {
+    fn id(&self) -> String {
+        self.task.id()
+    }
+    fn name(&self) -> RichString {
+        self.task.name()
+    }
+    fn description(&self) -> Option<RichString> {
+        self.task.description()
+    }
+    fn priority(&self) -> Priority {
+        self.priority.unwrap_or_else(|| self.task.priority())
+    }
+    fn state(&self) -> State {
+        self.task.state()
+    }
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        self.task.created_at()
+    }
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.task.updated_at()
+    }
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        self.task.completed_at()
+    }
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due.or_else(|| self.task.due())
+    }
+    fn scheduled(&self) -> Option<DateTimeUtc> {
+        self.task.scheduled()
+    }
+    fn place(&self) -> String {
+        self.task.place()
+    }
+    fn url(&self) -> String {
+        self.task.url()
+    }
+    fn labels(&self) -> Vec<String> {
+        self.task.labels()
+    }
+    fn parent_id(&self) -> Option<String> {
+        self.task.parent_id()
+    }
+    fn order(&self) -> Option<i64> {
+        self.task.order()
+    }
+    fn reminders(&self) -> Vec<DateTimeUtc> {
+        self.task.reminders()
+    }
+    fn recurrence(&self) -> Option<Recurrence> {
+        self.task.recurrence()
+    }
+    fn assignee(&self) -> Option<String> {
+        self.task.assignee()
+    }
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        self.task.is_assigned_to_me()
+    }
+    fn is_created_by_me(&self) -> Option<bool> {
+        self.task.is_created_by_me()
+    }
+    fn comment_count(&self) -> Option<u64> {
+        self.task.comment_count()
+    }
+    fn provider(&self) -> String {
+        self.task.provider()
+    }
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        self.task.project()
+    }
+    fn as_any(&self) -> &dyn Any {
+        self.task.as_any()
+    }
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+    fn const_patch_policy(&self) -> PatchPolicy {
+        self.task.const_patch_policy()
+    }
+}
+
+// Wraps the provider to apply the label rules to the listed tasks
+pub struct LabelRulesProvider {
+    inner: Box<dyn ProviderTrait>,
+    rules: LabelRules,
+}
+
+impl LabelRulesProvider {
+    pub fn new(inner: Box<dyn ProviderTrait>, rules: LabelRules) -> Self {
+        Self { inner, rules }
+    }
+
+    fn apply(&self, tasks: Vec<Box<dyn TaskTrait>>) -> Vec<Box<dyn TaskTrait>> {
+        tasks.into_iter().map(|t| self.rules.apply(t)).collect()
+    }
+}
+
+impl std::fmt::Debug for LabelRulesProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for LabelRulesProvider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        ProjectProviderTrait::list(self.inner.as_mut()).await
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for LabelRulesProvider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        if !self.rules.has_due() {
+            let tasks = TaskProviderTrait::list(self.inner.as_mut(), project, f).await?;
+            return Ok(self.apply(tasks));
+        }
+
+        // the task without the due date can get one, so the due filter is applied after the rules
+        let all_dates = filter::Filter {
+            states: f.states.clone(),
+            due: filter::Due::values(),
+        };
+        let tasks = TaskProviderTrait::list(self.inner.as_mut(), project, &all_dates).await?;
+        Ok(self.apply(tasks).into_iter().filter(|t| f.accept(t.as_ref())).collect())
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        self.inner.create(project_id, tp).await
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        self.inner.update(patches).await
+    }
+
+    async fn set_order(&mut self, ids: &[String]) -> Result<(), ProviderError> {
+        self.inner.set_order(ids).await
+    }
+
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let tasks = self.inner.query(query).await?;
+        Ok(self.apply(tasks))
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        self.inner.delete(t).await
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for LabelRulesProvider {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn type_name(&self) -> String {
+        self.inner.type_name()
+    }
+
+    async fn reload(&mut self) {
+        self.inner.reload().await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn supported_priorities(&self) -> Vec<Priority> {
+        self.inner.supported_priorities()
+    }
+
+    fn sync_state(&self) -> SyncState {
+        self.inner.sync_state()
+    }
+
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.inner.clear_cache().await
+    }
+
+    fn subscribe_on_changes(&self) -> Option<broadcast::Receiver<()>> {
+        self.inner.subscribe_on_changes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::time;
+
+    #[derive(Clone)]
+    struct TestTask {
+        labels: Vec<&'static str>,
+        priority: Priority,
+        due: Option<DateTimeUtc>,
+    }
+
+    impl TaskTrait for TestTask {
+        fn id(&self) -> String {
+            "id".to_string()
+        }
+        fn name(&self) -> RichString {
+            RichString::new("Fix the login")
+        }
+        fn priority(&self) -> Priority {
+            self.priority
+        }
+        fn state(&self) -> State {
+            State::Uncompleted
+        }
+        fn due(&self) -> Option<DateTimeUtc> {
+            self.due
+        }
+        fn labels(&self) -> Vec<String> {
+            self.labels.iter().map(|l| l.to_string()).collect()
+        }
+        fn provider(&self) -> String {
+            "GitHub".to_string()
+        }
+        fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+            None
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+        fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn parse_test() {
+        let rules = LabelRules::parse("P1 = High; p2=medium ; urgent = Today; later = Next week;").unwrap();
+        assert_eq!(
+            rules.rules,
+            vec![
+                ("p1".to_string(), Inference::Priority(Priority::High)),
+                ("p2".to_string(), Inference::Priority(Priority::Medium)),
+                ("urgent".to_string(), Inference::Due(DatePatchItem::Today)),
+                ("later".to_string(), Inference::Due(DatePatchItem::NextWeek)),
+            ]
+        );
+        assert_eq!(LabelRules::parse("").unwrap(), LabelRules::default());
+
+        for s in ["P1", "= High", "P1 = Urgent"] {
+            assert!(LabelRules::parse(s).is_err(), "Test '{s}' was failed");
+        }
+    }
+
+    #[test]
+    fn apply_test() {
+        let rules = LabelRules::parse("P1 = High; P2 = Medium; urgent = Today; soon = Tomorrow").unwrap();
+        let today: Option<DateTimeUtc> = DatePatchItem::Today.into();
+
+        struct Case<'a> {
+            name: &'a str,
+            task: TestTask,
+            expected_priority: Priority,
+            expected_due: Option<DateTimeUtc>,
+        }
+        let cases = [
+            Case {
+                name: "no labels",
+                task: TestTask {
+                    labels: vec![],
+                    priority: Priority::Normal,
+                    due: None,
+                },
+                expected_priority: Priority::Normal,
+                expected_due: None,
+            },
+            Case {
+                name: "the highest priority and the earliest date",
+                task: TestTask {
+                    labels: vec!["p2", "P1", "soon", "Urgent", "bug"],
+                    priority: Priority::Normal,
+                    due: None,
+                },
+                expected_priority: Priority::High,
+                expected_due: today,
+            },
+            Case {
+                name: "the provider's values are kept",
+                task: TestTask {
+                    labels: vec!["P1", "urgent"],
+                    priority: Priority::Low,
+                    due: Some(time::add_days(&today.unwrap(), 3)),
+                },
+                expected_priority: Priority::Low,
+                expected_due: Some(time::add_days(&today.unwrap(), 3)),
+            },
+        ];
+
+        for c in cases {
+            let t = rules.apply(Box::new(c.task));
+            assert_eq!(t.priority(), c.expected_priority, "Test '{}' was failed", c.name);
+            assert_eq!(t.due(), c.expected_due, "Test '{}' was failed", c.name);
+            assert!(
+                t.as_any().downcast_ref::<TestTask>().is_some(),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
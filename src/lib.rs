@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: MIT
+
+// The modules of the app are the library, so the benches can use them
+
+pub mod async_jobs;
+pub mod audit;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod cli;
+pub mod clipboard;
+pub mod daemon;
+pub mod dependencies;
+pub mod done;
+pub mod import;
+pub mod ipc;
+pub mod label_rules;
+pub mod memo;
+pub mod migration;
+#[cfg(feature = "notify")]
+pub mod notify;
+pub mod offline;
+pub mod panic_guard;
+pub mod passphrase;
+pub mod pinned;
+pub mod provider;
+pub mod settings;
+pub mod sound;
+pub mod spellcheck;
+pub mod starred;
+pub mod state_snapshot;
+pub mod sync;
+pub mod ui;
+pub mod waiting;
+pub mod wizard;
@@ -1,26 +1,56 @@
 // SPDX-License-Identifier: MIT
 
-mod async_jobs;
-mod migration;
-mod provider;
-mod settings;
-mod ui;
-mod wizard;
-
-use std::{
-    path::{Path, PathBuf},
-    str::FromStr,
-    sync::Arc,
-};
-use tatuin_providers::{
-    caldav::{self, AuthType},
-    config::Config,
-    github_issues, gitlab_todo, ical, obsidian, tatuin, todoist,
-};
+#[cfg(any(
+    feature = "obsidian",
+    feature = "markdown-dir",
+    feature = "todotxt",
+    feature = "inbox",
+    feature = "taskwarrior",
+    feature = "org"
+))]
+use std::path::Path;
+use std::{path::PathBuf, sync::Arc, time::Duration};
+#[cfg(feature = "caldav")]
+use tatuin_providers::caldav::{self, AuthType};
+#[cfg(feature = "deck")]
+use tatuin_providers::deck;
+#[cfg(feature = "github")]
+use tatuin_providers::github_issues;
+#[cfg(feature = "github")]
+use tatuin_providers::github_projects;
+#[cfg(feature = "gitlab")]
+use tatuin_providers::gitlab_issues;
+#[cfg(feature = "gitlab")]
+use tatuin_providers::gitlab_todo;
+#[cfg(feature = "ical")]
+use tatuin_providers::ical;
+#[cfg(feature = "inbox")]
+use tatuin_providers::inbox;
+#[cfg(feature = "jira")]
+use tatuin_providers::jira;
+#[cfg(feature = "mstodo")]
+use tatuin_providers::mstodo;
+#[cfg(feature = "notion")]
+use tatuin_providers::notion;
+#[cfg(feature = "obsidian")]
+use tatuin_providers::obsidian;
+#[cfg(feature = "tatuin-local")]
+use tatuin_providers::tatuin;
+#[cfg(feature = "todoist")]
+use tatuin_providers::todoist;
+// the Obsidian provider takes its delete mode from the markdown directory one
+#[cfg(any(feature = "markdown-dir", feature = "obsidian"))]
+use tatuin_providers::markdown_dir;
+#[cfg(feature = "org")]
+use tatuin_providers::org;
+#[cfg(feature = "taskwarrior")]
+use tatuin_providers::taskwarrior;
+#[cfg(feature = "todotxt")]
+use tatuin_providers::todotxt;
+use tatuin_providers::{OPTIONAL_PROVIDERS, config::Config};
 
 use clap::{Parser, Subcommand};
 use color_eyre::owo_colors::OwoColorize;
-use crossterm::{event::DisableMouseCapture, execute};
 use itertools::Itertools;
 use provider::Provider;
 use ratatui::style::Color;
@@ -31,16 +61,45 @@ use tracing_subscriber::fmt::format::FmtSpan;
 use ui::style;
 
 use tatuin_core::{
-    filter, folders, project,
-    provider::{ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
+    crypto::Cipher,
+    filter, folders,
+    in_flight::{DEFAULT_MAX_REQUESTS, RequestLimit},
+    placeholder, project,
+    provider::{ProjectProviderTrait, ProviderTrait, Query},
+    registry::ProviderRegistry,
+    tag_alias::TagAliases,
     task,
+    task_store::AggregatedTaskStore,
+    time,
 };
 
-use crate::migration::migrate_config;
+#[cfg(feature = "capture")]
+use ::tatuin::capture;
+#[cfg(feature = "notify")]
+use ::tatuin::notify;
+use ::tatuin::{
+    audit, cli, clipboard, daemon, dependencies, done, import, ipc, label_rules, memo,
+    migration::{migrate_config, migrate_settings},
+    offline, panic_guard, passphrase, pinned, provider, settings, sound, spellcheck, starred, state_snapshot, status,
+    sync, ui, waiting, wizard,
+};
 
 const APP_NAME: &str = "tatuin";
 const CONFIG_FILE_NAME: &str = "settings.toml";
 const KEEP_LOG_FILES_COUNT: usize = 5;
+// The types of the providers that load the tasks from the network
+const OFFLINE_CACHED_PROVIDERS: &[&str] = &[
+    #[cfg(feature = "todoist")]
+    todoist::PROVIDER_NAME,
+    #[cfg(feature = "gitlab")]
+    gitlab_issues::PROVIDER_NAME,
+    #[cfg(feature = "gitlab")]
+    gitlab_todo::PROVIDER_NAME,
+    #[cfg(feature = "github")]
+    github_issues::PROVIDER_NAME,
+    #[cfg(feature = "github")]
+    github_projects::PROVIDER_NAME,
+];
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -53,6 +112,14 @@ struct Cli {
 
     #[arg(short, long, name("THEME_NAME"), help("theme name"))]
     theme: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        global(true),
+        help("print only the requested output and the errors, for the scripts")
+    )]
+    quiet: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -73,12 +140,169 @@ enum Commands {
         provider: Option<String>,
     },
     AddProvider {},
+    Add {
+        #[arg(
+            name("TASK_NAME"),
+            required_unless_present("template"),
+            help("task name, it can contain the placeholders like {{date}}")
+        )]
+        name: Option<String>,
+
+        #[arg(
+            short,
+            long,
+            help("template from the settings, its name and description are used if not set")
+        )]
+        template: Option<String>,
+
+        #[arg(short, long, help("task description"))]
+        description: Option<String>,
+
+        #[arg(
+            long,
+            help("provider to add the task into, default_provider from the settings if not set")
+        )]
+        provider: Option<String>,
+
+        #[arg(
+            long,
+            help("project to add the task into, default_project from the settings if not set")
+        )]
+        project: Option<String>,
+    },
+    Done {
+        #[arg(
+            long("match"),
+            value_name("PATTERN"),
+            help("name of the uncompleted task, it's matched fuzzily")
+        )]
+        pattern: String,
+
+        #[arg(short, long, help("provider to search the task in, all providers if not set"))]
+        provider: Option<String>,
+
+        #[arg(long, help("complete the best match without asking if several tasks match"))]
+        first: bool,
+    },
+    Open {
+        #[arg(
+            long("match"),
+            value_name("PATTERN"),
+            help("name of the uncompleted task, it's matched fuzzily")
+        )]
+        pattern: String,
+
+        #[arg(short, long, help("provider to search the task in, all providers if not set"))]
+        provider: Option<String>,
+
+        #[arg(long, help("open the best match without asking if several tasks match"))]
+        first: bool,
+    },
+    Sync {
+        #[arg(short, long, help("provider to synchronize, all providers if not set"))]
+        provider: Option<String>,
+
+        #[arg(long, help("drop the caches and download everything again"))]
+        full: bool,
+    },
     ConfigDir {},
+    Import {
+        #[arg(name("PATH_TO_FILE"), help("/path/to/exported/file"))]
+        file: String,
+
+        #[arg(short, long, value_enum)]
+        format: import::Format,
+
+        #[arg(
+            short,
+            long,
+            value_name("PROVIDER/PROJECT"),
+            help("provider and project to import tasks into")
+        )]
+        into: String,
+
+        #[arg(long, help("print tasks without importing them"))]
+        dry_run: bool,
+
+        #[command(flatten)]
+        mapping: import::Mapping,
+    },
+    #[cfg(feature = "capture")]
+    Capture {
+        #[arg(long, help("check the configured chats once and exit"))]
+        once: bool,
+    },
+    #[cfg(feature = "notify")]
+    Notify {
+        #[arg(long, help("send the notifications once and exit"))]
+        once: bool,
+        #[arg(long, help("the context that is active whatever its hours are"))]
+        context: Option<String>,
+    },
+    Encrypt {},
+    Decrypt {},
+    State {
+        #[command(subcommand)]
+        command: StateCommands,
+    },
+    Daemon {
+        #[command(subcommand)]
+        command: DaemonCommands,
+    },
 }
 
-fn print_boxed_tasks(tasks: &[Box<dyn task::Task>]) {
+#[derive(Subcommand, Debug)]
+enum DaemonCommands {
+    Run {
+        #[arg(long, default_value_t = 300, help("seconds between the synchronizations"))]
+        interval: u64,
+    },
+    Install {
+        #[arg(long, default_value_t = 300, help("seconds between the synchronizations"))]
+        interval: u64,
+    },
+    Status {},
+    Uninstall {},
+}
+
+#[derive(Subcommand, Debug)]
+enum StateCommands {
+    Export {
+        #[arg(name("PATH_TO_FILE"), help("/path/to/states.toml"))]
+        file: PathBuf,
+
+        #[arg(short, long, help("state to export, all states are exported if not set"))]
+        name: Vec<String>,
+    },
+    Import {
+        #[arg(name("PATH_TO_FILE"), help("/path/to/states.toml"))]
+        file: PathBuf,
+
+        #[arg(short, long, help("state to import, all states are imported if not set"))]
+        name: Vec<String>,
+
+        #[arg(long, help("replace the existing states with the same names"))]
+        overwrite: bool,
+    },
+}
+
+fn format_task(t: &dyn task::Task) -> String {
+    format!(
+        "- [{}] {} ({}) ({})",
+        t.state(),
+        t.name().plain(),
+        format!(
+            "due: {}",
+            task::datetime_to_str(t.due(), &chrono::Local::now().timezone())
+        )
+        .blue(),
+        t.place().green()
+    )
+}
+
+fn print_tasks(tasks: &[Arc<dyn task::Task>]) {
     for t in tasks {
-        println!("{}", task::format(t.as_ref()));
+        println!("{}", format_task(t.as_ref()));
     }
 }
 
@@ -157,7 +381,8 @@ fn is_true(v: bool) -> bool {
     v
 }
 
-fn load_providers(cfg: &Settings) -> Result<Vec<Provider>, Box<dyn std::error::Error>> {
+// The cached tasks of the remote providers are shown first in the TUI only, the commands need the actual ones
+fn load_providers(cfg: &Settings, offline_mode: offline::Mode) -> Result<Vec<Provider>, Box<dyn std::error::Error>> {
     let providers_colors = style::provider_colors();
     let mut it = providers_colors.iter();
     let mut color = || -> &Color {
@@ -171,6 +396,13 @@ fn load_providers(cfg: &Settings) -> Result<Vec<Provider>, Box<dyn std::error::E
         }
     };
 
+    let cipher = cfg.cipher();
+    let audit_log = cfg
+        .audit
+        .enabled
+        .then(|| Arc::new(audit::AuditLog::new(&folders::log_folder(APP_NAME), &cfg.audit)));
+    let memory_cache = &cfg.memory_cache;
+    let is_offline_cache_enabled = cfg.offline_cache.enabled;
     let mut providers: Vec<Provider> = Vec::new();
 
     for (name, config) in &cfg.providers {
@@ -181,52 +413,207 @@ fn load_providers(cfg: &Settings) -> Result<Vec<Provider>, Box<dyn std::error::E
             continue;
         }
 
-        let cfg = Config::new(APP_NAME, name);
+        let cfg = Config::new(APP_NAME, name).with_cipher(cipher.clone());
         let try_config_value = |key: &str| -> Option<&str> { config.get(key).map(String::as_str) };
         let config_value = |key: &str| -> &str { try_config_value(key).unwrap() };
 
+        let offline_cache = (is_offline_cache_enabled && OFFLINE_CACHED_PROVIDERS.contains(&config_value("type")))
+            .then(|| folders::provider_cache_folder(APP_NAME, name))
+            .transpose()?;
+
         let p: Option<Box<dyn ProviderTrait>> = match config_value("type") {
+            #[cfg(feature = "tatuin-local")]
             tatuin::PROVIDER_NAME => Some(Box::new(tatuin::Provider::new(cfg)?)),
+            #[cfg(feature = "obsidian")]
             obsidian::PROVIDER_NAME => {
                 let mut path = config_value("path").to_string();
                 if !path.ends_with('/') {
                     path.push('/');
                 }
 
-                Some(Box::new(obsidian::Provider::new(cfg, Path::new(&path))))
+                let delete_mode = markdown_dir::DeleteMode::new(
+                    try_config_value("delete_mode"),
+                    try_config_value("archive_note"),
+                    Path::new(&path),
+                )?;
+                let mut p = obsidian::Provider::new(cfg, Path::new(&path)).with_delete_mode(delete_mode);
+                if let Some(keys) = try_config_value("project_keys") {
+                    let keys = keys
+                        .split(',')
+                        .map(|k| k.trim().to_string())
+                        .filter(|k| !k.is_empty())
+                        .collect::<Vec<String>>();
+                    p = p.with_project_keys(&keys);
+                }
+                if let Some(depth) = try_config_value("folder_projects_depth") {
+                    p = p.with_folder_depth(depth.parse::<usize>()?);
+                }
+                if try_config_value("git_commit").is_some_and(|v| v.parse::<bool>().is_ok_and(is_true)) {
+                    p = p.with_git_commit(try_config_value("git_commit_message"));
+                }
+                Some(Box::new(p))
             }
+            #[cfg(feature = "todoist")]
             todoist::PROVIDER_NAME => Some(Box::new(todoist::Provider::new(cfg, config_value("api_key")))),
+            #[cfg(feature = "gitlab")]
             gitlab_todo::PROVIDER_NAME => Some(Box::new(gitlab_todo::Provider::new(
                 cfg,
                 config_value("base_url"),
                 config_value("api_key"),
             ))),
+            #[cfg(feature = "gitlab")]
+            gitlab_issues::PROVIDER_NAME => Some(Box::new(gitlab_issues::Provider::new(
+                cfg,
+                config_value("base_url"),
+                config_value("api_key"),
+                try_config_value("groups").unwrap_or_default(),
+                try_config_value("projects").unwrap_or_default(),
+            ))),
+            #[cfg(feature = "github")]
             github_issues::PROVIDER_NAME => Some(Box::new(github_issues::Provider::new(
                 cfg,
                 config_value("api_key"),
                 config_value("repository"),
             ))),
+            #[cfg(feature = "github")]
+            github_projects::PROVIDER_NAME => {
+                let number = config_value("number")
+                    .parse::<i64>()
+                    .map_err(|e| format!("Wrong project number of section {name}: {e}"))?;
+                let defaults = github_projects::Statuses::default();
+                let status = |key: &str, default: String| try_config_value(key).map(str::to_string).unwrap_or(default);
+                let statuses = github_projects::Statuses {
+                    field: status("status_field", defaults.field),
+                    todo: status("todo_status", defaults.todo),
+                    in_progress: status("in_progress_status", defaults.in_progress),
+                    done: status("done_status", defaults.done),
+                };
+                Some(Box::new(
+                    github_projects::Provider::new(cfg, config_value("api_key"), config_value("owner"), number)
+                        .with_statuses(statuses)
+                        .with_due_field(try_config_value("due_field")),
+                ))
+            }
+            #[cfg(feature = "jira")]
+            jira::PROVIDER_NAME => Some(Box::new(jira::Provider::new(
+                cfg,
+                config_value("base_url"),
+                config_value("email"),
+                config_value("api_key"),
+            ))),
+            #[cfg(feature = "notion")]
+            notion::PROVIDER_NAME => Some(Box::new(notion::Provider::new(
+                cfg,
+                config_value("api_key"),
+                config_value("database_id"),
+                notion::Properties::new(
+                    try_config_value("state_property"),
+                    try_config_value("due_property"),
+                    try_config_value("description_property"),
+                ),
+            )?)),
+            #[cfg(feature = "mstodo")]
+            mstodo::PROVIDER_NAME => Some(Box::new(mstodo::Provider::new(
+                cfg,
+                try_config_value("tenant"),
+                config_value("client_id"),
+                config_value("api_key"),
+            ))),
+            #[cfg(feature = "deck")]
+            deck::PROVIDER_NAME => Some(Box::new(deck::Provider::new(
+                cfg,
+                config_value("url"),
+                config_value("login"),
+                config_value("password"),
+            ))),
+            #[cfg(feature = "ical")]
             ical::PROVIDER_NAME => Some(Box::new(ical::Provider::new(cfg, config_value("url"))?)),
+            #[cfg(feature = "caldav")]
             caldav::PROVIDER_NAME => Some(Box::new(caldav::Provider::new(
                 cfg,
                 config_value("url"),
                 config_value("login"),
                 config_value("password"),
-                try_config_value("auth_type").map(|t| AuthType::from_str(t).unwrap()),
+                try_config_value("auth_type").map(|t| t.parse::<AuthType>().unwrap()),
             )?)),
-            _ => {
-                println!("Unknown provider configuration for section: {name}");
+            #[cfg(feature = "markdown-dir")]
+            markdown_dir::PROVIDER_NAME => {
+                let conventions = markdown_dir::Conventions::new(
+                    try_config_value("task_regex"),
+                    try_config_value("bullet"),
+                    try_config_value("due_marker"),
+                    try_config_value("scheduled_marker"),
+                    try_config_value("completed_marker"),
+                )?;
+                Some(Box::new(markdown_dir::Provider::new(
+                    cfg,
+                    Path::new(config_value("path")),
+                    conventions,
+                )))
+            }
+            #[cfg(feature = "inbox")]
+            inbox::PROVIDER_NAME => Some(Box::new(inbox::Provider::new(cfg, Path::new(config_value("path"))))),
+            #[cfg(feature = "todotxt")]
+            todotxt::PROVIDER_NAME => Some(Box::new(todotxt::Provider::new(cfg, Path::new(config_value("path"))))),
+            #[cfg(feature = "taskwarrior")]
+            taskwarrior::PROVIDER_NAME => Some(Box::new(taskwarrior::Provider::new(
+                cfg,
+                try_config_value("command"),
+                try_config_value("data_location").map(Path::new),
+            ))),
+            #[cfg(feature = "org")]
+            org::PROVIDER_NAME => Some(Box::new(org::Provider::new(cfg, Path::new(config_value("path"))))),
+            t => {
+                let name = cfg.name();
+                match OPTIONAL_PROVIDERS.iter().find(|(type_name, _)| *type_name == t) {
+                    Some((_, feature)) => println!(
+                        "The provider type {t} of section {name} isn't compiled in, rebuild with the feature {feature}"
+                    ),
+                    None => println!("Unknown provider configuration for section: {name}"),
+                }
                 None
             }
         };
+        let p = match offline_cache {
+            Some(folder) => p.map(|p| {
+                Box::new(offline::OfflineProvider::new(p, &folder, cipher.clone(), offline_mode))
+                    as Box<dyn ProviderTrait>
+            }),
+            None => p,
+        };
+        let p = match try_config_value("label_rules") {
+            Some(rules) => {
+                let rules = label_rules::LabelRules::parse(rules)?;
+                p.map(|p| Box::new(label_rules::LabelRulesProvider::new(p, rules)) as Box<dyn ProviderTrait>)
+            }
+            None => p,
+        };
+        let p = match &audit_log {
+            Some(log) => p.map(|p| Box::new(audit::AuditedProvider::new(p, log.clone())) as Box<dyn ProviderTrait>),
+            None => p,
+        };
+        let p = p.map(|p| Box::new(memo::MemoizedProvider::new(p, memory_cache)) as Box<dyn ProviderTrait>);
         if let Some(p) = p {
+            let mut queries = Query::parse_list(try_config_value("queries").unwrap_or_default())?;
+            if !queries.is_empty() && !p.capabilities().queries {
+                println!("The provider {name} doesn't support the queries, they are skipped");
+                queries.clear();
+            }
             providers.push(provider::Provider {
                 name: name.to_string(),
                 type_name: p.type_name(),
+                group: try_config_value("group")
+                    .map(|g| g.trim().to_string())
+                    .filter(|g| !g.is_empty()),
                 color: *color(),
                 capabilities: p.capabilities(),
                 supported_priorities: p.supported_priorities(),
+                queries,
                 provider: Arc::new(RwLock::new(p)),
+                limit: RequestLimit::new(match try_config_value("max_requests") {
+                    Some(n) => n.parse::<usize>()?,
+                    None => DEFAULT_MAX_REQUESTS,
+                }),
             });
         }
     }
@@ -237,41 +624,142 @@ fn load_providers(cfg: &Settings) -> Result<Vec<Provider>, Box<dyn std::error::E
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> std::process::ExitCode {
     // console_subscriber::init();
 
     init_logging();
 
     tracing::info!("Start application");
 
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            let _ = e.print();
+            return if e.use_stderr() {
+                cli::ExitCode::Failure
+            } else {
+                cli::ExitCode::Ok
+            }
+            .into();
+        }
+    };
+    cli::set_quiet(cli.quiet);
+
+    let code = match run(cli).await {
+        Ok(()) => cli::ExitCode::Ok,
+        Err(e) => {
+            tracing::error!(target="main", error=?e, "Run the command");
+            eprintln!("Error: {e}");
+            cli::exit_code(e.as_ref())
+        }
+    };
+
+    tracing::info!("End application");
+    code.into()
+}
 
-    let mut cfg = if let Some(p) = cli.settings_file {
-        Settings::new(p.as_str())
+async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = if let Some(p) = &cli.settings_file {
+        PathBuf::from(p)
     } else {
         let config_dir = folders::config_folder(APP_NAME);
         let config_path = config_dir.join(CONFIG_FILE_NAME);
         if !std::fs::exists(&config_path).is_ok_and(is_true) {
             migrate_config(APP_NAME, CONFIG_FILE_NAME);
         }
-        Settings::new(config_path.to_str().unwrap())
+        config_path
     };
 
+    // the service is managed without the settings, they can be encrypted or have no providers yet
+    if let Some(Commands::Daemon { command }) = &cli.command {
+        let settings_file = cli.settings_file.is_some().then_some(config_path.as_path());
+        match command {
+            DaemonCommands::Install { interval } => {
+                return daemon::install(settings_file, Duration::from_secs(*interval));
+            }
+            DaemonCommands::Status {} => return daemon::status(),
+            DaemonCommands::Uninstall {} => return daemon::uninstall(),
+            DaemonCommands::Run { .. } => {}
+        }
+    }
+
+    migrate_settings(&config_path).map_err(cli::config_error)?;
+    let mut cfg = Settings::new(config_path.to_str().unwrap());
+
+    if cfg.is_encrypted() {
+        let cipher = Cipher::new(&passphrase::passphrase(&cfg.encryption, false).map_err(cli::config_error)?)
+            .map_err(cli::config_error)?;
+        cfg.decrypt(cipher).map_err(cli::config_error)?;
+    }
+
     if let Err(e) = load_theme(&cli.theme.or(cfg.theme.clone())) {
-        println!("Load theme error: {e}")
+        status!("Load theme error: {e}")
     }
 
-    let mut providers = load_providers(&cfg)?;
+    time::set_day_start_hour(cfg.interface.day_start_hour).map_err(cli::config_error)?;
+
+    // the TUI reads the tasks kept by the daemon instead of loading them on its own,
+    // the open subcommand starts the TUI if no session is running
+    let offline_mode = if cli
+        .command
+        .as_ref()
+        .is_some_and(|c| !matches!(c, Commands::Open { .. }))
+    {
+        offline::Mode::Network
+    } else if daemon::is_running(APP_NAME) {
+        offline::Mode::Shared
+    } else {
+        offline::Mode::CachedFirst
+    };
+    let mut providers = load_providers(&cfg, offline_mode).map_err(cli::config_error)?;
 
     if providers.is_empty() {
         println!("There is no provider that has been added yet. Please add one.");
         add_provider(&mut cfg)?;
-        providers = load_providers(&cfg)?;
+        providers = load_providers(&cfg, offline_mode).map_err(cli::config_error)?;
         if providers.is_empty() {
             return Ok(());
         }
     }
 
+    let creatable_providers = providers
+        .iter()
+        .filter(|p| p.capabilities.create_task)
+        .map(|p| p.name.as_str())
+        .collect_vec();
+    let (default_provider, default_project) = match cfg.check_default_destination(&creatable_providers) {
+        Ok(()) => (cfg.default_provider.clone(), cfg.default_project.clone()),
+        Err(e) => {
+            tracing::warn!(error=?e, "Wrong default destination");
+            status!("The default destination is ignored: {e}");
+            (None, None)
+        }
+    };
+
+    // the task is selected in the running session, otherwise the session is started with it
+    let mut task_to_open = None;
+    if let Some(Commands::Open {
+        pattern,
+        provider,
+        first,
+    }) = &cli.command
+    {
+        let request = ipc::Request::Open {
+            pattern: pattern.clone(),
+            provider: provider.clone(),
+            first: *first,
+            can_choose: done::can_choose(),
+        };
+        if let Some(message) = ipc::send(&ipc::socket_path(APP_NAME), &request).await? {
+            status!("{message}");
+            return Ok(());
+        }
+        match done::find(&providers, pattern, provider.as_deref(), *first, "open").await? {
+            Some((_, t)) => task_to_open = Some((t.provider(), t.id())),
+            None => return Ok(()),
+        }
+    }
+
     match &cli.command {
         Some(Commands::Providers {}) => {
             println!("Available providers: {}", wizard::AVAILABLE_PROVIDERS.join(", "));
@@ -282,18 +770,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 due: due_to_filter(due),
             };
 
-            let mut tasks = Vec::new();
-            for p in providers {
-                if let Some(provider_name) = provider
-                    && p.name != *provider_name
-                {
-                    continue;
-                }
-
-                let mut task_provider = p.provider.write().await;
-                tasks.append(&mut TaskProviderTrait::list(task_provider.as_mut(), None, &f).await?);
+            let mut registry = ProviderRegistry::new();
+            for p in providers
+                .iter()
+                .filter(|p| provider.as_ref().is_none_or(|n| p.name == *n))
+            {
+                registry.add_shared(&p.name, p.provider.clone())?;
+            }
+            let mut store = AggregatedTaskStore::new().with_sort_order(cfg.interface.sort_order);
+            if let Some((_, e)) = store.load(&registry, &f).await.into_iter().next() {
+                return Err(e.into());
             }
-            print_boxed_tasks(&tasks);
+            print_tasks(store.tasks());
         }
         Some(Commands::Projects { provider }) => {
             let mut projects = Vec::new();
@@ -312,17 +800,196 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             print_projects(&projects);
         }
         Some(Commands::AddProvider {}) => add_provider(&mut cfg)?,
+        Some(Commands::Add {
+            name,
+            template,
+            description,
+            provider,
+            project,
+        }) => {
+            let template = match template {
+                Some(t) => Some(cfg.templates.get(t).cloned().ok_or(cli::CliError::new(
+                    cli::ExitCode::ConfigError,
+                    format!("The template {t} is not found in the settings").as_str(),
+                ))?),
+                None => None,
+            };
+            let name = name
+                .clone()
+                .or(template.as_ref().map(|t| t.name.clone()))
+                .unwrap_or_default();
+            let description = description.clone().or(template.and_then(|t| t.description));
+            let name = placeholder::expand(&name, clipboard::read);
+            let description = description.map(|d| placeholder::expand(&d, clipboard::read));
+            let request = ipc::Request::Add {
+                name: name.clone(),
+                description: description.clone(),
+                provider: provider.clone().or(default_provider.clone()),
+                project: project.clone().or(default_project.clone()),
+            };
+            let message = match ipc::send(&ipc::socket_path(APP_NAME), &request).await? {
+                Some(message) => message,
+                None => {
+                    import::add(
+                        &providers,
+                        (default_provider.as_deref(), default_project.as_deref()),
+                        (provider.as_deref(), project.as_deref()),
+                        &name,
+                        description.as_deref(),
+                    )
+                    .await?
+                }
+            };
+            status!("{message}");
+        }
+        Some(Commands::Done {
+            pattern,
+            provider,
+            first,
+        }) => {
+            let request = ipc::Request::Done {
+                pattern: pattern.clone(),
+                provider: provider.clone(),
+                first: *first,
+                can_choose: done::can_choose(),
+            };
+            match ipc::send(&ipc::socket_path(APP_NAME), &request).await? {
+                Some(message) => status!("{message}"),
+                None => done::done(&providers, pattern, provider.as_deref(), *first).await?,
+            }
+        }
+        Some(Commands::Sync { provider, full }) => sync::sync(&providers, provider.as_deref(), *full).await?,
         Some(Commands::ConfigDir {}) => println!("{}", folders::config_folder(APP_NAME).to_str().unwrap()),
+        Some(Commands::Import {
+            file,
+            format,
+            into,
+            dry_run,
+            mapping,
+        }) => {
+            let content = std::fs::read_to_string(file)?;
+            let patches = import::parse(*format, &content, mapping)?;
+            import::import(&providers, into, &patches, *dry_run).await?;
+        }
+        #[cfg(feature = "capture")]
+        Some(Commands::Capture { once }) => capture::run(APP_NAME, &providers, &cfg.capture, *once).await?,
+        #[cfg(feature = "notify")]
+        Some(Commands::Notify { once, context }) => {
+            notify::run(
+                APP_NAME,
+                &providers,
+                &cfg.notify,
+                &cfg.contexts,
+                context.as_deref(),
+                *once,
+            )
+            .await?
+        }
+        Some(Commands::Encrypt {}) => {
+            let cipher = match cfg.cipher() {
+                Some(c) => c,
+                None => Cipher::new(&passphrase::passphrase(&cfg.encryption, true)?)?,
+            };
+            cfg.set_cipher(Some(cipher))?;
+            status!("The settings have been encrypted");
+        }
+        Some(Commands::Decrypt {}) => {
+            cfg.set_cipher(None)?;
+            status!("The settings have been decrypted");
+        }
+        Some(Commands::State { command }) => match command {
+            StateCommands::Export { file, name } => state_snapshot::export(&cfg, file, name)?,
+            StateCommands::Import { file, name, overwrite } => {
+                state_snapshot::import(&mut cfg, file, name, *overwrite)?
+            }
+        },
+        Some(Commands::Daemon {
+            command: DaemonCommands::Run { interval },
+        }) => daemon::run(APP_NAME, &providers, &cfg, Duration::from_secs(*interval)).await?,
         _ => {
             tracing::info!("Start tui");
+            let tag_aliases = TagAliases::parse(&cfg.tag_aliases).map_err(cli::config_error)?;
+            let spellchecker = if cfg.spellcheck.enabled {
+                match spellcheck::Spellchecker::load(&cfg.spellcheck) {
+                    Ok(sc) => Some(Arc::new(sc)),
+                    Err(e) => {
+                        tracing::warn!(error=?e, "Load the spellcheck dictionary");
+                        println!("The spellcheck is disabled: {e}");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            // the files are loaded before the terminal is taken, so their errors are printed to the usual screen
+            let starred = starred::StarredTasks::load(&folders::config_folder(APP_NAME).join("starred.toml"))
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error=?e, "Load the starred tasks");
+                    println!("The starred tasks can't be loaded: {e}");
+                    starred::StarredTasks::default()
+                });
+            let pinned = pinned::PinnedTasks::load(&folders::config_folder(APP_NAME).join("pinned.toml"))
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error=?e, "Load the pinned tasks");
+                    println!("The pinned tasks can't be loaded: {e}");
+                    pinned::PinnedTasks::default()
+                });
+            let waiting_tasks = waiting::WaitingTasks::load(&folders::config_folder(APP_NAME).join("waiting.toml"))
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error=?e, "Load the waiting tasks");
+                    println!("The waiting tasks can't be loaded: {e}");
+                    waiting::WaitingTasks::default()
+                });
+            let dependencies =
+                dependencies::Dependencies::load(&folders::config_folder(APP_NAME).join("dependencies.toml"))
+                    .unwrap_or_else(|e| {
+                        tracing::warn!(error=?e, "Load the dependencies of the tasks");
+                        println!("The dependencies of the tasks can't be loaded: {e}");
+                        dependencies::Dependencies::default()
+                    });
             color_eyre::install()?;
             let _guard = scopeguard::guard((), |_| {
-                let _ = execute!(std::io::stdout(), DisableMouseCapture);
+                ui::terminal::restore();
                 ratatui::restore();
                 tracing::info!("End tui");
             });
             let terminal = ratatui::init();
-            let app_result = ui::App::new(providers, cfg).await.run(terminal).await;
+            panic_guard::install_hook();
+            let sort_order = cfg.interface.sort_order;
+            let due_display = cfg.interface.due_display;
+            let cues = sound::Cues::new(&cfg.sounds);
+            sound::set_muted(cfg.sounds.muted);
+            let contexts = cfg.contexts.clone();
+            let follow_up = cfg.follow_up.clone();
+            let waiting_settings = cfg.waiting.clone();
+            let app_result = ui::App::new(providers, cfg)
+                .await
+                .sort_order(sort_order)
+                .await
+                .tag_aliases(tag_aliases)
+                .await
+                .follow_up(follow_up)
+                .await
+                .starred_tasks(starred)
+                .await
+                .pinned_tasks(pinned)
+                .await
+                .waiting(waiting_tasks, waiting_settings)
+                .await
+                .dependencies(dependencies)
+                .await
+                .due_display(due_display)
+                .sound_cues(cues)
+                .contexts(contexts)
+                .default_destination(default_provider, default_project)
+                .await
+                .spellchecker(spellchecker)
+                .await
+                .command_socket(ipc::socket_path(APP_NAME))
+                .open_task(task_to_open)
+                .await
+                .run(terminal)
+                .await;
             if let Err(e) = app_result {
                 tracing::error!(target="main", error=?e, "Run app");
                 return Err(e.into());
@@ -330,6 +997,5 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    tracing::info!("End application");
     Ok(())
 }
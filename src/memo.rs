@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: MIT
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tatuin_core::{
+    filter,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{Priority, Task as TaskTrait},
+    task_patch::{PatchError, TaskPatch},
+};
+use tokio::sync::broadcast;
+
+use crate::settings::MemoryCache;
+
+struct Entry<K, V> {
+    key: K,
+    loaded_at: Instant,
+    values: Vec<V>,
+}
+
+impl<K: PartialEq, V> Entry<K, V> {
+    fn get(&self, key: &K, ttl: Duration) -> Option<&[V]> {
+        (self.key == *key && self.loaded_at.elapsed() < ttl).then_some(self.values.as_slice())
+    }
+}
+
+type TasksKey = (Option<String>, filter::Filter);
+
+// Wraps the provider to keep the last loaded projects and tasks in memory for a while.
+// Any change made through the provider drops the memorized tasks.
+pub struct MemoizedProvider {
+    inner: Box<dyn ProviderTrait>,
+    projects_ttl: Duration,
+    tasks_ttl: Duration,
+    projects: Option<Entry<(), Box<dyn ProjectTrait>>>,
+    tasks: Option<Entry<TasksKey, Box<dyn TaskTrait>>>,
+}
+
+impl MemoizedProvider {
+    pub fn new(inner: Box<dyn ProviderTrait>, s: &MemoryCache) -> Self {
+        Self {
+            inner,
+            projects_ttl: Duration::from_secs(s.projects_ttl_secs),
+            tasks_ttl: Duration::from_secs(s.tasks_ttl_secs),
+            projects: None,
+            tasks: None,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.projects = None;
+        self.tasks = None;
+    }
+}
+
+impl std::fmt::Debug for MemoizedProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for MemoizedProvider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        if let Some(projects) = self.projects.as_ref().and_then(|e| e.get(&(), self.projects_ttl)) {
+            return Ok(projects.iter().map(|p| p.clone_boxed()).collect());
+        }
+
+        let projects = ProjectProviderTrait::list(self.inner.as_mut()).await?;
+        self.projects = Some(Entry {
+            key: (),
+            loaded_at: Instant::now(),
+            values: projects.iter().map(|p| p.clone_boxed()).collect(),
+        });
+        Ok(projects)
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for MemoizedProvider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let key = (project.as_ref().map(|p| p.id()), f.clone());
+        if let Some(tasks) = self.tasks.as_ref().and_then(|e| e.get(&key, self.tasks_ttl)) {
+            return Ok(tasks.iter().map(|t| t.clone_boxed()).collect());
+        }
+
+        let tasks = TaskProviderTrait::list(self.inner.as_mut(), project, f).await?;
+        self.tasks = Some(Entry {
+            key,
+            loaded_at: Instant::now(),
+            values: tasks.iter().map(|t| t.clone_boxed()).collect(),
+        });
+        Ok(tasks)
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        self.tasks = None;
+        self.inner.create(project_id, tp).await
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        self.tasks = None;
+        self.inner.update(patches).await
+    }
+
+    async fn set_order(&mut self, ids: &[String]) -> Result<(), ProviderError> {
+        self.tasks = None;
+        self.inner.set_order(ids).await
+    }
+
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        self.inner.query(query).await
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        self.tasks = None;
+        self.inner.delete(t).await
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for MemoizedProvider {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn type_name(&self) -> String {
+        self.inner.type_name()
+    }
+
+    async fn reload(&mut self) {
+        self.clear();
+        self.inner.reload().await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn supported_priorities(&self) -> Vec<Priority> {
+        self.inner.supported_priorities()
+    }
+
+    fn sync_state(&self) -> SyncState {
+        self.inner.sync_state()
+    }
+
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.clear();
+        self.inner.clear_cache().await
+    }
+
+    fn subscribe_on_changes(&self) -> Option<broadcast::Receiver<()>> {
+        self.inner.subscribe_on_changes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    #[derive(Debug, Default)]
+    struct CountingProvider {
+        projects_calls: Arc<AtomicUsize>,
+        tasks_calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl ProjectProviderTrait for CountingProvider {
+        async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+            self.projects_calls.fetch_add(1, Ordering::Relaxed);
+            Ok(Vec::new())
+        }
+    }
+
+    #[async_trait]
+    impl TaskProviderTrait for CountingProvider {
+        async fn list(
+            &mut self,
+            _project: Option<Box<dyn ProjectTrait>>,
+            _f: &filter::Filter,
+        ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+            self.tasks_calls.fetch_add(1, Ordering::Relaxed);
+            Ok(Vec::new())
+        }
+
+        async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+            Ok(())
+        }
+
+        async fn update(&mut self, _patches: &[TaskPatch]) -> Vec<PatchError> {
+            Vec::new()
+        }
+    }
+
+    #[async_trait]
+    impl ProviderTrait for CountingProvider {
+        fn name(&self) -> String {
+            "Counting".to_string()
+        }
+
+        fn type_name(&self) -> String {
+            "Counting".to_string()
+        }
+
+        async fn reload(&mut self) {}
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                create_task: true,
+                labels: false,
+                subtasks: false,
+                manual_order: false,
+                assignees: false,
+                queries: false,
+                url: false,
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn memoized_provider_test() {
+        let inner = CountingProvider::default();
+        let projects_calls = inner.projects_calls.clone();
+        let tasks_calls = inner.tasks_calls.clone();
+        let mut p = MemoizedProvider::new(
+            Box::new(inner),
+            &MemoryCache {
+                projects_ttl_secs: 60,
+                tasks_ttl_secs: 60,
+            },
+        );
+        let f = filter::Filter::full_filter();
+
+        ProjectProviderTrait::list(&mut p).await.unwrap();
+        ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(projects_calls.load(Ordering::Relaxed), 1);
+
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(tasks_calls.load(Ordering::Relaxed), 1);
+
+        // another filter
+        TaskProviderTrait::list(&mut p, None, &filter::Filter::default())
+            .await
+            .unwrap();
+        assert_eq!(tasks_calls.load(Ordering::Relaxed), 2);
+
+        // the changes drop the tasks only
+        p.create("project", &TaskPatch::default()).await.unwrap();
+        TaskProviderTrait::list(&mut p, None, &filter::Filter::default())
+            .await
+            .unwrap();
+        ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(tasks_calls.load(Ordering::Relaxed), 3);
+        assert_eq!(projects_calls.load(Ordering::Relaxed), 1);
+
+        p.reload().await;
+        ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(projects_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn disabled_test() {
+        let inner = CountingProvider::default();
+        let tasks_calls = inner.tasks_calls.clone();
+        let mut p = MemoizedProvider::new(
+            Box::new(inner),
+            &MemoryCache {
+                projects_ttl_secs: 0,
+                tasks_ttl_secs: 0,
+            },
+        );
+        let f = filter::Filter::full_filter();
+
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(tasks_calls.load(Ordering::Relaxed), 2);
+    }
+}
@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: MIT
 
+mod settings_schema;
 mod xdg_to_dirs;
 
+pub use settings_schema::{SETTINGS_VERSION, migrate_settings};
 pub use xdg_to_dirs::migrate_config;
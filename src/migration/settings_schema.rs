@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use tatuin_core::StringError;
+use toml::{Table, Value};
+
+const VERSION_KEY: &str = "version";
+
+type Apply = fn(&mut Table) -> Result<(), StringError>;
+
+struct Step {
+    description: &'static str,
+    apply: Apply,
+}
+
+// The step with index N migrates the settings from the version N to N + 1.
+// The new steps must be added to the end only.
+const STEPS: &[Step] = &[Step {
+    description: "Add the schema version",
+    apply: |_| Ok(()),
+}];
+
+pub const SETTINGS_VERSION: i64 = STEPS.len() as i64;
+
+fn version(t: &Table) -> Result<i64, StringError> {
+    match t.get(VERSION_KEY) {
+        None => Ok(0),
+        Some(Value::Integer(v)) if *v >= 0 => Ok(*v),
+        Some(v) => Err(StringError::new(format!("Wrong settings version {v}").as_str())),
+    }
+}
+
+// Returns the version the settings have been migrated from
+fn migrate_table(t: &mut Table) -> Result<i64, StringError> {
+    let from = version(t)?;
+    if from > SETTINGS_VERSION {
+        return Err(StringError::new(
+            format!(
+                "The settings version {from} is newer than the supported one {SETTINGS_VERSION}, update the application"
+            )
+            .as_str(),
+        ));
+    }
+
+    for step in &STEPS[from as usize..] {
+        tracing::info!(target:"settings_migration", step=step.description, "Apply the migration step");
+        (step.apply)(t)?;
+    }
+    t.insert(VERSION_KEY.to_string(), Value::Integer(SETTINGS_VERSION));
+
+    Ok(from)
+}
+
+fn backup_path(file_name: &Path, version: i64) -> PathBuf {
+    let mut p = file_name.as_os_str().to_os_string();
+    p.push(format!(".v{version}.bak"));
+    p.into()
+}
+
+// Brings the settings file to the current schema. The previous file is kept as a backup.
+pub fn migrate_settings(file_name: &Path) -> Result<(), Box<dyn Error>> {
+    if !std::fs::exists(file_name)? {
+        return Ok(());
+    }
+
+    let mut t = std::fs::read_to_string(file_name)?.parse::<Table>()?;
+    let from = version(&t)?;
+    if from == SETTINGS_VERSION {
+        return Ok(());
+    }
+
+    migrate_table(&mut t)?;
+
+    let backup = backup_path(file_name, from);
+    std::fs::copy(file_name, &backup)?;
+    std::fs::write(file_name, toml::to_string(&t)?)?;
+    crate::status!(
+        "The settings have been migrated from version {from} to {SETTINGS_VERSION}, the backup is {backup:?}"
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn migrate_table_test() {
+        struct Case<'a> {
+            name: &'a str,
+            content: &'a str,
+            expected_from: Option<i64>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "unversioned",
+                content: "theme = \"nord\"",
+                expected_from: Some(0),
+            },
+            Case {
+                name: "current",
+                content: "version = 1",
+                expected_from: Some(1),
+            },
+            Case {
+                name: "newer",
+                content: "version = 100",
+                expected_from: None,
+            },
+            Case {
+                name: "wrong",
+                content: "version = \"1\"",
+                expected_from: None,
+            },
+        ];
+
+        for c in CASES {
+            let mut t = c.content.parse::<Table>().unwrap();
+            let result = migrate_table(&mut t);
+            assert_eq!(result.ok(), c.expected_from, "Test '{}' was failed", c.name);
+            if c.expected_from.is_some() {
+                assert_eq!(version(&t).unwrap(), SETTINGS_VERSION, "Test '{}' was failed", c.name);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn migrate_settings_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let file_name = temp_dir.path().join("settings.toml");
+        let content = "theme = \"nord\"\n";
+        std::fs::write(&file_name, content).unwrap();
+
+        migrate_settings(&file_name).unwrap();
+        assert_eq!(std::fs::read_to_string(backup_path(&file_name, 0)).unwrap(), content);
+        let t = std::fs::read_to_string(&file_name).unwrap().parse::<Table>().unwrap();
+        assert_eq!(version(&t).unwrap(), SETTINGS_VERSION);
+        assert_eq!(t["theme"].as_str(), Some("nord"));
+
+        // the already migrated file isn't touched
+        std::fs::remove_file(backup_path(&file_name, 0)).unwrap();
+        migrate_settings(&file_name).unwrap();
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+        assert!(migrate_settings(&temp_dir.path().join("absent.toml")).is_ok());
+    }
+}
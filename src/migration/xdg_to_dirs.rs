@@ -17,12 +17,12 @@ pub fn migrate_config(app_name: &str, file_name: &str) {
     if let Some(config_file) = xdg::BaseDirectories::with_prefix(app_name).get_config_file(file_name)
         && std::fs::exists(&config_file).is_ok_and(|r| r)
     {
-        println!("Copy the config file {config_file:?} to the new location {new_config_file:?}");
+        crate::status!("Copy the config file {config_file:?} to the new location {new_config_file:?}");
         if let Err(e) = std::fs::copy(&config_file, &new_config_file) {
             panic!("Copy config from {config_file:?} to {new_config_file:?}: {e}");
         }
 
-        println!("Remove the old config file {config_file:?}");
+        crate::status!("Remove the old config file {config_file:?}");
         if let Err(e) = std::fs::remove_file(&config_file) {
             panic!("Remove the old config file {config_file:?}: {e}");
         }
@@ -34,7 +34,7 @@ pub fn migrate_config(app_name: &str, file_name: &str) {
 
 #[cfg(not(windows))]
 fn migrate_themes(app_name: &str) {
-    println!("Migrate theme files to the new location");
+    crate::status!("Migrate theme files to the new location");
 
     let config_folder = xdg::BaseDirectories::with_prefix(app_name)
         .get_config_home()
@@ -48,12 +48,12 @@ fn migrate_themes(app_name: &str) {
             {
                 let new_name = folders::config_folder(app_name).join(p.file_name().expect("Can't get a file name"));
 
-                println!("Copy the theme file {p:?} to the new location {new_name:?}");
+                crate::status!("Copy the theme file {p:?} to the new location {new_name:?}");
                 if let Err(e) = std::fs::copy(&p, &new_name) {
                     panic!("Copy theme file from {p:?} to {new_name:?}: {e}");
                 }
 
-                println!("Remove the old theme file {p:?}");
+                crate::status!("Remove the old theme file {p:?}");
                 if let Err(e) = std::fs::remove_file(&p) {
                     panic!("Remove the theme file {p:?} from old location: {e}");
                 }
@@ -64,7 +64,7 @@ fn migrate_themes(app_name: &str) {
 
 #[cfg(not(windows))]
 fn migrate_cache(app_name: &str) {
-    println!("Migrate provider's cache");
+    crate::status!("Migrate provider's cache");
 
     let old_cache = xdg::BaseDirectories::with_prefix(app_name)
         .get_cache_home()
@@ -75,12 +75,12 @@ fn migrate_cache(app_name: &str) {
 
     let new_cache = folders::cache_folder(app_name);
 
-    println!("Copy the folder {old_cache:?} to the new location {new_cache:?}");
+    crate::status!("Copy the folder {old_cache:?} to the new location {new_cache:?}");
     if let Err(e) = copy_recursively(&old_cache, &new_cache) {
         panic!("Copy cache folder {old_cache:?} to {new_cache:?}: {e}");
     }
 
-    println!("Remove the old cache {old_cache:?}");
+    crate::status!("Remove the old cache {old_cache:?}");
     if let Err(e) = std::fs::remove_dir_all(&old_cache) {
         panic!("Remove old cache folder {old_cache:?}: {e}");
     }
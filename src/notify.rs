@@ -0,0 +1,645 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use color_eyre::owo_colors::OwoColorize;
+use tatuin_core::{
+    StringError, filter,
+    provider::TaskProviderTrait,
+    task::{DateTimeUtc, Task as TaskTrait, datetime_to_str, due_group},
+};
+use tatuin_providers::{
+    config::Config,
+    notify::{Notification, Notifier, PublisherTrait, gotify, ntfy},
+};
+
+use crate::{
+    cli::{self, CliError},
+    provider::Provider,
+    settings::Context,
+};
+
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+const DEFAULT_TITLE_TEMPLATE: &str = "{status}: {name}";
+const DEFAULT_MESSAGE_TEMPLATE: &str = "Due: {due}\nProvider: {provider}";
+
+struct Target {
+    notifier: Notifier,
+    title: String,
+    message: String,
+    providers: Vec<String>,
+    interval: Duration,
+    next_run: Instant,
+}
+
+// The notification rules of a context, they're applied while the context is active
+struct ContextRule {
+    name: String,
+    hours: Option<(NaiveTime, NaiveTime)>,
+    days: Vec<Weekday>,
+    groups: Vec<String>,
+    muted_groups: Vec<String>,
+    lead_time: TimeDelta,
+}
+
+impl ContextRule {
+    fn new(name: &str, c: &Context) -> Result<Self, StringError> {
+        let err = |msg: String| StringError::new(format!("Context '{name}': {msg}").as_str());
+
+        let hours = match &c.hours {
+            Some(s) => Some(parse_hours(s).ok_or_else(|| err(format!("wrong hours '{s}'")))?),
+            None => None,
+        };
+        let days = c
+            .days
+            .iter()
+            .map(|d| d.parse::<Weekday>().map_err(|_| err(format!("wrong day '{d}'"))))
+            .collect::<Result<Vec<Weekday>, StringError>>()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            hours,
+            days,
+            groups: c.groups.clone(),
+            muted_groups: c.muted_groups.clone(),
+            lead_time: TimeDelta::minutes(c.lead_time.unwrap_or_default() as i64),
+        })
+    }
+
+    // The context without the hours and the days is active only if it's chosen by --context
+    fn is_active(&self, current: Option<&str>, now: &NaiveDateTime) -> bool {
+        if current == Some(self.name.as_str()) {
+            return true;
+        }
+        if self.hours.is_none() && self.days.is_empty() {
+            return false;
+        }
+
+        let (day, time) = match self.hours {
+            // the night hours belong to the day they start
+            Some((start, end)) if end <= start && now.time() < end => {
+                (now.date().pred_opt().unwrap_or(now.date()), true)
+            }
+            Some((start, end)) if end <= start => (now.date(), now.time() >= start),
+            Some((start, end)) => (now.date(), start <= now.time() && now.time() < end),
+            None => (now.date(), true),
+        };
+        time && (self.days.is_empty() || self.days.contains(&day.weekday()))
+    }
+
+    fn contains(&self, group: Option<&str>) -> bool {
+        self.groups.is_empty() || group.is_some_and(|g| self.groups.iter().any(|x| x == g))
+    }
+}
+
+fn parse_hours(s: &str) -> Option<(NaiveTime, NaiveTime)> {
+    let (start, end) = s.split_once('-')?;
+    let parse = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M").ok();
+    Some((parse(start)?, parse(end)?))
+}
+
+fn load_rules(contexts: &BTreeMap<String, Context>) -> Result<Vec<ContextRule>, StringError> {
+    contexts.iter().map(|(name, c)| ContextRule::new(name, c)).collect()
+}
+
+fn is_muted(rules: &[&ContextRule], group: Option<&str>) -> bool {
+    group.is_some_and(|g| rules.iter().any(|r| r.muted_groups.iter().any(|m| m == g)))
+}
+
+// The longest lead time of the active contexts that contain the group
+fn lead_time(rules: &[&ContextRule], group: Option<&str>) -> TimeDelta {
+    rules
+        .iter()
+        .filter(|r| r.contains(group))
+        .map(|r| r.lead_time)
+        .max()
+        .unwrap_or_default()
+}
+
+fn new_publisher(name: &str, cfg: &HashMap<String, String>) -> Result<Box<dyn PublisherTrait>, StringError> {
+    let value = |key: &str| -> Result<&str, StringError> {
+        cfg.get(key).map(String::as_str).ok_or(StringError::new(
+            format!("Notification '{name}': the key '{key}' is required").as_str(),
+        ))
+    };
+
+    match value("type")? {
+        ntfy::PUBLISHER_NAME => Ok(Box::new(ntfy::Publisher::new(
+            value("url")?,
+            value("topic")?,
+            value("token").ok(),
+        ))),
+        gotify::PUBLISHER_NAME => Ok(Box::new(gotify::Publisher::new(value("url")?, value("token")?))),
+        t => Err(StringError::new(
+            format!("Notification '{name}': unknown type '{t}'").as_str(),
+        )),
+    }
+}
+
+fn load(app_name: &str, settings: &HashMap<String, HashMap<String, String>>) -> Result<Vec<Target>, StringError> {
+    let mut result = Vec::new();
+    for (name, cfg) in settings {
+        let interval =
+            match cfg.get("interval") {
+                Some(s) => Duration::from_secs(s.parse::<u64>().map_err(|e| {
+                    StringError::new(format!("Notification '{name}': wrong interval '{s}': {e}").as_str())
+                })?),
+                None => DEFAULT_INTERVAL,
+            };
+
+        result.push(Target {
+            notifier: Notifier::new(Config::new(app_name, name), new_publisher(name, cfg)?),
+            title: cfg
+                .get("title")
+                .map_or(DEFAULT_TITLE_TEMPLATE, String::as_str)
+                .to_string(),
+            message: cfg
+                .get("message")
+                .map_or(DEFAULT_MESSAGE_TEMPLATE, String::as_str)
+                .to_string(),
+            providers: cfg
+                .get("providers")
+                .map(|s| s.split(',').map(|p| p.trim().to_string()).collect())
+                .unwrap_or_default(),
+            interval,
+            next_run: Instant::now(),
+        });
+    }
+    result.sort_by_key(|t| t.notifier.name());
+    Ok(result)
+}
+
+fn render(template: &str, t: &dyn TaskTrait) -> String {
+    let placeholders = [
+        ("{name}", t.name().plain()),
+        (
+            "{description}",
+            t.description().map(|d| d.display()).unwrap_or_default(),
+        ),
+        ("{provider}", t.provider()),
+        ("{project}", t.project().map(|p| p.name()).unwrap_or_default()),
+        ("{due}", datetime_to_str(t.due(), &Local::now().timezone())),
+        ("{priority}", t.priority().to_string()),
+        ("{status}", due_group(&t.due()).to_string()),
+        ("{url}", t.url()),
+    ];
+
+    placeholders
+        .iter()
+        .fold(template.to_string(), |s, (k, v)| s.replace(k, v))
+}
+
+// The last passed reminder or due date minus the lead time, the task is notified once for each of them
+fn notification_time(t: &dyn TaskTrait, now: DateTimeUtc, lead_time: TimeDelta) -> Option<DateTimeUtc> {
+    t.reminders()
+        .into_iter()
+        .chain(t.due().map(|d| d - lead_time))
+        .filter(|d| *d <= now)
+        .max()
+}
+
+fn to_notification(target: &Target, t: &dyn TaskTrait, at: DateTimeUtc) -> Notification {
+    Notification {
+        // the task is notified again if it has been rescheduled
+        id: format!("{}:{}", t.id(), at.to_rfc3339()),
+        title: render(&target.title, t),
+        message: render(&target.message, t),
+        url: t.url(),
+        priority: t.priority(),
+    }
+}
+
+async fn due_tasks(
+    providers: &[Provider],
+    names: &[String],
+    rules: &[&ContextRule],
+) -> Result<Vec<(Box<dyn TaskTrait>, DateTimeUtc)>, StringError> {
+    // the future tasks and the tasks without dates can have the reminders
+    let f = filter::Filter {
+        states: vec![filter::FilterState::Todo, filter::FilterState::InProgress],
+        due: filter::Due::values(),
+    };
+    let now = chrono::Utc::now();
+
+    let mut result = Vec::new();
+    for p in providers
+        .iter()
+        .filter(|p| names.is_empty() || names.contains(&p.name))
+        .filter(|p| !is_muted(rules, p.group.as_deref()))
+    {
+        let lead_time = lead_time(rules, p.group.as_deref());
+        let mut provider = p.provider.write().await;
+        let tasks = TaskProviderTrait::list(provider.as_mut(), None, &f).await?;
+        result.extend(
+            tasks
+                .into_iter()
+                .filter_map(|t| notification_time(t.as_ref(), now, lead_time).map(|at| (t, at))),
+        );
+    }
+    Ok(result)
+}
+
+async fn notify(providers: &[Provider], target: &mut Target, rules: &[&ContextRule]) -> Result<usize, StringError> {
+    let mut sent = 0;
+    for (t, at) in due_tasks(providers, &target.providers, rules).await? {
+        let n = to_notification(target, t.as_ref(), at);
+        if target.notifier.notify(&n).await? {
+            sent += 1;
+        }
+    }
+    Ok(sent)
+}
+
+pub async fn run(
+    app_name: &str,
+    providers: &[Provider],
+    settings: &HashMap<String, HashMap<String, String>>,
+    contexts: &BTreeMap<String, Context>,
+    context: Option<&str>,
+    once: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut targets = load(app_name, settings).map_err(cli::config_error)?;
+    if targets.is_empty() {
+        return Err(cli::config_error(StringError::new(
+            "There is no notification configured in the settings",
+        )));
+    }
+    if let Some(c) = context
+        && !contexts.contains_key(c)
+    {
+        return Err(CliError::no_match(format!("There is no context '{c}' in the settings").as_str()).into());
+    }
+    let rules = load_rules(contexts).map_err(cli::config_error)?;
+
+    loop {
+        let mut failed = false;
+        let now = Local::now().naive_local();
+        let active = rules
+            .iter()
+            .filter(|r| r.is_active(context, &now))
+            .collect::<Vec<&ContextRule>>();
+
+        for t in targets.iter_mut().filter(|t| t.next_run <= Instant::now()) {
+            match notify(providers, t, &active).await {
+                Ok(0) => {}
+                Ok(n) => crate::status!("{}: {n} notifications have been sent", t.notifier.name().purple()),
+                Err(e) => {
+                    tracing::error!(name=t.notifier.name(), error=?e, "Send notifications");
+                    eprintln!("{}: notification error: {e}", t.notifier.name().purple());
+                    failed = true;
+                }
+            }
+            t.next_run = Instant::now() + t.interval;
+        }
+
+        if once {
+            if failed {
+                return Err(CliError::provider("Some notifications haven't been sent").into());
+            }
+            return Ok(());
+        }
+
+        if let Some(next_run) = targets.iter().map(|t| t.next_run).min() {
+            tokio::time::sleep_until(next_run.into()).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::any::Any;
+    use tatuin_core::{
+        RichString,
+        project::Project as ProjectTrait,
+        task::{DateTimeUtc, Priority, State},
+    };
+
+    #[derive(Clone)]
+    struct TestTask {
+        due: Option<DateTimeUtc>,
+        reminders: Vec<DateTimeUtc>,
+    }
+
+    impl TaskTrait for TestTask {
+        fn id(&self) -> String {
+            "id".to_string()
+        }
+        fn name(&self) -> RichString {
+            RichString::new("Pay the bills")
+        }
+        fn priority(&self) -> Priority {
+            Priority::High
+        }
+        fn state(&self) -> State {
+            State::Uncompleted
+        }
+        fn due(&self) -> Option<DateTimeUtc> {
+            self.due
+        }
+        fn reminders(&self) -> Vec<DateTimeUtc> {
+            self.reminders.clone()
+        }
+        fn url(&self) -> String {
+            "https://example.com/1".to_string()
+        }
+        fn provider(&self) -> String {
+            "Home".to_string()
+        }
+        fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+            None
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+        fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn render_test() {
+        struct Case<'a> {
+            name: &'a str,
+            template: &'a str,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "default title",
+                template: DEFAULT_TITLE_TEMPLATE,
+                expected: "Overdue: Pay the bills",
+            },
+            Case {
+                name: "default message",
+                template: DEFAULT_MESSAGE_TEMPLATE,
+                expected: "Due: 2025-01-02\nProvider: Home",
+            },
+            Case {
+                name: "all fields",
+                template: "[{priority}] {name} {project}{description} {url} {unknown}",
+                expected: "[High] Pay the bills  https://example.com/1 {unknown}",
+            },
+        ];
+
+        let t = TestTask {
+            due: Some(
+                chrono::NaiveDate::from_ymd_opt(2025, 1, 2)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc(),
+            ),
+            reminders: Vec::new(),
+        };
+        for c in CASES {
+            assert_eq!(render(c.template, &t), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn notification_time_test() {
+        let dt = |s: &str| {
+            chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+                .unwrap()
+                .and_utc()
+        };
+        let now = dt("2025-01-02 12:00");
+
+        struct Case<'a> {
+            name: &'a str,
+            due: Option<&'a str>,
+            reminders: &'a [&'a str],
+            expected: Option<&'a str>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no dates",
+                due: None,
+                reminders: &[],
+                expected: None,
+            },
+            Case {
+                name: "overdue",
+                due: Some("2025-01-02 11:00"),
+                reminders: &[],
+                expected: Some("2025-01-02 11:00"),
+            },
+            Case {
+                name: "future",
+                due: Some("2025-01-03 11:00"),
+                reminders: &["2025-01-02 13:00"],
+                expected: None,
+            },
+            Case {
+                name: "the reminder before the due date",
+                due: Some("2025-01-03 11:00"),
+                reminders: &["2025-01-01 11:00", "2025-01-02 11:30", "2025-01-03 10:00"],
+                expected: Some("2025-01-02 11:30"),
+            },
+            Case {
+                name: "the reminder without the due date",
+                due: None,
+                reminders: &["2025-01-02 11:30"],
+                expected: Some("2025-01-02 11:30"),
+            },
+            Case {
+                name: "the due date after the reminder",
+                due: Some("2025-01-02 11:45"),
+                reminders: &["2025-01-02 11:30"],
+                expected: Some("2025-01-02 11:45"),
+            },
+        ];
+
+        for c in CASES {
+            let t = TestTask {
+                due: c.due.map(dt),
+                reminders: c.reminders.iter().map(|s| dt(s)).collect(),
+            };
+            assert_eq!(
+                notification_time(&t, now, TimeDelta::zero()),
+                c.expected.map(dt),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn context_rule_test() {
+        let dt = |s: &str| chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").unwrap();
+        let rule = |hours: Option<&str>, days: &[&str]| {
+            ContextRule::new(
+                "Work",
+                &Context {
+                    hours: hours.map(|h| h.to_string()),
+                    days: days.iter().map(|d| d.to_string()).collect(),
+                    ..Context::default()
+                },
+            )
+            .unwrap()
+        };
+
+        struct Case<'a> {
+            name: &'a str,
+            hours: Option<&'a str>,
+            days: &'a [&'a str],
+            current: Option<&'a str>,
+            now: &'a str,
+            expected: bool,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "not scheduled",
+                hours: None,
+                days: &[],
+                current: None,
+                now: "2025-01-06 10:00",
+                expected: false,
+            },
+            Case {
+                name: "chosen",
+                hours: Some("09:00-18:00"),
+                days: &[],
+                current: Some("Work"),
+                now: "2025-01-06 20:00",
+                expected: true,
+            },
+            Case {
+                name: "in the hours",
+                hours: Some("09:00-18:00"),
+                days: &["Mon", "Tue"],
+                current: Some("Personal"),
+                now: "2025-01-06 10:00",
+                expected: true,
+            },
+            Case {
+                name: "after the hours",
+                hours: Some("09:00-18:00"),
+                days: &[],
+                current: None,
+                now: "2025-01-06 18:00",
+                expected: false,
+            },
+            Case {
+                name: "another day",
+                hours: Some("09:00-18:00"),
+                days: &["Tue"],
+                current: None,
+                now: "2025-01-06 10:00",
+                expected: false,
+            },
+            Case {
+                name: "the whole day",
+                hours: None,
+                days: &["Monday"],
+                current: None,
+                now: "2025-01-06 23:00",
+                expected: true,
+            },
+            Case {
+                name: "the night after the day",
+                hours: Some("22:00-02:00"),
+                days: &["Mon"],
+                current: None,
+                now: "2025-01-07 01:00",
+                expected: true,
+            },
+            Case {
+                name: "the morning of the day",
+                hours: Some("22:00-02:00"),
+                days: &["Mon"],
+                current: None,
+                now: "2025-01-06 01:00",
+                expected: false,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                rule(c.hours, c.days).is_active(c.current, &dt(c.now)),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+
+        let err = |c: Context| ContextRule::new("Work", &c).err().unwrap().to_string();
+        assert_eq!(
+            err(Context {
+                hours: Some("9-18".to_string()),
+                ..Context::default()
+            }),
+            "Context 'Work': wrong hours '9-18'"
+        );
+        assert_eq!(
+            err(Context {
+                days: vec!["Someday".to_string()],
+                ..Context::default()
+            }),
+            "Context 'Work': wrong day 'Someday'"
+        );
+    }
+
+    #[test]
+    fn rules_test() {
+        let rule = |groups: &[&str], muted_groups: &[&str], lead_time: u64| {
+            ContextRule::new(
+                "Work",
+                &Context {
+                    groups: groups.iter().map(|g| g.to_string()).collect(),
+                    muted_groups: muted_groups.iter().map(|g| g.to_string()).collect(),
+                    lead_time: Some(lead_time),
+                    ..Context::default()
+                },
+            )
+            .unwrap()
+        };
+        let work = rule(&["Work"], &["Personal"], 30);
+        let all = rule(&[], &[], 10);
+        let rules = [&work, &all];
+
+        assert!(is_muted(&rules, Some("Personal")));
+        assert!(!is_muted(&rules, Some("Work")));
+        assert!(!is_muted(&rules, None));
+        assert!(!is_muted(&[], Some("Personal")));
+
+        assert_eq!(lead_time(&rules, Some("Work")), TimeDelta::minutes(30));
+        assert_eq!(lead_time(&rules, Some("Home")), TimeDelta::minutes(10));
+        assert_eq!(lead_time(&rules, None), TimeDelta::minutes(10));
+        assert_eq!(lead_time(&[&work], None), TimeDelta::zero());
+
+        let due = chrono::NaiveDate::from_ymd_opt(2025, 1, 2)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+            .and_utc();
+        let t = TestTask {
+            due: Some(due),
+            reminders: Vec::new(),
+        };
+        let now = due - TimeDelta::minutes(20);
+        assert_eq!(notification_time(&t, now, TimeDelta::zero()), None);
+        assert_eq!(
+            notification_time(&t, now, TimeDelta::minutes(30)),
+            Some(due - TimeDelta::minutes(30))
+        );
+    }
+
+    #[test]
+    fn load_test() {
+        let settings = HashMap::from([(
+            "phone".to_string(),
+            HashMap::from([
+                ("type".to_string(), "Gotify".to_string()),
+                ("url".to_string(), "https://gotify.example.com".to_string()),
+            ]),
+        )]);
+        let err = load("test_app", &settings).err().unwrap();
+        assert_eq!(err.to_string(), "Notification 'phone': the key 'token' is required");
+    }
+}
@@ -0,0 +1,1184 @@
+// SPDX-License-Identifier: MIT
+
+// The remote providers are cached on disk, so their tasks are shown at once on the start and without the network.
+// The changes made while the network is unavailable are queued and sent when the provider is loaded again.
+// While the daemon is running, the TUI reads the store kept by it and goes to the network only for the missing data.
+
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tatuin_core::{
+    RichString, StringError,
+    crypto::{Cipher, decrypt_content, encrypt_content},
+    filter,
+    patched_task::PatchedTask,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, Recurrence, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch, ValuePatch},
+};
+use tokio::{sync::broadcast, task::JoinHandle};
+
+const FILE_NAME: &str = "offline.json";
+// The task lists of the other projects and filters are dropped, the oldest first
+const MAX_TASK_LISTS: usize = 16;
+// How often the store is checked for the data written by the daemon
+const WATCH_PERIOD: Duration = Duration::from_secs(1);
+
+// How the stored data is used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    // the store is read only when the network is unavailable
+    Network,
+    // the stored data is shown once on the start, the provider is loaded from the network right after it
+    CachedFirst,
+    // the daemon keeps the store synchronized, the network is used for the data missing there
+    Shared,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedProject {
+    id: String,
+    name: String,
+    provider: String,
+    description: String,
+    parent_id: Option<String>,
+    is_inbox: bool,
+    is_favorite: bool,
+}
+
+impl CachedProject {
+    fn new(p: &dyn ProjectTrait) -> Self {
+        Self {
+            id: p.id(),
+            name: p.name(),
+            provider: p.provider(),
+            description: p.description(),
+            parent_id: p.parent_id(),
+            is_inbox: p.is_inbox(),
+            is_favorite: p.is_favorite(),
+        }
+    }
+}
+
+impl ProjectTrait for CachedProject {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+    fn provider(&self) -> String {
+        self.provider.clone()
+    }
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+    fn parent_id(&self) -> Option<String> {
+        self.parent_id.clone()
+    }
+    fn is_inbox(&self) -> bool {
+        self.is_inbox
+    }
+    fn is_favorite(&self) -> bool {
+        self.is_favorite
+    }
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+// The copy of the provider's task, the provider can't change it until the task is loaded from the network
+#[derive(Serialize, Deserialize, Clone)]
+struct CachedTask {
+    id: String,
+    name: String,
+    description: Option<String>,
+    priority: Priority,
+    state: State,
+    created_at: Option<DateTimeUtc>,
+    updated_at: Option<DateTimeUtc>,
+    completed_at: Option<DateTimeUtc>,
+    due: Option<DateTimeUtc>,
+    scheduled: Option<DateTimeUtc>,
+    place: String,
+    url: String,
+    labels: Vec<String>,
+    parent_id: Option<String>,
+    order: Option<i64>,
+    reminders: Vec<DateTimeUtc>,
+    recurrence: Option<Recurrence>,
+    assignee: Option<String>,
+    is_assigned_to_me: Option<bool>,
+    is_created_by_me: Option<bool>,
+    comment_count: Option<u64>,
+    provider: String,
+    project: Option<CachedProject>,
+    patch_policy: PatchPolicy,
+}
+
+impl CachedTask {
+    fn new(t: &dyn TaskTrait) -> Self {
+        Self {
+            id: t.id(),
+            name: t.name().raw(),
+            description: t.description().map(|d| d.raw()),
+            priority: t.priority(),
+            state: t.state(),
+            created_at: t.created_at(),
+            updated_at: t.updated_at(),
+            completed_at: t.completed_at(),
+            due: t.due(),
+            scheduled: t.scheduled(),
+            place: t.place(),
+            url: t.url(),
+            labels: t.labels(),
+            parent_id: t.parent_id(),
+            order: t.order(),
+            reminders: t.reminders(),
+            recurrence: t.recurrence(),
+            assignee: t.assignee(),
+            is_assigned_to_me: t.is_assigned_to_me(),
+            is_created_by_me: t.is_created_by_me(),
+            comment_count: t.comment_count(),
+            provider: t.provider(),
+            project: t.project().map(|p| CachedProject::new(p.as_ref())),
+            patch_policy: t.const_patch_policy(),
+        }
+    }
+}
+
+impl TaskTrait for CachedTask {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+    fn name(&self) -> RichString {
+        RichString::new(&self.name)
+    }
+    fn description(&self) -> Option<RichString> {
+        self.description.as_deref().map(RichString::new)
+    }
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+    fn state(&self) -> State {
+        self.state
+    }
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        self.created_at
+    }
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.updated_at
+    }
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        self.completed_at
+    }
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+    fn scheduled(&self) -> Option<DateTimeUtc> {
+        self.scheduled
+    }
+    fn place(&self) -> String {
+        self.place.clone()
+    }
+    fn url(&self) -> String {
+        self.url.clone()
+    }
+    fn labels(&self) -> Vec<String> {
+        self.labels.clone()
+    }
+    fn parent_id(&self) -> Option<String> {
+        self.parent_id.clone()
+    }
+    fn order(&self) -> Option<i64> {
+        self.order
+    }
+    fn reminders(&self) -> Vec<DateTimeUtc> {
+        self.reminders.clone()
+    }
+    fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence
+    }
+    fn assignee(&self) -> Option<String> {
+        self.assignee.clone()
+    }
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        self.is_assigned_to_me
+    }
+    fn is_created_by_me(&self) -> Option<bool> {
+        self.is_created_by_me
+    }
+    fn comment_count(&self) -> Option<u64> {
+        self.comment_count
+    }
+    fn provider(&self) -> String {
+        self.provider.clone()
+    }
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        self.project.as_ref().map(|p| p.clone_boxed())
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+    fn const_patch_policy(&self) -> PatchPolicy {
+        self.patch_policy.clone()
+    }
+}
+
+// The queued change is kept with the copy of its task, so it's sent after the restart too
+#[derive(Serialize, Deserialize, Clone)]
+struct QueuedPatch {
+    task: CachedTask,
+    name: ValuePatch<String>,
+    description: ValuePatch<String>,
+    due: ValuePatch<DatePatchItem>,
+    scheduled: ValuePatch<DatePatchItem>,
+    priority: ValuePatch<Priority>,
+    state: ValuePatch<State>,
+    labels: ValuePatch<Vec<String>>,
+    parent: ValuePatch<String>,
+    #[serde(default)]
+    url: ValuePatch<String>,
+}
+
+impl QueuedPatch {
+    fn new(p: &TaskPatch) -> Option<Self> {
+        p.task.as_ref().map(|t| Self {
+            task: CachedTask::new(t.as_ref()),
+            name: p.name.clone(),
+            description: p.description.clone(),
+            due: p.due.clone(),
+            scheduled: p.scheduled.clone(),
+            priority: p.priority.clone(),
+            state: p.state.clone(),
+            labels: p.labels.clone(),
+            parent: p.parent.clone(),
+            url: p.url.clone(),
+        })
+    }
+
+    fn patch(&self, task: Arc<dyn TaskTrait>) -> TaskPatch {
+        TaskPatch {
+            task: Some(task),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            due: self.due.clone(),
+            scheduled: self.scheduled.clone(),
+            priority: self.priority.clone(),
+            state: self.state.clone(),
+            labels: self.labels.clone(),
+            parent: self.parent.clone(),
+            url: self.url.clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct TaskList {
+    project_id: Option<String>,
+    filter: filter::Filter,
+    tasks: Vec<CachedTask>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Snapshot {
+    projects: Option<Vec<CachedProject>>,
+    task_lists: Vec<TaskList>,
+    synced_at: Option<DateTimeUtc>,
+    // the changes that haven't been sent because of the network
+    #[serde(default)]
+    queue: Vec<QueuedPatch>,
+}
+
+fn is_network_error(e: &ProviderError) -> bool {
+    matches!(e, ProviderError::Network(_) | ProviderError::RateLimited { .. })
+}
+
+fn is_network_patch_error(e: &PatchError) -> bool {
+    e.kind == PatchErrorKind::Network
+}
+
+// Wraps the remote provider to keep its last loaded projects and tasks on disk
+pub struct OfflineProvider {
+    inner: Box<dyn ProviderTrait>,
+    file_name: PathBuf,
+    cipher: Option<Cipher>,
+    snapshot: Snapshot,
+    mode: Mode,
+    show_cached_projects: bool,
+    show_cached_tasks: bool,
+    is_offline: bool,
+    // the stored tasks are outdated by the changes made here until the daemon loads them again
+    is_stale: bool,
+    tx: broadcast::Sender<()>,
+    // the refresh is announced to the first subscriber if it has been requested before the subscription
+    refresh_pending: AtomicBool,
+    watcher: OnceLock<JoinHandle<()>>,
+}
+
+impl OfflineProvider {
+    pub fn new(inner: Box<dyn ProviderTrait>, cache_folder: &Path, cipher: Option<Cipher>, mode: Mode) -> Self {
+        let file_name = cache_folder.join(FILE_NAME);
+        let snapshot = load_or_default(&file_name, cipher.as_ref());
+        let (tx, _) = broadcast::channel(1);
+
+        Self {
+            inner,
+            file_name,
+            cipher,
+            snapshot,
+            mode,
+            show_cached_projects: mode == Mode::CachedFirst,
+            show_cached_tasks: mode == Mode::CachedFirst,
+            is_offline: false,
+            is_stale: false,
+            tx,
+            refresh_pending: AtomicBool::new(false),
+            watcher: OnceLock::new(),
+        }
+    }
+
+    // The store is replaced at once, so the other process never reads it half-written
+    fn save(&mut self) {
+        self.save_with_queue(|_| {});
+    }
+
+    // The daemon and the TUI share the store, so it's changed under the lock and the queue is taken from it:
+    // the other process could have added or sent the changes since it was read
+    fn save_with_queue(&mut self, change: impl FnOnce(&mut Vec<QueuedPatch>)) {
+        let _lock = lock(&self.file_name)
+            .inspect_err(|e| tracing::warn!(file_name=?self.file_name, error=?e, "Lock the offline cache"))
+            .ok();
+        match load(&self.file_name, self.cipher.as_ref()) {
+            Ok(s) => self.snapshot.queue = s.queue,
+            Err(e) => tracing::warn!(file_name=?self.file_name, error=?e, "Load the offline cache queue"),
+        }
+        change(&mut self.snapshot.queue);
+
+        let tmp_file_name = self.file_name.with_extension("json.tmp");
+        let r = serde_json::to_vec(&self.snapshot)
+            .map_err(|e| StringError::new(e.to_string().as_str()))
+            .and_then(|data| encrypt_content(self.cipher.as_ref(), data))
+            .and_then(|data| std::fs::write(&tmp_file_name, data).map_err(StringError::from))
+            .and_then(|_| std::fs::rename(&tmp_file_name, &self.file_name).map_err(StringError::from));
+        if let Err(e) = r {
+            tracing::warn!(file_name=?self.file_name, error=?e, "Save the offline cache");
+        }
+    }
+
+    // The list of the same project and filter, otherwise the wider one stored for another filter or all the projects
+    fn cached_tasks(&self, project_id: &Option<String>, f: &filter::Filter) -> Option<Vec<Box<dyn TaskTrait>>> {
+        let lists = &self.snapshot.task_lists;
+        if let Some(l) = lists.iter().find(|l| l.project_id == *project_id && l.filter == *f) {
+            return Some(l.tasks.iter().map(|t| t.clone_boxed()).collect());
+        }
+
+        let covers = |l: &&TaskList| {
+            (l.project_id.is_none() || l.project_id == *project_id)
+                && f.states.iter().all(|s| l.filter.states.contains(s))
+                && f.due.iter().all(|d| l.filter.due.contains(d))
+        };
+        lists.iter().find(covers).map(|l| {
+            l.tasks
+                .iter()
+                .filter(|t| {
+                    f.accept(*t)
+                        && project_id
+                            .as_ref()
+                            .is_none_or(|id| t.project.as_ref().is_some_and(|p| p.id == *id))
+                })
+                .map(|t| t.clone_boxed())
+                .collect()
+        })
+    }
+
+    fn store_tasks(&mut self, project_id: Option<String>, f: &filter::Filter, tasks: &[Box<dyn TaskTrait>]) {
+        self.snapshot
+            .task_lists
+            .retain(|l| l.project_id != project_id || l.filter != *f);
+        self.snapshot.task_lists.push(TaskList {
+            project_id,
+            filter: f.clone(),
+            tasks: tasks.iter().map(|t| CachedTask::new(t.as_ref())).collect(),
+        });
+        if self.snapshot.task_lists.len() > MAX_TASK_LISTS {
+            self.snapshot.task_lists.remove(0);
+        }
+        self.snapshot.synced_at = Some(chrono::Utc::now());
+        self.save();
+    }
+
+    // The tasks are shown with the queued changes, as they have been sent already
+    fn with_queued(&self, tasks: Vec<Box<dyn TaskTrait>>) -> Vec<Box<dyn TaskTrait>> {
+        tasks
+            .into_iter()
+            .map(|t| {
+                let id = t.id();
+                self.snapshot.queue.iter().filter(|q| q.task.id == id).fold(t, |t, q| {
+                    let t: Arc<dyn TaskTrait> = t.into();
+                    Box::new(PatchedTask::new(t.clone(), Some(q.patch(t))))
+                })
+            })
+            .collect()
+    }
+
+    fn announce_refresh(&self) {
+        if self.tx.send(()).is_err() {
+            self.refresh_pending.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn enqueue<'a>(&mut self, patches: impl IntoIterator<Item = &'a TaskPatch>) {
+        let queued = patches
+            .into_iter()
+            .filter_map(QueuedPatch::new)
+            .collect::<Vec<QueuedPatch>>();
+        self.save_with_queue(|queue| queue.extend(queued));
+    }
+
+    // Returns whether some of the queued changes have been sent.
+    // The changes are removed from the queue only after the provider has answered, so the aborted load keeps them.
+    async fn send_queued(&mut self, loaded: &[Box<dyn TaskTrait>]) -> bool {
+        // the other process could have sent the changes already
+        if let Ok(s) = load(&self.file_name, self.cipher.as_ref()) {
+            self.snapshot.queue = s.queue;
+        }
+        // the cached task is replaced by the provider's one to be changed, the changes of the tasks not loaded wait
+        let ready = self
+            .snapshot
+            .queue
+            .iter()
+            .filter_map(|q| {
+                loaded
+                    .iter()
+                    .find(|t| t.id() == q.task.id)
+                    .map(|t| q.patch(Arc::from(t.clone_boxed())))
+            })
+            .collect::<Vec<TaskPatch>>();
+        if ready.is_empty() {
+            return false;
+        }
+
+        let errors = self.inner.update(&ready).await;
+        let sent = ready.len() > errors.len();
+        let mut failed_ids = Vec::new();
+        for e in errors {
+            if is_network_patch_error(&e) {
+                failed_ids.push(e.task.id());
+            } else {
+                tracing::error!(task_id = e.task.id(), error = e.error, "Send the queued change");
+            }
+        }
+        let done_ids = ready
+            .iter()
+            .filter_map(|p| p.task.as_ref().map(|t| t.id()))
+            .filter(|id| !failed_ids.contains(id))
+            .collect::<Vec<String>>();
+        self.save_with_queue(|queue| queue.retain(|q| !done_ids.contains(&q.task.id)));
+        sent
+    }
+}
+
+impl Drop for OfflineProvider {
+    fn drop(&mut self) {
+        if let Some(w) = self.watcher.get() {
+            w.abort();
+        }
+    }
+}
+
+fn load_or_default(file_name: &Path, cipher: Option<&Cipher>) -> Snapshot {
+    load(file_name, cipher).unwrap_or_else(|e| {
+        tracing::warn!(file_name=?file_name, error=?e, "Load the offline cache");
+        Snapshot::default()
+    })
+}
+
+fn load(file_name: &Path, cipher: Option<&Cipher>) -> Result<Snapshot, StringError> {
+    let data = match std::fs::read(file_name) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Snapshot::default()),
+        Err(e) => return Err(e.into()),
+    };
+    serde_json::from_slice(&decrypt_content(cipher, data)?).map_err(|e| StringError::new(e.to_string().as_str()))
+}
+
+// The advisory lock of the store, it's released when the file is closed
+fn lock(file_name: &Path) -> std::io::Result<std::fs::File> {
+    let f = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(file_name.with_extension("json.lock"))?;
+    f.lock()?;
+    Ok(f)
+}
+
+// The modification time and the size of the store, None if it doesn't exist
+async fn file_version(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    tokio::fs::metadata(path)
+        .await
+        .ok()
+        .map(|m| (m.modified().ok(), m.len()))
+}
+
+async fn watch(path: PathBuf, changes: broadcast::Sender<()>) {
+    let mut version = file_version(&path).await;
+    let mut interval = tokio::time::interval(WATCH_PERIOD);
+    loop {
+        interval.tick().await;
+        let v = file_version(&path).await;
+        if v != version {
+            version = v;
+            tracing::debug!(path=?path, "The offline cache has been changed");
+            let _ = changes.send(());
+        }
+    }
+}
+
+impl std::fmt::Debug for OfflineProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for OfflineProvider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let cached = |s: &Self| {
+            s.snapshot
+                .projects
+                .as_ref()
+                .map(|projects| projects.iter().map(|p| p.clone_boxed()).collect())
+        };
+
+        if (std::mem::take(&mut self.show_cached_projects) || self.mode == Mode::Shared)
+            && let Some(projects) = cached(self)
+        {
+            return Ok(projects);
+        }
+
+        match ProjectProviderTrait::list(self.inner.as_mut()).await {
+            Ok(projects) => {
+                self.snapshot.projects = Some(projects.iter().map(|p| CachedProject::new(p.as_ref())).collect());
+                self.save();
+                Ok(projects)
+            }
+            Err(e) if is_network_error(&e) => cached(self).ok_or(e),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for OfflineProvider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let project_id = project.as_ref().map(|p| p.id());
+        if std::mem::take(&mut self.show_cached_tasks)
+            && let Some(tasks) = self.cached_tasks(&project_id, f)
+        {
+            self.announce_refresh();
+            return Ok(self.with_queued(tasks));
+        }
+        if self.mode == Mode::Shared
+            && !self.is_stale
+            && let Some(tasks) = self.cached_tasks(&project_id, f)
+        {
+            return Ok(self.with_queued(tasks));
+        }
+
+        let mut result =
+            TaskProviderTrait::list(self.inner.as_mut(), project.as_ref().map(|p| p.clone_boxed()), f).await;
+        if let Ok(tasks) = &result
+            && !self.snapshot.queue.is_empty()
+            && self.send_queued(tasks).await
+        {
+            result = TaskProviderTrait::list(self.inner.as_mut(), project, f).await;
+        }
+
+        match result {
+            Ok(tasks) => {
+                self.is_offline = false;
+                self.is_stale = false;
+                self.store_tasks(project_id, f, &tasks);
+                Ok(self.with_queued(tasks))
+            }
+            Err(e) if is_network_error(&e) => {
+                self.is_offline = true;
+                match self.cached_tasks(&project_id, f) {
+                    Some(tasks) => Ok(self.with_queued(tasks)),
+                    None => Err(e),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        self.is_stale = true;
+        self.inner.create(project_id, tp).await
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        // the cached tasks can't be changed by the provider until they are loaded again
+        let (queued, ready): (Vec<TaskPatch>, Vec<TaskPatch>) = patches
+            .iter()
+            .cloned()
+            .partition(|p| self.is_offline || p.task.as_ref().is_some_and(|t| t.as_any().is::<CachedTask>()));
+        if !queued.is_empty() {
+            self.enqueue(&queued);
+        }
+        self.is_stale = true;
+        if ready.is_empty() {
+            return Vec::new();
+        }
+
+        let (network_errors, errors): (Vec<PatchError>, Vec<PatchError>) = self
+            .inner
+            .update(&ready)
+            .await
+            .into_iter()
+            .partition(is_network_patch_error);
+        let failed = ready
+            .iter()
+            .filter(|p| {
+                p.task
+                    .as_ref()
+                    .is_some_and(|t| network_errors.iter().any(|e| e.is_task(t.as_ref())))
+            })
+            .cloned()
+            .collect::<Vec<TaskPatch>>();
+        if !failed.is_empty() {
+            self.enqueue(&failed);
+        }
+        errors
+    }
+
+    async fn set_order(&mut self, ids: &[String]) -> Result<(), ProviderError> {
+        self.is_stale = true;
+        self.inner.set_order(ids).await
+    }
+
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        self.inner.query(query).await
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        self.is_stale = true;
+        self.inner.delete(t).await
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for OfflineProvider {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn type_name(&self) -> String {
+        self.inner.type_name()
+    }
+
+    // The daemon could have written the fresher data since the store was read
+    async fn reload(&mut self) {
+        // the queue of this process is written to the store with every its change, so the stored one covers it
+        match load(&self.file_name, self.cipher.as_ref()) {
+            Ok(s) => self.snapshot = s,
+            Err(e) => tracing::warn!(file_name=?self.file_name, error=?e, "Load the offline cache"),
+        }
+        self.inner.reload().await
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+
+    fn supported_priorities(&self) -> Vec<Priority> {
+        self.inner.supported_priorities()
+    }
+
+    fn sync_state(&self) -> SyncState {
+        let mut st = self.inner.sync_state();
+        st.cache_path = st.cache_path.or_else(|| Some(self.file_name.clone()));
+        st.last_sync = st.last_sync.or(self.snapshot.synced_at);
+        st
+    }
+
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.snapshot = Snapshot::default();
+        match std::fs::remove_file(&self.file_name) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(ProviderError::Other(e.to_string())),
+            _ => {}
+        }
+        self.inner.clear_cache().await
+    }
+
+    // The remote providers don't notify about their changes, so the refresh after showing the cached tasks
+    // and the changes of the store written by the daemon are sent
+    fn subscribe_on_changes(&self) -> Option<broadcast::Receiver<()>> {
+        if let Some(rx) = self.inner.subscribe_on_changes() {
+            return Some(rx);
+        }
+
+        if self.mode == Mode::Shared {
+            self.watcher
+                .get_or_init(|| tokio::spawn(watch(self.file_name.clone(), self.tx.clone())));
+        }
+        let rx = self.tx.subscribe();
+        if self.refresh_pending.swap(false, Ordering::Relaxed) {
+            let _ = self.tx.send(());
+        }
+        Some(rx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Mutex;
+    use tatuin_core::task_patch::ValuePatch;
+
+    #[derive(Debug, Clone)]
+    struct TestProject;
+
+    impl ProjectTrait for TestProject {
+        fn id(&self) -> String {
+            "inbox".to_string()
+        }
+        fn name(&self) -> String {
+            "Inbox".to_string()
+        }
+        fn provider(&self) -> String {
+            "Remote".to_string()
+        }
+        fn description(&self) -> String {
+            String::new()
+        }
+        fn parent_id(&self) -> Option<String> {
+            None
+        }
+        fn is_inbox(&self) -> bool {
+            true
+        }
+        fn is_favorite(&self) -> bool {
+            false
+        }
+        fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[derive(Clone)]
+    struct TestTask {
+        id: &'static str,
+        name: &'static str,
+    }
+
+    impl TaskTrait for TestTask {
+        fn id(&self) -> String {
+            self.id.to_string()
+        }
+        fn name(&self) -> RichString {
+            RichString::new(self.name)
+        }
+        fn state(&self) -> State {
+            State::Uncompleted
+        }
+        fn provider(&self) -> String {
+            "Remote".to_string()
+        }
+        fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+            Some(Box::new(TestProject))
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+        fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+            Box::new(self.clone())
+        }
+    }
+
+    // The remote side shared with the test, the provider is moved into the wrapper
+    #[derive(Default)]
+    struct Remote {
+        is_online: bool,
+        tasks: Vec<TestTask>,
+        // the ids of the tasks that the patches have been received for
+        patched: Vec<String>,
+        list_calls: usize,
+        // the changes are never answered, like the load aborted while they are sent
+        is_hanging: bool,
+    }
+
+    #[derive(Debug, Clone)]
+    struct RemoteProvider {
+        remote: Arc<Mutex<Remote>>,
+    }
+
+    impl std::fmt::Debug for Remote {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "Remote")
+        }
+    }
+
+    #[async_trait]
+    impl ProjectProviderTrait for RemoteProvider {
+        async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+            if !self.remote.lock().unwrap().is_online {
+                return Err(ProviderError::Network("no connection".to_string()));
+            }
+            Ok(vec![Box::new(TestProject)])
+        }
+    }
+
+    #[async_trait]
+    impl TaskProviderTrait for RemoteProvider {
+        async fn list(
+            &mut self,
+            _project: Option<Box<dyn ProjectTrait>>,
+            _f: &filter::Filter,
+        ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+            let mut r = self.remote.lock().unwrap();
+            r.list_calls += 1;
+            if !r.is_online {
+                return Err(ProviderError::Network("no connection".to_string()));
+            }
+            Ok(r.tasks.iter().map(|t| t.clone_boxed()).collect())
+        }
+
+        async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+            Ok(())
+        }
+
+        async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+            let is_hanging = self.remote.lock().unwrap().is_hanging;
+            if is_hanging {
+                std::future::pending::<()>().await;
+            }
+            let mut r = self.remote.lock().unwrap();
+            let mut errors = Vec::new();
+            for p in patches {
+                let task = p.task.clone().unwrap();
+                if !r.is_online {
+                    errors.push(PatchError {
+                        task,
+                        error: ProviderError::Network("no connection".to_string()).to_string(),
+                        kind: PatchErrorKind::Network,
+                    });
+                } else if task.as_any().is::<TestTask>() {
+                    r.patched.push(task.id());
+                } else {
+                    errors.push(PatchError {
+                        task,
+                        error: "wrong task type".to_string(),
+                        kind: PatchErrorKind::Other,
+                    });
+                }
+            }
+            errors
+        }
+    }
+
+    #[async_trait]
+    impl ProviderTrait for RemoteProvider {
+        fn name(&self) -> String {
+            "Remote".to_string()
+        }
+
+        fn type_name(&self) -> String {
+            "Remote".to_string()
+        }
+
+        async fn reload(&mut self) {}
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                create_task: true,
+                labels: false,
+                subtasks: false,
+                manual_order: false,
+                assignees: false,
+                queries: false,
+                url: false,
+            }
+        }
+    }
+
+    fn remote(is_online: bool) -> Arc<Mutex<Remote>> {
+        Arc::new(Mutex::new(Remote {
+            is_online,
+            tasks: vec![TestTask {
+                id: "1",
+                name: "Pay rent",
+            }],
+            ..Remote::default()
+        }))
+    }
+
+    fn provider(remote: &Arc<Mutex<Remote>>, dir: &Path, mode: Mode) -> OfflineProvider {
+        OfflineProvider::new(Box::new(RemoteProvider { remote: remote.clone() }), dir, None, mode)
+    }
+
+    fn todo_filter() -> filter::Filter {
+        filter::Filter {
+            states: vec![filter::FilterState::Todo],
+            due: vec![filter::Due::NoDate],
+        }
+    }
+
+    fn names(tasks: &[Box<dyn TaskTrait>]) -> Vec<String> {
+        tasks.iter().map(|t| t.name().raw()).collect()
+    }
+
+    fn rename(t: &dyn TaskTrait, name: &str) -> TaskPatch {
+        TaskPatch {
+            task: Some(Arc::from(t.clone_boxed())),
+            name: ValuePatch::Value(name.to_string()),
+            ..TaskPatch::default()
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn offline_list_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+
+        let r = remote(false);
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        assert!(
+            TaskProviderTrait::list(&mut p, None, &f).await.is_err(),
+            "nothing is cached yet"
+        );
+
+        r.lock().unwrap().is_online = true;
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        ProjectProviderTrait::list(&mut p).await.unwrap();
+
+        // the next start without the network
+        r.lock().unwrap().is_online = false;
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(names(&tasks), ["Pay rent"]);
+        assert_eq!(tasks[0].project().unwrap().name(), "Inbox");
+        assert_eq!(ProjectProviderTrait::list(&mut p).await.unwrap().len(), 1);
+        let tasks = TaskProviderTrait::list(&mut p, Some(Box::new(TestProject)), &todo_filter())
+            .await
+            .unwrap();
+        assert_eq!(
+            names(&tasks),
+            ["Pay rent"],
+            "the narrower list is taken from the wider one"
+        );
+        let f = filter::Filter {
+            states: vec![filter::FilterState::Completed],
+            due: Vec::new(),
+        };
+        assert!(TaskProviderTrait::list(&mut p, None, &f).await.unwrap().is_empty());
+        assert!(p.sync_state().last_sync.is_some());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn show_cached_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+        let r = remote(true);
+        TaskProviderTrait::list(&mut provider(&r, dir.path(), Mode::Network), None, &f)
+            .await
+            .unwrap();
+        r.lock().unwrap().tasks[0].name = "Pay the rent";
+
+        let mut p = provider(&r, dir.path(), Mode::CachedFirst);
+        let calls = r.lock().unwrap().list_calls;
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(names(&tasks), ["Pay rent"]);
+        assert_eq!(
+            r.lock().unwrap().list_calls,
+            calls,
+            "the cached tasks are shown without the network"
+        );
+
+        // the refresh is announced to the subscriber that comes later
+        let mut rx = p.subscribe_on_changes().unwrap();
+        assert!(rx.try_recv().is_ok());
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(names(&tasks), ["Pay the rent"]);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn queued_changes_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+        let r = remote(true);
+        TaskProviderTrait::list(&mut provider(&r, dir.path(), Mode::Network), None, &f)
+            .await
+            .unwrap();
+
+        // the cached task is changed without the network
+        r.lock().unwrap().is_online = false;
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert!(p.update(&[rename(tasks[0].as_ref(), "Pay the rent")]).await.is_empty());
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(names(&tasks), ["Pay the rent"], "the queued change is shown");
+        assert!(r.lock().unwrap().patched.is_empty());
+
+        // the network returns
+        r.lock().unwrap().is_online = true;
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(r.lock().unwrap().patched, ["1"]);
+        assert!(p.snapshot.queue.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn persisted_queue_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+        let r = remote(true);
+        TaskProviderTrait::list(&mut provider(&r, dir.path(), Mode::Network), None, &f)
+            .await
+            .unwrap();
+
+        r.lock().unwrap().is_online = false;
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert!(p.update(&[rename(tasks[0].as_ref(), "Pay the rent")]).await.is_empty());
+        drop(p);
+
+        // the restart, the load is aborted while the queued change is sent
+        r.lock().unwrap().is_online = true;
+        r.lock().unwrap().is_hanging = true;
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), TaskProviderTrait::list(&mut p, None, &f))
+                .await
+                .is_err()
+        );
+        drop(p);
+
+        r.lock().unwrap().is_hanging = false;
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        assert_eq!(p.snapshot.queue.len(), 1);
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(r.lock().unwrap().patched, ["1"]);
+        assert!(provider(&r, dir.path(), Mode::Network).snapshot.queue.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn shared_queue_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+        let r = remote(true);
+        TaskProviderTrait::list(&mut provider(&r, dir.path(), Mode::Network), None, &f)
+            .await
+            .unwrap();
+
+        // the daemon and the TUI queue their changes without the network
+        r.lock().unwrap().is_online = false;
+        let mut daemon = provider(&r, dir.path(), Mode::Network);
+        let mut p = provider(&r, dir.path(), Mode::Shared);
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert!(p.update(&[rename(tasks[0].as_ref(), "Pay the rent")]).await.is_empty());
+        let tasks = TaskProviderTrait::list(&mut daemon, None, &f).await.unwrap();
+        assert!(
+            daemon
+                .update(&[rename(tasks[0].as_ref(), "Pay rent today")])
+                .await
+                .is_empty()
+        );
+        assert_eq!(daemon.snapshot.queue.len(), 2, "the queue of the TUI is kept");
+
+        p.reload().await;
+        assert_eq!(p.snapshot.queue.len(), 2, "the queue of the daemon is read");
+
+        // the daemon sends both changes
+        r.lock().unwrap().is_online = true;
+        TaskProviderTrait::list(&mut daemon, None, &f).await.unwrap();
+        assert_eq!(r.lock().unwrap().patched, ["1", "1"]);
+        p.reload().await;
+        assert!(p.snapshot.queue.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn network_patch_error_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+        let r = remote(true);
+        let mut p = provider(&r, dir.path(), Mode::Network);
+        let tasks = TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+
+        // the connection is lost while the change is sent
+        r.lock().unwrap().is_online = false;
+        assert!(p.update(&[rename(tasks[0].as_ref(), "Pay the rent")]).await.is_empty());
+        assert_eq!(p.snapshot.queue.len(), 1);
+
+        r.lock().unwrap().is_online = true;
+        TaskProviderTrait::list(&mut p, None, &f).await.unwrap();
+        assert_eq!(r.lock().unwrap().patched, ["1"]);
+        assert!(p.snapshot.queue.is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn shared_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let f = filter::Filter::full_filter();
+        let r = remote(true);
+        let mut daemon = provider(&r, dir.path(), Mode::Network);
+        TaskProviderTrait::list(&mut daemon, None, &f).await.unwrap();
+
+        let mut p = provider(&r, dir.path(), Mode::Shared);
+        let mut changes = p.subscribe_on_changes().unwrap();
+        let calls = r.lock().unwrap().list_calls;
+        let tasks = TaskProviderTrait::list(&mut p, None, &todo_filter()).await.unwrap();
+        assert_eq!(names(&tasks), ["Pay rent"]);
+        assert_eq!(r.lock().unwrap().list_calls, calls, "the store of the daemon is read");
+
+        // the daemon loads the task changed by somebody else
+        tokio::time::sleep(WATCH_PERIOD / 2).await;
+        r.lock().unwrap().tasks[0].name = "Pay the rent";
+        TaskProviderTrait::list(&mut daemon, None, &f).await.unwrap();
+        tokio::time::timeout(WATCH_PERIOD * 5, changes.recv())
+            .await
+            .expect("The change of the store hasn't been noticed")
+            .unwrap();
+        p.reload().await;
+        let tasks = TaskProviderTrait::list(&mut p, None, &todo_filter()).await.unwrap();
+        assert_eq!(names(&tasks), ["Pay the rent"]);
+
+        // the stored task is changed through the network, the store is outdated until the tasks are loaded again
+        assert!(
+            p.update(&[rename(tasks[0].as_ref(), "Pay rent today")])
+                .await
+                .is_empty()
+        );
+        TaskProviderTrait::list(&mut p, None, &todo_filter()).await.unwrap();
+        assert_eq!(r.lock().unwrap().patched, ["1"]);
+        let calls = r.lock().unwrap().list_calls;
+        TaskProviderTrait::list(&mut p, None, &todo_filter()).await.unwrap();
+        assert_eq!(r.lock().unwrap().list_calls, calls);
+    }
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    any::Any,
+    cell::Cell,
+    panic::{AssertUnwindSafe, catch_unwind},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tatuin_core::provider::ProviderError;
+
+thread_local! {
+    static IS_GUARDED: Cell<bool> = const { Cell::new(false) };
+}
+
+// The default hook (installed by ratatui) restores the terminal, so the TUI is broken after any panic.
+// The panics caught by the guard are only logged and the application keeps working.
+// The panic can't be caught if the process aborts on it, so the terminal is restored anyway then.
+pub fn install_hook() {
+    let prev = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if cfg!(panic = "unwind") && IS_GUARDED.get() {
+            tracing::error!(target:"panic_guard", panic=%info, "The guarded call panicked");
+        } else {
+            prev(info);
+        }
+    }));
+}
+
+fn panic_message(p: &(dyn Any + Send)) -> String {
+    if let Some(s) = p.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = p.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown reason".to_string()
+    }
+}
+
+struct Guarded<F: Future> {
+    inner: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Guarded<F> {
+    type Output = Result<F::Output, ProviderError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let was_guarded = IS_GUARDED.replace(true);
+        let result = catch_unwind(AssertUnwindSafe(|| self.inner.as_mut().poll(cx)));
+        IS_GUARDED.set(was_guarded);
+
+        match result {
+            Ok(Poll::Ready(v)) => Poll::Ready(Ok(v)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(p) => Poll::Ready(Err(ProviderError::Other(format!(
+                "the provider panicked: {}",
+                panic_message(p.as_ref())
+            )))),
+        }
+    }
+}
+
+// Runs the provider's call and turns its panic into the error
+pub async fn guarded<F: Future>(f: F) -> Result<F::Output, ProviderError> {
+    Guarded { inner: Box::pin(f) }.await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn guarded_test() {
+        assert_eq!(guarded(async { 42 }).await, Ok(42));
+
+        let err = guarded(async {
+            tokio::task::yield_now().await;
+            panic!("Unknown state");
+        })
+        .await;
+        assert_eq!(
+            err,
+            Err::<(), _>(ProviderError::Other("the provider panicked: Unknown state".to_string()))
+        );
+        assert!(!IS_GUARDED.get());
+    }
+}
@@ -0,0 +1,74 @@
+// SPDX-License-Identifier: MIT
+
+use std::{error::Error, io::Write, process::Command};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal,
+};
+use tatuin_core::StringError;
+
+use crate::settings::Encryption;
+
+const PASSPHRASE_ENV: &str = "TATUIN_PASSPHRASE";
+
+// The passphrase is taken from the environment, from the configured command
+// (e.g. `secret-tool lookup tatuin passphrase` to use the system keyring) or from the user.
+pub fn passphrase(cfg: &Encryption, confirm: bool) -> Result<String, Box<dyn Error>> {
+    if let Ok(p) = std::env::var(PASSPHRASE_ENV) {
+        return Ok(p);
+    }
+
+    if let Some(cmd) = &cfg.passphrase_command {
+        let output = Command::new("sh").arg("-c").arg(cmd).output()?;
+        if !output.status.success() {
+            return Err(StringError::new(
+                format!(
+                    "The passphrase command failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+                .as_str(),
+            )
+            .into());
+        }
+        return Ok(String::from_utf8(output.stdout)?
+            .trim_end_matches(['\r', '\n'])
+            .to_string());
+    }
+
+    let p = prompt("Passphrase: ")?;
+    if confirm && p != prompt("Repeat the passphrase: ")? {
+        return Err(StringError::new("The passphrases don't match").into());
+    }
+    Ok(p)
+}
+
+fn prompt(title: &str) -> Result<String, Box<dyn Error>> {
+    print!("{title}");
+    std::io::stdout().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let _guard = scopeguard::guard((), |_| {
+        let _ = terminal::disable_raw_mode();
+        println!();
+    });
+
+    let mut result = String::new();
+    loop {
+        if let Event::Key(k) = event::read()?
+            && k.kind == KeyEventKind::Press
+        {
+            match k.code {
+                KeyCode::Enter => return Ok(result),
+                KeyCode::Backspace => {
+                    result.pop();
+                }
+                KeyCode::Char('c') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Err(StringError::new("The passphrase input has been cancelled").into());
+                }
+                KeyCode::Char(c) => result.push(c),
+                _ => {}
+            }
+        }
+    }
+}
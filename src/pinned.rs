@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MIT
+
+// The pinned tasks are shown above all the others in the order of pinning, whatever provider or project is selected
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::starred::TaskRef;
+
+#[derive(Serialize, Deserialize, Default)]
+struct PinnedFile {
+    #[serde(default)]
+    tasks: Vec<TaskRef>,
+}
+
+#[derive(Default)]
+pub struct PinnedTasks {
+    // None keeps the pins only in memory
+    file_name: Option<PathBuf>,
+    tasks: Vec<TaskRef>,
+}
+
+impl PinnedTasks {
+    pub fn load(file_name: &Path) -> Result<Self, Box<dyn Error>> {
+        let tasks = match std::fs::read_to_string(file_name) {
+            Ok(s) => toml::from_str::<PinnedFile>(&s)?.tasks,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            file_name: Some(file_name.to_path_buf()),
+            tasks,
+        })
+    }
+
+    // The place of the task among the pinned ones, None if it isn't pinned
+    pub fn position(&self, provider: &str, id: &str) -> Option<usize> {
+        let r = TaskRef::new(provider, id);
+        self.tasks.iter().position(|t| *t == r)
+    }
+
+    // Returns whether the task is pinned now, the new pins go to the end
+    pub fn toggle(&mut self, provider: &str, id: &str) -> Result<bool, Box<dyn Error>> {
+        let pinned = match self.position(provider, id) {
+            Some(i) => {
+                self.tasks.remove(i);
+                false
+            }
+            None => {
+                self.tasks.push(TaskRef::new(provider, id));
+                true
+            }
+        };
+        self.save()?;
+        Ok(pinned)
+    }
+
+    // Moves the pinned task over the other pinned one, the pins between them keep their order.
+    // The tasks are given as (provider, id)
+    pub fn move_pin(&mut self, task: (&str, &str), over: (&str, &str)) -> Result<(), Box<dyn Error>> {
+        let (Some(from), Some(to)) = (self.position(task.0, task.1), self.position(over.0, over.1)) else {
+            return Ok(());
+        };
+        let r = self.tasks.remove(from);
+        self.tasks.insert(to, r);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(f) = &self.file_name {
+            let s = toml::to_string(&PinnedFile {
+                tasks: self.tasks.clone(),
+            })?;
+            std::fs::write(f, s)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pin_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_name = dir.path().join("pinned.toml");
+
+        let mut p = PinnedTasks::load(&file_name).unwrap();
+        assert_eq!(p.position("Todoist", "1"), None);
+        assert!(p.toggle("Todoist", "1").unwrap());
+        assert!(p.toggle("Obsidian", "1").unwrap());
+        assert!(p.toggle("Todoist", "2").unwrap());
+        assert_eq!(p.position("Todoist", "2"), Some(2));
+
+        // the pin between them keeps its order relative to the others
+        p.move_pin(("Todoist", "2"), ("Todoist", "1")).unwrap();
+        assert_eq!(p.position("Todoist", "2"), Some(0));
+        assert_eq!(p.position("Todoist", "1"), Some(1));
+        assert_eq!(p.position("Obsidian", "1"), Some(2));
+        p.move_pin(("Todoist", "2"), ("Obsidian", "1")).unwrap();
+        p.move_pin(("Todoist", "2"), ("Todoist", "3")).unwrap();
+
+        let mut p = PinnedTasks::load(&file_name).unwrap();
+        assert_eq!(p.position("Todoist", "1"), Some(0));
+        assert_eq!(p.position("Obsidian", "1"), Some(1));
+        assert_eq!(p.position("Todoist", "2"), Some(2));
+        assert!(!p.toggle("Todoist", "1").unwrap());
+        assert_eq!(p.position("Todoist", "2"), Some(1));
+        assert_eq!(PinnedTasks::load(&file_name).unwrap().position("Todoist", "1"), None);
+    }
+}
@@ -2,7 +2,8 @@
 
 use ratatui::style::Color;
 use tatuin_core::{
-    provider::{Capabilities, ProviderTrait},
+    in_flight::RequestLimit,
+    provider::{Capabilities, ProviderTrait, Query},
     task::Priority,
     types::ArcRwLock,
 };
@@ -11,8 +12,14 @@ use tatuin_core::{
 pub struct Provider {
     pub name: String,
     pub type_name: String,
+    // the section of the Providers block, e.g. Work or Personal
+    pub group: Option<String>,
     pub color: Color,
     pub capabilities: Capabilities,
     pub supported_priorities: Vec<Priority>,
+    // shown as the tabs above the tasks
+    pub queries: Vec<Query>,
     pub provider: ArcRwLock<Box<dyn ProviderTrait>>,
+    // the loads of the tasks, the projects and the queries run under it
+    pub limit: RequestLimit,
 }
@@ -1,13 +1,23 @@
 // SPDX-License-Identifier: MIT
 
+use crate::migration::SETTINGS_VERSION;
 use config::{Config, File, FileFormat};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
-use tatuin_core::state::{State, StateSettings};
+use tatuin_core::{
+    StringError,
+    crypto::{Cipher, is_encrypted_str},
+    filter::Filter,
+    state::{State, StateSettings},
+    tag_alias::TagAliases,
+    task_store::SortOrder,
+};
 
 const DEFAULT_STATE_NAME: &str = "default";
+const SECRET_SECTIONS: [&str; 3] = ["providers", "capture", "notify"];
+const SECRET_KEYS: [&str; 3] = ["api_key", "password", "token"];
 
 #[derive(Serialize, Deserialize)]
 pub struct TaskInfoPanel {
@@ -25,6 +35,250 @@ impl Default for TaskInfoPanel {
 #[derive(Serialize, Deserialize, Default)]
 pub struct Interface {
     pub task_info_panel: TaskInfoPanel,
+    // how the tasks are sorted inside the due groups
+    #[serde(default)]
+    pub sort_order: SortOrder,
+    // the hour the day starts at, the tasks due before it are still shown as the previous day's ones
+    #[serde(default)]
+    pub day_start_hour: u32,
+    #[serde(default)]
+    pub due_display: DueDisplay,
+}
+
+// How the due dates are shown in the tasks list
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DueDisplay {
+    // the date and time, e.g. 2025-01-08 10:30:00
+    #[default]
+    Absolute,
+    // the time left or overdue, e.g. "in 2h 5m" or "3d overdue"
+    Relative,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Audit {
+    pub enabled: bool,
+    pub max_file_size_kb: u64,
+    pub max_files: usize,
+}
+
+impl Default for Audit {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_file_size_kb: 10 * 1024,
+            max_files: 5,
+        }
+    }
+}
+
+// The projects and the tasks are kept in memory for a while, so the repeated calls don't go to the network.
+// The zero time disables the cache.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct MemoryCache {
+    pub projects_ttl_secs: u64,
+    pub tasks_ttl_secs: u64,
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self {
+            projects_ttl_secs: 300,
+            tasks_ttl_secs: 30,
+        }
+    }
+}
+
+// The remote providers keep their last loaded projects and tasks on disk to be shown without the network
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct OfflineCache {
+    pub enabled: bool,
+}
+
+impl Default for OfflineCache {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Confirmations {
+    pub delete_task: bool,
+    pub duplicate_task: bool,
+    pub exit_with_uncommitted_changes: bool,
+    // completing a task with the uncompleted subtasks offers to complete them too
+    pub complete_subtasks: bool,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            delete_task: true,
+            duplicate_task: true,
+            exit_with_uncommitted_changes: true,
+            complete_subtasks: true,
+        }
+    }
+}
+
+// Completing a task with the tag offers to create the follow-up task, e.g. for the delegated and the waiting-for tasks
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct FollowUp {
+    pub tag: Option<String>,
+    pub due_in_days: u64,
+    pub name_prefix: String,
+}
+
+impl Default for FollowUp {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            due_in_days: 3,
+            name_prefix: "Follow up: ".to_string(),
+        }
+    }
+}
+
+// The tag is compared with the aliases merged and case-insensitively
+fn has_tag(labels: &[String], tag: &Option<String>, aliases: &TagAliases) -> bool {
+    tag.as_ref().is_some_and(|tag| {
+        let tag = aliases.canonical(tag);
+        labels.iter().any(|l| aliases.canonical(l).eq_ignore_ascii_case(&tag))
+    })
+}
+
+impl FollowUp {
+    pub fn is_required(&self, labels: &[String], aliases: &TagAliases) -> bool {
+        has_tag(labels, &self.tag, aliases)
+    }
+
+    pub fn name(&self, task_name: &str) -> String {
+        format!("{}{task_name}", self.name_prefix)
+    }
+}
+
+// The tasks delegated to somebody else are marked locally or tagged in the provider.
+// They're reminded every nag interval of the waiting, the zero interval disables the reminders.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Waiting {
+    pub tag: Option<String>,
+    pub nag_interval_days: u32,
+}
+
+impl Default for Waiting {
+    fn default() -> Self {
+        Self {
+            tag: None,
+            nag_interval_days: 3,
+        }
+    }
+}
+
+impl Waiting {
+    pub fn is_tagged(&self, labels: &[String], aliases: &TagAliases) -> bool {
+        has_tag(labels, &self.tag, aliases)
+    }
+}
+
+// The misspelled words are highlighted in the task editors.
+// The hunspell dictionary is looked up by the language or taken from the path.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Spellcheck {
+    pub enabled: bool,
+    pub language: String,
+    pub dictionary: Option<String>,
+    pub words: Vec<String>,
+}
+
+impl Default for Spellcheck {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            language: "en_US".to_string(),
+            dictionary: None,
+            words: Vec::new(),
+        }
+    }
+}
+
+// The sound cues of the events, the cue is "bell" or the path of a sound file
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Sounds {
+    pub muted: bool,
+    pub reminder: Option<String>,
+    pub commit_succeeded: Option<String>,
+    pub commit_failed: Option<String>,
+}
+
+// The provider groups, the filter and the accent color that are switched together, e.g. the work and the personal modes
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Context {
+    // the groups shown in All, the other groups are excluded; the empty list shows all of them
+    pub groups: Vec<String>,
+    pub filter: Option<Filter>,
+    // the background of the active block header, e.g. "blue" or "#5e81ac"
+    pub accent: Option<String>,
+    // the time when the notifier treats the context as active, e.g. "09:00-18:00", the range can pass midnight
+    pub hours: Option<String>,
+    // the week days of the hours like "Mon", every day if it's empty
+    pub days: Vec<String>,
+    // the groups that aren't notified while the context is active
+    pub muted_groups: Vec<String>,
+    // the minutes before the due date when the tasks of the context groups are notified
+    pub lead_time: Option<u64>,
+}
+
+// The context after the current one, the first one follows the last one
+pub fn next_context<'a>(contexts: &'a BTreeMap<String, Context>, current: Option<&str>) -> Option<&'a String> {
+    let mut names = contexts.keys();
+    match current {
+        Some(c) => names
+            .clone()
+            .skip_while(|n| n.as_str() != c)
+            .nth(1)
+            .or_else(|| names.next()),
+        None => names.next(),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Confirmation {
+    DeleteTask,
+    DuplicateTask,
+    ExitWithUncommittedChanges,
+    CompleteSubtasks,
+}
+
+// The confirmations can be switched off by the "Don't ask again" checkbox
+pub trait ConfirmationSettings: Send + Sync {
+    fn is_confirmation_required(&self, c: Confirmation) -> bool;
+    fn set_confirmation_required(&mut self, c: Confirmation, is_required: bool) -> Result<(), Box<dyn Error>>;
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Encryption {
+    #[serde(default)]
+    pub enabled: bool,
+
+    pub passphrase_command: Option<String>,
+}
+
+// The name and the description of the task added by `tatuin add --template <name>`, they can contain the placeholders
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct TaskTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -32,6 +286,12 @@ pub struct Settings {
     #[serde(skip_serializing, skip_deserializing)]
     file_name: String,
 
+    #[serde(skip_serializing, skip_deserializing)]
+    cipher: Option<Cipher>,
+
+    #[serde(default)]
+    version: i64,
+
     pub providers: HashMap<String, HashMap<String, String>>,
 
     #[serde(default)]
@@ -39,8 +299,56 @@ pub struct Settings {
 
     pub theme: Option<String>,
 
+    // The destination of the tasks added without choosing the provider and project
+    pub default_provider: Option<String>,
+    pub default_project: Option<String>,
+
     #[serde(default)]
     pub interface: Interface,
+
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub capture: HashMap<String, HashMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub notify: HashMap<String, HashMap<String, String>>,
+
+    #[serde(default)]
+    pub encryption: Encryption,
+
+    #[serde(default)]
+    pub audit: Audit,
+
+    #[serde(default)]
+    pub memory_cache: MemoryCache,
+
+    #[serde(default)]
+    pub offline_cache: OfflineCache,
+
+    #[serde(default)]
+    pub confirmations: Confirmations,
+
+    #[serde(default)]
+    pub spellcheck: Spellcheck,
+
+    #[serde(default)]
+    pub sounds: Sounds,
+
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, TaskTemplate>,
+
+    #[serde(default)]
+    pub follow_up: FollowUp,
+
+    #[serde(default)]
+    pub waiting: Waiting,
+
+    // the equivalent labels of the providers, e.g. "wip = in-progress = doing", shown as the first one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag_aliases: Vec<String>,
+
+    // sorted by name, it's the order of switching to the next context
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub contexts: BTreeMap<String, Context>,
 }
 
 impl Settings {
@@ -58,6 +366,7 @@ impl Settings {
 
         Self {
             file_name: file_name.to_string(),
+            version: SETTINGS_VERSION,
             ..Settings::default()
         }
     }
@@ -68,8 +377,81 @@ impl Settings {
         self.save_to_file()
     }
 
+    fn secret_sections(&mut self) -> [&mut HashMap<String, HashMap<String, String>>; 3] {
+        [&mut self.providers, &mut self.capture, &mut self.notify]
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.enabled
+            || [&self.providers, &self.capture, &self.notify]
+                .iter()
+                .flat_map(|section| section.values())
+                .flat_map(|cfg| cfg.values())
+                .any(|v| is_encrypted_str(v))
+    }
+
+    // The default provider should be one of the loaded ones that can create tasks
+    pub fn check_default_destination(&self, creatable_providers: &[&str]) -> Result<(), StringError> {
+        match (&self.default_provider, &self.default_project) {
+            (None, None) => Ok(()),
+            (None, Some(_)) => Err(StringError::new("default_project is set without default_provider")),
+            (Some(p), _) if !creatable_providers.contains(&p.as_str()) => Err(StringError::new(
+                format!("default_provider '{p}' isn't loaded or can't create tasks").as_str(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn cipher(&self) -> Option<Cipher> {
+        self.cipher.clone()
+    }
+
+    // Decrypts the secret fields in memory. They are encrypted back on saving.
+    pub fn decrypt(&mut self, cipher: Cipher) -> Result<(), StringError> {
+        for section in self.secret_sections() {
+            for v in section.values_mut().flat_map(|cfg| cfg.values_mut()) {
+                if is_encrypted_str(v) {
+                    *v = cipher.decrypt_str(v)?;
+                }
+            }
+        }
+
+        self.cipher = Some(cipher);
+        Ok(())
+    }
+
+    pub fn set_cipher(&mut self, cipher: Option<Cipher>) -> Result<(), Box<dyn Error>> {
+        self.encryption.enabled = cipher.is_some();
+        self.cipher = cipher;
+
+        self.save_to_file()
+    }
+
+    fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        let mut value = toml::Table::try_from(self)?;
+
+        if let Some(c) = &self.cipher {
+            for section in SECRET_SECTIONS {
+                let configs = value.get_mut(section).and_then(|v| v.as_table_mut());
+                for cfg in configs
+                    .into_iter()
+                    .flat_map(|t| t.iter_mut().map(|(_, v)| v))
+                    .filter_map(|v| v.as_table_mut())
+                {
+                    for key in SECRET_KEYS {
+                        if let Some(toml::Value::String(v)) = cfg.get_mut(key) {
+                            *v = c.encrypt_str(v.as_str())?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(toml::to_string(&value)?)
+    }
+
     fn save_to_file(&self) -> Result<(), Box<dyn Error>> {
-        let s = toml::to_string(self)?;
+        let s = self.to_toml()?;
 
         std::fs::write(&self.file_name, s)?;
 
@@ -110,3 +492,226 @@ impl StateSettings for Settings {
         result
     }
 }
+
+impl Confirmations {
+    fn value_mut(&mut self, c: Confirmation) -> &mut bool {
+        match c {
+            Confirmation::DeleteTask => &mut self.delete_task,
+            Confirmation::DuplicateTask => &mut self.duplicate_task,
+            Confirmation::ExitWithUncommittedChanges => &mut self.exit_with_uncommitted_changes,
+            Confirmation::CompleteSubtasks => &mut self.complete_subtasks,
+        }
+    }
+}
+
+impl ConfirmationSettings for Settings {
+    fn is_confirmation_required(&self, c: Confirmation) -> bool {
+        match c {
+            Confirmation::DeleteTask => self.confirmations.delete_task,
+            Confirmation::DuplicateTask => self.confirmations.duplicate_task,
+            Confirmation::ExitWithUncommittedChanges => self.confirmations.exit_with_uncommitted_changes,
+            Confirmation::CompleteSubtasks => self.confirmations.complete_subtasks,
+        }
+    }
+
+    fn set_confirmation_required(&mut self, c: Confirmation, is_required: bool) -> Result<(), Box<dyn Error>> {
+        *self.confirmations.value_mut(c) = is_required;
+        self.save_to_file()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::filter::{Due, FilterState};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn encrypted_secrets_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let file_name = temp_dir.path().join("settings.toml");
+        let file_name = file_name.to_str().unwrap();
+
+        let mut s = Settings::new(file_name);
+        assert!(!s.is_encrypted());
+        s.add_provider(
+            "todoist",
+            &HashMap::from([
+                ("type".to_string(), "Todoist".to_string()),
+                ("api_key".to_string(), "secret key".to_string()),
+            ]),
+        )
+        .unwrap();
+        s.set_cipher(Some(Cipher::new("passphrase").unwrap())).unwrap();
+
+        let content = std::fs::read_to_string(file_name).unwrap();
+        assert!(content.contains("type = \"Todoist\""));
+        assert!(!content.contains("secret key"));
+
+        let mut s = Settings::new(file_name);
+        assert!(s.is_encrypted());
+        assert!(s.decrypt(Cipher::new("wrong").unwrap()).is_err());
+        s.decrypt(Cipher::new("passphrase").unwrap()).unwrap();
+        assert_eq!(s.providers["todoist"]["api_key"], "secret key");
+
+        s.set_cipher(None).unwrap();
+        assert!(std::fs::read_to_string(file_name).unwrap().contains("secret key"));
+    }
+
+    #[test]
+    fn check_default_destination_test() {
+        struct Case<'a> {
+            name: &'a str,
+            provider: Option<&'a str>,
+            project: Option<&'a str>,
+            is_ok: bool,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no defaults",
+                provider: None,
+                project: None,
+                is_ok: true,
+            },
+            Case {
+                name: "provider only",
+                provider: Some("todoist"),
+                project: None,
+                is_ok: true,
+            },
+            Case {
+                name: "provider and project",
+                provider: Some("todoist"),
+                project: Some("Inbox"),
+                is_ok: true,
+            },
+            Case {
+                name: "project only",
+                provider: None,
+                project: Some("Inbox"),
+                is_ok: false,
+            },
+            Case {
+                name: "unknown provider",
+                provider: Some("obsidian"),
+                project: Some("Inbox"),
+                is_ok: false,
+            },
+        ];
+
+        for c in CASES {
+            let s = Settings {
+                default_provider: c.provider.map(|p| p.to_string()),
+                default_project: c.project.map(|p| p.to_string()),
+                ..Settings::default()
+            };
+            assert_eq!(
+                s.check_default_destination(&["todoist", "markdown"]).is_ok(),
+                c.is_ok,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn contexts_test() {
+        let s = toml::from_str::<Settings>(
+            r#"
+providers = {}
+
+[contexts.Work]
+groups = ["Work"]
+filter = { states = ["Todo", "InProgress"], due = ["Overdue", "Today"] }
+accent = "blue"
+
+[contexts.Personal]
+"#,
+        )
+        .unwrap();
+
+        let work = &s.contexts["Work"];
+        assert_eq!(work.groups, vec!["Work"]);
+        assert_eq!(
+            work.filter,
+            Some(Filter {
+                states: vec![FilterState::Todo, FilterState::InProgress],
+                due: vec![Due::Overdue, Due::Today],
+            })
+        );
+        assert_eq!(work.accent.as_deref(), Some("blue"));
+        assert!(s.contexts["Personal"].groups.is_empty());
+        assert!(s.contexts["Personal"].filter.is_none());
+
+        assert_eq!(next_context(&s.contexts, None).map(String::as_str), Some("Personal"));
+        assert_eq!(
+            next_context(&s.contexts, Some("Personal")).map(String::as_str),
+            Some("Work")
+        );
+        assert_eq!(
+            next_context(&s.contexts, Some("Work")).map(String::as_str),
+            Some("Personal")
+        );
+        assert_eq!(
+            next_context(&s.contexts, Some("Removed")).map(String::as_str),
+            Some("Personal")
+        );
+        assert_eq!(next_context(&BTreeMap::new(), None), None);
+    }
+
+    #[test]
+    fn follow_up_test() {
+        let aliases = TagAliases::parse(&["waiting = waiting-for".to_string()]).unwrap();
+        let labels = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        struct Case<'a> {
+            name: &'a str,
+            tag: Option<&'a str>,
+            labels: Vec<String>,
+            expected: bool,
+        }
+        let cases = [
+            Case {
+                name: "no tag",
+                tag: None,
+                labels: labels(&["followup"]),
+                expected: false,
+            },
+            Case {
+                name: "tag",
+                tag: Some("followup"),
+                labels: labels(&["home", "FollowUp"]),
+                expected: true,
+            },
+            Case {
+                name: "another tag",
+                tag: Some("followup"),
+                labels: labels(&["home"]),
+                expected: false,
+            },
+            Case {
+                name: "alias",
+                tag: Some("waiting"),
+                labels: labels(&["Waiting-For"]),
+                expected: true,
+            },
+        ];
+
+        for c in cases {
+            let f = FollowUp {
+                tag: c.tag.map(str::to_string),
+                ..FollowUp::default()
+            };
+            assert_eq!(
+                f.is_required(&c.labels, &aliases),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+
+        let s = toml::from_str::<Settings>("providers = {}\n[follow_up]\ntag = \"followup\"\n").unwrap();
+        assert_eq!(s.follow_up.due_in_days, 3);
+        assert_eq!(s.follow_up.name("Call Bob"), "Follow up: Call Bob");
+    }
+}
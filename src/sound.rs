@@ -0,0 +1,142 @@
+// SPDX-License-Identifier: MIT
+
+// The sound cues of the events, they're heard when tatuin runs in a background pane.
+// The cue is the terminal bell or a sound file that is played by the first player that works.
+
+use std::{
+    io::Write,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crate::settings::Sounds;
+
+const BELL: &str = "bell";
+
+const PLAYERS: &[(&str, &[&str])] = &[("pw-play", &[]), ("paplay", &[]), ("aplay", &["-q"]), ("afplay", &[])];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cue {
+    Bell,
+    File(PathBuf),
+}
+
+impl Cue {
+    // The empty value switches the cue off
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "" => None,
+            BELL => Some(Self::Bell),
+            path => Some(Self::File(PathBuf::from(path))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Reminder,
+    CommitSucceeded,
+    CommitFailed,
+}
+
+#[derive(Debug, Default)]
+pub struct Cues {
+    reminder: Option<Cue>,
+    commit_succeeded: Option<Cue>,
+    commit_failed: Option<Cue>,
+}
+
+impl Cues {
+    pub fn new(s: &Sounds) -> Self {
+        let cue = |v: &Option<String>| v.as_deref().and_then(Cue::parse);
+        Self {
+            reminder: cue(&s.reminder),
+            commit_succeeded: cue(&s.commit_succeeded),
+            commit_failed: cue(&s.commit_failed),
+        }
+    }
+
+    fn cue(&self, e: Event) -> Option<&Cue> {
+        match e {
+            Event::Reminder => self.reminder.as_ref(),
+            Event::CommitSucceeded => self.commit_succeeded.as_ref(),
+            Event::CommitFailed => self.commit_failed.as_ref(),
+        }
+    }
+
+    pub fn play(&self, e: Event) {
+        if is_muted() {
+            return;
+        }
+        if let Some(c) = self.cue(e) {
+            tracing::debug!(target:"sound", event=?e, cue=?c, "Play the cue");
+            play(c.clone());
+        }
+    }
+}
+
+static MUTED: AtomicBool = AtomicBool::new(false);
+
+pub fn set_muted(muted: bool) {
+    MUTED.store(muted, Ordering::Relaxed);
+}
+
+pub fn is_muted() -> bool {
+    MUTED.load(Ordering::Relaxed)
+}
+
+// Returns whether the cues are muted now
+pub fn toggle_muted() -> bool {
+    !MUTED.fetch_xor(true, Ordering::Relaxed)
+}
+
+fn play(cue: Cue) {
+    match cue {
+        Cue::Bell => {
+            let mut stdout = std::io::stdout();
+            let _ = stdout.write_all(b"\x07");
+            let _ = stdout.flush();
+        }
+        // the file is played in the background, so the interface isn't blocked
+        Cue::File(path) => {
+            tokio::task::spawn_blocking(move || {
+                let played = PLAYERS.iter().any(|(program, args)| {
+                    Command::new(program)
+                        .args(*args)
+                        .arg(&path)
+                        .stdin(Stdio::null())
+                        .stdout(Stdio::null())
+                        .stderr(Stdio::null())
+                        .status()
+                        .is_ok_and(|s| s.success())
+                });
+                if !played {
+                    tracing::warn!(target:"sound", path=?path, "There is no player for the sound file");
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cues_test() {
+        let cues = Cues::new(&Sounds {
+            muted: false,
+            reminder: Some("bell".to_string()),
+            commit_succeeded: Some(" ".to_string()),
+            commit_failed: Some("/usr/share/sounds/error.wav".to_string()),
+        });
+        assert_eq!(cues.cue(Event::Reminder), Some(&Cue::Bell));
+        assert_eq!(cues.cue(Event::CommitSucceeded), None);
+        assert_eq!(
+            cues.cue(Event::CommitFailed),
+            Some(&Cue::File(PathBuf::from("/usr/share/sounds/error.wav")))
+        );
+        assert_eq!(Cues::new(&Sounds::default()).cue(Event::Reminder), None);
+    }
+}
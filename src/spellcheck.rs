@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use tatuin_core::StringError;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::settings::Spellcheck as SpellcheckSettings;
+
+const DICTIONARY_FOLDERS: [&str; 3] = ["/usr/share/hunspell", "/usr/share/myspell", "/usr/share/myspell/dicts"];
+
+// The affix rules of the dictionaries aren't applied, the common endings are tried instead
+const ENDINGS: [&str; 7] = ["'s", "s", "es", "d", "ed", "ing", "ly"];
+
+pub type SharedSpellchecker = Arc<Spellchecker>;
+
+#[derive(Default)]
+pub struct Spellchecker {
+    words: HashSet<String>,
+}
+
+fn find_dictionary(language: &str) -> Option<PathBuf> {
+    DICTIONARY_FOLDERS
+        .iter()
+        .map(|f| Path::new(f).join(format!("{language}.dic")))
+        .find(|p| p.exists())
+}
+
+// The urls, paths, emails, tags and the tokens with digits aren't checked
+fn is_checkable(token: &[char]) -> bool {
+    token.first().is_some_and(|c| *c != '#')
+        && !token
+            .iter()
+            .any(|c| c.is_ascii_digit() || matches!(c, '/' | '@' | ':' | '_' | '\\'))
+}
+
+impl Spellchecker {
+    // Both the hunspell dictionaries (the word count on the first line and the flags after '/')
+    // and the plain word lists with a word per line are supported
+    pub fn from_dictionary(content: &str) -> Self {
+        Self {
+            words: content
+                .lines()
+                .filter_map(|l| l.split('/').next())
+                .map(|w| w.trim())
+                .filter(|w| !w.is_empty() && !w.chars().all(|c| c.is_ascii_digit()))
+                .map(|w| w.to_lowercase())
+                .collect(),
+        }
+    }
+
+    pub fn with_words(mut self, words: &[String]) -> Self {
+        self.words.extend(words.iter().map(|w| w.to_lowercase()));
+        self
+    }
+
+    pub fn load(s: &SpellcheckSettings) -> Result<Self, StringError> {
+        let path = match &s.dictionary {
+            Some(p) => PathBuf::from(p),
+            None => find_dictionary(&s.language).ok_or_else(|| {
+                StringError::new(format!("the dictionary for the language '{}' is not found", s.language).as_str())
+            })?,
+        };
+
+        let content = std::fs::read(&path)
+            .map_err(|e| StringError::new(format!("can't read the dictionary {}: {e}", path.display()).as_str()))?;
+        Ok(Self::from_dictionary(&String::from_utf8_lossy(&content)).with_words(&s.words))
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        let w = word.to_lowercase();
+        self.words.contains(&w)
+            || ENDINGS.iter().any(|e| {
+                w.strip_suffix(e)
+                    .is_some_and(|stem| !stem.is_empty() && self.words.contains(stem))
+            })
+    }
+
+    // Returns the grapheme ranges of the misspelled words
+    pub fn misspelled(&self, text: &str) -> Vec<(usize, usize)> {
+        let graphemes = text.graphemes(true).collect::<Vec<&str>>();
+        // the graphemes are classified by their first characters
+        let chars = graphemes
+            .iter()
+            .map(|g| g.chars().next().unwrap_or_default())
+            .collect::<Vec<char>>();
+        let mut result = Vec::new();
+
+        let mut token_start = 0;
+        for token in chars.split(|c| c.is_whitespace()) {
+            if is_checkable(token) {
+                let mut i = 0;
+                while i < token.len() {
+                    if !token[i].is_alphabetic() {
+                        i += 1;
+                        continue;
+                    }
+
+                    let start = i;
+                    while i < token.len()
+                        && (token[i].is_alphabetic()
+                            || (token[i] == '\'' && token.get(i + 1).is_some_and(|c| c.is_alphabetic())))
+                    {
+                        i += 1;
+                    }
+
+                    let word = graphemes[token_start + start..token_start + i].concat();
+                    if i - start > 1 && !self.is_correct(&word) {
+                        result.push((token_start + start, token_start + i));
+                    }
+                }
+            }
+            token_start += token.len() + 1;
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DICTIONARY: &str = "5\nbuy/S\nmilk/SM\ncreate/DSG\ndon't\nbox\n";
+
+    #[test]
+    fn is_correct_test() {
+        let sc = Spellchecker::from_dictionary(DICTIONARY).with_words(&["Tatuin".to_string()]);
+
+        struct Case<'a> {
+            name: &'a str,
+            word: &'a str,
+            expected: bool,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "dictionary word",
+                word: "milk",
+                expected: true,
+            },
+            Case {
+                name: "capitalized",
+                word: "Milk",
+                expected: true,
+            },
+            Case {
+                name: "with an apostrophe",
+                word: "don't",
+                expected: true,
+            },
+            Case {
+                name: "common ending",
+                word: "boxes",
+                expected: true,
+            },
+            Case {
+                name: "past tense",
+                word: "created",
+                expected: true,
+            },
+            Case {
+                name: "user word",
+                word: "tatuin",
+                expected: true,
+            },
+            Case {
+                name: "misspelled",
+                word: "mlik",
+                expected: false,
+            },
+            Case {
+                name: "the word count isn't a word",
+                word: "5",
+                expected: false,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(sc.is_correct(c.word), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn misspelled_test() {
+        let sc = Spellchecker::from_dictionary(DICTIONARY);
+        assert_eq!(sc.misspelled("buy mlik"), vec![(4, 8)]);
+        assert_eq!(sc.misspelled("Buy milk, brad!"), vec![(10, 14)]);
+        assert_eq!(sc.misspelled("купить milk"), vec![(0, 6)]);
+        assert_eq!(sc.misspelled("cafe\u{301} mlik"), vec![(0, 4), (5, 9)]);
+        assert!(sc.misspelled("buy 2x https://exmpl.com #grcry a").is_empty());
+        assert!(sc.misspelled("").is_empty());
+    }
+
+    #[test]
+    fn load_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let p = temp_dir.path().join("en_TEST.dic");
+        std::fs::write(&p, DICTIONARY).unwrap();
+
+        let s = SpellcheckSettings {
+            dictionary: Some(p.to_str().unwrap().to_string()),
+            words: vec!["todoist".to_string()],
+            ..SpellcheckSettings::default()
+        };
+        let sc = Spellchecker::load(&s).unwrap();
+        assert!(sc.is_correct("buy") && sc.is_correct("Todoist"));
+
+        let s = SpellcheckSettings {
+            language: "xx_XX".to_string(),
+            ..SpellcheckSettings::default()
+        };
+        assert!(Spellchecker::load(&s).is_err());
+    }
+}
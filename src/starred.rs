@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+
+// The starred tasks are kept locally, so the tasks of any provider can be starred
+
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+// The task of any provider, the pinned tasks are kept the same way
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct TaskRef {
+    provider: String,
+    id: String,
+}
+
+impl TaskRef {
+    pub fn new(provider: &str, id: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+            id: id.to_string(),
+        }
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.provider
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct StarredFile {
+    #[serde(default)]
+    tasks: BTreeSet<TaskRef>,
+}
+
+#[derive(Default)]
+pub struct StarredTasks {
+    // None keeps the stars only in memory
+    file_name: Option<PathBuf>,
+    tasks: BTreeSet<TaskRef>,
+}
+
+impl StarredTasks {
+    pub fn load(file_name: &Path) -> Result<Self, Box<dyn Error>> {
+        let tasks = match std::fs::read_to_string(file_name) {
+            Ok(s) => toml::from_str::<StarredFile>(&s)?.tasks,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            file_name: Some(file_name.to_path_buf()),
+            tasks,
+        })
+    }
+
+    pub fn is_starred(&self, provider: &str, id: &str) -> bool {
+        self.tasks.contains(&TaskRef::new(provider, id))
+    }
+
+    // Returns whether the task is starred now
+    pub fn toggle(&mut self, provider: &str, id: &str) -> Result<bool, Box<dyn Error>> {
+        let r = TaskRef::new(provider, id);
+        let starred = !self.tasks.remove(&r);
+        if starred {
+            self.tasks.insert(r);
+        }
+        self.save()?;
+        Ok(starred)
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(f) = &self.file_name {
+            let s = toml::to_string(&StarredFile {
+                tasks: self.tasks.clone(),
+            })?;
+            std::fs::write(f, s)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn toggle_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_name = dir.path().join("starred.toml");
+
+        let mut s = StarredTasks::load(&file_name).unwrap();
+        assert!(!s.is_starred("Todoist", "1"));
+        assert!(s.toggle("Todoist", "1").unwrap());
+        assert!(s.toggle("Obsidian", "1").unwrap());
+        assert!(s.is_starred("Todoist", "1"));
+        assert!(!s.is_starred("Todoist", "2"));
+
+        let mut s = StarredTasks::load(&file_name).unwrap();
+        assert!(s.is_starred("Todoist", "1"));
+        assert!(!s.toggle("Todoist", "1").unwrap());
+        assert!(!s.is_starred("Todoist", "1"));
+        assert!(StarredTasks::load(&file_name).unwrap().is_starred("Obsidian", "1"));
+    }
+}
@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: MIT
+
+use std::{collections::BTreeMap, error::Error, path::Path};
+
+use serde::{Deserialize, Serialize};
+use tatuin_core::state::{State, StateSettings};
+
+use crate::cli::CliError;
+
+// The shareable file with the saved states
+#[derive(Serialize, Deserialize, Default)]
+struct Snapshot {
+    states: BTreeMap<String, State>,
+}
+
+fn to_snapshot(s: &dyn StateSettings, names: &[String]) -> Result<Snapshot, CliError> {
+    let existing = s.states();
+    let names = if names.is_empty() { &existing } else { names };
+
+    let mut snapshot = Snapshot::default();
+    for name in names {
+        if !existing.contains(name) {
+            return Err(CliError::no_match(format!("The state {name} is not found").as_str()));
+        }
+        snapshot.states.insert(name.clone(), s.load(Some(name)));
+    }
+    Ok(snapshot)
+}
+
+// Returns the names of the imported states
+fn apply_snapshot(
+    s: &mut dyn StateSettings,
+    snapshot: Snapshot,
+    names: &[String],
+    overwrite: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let existing = s.states();
+    for name in names {
+        if !snapshot.states.contains_key(name) {
+            return Err(CliError::no_match(format!("The state {name} is not found in the file").as_str()).into());
+        }
+    }
+
+    let mut imported = Vec::new();
+    for (name, state) in snapshot.states {
+        if !names.is_empty() && !names.contains(&name) {
+            continue;
+        }
+        if existing.contains(&name) && !overwrite {
+            crate::status!("The state {name} already exists, skip it");
+            continue;
+        }
+        s.save(Some(&name), state)?;
+        imported.push(name);
+    }
+    Ok(imported)
+}
+
+// Exports the states (all of them if names are empty) into the TOML file
+pub fn export(s: &dyn StateSettings, file_name: &Path, names: &[String]) -> Result<(), Box<dyn Error>> {
+    let snapshot = to_snapshot(s, names)?;
+    std::fs::write(file_name, toml::to_string(&snapshot)?)?;
+    crate::status!(
+        "The states {} have been exported to {file_name:?}",
+        snapshot.states.keys().cloned().collect::<Vec<String>>().join(", ")
+    );
+    Ok(())
+}
+
+// Imports the states (all of them if names are empty) from the TOML file.
+// The existing states are kept unless overwrite is set.
+pub fn import(
+    s: &mut dyn StateSettings,
+    file_name: &Path,
+    names: &[String],
+    overwrite: bool,
+) -> Result<(), Box<dyn Error>> {
+    let snapshot = toml::from_str::<Snapshot>(&std::fs::read_to_string(file_name)?)?;
+    let imported = apply_snapshot(s, snapshot, names, overwrite)?;
+    if imported.is_empty() {
+        crate::status!("No state has been imported");
+    } else {
+        crate::status!("The states {} have been imported", imported.join(", "));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct Settings {
+        states: HashMap<String, State>,
+    }
+
+    impl StateSettings for Settings {
+        fn load(&self, name: Option<&str>) -> State {
+            self.states.get(name.unwrap()).cloned().unwrap_or_default()
+        }
+
+        fn save(&mut self, name: Option<&str>, state: State) -> Result<(), Box<dyn Error>> {
+            self.states.insert(name.unwrap().to_string(), state);
+            Ok(())
+        }
+
+        fn remove(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+            self.states.remove(name);
+            Ok(())
+        }
+
+        fn states(&self) -> Vec<String> {
+            let mut result: Vec<String> = self.states.keys().cloned().collect();
+            result.sort();
+            result
+        }
+    }
+
+    fn state(value: &str) -> State {
+        State::from([("selected".to_string(), value.to_string())])
+    }
+
+    fn settings(names: &[(&str, &str)]) -> Settings {
+        Settings {
+            states: names.iter().map(|(n, v)| (n.to_string(), state(v))).collect(),
+        }
+    }
+
+    fn selected(s: &Settings, name: &str) -> Option<String> {
+        s.states.get(name).and_then(|st| st.get("selected").cloned())
+    }
+
+    #[test]
+    fn to_snapshot_test() {
+        let s = settings(&[("default", "home"), ("work", "office")]);
+
+        let snapshot = to_snapshot(&s, &[]).unwrap();
+        assert_eq!(snapshot.states.keys().collect::<Vec<_>>(), vec!["default", "work"]);
+
+        let snapshot = to_snapshot(&s, &["work".to_string()]).unwrap();
+        assert_eq!(snapshot.states.keys().collect::<Vec<_>>(), vec!["work"]);
+
+        assert!(to_snapshot(&s, &["absent".to_string()]).is_err());
+    }
+
+    #[test]
+    fn apply_snapshot_test() {
+        struct Case<'a> {
+            name: &'a str,
+            names: &'a [&'a str],
+            overwrite: bool,
+            expected_imported: &'a [&'a str],
+            expected_work: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "keep existing",
+                names: &[],
+                overwrite: false,
+                expected_imported: &["default"],
+                expected_work: "office",
+            },
+            Case {
+                name: "overwrite existing",
+                names: &[],
+                overwrite: true,
+                expected_imported: &["default", "work"],
+                expected_work: "laptop",
+            },
+            Case {
+                name: "selected only",
+                names: &["work"],
+                overwrite: true,
+                expected_imported: &["work"],
+                expected_work: "laptop",
+            },
+        ];
+
+        for c in CASES {
+            let mut s = settings(&[("work", "office")]);
+            let snapshot = to_snapshot(&settings(&[("default", "home"), ("work", "laptop")]), &[]).unwrap();
+            let names = c.names.iter().map(|n| n.to_string()).collect::<Vec<String>>();
+            let imported = apply_snapshot(&mut s, snapshot, &names, c.overwrite).unwrap();
+            assert_eq!(imported, c.expected_imported, "Test '{}' was failed", c.name);
+            assert_eq!(
+                selected(&s, "work").as_deref(),
+                Some(c.expected_work),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn export_import_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let file_name = temp_dir.path().join("states.toml");
+
+        export(&settings(&[("default", "home"), ("work", "office")]), &file_name, &[]).unwrap();
+
+        let mut s = Settings::default();
+        import(&mut s, &file_name, &[], false).unwrap();
+        assert_eq!(selected(&s, "default").as_deref(), Some("home"));
+        assert_eq!(selected(&s, "work").as_deref(), Some("office"));
+
+        assert!(import(&mut s, &file_name, &["absent".to_string()], false).is_err());
+    }
+}
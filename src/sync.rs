@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: MIT
+
+// Refreshing the data of the providers without the TUI, e.g. by cron or a systemd timer,
+// so the interactive session starts with the warm caches
+
+use std::error::Error;
+
+use color_eyre::owo_colors::OwoColorize;
+use tatuin_core::{
+    filter::{Due, Filter, FilterState},
+    provider::{ProjectProviderTrait, ProviderError, TaskProviderTrait},
+};
+
+use crate::{cli::CliError, provider::Provider};
+
+// Returns the number of the loaded uncompleted tasks
+async fn sync_provider(p: &Provider, f: &Filter, full: bool) -> Result<usize, ProviderError> {
+    let mut provider = p.provider.write().await;
+    if full {
+        provider.clear_cache().await?;
+    }
+    ProjectProviderTrait::list(provider.as_mut()).await?;
+    Ok(TaskProviderTrait::list(provider.as_mut(), None, f).await?.len())
+}
+
+pub async fn sync(providers: &[Provider], provider_name: Option<&str>, full: bool) -> Result<(), Box<dyn Error>> {
+    let f = Filter {
+        states: vec![FilterState::Todo, FilterState::InProgress],
+        due: Due::values(),
+    };
+
+    let providers = providers
+        .iter()
+        .filter(|p| provider_name.is_none_or(|n| n == p.name))
+        .collect::<Vec<&Provider>>();
+    if let Some(n) = provider_name
+        && providers.is_empty()
+    {
+        return Err(CliError::no_match(format!("Provider '{n}' is not found").as_str()).into());
+    }
+
+    let mut failed = Vec::new();
+    for p in providers {
+        match sync_provider(p, &f, full).await {
+            Ok(count) => crate::status!("{}: {count} tasks have been synchronized", p.name.purple()),
+            Err(e) => {
+                tracing::error!(provider=p.name, error=?e, "Synchronize the provider");
+                eprintln!("{}: sync error: {e}", p.name.purple());
+                failed.push(p.name.clone());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(CliError::provider(
+            format!("The providers {} haven't been synchronized", failed.join(", ")).as_str(),
+        )
+        .into());
+    }
+    Ok(())
+}
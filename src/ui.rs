@@ -1,22 +1,32 @@
 // SPDX-License-Identifier: MIT
 
+mod command_palette;
 mod widgets;
 use crate::async_jobs::AsyncJobStorage;
-use crate::settings::Settings;
+use crate::dependencies::Dependencies;
+use crate::ipc;
+use crate::pinned::PinnedTasks;
+use crate::settings::{self, Confirmation, ConfirmationSettings, Context, DueDisplay, FollowUp, Settings, Waiting};
+use crate::sound::{self, Cues};
+use crate::spellcheck::SharedSpellchecker;
+use crate::starred::StarredTasks;
 use crate::ui::dialogs::{ConfirmationDialog, ConfirmationDialogIcon, StandardButton};
 use crate::ui::draw_helper::CursorStyle;
+use crate::waiting::WaitingTasks;
 
 use super::provider::Provider;
 use super::ui::{
-    dialogs::{DialogTrait, KeyBindingsHelpDialog, StatesDialog, TextInputDialog},
+    dialogs::{
+        DialogTrait, KeyBindingsHelpDialog, ListDialog, StatesDialog, SyncStateAction, SyncStateDialog, TextInputDialog,
+    },
+    providers_widget::ProvidersWidget,
+    tasks_widget::ProvidersStorage,
     widgets::{WidgetStateTrait, WidgetTrait},
 };
 use async_trait::async_trait;
 use color_eyre::Result;
-use crossterm::event::{
-    DisableMouseCapture, EnableMouseCapture, Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
-    KeyboardEnhancementFlags, MouseEvent, PushKeyboardEnhancementFlags,
-};
+use command_palette::PaletteCommand;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseEvent};
 use ratatui::{
     DefaultTerminal,
     buffer::Buffer,
@@ -28,35 +38,43 @@ use ratatui::{
 use regex::Regex;
 use shortcut::{AcceptResult, Shortcut};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     hash::Hash,
     io::{Write, stdout},
-    slice::{Iter, IterMut},
+    path::PathBuf,
     str::FromStr,
     sync::Arc,
     time::Duration,
 };
-use tasks_widget::ErrorLoggerTrait;
 use tatuin_core::{
+    event_bus::{AppEvent, EventBus},
     filter, project,
     state::{State, StateSettings, StatefulObject, state_from_str},
+    tag_alias::TagAliases,
+    task_store::SortOrder,
     types::ArcRwLock,
 };
-use tokio::sync::{OnceCell, RwLock, mpsc};
+use tokio::sync::{OnceCell, RwLock, broadcast, mpsc};
 mod dialogs;
 mod filter_widget;
+pub mod fuzzy;
 mod header;
 mod key_buffer;
+mod keyboard_handler;
 mod list;
 mod mouse_handler;
+mod providers_widget;
 mod selectable_list;
 mod shortcut;
 pub mod style;
 mod task_info_widget;
 mod tasks_widget;
-use crossterm::execute;
-mod keyboard_handler;
+pub mod terminal;
+mod text_layout;
+mod which_key;
 use widgets::HyperlinkWidget;
+// the rows are built on every reload, the benches build them too
+pub use widgets::{Marks, TaskRow};
 mod draw_helper;
 mod order_changer;
 use mouse_handler::MouseHandler;
@@ -97,6 +115,17 @@ trait AppBlockWidget: WidgetTrait {
     async fn select_last(&mut self);
 }
 
+enum TextInput {
+    StateName,
+    ShellCommand,
+}
+
+#[derive(Debug)]
+enum TerminalAction {
+    Suspend,
+    Shell(String),
+}
+
 #[derive(Default)]
 struct SetCursorPosCmd {
     pos: Option<Position>,
@@ -159,9 +188,7 @@ impl ErrorLogger {
     fn clear(&mut self) {
         self.errors.clear();
     }
-}
 
-impl ErrorLoggerTrait for ErrorLogger {
     fn add_error(&mut self, message: &str) {
         self.errors.push(message.to_string())
     }
@@ -169,7 +196,11 @@ impl ErrorLoggerTrait for ErrorLogger {
 
 pub struct App {
     should_exit: bool,
-    providers: ArcRwLock<SelectableList<Provider>>,
+    // the name of the restored state, None is the default one
+    current_state: Option<String>,
+    contexts: BTreeMap<String, Context>,
+    current_context: Option<String>,
+    providers: ArcRwLock<ProvidersWidget>,
     projects: ArcRwLock<SelectableList<Box<dyn project::Project>>>,
     async_jobs: ArcRwLock<SelectableList<String>>,
     current_block: AppBlock,
@@ -182,7 +213,8 @@ pub struct App {
     home_link: HyperlinkWidget,
     tg_link: HyperlinkWidget,
 
-    error_logger: ArcRwLock<ErrorLogger>,
+    error_logger: ErrorLogger,
+    events: EventBus,
     app_blocks: HashMap<AppBlock, ArcRwLock<dyn AppBlockWidget>>,
     stateful_widgets: HashMap<AppBlock, ArcRwLock<dyn StatefulObject>>,
     key_buffer: key_buffer::KeyBuffer,
@@ -192,53 +224,64 @@ pub struct App {
     load_state_shortcut: Shortcut,
     save_state_shortcut: Shortcut,
     show_keybindings_help_shortcut: Shortcut,
+    show_sync_state_shortcut: Shortcut,
+    shell_command_shortcut: Shortcut,
+    mute_shortcut: Shortcut,
+    choose_context_shortcut: Shortcut,
+    next_context_shortcut: Shortcut,
+    // it's a shortcut of the Projects block, the clone is kept to subscribe to it
+    add_task_here_shortcut: Shortcut,
+    // the shortcuts of the Providers block
+    collapse_group_shortcut: Shortcut,
+    exclude_group_shortcut: Shortcut,
 
     all_shortcuts: Vec<Arc<std::sync::RwLock<shortcut::SharedData>>>,
+    shortcut_conflicts: Vec<String>,
 
     dialog: Option<Box<dyn DialogTrait>>,
+    // what the text of the input dialog is used for
+    text_input: TextInput,
+    // it's run in the main loop because the terminal is left for it
+    terminal_action: Option<TerminalAction>,
 
     settings: ArcRwLock<Settings>,
+    cues: Cues,
     set_cursor_pos_cmd: SetCursorPosCmd,
-}
-
-impl tasks_widget::ProvidersStorage for SelectableList<Provider> {
-    fn iter_mut<'a>(&'a mut self) -> IterMut<'a, Provider> {
-        self.iter_mut()
-    }
-    fn iter<'a>(&'a self) -> Iter<'a, Provider> {
-        self.iter()
-    }
-    fn provider(&self, name: &str) -> Provider {
-        self.iter()
-            .find(|p| p.name == name)
-            .unwrap_or_else(|| panic!("Provider with name='{name}' not found"))
-            .clone()
-    }
+    // the socket the subcommands send their changes through while the app is running
+    command_socket: Option<PathBuf>,
+    command_server: Option<ipc::Server>,
 }
 
 #[async_trait]
 impl tasks_widget::TaskInfoViewerTrait for task_info_widget::TaskInfoWidget {
-    async fn set_task(&mut self, task: Option<Box<dyn crate::task::Task>>) {
+    async fn set_task(&mut self, task: Option<std::sync::Arc<dyn tatuin_core::task::Task>>) {
         self.set_task(task).await;
     }
 }
 
 impl App {
     pub async fn new(providers: Vec<Provider>, settings: Settings) -> Self {
+        let collapse_group_shortcut = Shortcut::new("Collapse or expand the provider group", &['o']);
+        let exclude_group_shortcut = Shortcut::new("Include or exclude the provider group from All", &['x']);
         let providers_widget = Arc::new(RwLock::new(
-            SelectableList::new(providers, Some(0))
-                .add_all_item()
-                .shortcut(Shortcut::new("Activate Providers block", &['g', 'v'])),
+            ProvidersWidget::new(providers, Shortcut::new("Activate Providers block", &['g', 'v']))
+                .block_shortcut(collapse_group_shortcut.clone())
+                .block_shortcut(exclude_group_shortcut.clone()),
         ));
-        let error_logger = Arc::new(RwLock::new(ErrorLogger::new()));
+        let events = EventBus::default();
         let task_info_widget = Arc::new(RwLock::new(task_info_widget::TaskInfoWidget::new(
             task_info_widget::Config {
                 description_line_count: settings.interface.task_info_panel.description_line_count,
             },
         )));
         let async_jobs_storage = Arc::new(RwLock::new(AsyncJobStorage::default()));
+        let settings = Arc::new(RwLock::new(settings));
+        let add_task_here_shortcut = Shortcut::new("Create a task in the selected project", &['n']);
         let mut s = Self {
             should_exit: false,
+            current_state: None,
+            contexts: BTreeMap::new(),
+            current_context: None,
             current_block: AppBlock::TaskList,
             draw_helper: None,
             async_jobs_storage: async_jobs_storage.clone(),
@@ -246,7 +289,8 @@ impl App {
             projects: Arc::new(RwLock::new(
                 SelectableList::default()
                     .add_all_item()
-                    .shortcut(Shortcut::new("Activate Projects block", &['g', 'p'])),
+                    .shortcut(Shortcut::new("Activate Projects block", &['g', 'p']))
+                    .block_shortcut(add_task_here_shortcut.clone()),
             )),
             async_jobs: Arc::new(RwLock::new(SelectableList::new(Vec::new(), None))),
             filter_widget: filter_widget::FilterWidget::new(filter::Filter {
@@ -255,15 +299,17 @@ impl App {
             }),
             tasks_widget: tasks_widget::TasksWidget::new(
                 providers_widget.clone(),
-                error_logger.clone(),
+                events.clone(),
                 task_info_widget.clone(),
                 async_jobs_storage.clone(),
+                settings.clone(),
             )
             .await,
             task_info_widget,
             home_link: HyperlinkWidget::new("[Homepage]", "https://github.com/panter-dsd/tatuin"),
             tg_link: HyperlinkWidget::new("[Telegram]", "https://t.me/tatuin_project"),
-            error_logger: error_logger.clone(),
+            error_logger: ErrorLogger::new(),
+            events,
             app_blocks: HashMap::new(),
             stateful_widgets: HashMap::new(),
             key_buffer: key_buffer::KeyBuffer::default(),
@@ -274,10 +320,26 @@ impl App {
             load_state_shortcut: Shortcut::new("Load state", &['s', 'l']).global(),
             save_state_shortcut: Shortcut::new("Save the current state", &['s', 's']).global(),
             show_keybindings_help_shortcut: Shortcut::new("Show help", &['?']).global().with_short_name("Help"),
+            add_task_here_shortcut,
+            collapse_group_shortcut,
+            exclude_group_shortcut,
+            show_sync_state_shortcut: Shortcut::new("Show sync state of the selected provider", &['s', 'i']).global(),
+            shell_command_shortcut: Shortcut::new("Run a shell command", &['!']).global(),
+            mute_shortcut: Shortcut::new("Mute or unmute the sound cues", &['s', 'm']).global(),
+            choose_context_shortcut: Shortcut::new("Choose the context", &['s', 'c']).global(),
+            next_context_shortcut: Shortcut::new("Switch to the next context", &[')'])
+                .global()
+                .with_short_name("Context"),
             all_shortcuts: Vec::new(),
+            shortcut_conflicts: Vec::new(),
             dialog: None,
-            settings: Arc::new(RwLock::new(settings)),
+            text_input: TextInput::StateName,
+            terminal_action: None,
+            settings,
+            cues: Cues::default(),
             set_cursor_pos_cmd: SetCursorPosCmd::default(),
+            command_socket: None,
+            command_server: None,
         };
 
         s.app_blocks.insert(AppBlock::Providers, s.providers.clone());
@@ -291,17 +353,100 @@ impl App {
         s.all_shortcuts.push(s.load_state_shortcut.internal_data());
         s.all_shortcuts.push(s.save_state_shortcut.internal_data());
         s.all_shortcuts.push(s.show_keybindings_help_shortcut.internal_data());
+        s.all_shortcuts.push(s.show_sync_state_shortcut.internal_data());
+        s.all_shortcuts.push(s.shell_command_shortcut.internal_data());
+        s.all_shortcuts.push(s.mute_shortcut.internal_data());
+        s.all_shortcuts.push(s.choose_context_shortcut.internal_data());
+        s.all_shortcuts.push(s.next_context_shortcut.internal_data());
 
         s.stateful_widgets.insert(AppBlock::Providers, s.providers.clone());
         s.stateful_widgets.insert(AppBlock::Projects, s.projects.clone());
         s.stateful_widgets.insert(AppBlock::Filter, s.filter_widget.clone());
+        s.stateful_widgets.insert(AppBlock::TaskList, s.tasks_widget.clone());
 
         s
     }
 
+    // The provider and project preselected in the dialog of the task creation
+    pub async fn default_destination(self, provider: Option<String>, project: Option<String>) -> Self {
+        self.tasks_widget
+            .write()
+            .await
+            .set_default_destination(provider, project);
+        self
+    }
+
+    pub async fn spellchecker(self, sc: Option<SharedSpellchecker>) -> Self {
+        self.tasks_widget.write().await.set_spellchecker(sc);
+        self
+    }
+
+    pub async fn sort_order(self, order: SortOrder) -> Self {
+        self.tasks_widget.write().await.set_sort_order(order);
+        self
+    }
+
+    pub async fn tag_aliases(self, aliases: TagAliases) -> Self {
+        self.tasks_widget.write().await.set_tag_aliases(aliases);
+        self
+    }
+
+    pub async fn follow_up(self, follow_up: FollowUp) -> Self {
+        self.tasks_widget.write().await.set_follow_up(follow_up);
+        self
+    }
+
+    pub async fn starred_tasks(self, starred: StarredTasks) -> Self {
+        self.tasks_widget.write().await.set_starred_tasks(starred);
+        self
+    }
+
+    pub fn due_display(self, d: DueDisplay) -> Self {
+        widgets::set_due_display(d);
+        self
+    }
+
+    pub fn sound_cues(mut self, cues: Cues) -> Self {
+        self.cues = cues;
+        self
+    }
+
+    pub fn contexts(mut self, contexts: BTreeMap<String, Context>) -> Self {
+        self.contexts = contexts;
+        self
+    }
+
+    pub fn command_socket(mut self, path: PathBuf) -> Self {
+        self.command_socket = Some(path);
+        self
+    }
+
+    // The task found by the open subcommand, it's selected when it's loaded
+    pub async fn open_task(mut self, task: Option<(String, String)>) -> Self {
+        if let Some((provider, task_id)) = task {
+            self.tasks_widget.write().await.open_task(provider, task_id).await;
+            self.current_block = AppBlock::TaskList;
+        }
+        self
+    }
+
+    pub async fn waiting(self, waiting: WaitingTasks, settings: Waiting) -> Self {
+        self.tasks_widget.write().await.set_waiting(waiting, settings);
+        self
+    }
+
+    pub async fn dependencies(self, dependencies: Dependencies) -> Self {
+        self.tasks_widget.write().await.set_dependencies(dependencies);
+        self
+    }
+
+    pub async fn pinned_tasks(self, pinned: PinnedTasks) -> Self {
+        self.tasks_widget.write().await.set_pinned_tasks(pinned);
+        self
+    }
+
     pub async fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        execute!(std::io::stdout(), EnableMouseCapture)?;
-        self.enable_advanced_terminal_flags();
+        terminal::enable();
 
         for b in self.app_blocks.values_mut() {
             let mut b = b.write().await;
@@ -314,6 +459,18 @@ impl App {
                 .extend(b.shortcuts().iter().map(|s| s.internal_data()));
         }
 
+        self.shortcut_conflicts = self.find_shortcut_conflicts().await;
+        if !self.shortcut_conflicts.is_empty() {
+            tracing::warn!(target:"app", conflicts=?self.shortcut_conflicts, "Shortcut conflicts");
+            self.error_logger.add_error(
+                format!(
+                    "Some shortcuts conflict with each other:\n{}",
+                    self.shortcut_conflicts.join("\n")
+                )
+                .as_str(),
+            );
+        }
+
         if self.settings.read().await.states().is_empty() {
             // If there is no states, save the original as default
             self.save_state(None).await;
@@ -346,7 +503,18 @@ impl App {
         let mut load_state_accepted = self.load_state_shortcut.subscribe_to_accepted();
         let mut save_state_accepted = self.save_state_shortcut.subscribe_to_accepted();
         let mut show_keybindings_help_shortcut_accepted = self.show_keybindings_help_shortcut.subscribe_to_accepted();
-        let mut on_tasks_changed = self.tasks_widget.read().await.subscribe_on_changes();
+        let mut show_sync_state_accepted = self.show_sync_state_shortcut.subscribe_to_accepted();
+        let mut add_task_here_accepted = self.add_task_here_shortcut.subscribe_to_accepted();
+        let mut collapse_group_accepted = self.collapse_group_shortcut.subscribe_to_accepted();
+        let mut exclude_group_accepted = self.exclude_group_shortcut.subscribe_to_accepted();
+        let mut shell_command_accepted = self.shell_command_shortcut.subscribe_to_accepted();
+        let mut mute_accepted = self.mute_shortcut.subscribe_to_accepted();
+        let mut choose_context_accepted = self.choose_context_shortcut.subscribe_to_accepted();
+        let mut next_context_accepted = self.next_context_shortcut.subscribe_to_accepted();
+        let mut job_control = terminal::JobControlSignals::new()?;
+        let mut events_rx = self.events.subscribe();
+        self.watch_providers().await;
+        self.serve_commands().await;
         let mut on_jobs_changed = self.async_jobs_storage.read().await.subscribe_on_changes();
 
         let mut screen_size = dh.read().await.screen_size();
@@ -370,7 +538,11 @@ impl App {
 
             tokio::select! {
                 _ = redraw_rx.recv() => {},
-                _ = redraw_interval.tick() => {},
+                _ = redraw_interval.tick() => {
+                    if self.tasks_widget.write().await.refresh_due_times().await > 0 {
+                        self.cues.play(sound::Event::Reminder);
+                    }
+                },
                 Some(cmd) = set_cursor_pos_rx.recv() => {
                     self.set_cursor_pos_cmd = cmd;
                 },
@@ -382,14 +554,13 @@ impl App {
                         Event::Mouse(ev) => {
                             self.handle_mouse(ev).await;
                         },
+                        Event::Paste(text) => {
+                            self.handle_paste(&text).await;
+                        },
                         _ => {},
                     };
                 },
-                _ = on_tasks_changed.recv() => {
-                    if self.selected_project_id().await.is_none() {
-                        self.load_projects().await;
-                    }
-                },
+                Some(e) = events_rx.recv() => self.handle_event(e).await,
                 _ = on_jobs_changed.recv() => {
                     self.async_jobs.write().await.set_items(self.async_jobs_storage.read().await.jobs());
                 },
@@ -398,10 +569,43 @@ impl App {
                 _ = load_state_accepted.recv() => self.load_state().await,
                 _ = save_state_accepted.recv() => self.save_state_as(),
                 _ = show_keybindings_help_shortcut_accepted.recv() => self.show_keybindings_help().await,
+                _ = show_sync_state_accepted.recv() => self.show_sync_state().await,
+                _ = add_task_here_accepted.recv() => self.add_task_here().await,
+                _ = collapse_group_accepted.recv() => self.toggle_group_collapsed().await,
+                _ = exclude_group_accepted.recv() => self.toggle_group_excluded().await,
+                _ = shell_command_accepted.recv() => self.show_shell_command_dialog(),
+                _ = mute_accepted.recv() => {
+                    sound::toggle_muted();
+                },
+                _ = choose_context_accepted.recv() => self.choose_context().await,
+                _ = next_context_accepted.recv() => {
+                    let next = settings::next_context(&self.contexts, self.current_context.as_deref()).cloned();
+                    match next {
+                        Some(name) => self.switch_context(&name).await,
+                        None => self.add_error("There are no contexts in the settings").await,
+                    }
+                },
+                jc = job_control.recv() => match jc {
+                    terminal::JobControl::Suspend => self.terminal_action = Some(TerminalAction::Suspend),
+                    // the process has been stopped by somebody else, the screen could be changed meanwhile
+                    terminal::JobControl::Resume => {
+                        let _ = terminal.clear();
+                    }
+                },
+            }
+
+            if let Some(action) = self.terminal_action.take() {
+                // the events aren't read while the terminal belongs to the shell
+                drop(events);
+                self.leave_terminal(&mut terminal, action).await;
+                events = EventStream::new();
             }
         }
 
-        execute!(std::io::stdout(), DisableMouseCapture)?;
+        // The last used task destination is kept without saving the whole state explicitly
+        self.save_block_state(AppBlock::TaskList).await;
+
+        terminal::restore();
         Ok(())
     }
 
@@ -451,7 +655,78 @@ impl App {
     }
 
     async fn add_error(&mut self, message: &str) {
-        self.error_logger.write().await.add_error(message);
+        self.error_logger.add_error(message);
+    }
+
+    // The dialog belongs to the tasks block, so it's activated to handle the keys
+    async fn add_task_here(&mut self) {
+        self.tasks_widget
+            .write()
+            .await
+            .show_add_task_dialog(None, None, false)
+            .await;
+        self.current_block = AppBlock::TaskList;
+        self.update_activity_state().await;
+    }
+
+    async fn toggle_group_collapsed(&mut self) {
+        self.providers.write().await.toggle_selected_group_collapsed();
+        self.update_task_filter().await;
+    }
+
+    async fn toggle_group_excluded(&mut self) {
+        if self.providers.write().await.toggle_selected_group_excluded().is_none() {
+            self.add_error("Select a provider group to include or exclude it").await;
+            return;
+        }
+        self.update_task_filter().await;
+    }
+
+    async fn choose_context(&mut self) {
+        if self.contexts.is_empty() {
+            self.add_error("There are no contexts in the settings").await;
+            return;
+        }
+        let names = self.contexts.keys().cloned().collect::<Vec<String>>();
+        let d = ListDialog::new(&names, self.current_context.as_deref().unwrap_or_default());
+        self.dialog = Some(Box::new(d));
+    }
+
+    // The provider groups, the filter and the accent of the context are applied at once
+    async fn switch_context(&mut self, name: &str) {
+        let Some(c) = self.contexts.get(name).cloned() else {
+            return;
+        };
+        tracing::info!(target:"app", context=name, "Switch the context");
+
+        let unknown_groups = self.providers.write().await.show_groups(&c.groups);
+        if !unknown_groups.is_empty() {
+            self.add_error(
+                format!(
+                    "The context {name} has unknown provider groups: {}",
+                    unknown_groups.join(", ")
+                )
+                .as_str(),
+            )
+            .await;
+        }
+
+        if let Some(f) = c.filter {
+            self.filter_widget.write().await.set_filter(f);
+        }
+
+        match c.accent.as_deref().map(Color::from_str).transpose() {
+            Ok(accent) => style::set_accent(accent),
+            Err(_) => {
+                style::set_accent(None);
+                self.add_error(format!("The context {name} has a wrong accent color").as_str())
+                    .await;
+            }
+        }
+
+        self.current_context = Some(name.to_string());
+        self.projects.write().await.select_first().await;
+        self.update_task_filter().await;
     }
 
     async fn selected_project_id(&self) -> Option<String> {
@@ -459,11 +734,126 @@ impl App {
     }
 
     async fn load_tasks(&mut self) {
-        self.tasks_widget
-            .write()
-            .await
-            .load_tasks(&self.filter_widget.read().await.filter())
-            .await;
+        self.events
+            .publish(AppEvent::FilterChanged(self.filter_widget.read().await.filter()));
+    }
+
+    async fn handle_event(&mut self, e: AppEvent) {
+        match e {
+            AppEvent::TasksLoaded { .. } => {
+                if self.selected_project_id().await.is_none() {
+                    self.load_projects().await;
+                }
+            }
+            AppEvent::ProviderError { message, .. } | AppEvent::Error(message) => self.add_error(&message).await,
+            AppEvent::PatchCommitted { failed, .. } => self.cues.play(if failed == 0 {
+                sound::Event::CommitSucceeded
+            } else {
+                sound::Event::CommitFailed
+            }),
+            AppEvent::ProviderChanged { provider } => {
+                let p = self.providers.read().await.iter().find(|p| p.name == provider).cloned();
+                if let Some(p) = p {
+                    p.provider.write().await.reload().await;
+                    self.load_tasks().await;
+                }
+            }
+            AppEvent::OpenTask { provider, task_id } => {
+                self.tasks_widget.write().await.open_task(provider, task_id).await;
+                self.current_block = AppBlock::TaskList;
+                self.update_activity_state().await;
+            }
+            AppEvent::FilterChanged(_) => {}
+        }
+    }
+
+    // Forwards the changes made outside the app to the event bus, so the tasks are reloaded
+    async fn watch_providers(&self) {
+        for p in self.providers.read().await.iter() {
+            let Some(mut changes) = p.provider.read().await.subscribe_on_changes() else {
+                continue;
+            };
+            let events = self.events.clone();
+            let provider = p.name.clone();
+            tokio::spawn(async move {
+                while let Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) = changes.recv().await {
+                    events.publish(AppEvent::ProviderChanged {
+                        provider: provider.clone(),
+                    });
+                }
+            });
+        }
+    }
+
+    // The tasks changed by the subcommands are reloaded like the ones changed outside the app.
+    // The task opened by the subcommand is selected.
+    async fn serve_commands(&mut self) {
+        let Some(path) = self.command_socket.clone() else {
+            return;
+        };
+        let providers = self.providers.read().await.iter().cloned().collect::<Vec<Provider>>();
+        let events = self.events.clone();
+        let on_done = move |n: ipc::Notice| {
+            events.publish(match n {
+                ipc::Notice::Changed { provider } => AppEvent::ProviderChanged { provider },
+                ipc::Notice::Open { provider, task_id } => AppEvent::OpenTask { provider, task_id },
+            })
+        };
+        match ipc::Server::start(&path, providers, on_done).await {
+            Ok(Some(server)) => self.command_server = Some(server),
+            Ok(None) => tracing::info!(target:"app", path=?path, "Another session serves the commands"),
+            Err(e) => {
+                tracing::error!(target:"app", path=?path, error=?e, "Serve the commands");
+                self.add_error(format!("The commands can't be sent to this session: {e}").as_str())
+                    .await;
+            }
+        }
+    }
+
+    fn app_shortcuts(&mut self) -> Vec<&mut Shortcut> {
+        vec![
+            &mut self.select_first_shortcut,
+            &mut self.select_last_shortcut,
+            &mut self.load_state_shortcut,
+            &mut self.save_state_shortcut,
+            &mut self.show_keybindings_help_shortcut,
+            &mut self.show_sync_state_shortcut,
+            &mut self.shell_command_shortcut,
+            &mut self.mute_shortcut,
+            &mut self.choose_context_shortcut,
+            &mut self.next_context_shortcut,
+        ]
+    }
+
+    // The shortcuts that can be accepted in the current block
+    async fn available_shortcuts(&mut self) -> Vec<Arc<std::sync::RwLock<shortcut::SharedData>>> {
+        let mut result = Vec::new();
+        for (t, b) in &self.app_blocks {
+            let mut b = b.write().await;
+            result.extend(b.activate_shortcuts().iter().map(|s| s.internal_data()));
+            result.extend(
+                b.shortcuts()
+                    .iter()
+                    .filter(|s| s.is_global() || self.current_block == *t)
+                    .map(|s| s.internal_data()),
+            );
+        }
+        result.extend(self.app_shortcuts().iter().map(|s| s.internal_data()));
+        result
+    }
+
+    async fn find_shortcut_conflicts(&mut self) -> Vec<String> {
+        let mut shortcuts = Vec::new();
+        for (t, b) in &self.app_blocks {
+            let mut b = b.write().await;
+            shortcuts.extend(b.activate_shortcuts().iter().map(|s| (None, s.internal_data())));
+            shortcuts.extend(b.shortcuts().iter().map(|s| {
+                let scope = if s.is_global() { None } else { Some(t.clone()) };
+                (scope, s.internal_data())
+            }));
+        }
+        shortcuts.extend(self.app_shortcuts().iter().map(|s| (None, s.internal_data())));
+        shortcut::find_conflicts(&shortcuts)
     }
 
     async fn handle_shortcuts(&mut self, key: &KeyEvent) -> bool {
@@ -506,25 +896,34 @@ impl App {
 
         self.update_activity_state().await;
 
-        let shortcuts = vec![
-            &mut self.select_first_shortcut,
-            &mut self.select_last_shortcut,
-            &mut self.load_state_shortcut,
-            &mut self.save_state_shortcut,
-            &mut self.show_keybindings_help_shortcut,
-        ];
-        for s in shortcuts {
+        let mut accepted = false;
+        for s in self.app_shortcuts() {
             match s.accept(&keys) {
-                AcceptResult::Accepted => {
-                    self.key_buffer.clear();
-                    found_shortcut = true;
-                }
+                AcceptResult::Accepted => accepted = true,
                 AcceptResult::PartiallyAccepted => found_shortcut = true,
                 AcceptResult::NotAccepted => {}
             }
         }
+        if accepted {
+            self.key_buffer.clear();
+        }
+
+        found_shortcut || accepted
+    }
+
+    async fn handle_paste(&mut self, text: &str) {
+        if let Some(d) = &mut self.dialog {
+            d.handle_paste(text).await;
+            return;
+        }
 
-        found_shortcut
+        self.app_blocks
+            .get_mut(&self.current_block)
+            .unwrap()
+            .write()
+            .await
+            .handle_paste(text)
+            .await;
     }
 
     async fn handle_key(&mut self, key: KeyEvent) {
@@ -533,6 +932,16 @@ impl App {
             return;
         }
 
+        // it's checked before the blocks and the shortcuts, they don't look at the modifiers
+        if key.kind == KeyEventKind::Press
+            && key.code == KeyCode::Char('p')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.key_buffer.clear();
+            self.show_command_palette().await;
+            return;
+        }
+
         let handled_by_current_block = self
             .app_blocks
             .get_mut(&self.current_block)
@@ -557,21 +966,29 @@ impl App {
 
         match key.code {
             KeyCode::Char('q') => {
-                if self.error_logger.read().await.is_empty() {
-                    if self.tasks_widget.read().await.has_changes() {
+                if self.error_logger.is_empty() {
+                    if self.tasks_widget.read().await.has_changes()
+                        && self
+                            .settings
+                            .read()
+                            .await
+                            .is_confirmation_required(Confirmation::ExitWithUncommittedChanges)
+                    {
                         let d = ConfirmationDialog::new(
                             "Exit",
                             "You have uncommitted changes.\nDo you want to exit and lose them?",
                             &[StandardButton::Yes, StandardButton::No],
                             StandardButton::No,
                         )
-                        .icon(ConfirmationDialogIcon::Warning);
+                        .icon(ConfirmationDialogIcon::Warning)
+                        .danger()
+                        .with_dont_ask_again();
                         self.dialog = Some(Box::new(d));
                     } else {
                         self.should_exit = true;
                     }
                 } else {
-                    self.error_logger.write().await.clear();
+                    self.error_logger.clear();
                 }
             }
             KeyCode::Char('h') | KeyCode::Left => {
@@ -600,6 +1017,9 @@ impl App {
             KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.reload().await;
             }
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.terminal_action = Some(TerminalAction::Suspend);
+            }
             _ => {}
         }
     }
@@ -679,25 +1099,25 @@ impl App {
     }
 
     async fn update_task_filter(&mut self) {
-        let mut selected_providers = Vec::new();
-        if let Some(p) = self.providers.read().await.selected() {
-            selected_providers.push(p.name.clone());
-        }
+        let providers = self.providers.read().await;
+        let (selected_providers, excluded_providers) = (providers.selected_providers(), providers.excluded_providers());
+        drop(providers);
         self.tasks_widget
             .write()
             .await
-            .set_providers_filter(&selected_providers)
+            .set_providers_filter(&selected_providers, &excluded_providers)
             .await;
 
         let mut selected_projects = Vec::new();
+        let mut selected_project = None;
         if let Some(p) = self.projects.read().await.selected() {
             selected_projects.push(p.name());
+            selected_project = Some((p.provider(), p.id()));
         }
-        self.tasks_widget
-            .write()
-            .await
-            .set_projects_filter(&selected_projects)
-            .await;
+        let mut tasks_widget = self.tasks_widget.write().await;
+        tasks_widget.set_projects_filter(&selected_projects).await;
+        tasks_widget.set_selected_project(selected_project);
+        drop(tasks_widget);
 
         if self.current_block != AppBlock::Projects {
             self.load_projects().await;
@@ -803,13 +1223,13 @@ impl App {
         self.render_task_description(task_description_area, buf).await;
         self.render_tasks(list_area, buf).await;
 
-        if !self.error_logger.read().await.is_empty() {
+        if !self.error_logger.is_empty() {
             let block = Block::bordered()
                 .border_style(style::default_style().fg(Color::Red))
                 .title("Alert!");
             let area = popup_area(area, Size::new(area.width / 2, 40));
             Clear {}.render(area, buf);
-            Paragraph::new(self.error_logger.read().await.alert())
+            Paragraph::new(self.error_logger.alert())
                 .block(block)
                 .wrap(Wrap { trim: true })
                 .render(area, buf);
@@ -820,9 +1240,29 @@ impl App {
             let area = popup_area(area, size);
             Clear {}.render(area, buf);
             d.render(area, buf).await;
+        } else if !self.key_buffer.is_empty() {
+            self.render_which_key(main_area, buf).await;
         }
     }
 
+    async fn render_which_key(&mut self, area: Rect, buf: &mut Buffer) {
+        let shortcuts = self.available_shortcuts().await;
+        let w = which_key::WhichKey::new(self.key_buffer.keys(), &shortcuts);
+        if w.is_empty() {
+            return;
+        }
+
+        let size = w.size();
+        let [area] = Layout::vertical([Constraint::Length(size.height)])
+            .flex(Flex::End)
+            .areas(area);
+        let [area] = Layout::horizontal([Constraint::Length(size.width)])
+            .flex(Flex::End)
+            .areas(area);
+        Clear {}.render(area, buf);
+        w.render(area, buf);
+    }
+
     fn render_header(area: Rect, buf: &mut Buffer) {
         Paragraph::new("Tatuin (Task Aggregator TUI for N providers)")
             .bold()
@@ -860,6 +1300,22 @@ impl App {
             }
         }
 
+        let due_soon = self.tasks_widget.read().await.due_soon();
+        if due_soon.today > 0 {
+            lines.push(Span::styled(
+                format!(" ⏰ {} in 1h, {} today", due_soon.next_hour, due_soon.today),
+                style::warning_text_style(),
+            ));
+        }
+
+        if let Some(c) = &self.current_context {
+            lines.push(Span::styled(format!(" [{c}]"), style::footer_keys_label_fg()));
+        }
+
+        if sound::is_muted() {
+            lines.push(Span::styled(" 🔇", style::footer_keys_label_fg()));
+        }
+
         if !self.key_buffer.is_empty() {
             lines.push(Span::styled(" Keys: ", style::footer_keys_label_fg()));
             lines.push(Span::styled(self.key_buffer.to_string(), style::footer_keys_fg()));
@@ -890,12 +1346,7 @@ impl App {
     }
 
     async fn render_providers(&mut self, area: Rect, buf: &mut Buffer) {
-        self.providers.write().await.render(
-            "Providers",
-            |p| -> ListItem { ListItem::from(Span::styled(format!("{} ({})", p.name, p.type_name), p.color)) },
-            area,
-            buf,
-        );
+        self.providers.write().await.render(area, buf).await;
     }
 
     async fn render_projects(&mut self, area: Rect, buf: &mut Buffer) {
@@ -940,9 +1391,34 @@ impl App {
     fn save_state_as(&mut self) {
         let mut d = TextInputDialog::new("State name", Regex::new(r"^[[:alpha:]]+[\[[:alpha:]\]\-_]*$").unwrap());
         d.set_draw_helper(self.draw_helper.as_ref().unwrap().clone());
+        self.text_input = TextInput::StateName;
         self.dialog = Some(Box::new(d));
     }
 
+    fn show_shell_command_dialog(&mut self) {
+        let mut d = TextInputDialog::new("Shell command", Regex::new(r"^.*$").unwrap());
+        d.set_draw_helper(self.draw_helper.as_ref().unwrap().clone());
+        self.text_input = TextInput::ShellCommand;
+        self.dialog = Some(Box::new(d));
+    }
+
+    async fn leave_terminal(&mut self, t: &mut DefaultTerminal, action: TerminalAction) {
+        tracing::info!(target:"app", action=?action, "Leave the terminal");
+        terminal::leave();
+        match action {
+            TerminalAction::Suspend => terminal::suspend(),
+            TerminalAction::Shell(command) => {
+                if let Err(e) = terminal::run_shell_command(&command).await {
+                    self.add_error(format!("Run the shell command `{command}` error: {e}").as_str())
+                        .await;
+                }
+            }
+        }
+        if let Err(e) = terminal::enter(t) {
+            tracing::error!(target:"app", error=?e, "Enter the terminal");
+        }
+    }
+
     async fn save_state(&mut self, name: Option<&str>) {
         let mut state = State::default();
 
@@ -957,7 +1433,24 @@ impl App {
         }
     }
 
+    // Updates only the block in the current state, the rest of the state stays as it was saved
+    async fn save_block_state(&mut self, block: AppBlock) {
+        let Some(w) = self.stateful_widgets.get(&block) else {
+            return;
+        };
+
+        let name = self.current_state.clone();
+        let mut state = self.settings.read().await.load(name.as_deref());
+        state.insert_str(block.to_string().as_str(), w.read().await.save().await.into());
+
+        let r = self.settings.write().await.save(name.as_deref(), state);
+        if let Err(e) = r {
+            self.add_error(format!("Save state error: {}", e).as_str()).await;
+        }
+    }
+
     async fn restore_state(&mut self, name: Option<&str>) {
+        self.current_state = name.map(|n| n.to_string());
         for (block_name, st) in self.settings.read().await.load(name).as_map() {
             if let Ok(n) = AppBlock::from_str(block_name.as_str())
                 && let Some(b) = self.stateful_widgets.get_mut(&n)
@@ -989,17 +1482,127 @@ impl App {
             }
         }
 
+        if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<ListDialog<String>>()
+            && d.accepted()
+            && let Some(name) = d.selected().cloned()
+        {
+            self.switch_context(&name).await;
+        }
+
+        if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<ListDialog<PaletteCommand>>()
+            && d.accepted()
+            && let Some(c) = d.selected().cloned()
+        {
+            self.run_palette_command(c).await;
+        }
+
         if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<TextInputDialog>() {
             let t = d.text();
             if !t.is_empty() {
-                self.save_state(Some(t.as_str())).await;
+                match self.text_input {
+                    TextInput::StateName => self.save_state(Some(t.as_str())).await,
+                    TextInput::ShellCommand => self.terminal_action = Some(TerminalAction::Shell(t)),
+                }
             }
         }
         if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<ConfirmationDialog>()
             && d.accepted()
         {
+            if d.dont_ask_again() {
+                let r = self
+                    .settings
+                    .write()
+                    .await
+                    .set_confirmation_required(Confirmation::ExitWithUncommittedChanges, false);
+                if let Err(e) = r {
+                    self.add_error(format!("Save settings error: {e}").as_str()).await;
+                }
+            }
             self.should_exit = true;
         }
+
+        if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<SyncStateDialog>()
+            && let Some(action) = d.action()
+        {
+            self.apply_sync_action(action).await;
+        }
+    }
+
+    async fn show_sync_state(&mut self) {
+        let Some(p) = self.providers.read().await.selected().cloned() else {
+            self.add_error("Select a provider to see its sync state").await;
+            return;
+        };
+
+        let st = p.provider.read().await.sync_state();
+        let pending_changes = self.tasks_widget.read().await.pending_changes(&p.name);
+        let d = SyncStateDialog::new(&p.name, &p.type_name, &st, pending_changes);
+        self.dialog = Some(Box::new(d));
+    }
+
+    async fn apply_sync_action(&mut self, action: SyncStateAction) {
+        let Some(p) = self.providers.read().await.selected().cloned() else {
+            return;
+        };
+
+        match action {
+            SyncStateAction::ClearCache => {
+                if let Err(e) = p.provider.write().await.clear_cache().await {
+                    self.add_error(format!("Clear the cache of {}: {e}", p.name).as_str())
+                        .await;
+                }
+            }
+            SyncStateAction::Resync => p.provider.write().await.reload().await,
+            SyncStateAction::Close => return,
+        }
+
+        self.load_tasks().await;
+    }
+
+    async fn show_command_palette(&mut self) {
+        let states = self.settings.read().await.states();
+        let contexts = self.contexts.keys().cloned().collect::<Vec<String>>();
+        let commands = command_palette::commands(&self.all_shortcuts, &states, &contexts);
+        let d = ListDialog::new(&commands, "").searchable(true).show_top_title(false);
+        self.dialog = Some(Box::new(d));
+    }
+
+    async fn run_palette_command(&mut self, c: PaletteCommand) {
+        match c {
+            PaletteCommand::Shortcut { keys, data, .. } => {
+                // the shortcut is accepted like it's typed, so the block owning it becomes the current one
+                for (t, b) in &self.app_blocks {
+                    let mut b = b.write().await;
+                    let mut found = false;
+                    for s in b.activate_shortcuts() {
+                        if Arc::ptr_eq(&s.internal_data(), &data) {
+                            s.accept(&keys);
+                            found = true;
+                        }
+                    }
+                    for s in b.shortcuts() {
+                        if Arc::ptr_eq(&s.internal_data(), &data) {
+                            s.accept(&keys);
+                            found = true;
+                        }
+                    }
+                    if found {
+                        self.current_block = t.clone();
+                    }
+                }
+                self.update_activity_state().await;
+
+                if let Some(s) = self
+                    .app_shortcuts()
+                    .into_iter()
+                    .find(|s| Arc::ptr_eq(&s.internal_data(), &data))
+                {
+                    s.accept(&keys);
+                }
+            }
+            PaletteCommand::State(name) => self.restore_state(Some(name.as_str())).await,
+            PaletteCommand::Context(name) => self.switch_context(&name).await,
+        }
     }
 
     async fn show_keybindings_help(&mut self) {
@@ -1019,20 +1622,10 @@ impl App {
                 .filter(|s| s.read().unwrap().is_global)
                 .cloned()
                 .collect::<Vec<Arc<std::sync::RwLock<shortcut::SharedData>>>>(),
+            &self.shortcut_conflicts,
         );
         self.dialog = Some(Box::new(d));
     }
-
-    fn enable_advanced_terminal_flags(&self) {
-        let r = execute!(
-            std::io::stdout(),
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
-        );
-
-        if let Err(e) = r {
-            tracing::error!(target="app", error=?e, "Enable advanced terminal features");
-        }
-    }
 }
 
 fn popup_area(area: Rect, size: Size) -> Rect {
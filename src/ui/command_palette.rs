@@ -0,0 +1,87 @@
+// SPDX-License-Identifier: MIT
+
+use std::{fmt, sync::Arc};
+
+use tatuin_core::types::ArcRwLockBlocked;
+
+use super::shortcut::{SharedData, keys_to_str};
+
+// The action that can be run from the command palette without remembering its keys
+#[derive(Clone)]
+pub enum PaletteCommand {
+    Shortcut {
+        name: String,
+        keys: Vec<char>,
+        data: ArcRwLockBlocked<SharedData>,
+    },
+    State(String),
+    Context(String),
+}
+
+impl fmt::Display for PaletteCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Shortcut { name, keys, .. } => write!(f, "{name} ({})", keys_to_str(keys).trim_end()),
+            Self::State(name) => write!(f, "Load the state: {name}"),
+            Self::Context(name) => write!(f, "Switch to the context: {name}"),
+        }
+    }
+}
+
+// The shortcuts of the registry go first, every shortcut is listed once
+pub fn commands(
+    shortcuts: &[ArcRwLockBlocked<SharedData>],
+    states: &[String],
+    contexts: &[String],
+) -> Vec<PaletteCommand> {
+    let mut result: Vec<PaletteCommand> = Vec::new();
+    for s in shortcuts {
+        let is_listed = result
+            .iter()
+            .any(|c| matches!(c, PaletteCommand::Shortcut { data, .. } if Arc::ptr_eq(data, s)));
+        if is_listed {
+            continue;
+        }
+        let d = s.read().unwrap();
+        result.push(PaletteCommand::Shortcut {
+            name: d.name.clone(),
+            keys: d.keys.clone(),
+            data: s.clone(),
+        });
+    }
+    result.extend(states.iter().cloned().map(PaletteCommand::State));
+    result.extend(contexts.iter().cloned().map(PaletteCommand::Context));
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ui::shortcut::Shortcut;
+
+    #[test]
+    fn commands_test() {
+        let help = Shortcut::new("Show help", &['?']).internal_data();
+        let load_state = Shortcut::new("Load state", &['s', 'l']).internal_data();
+        let first = Shortcut::new("Select first", &['g', 'g']).internal_data();
+
+        let commands = commands(
+            &[help.clone(), load_state, help, first],
+            &["work".to_string()],
+            &["home".to_string()],
+        )
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>();
+        assert_eq!(
+            commands,
+            [
+                "Show help (?)",
+                "Load state (sl)",
+                "Select first (gg)",
+                "Load the state: work",
+                "Switch to the context: home"
+            ]
+        );
+    }
+}
@@ -1,19 +1,25 @@
 // SPDX-License-Identifier: MIT
 
+mod commit_preview;
 mod confirmation;
 mod create_update_task;
+mod description;
 mod dialog;
 mod key_bindings_help;
 mod list;
 mod multi_select_list;
 mod states;
+mod sync_state;
 mod text_input;
 
+pub use commit_preview::{DescriptionChange, Dialog as CommitPreviewDialog};
 pub use confirmation::{Dialog as ConfirmationDialog, Icon as ConfirmationDialogIcon, StandardButton};
 pub use create_update_task::Dialog as CreateUpdateTaskDialog;
+pub use description::Dialog as DescriptionDialog;
 pub use dialog::DialogTrait;
 pub use key_bindings_help::Dialog as KeyBindingsHelpDialog;
 pub use list::Dialog as ListDialog;
 pub use multi_select_list::Dialog as MultiSelectListDialog;
 pub use states::Dialog as StatesDialog;
+pub use sync_state::{Action as SyncStateAction, Dialog as SyncStateDialog};
 pub use text_input::Dialog as TextInputDialog;
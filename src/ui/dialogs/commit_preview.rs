@@ -0,0 +1,318 @@
+// SPDX-License-Identifier: MIT
+
+use std::any::Any;
+
+use super::DialogTrait;
+use crate::ui::{
+    keyboard_handler::KeyboardHandler,
+    mouse_handler::MouseHandler,
+    style,
+    widgets::{WidgetState, WidgetStateTrait, WidgetTrait},
+};
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect, Size},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+
+const MIN_WIDTH: u16 = 40;
+const MAX_TEXT_HEIGHT: u16 = 20;
+// The unchanged lines shown around the changed ones
+const CONTEXT_LINES: usize = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+enum DiffLine<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+impl DiffLine<'_> {
+    fn is_changed(&self) -> bool {
+        !matches!(self, Self::Same(_))
+    }
+}
+
+// The line by line difference of the texts by their longest common subsequence
+fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<DiffLine<'a>> {
+    let old = old.lines().collect::<Vec<&str>>();
+    let new = new.lines().collect::<Vec<&str>>();
+
+    // lcs[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut result = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            result.push(DiffLine::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            result.push(DiffLine::Removed(old[i]));
+            i += 1;
+        } else {
+            result.push(DiffLine::Added(new[j]));
+            j += 1;
+        }
+    }
+    result.extend(old[i..].iter().map(|l| DiffLine::Removed(l)));
+    result.extend(new[j..].iter().map(|l| DiffLine::Added(l)));
+    result
+}
+
+// The changed lines with their context, None is the skipped unchanged lines
+fn hunks<'a, 'b>(diff: &'b [DiffLine<'a>]) -> Vec<Option<&'b DiffLine<'a>>> {
+    let is_shown = |i: usize| {
+        let from = i.saturating_sub(CONTEXT_LINES);
+        let to = (i + CONTEXT_LINES + 1).min(diff.len());
+        diff[from..to].iter().any(|l| l.is_changed())
+    };
+
+    let mut result = Vec::new();
+    for (i, l) in diff.iter().enumerate() {
+        if is_shown(i) {
+            result.push(Some(l));
+        } else if result.last().is_some_and(|l| l.is_some()) || (result.is_empty() && i == 0) {
+            result.push(None);
+        }
+    }
+    if result.last().is_some_and(|l| l.is_none()) {
+        result.pop();
+    }
+    result
+}
+
+pub struct DescriptionChange {
+    pub task_name: String,
+    pub old: String,
+    pub new: String,
+}
+
+// The preview of the description changes that are going to be committed
+pub struct Dialog {
+    lines: Vec<Line<'static>>,
+    scroll: u16,
+    width: u16,
+    accepted: bool,
+    should_be_closed: bool,
+    widget_state: WidgetState,
+}
+crate::impl_widget_state_trait!(Dialog);
+
+impl Dialog {
+    pub fn new(changes: &[DescriptionChange]) -> Self {
+        let added = Style::default().fg(style::diff_added_fg());
+        let removed = Style::default().fg(style::diff_removed_fg());
+
+        let mut lines = Vec::new();
+        for c in changes {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::styled(
+                c.task_name.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ));
+            for l in hunks(&diff_lines(&c.old, &c.new)) {
+                lines.push(match l {
+                    Some(DiffLine::Same(s)) => Line::raw(format!("  {s}")),
+                    Some(DiffLine::Removed(s)) => Line::styled(format!("- {s}"), removed),
+                    Some(DiffLine::Added(s)) => Line::styled(format!("+ {s}"), added),
+                    None => Line::raw("  …"),
+                });
+            }
+        }
+
+        Self {
+            lines,
+            scroll: 0,
+            width: MIN_WIDTH,
+            accepted: false,
+            should_be_closed: false,
+            widget_state: WidgetState::default(),
+        }
+    }
+
+    // The height of the text wrapped by the current width
+    fn text_height(&self) -> u16 {
+        let width = self.width.saturating_sub(2).max(1) as usize;
+        self.lines.iter().map(|l| l.width().div_ceil(width).max(1) as u16).sum()
+    }
+
+    fn visible_text_height(&self) -> u16 {
+        self.text_height().min(MAX_TEXT_HEIGHT)
+    }
+
+    fn scroll_by(&mut self, delta: i32) {
+        let max_scroll = self.text_height().saturating_sub(self.visible_text_height()) as i32;
+        self.scroll = (self.scroll as i32 + delta).clamp(0, max_scroll) as u16;
+    }
+
+    fn close(&mut self, accepted: bool) {
+        self.accepted = accepted;
+        self.should_be_closed = true;
+    }
+}
+
+#[async_trait]
+impl WidgetTrait for Dialog {
+    async fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let b = Block::default()
+            .style(style::default_style())
+            .title_top(" Commit the description changes? ")
+            .title_bottom("y or Enter to commit, n or Esc to cancel, j/k to scroll")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(style::border_color());
+
+        Paragraph::new(self.lines.clone())
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(b)
+            .render(area, buf);
+    }
+
+    fn min_size(&self) -> Size {
+        Size::new(MIN_WIDTH, self.visible_text_height() + 2)
+    }
+
+    fn size(&self) -> Size {
+        Size::new(self.width, self.visible_text_height() + 2)
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.width = size.width.max(MIN_WIDTH);
+        self.scroll_by(0);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl DialogTrait for Dialog {
+    fn accepted(&self) -> bool {
+        self.accepted
+    }
+
+    fn should_be_closed(&self) -> bool {
+        self.should_be_closed
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl KeyboardHandler for Dialog {
+    async fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let page = self.visible_text_height() as i32;
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') => self.close(true),
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => self.close(false),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_by(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_by(-1),
+            KeyCode::PageDown => self.scroll_by(page),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::Char('g') | KeyCode::Home => self.scroll = 0,
+            KeyCode::Char('G') | KeyCode::End => self.scroll_by(i32::from(u16::MAX)),
+            _ => {}
+        }
+
+        true
+    }
+}
+
+#[async_trait]
+impl MouseHandler for Dialog {
+    async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diff_lines_test() {
+        use DiffLine::*;
+
+        struct Case<'a> {
+            name: &'a str,
+            old: &'a str,
+            new: &'a str,
+            expected: Vec<DiffLine<'a>>,
+        }
+        let cases = [
+            Case {
+                name: "same text",
+                old: "a\nb",
+                new: "a\nb",
+                expected: vec![Same("a"), Same("b")],
+            },
+            Case {
+                name: "new description",
+                old: "",
+                new: "a\nb",
+                expected: vec![Added("a"), Added("b")],
+            },
+            Case {
+                name: "removed description",
+                old: "a",
+                new: "",
+                expected: vec![Removed("a")],
+            },
+            Case {
+                name: "changed line in the middle",
+                old: "a\nb\nc",
+                new: "a\nB\nc",
+                expected: vec![Same("a"), Removed("b"), Added("B"), Same("c")],
+            },
+            Case {
+                name: "inserted and appended lines",
+                old: "a\nc",
+                new: "a\nb\nc\nd",
+                expected: vec![Same("a"), Added("b"), Same("c"), Added("d")],
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(diff_lines(c.old, c.new), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn hunks_test() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9";
+        let new = "1\n2\n3\n4\n5\n6\n7\n8\nnine";
+        let diff = diff_lines(old, new);
+        let shown = hunks(&diff)
+            .iter()
+            .map(|l| match l {
+                Some(DiffLine::Same(s)) => s.to_string(),
+                Some(DiffLine::Removed(s)) => format!("-{s}"),
+                Some(DiffLine::Added(s)) => format!("+{s}"),
+                None => "…".to_string(),
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(shown, vec!["…", "7", "8", "-9", "+nine"]);
+
+        let diff = diff_lines("a\nb", "a\nb");
+        assert!(hunks(&diff).is_empty(), "the unchanged text has no hunks");
+    }
+}
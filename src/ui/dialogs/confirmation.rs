@@ -42,12 +42,16 @@ pub enum Icon {
     Custom(char),
 }
 
+const DONT_ASK_AGAIN: &str = "Don't ask again (d)";
+
 pub struct Dialog {
     title: String,
     icon: Option<Icon>,
     question: String,
     buttons: Vec<DialogButton>,
     choice: Option<StandardButton>,
+    // None if the checkbox isn't shown
+    dont_ask_again: Option<bool>,
     should_be_closed: bool,
     widget_state: WidgetState,
 }
@@ -72,6 +76,7 @@ impl Dialog {
             question: question.to_string(),
             buttons,
             choice: None,
+            dont_ask_again: None,
             should_be_closed: false,
             widget_state: WidgetState::default(),
         }
@@ -82,6 +87,31 @@ impl Dialog {
         self
     }
 
+    // The accepting buttons are highlighted as the destructive ones
+    pub fn danger(mut self) -> Self {
+        for b in &mut self.buttons {
+            b.widget
+                .set_danger(matches!(b.standard_button, StandardButton::Ok | StandardButton::Yes));
+        }
+        self
+    }
+
+    pub fn with_dont_ask_again(mut self) -> Self {
+        self.dont_ask_again = Some(false);
+        self
+    }
+
+    pub fn dont_ask_again(&self) -> bool {
+        self.dont_ask_again.unwrap_or(false)
+    }
+
+    fn choose(&mut self, buttons: &[StandardButton]) {
+        if let Some(b) = self.buttons.iter().find(|b| buttons.contains(&b.standard_button)) {
+            self.should_be_closed = true;
+            self.choice = Some(b.standard_button);
+        }
+    }
+
     fn order_calculator(&mut self) -> OrderChanger<'_> {
         OrderChanger::new(
             self.buttons
@@ -117,14 +147,23 @@ impl WidgetTrait for Dialog {
             .borders(Borders::ALL)
             .border_style(style::border_color());
 
-        let [question_area, buttons_area] =
-            Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).areas(b.inner(area));
+        let [question_area, checkbox_area, buttons_area] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(self.dont_ask_again.map_or(0, |_| 1)),
+            Constraint::Fill(1),
+        ])
+        .areas(b.inner(area));
         b.render(area, buf);
 
         Paragraph::new(self.question.as_str())
             .alignment(Alignment::Center)
             .wrap(Wrap { trim: true })
             .render(question_area, buf);
+        if let Some(checked) = self.dont_ask_again {
+            Paragraph::new(format!("[{}] {DONT_ASK_AGAIN}", if checked { 'x' } else { ' ' }))
+                .alignment(Alignment::Center)
+                .render(checkbox_area, buf);
+        }
         let all_buttons_width: u16 = self
             .buttons
             .iter()
@@ -155,9 +194,13 @@ impl WidgetTrait for Dialog {
     }
 
     fn size(&self) -> Size {
-        let width = Text::raw(&self.title).width().max(Text::raw(&self.question).width());
+        let mut width = Text::raw(&self.title).width().max(Text::raw(&self.question).width());
         let mut height = self.question.chars().filter(|c| *c == '\n').count() as u16 + 1;
         height += 1; // empty line
+        if self.dont_ask_again.is_some() {
+            width = width.max(Text::raw(DONT_ASK_AGAIN).width() + 4);
+            height += 1;
+        }
         height += self
             .buttons
             .iter()
@@ -212,6 +255,13 @@ impl KeyboardHandler for Dialog {
                 self.should_be_closed = true;
                 self.choice = Some(StandardButton::Cancel);
             }
+            KeyCode::Char('y') => self.choose(&[StandardButton::Yes, StandardButton::Ok]),
+            KeyCode::Char('n') => self.choose(&[StandardButton::No, StandardButton::Cancel]),
+            KeyCode::Char('d') => {
+                if let Some(checked) = &mut self.dont_ask_again {
+                    *checked = !*checked;
+                }
+            }
             KeyCode::Tab => {
                 self.next_widget().await;
             }
@@ -229,3 +279,48 @@ impl KeyboardHandler for Dialog {
 impl MouseHandler for Dialog {
     async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(ch: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn single_key_answers_test() {
+        let buttons = [StandardButton::Yes, StandardButton::No];
+
+        let mut d = Dialog::new("Delete", "Delete?", &buttons, StandardButton::No);
+        d.handle_key(key('y')).await;
+        assert!(d.should_be_closed() && d.accepted());
+
+        let mut d = Dialog::new("Delete", "Delete?", &buttons, StandardButton::Yes);
+        d.handle_key(key('n')).await;
+        assert!(d.should_be_closed() && !d.accepted());
+
+        // there is no button to press
+        let mut d = Dialog::new("Info", "Done", &[StandardButton::Ok], StandardButton::Ok);
+        d.handle_key(key('n')).await;
+        assert!(!d.should_be_closed());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn dont_ask_again_test() {
+        let buttons = [StandardButton::Yes, StandardButton::No];
+
+        let mut d = Dialog::new("Delete", "Delete?", &buttons, StandardButton::Yes);
+        d.handle_key(key('d')).await;
+        assert!(!d.dont_ask_again(), "the checkbox isn't shown");
+
+        let mut d = Dialog::new("Delete", "Delete?", &buttons, StandardButton::Yes).with_dont_ask_again();
+        d.handle_key(key('d')).await;
+        assert!(d.dont_ask_again());
+        d.handle_key(key('d')).await;
+        assert!(!d.dont_ask_again());
+    }
+}
@@ -1,6 +1,6 @@
 // SPDX-License-Identifier: MIT
 
-use std::{any::Any, sync::Arc};
+use std::{any::Any, collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
@@ -10,6 +10,8 @@ use ratatui::{
     widgets::{Block, Borders, Widget},
 };
 use tatuin_core::{
+    placeholder,
+    project::Project as ProjectTrait,
     provider::ProjectProviderTrait,
     state::{State, StatefulObject, state_from_str},
     task::{DateTimeUtc, Priority, Task as TaskTrait},
@@ -17,16 +19,20 @@ use tatuin_core::{
     types::ArcRwLock,
 };
 
-use crate::ui::{
-    draw_helper::DrawHelper,
-    keyboard_handler::KeyboardHandler,
-    mouse_handler::MouseHandler,
-    order_changer::OrderChanger,
-    style,
-    tasks_widget::ProvidersStorage,
-    widgets::{
-        Button, ComboBox, ComboBoxItem, CustomWidgetItemUpdater, DateEditor, LineEdit, Text, TextEdit, WidgetState,
-        WidgetStateTrait, WidgetTrait,
+use crate::{
+    clipboard,
+    spellcheck::SharedSpellchecker,
+    ui::{
+        draw_helper::DrawHelper,
+        keyboard_handler::KeyboardHandler,
+        mouse_handler::MouseHandler,
+        order_changer::OrderChanger,
+        style,
+        tasks_widget::ProvidersStorage,
+        widgets::{
+            Button, ComboBox, ComboBoxItem, CustomWidgetItemUpdater, DateEditor, LineEdit, Text, TextEdit, WidgetState,
+            WidgetStateTrait, WidgetTrait,
+        },
     },
 };
 
@@ -55,7 +61,9 @@ pub struct Dialog {
     providers_storage: ArcRwLock<dyn ProvidersStorage>,
     widget_state: WidgetState,
     size: Size,
-    task: Option<Box<dyn TaskTrait>>,
+    task: Option<Arc<dyn TaskTrait>>,
+    // the created task becomes a subtask of it
+    parent: Option<Arc<dyn TaskTrait>>,
 
     provider_selector: ComboBox<String>,
     project_selector: ComboBox<String>,
@@ -71,6 +79,11 @@ pub struct Dialog {
     priority_selector: ComboBox<Priority>,
     due_date_selector: ComboBox<DatePatchItem>,
 
+    // the labels are editable only for the providers that support them
+    labels_supported: bool,
+    labels_caption: Text,
+    labels_editor: LineEdit,
+
     create_task_button: Button,
     create_task_and_another_one: Button,
 }
@@ -119,7 +132,8 @@ impl Dialog {
             widget_state: WidgetState::default(),
             size: Size::new(100, 20),
             task: None,
-            project_selector: ComboBox::new("Project", &[]),
+            parent: None,
+            project_selector: ComboBox::new("Project", &[]).searchable().await,
             task_name_caption: Text::new("Task name"),
             task_name_editor: LineEdit::new(None),
             task_description_caption: Text::new("Task description"),
@@ -138,6 +152,9 @@ impl Dialog {
             ))
             .await,
             due_date_selector,
+            labels_supported: false,
+            labels_caption: Text::new("Labels (comma separated)"),
+            labels_editor: LineEdit::new(None),
             create_task_button: Button::new("Create a task and close\nCtrl+Enter"),
             create_task_and_another_one: Button::new("Create a task\nShift+Enter"),
         };
@@ -147,13 +164,21 @@ impl Dialog {
         s
     }
 
-    fn is_task_creation(&self) -> bool {
+    pub fn is_task_creation(&self) -> bool {
         self.task.is_none()
     }
 
     async fn on_provider_changed(&mut self) {
         self.fill_project_selector_items().await;
         self.fill_priority_selector_items().await;
+
+        let provider_name = self.provider_selector.value().await.map(|item| item.text().to_string());
+        self.labels_supported = self
+            .providers_storage
+            .read()
+            .await
+            .iter()
+            .any(|p| Some(&p.name) == provider_name.as_ref() && p.capabilities.labels);
     }
 
     async fn set_provider(&mut self, provider: &str) {
@@ -172,8 +197,34 @@ impl Dialog {
         }
     }
 
-    pub async fn set_task(&mut self, task: &dyn TaskTrait) {
-        self.task = Some(task.clone_boxed());
+    // The project is looked up by its name or id
+    pub async fn set_destination(&mut self, provider: &str, project: Option<&str>) {
+        self.set_provider(provider).await;
+        if let Some(project) = project {
+            self.project_selector
+                .set_current_item_if(|item| item.text() == project || item.data() == project)
+                .await;
+        }
+        self.provider_selector.set_active(false);
+        self.task_name_editor.set_active(true);
+        self.update_enabled_state().await;
+    }
+
+    // The subtask is created in the provider and the project of the parent task
+    pub async fn set_parent(&mut self, parent: &Arc<dyn TaskTrait>) {
+        self.parent = Some(parent.clone());
+        self.title = format!("Create a subtask of \"{}\"", parent.name().plain());
+        let project_id = parent.project().map(|p| p.id());
+        self.set_destination(parent.provider().as_str(), project_id.as_deref())
+            .await;
+    }
+
+    pub fn parent(&self) -> Option<Arc<dyn TaskTrait>> {
+        self.parent.clone()
+    }
+
+    pub async fn set_task(&mut self, task: &Arc<dyn TaskTrait>) {
+        self.task = Some(task.clone());
         self.create_task_and_another_one.set_visible(false);
         self.create_task_button
             .set_title("Update the task and close\nCtrl+Enter");
@@ -187,6 +238,7 @@ impl Dialog {
         if let Some(d) = task.description() {
             self.task_description_editor.set_text(&d.raw());
         }
+        self.labels_editor.set_text(task.labels().join(", ").as_str());
         self.priority_selector
             .set_current_item(&ComboBoxItem::new(
                 task.priority().to_string().as_str(),
@@ -194,7 +246,24 @@ impl Dialog {
             ))
             .await;
 
-        let task_due = task.due();
+        self.set_due(task.due()).await;
+
+        self.provider_selector.set_active(false);
+        self.task_name_editor.set_active(true);
+        self.update_enabled_state().await
+    }
+
+    // The follow-up is created next to the completed task
+    pub async fn set_follow_up(&mut self, task: &Arc<dyn TaskTrait>, name: &str, due: DateTimeUtc) {
+        self.title = format!("Create a follow-up of \"{}\"", task.name().plain());
+        let project_id = task.project().map(|p| p.id());
+        self.set_destination(task.provider().as_str(), project_id.as_deref())
+            .await;
+        self.task_name_editor.set_text(name);
+        self.set_due(Some(due)).await;
+    }
+
+    async fn set_due(&mut self, task_due: Option<DateTimeUtc>) {
         let due: DatePatchItem = task_due.map_or(DatePatchItem::NoDate, |d| d.into());
         if let Some(dt) = task_due {
             self.due_date_selector.remove_all_custom_widgets().await;
@@ -217,10 +286,6 @@ impl Dialog {
                 due,
             ))
             .await;
-
-        self.provider_selector.set_active(false);
-        self.task_name_editor.set_active(true);
-        self.update_enabled_state().await
     }
 
     pub async fn provider_name(&self) -> Option<String> {
@@ -239,6 +304,14 @@ impl Dialog {
         self.project_selector.value().await.map(|item| item.data().to_string())
     }
 
+    // The chosen provider and project only, to preselect them in the next dialog
+    pub async fn destination(&self) -> State {
+        State::from([
+            (PROVIDER_KEY.to_string(), self.provider_selector.save().await.into()),
+            (PROJECT_KEY.to_string(), self.project_selector.save().await.into()),
+        ])
+    }
+
     pub fn add_another_one(&self) -> bool {
         self.add_another_one
     }
@@ -254,12 +327,39 @@ impl Dialog {
         self.create_task_button.set_title("Create tasks and close\nCtrl+Enter");
     }
 
+    pub fn set_spellchecker(&mut self, sc: SharedSpellchecker) {
+        self.task_name_editor.set_spellchecker(sc.clone());
+        self.task_description_editor.set_spellchecker(sc.clone());
+        self.batch_name_editor.set_spellchecker(sc);
+    }
+
     pub async fn task_patches(&self) -> Vec<TaskPatch> {
         if !self.can_create_task() {
             return Vec::new();
         }
         let due: ValuePatch<DatePatchItem> = self.due_date_selector.value().await.map(|item| *item.data()).into();
         let priority: ValuePatch<Priority> = self.priority_selector.value().await.map(|item| *item.data()).into();
+        let labels = if self.labels_supported {
+            match parse_labels(&self.labels_editor.text()) {
+                l if l.is_empty() => ValuePatch::Empty,
+                l => ValuePatch::Value(l),
+            }
+        } else {
+            ValuePatch::NotSet
+        };
+        // the parent can't be set if another provider was chosen
+        let provider_name = self.provider_selector.value().await.map(|item| item.text().to_string());
+        let parent: ValuePatch<String> = self
+            .parent
+            .as_ref()
+            .filter(|p| Some(p.provider()) == provider_name)
+            .map(|p| p.id())
+            .into();
+        // the placeholders are expanded only in the created tasks, the edited ones keep them as they are
+        let expand = |text: String| match self.task {
+            Some(_) => text,
+            None => placeholder::expand(&text, clipboard::read),
+        };
 
         if self.batch_mode {
             self.batch_name_editor
@@ -269,28 +369,34 @@ impl Dialog {
                 .filter(|s| !s.is_empty())
                 .map(|name| TaskPatch {
                     task: None,
-                    name: ValuePatch::Value(name),
+                    name: ValuePatch::Value(expand(name)),
                     description: ValuePatch::Empty,
                     due: due.clone(),
                     scheduled: ValuePatch::NotSet,
                     priority: priority.clone(),
                     state: ValuePatch::NotSet,
+                    labels: labels.clone(),
+                    parent: parent.clone(),
+                    url: ValuePatch::NotSet,
                 })
                 .collect()
         } else {
             let description = self.task_description_editor.text();
             vec![TaskPatch {
-                task: self.task.as_ref().map(|t| t.clone_boxed()),
-                name: ValuePatch::Value(self.task_name_editor.text()),
+                task: self.task.clone(),
+                name: ValuePatch::Value(expand(self.task_name_editor.text())),
                 description: if description.is_empty() {
                     ValuePatch::Empty
                 } else {
-                    ValuePatch::Value(description)
+                    ValuePatch::Value(expand(description))
                 },
                 due,
                 scheduled: ValuePatch::NotSet,
                 priority,
                 state: ValuePatch::NotSet,
+                labels,
+                parent,
+                url: ValuePatch::NotSet,
             }]
         }
     }
@@ -304,6 +410,7 @@ impl Dialog {
             &mut self.task_description_editor,
             &mut self.priority_selector,
             &mut self.due_date_selector,
+            &mut self.labels_editor,
             &mut self.create_task_button,
             &mut self.create_task_and_another_one,
         ])
@@ -350,6 +457,7 @@ impl Dialog {
 
         self.priority_selector.set_enabled(can_input_name);
         self.due_date_selector.set_enabled(can_input_name);
+        self.labels_editor.set_enabled(can_input_name && self.labels_supported);
 
         self.create_task_button.set_enabled(can_create);
         self.create_task_and_another_one
@@ -363,18 +471,32 @@ impl Dialog {
         }
         let provider_name = provider_name.unwrap();
 
-        let mut providers = self.providers_storage.write().await;
-        let provider = providers.iter_mut().find(|p| p.name == provider_name);
-        if provider.is_none() {
+        // the storage isn't locked while the provider is busy, e.g. with loading the tasks
+        let provider = self
+            .providers_storage
+            .read()
+            .await
+            .iter()
+            .find(|p| p.name == provider_name)
+            .map(|p| (p.provider.clone(), p.limit.clone()));
+        let Some((provider, limit)) = provider else {
             return;
-        }
-        let provider = provider.unwrap();
-        if let Ok(projects) = ProjectProviderTrait::list(provider.provider.write().await.as_mut()).await {
+        };
+        if let Ok(projects) = limit
+            .run(async { ProjectProviderTrait::list(provider.write().await.as_mut()).await })
+            .await
+        {
             self.project_selector
                 .set_items(
                     &projects
                         .iter()
-                        .map(|p| ComboBoxItem::new(p.name().as_str(), p.id()))
+                        .map(|p| {
+                            ComboBoxItem::new(p.name().as_str(), p.id()).display(&format!(
+                                "{} [{}]",
+                                project_path(&projects, p.id().as_str()),
+                                p.provider()
+                            ))
+                        })
                         .collect::<Vec<ComboBoxItem<_>>>(),
                 )
                 .await;
@@ -427,6 +549,38 @@ impl DialogTrait for Dialog {
     }
 }
 
+// The labels are separated by commas, the empty and repeated ones are skipped
+fn parse_labels(s: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for l in s.split(',').map(str::trim).filter(|l| !l.is_empty()) {
+        if !result.iter().any(|r| r == l) {
+            result.push(l.to_string());
+        }
+    }
+    result
+}
+
+// The names of the project and its parents joined from the root one
+fn project_path(projects: &[Box<dyn ProjectTrait>], id: &str) -> String {
+    let by_id = projects
+        .iter()
+        .map(|p| (p.id(), p))
+        .collect::<HashMap<String, &Box<dyn ProjectTrait>>>();
+
+    let mut names = Vec::new();
+    let mut current = by_id.get(id);
+    while let Some(p) = current {
+        if names.len() > by_id.len() {
+            // the parents are looped
+            break;
+        }
+        names.push(p.name());
+        current = p.parent_id().and_then(|parent| by_id.get(&parent));
+    }
+    names.reverse();
+    names.join(" / ")
+}
+
 #[async_trait]
 impl WidgetTrait for Dialog {
     async fn render(&mut self, area: Rect, buf: &mut Buffer) {
@@ -441,6 +595,7 @@ impl WidgetTrait for Dialog {
 
         self.task_name_caption.set_size(inner_area.as_size());
         self.task_description_caption.set_size(inner_area.as_size());
+        self.labels_caption.set_size(inner_area.as_size());
         self.task_description_editor.set_size(Size::new(inner_area.width, 5));
         self.batch_name_editor.set_size(Size::new(inner_area.width, 8));
 
@@ -451,6 +606,8 @@ impl WidgetTrait for Dialog {
             task_description_caption_area,
             task_description_editor_area,
             priority_and_due_area,
+            labels_caption_area,
+            labels_editor_area,
             _,
             buttons_area,
         ] = Layout::vertical([
@@ -472,6 +629,8 @@ impl WidgetTrait for Dialog {
                 self.task_description_editor.size().height
             }),
             Constraint::Length(self.priority_selector.size().height),
+            Constraint::Length(self.labels_caption.size().height),
+            Constraint::Length(self.labels_editor.size().height),
             Constraint::Fill(1),
             Constraint::Length(self.create_task_button.size().height),
         ])
@@ -531,6 +690,8 @@ impl WidgetTrait for Dialog {
             (&mut self.task_description_editor, task_description_editor_area),
             (&mut self.priority_selector, priority_area),
             (&mut self.due_date_selector, due_date_area),
+            (&mut self.labels_caption, labels_caption_area),
+            (&mut self.labels_editor, labels_editor_area),
         ];
 
         // the active should render last
@@ -556,6 +717,7 @@ impl WidgetTrait for Dialog {
         self.task_name_editor.set_draw_helper(dh.clone());
         self.task_description_editor.set_draw_helper(dh.clone());
         self.batch_name_editor.set_draw_helper(dh.clone());
+        self.labels_editor.set_draw_helper(dh.clone());
         self.draw_helper = Some(dh);
     }
 
@@ -564,7 +726,7 @@ impl WidgetTrait for Dialog {
     }
 
     fn min_size(&self) -> Size {
-        Size::new(90, 23)
+        Size::new(90, 25)
     }
 
     fn size(&self) -> Size {
@@ -666,6 +828,10 @@ impl KeyboardHandler for Dialog {
             return true;
         }
 
+        if self.labels_editor.is_active() && self.labels_editor.handle_key(key).await {
+            return true;
+        }
+
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') => {
                 self.should_be_closed = true;
@@ -688,6 +854,24 @@ impl KeyboardHandler for Dialog {
 
         true
     }
+
+    async fn handle_paste(&mut self, text: &str) -> bool {
+        let editor: &mut (dyn KeyboardHandler + Send) = if self.task_name_editor.is_active() {
+            &mut self.task_name_editor
+        } else if self.task_description_editor.is_active() {
+            &mut self.task_description_editor
+        } else if self.batch_name_editor.is_active() {
+            &mut self.batch_name_editor
+        } else if self.labels_editor.is_active() {
+            &mut self.labels_editor
+        } else {
+            return false;
+        };
+
+        let handled = editor.handle_paste(text).await;
+        self.update_enabled_state().await;
+        handled
+    }
 }
 
 #[async_trait]
@@ -742,3 +926,15 @@ impl StatefulObject for Dialog {
         self.update_enabled_state().await;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_labels_test() {
+        assert_eq!(parse_labels("work, home ,work,,  "), vec!["work", "home"]);
+        assert_eq!(parse_labels("Nextcloud Tasks"), vec!["Nextcloud Tasks"]);
+        assert!(parse_labels(" , ").is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+// SPDX-License-Identifier: MIT
+
+use std::any::Any;
+
+use super::DialogTrait;
+use crate::ui::{
+    keyboard_handler::KeyboardHandler,
+    mouse_handler::MouseHandler,
+    style,
+    widgets::{WidgetState, WidgetStateTrait, WidgetTrait},
+};
+use async_trait::async_trait;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect, Size},
+    text::Line,
+    widgets::{Block, Borders, Paragraph, Widget, Wrap},
+};
+
+const MIN_WIDTH: u16 = 40;
+const MAX_TEXT_HEIGHT: u16 = 20;
+
+// The read-only popup with the full description of the task
+pub struct Dialog {
+    title: String,
+    lines: Vec<String>,
+    scroll: u16,
+    width: u16,
+    should_be_closed: bool,
+    widget_state: WidgetState,
+}
+crate::impl_widget_state_trait!(Dialog);
+
+impl Dialog {
+    pub fn new(title: &str, description: &str) -> Self {
+        let lines = if description.trim().is_empty() {
+            vec!["There is no description".to_string()]
+        } else {
+            description.lines().map(|l| l.to_string()).collect()
+        };
+
+        Self {
+            title: format!(" {title} "),
+            lines,
+            scroll: 0,
+            width: MIN_WIDTH,
+            should_be_closed: false,
+            widget_state: WidgetState::default(),
+        }
+    }
+
+    // The height of the text wrapped by the current width
+    fn text_height(&self) -> u16 {
+        let width = self.width.saturating_sub(2).max(1) as usize;
+        self.lines
+            .iter()
+            .map(|l| Line::raw(l.as_str()).width().div_ceil(width).max(1) as u16)
+            .sum()
+    }
+
+    fn visible_text_height(&self) -> u16 {
+        self.text_height().min(MAX_TEXT_HEIGHT)
+    }
+
+    fn scroll_by(&mut self, delta: i32) {
+        let max_scroll = self.text_height().saturating_sub(self.visible_text_height()) as i32;
+        self.scroll = (self.scroll as i32 + delta).clamp(0, max_scroll) as u16;
+    }
+}
+
+#[async_trait]
+impl WidgetTrait for Dialog {
+    async fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let b = Block::default()
+            .style(style::default_style())
+            .title_top(self.title.as_str())
+            .title_bottom("j/k to scroll, q or Esc to close")
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(style::border_color());
+
+        Paragraph::new(self.lines.join("\n"))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0))
+            .block(b)
+            .render(area, buf);
+    }
+
+    fn min_size(&self) -> Size {
+        Size::new(MIN_WIDTH, self.visible_text_height() + 2)
+    }
+
+    fn size(&self) -> Size {
+        Size::new(self.width, self.visible_text_height() + 2)
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.width = size.width.max(MIN_WIDTH);
+        self.scroll_by(0);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl DialogTrait for Dialog {
+    fn accepted(&self) -> bool {
+        false
+    }
+
+    fn should_be_closed(&self) -> bool {
+        self.should_be_closed
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl KeyboardHandler for Dialog {
+    async fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let page = self.visible_text_height() as i32;
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => self.should_be_closed = true,
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_by(1),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_by(-1),
+            KeyCode::PageDown => self.scroll_by(page),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::Char('g') | KeyCode::Home => self.scroll = 0,
+            KeyCode::Char('G') | KeyCode::End => self.scroll_by(i32::from(u16::MAX)),
+            _ => {}
+        }
+
+        true
+    }
+}
+
+#[async_trait]
+impl MouseHandler for Dialog {
+    async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
+}
@@ -6,7 +6,7 @@ use super::DialogTrait;
 use crate::ui::{
     keyboard_handler::KeyboardHandler,
     mouse_handler::MouseHandler,
-    shortcut::SharedData,
+    shortcut::{SharedData, keys_to_str},
     style,
     widgets::{WidgetState, WidgetStateTrait, WidgetTrait},
 };
@@ -29,23 +29,12 @@ struct Shortcut {
 pub struct Dialog {
     active_block_shortcuts: Vec<Shortcut>,
     global_shortcuts: Vec<Shortcut>,
+    conflicts: Vec<String>,
     should_be_closed: bool,
     widget_state: WidgetState,
 }
 crate::impl_widget_state_trait!(Dialog);
 
-fn keys_to_str(keys: &[char]) -> String {
-    let mut s = String::new();
-    for c in keys {
-        if *c == ' ' {
-            s.push_str("space ");
-        } else {
-            s.push(*c)
-        }
-    }
-    s
-}
-
 fn shared_data_to_shortcut(s: &ArcRwLockBlocked<SharedData>) -> Shortcut {
     let d = s.read().unwrap();
     Shortcut {
@@ -58,6 +47,7 @@ impl Dialog {
     pub fn new(
         active_block_shortcuts: &[ArcRwLockBlocked<SharedData>],
         global_shortcuts: &[ArcRwLockBlocked<SharedData>],
+        conflicts: &[String],
     ) -> Self {
         let mut active: Vec<Shortcut> = active_block_shortcuts.iter().map(shared_data_to_shortcut).collect();
         let mut global: Vec<Shortcut> = global_shortcuts.iter().map(shared_data_to_shortcut).collect();
@@ -68,10 +58,19 @@ impl Dialog {
         Self {
             active_block_shortcuts: active,
             global_shortcuts: global,
+            conflicts: conflicts.to_vec(),
             should_be_closed: false,
             widget_state: WidgetState::default(),
         }
     }
+
+    fn conflicts_height(&self) -> u16 {
+        if self.conflicts.is_empty() {
+            0
+        } else {
+            self.conflicts.len() as u16 + 1 /*subhead*/
+        }
+    }
 }
 
 #[async_trait]
@@ -93,9 +92,10 @@ impl WidgetTrait for Dialog {
         area.x += 1;
         area.width -= 2;
 
-        let [active_area, global_area] = Layout::vertical([
+        let [active_area, global_area, conflicts_area] = Layout::vertical([
             Constraint::Length(self.active_block_shortcuts.len() as u16 + 1),
             Constraint::Fill(1),
+            Constraint::Length(self.conflicts_height()),
         ])
         .areas(area);
 
@@ -136,11 +136,24 @@ impl WidgetTrait for Dialog {
             .title_alignment(ratatui::layout::Alignment::Center)
             .title_top("Global shortcuts");
         List::new(global_items).block(global_block).render(global_area, buf);
+
+        if !self.conflicts.is_empty() {
+            let conflicts_block = Block::default()
+                .title_alignment(Alignment::Center)
+                .title_top("Conflicts");
+            List::new(
+                self.conflicts
+                    .iter()
+                    .map(|c| Line::styled(c.as_str(), style::warning_text_style())),
+            )
+            .block(conflicts_block)
+            .render(conflicts_area, buf);
+        }
     }
 
     fn size(&self) -> Size {
         let count = (self.active_block_shortcuts.len() + self.global_shortcuts.len()) as u16;
-        Size::new(70, count + 2/*head_tail*/ * 2 /*subheads*/)
+        Size::new(70, count + 2/*head_tail*/ * 2 /*subheads*/ + self.conflicts_height())
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -4,6 +4,7 @@ use std::{any::Any, fmt::Display, sync::Arc};
 
 use super::DialogTrait;
 use crate::ui::{
+    fuzzy,
     keyboard_handler::KeyboardHandler,
     mouse_handler::MouseHandler,
     selectable_list::SelectableList,
@@ -19,11 +20,19 @@ use ratatui::{
     widgets::{Block, Borders, ListItem, Widget},
 };
 
-const FOOTER: &str = "Use j/k (up/down) for moving and Enter for applying";
+const FOOTER: &str = "Use j/k (up/down) for moving, / for searching and Enter for applying";
+const SEARCH_TITLE: &str = "Type to search: ";
+const SEARCH_MIN_WIDTH: usize = 20;
 
 pub struct Dialog<T> {
     title: String,
     width: u16,
+    all_items: Vec<T>,
+    // the indexes of the shown items in all_items
+    visible: Vec<usize>,
+    // the typed pattern while the items are searched
+    search: Option<String>,
+    is_always_searchable: bool,
     items: SelectableList<T>,
     custom_widgets: Vec<Arc<dyn WidgetTrait>>,
     should_be_closed: bool,
@@ -67,6 +76,10 @@ where
         let mut s = Self {
             title: format!("Current value: {current}"),
             width: 0,
+            all_items: items.to_vec(),
+            visible: (0..items.len()).collect(),
+            search: None,
+            is_always_searchable: false,
             items: SelectableList::new(
                 items.to_vec(),
                 items.iter().position(|s| s.to_string() == current).or(Some(0)),
@@ -100,9 +113,48 @@ where
         if self.show_bottom_title {
             w = w.max(Text::from(FOOTER).width());
         }
+        if self.search.is_some() {
+            w = w.max(Text::from(SEARCH_TITLE).width() + SEARCH_MIN_WIDTH);
+        }
         self.width = w as u16;
     }
 
+    // Typing filters the items without pressing / first, so the items are navigated with the arrows only
+    pub fn searchable(mut self, is_searchable: bool) -> Self {
+        self.is_always_searchable = is_searchable;
+        self.search = is_searchable.then(String::new);
+        self.calculate_width();
+        self
+    }
+
+    fn start_search(&mut self) {
+        self.search = Some(String::new());
+        self.calculate_width();
+    }
+
+    fn stop_search(&mut self) {
+        self.search = self.is_always_searchable.then(String::new);
+        self.apply_search();
+        self.calculate_width();
+    }
+
+    // The custom widgets' items are always shown
+    fn apply_search(&mut self) {
+        let pattern = self.search.clone().unwrap_or_default();
+        let regular_count = self.all_items.len() - self.custom_widgets.len();
+        let texts = self.all_items[..regular_count]
+            .iter()
+            .map(|i| i.to_string())
+            .collect::<Vec<String>>();
+        self.visible = fuzzy::filter(&pattern, texts.iter().map(String::as_str));
+        self.visible.extend(regular_count..self.all_items.len());
+
+        self.items
+            .set_items(self.visible.iter().map(|i| self.all_items[*i].clone()).collect());
+        self.items
+            .set_selected_index(if self.visible.is_empty() { None } else { Some(0) });
+    }
+
     pub fn show_top_title(mut self, is_show: bool) -> Self {
         self.show_top_title = is_show;
         self.calculate_width();
@@ -123,9 +175,10 @@ where
         }
     }
 
+    // The index in the items that the dialog was created with
     pub fn selected_index(&self) -> Option<usize> {
         if self.item_has_chosen {
-            self.items.selected_index()
+            self.items.selected_index().and_then(|i| self.visible.get(i).copied())
         } else {
             None
         }
@@ -137,6 +190,8 @@ where
     }
 
     pub fn add_custom_widget(&mut self, item: T, w: Arc<dyn WidgetTrait>) {
+        self.all_items.push(item.clone());
+        self.visible.push(self.all_items.len() - 1);
         self.items.add_item(item);
         self.custom_widgets.push(w);
         self.calculate_width();
@@ -146,6 +201,14 @@ where
         self.custom_widgets.clone()
     }
 
+    // The custom widgets can take more than their items' rows, e.g. the calendar
+    fn custom_widgets_extra_height(&self) -> u16 {
+        self.custom_widgets
+            .iter()
+            .map(|w| w.size().height.saturating_sub(1))
+            .sum()
+    }
+
     fn current_custom_widget_index(&self) -> Option<usize> {
         if let Some(idx) = self.items.selected_index()
             && idx < self.items.len()
@@ -170,7 +233,9 @@ where
             .title_alignment(ratatui::layout::Alignment::Center)
             .borders(Borders::ALL)
             .border_style(style::border_color());
-        if self.show_top_title {
+        if let Some(search) = &self.search {
+            b = b.title_top(format!("{SEARCH_TITLE}{search}"));
+        } else if self.show_top_title {
             b = b.title_top(self.title.as_str());
         }
         if self.show_bottom_title {
@@ -185,20 +250,24 @@ where
         );
 
         let inner_area = b.inner(area);
-        self.items
-            .render("", |s| ListItem::from(s.to_string()), inner_area, buf);
+        let mut list_area = inner_area;
+        list_area.height = list_area.height.saturating_sub(self.custom_widgets_extra_height());
+        self.items.render("", |s| ListItem::from(s.to_string()), list_area, buf);
 
         let custom_widgets_len = self.custom_widgets.len() as u16;
-        let custom_widgets_y = inner_area.y + inner_area.height - custom_widgets_len;
+        let mut custom_widget_y = list_area.y + list_area.height - custom_widgets_len;
         for (i, w) in self.custom_widgets.iter_mut().enumerate() {
             let item_index = self.items.len() - custom_widgets_len as usize + i;
             let text_width = Text::from(self.items.iter().nth(item_index).unwrap().to_string()).width() as u16;
+            let height = w.size().height.max(1);
             let rect = Rect::new(
                 inner_area.x + text_width + 2,
-                custom_widgets_y + i as u16,
-                inner_area.width,
-                1,
-            );
+                custom_widget_y,
+                inner_area.width.saturating_sub(text_width + 2),
+                height,
+            )
+            .intersection(inner_area);
+            custom_widget_y += height;
 
             Arc::get_mut(w).unwrap().render(rect, buf).await;
         }
@@ -206,8 +275,17 @@ where
 
     fn size(&self) -> Size {
         let mut s = self.items.size();
-        s.height += 2;
+        s.height += 2 + self.custom_widgets_extra_height();
         s.width = self.width + 1/*selector*/ + 2 /*borders*/;
+
+        let custom_widgets_width = self
+            .custom_widgets
+            .iter()
+            .zip(self.items.iter().skip(self.items.len() - self.custom_widgets.len()))
+            .map(|(w, item)| Text::from(item.to_string()).width() as u16 + 2 + w.size().width + 2)
+            .max()
+            .unwrap_or_default();
+        s.width = s.width.max(custom_widgets_width);
         s
     }
 
@@ -253,17 +331,40 @@ where
             }
         }
 
+        if let Some(search) = &mut self.search {
+            match key.code {
+                KeyCode::Char(ch) => {
+                    search.push(ch);
+                    self.apply_search();
+                    return true;
+                }
+                KeyCode::Backspace => {
+                    search.pop();
+                    self.apply_search();
+                    return true;
+                }
+                KeyCode::Esc if !search.is_empty() || !self.is_always_searchable => {
+                    self.stop_search();
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
         match key.code {
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.should_be_closed = true;
             }
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::PageDown => self.items.select_next_page(),
+            KeyCode::PageUp => self.items.select_previous_page(),
             KeyCode::Char('j') | KeyCode::Char('n') | KeyCode::Down => self.items.select_next().await,
             KeyCode::Char('k') | KeyCode::Char('p') | KeyCode::Up => self.items.select_previous().await,
             KeyCode::Char('g') | KeyCode::Home => self.items.select_first().await,
             KeyCode::Char('G') | KeyCode::End => self.items.select_last().await,
             KeyCode::Enter => {
                 self.should_be_closed = true;
-                self.item_has_chosen = true;
+                self.item_has_chosen = self.items.selected_index().is_some();
             }
             KeyCode::Char('l') | KeyCode::Right => {
                 if let Some(idx) = self.current_custom_widget_index() {
@@ -4,6 +4,7 @@ use std::{any::Any, fmt::Display};
 
 use super::DialogTrait;
 use crate::ui::{
+    fuzzy,
     keyboard_handler::KeyboardHandler,
     mouse_handler::MouseHandler,
     selectable_list::SelectableList,
@@ -19,11 +20,18 @@ use ratatui::{
     widgets::{Block, Borders, ListItem, Widget},
 };
 
-const FOOTER: &str = "Use j/k (up/down) for moving, Space for selecting and Enter for applying";
+const FOOTER: &str = "Use j/k (up/down) for moving, / for searching, Space for selecting, a/i/c for selecting all/inverting/clearing and Enter for applying";
+const SEARCH_TITLE: &str = "Type to search: ";
+const SEARCH_MIN_WIDTH: usize = 20;
 
 pub struct Dialog<T> {
     title: String,
     width: u16,
+    all_items: Vec<T>,
+    // the typed pattern while the items are searched
+    search: Option<String>,
+    // Enter finishes typing, so the found items can be selected
+    is_typing: bool,
     items: SelectableList<T>,
     selected: Vec<T>,
     should_be_closed: bool,
@@ -68,6 +76,9 @@ where
         let mut s = Self {
             title: "Select one or several items".to_string(),
             width: 0,
+            all_items: items.to_vec(),
+            search: None,
+            is_typing: false,
             items: SelectableList::new(items.to_vec(), Some(0)),
             selected: Vec::new(),
             should_be_closed: false,
@@ -99,6 +110,15 @@ where
     pub fn selected(&self) -> Vec<T> {
         self.selected.clone()
     }
+
+    fn header(&self) -> String {
+        format!(
+            "{} ({} of {} selected)",
+            self.title,
+            self.selected.len(),
+            self.all_items.len()
+        )
+    }
 }
 
 impl<T> Dialog<T>
@@ -113,13 +133,34 @@ where
             .max()
             .unwrap_or_default();
         if self.show_top_title {
-            w = w.max(Text::from(self.title.as_str()).width());
+            w = w.max(Text::from(self.header()).width());
         }
         if self.show_bottom_title {
             w = w.max(Text::from(FOOTER).width());
         }
+        if self.search.is_some() {
+            w = w.max(Text::from(SEARCH_TITLE).width() + SEARCH_MIN_WIDTH);
+        }
         self.width = w as u16;
     }
+
+    // The selected items stay selected even if they're hidden by the search
+    fn apply_search(&mut self) {
+        let pattern = self.search.clone().unwrap_or_default();
+        let texts = self.all_items.iter().map(|i| i.to_string()).collect::<Vec<String>>();
+        let visible = fuzzy::filter(&pattern, texts.iter().map(String::as_str));
+
+        self.items
+            .set_items(visible.iter().map(|i| self.all_items[*i].clone()).collect());
+        self.items
+            .set_selected_index(if visible.is_empty() { None } else { Some(0) });
+    }
+
+    fn set_search(&mut self, search: Option<String>) {
+        self.search = search;
+        self.apply_search();
+        self.calculate_width();
+    }
 }
 
 #[async_trait]
@@ -133,8 +174,10 @@ where
             .title_alignment(ratatui::layout::Alignment::Center)
             .borders(Borders::ALL)
             .border_style(style::border_color());
-        if self.show_top_title {
-            b = b.title_top(self.title.as_str());
+        if let Some(search) = &self.search {
+            b = b.title_top(format!("{SEARCH_TITLE}{search}"));
+        } else if self.show_top_title {
+            b = b.title_top(self.header());
         }
         if self.show_bottom_title {
             b = b.title_bottom(FOOTER);
@@ -197,16 +240,65 @@ where
     T: Display + PartialEq + Send + Sync + Clone + 'static,
 {
     async fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(search) = &self.search
+            && self.is_typing
+        {
+            let mut search = search.clone();
+            match key.code {
+                KeyCode::Char(ch) => {
+                    search.push(ch);
+                    self.set_search(Some(search));
+                    return true;
+                }
+                KeyCode::Backspace => {
+                    search.pop();
+                    self.set_search(Some(search));
+                    return true;
+                }
+                KeyCode::Enter => {
+                    self.is_typing = false;
+                    return true;
+                }
+                KeyCode::Esc => {
+                    self.is_typing = false;
+                    self.set_search(None);
+                    return true;
+                }
+                _ => {}
+            }
+        }
+
         match key.code {
+            KeyCode::Esc if self.search.is_some() => self.set_search(None),
             KeyCode::Char('q') | KeyCode::Esc => {
                 self.should_be_closed = true;
             }
+            KeyCode::Char('/') => {
+                self.is_typing = true;
+                self.set_search(Some(String::new()));
+            }
+            KeyCode::PageDown => self.items.select_next_page(),
+            KeyCode::PageUp => self.items.select_previous_page(),
             KeyCode::Char('j') | KeyCode::Char('n') | KeyCode::Down => self.items.select_next().await,
             KeyCode::Char('k') | KeyCode::Char('p') | KeyCode::Up => self.items.select_previous().await,
             KeyCode::Char('g') | KeyCode::Home => self.items.select_first().await,
             KeyCode::Char('G') | KeyCode::End => self.items.select_last().await,
+            // select all and invert are applied to the shown items only, so they can be combined with the search
             KeyCode::Char('a') => {
-                self.selected = self.items.iter().cloned().collect();
+                for item in self.items.iter() {
+                    if !self.selected.contains(item) {
+                        self.selected.push(item.clone());
+                    }
+                }
+            }
+            KeyCode::Char('i') => {
+                for item in self.items.iter() {
+                    if let Some(idx) = self.selected.iter().position(|s| s == item) {
+                        self.selected.remove(idx);
+                    } else {
+                        self.selected.push(item.clone());
+                    }
+                }
             }
             KeyCode::Char('c') => self.selected.clear(),
             KeyCode::Char(' ') => {
@@ -228,3 +320,43 @@ where
         true
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    async fn press(d: &mut Dialog<String>, keys: &str) {
+        for ch in keys.chars() {
+            d.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)).await;
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn select_all_invert_clear_test() {
+        let items = ["work", "home", "books"].map(String::from);
+        let mut d = Dialog::new(&items);
+        assert_eq!(d.header(), "Select one or several items (0 of 3 selected)");
+
+        press(&mut d, " ").await;
+        assert_eq!(d.selected(), vec!["work"]);
+
+        press(&mut d, "i").await;
+        assert_eq!(d.selected(), vec!["home", "books"]);
+
+        press(&mut d, "a").await;
+        assert_eq!(d.selected(), vec!["home", "books", "work"]);
+        assert_eq!(d.header(), "Select one or several items (3 of 3 selected)");
+
+        press(&mut d, "c").await;
+        assert!(d.selected().is_empty());
+
+        // only the found items are inverted
+        press(&mut d, "/oo").await;
+        d.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)).await;
+        press(&mut d, "i").await;
+        assert_eq!(d.selected(), vec!["books"]);
+        assert!(!d.should_be_closed());
+    }
+}
@@ -0,0 +1,237 @@
+// SPDX-License-Identifier: MIT
+
+use std::any::Any;
+
+use super::DialogTrait;
+use crate::ui::{
+    draw_helper::DrawHelper,
+    keyboard_handler::KeyboardHandler,
+    mouse_handler::MouseHandler,
+    order_changer::OrderChanger,
+    style,
+    widgets::{Button, WidgetState, WidgetStateTrait, WidgetTrait},
+};
+use async_trait::async_trait;
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect, Size},
+    text::Text,
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use tatuin_core::provider::SyncState;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, strum::Display)]
+pub enum Action {
+    #[strum(to_string = "Clear cache")]
+    ClearCache,
+    #[strum(to_string = "Resync")]
+    Resync,
+    #[strum(to_string = "Close")]
+    Close,
+}
+
+struct ActionButton {
+    action: Action,
+    widget: Button,
+}
+
+pub struct Dialog {
+    title: String,
+    lines: Vec<(&'static str, String)>,
+    buttons: Vec<ActionButton>,
+    action: Option<Action>,
+    should_be_closed: bool,
+    widget_state: WidgetState,
+}
+crate::impl_widget_state_trait!(Dialog);
+
+impl Dialog {
+    pub fn new(provider_name: &str, type_name: &str, st: &SyncState, pending_changes: usize) -> Self {
+        let lines = vec![
+            ("Provider", format!("{provider_name} ({type_name})")),
+            (
+                "Cache",
+                st.cache_path
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "in memory".to_string()),
+            ),
+            ("Cached objects", st.cached_objects.to_string()),
+            (
+                "Last sync",
+                st.last_sync
+                    .map(|d| d.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "never".to_string()),
+            ),
+            ("Pending changes", pending_changes.to_string()),
+        ];
+
+        let buttons = [Action::ClearCache, Action::Resync, Action::Close]
+            .iter()
+            .map(|a| {
+                let mut button = Button::new(a.to_string().as_str());
+                button.set_active(*a == Action::Close);
+                ActionButton {
+                    action: *a,
+                    widget: button,
+                }
+            })
+            .collect();
+
+        Self {
+            title: "Sync state".to_string(),
+            lines,
+            buttons,
+            action: None,
+            should_be_closed: false,
+            widget_state: WidgetState::default(),
+        }
+    }
+
+    pub fn action(&self) -> Option<Action> {
+        self.action
+    }
+
+    fn text(&self) -> String {
+        let title_width = self.lines.iter().map(|(t, _)| t.len()).max().unwrap_or_default();
+        self.lines
+            .iter()
+            .map(|(t, v)| format!("{t:>title_width$}: {v}"))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn order_calculator(&mut self) -> OrderChanger<'_> {
+        OrderChanger::new(
+            self.buttons
+                .iter_mut()
+                .map(|b| &mut b.widget as &'_ mut dyn WidgetTrait)
+                .collect(),
+        )
+    }
+}
+
+#[async_trait]
+impl WidgetTrait for Dialog {
+    async fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let b = Block::default()
+            .title_top(self.title.as_str())
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(style::border_color());
+
+        let buttons_height = self
+            .buttons
+            .iter()
+            .map(|b| b.widget.size().height)
+            .max()
+            .unwrap_or_default();
+        let [text_area, _, buttons_area] = Layout::vertical([
+            Constraint::Length(self.lines.len() as u16),
+            Constraint::Length(1),
+            Constraint::Length(buttons_height),
+        ])
+        .areas(b.inner(area));
+        b.render(area, buf);
+
+        Paragraph::new(self.text()).render(text_area, buf);
+
+        let all_buttons_width: u16 = self
+            .buttons
+            .iter()
+            .map(|b| b.widget.size().width + 1 /*separate*/)
+            .sum();
+        let mut offset = buttons_area.width.saturating_sub(all_buttons_width) / 2;
+        for b in &mut self.buttons {
+            let w = b.widget.size().width;
+            b.widget
+                .render(
+                    Rect {
+                        x: buttons_area.x + offset,
+                        y: buttons_area.y,
+                        width: w,
+                        height: buttons_area.height,
+                    },
+                    buf,
+                )
+                .await;
+            offset += w + 1;
+        }
+    }
+
+    fn set_draw_helper(&mut self, dh: DrawHelper) {
+        for b in &mut self.buttons {
+            b.widget.set_draw_helper(dh.clone());
+        }
+    }
+
+    fn size(&self) -> Size {
+        let buttons_width: u16 = self.buttons.iter().map(|b| b.widget.size().width + 1).sum();
+        let width = (Text::raw(self.text()).width() as u16).max(buttons_width);
+        let mut height = self.lines.len() as u16;
+        height += 1; // empty line
+        height += self
+            .buttons
+            .iter()
+            .map(|b| b.widget.size().height)
+            .max()
+            .unwrap_or_default();
+
+        Size::new(width + 2, height + 2)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl DialogTrait for Dialog {
+    fn accepted(&self) -> bool {
+        self.action.is_some_and(|a| a != Action::Close)
+    }
+
+    fn should_be_closed(&self) -> bool {
+        self.should_be_closed
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl KeyboardHandler for Dialog {
+    async fn handle_key(&mut self, key: KeyEvent) -> bool {
+        for b in &mut self.buttons {
+            if b.widget.handle_key(key).await {
+                self.should_be_closed = true;
+                self.action = Some(b.action);
+                return true;
+            }
+        }
+
+        match key.code {
+            KeyCode::Esc => {
+                self.should_be_closed = true;
+                self.action = Some(Action::Close);
+            }
+            KeyCode::Tab => {
+                self.order_calculator().select_next();
+            }
+            KeyCode::BackTab => {
+                self.order_calculator().select_prev();
+            }
+            _ => {}
+        }
+
+        true
+    }
+}
+
+#[async_trait]
+impl MouseHandler for Dialog {
+    async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
+}
@@ -121,6 +121,10 @@ impl KeyboardHandler for Dialog {
         }
         true
     }
+
+    async fn handle_paste(&mut self, text: &str) -> bool {
+        self.edit.handle_paste(text).await
+    }
 }
 
 #[async_trait]
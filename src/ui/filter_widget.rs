@@ -184,6 +184,10 @@ impl FilterWidget {
         self.filter.clone()
     }
 
+    pub fn set_filter(&mut self, f: Filter) {
+        self.filter = f;
+    }
+
     pub fn next_block(&mut self) -> bool {
         match self.current_block {
             FilterBlock::State => {
@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+
+const MATCH_SCORE: i64 = 1;
+const CONSECUTIVE_BONUS: i64 = 5;
+const WORD_START_BONUS: i64 = 3;
+
+// Returns the score of the text if the pattern is its case-insensitive subsequence.
+// The consecutive characters and the characters at the word starts score more, the gaps score less.
+pub fn score(pattern: &str, text: &str) -> Option<i64> {
+    let text = text.to_lowercase().chars().collect::<Vec<char>>();
+    let mut result = 0;
+    let mut pos = 0;
+    let mut prev: Option<usize> = None;
+
+    for ch in pattern.to_lowercase().chars() {
+        let idx = (pos..text.len()).find(|i| text[*i] == ch)?;
+        result += MATCH_SCORE;
+        if prev.is_some_and(|p| p + 1 == idx) {
+            result += CONSECUTIVE_BONUS;
+        }
+        if idx == 0 || !text[idx - 1].is_alphanumeric() {
+            result += WORD_START_BONUS;
+        }
+        result -= (idx - pos) as i64;
+
+        prev = Some(idx);
+        pos = idx + 1;
+    }
+
+    Some(result)
+}
+
+// Returns the indexes of the matched texts, the best matches first
+pub fn filter<'a>(pattern: &str, texts: impl Iterator<Item = &'a str>) -> Vec<usize> {
+    let mut matched = texts
+        .enumerate()
+        .filter_map(|(i, t)| score(pattern, t).map(|s| (i, s)))
+        .collect::<Vec<(usize, i64)>>();
+    matched.sort_by_key(|(_, s)| std::cmp::Reverse(*s));
+    matched.into_iter().map(|(i, _)| i).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn score_test() {
+        struct Case<'a> {
+            name: &'a str,
+            pattern: &'a str,
+            text: &'a str,
+            matched: bool,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "empty pattern",
+                pattern: "",
+                text: "Inbox",
+                matched: true,
+            },
+            Case {
+                name: "subsequence",
+                pattern: "wrk",
+                text: "Work / Reports",
+                matched: true,
+            },
+            Case {
+                name: "case insensitive",
+                pattern: "INB",
+                text: "Inbox",
+                matched: true,
+            },
+            Case {
+                name: "wrong order",
+                pattern: "xobni",
+                text: "Inbox",
+                matched: false,
+            },
+            Case {
+                name: "absent character",
+                pattern: "inz",
+                text: "Inbox",
+                matched: false,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                score(c.pattern, c.text).is_some(),
+                c.matched,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn filter_test() {
+        let texts = ["Personal / Shopping", "Home", "Work / Shopping list", "Books"];
+        assert_eq!(filter("sho", texts.iter().copied()), vec![2, 0]);
+        assert_eq!(filter("ho", texts.iter().copied()), vec![1, 2, 0]);
+        assert_eq!(filter("", texts.iter().copied()), vec![0, 1, 2, 3]);
+        assert!(filter("xyz", texts.iter().copied()).is_empty());
+    }
+}
@@ -24,6 +24,10 @@ impl KeyBuffer {
     pub fn is_empty(&self) -> bool {
         self.keys.is_empty()
     }
+
+    pub fn keys(&self) -> &[char] {
+        &self.keys
+    }
 }
 
 impl std::fmt::Display for KeyBuffer {
@@ -6,4 +6,9 @@ use crossterm::event::KeyEvent;
 #[async_trait]
 pub trait KeyboardHandler {
     async fn handle_key(&mut self, key: KeyEvent) -> bool;
+
+    // The bracketed paste comes as one text, so its newlines aren't handled as Enter presses
+    async fn handle_paste(&mut self, _text: &str) -> bool {
+        false
+    }
 }
@@ -0,0 +1,432 @@
+// SPDX-License-Identifier: MIT
+
+// The providers can be grouped by the `group` key of their settings (e.g. Work and Personal).
+// The groups are the collapsible sections of the list, the excluded groups are hidden from the "All" view.
+
+use super::{
+    AppBlockWidget,
+    keyboard_handler::KeyboardHandler,
+    list,
+    mouse_handler::MouseHandler,
+    shortcut::Shortcut,
+    tasks_widget::ProvidersStorage,
+    widgets::{WidgetState, WidgetStateTrait, WidgetTrait},
+};
+use crate::provider::Provider;
+use async_trait::async_trait;
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Size},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{ListItem, ListState, StatefulWidget},
+};
+use std::{
+    any::Any,
+    collections::BTreeSet,
+    slice::{Iter, IterMut},
+};
+use tatuin_core::state::{State, StatefulObject};
+
+const DEFAULT_WIDTH: u16 = 10;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Row {
+    All,
+    Group(String),
+    // the index of the provider
+    Provider(usize),
+}
+
+// The ungrouped providers go first, then the groups sorted by name with their providers
+fn rows(groups: &[Option<String>], collapsed: &BTreeSet<String>) -> Vec<Row> {
+    let mut result = vec![Row::All];
+    result.extend(
+        groups
+            .iter()
+            .enumerate()
+            .filter(|(_, g)| g.is_none())
+            .map(|(i, _)| Row::Provider(i)),
+    );
+
+    let names = groups.iter().flatten().collect::<BTreeSet<&String>>();
+    for name in names {
+        result.push(Row::Group(name.to_string()));
+        if !collapsed.contains(name) {
+            result.extend(
+                groups
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, g)| g.as_ref() == Some(name))
+                    .map(|(i, _)| Row::Provider(i)),
+            );
+        }
+    }
+
+    result
+}
+
+pub struct ProvidersWidget {
+    providers: Vec<Provider>,
+    collapsed: BTreeSet<String>,
+    excluded: BTreeSet<String>,
+    state: ListState,
+    shortcut: Shortcut,
+    // the shortcuts that work in the block only
+    block_shortcuts: Vec<Shortcut>,
+    widget_state: WidgetState,
+
+    width: u16,
+}
+
+impl ProvidersWidget {
+    pub fn new(providers: Vec<Provider>, shortcut: Shortcut) -> Self {
+        Self {
+            providers,
+            collapsed: BTreeSet::new(),
+            excluded: BTreeSet::new(),
+            state: ListState::default().with_selected(Some(0)),
+            shortcut,
+            block_shortcuts: Vec::new(),
+            widget_state: WidgetState::default(),
+            width: DEFAULT_WIDTH, // will be recalculated after the first render
+        }
+    }
+
+    pub fn block_shortcut(mut self, s: Shortcut) -> Self {
+        self.block_shortcuts.push(s);
+        self
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let groups = self.providers.iter().map(|p| p.group.clone()).collect::<Vec<_>>();
+        rows(&groups, &self.collapsed)
+    }
+
+    // The count of the shown rows
+    pub fn len(&self) -> usize {
+        self.rows().len()
+    }
+
+    fn selected_row(&self) -> Option<Row> {
+        let rows = self.rows();
+        let idx = self.state.selected()?.min(rows.len() - 1);
+        rows.into_iter().nth(idx)
+    }
+
+    pub fn selected(&self) -> Option<&Provider> {
+        match self.selected_row()? {
+            Row::Provider(i) => self.providers.get(i),
+            _ => None,
+        }
+    }
+
+    // The group of the selected header or of the selected provider
+    fn selected_group(&self) -> Option<String> {
+        match self.selected_row()? {
+            Row::Group(g) => Some(g),
+            Row::Provider(i) => self.providers[i].group.clone(),
+            Row::All => None,
+        }
+    }
+
+    fn group_providers(&self, group: &str) -> Vec<String> {
+        self.providers
+            .iter()
+            .filter(|p| p.group.as_deref() == Some(group))
+            .map(|p| p.name.to_string())
+            .collect()
+    }
+
+    // The providers the tasks are shown for, the empty list means all of them
+    pub fn selected_providers(&self) -> Vec<String> {
+        match self.selected_row() {
+            Some(Row::Provider(i)) => vec![self.providers[i].name.to_string()],
+            Some(Row::Group(g)) => self.group_providers(&g),
+            Some(Row::All) | None => Vec::new(),
+        }
+    }
+
+    // The providers of the excluded groups, they're shown only if they're selected explicitly
+    pub fn excluded_providers(&self) -> Vec<String> {
+        let selected = self.selected_providers();
+        self.excluded
+            .iter()
+            .flat_map(|g| self.group_providers(g))
+            .filter(|p| !selected.contains(p))
+            .collect()
+    }
+
+    // The selected row is kept, or its group if it's collapsed
+    pub fn toggle_selected_group_collapsed(&mut self) {
+        let Some(group) = self.selected_group() else {
+            return;
+        };
+        if !self.collapsed.remove(&group) {
+            self.collapsed.insert(group.to_string());
+        }
+
+        let selected = self
+            .selected_row()
+            .filter(|r| matches!(r, Row::Group(_)))
+            .unwrap_or(Row::Group(group));
+        let idx = self.rows().iter().position(|r| *r == selected);
+        self.state.select(idx);
+    }
+
+    // Returns whether the group is excluded from the "All" view now
+    pub fn toggle_selected_group_excluded(&mut self) -> Option<bool> {
+        let group = self.selected_group()?;
+        let excluded = !self.excluded.remove(&group);
+        if excluded {
+            self.excluded.insert(group);
+        }
+        Some(excluded)
+    }
+
+    // Only the given groups are shown in All, the empty list shows all of them. Returns the unknown groups
+    pub fn show_groups(&mut self, groups: &[String]) -> Vec<String> {
+        let names = self
+            .providers
+            .iter()
+            .filter_map(|p| p.group.clone())
+            .collect::<BTreeSet<String>>();
+        self.excluded = if groups.is_empty() {
+            BTreeSet::new()
+        } else {
+            names.iter().filter(|g| !groups.contains(g)).cloned().collect()
+        };
+        self.state.select(Some(0));
+        groups.iter().filter(|g| !names.contains(*g)).cloned().collect()
+    }
+
+    fn row_item(&self, r: &Row) -> ListItem<'static> {
+        match r {
+            Row::All => ListItem::from("All"),
+            Row::Group(g) => {
+                let mut text = format!("{} {g}", if self.collapsed.contains(g) { "▸" } else { "▾" });
+                let mut s = Style::default().add_modifier(Modifier::BOLD);
+                if self.excluded.contains(g) {
+                    text.push_str(" (excluded)");
+                    s = s.add_modifier(Modifier::DIM);
+                }
+                ListItem::from(Span::styled(text, s))
+            }
+            Row::Provider(i) => {
+                let p = &self.providers[*i];
+                let indent = if p.group.is_some() { "  " } else { "" };
+                ListItem::from(Span::styled(format!("{indent}{} ({})", p.name, p.type_name), p.color))
+            }
+        }
+    }
+}
+
+impl ProvidersStorage for ProvidersWidget {
+    fn iter_mut<'a>(&'a mut self) -> IterMut<'a, Provider> {
+        self.providers.iter_mut()
+    }
+    fn iter<'a>(&'a self) -> Iter<'a, Provider> {
+        self.providers.iter()
+    }
+    fn provider(&self, name: &str) -> Provider {
+        self.providers
+            .iter()
+            .find(|p| p.name == name)
+            .unwrap_or_else(|| panic!("Provider with name='{name}' not found"))
+            .clone()
+    }
+}
+
+impl WidgetStateTrait for ProvidersWidget {
+    fn is_active(&self) -> bool {
+        self.widget_state.is_active()
+    }
+
+    fn set_active(&mut self, is_active: bool) {
+        self.widget_state.set_active(is_active);
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.widget_state.is_enabled()
+    }
+
+    fn set_enabled(&mut self, is_enabled: bool) {
+        self.widget_state.set_enabled(is_enabled);
+    }
+
+    fn is_visible(&self) -> bool {
+        self.widget_state.is_visible()
+    }
+
+    fn set_visible(&mut self, is_visible: bool) {
+        self.widget_state.set_visible(is_visible);
+    }
+}
+
+#[async_trait]
+impl AppBlockWidget for ProvidersWidget {
+    fn activate_shortcuts(&mut self) -> Vec<&mut Shortcut> {
+        vec![&mut self.shortcut]
+    }
+
+    fn shortcuts(&mut self) -> Vec<&mut Shortcut> {
+        self.block_shortcuts.iter_mut().collect()
+    }
+
+    async fn select_next(&mut self) {
+        self.state.select_next();
+    }
+
+    async fn select_previous(&mut self) {
+        self.state.select_previous();
+    }
+
+    async fn select_first(&mut self) {
+        self.state.select_first();
+    }
+
+    async fn select_last(&mut self) {
+        self.state.select_last();
+    }
+}
+
+#[async_trait]
+impl WidgetTrait for ProvidersWidget {
+    async fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let items = self.rows().iter().map(|r| self.row_item(r)).collect::<Vec<ListItem>>();
+
+        self.width = items
+            .iter()
+            .map(|item| item.width())
+            .max()
+            .unwrap_or(DEFAULT_WIDTH as usize) as u16;
+
+        let title = format!("Providers ({})", self.providers.len());
+        let l = list::List::new(&items, self.is_active())
+            .shortcut(&self.shortcut)
+            .title(title.as_str());
+
+        StatefulWidget::render(l.widget(), area, buf, &mut self.state);
+    }
+
+    fn size(&self) -> Size {
+        Size::new(self.width, self.len() as u16)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[async_trait]
+impl MouseHandler for ProvidersWidget {
+    async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
+}
+
+#[async_trait]
+impl KeyboardHandler for ProvidersWidget {
+    async fn handle_key(&mut self, _key: KeyEvent) -> bool {
+        false
+    }
+}
+
+const STATE_KEY: &str = "selected_item_index";
+const COLLAPSED_GROUPS_KEY: &str = "collapsed_groups";
+const EXCLUDED_GROUPS_KEY: &str = "excluded_groups";
+
+fn groups_to_str(groups: &BTreeSet<String>) -> String {
+    groups.iter().cloned().collect::<Vec<String>>().join(",")
+}
+
+fn groups_from_str(s: &str) -> BTreeSet<String> {
+    s.split(',').filter(|g| !g.is_empty()).map(|g| g.to_string()).collect()
+}
+
+#[async_trait]
+impl StatefulObject for ProvidersWidget {
+    async fn save(&self) -> State {
+        let mut s = State::default();
+        s.insert(
+            STATE_KEY,
+            self.state.selected().unwrap_or_default().to_string().as_str(),
+        );
+        s.insert(COLLAPSED_GROUPS_KEY, groups_to_str(&self.collapsed).as_str());
+        s.insert(EXCLUDED_GROUPS_KEY, groups_to_str(&self.excluded).as_str());
+        s
+    }
+
+    async fn restore(&mut self, state: State) {
+        self.collapsed = state
+            .get(COLLAPSED_GROUPS_KEY)
+            .map(|s| groups_from_str(s))
+            .unwrap_or_default();
+        self.excluded = state
+            .get(EXCLUDED_GROUPS_KEY)
+            .map(|s| groups_from_str(s))
+            .unwrap_or_default();
+        if let Some(idx) = state.get(STATE_KEY)
+            && let Ok(idx) = idx.parse::<usize>()
+        {
+            self.state.select(Some(idx));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rows_test() {
+        struct Case<'a> {
+            name: &'a str,
+            groups: &'a [Option<&'a str>],
+            collapsed: &'a [&'a str],
+            expected: Vec<Row>,
+        }
+        let g = |s: &str| Row::Group(s.to_string());
+        let cases = [
+            Case {
+                name: "no groups",
+                groups: &[None, None],
+                collapsed: &[],
+                expected: vec![Row::All, Row::Provider(0), Row::Provider(1)],
+            },
+            Case {
+                name: "ungrouped providers go first",
+                groups: &[Some("Work"), None, Some("Personal"), Some("Work")],
+                collapsed: &[],
+                expected: vec![
+                    Row::All,
+                    Row::Provider(1),
+                    g("Personal"),
+                    Row::Provider(2),
+                    g("Work"),
+                    Row::Provider(0),
+                    Row::Provider(3),
+                ],
+            },
+            Case {
+                name: "collapsed group",
+                groups: &[Some("Work"), None, Some("Personal"), Some("Work")],
+                collapsed: &["Work"],
+                expected: vec![Row::All, Row::Provider(1), g("Personal"), Row::Provider(2), g("Work")],
+            },
+        ];
+
+        for c in cases {
+            let groups = c.groups.iter().map(|g| g.map(|g| g.to_string())).collect::<Vec<_>>();
+            let collapsed = c.collapsed.iter().map(|g| g.to_string()).collect();
+            assert_eq!(rows(&groups, &collapsed), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn groups_str_test() {
+        let groups = groups_from_str("Personal,,Work");
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups_to_str(&groups), "Personal,Work");
+        assert!(groups_from_str("").is_empty());
+    }
+}
@@ -11,10 +11,7 @@ use ratatui::{
     layout::{Rect, Size},
     widgets::{ListItem, ListState, StatefulWidget},
 };
-use std::{
-    any::Any,
-    slice::{Iter, IterMut},
-};
+use std::{any::Any, slice::Iter};
 use tatuin_core::state::{State, StatefulObject};
 
 const DEFAULT_WIDTH: u16 = 10;
@@ -24,10 +21,14 @@ pub struct SelectableList<T> {
     state: ListState,
     add_all_item: bool,
     shortcut: Option<Shortcut>,
+    // the shortcuts that work in the block only
+    block_shortcuts: Vec<Shortcut>,
     show_count_in_title: bool,
     widget_state: WidgetState,
 
     width: u16,
+    // the count of the rows shown by the last render
+    page_size: u16,
 }
 
 impl<T> WidgetStateTrait for SelectableList<T> {
@@ -69,6 +70,10 @@ where
         }
     }
 
+    fn shortcuts(&mut self) -> Vec<&mut Shortcut> {
+        self.block_shortcuts.iter_mut().collect()
+    }
+
     async fn select_next(&mut self) {
         self.state.select_next();
     }
@@ -129,8 +134,10 @@ impl<T> SelectableList<T> {
             state: ListState::default().with_selected(selected),
             add_all_item: false,
             shortcut: None,
+            block_shortcuts: Vec::new(),
             show_count_in_title: true,
             width: DEFAULT_WIDTH, // will be recalculated after the first render
+            page_size: 1,
             widget_state: WidgetState::default(),
         }
     }
@@ -146,6 +153,11 @@ impl<T> SelectableList<T> {
         self
     }
 
+    pub fn block_shortcut(mut self, s: Shortcut) -> Self {
+        self.block_shortcuts.push(s);
+        self
+    }
+
     pub fn add_item(&mut self, item: T) {
         self.items.push(item);
     }
@@ -162,10 +174,6 @@ impl<T> SelectableList<T> {
         self.items.iter()
     }
 
-    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
-        self.items.iter_mut()
-    }
-
     pub fn len(&self) -> usize {
         self.items.len()
     }
@@ -178,6 +186,25 @@ impl<T> SelectableList<T> {
         self.state.select(index);
     }
 
+    fn last_index(&self) -> Option<usize> {
+        let count = self.items.len() + usize::from(self.add_all_item);
+        count.checked_sub(1)
+    }
+
+    pub fn select_next_page(&mut self) {
+        if let Some(last) = self.last_index() {
+            let idx = self.state.selected().unwrap_or_default() + self.page_size as usize;
+            self.state.select(Some(idx.min(last)));
+        }
+    }
+
+    pub fn select_previous_page(&mut self) {
+        if self.last_index().is_some() {
+            let idx = self.state.selected().unwrap_or_default();
+            self.state.select(Some(idx.saturating_sub(self.page_size as usize)));
+        }
+    }
+
     pub fn selected(&self) -> Option<&T> {
         if self.state.selected().is_some() && !self.items.is_empty() {
             let idx = std::cmp::min(
@@ -225,6 +252,14 @@ impl<T> SelectableList<T> {
             l = l.title(header_title.as_str());
         }
 
+        // the header has the borders
+        self.page_size = if title.is_empty() {
+            area.height
+        } else {
+            area.height.saturating_sub(2)
+        }
+        .max(1);
+
         StatefulWidget::render(l.widget(), area, buf, &mut self.state);
     }
 }
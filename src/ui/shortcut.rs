@@ -19,6 +19,8 @@ pub struct SharedData {
     current_input_keys: Vec<char>,
 }
 
+// The clones share the state and the subscribers
+#[derive(Clone)]
 pub struct Shortcut {
     data: ArcRwLockBlocked<SharedData>,
     tx: broadcast::Sender<()>,
@@ -92,6 +94,62 @@ impl Shortcut {
     }
 }
 
+pub fn keys_to_str(keys: &[char]) -> String {
+    let mut s = String::new();
+    for c in keys {
+        if *c == ' ' {
+            s.push_str("space ");
+        } else {
+            s.push(*c)
+        }
+    }
+    s
+}
+
+// Returns the descriptions of the shortcuts that can't be both accepted.
+// The scope None is global, such shortcuts can conflict with the shortcuts of any scope.
+pub fn find_conflicts<S: PartialEq>(shortcuts: &[(Option<S>, ArcRwLockBlocked<SharedData>)]) -> Vec<String> {
+    let mut result = Vec::new();
+
+    for (i, (l_scope, l)) in shortcuts.iter().enumerate() {
+        for (r_scope, r) in &shortcuts[i + 1..] {
+            if Arc::ptr_eq(l, r) {
+                continue;
+            }
+
+            let same_scope = l_scope.is_none() || r_scope.is_none() || l_scope == r_scope;
+            if !same_scope {
+                continue;
+            }
+
+            let (l, r) = (l.read().unwrap(), r.read().unwrap());
+            let (short, long) = if l.keys.len() <= r.keys.len() {
+                (&l, &r)
+            } else {
+                (&r, &l)
+            };
+            if short.keys == long.keys {
+                result.push(format!(
+                    "'{}' and '{}' have the same keys '{}'",
+                    l.name,
+                    r.name,
+                    keys_to_str(&l.keys)
+                ));
+            } else if long.keys.starts_with(&short.keys) {
+                result.push(format!(
+                    "'{}' ({}) shadows '{}' ({})",
+                    short.name,
+                    keys_to_str(&short.keys),
+                    long.name,
+                    keys_to_str(&long.keys)
+                ));
+            }
+        }
+    }
+
+    result
+}
+
 impl std::fmt::Debug for Shortcut {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let data = self.data.read().unwrap();
@@ -102,3 +160,53 @@ impl std::fmt::Debug for Shortcut {
         )
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_conflicts_test() {
+        let commit = Shortcut::new("Commit changes", &['c', 'c']).internal_data();
+        let change_due = Shortcut::new("Change due", &['c', 'd']).internal_data();
+        let create = Shortcut::new("Create a task", &['c']).internal_data();
+        let complete = Shortcut::new("Complete", &['c', 'c']).internal_data();
+
+        struct Case<'a> {
+            name: &'a str,
+            shortcuts: Vec<(Option<&'a str>, ArcRwLockBlocked<SharedData>)>,
+            expected: &'a [&'a str],
+        }
+        let cases = [
+            Case {
+                name: "no conflicts",
+                shortcuts: vec![(None, commit.clone()), (Some("tasks"), change_due.clone())],
+                expected: &[],
+            },
+            Case {
+                name: "the same shortcut twice",
+                shortcuts: vec![(None, commit.clone()), (Some("tasks"), commit.clone())],
+                expected: &[],
+            },
+            Case {
+                name: "same keys",
+                shortcuts: vec![(None, commit.clone()), (Some("tasks"), complete.clone())],
+                expected: &["'Commit changes' and 'Complete' have the same keys 'cc'"],
+            },
+            Case {
+                name: "shadowed",
+                shortcuts: vec![(Some("tasks"), change_due.clone()), (None, create.clone())],
+                expected: &["'Create a task' (c) shadows 'Change due' (cd)"],
+            },
+            Case {
+                name: "different blocks",
+                shortcuts: vec![(Some("tasks"), commit.clone()), (Some("projects"), complete.clone())],
+                expected: &[],
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(find_conflicts(&c.shortcuts), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+}
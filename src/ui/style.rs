@@ -1,13 +1,23 @@
 // SPDX-License-Identifier: MIT
 
-use std::{collections::HashMap, str::FromStr, sync::RwLock};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{
+        RwLock,
+        atomic::{AtomicU8, Ordering},
+    },
+};
 
-use crate::task::Priority;
 use clap::ValueEnum;
-use ratatui::style::{
-    Color, Modifier, Style,
-    palette::tailwind::{BLUE, GREEN, SLATE},
+use ratatui::{
+    style::{
+        Color, Modifier, Style,
+        palette::tailwind::{BLUE, GREEN, RED, SLATE},
+    },
+    text::Span,
 };
+use tatuin_core::task::Priority;
 
 #[derive(PartialEq, Eq, std::hash::Hash, ValueEnum, Copy, Clone, Debug)]
 #[clap(rename_all = "snake_case")]
@@ -56,6 +66,8 @@ enum ColorElement {
     DisabledButtonFG,
     DisabledButtonBG,
     InactiveButtonFG,
+    DangerButtonFG,
+    DangerButtonBG,
     WarningTextFG,
     BorderColor,
     LowestPriorityFG,
@@ -65,11 +77,128 @@ enum ColorElement {
     HighPriorityFG,
     HighestPriorityFG,
     FilterPanelBG,
+    StarredTaskFG,
+    DueSoonRowBG,
+    DiffAddedFG,
+    DiffRemovedFG,
 }
 
 static THEME_MAP: RwLock<Option<HashMap<ColorElement, Color>>> = RwLock::new(None);
 
+// The accent of the current context, it replaces the background of the active block header
+static ACCENT: RwLock<Option<Color>> = RwLock::new(None);
+
+pub fn set_accent(c: Option<Color>) {
+    *ACCENT.write().unwrap() = c;
+}
+
+/// How many colors the terminal can show, the other colors are replaced with the nearest supported ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ColorSupport {
+    // the 16 named colors
+    Basic,
+    // the 256 colors palette
+    Indexed,
+    TrueColor,
+}
+
+static COLOR_SUPPORT: AtomicU8 = AtomicU8::new(ColorSupport::TrueColor as u8);
+
+pub fn set_color_support(support: ColorSupport) {
+    COLOR_SUPPORT.store(support as u8, Ordering::Relaxed);
+}
+
+fn color_support() -> ColorSupport {
+    match COLOR_SUPPORT.load(Ordering::Relaxed) {
+        0 => ColorSupport::Basic,
+        1 => ColorSupport::Indexed,
+        _ => ColorSupport::TrueColor,
+    }
+}
+
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn indexed_to_rgb(i: u8) -> (u8, u8, u8) {
+    match i {
+        0..16 => BASIC_COLORS[i as usize].1,
+        16..232 => {
+            let i = i - 16;
+            (
+                CUBE_LEVELS[(i / 36) as usize],
+                CUBE_LEVELS[(i / 6 % 6) as usize],
+                CUBE_LEVELS[(i % 6) as usize],
+            )
+        }
+        _ => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+    }
+}
+
+// Replaces the color that the terminal can't show with the nearest one
+fn downgrade_color(c: Color, support: ColorSupport) -> Color {
+    let rgb = match c {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(i) => indexed_to_rgb(i),
+        _ => return c,
+    };
+
+    match (support, c) {
+        (ColorSupport::TrueColor, _) | (ColorSupport::Indexed, Color::Indexed(_)) => c,
+        (ColorSupport::Indexed, _) => {
+            let level = |v: u8| {
+                (0..CUBE_LEVELS.len())
+                    .min_by_key(|&i| CUBE_LEVELS[i].abs_diff(v))
+                    .unwrap() as u8
+            };
+            Color::Indexed(16 + 36 * level(rgb.0) + 6 * level(rgb.1) + level(rgb.2))
+        }
+        (ColorSupport::Basic, _) => {
+            let distance = |(r, g, b): (u8, u8, u8)| {
+                let d = |l: u8, r: u8| (l as i32 - r as i32).pow(2);
+                d(r, rgb.0) + d(g, rgb.1) + d(b, rgb.2)
+            };
+            BASIC_COLORS
+                .iter()
+                .min_by_key(|(_, c)| distance(*c))
+                .map(|(c, _)| *c)
+                .unwrap()
+        }
+    }
+}
+
 fn element_color(element: ColorElement) -> Color {
+    downgrade_color(theme_color(element), color_support())
+}
+
+fn theme_color(element: ColorElement) -> Color {
+    if element == ColorElement::ActiveBlockBG
+        && let Some(c) = *ACCENT.read().unwrap()
+    {
+        return c;
+    }
+
     if let Some(m) = &*THEME_MAP.read().unwrap()
         && let Some(c) = m.get(&element)
     {
@@ -125,6 +254,8 @@ fn element_color(element: ColorElement) -> Color {
         DisabledButtonFG => DEFAULT_FG,
         DisabledButtonBG => Color::DarkGray,
         InactiveButtonFG => DEFAULT_FG,
+        DangerButtonFG => SLATE.c100,
+        DangerButtonBG => RED.c800,
         WarningTextFG => Color::Yellow,
         BorderColor => DEFAULT_FG,
         LowestPriorityFG => Color::DarkGray,
@@ -134,6 +265,10 @@ fn element_color(element: ColorElement) -> Color {
         HighPriorityFG => Color::LightRed,
         HighestPriorityFG => Color::Red,
         FilterPanelBG => Color::Yellow,
+        StarredTaskFG => Color::Yellow,
+        DueSoonRowBG => Color::Rgb(96, 64, 0),
+        DiffAddedFG => Color::LightGreen,
+        DiffRemovedFG => Color::LightRed,
     }
 }
 
@@ -298,10 +433,35 @@ pub fn enabled_button_style() -> Style {
         .fg(element_color(ColorElement::EnabledButtonFG))
 }
 
+pub fn danger_button_style() -> Style {
+    default_style()
+        .fg(element_color(ColorElement::DangerButtonFG))
+        .bg(element_color(ColorElement::DangerButtonBG))
+}
+
 pub fn warning_text_style() -> Style {
     default_style().fg(element_color(ColorElement::WarningTextFG))
 }
 
+pub fn misspelled_word_style() -> Style {
+    Style::new()
+        .fg(element_color(ColorElement::WarningTextFG))
+        .add_modifier(Modifier::UNDERLINED)
+}
+
+// Groups the consecutive graphemes of the same style into spans
+pub fn styled_spans(graphemes: &[&str], style_of: impl Fn(usize) -> Style) -> Vec<Span<'static>> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for i in 1..=graphemes.len() {
+        if i == graphemes.len() || style_of(i) != style_of(start) {
+            result.push(Span::styled(graphemes[start..i].concat(), style_of(start)));
+            start = i;
+        }
+    }
+    result
+}
+
 pub fn border_color() -> Color {
     element_color(ColorElement::BorderColor)
 }
@@ -329,6 +489,22 @@ pub fn priority_color(p: &Priority) -> Color {
     }
 }
 
+pub fn starred_fg() -> Color {
+    element_color(ColorElement::StarredTaskFG)
+}
+
+pub fn due_soon_row_bg() -> Color {
+    element_color(ColorElement::DueSoonRowBG)
+}
+
+pub fn diff_added_fg() -> Color {
+    element_color(ColorElement::DiffAddedFG)
+}
+
+pub fn diff_removed_fg() -> Color {
+    element_color(ColorElement::DiffRemovedFG)
+}
+
 pub fn filter_panel_bg() -> Color {
     element_color(ColorElement::FilterPanelBG)
 }
@@ -336,3 +512,65 @@ pub fn filter_panel_bg() -> Color {
 pub fn tag_icon() -> &'static str {
     "🏷️"
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn downgrade_color_test() {
+        struct Case<'a> {
+            name: &'a str,
+            color: Color,
+            support: ColorSupport,
+            expected: Color,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "true color is kept",
+                color: Color::Rgb(255, 192, 203),
+                support: ColorSupport::TrueColor,
+                expected: Color::Rgb(255, 192, 203),
+            },
+            Case {
+                name: "true color to the palette",
+                color: Color::Rgb(255, 192, 203),
+                support: ColorSupport::Indexed,
+                expected: Color::Indexed(218),
+            },
+            Case {
+                name: "palette color is kept",
+                color: Color::Indexed(218),
+                support: ColorSupport::Indexed,
+                expected: Color::Indexed(218),
+            },
+            Case {
+                name: "true color to the basic",
+                color: Color::Rgb(30, 41, 59),
+                support: ColorSupport::Basic,
+                expected: Color::Black,
+            },
+            Case {
+                name: "palette color to the basic",
+                color: Color::Indexed(196),
+                support: ColorSupport::Basic,
+                expected: Color::LightRed,
+            },
+            Case {
+                name: "named color is kept",
+                color: Color::LightCyan,
+                support: ColorSupport::Basic,
+                expected: Color::LightCyan,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                downgrade_color(c.color, c.support),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -42,7 +42,7 @@ pub struct Config {
 
 pub struct TaskInfoWidget {
     cfg: Config,
-    t: Option<Box<dyn TaskTrait>>,
+    t: Option<Arc<dyn TaskTrait>>,
     shortcut: Shortcut,
     entries: ArcRwLock<Vec<Entry>>,
     widget_state: WidgetState,
@@ -88,7 +88,7 @@ impl TaskInfoWidget {
         }
     }
 
-    pub async fn set_task(&mut self, t: Option<Box<dyn TaskTrait>>) {
+    pub async fn set_task(&mut self, t: Option<Arc<dyn TaskTrait>>) {
         self.t = t;
 
         let mut entries = Vec::new();
@@ -120,6 +120,13 @@ impl TaskInfoWidget {
                 });
             }
 
+            if let Some(r) = t.recurrence() {
+                entries.push(Entry {
+                    title: "Recurrence".to_string(),
+                    widget: Box::new(Text::new(r.to_string().as_str())),
+                });
+            }
+
             if let Some(d) = t.completed_at() {
                 entries.push(Entry {
                     title: "Completed at".to_string(),
@@ -132,6 +139,20 @@ impl TaskInfoWidget {
                 widget: Box::new(Text::new(t.priority().to_string().as_str())),
             });
 
+            if let Some(c) = t.comment_count() {
+                entries.push(Entry {
+                    title: "Comments".to_string(),
+                    widget: Box::new(Text::new(c.to_string().as_str())),
+                });
+            }
+
+            if let Some(a) = t.assignee() {
+                entries.push(Entry {
+                    title: "Assignee".to_string(),
+                    widget: Box::new(Text::new(a.as_str())),
+                });
+            }
+
             if let Some(d) = t.description().map(|d| d.display())
                 && !d.trim().is_empty()
             {
@@ -155,6 +176,14 @@ impl TaskInfoWidget {
                 });
             }
 
+            // the links of the name and the description are opened by the "o1", "o2"... shortcuts
+            for (i, (text, url)) in task::links(t.as_ref()).iter().enumerate() {
+                entries.push(Entry {
+                    title: format!("Link {}", i + 1),
+                    widget: Box::new(HyperlinkWidget::new(text, url)),
+                });
+            }
+
             if let Some(d) = t.created_at() {
                 entries.push(Entry {
                     title: "Created at".to_string(),
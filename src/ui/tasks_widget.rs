@@ -2,44 +2,74 @@
 
 use super::{
     AppBlockWidget,
-    dialogs::{ConfirmationDialog, CreateUpdateTaskDialog, DialogTrait, ListDialog, StandardButton},
+    dialogs::{
+        CommitPreviewDialog, ConfirmationDialog, CreateUpdateTaskDialog, DescriptionChange, DescriptionDialog,
+        DialogTrait, ListDialog, StandardButton,
+    },
     draw_helper::{DrawHelper, global_dialog_area},
     header::Header,
     keyboard_handler::KeyboardHandler,
     mouse_handler::MouseHandler,
     shortcut::Shortcut,
-    style::default_style,
-    widgets::{DateEditor, TaskRow, WidgetState, WidgetStateTrait, WidgetTrait},
+    style::{default_style, selected_row_style},
+    widgets::{DateEditor, LineEdit, Marks, TaskRow, WidgetState, WidgetStateTrait, WidgetTrait},
 };
 use crate::{
     async_jobs::{AsyncJob, AsyncJobStorage},
-    filter::Filter,
-    project::Project as ProjectTrait,
+    dependencies::Dependencies,
+    panic_guard::guarded,
+    pinned::PinnedTasks,
     provider::Provider,
-    task::{self, DateTimeUtc, Priority, State, Task as TaskTrait, datetime_to_str, due_group},
+    settings::{Confirmation, ConfirmationSettings, FollowUp, Waiting},
+    spellcheck::SharedSpellchecker,
+    starred::{StarredTasks, TaskRef},
     ui::{dialogs::MultiSelectListDialog, widgets::FilterPanel},
+    waiting::{WaitingTasks, is_nag_due},
 };
 use async_trait::async_trait;
 use chrono::Local;
-use crossterm::event::{KeyEvent, MouseEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, MouseEvent};
 use itertools::Itertools;
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect, Size},
-    text::Text,
+    text::{Line, Span, Text},
     widgets::{Clear, ListState, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
 };
-use std::{any::Any, slice::Iter, slice::IterMut, sync::Arc};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    ops::Range,
+    slice::Iter,
+    slice::IterMut,
+    sync::Arc,
+    time::Duration,
+};
 use tatuin_core::{
+    event_bus::{AppEvent, EventBus},
+    filter::{Due, Filter},
+    in_flight::InFlight,
     patched_task::PatchedTask,
-    provider::TaskProviderTrait,
-    state::{State as ObjectState, StatefulObject},
-    task_patch::{DatePatchItem, PatchError, TaskPatch, ValuePatch},
+    project::Project as ProjectTrait,
+    provider::{ProviderError, Query, TaskProviderTrait},
+    state::{State as ObjectState, StatefulObject, state_from_str},
+    tag_alias::TagAliases,
+    task::{self, DateTimeUtc, Priority, State, Task as TaskTrait, datetime_to_str},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch, ValuePatch},
+    task_store::{AggregatedTaskStore, Selection, SortOrder, merge_provider_tasks},
+    time::{self, DueAlarm},
     types::ArcRwLock,
 };
-use tokio::sync::{RwLock, broadcast};
+use tokio::{
+    sync::{RwLock, mpsc},
+    task::AbortHandle,
+};
 use tracing::{Instrument, Level};
 
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(60);
+// the links after the ninth one can be opened from the task info only
+const MAX_OPENED_LINK_NUMBER: u32 = 9;
+
 #[derive(Debug, Default)]
 struct Patch {
     provider_name: Option<String>,
@@ -59,14 +89,9 @@ pub trait ProvidersStorage: Send + Sync {
     fn provider(&self, name: &str) -> Provider;
 }
 
-pub trait ErrorLoggerTrait: Send + Sync {
-    fn add_error(&mut self, message: &str);
-}
-type ErrorLogger = ArcRwLock<dyn ErrorLoggerTrait>;
-
 #[async_trait]
 pub trait TaskInfoViewerTrait: Send + Sync {
-    async fn set_task(&mut self, task: Option<Box<dyn TaskTrait>>);
+    async fn set_task(&mut self, task: Option<Arc<dyn TaskTrait>>);
 }
 
 type TaskInfoViewer = ArcRwLock<dyn TaskInfoViewerTrait>;
@@ -79,37 +104,118 @@ enum AsyncCommandType {
     EditTask,
     DeleteTask,
     DuplicateTask,
+    FollowUp,
+    CompleteSubtasks,
+    LinkBlocker,
+    CompleteBlocked,
+}
+
+impl AsyncCommandType {
+    fn confirmation(&self) -> Option<Confirmation> {
+        match self {
+            Self::DeleteTask => Some(Confirmation::DeleteTask),
+            Self::DuplicateTask => Some(Confirmation::DuplicateTask),
+            Self::CompleteSubtasks => Some(Confirmation::CompleteSubtasks),
+            _ => None,
+        }
+    }
+}
+
+struct CommitResult {
+    provider_name: String,
+    patches: Vec<TaskPatch>,
+    errors: Vec<PatchError>,
+}
+
+// The results of the provider operations that are run without holding the widget's lock
+enum Message {
+    Committed(Vec<CommitResult>),
+    TaskCreated,
+    TaskDeleted(Arc<dyn TaskTrait>),
+    TasksReordered,
+    QueryLoaded {
+        tab: usize,
+        result: Result<Vec<Box<dyn TaskTrait>>, String>,
+    },
+    Failed {
+        provider: String,
+        message: String,
+    },
+}
+
+// The saved query of the provider, its tasks are loaded when the tab is shown for the first time
+struct QueryTab {
+    provider: String,
+    query: Query,
+    tasks: Option<Vec<Arc<dyn TaskTrait>>>,
+}
+
+impl QueryTab {
+    fn key(&self) -> String {
+        format!("query {} {}", self.provider, self.query.query)
+    }
+}
+
+// The running load of the provider's tasks, it's aborted when the tasks are loaded with another filter
+struct ProviderLoad {
+    key: String,
+    generation: u64,
+    handle: AbortHandle,
+}
+
+// The task offered to block the task under cursor, the already blocking ones are checked
+#[derive(Clone)]
+struct BlockerItem {
+    task: TaskRef,
+    name: String,
+    is_linked: bool,
+}
+
+impl std::fmt::Display for BlockerItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mark = if self.is_linked { "x" } else { " " };
+        write!(f, "[{mark}] {} ({})", self.name, self.task.provider())
+    }
 }
 
 struct AsyncCommand {
     command_type: AsyncCommandType,
-    task: Box<dyn TaskTrait>,
+    task: Arc<dyn TaskTrait>,
 }
 
 impl AsyncCommand {
-    fn new(command_type: AsyncCommandType, task: &dyn TaskTrait) -> Self {
+    fn new(command_type: AsyncCommandType, task: &Arc<dyn TaskTrait>) -> Self {
         Self {
             command_type,
-            task: task.clone_boxed(),
+            task: task.clone(),
         }
     }
 }
 
 pub struct TasksWidget {
     providers_storage: ArcRwLock<dyn ProvidersStorage>,
-    error_logger: ErrorLogger,
+    events: EventBus,
     task_info_viewer: TaskInfoViewer,
-    all_tasks: Vec<Box<dyn TaskTrait>>,
+    // the tasks of all providers, the changes made in the UI are kept in changed_tasks
+    store: AggregatedTaskStore,
     changed_tasks: Vec<TaskPatch>,
     tasks: Vec<TaskRow>,
     providers_filter: Vec<String>,
+    excluded_providers: Vec<String>,
     projects_filter: Vec<String>,
     draw_helper: Option<DrawHelper>,
-    on_changes_broadcast: broadcast::Sender<()>,
     async_jobs_storage: ArcRwLock<AsyncJobStorage>,
     list_state: ListState,
+    // the task to select when it's shown, it's set by the open subcommand
+    pending_selection: Option<TaskKey>,
+    // the first shown row, it's changed only when the selected row goes out of the view
+    offset: usize,
+    visible_rows: Range<usize>,
     widget_state: WidgetState,
     async_command: Option<AsyncCommand>,
+    confirmation_settings: ArcRwLock<dyn ConfirmationSettings>,
+    messages_tx: mpsc::UnboundedSender<Message>,
+    is_committing: bool,
 
     activate_shortcut: Shortcut,
     commit_changes_shortcut: Shortcut,
@@ -121,16 +227,67 @@ pub struct TasksWidget {
     undo_changes_shortcut: Shortcut,
     add_task_shortcut: Shortcut,
     add_tasks_shortcut: Shortcut,
+    add_subtask_shortcut: Shortcut,
+    move_down_shortcut: Shortcut,
+    move_up_shortcut: Shortcut,
     edit_task_shortcut: Shortcut,
+    edit_name_shortcut: Shortcut,
     delete_task_shortcut: Shortcut,
     open_task_link_shortcut: Shortcut,
+    open_link_shortcuts: Vec<Shortcut>,
+    peek_description_shortcut: Shortcut,
     duplicate_task_shortcut: Shortcut,
     filter_by_tag_shortcut: Shortcut,
-
+    filter_by_involvement_shortcut: Shortcut,
+    next_tab_shortcut: Shortcut,
+    previous_tab_shortcut: Shortcut,
+    refresh_tab_shortcut: Shortcut,
+    star_shortcut: Shortcut,
+    pin_shortcut: Shortcut,
+    wait_shortcut: Shortcut,
+    waiting_view_shortcut: Shortcut,
+    collapse_shortcut: Shortcut,
+    link_blocker_shortcut: Shortcut,
+
+    query_tabs: Vec<QueryTab>,
+    // None is the tab with all the tasks
+    active_tab: Option<usize>,
+    // the loads of the tasks and the queries that are running now
+    in_flight: InFlight,
+    loads: HashMap<String, ProviderLoad>,
+    // the late results of the older loads are dropped
+    load_generation: u64,
     last_filter: Filter,
+    // the provider and project of the last created task
+    last_destination: Option<ObjectState>,
+    // the provider and project from the settings, they're used until a task is created
+    default_destination: (Option<String>, Option<String>),
+    // the provider and id of the project selected in the Projects block
+    selected_project: Option<(String, String)>,
+    sort_order: SortOrder,
+    // the equivalent labels are shown and filtered as one tag
+    tag_aliases: TagAliases,
+    follow_up: FollowUp,
+    starred: StarredTasks,
+    pinned: PinnedTasks,
+    waiting: WaitingTasks,
+    waiting_settings: Waiting,
+    // only the waiting tasks are shown, the longest waiting first
+    waiting_view: bool,
+    // the subtasks of these tasks are hidden
+    collapsed: HashSet<TaskKey>,
+    dependencies: Dependencies,
+    // the blocked task whose completion has been confirmed
+    confirmed_blocked: Option<TaskKey>,
+    due_soon: DueSoon,
+    // the reminders that have passed before it have been played
+    reminders_checked_at: DateTimeUtc,
+    spellchecker: Option<SharedSpellchecker>,
 
     dialog: Option<Box<dyn DialogTrait>>,
     is_global_dialog: bool,
+    // the editor of the task's name shown in place of the row
+    name_editor: Option<(TaskKey, LineEdit)>,
     filter_panel: FilterPanel,
 
     arc_self: Option<ArcRwLock<Self>>,
@@ -146,7 +303,11 @@ impl AppBlockWidget for TasksWidget {
         vec![
             &mut self.add_task_shortcut,
             &mut self.add_tasks_shortcut,
+            &mut self.add_subtask_shortcut,
+            &mut self.move_down_shortcut,
+            &mut self.move_up_shortcut,
             &mut self.edit_task_shortcut,
+            &mut self.edit_name_shortcut,
             &mut self.delete_task_shortcut,
             &mut self.commit_changes_shortcut,
             &mut self.swap_completed_state_shortcut,
@@ -156,9 +317,23 @@ impl AppBlockWidget for TasksWidget {
             &mut self.change_priority_shortcut,
             &mut self.undo_changes_shortcut,
             &mut self.open_task_link_shortcut,
+            &mut self.peek_description_shortcut,
             &mut self.duplicate_task_shortcut,
             &mut self.filter_by_tag_shortcut,
+            &mut self.filter_by_involvement_shortcut,
+            &mut self.next_tab_shortcut,
+            &mut self.previous_tab_shortcut,
+            &mut self.refresh_tab_shortcut,
+            &mut self.star_shortcut,
+            &mut self.pin_shortcut,
+            &mut self.wait_shortcut,
+            &mut self.waiting_view_shortcut,
+            &mut self.collapse_shortcut,
+            &mut self.link_blocker_shortcut,
         ]
+        .into_iter()
+        .chain(self.open_link_shortcuts.iter_mut())
+        .collect()
     }
 
     async fn select_next(&mut self) {
@@ -193,27 +368,47 @@ impl AppBlockWidget for TasksWidget {
 impl TasksWidget {
     pub async fn new(
         providers_storage: ArcRwLock<dyn ProvidersStorage>,
-        error_logger: ErrorLogger,
+        events: EventBus,
         task_info_viewer: TaskInfoViewer,
         async_jobs_storage: ArcRwLock<AsyncJobStorage>,
+        confirmation_settings: ArcRwLock<dyn ConfirmationSettings>,
     ) -> ArcRwLock<Self> {
-        let (tx, _) = broadcast::channel(1);
+        let (messages_tx, mut messages_rx) = mpsc::unbounded_channel();
+        let mut events_rx = events.subscribe();
+        let query_tabs = providers_storage
+            .read()
+            .await
+            .iter()
+            .flat_map(|p| {
+                p.queries.iter().map(|q| QueryTab {
+                    provider: p.name.clone(),
+                    query: q.clone(),
+                    tasks: None,
+                })
+            })
+            .collect();
 
         let s = Arc::new(RwLock::new(Self {
             providers_storage,
-            error_logger,
+            events,
             task_info_viewer,
-            all_tasks: Vec::new(),
+            store: AggregatedTaskStore::new(),
             changed_tasks: Vec::new(),
             list_state: ListState::default(),
+            pending_selection: None,
+            offset: 0,
+            visible_rows: 0..0,
             widget_state: WidgetState::default(),
             async_command: None,
+            confirmation_settings,
+            messages_tx,
+            is_committing: false,
             activate_shortcut: Shortcut::new("Activate Tasks block", &['g', 't']),
             tasks: Vec::new(),
             projects_filter: Vec::new(),
             providers_filter: Vec::new(),
+            excluded_providers: Vec::new(),
             draw_helper: None,
-            on_changes_broadcast: tx,
             async_jobs_storage,
             commit_changes_shortcut: Shortcut::new("Commit changes", &['c', 'c'])
                 .global()
@@ -233,17 +428,74 @@ impl TasksWidget {
             add_tasks_shortcut: Shortcut::new("Create tasks", &['A'])
                 .global()
                 .with_short_name("Create tasks"),
+            add_subtask_shortcut: Shortcut::new("Create a subtask of the task", &['S'])
+                .with_short_name("Create a subtask"),
+            move_down_shortcut: Shortcut::new("Move the task down", &['J']),
+            move_up_shortcut: Shortcut::new("Move the task up", &['K']),
             edit_task_shortcut: Shortcut::new("Edit the task", &['e']).with_short_name("Edit task"),
+            edit_name_shortcut: Shortcut::new("Edit the task's name inline", &['i']),
             delete_task_shortcut: Shortcut::new("Delete the task", &['d']).with_short_name("Delete task"),
-            open_task_link_shortcut: Shortcut::new("Open the task's link", &['o']),
+            open_task_link_shortcut: Shortcut::new("Open the task's link", &['o', 'o']),
+            open_link_shortcuts: (1..=MAX_OPENED_LINK_NUMBER)
+                .map(|i| {
+                    Shortcut::new(
+                        format!("Open the link {i} of the task").as_str(),
+                        &['o', char::from_digit(i, 10).unwrap()],
+                    )
+                })
+                .collect(),
+            peek_description_shortcut: Shortcut::new("Peek the task's description", &['v']),
             duplicate_task_shortcut: Shortcut::new("Duplicate the task", &['m', 'c']),
             filter_by_tag_shortcut: Shortcut::new("Filter by tag", &['f', 't'])
                 .with_short_name("Filter by tag")
                 .global(),
+            filter_by_involvement_shortcut: Shortcut::new(
+                "Switch the filter: all, assigned to me, created by me",
+                &['f', 'a'],
+            )
+            .with_short_name("Filter by assignee")
+            .global(),
+            next_tab_shortcut: Shortcut::new("Show the next query tab", &[']']),
+            previous_tab_shortcut: Shortcut::new("Show the previous query tab", &['[']),
+            refresh_tab_shortcut: Shortcut::new("Refresh the query tab", &['R']),
+            star_shortcut: Shortcut::new("Star the task", &['*']).with_short_name("Star"),
+            pin_shortcut: Shortcut::new("Pin the task to the top", &['P']).with_short_name("Pin"),
+            wait_shortcut: Shortcut::new("Mark the task as waiting for somebody", &['W']).with_short_name("Waiting"),
+            waiting_view_shortcut: Shortcut::new("Show the waiting tasks only", &['f', 'w'])
+                .with_short_name("Waiting view")
+                .global(),
+            collapse_shortcut: Shortcut::new("Collapse or expand the subtasks of the task", &['z', 'a'])
+                .with_short_name("Fold"),
+            link_blocker_shortcut: Shortcut::new("Link or unlink the task that blocks the task", &['b'])
+                .with_short_name("Blocked by"),
+
+            query_tabs,
+            active_tab: None,
+            in_flight: InFlight::default(),
+            loads: HashMap::new(),
+            load_generation: 0,
 
             last_filter: Filter::default(),
+            last_destination: None,
+            default_destination: (None, None),
+            selected_project: None,
+            sort_order: SortOrder::default(),
+            tag_aliases: TagAliases::default(),
+            follow_up: FollowUp::default(),
+            starred: StarredTasks::default(),
+            pinned: PinnedTasks::default(),
+            waiting: WaitingTasks::default(),
+            waiting_settings: Waiting::default(),
+            waiting_view: false,
+            collapsed: HashSet::new(),
+            dependencies: Dependencies::default(),
+            confirmed_blocked: None,
+            due_soon: DueSoon::default(),
+            reminders_checked_at: chrono::Utc::now(),
+            spellchecker: None,
             dialog: None,
             is_global_dialog: true,
+            name_editor: None,
             filter_panel: FilterPanel::new(),
             arc_self: None,
         }));
@@ -261,19 +513,46 @@ impl TasksWidget {
                 let mut undo_changes_rx = s_guard.undo_changes_shortcut.subscribe_to_accepted();
                 let mut add_task_rx = s_guard.add_task_shortcut.subscribe_to_accepted();
                 let mut add_tasks_rx = s_guard.add_tasks_shortcut.subscribe_to_accepted();
+                let mut add_subtask_rx = s_guard.add_subtask_shortcut.subscribe_to_accepted();
+                let mut move_down_rx = s_guard.move_down_shortcut.subscribe_to_accepted();
+                let mut move_up_rx = s_guard.move_up_shortcut.subscribe_to_accepted();
                 let mut edit_task_rx = s_guard.edit_task_shortcut.subscribe_to_accepted();
+                let mut edit_name_rx = s_guard.edit_name_shortcut.subscribe_to_accepted();
                 let mut delete_task_rx = s_guard.delete_task_shortcut.subscribe_to_accepted();
                 let mut open_task_link_rx = s_guard.open_task_link_shortcut.subscribe_to_accepted();
+                let mut peek_description_rx = s_guard.peek_description_shortcut.subscribe_to_accepted();
                 let mut duplicate_task_rx = s_guard.duplicate_task_shortcut.subscribe_to_accepted();
                 let mut filter_by_tag_rx = s_guard.filter_by_tag_shortcut.subscribe_to_accepted();
+                let mut filter_by_involvement_rx = s_guard.filter_by_involvement_shortcut.subscribe_to_accepted();
+                let mut next_tab_rx = s_guard.next_tab_shortcut.subscribe_to_accepted();
+                let mut previous_tab_rx = s_guard.previous_tab_shortcut.subscribe_to_accepted();
+                let mut refresh_tab_rx = s_guard.refresh_tab_shortcut.subscribe_to_accepted();
+                let mut star_rx = s_guard.star_shortcut.subscribe_to_accepted();
+                let mut pin_rx = s_guard.pin_shortcut.subscribe_to_accepted();
+                let mut wait_rx = s_guard.wait_shortcut.subscribe_to_accepted();
+                let mut waiting_view_rx = s_guard.waiting_view_shortcut.subscribe_to_accepted();
+                let mut collapse_rx = s_guard.collapse_shortcut.subscribe_to_accepted();
+                let mut link_blocker_rx = s_guard.link_blocker_shortcut.subscribe_to_accepted();
+                let (open_link_tx, mut open_link_rx) = mpsc::unbounded_channel();
+                for (i, shortcut) in s_guard.open_link_shortcuts.iter().enumerate() {
+                    let mut rx = shortcut.subscribe_to_accepted();
+                    let tx = open_link_tx.clone();
+                    tokio::spawn(async move { while rx.recv().await.is_ok() && tx.send(i).is_ok() {} });
+                }
                 drop(s_guard);
 
                 loop {
                     tokio::select! {
+                        Some(m) = messages_rx.recv() => s.write().await.apply_message(m).await,
+                        Some(e) = events_rx.recv() => {
+                            if let AppEvent::FilterChanged(f) = e {
+                                s.write().await.load_tasks(&f).await;
+                            }
+                        },
                         _ = commit_changes_rx.recv() => {
                             let mut s = s.write().await;
-                            if s.has_changes() {
-                                s.commit_changes().await;
+                            if s.has_changes() && !s.is_committing {
+                                s.request_commit().await;
                             }
                         },
                         _ = swap_completed_state_rx.recv() => s.write().await.change_check_state(None).await,
@@ -286,40 +565,56 @@ impl TasksWidget {
                         _ = change_due_rx.recv() => {
                                 let mut s = s.write().await;
                                 if let Some(t) = s.selected_task() {
-                                    s.async_command = Some(AsyncCommand::new(AsyncCommandType::ChangeDueDate, t.as_ref()));
+                                    s.async_command = Some(AsyncCommand::new(AsyncCommandType::ChangeDueDate, &t));
                                     s.show_change_due_date_dialog().await
                                 }
                             },
                         _ = change_scheduled_rx.recv() => {
                                 let mut s = s.write().await;
                                 if let Some(t) = s.selected_task() {
-                                    s.async_command = Some(AsyncCommand::new(AsyncCommandType::ChangeScheduledDate, t.as_ref()));
+                                    s.async_command = Some(AsyncCommand::new(AsyncCommandType::ChangeScheduledDate, &t));
                                     s.show_change_scheduled_date_dialog().await
                                 }
                             },
                         _ = change_priority_rx.recv() => {
                                 let mut s = s.write().await;
                                 if let Some(t) = s.selected_task() {
-                                    s.async_command = Some(AsyncCommand::new(AsyncCommandType::ChangePriority, t.as_ref()));
+                                    s.async_command = Some(AsyncCommand::new(AsyncCommandType::ChangePriority, &t));
                                     s.show_change_priority_dialog().await
                                 }
                             },
                         _ = undo_changes_rx.recv() => s.write().await.undo_changes().await,
+                        _ = star_rx.recv() => s.write().await.toggle_star().await,
+                        _ = pin_rx.recv() => s.write().await.toggle_pin().await,
+                        _ = wait_rx.recv() => s.write().await.toggle_waiting().await,
+                        _ = waiting_view_rx.recv() => s.write().await.switch_waiting_view().await,
+                        _ = collapse_rx.recv() => s.write().await.toggle_collapsed().await,
+                        _ = link_blocker_rx.recv() => s.write().await.show_link_blocker_dialog(),
                         _ = add_task_rx.recv() => s.write().await.show_add_task_dialog(None, None, false).await,
                         _ = add_tasks_rx.recv() => s.write().await.show_add_task_dialog(None, None, true).await,
+                        _ = move_down_rx.recv() => s.write().await.move_task(true).await,
+                        _ = move_up_rx.recv() => s.write().await.move_task(false).await,
+                        _ = add_subtask_rx.recv() => {
+                            let mut s = s.write().await;
+                            if let Some(t) = s.selected_task()
+                                && s.supports_subtasks(t.provider().as_str()).await {
+                                s.show_add_subtask_dialog(&t, None).await;
+                            }
+                        },
                         _ = edit_task_rx.recv() => {
                             let mut s = s.write().await;
                             if let Some(t) = s.selected_task()
                                 && t.patch_policy().is_editable {
-                                s.async_command = Some(AsyncCommand::new(AsyncCommandType::EditTask, t.as_ref()));
+                                s.async_command = Some(AsyncCommand::new(AsyncCommandType::EditTask, &t));
                                 s.show_add_task_dialog(Some(t), None, false).await;
                             }
                         },
+                        _ = edit_name_rx.recv() => s.write().await.show_name_editor().await,
                         _ = delete_task_rx.recv() => {
                             let mut s = s.write().await;
                             if let Some(t) = s.selected_task()
                                 && t.patch_policy().is_removable {
-                                s.async_command = Some(AsyncCommand::new(AsyncCommandType::DeleteTask, t.as_ref()));
+                                s.async_command = Some(AsyncCommand::new(AsyncCommandType::DeleteTask, &t));
                                 s.show_delete_task_dialog(t.as_ref()).await;
                             }
                         },
@@ -327,19 +622,31 @@ impl TasksWidget {
                             if let Some(t) = s.read().await.selected_task()
                                 && !t.url().is_empty()
                                 && let Err(e) = tatuin_core::utils::open_url(t.url().as_str()){
-                                s.write().await.error_logger.write().await.add_error(e.to_string().as_str());
+                                s.read().await.events.publish(AppEvent::Error(e.to_string()));
+                            }
+                        }
+                        Some(i) = open_link_rx.recv() => {
+                            if let Some(t) = s.read().await.selected_task()
+                                && let Some((_, url)) = task::links(t.as_ref()).get(i)
+                                && let Err(e) = tatuin_core::utils::open_url(url) {
+                                s.read().await.events.publish(AppEvent::Error(e.to_string()));
                             }
                         }
+                        _ = peek_description_rx.recv() => s.write().await.show_description_dialog().await,
                         _ = duplicate_task_rx.recv() => {
                             let mut s = s.write().await;
                             if let Some(t) = s.selected_task() {
-                                s.async_command = Some(AsyncCommand::new(AsyncCommandType::DuplicateTask, t.as_ref()));
+                                s.async_command = Some(AsyncCommand::new(AsyncCommandType::DuplicateTask, &t));
                                 s.show_duplicate_task_dialog(t.as_ref()).await;
                             }
                         },
                         _ = filter_by_tag_rx.recv() => {
                             s.write().await.show_filter_by_tag_dialog().await;
                         },
+                        _ = filter_by_involvement_rx.recv() => s.write().await.switch_involvement_filter().await,
+                        _ = next_tab_rx.recv() => s.write().await.switch_tab(true).await,
+                        _ = previous_tab_rx.recv() => s.write().await.switch_tab(false).await,
+                        _ = refresh_tab_rx.recv() => s.write().await.load_active_tab(),
                     }
 
                     s.write().await.update_task_info_view().await;
@@ -352,8 +659,9 @@ impl TasksWidget {
         s
     }
 
-    pub async fn set_providers_filter(&mut self, providers: &[String]) {
+    pub async fn set_providers_filter(&mut self, providers: &[String], excluded: &[String]) {
         self.providers_filter = providers.to_vec();
+        self.excluded_providers = excluded.to_vec();
         self.filter_tasks().await;
     }
 
@@ -362,48 +670,114 @@ impl TasksWidget {
         self.filter_tasks().await;
     }
 
-    pub fn subscribe_on_changes(&self) -> broadcast::Receiver<()> {
-        self.on_changes_broadcast.subscribe()
-    }
-
     async fn filter_tasks(&mut self) {
-        self.tasks = self
-            .all_tasks
+        let mut selection = Selection {
+            providers: self.providers_filter.clone(),
+            excluded_providers: self.excluded_providers.clone(),
+            projects: self.projects_filter.clone(),
+            tags: self.filter_panel.tag_filter(),
+            tag_aliases: self.tag_aliases.clone(),
+            involvement: self.filter_panel.involvement(),
+        };
+        let source = match self.active_tab {
+            Some(i) => {
+                // the query defines the provider and the projects of its tasks
+                selection.providers.clear();
+                selection.projects.clear();
+                self.query_tabs[i].tasks.as_deref().unwrap_or_default()
+            }
+            None => self.store.tasks(),
+        };
+        let previous_keys = self.tasks.iter().map(|r| task_key(r.task())).collect::<Vec<TaskKey>>();
+
+        // the rows of the unchanged tasks are reused, so only the new or changed ones are rebuilt
+        let mut rows = std::mem::take(&mut self.tasks)
+            .into_iter()
+            .map(|r| (task_key(r.task()), r))
+            .collect::<HashMap<TaskKey, TaskRow>>();
+        // the pinned tasks are shown whatever provider or project is selected
+        let pinned_selection = Selection {
+            providers: Vec::new(),
+            projects: Vec::new(),
+            ..selection.clone()
+        };
+        let (mut pinned, mut filtered): (Vec<_>, Vec<_>) = source
             .iter()
-            .filter(|t| {
-                if !self.providers_filter.is_empty() && !self.providers_filter.contains(&t.provider()) {
-                    return false;
-                }
-                if let Some(tp) = t.project()
-                    && !self.projects_filter.is_empty()
-                    && !self.projects_filter.contains(&tp.name())
-                {
-                    return false;
-                }
-
-                let tag_filter = self.filter_panel.tag_filter();
-                if !(tag_filter.is_empty() || t.labels().iter().any(|t| tag_filter.contains(t))) {
+            .filter(|t| self.active_tab.is_none() || self.last_filter.accept(t.as_ref()))
+            .filter(|t| !self.waiting_view || self.is_waiting(t.as_ref()))
+            .filter(|t| pinned_selection.matches(t.as_ref()))
+            .partition(|t| self.pin_position(t.as_ref()).is_some());
+        filtered.retain(|t| selection.matches(t.as_ref()));
+        pinned.sort_by_key(|t| self.pin_position(t.as_ref()));
+        if self.waiting_view {
+            // the longest waiting tasks go first
+            filtered.sort_by_key(|t| self.waiting_since(t.as_ref()));
+        } else {
+            // the starred tasks go first in their due group, the order of the rest is kept
+            filtered.sort_by_key(|t| (task::due_group(&t.planned_date()), !self.is_starred(t.as_ref())));
+        }
+        // the subtasks are shown right under their parents unless the parent is collapsed
+        let mut collapsed_depth: Option<usize> = None;
+        let ordered = pinned
+            .iter()
+            .map(|t| (*t, 0))
+            .chain(
+                task::hierarchy(&filtered.iter().map(|t| t.as_ref()).collect::<Vec<&dyn TaskTrait>>())
+                    .into_iter()
+                    .map(|(i, depth)| (filtered[i], depth)),
+            )
+            .filter(|(t, depth)| {
+                if collapsed_depth.is_some_and(|d| *depth > d) {
                     return false;
                 }
+                collapsed_depth = self.collapsed.contains(&task_key(t.as_ref())).then_some(*depth);
                 true
             })
-            .map(|t| TaskRow::new(t.as_ref(), &self.changed_tasks))
+            .collect::<Vec<(&Arc<dyn TaskTrait>, usize)>>();
+        self.tasks = ordered
+            .into_iter()
+            .map(|(t, depth)| {
+                let marks = self.marks(t.as_ref());
+                match rows.remove(&task_key(t.as_ref())) {
+                    Some(r) => r.update(t, &self.changed_tasks, depth, marks),
+                    None => TaskRow::new(t, &self.changed_tasks, depth, marks),
+                }
+            })
             .collect();
 
-        self.list_state = if self.tasks.is_empty() {
-            ListState::default()
-        } else {
-            let selected_idx = self
-                .list_state
-                .selected()
-                .map(|i| if i >= self.tasks.len() { self.tasks.len() - 1 } else { i })
-                .unwrap_or_else(|| 0);
-            ListState::default().with_selected(Some(selected_idx))
-        };
+        // the reused rows could be shown before at other positions
+        self.visible_rows = 0..self.tasks.len();
+        self.due_soon = due_soon(self.store.tasks());
+
+        let keys = self.tasks.iter().map(|r| task_key(r.task())).collect::<Vec<TaskKey>>();
+        self.list_state =
+            ListState::default().with_selected(restore_selection(&previous_keys, self.list_state.selected(), &keys));
+        self.select_pending();
 
         self.update_task_info_view().await;
     }
 
+    // The task is selected at once if it's shown, otherwise after the tasks are loaded
+    pub async fn open_task(&mut self, provider: String, task_id: String) {
+        self.pending_selection = Some((provider, task_id));
+        if self.select_pending() {
+            self.update_task_info_view().await;
+        }
+    }
+
+    fn select_pending(&mut self) -> bool {
+        let Some(idx) = self
+            .pending_selection
+            .as_ref()
+            .and_then(|k| self.tasks.iter().position(|r| task_key(r.task()) == *k))
+        else {
+            return false;
+        };
+        self.list_state.select(Some(idx));
+        self.pending_selection = None;
+        true
+    }
+
     pub fn tasks_projects(&self) -> Vec<Box<dyn ProjectTrait>> {
         let mut projects: Vec<Box<dyn ProjectTrait>> = Vec::new();
 
@@ -421,7 +795,7 @@ impl TasksWidget {
         projects
     }
 
-    pub fn selected_task(&self) -> Option<Box<dyn TaskTrait>> {
+    pub fn selected_task(&self) -> Option<Arc<dyn TaskTrait>> {
         if self.tasks.is_empty() {
             return None;
         }
@@ -429,63 +803,183 @@ impl TasksWidget {
         let t = self
             .list_state
             .selected()
-            .map(|i| self.tasks[std::cmp::min(i, self.tasks.len() - 1)].task())?;
-        let p = self.changed_tasks.iter().find(|p| p.is_task(t));
-        Some(Box::new(PatchedTask::new(t.clone_boxed(), p.cloned())))
+            .map(|i| self.tasks[std::cmp::min(i, self.tasks.len() - 1)].shared_task())?;
+        let p = self.changed_tasks.iter().find(|p| p.is_task(t.as_ref()));
+        Some(Arc::new(PatchedTask::new(t.clone(), p.cloned())))
     }
 
     pub fn has_changes(&self) -> bool {
         !self.changed_tasks.is_empty()
     }
 
+    pub fn pending_changes(&self, provider_name: &str) -> usize {
+        self.changed_tasks
+            .iter()
+            .filter(|c| c.task.as_ref().is_some_and(|t| t.provider() == provider_name))
+            .count()
+    }
+
+    // Runs the provider's operation in the background, so the widget isn't locked during the network calls
+    fn spawn_provider_operation<F>(&self, job_name: String, f: F)
+    where
+        F: Future<Output = Message> + Send + 'static,
+    {
+        let tx = self.messages_tx.clone();
+        let async_jobs = self.async_jobs_storage.clone();
+        tokio::spawn(async move {
+            let _job = AsyncJob::new(job_name.as_str(), async_jobs).await;
+            // The receiver lives as long as the widget
+            let _ = tx.send(f.await);
+        });
+    }
+
+    async fn apply_message(&mut self, m: Message) {
+        match m {
+            Message::Committed(results) => {
+                for r in results {
+                    self.process_patch_errors(&r.provider_name, &r.errors);
+                    self.events.publish(AppEvent::PatchCommitted {
+                        provider: r.provider_name.clone(),
+                        committed: r.patches.len() - r.errors.len(),
+                        failed: r.errors.len(),
+                    });
+                    self.changed_tasks.retain(|c| {
+                        let patched = r
+                            .patches
+                            .iter()
+                            .any(|tp| tp.task.as_ref().is_some_and(|t| c.is_task(t.as_ref())))
+                            && !r
+                                .errors
+                                .iter()
+                                .any(|pe| c.task.as_ref().is_some_and(|t| pe.is_task(t.as_ref())));
+                        !patched
+                    });
+                }
+                self.is_committing = false;
+            }
+            Message::TaskCreated | Message::TasksReordered => {}
+            Message::QueryLoaded { tab, result } => {
+                let provider = self.query_tabs[tab].provider.clone();
+                if self.in_flight.finish(&self.query_tabs[tab].key()) {
+                    self.spawn_query(tab);
+                }
+                match result {
+                    Ok(tasks) => {
+                        let mut sorted = Vec::new();
+                        merge_provider_tasks(&mut sorted, &provider, tasks, self.sort_order);
+                        self.query_tabs[tab].tasks = Some(sorted);
+                        if self.active_tab == Some(tab) {
+                            self.filter_tasks().await;
+                        }
+                    }
+                    Err(message) => self.events.publish(AppEvent::ProviderError { provider, message }),
+                }
+                return;
+            }
+            Message::TaskDeleted(t) => self.changed_tasks.retain(|c| !c.is_task(t.as_ref())),
+            Message::Failed { provider, message } => {
+                self.events.publish(AppEvent::ProviderError { provider, message });
+                return;
+            }
+        }
+
+        self.load_tasks(&self.last_filter.clone()).await;
+    }
+
+    // The changed descriptions are previewed as a diff before they're pushed to the providers
+    fn description_changes(&self) -> Vec<DescriptionChange> {
+        self.changed_tasks
+            .iter()
+            .filter_map(|c| {
+                let t = c.task.as_ref()?;
+                let new = match &c.description {
+                    ValuePatch::NotSet => return None,
+                    ValuePatch::Empty => String::new(),
+                    ValuePatch::Value(d) => d.clone(),
+                };
+                let old = t.description().map(|d| d.raw()).unwrap_or_default();
+                (old != new).then(|| DescriptionChange {
+                    task_name: t.name().plain(),
+                    old,
+                    new,
+                })
+            })
+            .collect()
+    }
+
+    async fn request_commit(&mut self) {
+        let changes = self.description_changes();
+        if changes.is_empty() {
+            self.commit_changes().await;
+        } else {
+            self.dialog = Some(Box::new(CommitPreviewDialog::new(&changes)));
+            self.is_global_dialog = true;
+        }
+    }
+
     async fn commit_changes(&mut self) {
-        for p in self.providers_storage.write().await.iter_mut() {
-            let name = &p.name;
+        let mut commits = Vec::new();
+        for p in self.providers_storage.read().await.iter() {
             let patches = self
                 .changed_tasks
                 .iter()
-                .filter(|c| c.task.as_ref().is_some_and(|t| &t.provider() == name))
+                .filter(|c| c.task.as_ref().is_some_and(|t| t.provider() == p.name))
                 .cloned()
                 .collect::<Vec<TaskPatch>>();
 
-            if patches.is_empty() {
-                continue;
+            if !patches.is_empty() {
+                commits.push((p.clone(), patches));
             }
+        }
 
-            let errors = p.provider.write().await.update(&patches).await;
-            self.process_patch_errors(name, &errors).await;
-
-            self.changed_tasks.retain(|c| {
-                let patched = patches
-                    .iter()
-                    .any(|tp| tp.task.as_ref().is_some_and(|t| c.is_task(t.as_ref())))
-                    && !errors
+        self.is_committing = true;
+        self.spawn_provider_operation("Commit changes".to_string(), async move {
+            let mut results = Vec::new();
+            for (p, patches) in commits {
+                let result = guarded(async {
+                    let mut provider = p.provider.write().await;
+                    let errors = provider.update(&patches).await;
+                    provider.reload().await;
+                    errors
+                })
+                .await;
+                let errors = match result {
+                    Ok(errors) => errors,
+                    Err(e) => patches
                         .iter()
-                        .any(|pe| c.task.as_ref().is_some_and(|t| pe.is_task(t.as_ref())));
-                !patched
-            });
-
-            p.provider.write().await.reload().await;
-        }
+                        .filter_map(|tp| tp.task.as_ref())
+                        .map(|t| PatchError {
+                            task: t.clone(),
+                            error: e.to_string(),
+                            kind: PatchErrorKind::Other,
+                        })
+                        .collect(),
+                };
 
-        self.load_tasks(&self.last_filter.clone()).await;
+                results.push(CommitResult {
+                    provider_name: p.name,
+                    patches,
+                    errors,
+                });
+            }
+            Message::Committed(results)
+        });
     }
 
-    async fn process_patch_errors(&self, provider_name: &str, errors: &[PatchError]) {
-        let mut error_logger = self.error_logger.write().await;
+    fn process_patch_errors(&self, provider_name: &str, errors: &[PatchError]) {
         for e in errors {
-            error_logger.add_error(
-                format!(
+            self.events.publish(AppEvent::ProviderError {
+                provider: provider_name.to_string(),
+                message: format!(
                     "Provider {provider_name} returns error when changing the task: {}",
                     e.error
-                )
-                .as_str(),
-            );
+                ),
+            });
         }
     }
 
     fn inline_dialog_area(&self, size: Size, area: Rect) -> Rect {
-        let idx = self.list_state.selected().unwrap_or(0) as u16;
+        let idx = self.list_state.selected().unwrap_or(0).saturating_sub(self.offset) as u16;
 
         let mut y = area.y + 1 /*title*/ + idx+1/*right below the item*/;
         if area.height - y < size.height {
@@ -523,7 +1017,8 @@ impl TasksWidget {
 
     fn remove_changed_tasks_that_are_not_exists_anymore(&mut self) {
         self.changed_tasks.retain(|c| {
-            self.all_tasks
+            self.store
+                .tasks()
                 .iter()
                 .find(|t| c.is_task(t.as_ref()))
                 .is_some_and(|t| c.task.as_ref().is_some_and(|task| t.state() == task.state()))
@@ -536,54 +1031,166 @@ impl TasksWidget {
 
         tracing::event!(name: "load_tasks", Level::INFO, filter = ?&f, "Load tasks");
 
-        for p in self.providers_storage.write().await.iter_mut() {
-            tokio::spawn({
+        // the hidden tabs are reloaded when they're shown next time
+        for (i, t) in self.query_tabs.iter_mut().enumerate() {
+            if Some(i) != self.active_tab {
+                t.tasks = None;
+            }
+        }
+        self.load_active_tab();
+
+        for p in self.providers_storage.read().await.iter() {
+            // the same load is repeated once after the running one instead of being sent in parallel
+            let key = format!("tasks {} {f:?}", p.name);
+            if let Some(l) = self.loads.get(&p.name)
+                && l.key != key
+            {
+                l.handle.abort();
+                self.in_flight.cancel(&l.key);
+                self.loads.remove(&p.name);
+            }
+            if !self.in_flight.start(&key) {
+                continue;
+            }
+
+            self.load_generation += 1;
+            let generation = self.load_generation;
+            let handle = tokio::spawn({
                 let name = p.name.clone();
                 let s = s.clone();
+                let limit = p.limit.clone();
                 let p = p.provider.clone();
                 let f = f.clone();
+                let key = key.clone();
                 let async_jobs = self.async_jobs_storage.clone();
 
                 let span = tracing::span!(Level::INFO, "load_provider_tasks", name = name, "Load provider's tasks");
                 async move {
                     let _job = AsyncJob::new(format!("Load tasks from provider {name}").as_str(), async_jobs).await;
-                    let tasks = TaskProviderTrait::list(p.write().await.as_mut(), None, &f).await;
-
-                    let mut s = s.write().await;
-                    s.all_tasks.retain(|t| t.provider() != name);
-
-                    match tasks {
-                        Ok(t) => {
-                            s.all_tasks
-                                .append(&mut t.iter().map(|t| t.clone_boxed()).collect::<Vec<Box<dyn TaskTrait>>>());
-                            s.all_tasks.sort_by(|l, r| {
-                                due_group(&l.planned_date())
-                                    .cmp(&due_group(&r.planned_date()))
-                                    .then_with(|| r.priority().cmp(&l.priority()))
-                                    .then_with(|| l.due().cmp(&r.due()))
-                                    .then_with(|| project_name(l.as_ref()).cmp(&project_name(r.as_ref())))
-                                    .then_with(|| l.name().display().cmp(&r.name().display()))
-                            });
-
-                            s.remove_changed_tasks_that_are_not_exists_anymore();
-                            s.filter_tasks().await;
-                            let _ = s.on_changes_broadcast.send(());
-                        }
-                        Err(err) => {
-                            s.error_logger
-                                .write()
+                    loop {
+                        let tasks = loop {
+                            let tasks = limit
+                                .run(guarded(async {
+                                    TaskProviderTrait::list(p.write().await.as_mut(), None, &f).await
+                                }))
                                 .await
-                                .add_error(format!("Load provider {name} projects failure: {err}").as_str());
+                                .and_then(|t| t);
+                            if let Err(ProviderError::RateLimited { retry_after }) = &tasks {
+                                let delay = retry_after.unwrap_or(DEFAULT_RETRY_DELAY);
+                                s.read().await.events.publish(AppEvent::ProviderError {
+                                    provider: name.clone(),
+                                    message: format!(
+                                        "Provider {name}: rate limit exceeded, the tasks will be reloaded in {}s",
+                                        delay.as_secs()
+                                    ),
+                                });
+                                tokio::time::sleep(delay).await;
+                                continue;
+                            }
+                            break tasks;
+                        };
+
+                        let mut s = s.write().await;
+                        if s.loads.get(&name).is_none_or(|l| l.generation != generation) {
+                            // the load has been replaced by the newer one while it was waiting for the lock
+                            return;
+                        }
+
+                        match tasks {
+                            Ok(t) => {
+                                let count = t.len();
+                                s.store.set_provider_tasks(&name, t);
+                                s.update_waiting_tags(&name);
+
+                                s.remove_changed_tasks_that_are_not_exists_anymore();
+                                s.filter_tasks().await;
+                                s.events.publish(AppEvent::TasksLoaded {
+                                    provider: name.clone(),
+                                    count,
+                                });
+                            }
+                            Err(err) => {
+                                s.store.remove_provider_tasks(&name);
+                                s.events.publish(AppEvent::ProviderError {
+                                    provider: name.clone(),
+                                    message: format!(
+                                        "Load provider {name} projects failure: {}",
+                                        error_message(&name, &err)
+                                    ),
+                                });
+                            }
+                        }
+                        if !s.in_flight.finish(&key) {
+                            s.loads.remove(&name);
+                            break;
                         }
                     }
                 }
                 .instrument(span)
             });
+            self.loads.insert(
+                p.name.clone(),
+                ProviderLoad {
+                    key,
+                    generation,
+                    handle: handle.abort_handle(),
+                },
+            );
         }
     }
 
     pub async fn reload(&mut self) {
         self.changed_tasks.clear();
+        for t in &mut self.query_tabs {
+            t.tasks = None;
+        }
+    }
+
+    async fn switch_tab(&mut self, next: bool) {
+        if self.query_tabs.is_empty() {
+            return;
+        }
+        self.active_tab = next_tab(self.active_tab, self.query_tabs.len(), next);
+        if let Some(i) = self.active_tab
+            && self.query_tabs[i].tasks.is_none()
+        {
+            self.load_active_tab();
+        }
+        self.filter_tasks().await;
+    }
+
+    fn load_active_tab(&mut self) {
+        if let Some(i) = self.active_tab
+            && self.in_flight.start(&self.query_tabs[i].key())
+        {
+            self.spawn_query(i);
+        }
+    }
+
+    fn spawn_query(&self, i: usize) {
+        let tab = &self.query_tabs[i];
+        let name = tab.provider.clone();
+        let query = tab.query.query.clone();
+        let providers_storage = self.providers_storage.clone();
+
+        self.spawn_provider_operation(
+            format!("Load the query '{}' from provider {name}", tab.query.name),
+            async move {
+                let p = providers_storage.read().await.provider(&name);
+                let result = p
+                    .limit
+                    .run(guarded(async { p.provider.write().await.query(&query).await }))
+                    .await
+                    .and_then(|r| r);
+                Message::QueryLoaded {
+                    tab: i,
+                    result: result.map_err(|e| {
+                        tracing::error!(error=?e, query=query, "Load the query");
+                        error_message(&name, &e)
+                    }),
+                }
+            },
+        );
     }
 
     async fn show_change_due_date_dialog(&mut self) {
@@ -654,7 +1261,19 @@ impl TasksWidget {
         }
 
         let patched_task = patched_task.unwrap();
-        let t = self.tasks[selected.unwrap()].task();
+        let task = self.tasks[selected.unwrap()].shared_task().clone();
+        let t = task.as_ref();
+
+        // the blocked task is completed only after the confirmation
+        let is_completing = state.map_or(self.patched_state(t) != State::Completed, |s| s == State::Completed);
+        if is_completing && self.confirmed_blocked.take().is_none_or(|k| k != task_key(t)) {
+            let blockers = self.unfinished_blockers(t);
+            if !blockers.is_empty() {
+                self.async_command = Some(AsyncCommand::new(AsyncCommandType::CompleteBlocked, &task));
+                self.show_complete_blocked_dialog(t, &blockers);
+                return;
+            }
+        }
 
         let mut current_state = t.state();
         span.record("current_state", current_state.to_string());
@@ -681,26 +1300,66 @@ impl TasksWidget {
             match self.changed_tasks.iter_mut().find(|p| p.is_task(t)) {
                 Some(p) => p.state = ValuePatch::Value(new_state),
                 None => self.changed_tasks.push(TaskPatch {
-                    task: Some(t.clone_boxed()),
+                    task: Some(task.clone()),
                     state: ValuePatch::Value(new_state),
                     ..TaskPatch::default()
                 }),
             }
+
+            if new_state == State::Completed {
+                let subtasks = self.uncompleted_subtasks(t);
+                if subtasks.is_empty() {
+                    self.offer_follow_up(&task);
+                } else {
+                    self.async_command = Some(AsyncCommand::new(AsyncCommandType::CompleteSubtasks, &task));
+                    self.show_complete_subtasks_dialog(t, subtasks.len()).await;
+                }
+            }
         }
 
         self.recreate_current_task_row().await;
     }
 
+    fn offer_follow_up(&mut self, task: &Arc<dyn TaskTrait>) {
+        if self.follow_up.is_required(&task.labels(), &self.tag_aliases) {
+            self.async_command = Some(AsyncCommand::new(AsyncCommandType::FollowUp, task));
+            self.show_follow_up_confirmation_dialog(task.as_ref());
+        }
+    }
+
+    // The subtasks are completed along with the parent unless it's declined, the follow-up is offered after that
+    async fn on_complete_subtasks_answered(&mut self, task: &Arc<dyn TaskTrait>, complete: bool) {
+        self.async_command = None;
+        if complete {
+            for s in self.uncompleted_subtasks(task.as_ref()) {
+                if !s.patch_policy().available_states.contains(&State::Completed) {
+                    continue;
+                }
+                match self.changed_tasks.iter_mut().find(|p| p.is_task(s.as_ref())) {
+                    Some(p) => p.state = ValuePatch::Value(State::Completed),
+                    None => self.changed_tasks.push(TaskPatch {
+                        task: Some(s.clone()),
+                        state: ValuePatch::Value(State::Completed),
+                        ..TaskPatch::default()
+                    }),
+                }
+            }
+            self.filter_tasks().await;
+        }
+        self.offer_follow_up(task);
+    }
+
     async fn change_due_date(&mut self, due: &DatePatchItem) {
         if self.async_command.is_none() {
             return;
         }
 
-        let t = self.async_command.as_ref().unwrap().task.as_ref();
+        let task = self.async_command.as_ref().unwrap().task.clone();
+        let t = task.as_ref();
         match self.changed_tasks.iter_mut().find(|p| p.is_task(t)) {
             Some(p) => p.due = ValuePatch::Value(*due),
             None => self.changed_tasks.push(TaskPatch {
-                task: Some(t.clone_boxed()),
+                task: Some(task.clone()),
                 due: ValuePatch::Value(*due),
                 ..TaskPatch::default()
             }),
@@ -713,11 +1372,12 @@ impl TasksWidget {
             return;
         }
 
-        let t = self.async_command.as_ref().unwrap().task.as_ref();
+        let task = self.async_command.as_ref().unwrap().task.clone();
+        let t = task.as_ref();
         match self.changed_tasks.iter_mut().find(|p| p.is_task(t)) {
             Some(p) => p.scheduled = ValuePatch::Value(*date),
             None => self.changed_tasks.push(TaskPatch {
-                task: Some(t.clone_boxed()),
+                task: Some(task.clone()),
                 scheduled: ValuePatch::Value(*date),
                 ..TaskPatch::default()
             }),
@@ -730,7 +1390,8 @@ impl TasksWidget {
             return;
         }
 
-        let t = self.async_command.as_ref().unwrap().task.as_ref();
+        let task = self.async_command.as_ref().unwrap().task.clone();
+        let t = task.as_ref();
         match self.changed_tasks.iter_mut().find(|p| p.is_task(t)) {
             Some(p) => {
                 p.priority = if *priority == t.priority() {
@@ -743,7 +1404,7 @@ impl TasksWidget {
                 }
             }
             None => self.changed_tasks.push(TaskPatch {
-                task: Some(t.clone_boxed()),
+                task: Some(task.clone()),
                 priority: ValuePatch::Value(*priority),
                 ..TaskPatch::default()
             }),
@@ -751,6 +1412,83 @@ impl TasksWidget {
         self.recreate_current_task_row().await;
     }
 
+    async fn show_name_editor(&mut self) {
+        let Some(t) = self.selected_task() else {
+            return;
+        };
+        if !t.patch_policy().is_editable {
+            return;
+        }
+
+        let mut e = LineEdit::new(None);
+        e.set_text(t.name().raw().as_str());
+        e.set_active(true);
+        if let Some(sc) = &self.spellchecker {
+            e.set_spellchecker(sc.clone());
+        }
+        if let Some(dh) = &self.draw_helper {
+            e.set_draw_helper(dh.clone());
+        }
+        self.name_editor = Some((task_key(t.as_ref()), e));
+    }
+
+    async fn handle_name_editor_key(&mut self, key: KeyEvent) {
+        let Some((task_key, e)) = &mut self.name_editor else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Enter => {
+                let (task_key, name) = (task_key.clone(), e.text());
+                self.close_name_editor().await;
+                self.change_name(&task_key, name.trim()).await;
+            }
+            KeyCode::Esc => self.close_name_editor().await,
+            _ => {
+                e.handle_key(key).await;
+            }
+        }
+    }
+
+    async fn close_name_editor(&mut self) {
+        self.name_editor = None;
+        if let Some(dh) = &self.draw_helper {
+            dh.write().await.hide_cursor();
+        }
+    }
+
+    async fn change_name(&mut self, key: &TaskKey, name: &str) {
+        if name.is_empty() {
+            return;
+        }
+        let Some(idx) = self.tasks.iter().position(|r| task_key(r.task()) == *key) else {
+            return;
+        };
+
+        let task = self.tasks[idx].shared_task().clone();
+        let t = task.as_ref();
+        let value = if name == t.name().raw() {
+            ValuePatch::NotSet
+        } else {
+            ValuePatch::Value(name.to_string())
+        };
+        match self.changed_tasks.iter_mut().find(|p| p.is_task(t)) {
+            Some(p) => {
+                p.name = value;
+                if p.is_empty() {
+                    self.changed_tasks.retain(|c| !c.is_task(t));
+                }
+            }
+            None if value.is_set() => self.changed_tasks.push(TaskPatch {
+                task: Some(task.clone()),
+                name: value,
+                ..TaskPatch::default()
+            }),
+            None => {}
+        }
+        self.tasks[idx] = self.recreated_row(idx);
+    }
+
     async fn undo_changes(&mut self) {
         let selected = self.list_state.selected();
         if selected.is_none() {
@@ -764,64 +1502,528 @@ impl TasksWidget {
 
     async fn recreate_current_task_row(&mut self) {
         let idx = self.list_state.selected().unwrap();
-        self.tasks[idx] = TaskRow::new(self.tasks[idx].task(), &self.changed_tasks);
+        self.tasks[idx] = self.recreated_row(idx);
+    }
+
+    fn recreated_row(&self, idx: usize) -> TaskRow {
+        let r = &self.tasks[idx];
+        TaskRow::new(r.shared_task(), &self.changed_tasks, r.depth(), self.marks(r.task()))
     }
 
     async fn update_task_info_view(&mut self) {
         self.task_info_viewer.write().await.set_task(self.selected_task()).await;
     }
 
-    pub async fn show_add_task_dialog(
-        &mut self,
-        task: Option<Box<dyn TaskTrait>>,
-        state: Option<ObjectState>,
-        batch_mode: bool,
-    ) {
-        let title = if task.is_some() {
-            "Update the task"
-        } else if batch_mode {
-            "Create tasks (batch)"
-        } else {
-            "Create a task"
-        };
+    pub fn set_selected_project(&mut self, project: Option<(String, String)>) {
+        self.selected_project = project;
+    }
 
-        let mut d = CreateUpdateTaskDialog::new(title, self.providers_storage.clone()).await;
+    pub fn set_default_destination(&mut self, provider: Option<String>, project: Option<String>) {
+        self.default_destination = (provider, project);
+    }
 
-        if batch_mode {
-            d.set_batch_mode();
-        }
+    pub fn set_spellchecker(&mut self, sc: Option<SharedSpellchecker>) {
+        self.spellchecker = sc;
+    }
 
-        if let Some(t) = task {
-            d.set_task(t.as_ref()).await;
-        } else if let Some(s) = state {
-            d.restore(s).await;
-        }
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
+        self.store.set_sort_order(order);
+    }
 
-        if let Some(dh) = &self.draw_helper {
-            d.set_draw_helper(dh.clone());
-        }
+    pub fn set_tag_aliases(&mut self, aliases: TagAliases) {
+        self.tag_aliases = aliases;
+    }
 
-        self.dialog = Some(Box::new(d));
-        self.is_global_dialog = true;
+    pub fn set_follow_up(&mut self, follow_up: FollowUp) {
+        self.follow_up = follow_up;
     }
 
-    async fn show_delete_task_dialog(&mut self, task: &dyn TaskTrait) {
-        let mut d = ConfirmationDialog::new(
-            "Delete the task",
-            format!("Do you really want to delete the task\n\"{}\"?", task.name().display()).as_str(),
-            &[StandardButton::Yes, StandardButton::No],
-            StandardButton::Yes,
-        );
-        if let Some(dh) = &self.draw_helper {
-            d.set_draw_helper(dh.clone());
-        }
-        self.dialog = Some(Box::new(d));
+    pub fn set_starred_tasks(&mut self, starred: StarredTasks) {
+        self.starred = starred;
     }
 
-    async fn show_duplicate_task_dialog(&mut self, task: &dyn TaskTrait) {
+    // The relative due times and the due alarms are updated by the minute tick, only the changed rows are rebuilt.
+    // Returns the number of the reminders that have fired since the previous tick.
+    pub async fn refresh_due_times(&mut self) -> usize {
+        self.filter_tasks().await;
+
+        let now = chrono::Utc::now();
+        let fired = fired_reminders(self.store.tasks(), &self.reminders_checked_at, &now) + self.nags(&now);
+        self.reminders_checked_at = now;
+        fired
+    }
+
+    // The waiting tasks resurface every nag interval with the reminder
+    fn nags(&self, now: &DateTimeUtc) -> usize {
+        let interval = chrono::Duration::days(i64::from(self.waiting_settings.nag_interval_days));
+        self.store
+            .tasks()
+            .iter()
+            .filter_map(|t| {
+                self.is_waiting(t.as_ref())
+                    .then(|| self.waiting_since(t.as_ref()))
+                    .flatten()
+            })
+            .filter(|since| is_nag_due(since, &self.reminders_checked_at, now, interval))
+            .count()
+    }
+
+    pub fn due_soon(&self) -> DueSoon {
+        self.due_soon
+    }
+
+    pub fn set_pinned_tasks(&mut self, pinned: PinnedTasks) {
+        self.pinned = pinned;
+    }
+
+    pub fn set_waiting(&mut self, waiting: WaitingTasks, settings: Waiting) {
+        self.waiting = waiting;
+        self.waiting_settings = settings;
+    }
+
+    fn waiting_since(&self, t: &dyn TaskTrait) -> Option<DateTimeUtc> {
+        self.waiting.since(&t.provider(), &t.id())
+    }
+
+    // The completed tasks don't wait anymore
+    fn is_waiting(&self, t: &dyn TaskTrait) -> bool {
+        t.state() != State::Completed && self.waiting_since(t).is_some()
+    }
+
+    // The loaded tasks with the waiting tag start waiting, the ones without it stop
+    fn update_waiting_tags(&mut self, provider: &str) {
+        if self.waiting_settings.tag.is_none() {
+            return;
+        }
+
+        let tasks = self
+            .store
+            .tasks()
+            .iter()
+            .filter(|t| t.provider() == provider)
+            .map(|t| (t.id(), self.waiting_settings.is_tagged(&t.labels(), &self.tag_aliases)))
+            .collect::<Vec<(String, bool)>>();
+        if let Err(e) = self.waiting.update_tagged(provider, &tasks, &chrono::Utc::now()) {
+            self.events
+                .publish(AppEvent::Error(format!("Save the waiting tasks error: {e}")));
+        }
+    }
+
+    fn is_starred(&self, t: &dyn TaskTrait) -> bool {
+        self.starred.is_starred(&t.provider(), &t.id())
+    }
+
+    fn pin_position(&self, t: &dyn TaskTrait) -> Option<usize> {
+        self.pinned.position(&t.provider(), &t.id())
+    }
+
+    fn marks(&self, t: &dyn TaskTrait) -> Marks {
+        Marks {
+            starred: self.is_starred(t),
+            pinned: self.pin_position(t).is_some(),
+            waiting_since: self.is_waiting(t).then(|| self.waiting_since(t)).flatten(),
+            collapsed: self.collapsed.contains(&task_key(t)),
+            blocked: !self.unfinished_blockers(t).is_empty(),
+        }
+    }
+
+    // The state with the uncommitted changes
+    fn patched_state(&self, t: &dyn TaskTrait) -> State {
+        self.changed_tasks
+            .iter()
+            .find(|p| p.is_task(t))
+            .and_then(|p| p.state.value())
+            .unwrap_or(t.state())
+    }
+
+    fn uncompleted_subtasks(&self, t: &dyn TaskTrait) -> Vec<Arc<dyn TaskTrait>> {
+        let tasks = self
+            .store
+            .tasks()
+            .iter()
+            .map(|t| t.as_ref())
+            .collect::<Vec<&dyn TaskTrait>>();
+        task::subtasks(t, &tasks)
+            .into_iter()
+            .map(|i| &self.store.tasks()[i])
+            .filter(|s| self.patched_state(s.as_ref()) != State::Completed)
+            .cloned()
+            .collect()
+    }
+
+    // The blocking tasks that aren't completed, the ones that aren't loaded are considered completed
+    fn unfinished_blockers(&self, t: &dyn TaskTrait) -> Vec<Arc<dyn TaskTrait>> {
+        self.dependencies
+            .blockers(&t.provider(), &t.id())
+            .iter()
+            .filter_map(|r| {
+                self.store
+                    .tasks()
+                    .iter()
+                    .find(|a| a.provider() == r.provider() && a.id() == r.id())
+            })
+            .filter(|b| self.patched_state(b.as_ref()) != State::Completed)
+            .cloned()
+            .collect()
+    }
+
+    pub fn set_dependencies(&mut self, dependencies: Dependencies) {
+        self.dependencies = dependencies;
+    }
+
+    fn show_link_blocker_dialog(&mut self) {
+        let Some(idx) = self.list_state.selected().filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        let task = self.tasks[idx].shared_task().clone();
+        let blockers = self.dependencies.blockers(&task.provider(), &task.id());
+        let items = self
+            .store
+            .tasks()
+            .iter()
+            .filter(|t| task_key(t.as_ref()) != task_key(task.as_ref()))
+            .filter(|t| self.patched_state(t.as_ref()) != State::Completed)
+            .map(|t| {
+                let r = TaskRef::new(&t.provider(), &t.id());
+                BlockerItem {
+                    is_linked: blockers.contains(&r),
+                    task: r,
+                    name: t.name().plain(),
+                }
+            })
+            .sorted_by_key(|i| !i.is_linked)
+            .collect::<Vec<BlockerItem>>();
+        if items.is_empty() {
+            self.events
+                .publish(AppEvent::Error("There is no task that can block it".to_string()));
+            return;
+        }
+
+        self.async_command = Some(AsyncCommand::new(AsyncCommandType::LinkBlocker, &task));
+        let d = ListDialog::new(&items, "").searchable(true);
+        self.dialog = Some(Box::new(d));
+        self.is_global_dialog = false;
+    }
+
+    async fn toggle_blocker(&mut self, blocker: &BlockerItem) {
+        let Some(cmd) = self.async_command.take() else {
+            return;
+        };
+        let t = cmd.task.as_ref();
+        if let Err(e) = self
+            .dependencies
+            .toggle(&TaskRef::new(&t.provider(), &t.id()), &blocker.task)
+        {
+            self.events
+                .publish(AppEvent::Error(format!("Link the blocking task error: {e}")));
+            return;
+        }
+        self.filter_tasks().await;
+    }
+
+    async fn toggle_collapsed(&mut self) {
+        let Some(idx) = self.list_state.selected().filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        let key = task_key(self.tasks[idx].task());
+        if !self.collapsed.remove(&key) {
+            let tasks = self
+                .store
+                .tasks()
+                .iter()
+                .map(|t| t.as_ref())
+                .collect::<Vec<&dyn TaskTrait>>();
+            if task::subtasks(self.tasks[idx].task(), &tasks).is_empty() {
+                self.events
+                    .publish(AppEvent::Error("The task has no subtasks".to_string()));
+                return;
+            }
+            self.collapsed.insert(key);
+        }
+        self.filter_tasks().await;
+    }
+
+    async fn toggle_star(&mut self) {
+        let Some(idx) = self.list_state.selected().filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        let t = self.tasks[idx].task();
+        if let Err(e) = self.starred.toggle(&t.provider(), &t.id()) {
+            self.events
+                .publish(AppEvent::Error(format!("Star the task error: {e}")));
+            return;
+        }
+        self.filter_tasks().await;
+    }
+
+    async fn toggle_waiting(&mut self) {
+        let Some(idx) = self.list_state.selected().filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        let t = self.tasks[idx].task();
+        if self.waiting_settings.is_tagged(&t.labels(), &self.tag_aliases) {
+            self.events.publish(AppEvent::Error(
+                "The task is waiting while it has the waiting tag".to_string(),
+            ));
+            return;
+        }
+        if let Err(e) = self.waiting.toggle(&t.provider(), &t.id(), &chrono::Utc::now()) {
+            self.events
+                .publish(AppEvent::Error(format!("Mark the task as waiting error: {e}")));
+            return;
+        }
+        self.filter_tasks().await;
+    }
+
+    async fn switch_waiting_view(&mut self) {
+        self.waiting_view = !self.waiting_view;
+        self.filter_tasks().await;
+    }
+
+    async fn toggle_pin(&mut self) {
+        let Some(idx) = self.list_state.selected().filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        let t = self.tasks[idx].task();
+        if let Err(e) = self.pinned.toggle(&t.provider(), &t.id()) {
+            self.events.publish(AppEvent::Error(format!("Pin the task error: {e}")));
+            return;
+        }
+        self.filter_tasks().await;
+    }
+
+    // Moves the pinned task over its visible pinned neighbour, the selection follows the task
+    async fn move_pin(&mut self, idx: usize, down: bool) {
+        let Some(over) = (if down { idx.checked_add(1) } else { idx.checked_sub(1) })
+            .and_then(|i| self.tasks.get(i))
+            .filter(|r| r.is_pinned())
+            .map(|r| r.task())
+        else {
+            return;
+        };
+        let t = self.tasks[idx].task();
+        if let Err(e) = self
+            .pinned
+            .move_pin((&t.provider(), &t.id()), (&over.provider(), &over.id()))
+        {
+            self.events.publish(AppEvent::Error(format!("Move the pin error: {e}")));
+            return;
+        }
+        self.filter_tasks().await;
+    }
+
+    // Moves the selected task over its visible neighbour of the same provider, project and due group
+    async fn move_task(&mut self, down: bool) {
+        let Some(idx) = self.list_state.selected().filter(|i| *i < self.tasks.len()) else {
+            return;
+        };
+        if self.tasks[idx].is_pinned() {
+            self.move_pin(idx, down).await;
+            return;
+        }
+        let t = self.tasks[idx].task();
+        let provider = self.providers_storage.read().await.provider(t.provider().as_str());
+        if !provider.capabilities.manual_order {
+            return;
+        }
+        if self.sort_order != SortOrder::Native {
+            self.events.publish(AppEvent::Error(
+                "The tasks can be moved only with the native sort order".to_string(),
+            ));
+            return;
+        }
+
+        let same_group = |o: &dyn TaskTrait| {
+            o.provider() == t.provider()
+                && o.project().map(|p| p.id()) == t.project().map(|p| p.id())
+                && task::due_group(&o.planned_date()) == task::due_group(&t.planned_date())
+        };
+        let all = self
+            .store
+            .tasks()
+            .iter()
+            .filter(|o| same_group(o.as_ref()))
+            .map(|o| o.id())
+            .collect::<Vec<String>>();
+        let visible = self
+            .tasks
+            .iter()
+            .map(|r| r.task())
+            .filter(|o| same_group(*o))
+            .map(|o| o.id())
+            .collect::<Vec<String>>();
+        let Some(ids) = moved_order(&all, &visible, t.id().as_str(), down) else {
+            return;
+        };
+
+        self.spawn_provider_operation(format!("Reorder the tasks in provider {}", provider.name), async move {
+            let result = guarded(async {
+                let mut p = provider.provider.write().await;
+                p.set_order(&ids).await?;
+                p.reload().await;
+                Ok(())
+            })
+            .await
+            .and_then(|r| r);
+            match result {
+                Ok(()) => Message::TasksReordered,
+                Err(e) => {
+                    tracing::error!(error=?e, "Reorder the tasks");
+                    Message::Failed {
+                        provider: provider.name.clone(),
+                        message: error_message(&provider.name, &e),
+                    }
+                }
+            }
+        });
+    }
+
+    pub async fn show_add_task_dialog(
+        &mut self,
+        task: Option<Arc<dyn TaskTrait>>,
+        state: Option<ObjectState>,
+        batch_mode: bool,
+    ) {
+        let title = if task.is_some() {
+            "Update the task"
+        } else if batch_mode {
+            "Create tasks (batch)"
+        } else {
+            "Create a task"
+        };
+
+        let mut d = CreateUpdateTaskDialog::new(title, self.providers_storage.clone()).await;
+
+        if batch_mode {
+            d.set_batch_mode();
+        }
+
+        if let Some(t) = task {
+            d.set_task(&t).await;
+        } else if let Some(s) = state {
+            d.restore(s).await;
+        } else if let Some((provider, project_id)) = &self.selected_project {
+            d.set_destination(provider, Some(project_id)).await;
+        } else if let Some(s) = self.last_destination.clone() {
+            d.restore(s).await;
+        } else if let (Some(provider), project) = &self.default_destination {
+            d.set_destination(provider, project.as_deref()).await;
+        }
+
+        self.open_task_dialog(d);
+    }
+
+    async fn show_add_subtask_dialog(&mut self, parent: &Arc<dyn TaskTrait>, state: Option<ObjectState>) {
+        let mut d = CreateUpdateTaskDialog::new("Create a subtask", self.providers_storage.clone()).await;
+        if let Some(s) = state {
+            d.restore(s).await;
+        }
+        d.set_parent(parent).await;
+        self.open_task_dialog(d);
+    }
+
+    fn open_task_dialog(&mut self, mut d: CreateUpdateTaskDialog) {
+        if let Some(sc) = &self.spellchecker {
+            d.set_spellchecker(sc.clone());
+        }
+        if let Some(dh) = &self.draw_helper {
+            d.set_draw_helper(dh.clone());
+        }
+
+        self.dialog = Some(Box::new(d));
+        self.is_global_dialog = true;
+    }
+
+    async fn supports_subtasks(&self, provider: &str) -> bool {
+        self.providers_storage
+            .read()
+            .await
+            .iter()
+            .any(|p| p.name == provider && p.capabilities.subtasks)
+    }
+
+    async fn show_delete_task_dialog(&mut self, task: &dyn TaskTrait) {
+        if !self
+            .confirmation_settings
+            .read()
+            .await
+            .is_confirmation_required(Confirmation::DeleteTask)
+        {
+            self.on_async_command_confirmed().await;
+            return;
+        }
+
         let mut d = ConfirmationDialog::new(
-            "Duplicate the task",
-            format!("Do you want to duplicate the task\n\"{}\"?", task.name().display()).as_str(),
+            "Delete the task",
+            format!("Do you really want to delete the task\n\"{}\"?", task.name().plain()).as_str(),
+            &[StandardButton::Yes, StandardButton::No],
+            StandardButton::Yes,
+        )
+        .danger()
+        .with_dont_ask_again();
+        if let Some(dh) = &self.draw_helper {
+            d.set_draw_helper(dh.clone());
+        }
+        self.dialog = Some(Box::new(d));
+    }
+
+    async fn show_complete_subtasks_dialog(&mut self, task: &dyn TaskTrait, count: usize) {
+        if !self
+            .confirmation_settings
+            .read()
+            .await
+            .is_confirmation_required(Confirmation::CompleteSubtasks)
+        {
+            let task = self.async_command.as_ref().unwrap().task.clone();
+            self.on_complete_subtasks_answered(&task, true).await;
+            return;
+        }
+
+        let mut d = ConfirmationDialog::new(
+            "Complete the subtasks",
+            format!(
+                "The task\n\"{}\"\nhas {count} uncompleted subtask(s). Do you want to complete them too?",
+                task.name().plain()
+            )
+            .as_str(),
+            &[StandardButton::Yes, StandardButton::No],
+            StandardButton::Yes,
+        )
+        .with_dont_ask_again();
+        if let Some(dh) = &self.draw_helper {
+            d.set_draw_helper(dh.clone());
+        }
+        self.dialog = Some(Box::new(d));
+    }
+
+    fn show_complete_blocked_dialog(&mut self, task: &dyn TaskTrait, blockers: &[Arc<dyn TaskTrait>]) {
+        let mut d = ConfirmationDialog::new(
+            "The task is blocked",
+            format!(
+                "The task\n\"{}\"\nis blocked by the uncompleted tasks:\n{}\nDo you want to complete it anyway?",
+                task.name().plain(),
+                blockers.iter().map(|b| format!("- {}", b.name().plain())).join("\n")
+            )
+            .as_str(),
+            &[StandardButton::Yes, StandardButton::No],
+            StandardButton::No,
+        )
+        .danger();
+        if let Some(dh) = &self.draw_helper {
+            d.set_draw_helper(dh.clone());
+        }
+        self.dialog = Some(Box::new(d));
+    }
+
+    fn show_follow_up_confirmation_dialog(&mut self, task: &dyn TaskTrait) {
+        let mut d = ConfirmationDialog::new(
+            "Create a follow-up",
+            format!(
+                "Do you want to create a follow-up of the task\n\"{}\"?",
+                task.name().plain()
+            )
+            .as_str(),
             &[StandardButton::Yes, StandardButton::No],
             StandardButton::Yes,
         );
@@ -831,6 +2033,39 @@ impl TasksWidget {
         self.dialog = Some(Box::new(d));
     }
 
+    // The follow-up is created in the project of the completed task and due in the configured days
+    async fn show_follow_up_dialog(&mut self, task: &Arc<dyn TaskTrait>) {
+        let mut d = CreateUpdateTaskDialog::new("Create a follow-up", self.providers_storage.clone()).await;
+        let due = time::add_days(&time::today(), self.follow_up.due_in_days);
+        let name = self.follow_up.name(&task.name().raw());
+        d.set_follow_up(task, &name, due).await;
+        self.open_task_dialog(d);
+    }
+
+    async fn show_duplicate_task_dialog(&mut self, task: &dyn TaskTrait) {
+        if !self
+            .confirmation_settings
+            .read()
+            .await
+            .is_confirmation_required(Confirmation::DuplicateTask)
+        {
+            self.on_async_command_confirmed().await;
+            return;
+        }
+
+        let mut d = ConfirmationDialog::new(
+            "Duplicate the task",
+            format!("Do you want to duplicate the task\n\"{}\"?", task.name().plain()).as_str(),
+            &[StandardButton::Yes, StandardButton::No],
+            StandardButton::Yes,
+        )
+        .with_dont_ask_again();
+        if let Some(dh) = &self.draw_helper {
+            d.set_draw_helper(dh.clone());
+        }
+        self.dialog = Some(Box::new(d));
+    }
+
     #[tracing::instrument(level = "info", target = "tasks_widget")]
     async fn create_or_update_task(&mut self, patch: &Patch) {
         let provider = self
@@ -843,32 +2078,20 @@ impl TasksWidget {
 
         if let Some(task) = &tp.task {
             match self.changed_tasks.iter_mut().find(|p| p.is_task(task.as_ref())) {
-                Some(p) => {
-                    replace_if(&mut p.name, &tp.name);
-                    replace_if(&mut p.description, &tp.description);
-                    replace_if(&mut p.due, &tp.due);
-                    replace_if(&mut p.priority, &tp.priority);
-                    replace_if(&mut p.state, &tp.state);
-                }
+                Some(p) => p.merge(tp),
                 None => {
                     let mut tp = tp.clone();
-                    tp.task = Some(task.clone_boxed());
+                    tp.task = Some(task.clone());
                     self.changed_tasks.push(tp);
                 }
             }
             self.recreate_current_task_row().await;
         } else {
-            let mut provider = provider.provider.write().await;
-            match provider.create(project_id, tp).await {
-                Ok(()) => {
-                    provider.reload().await;
-                }
-                Err(e) => {
-                    tracing::error!(error=?e, "Create a task");
-                    self.error_logger.write().await.add_error(e.to_string().as_str());
-                }
-            };
-            self.load_tasks(&self.last_filter.clone()).await;
+            let project_id = project_id.clone();
+            let tp = tp.clone();
+            self.spawn_provider_operation(format!("Create a task in provider {}", provider.name), async move {
+                create_task(&provider, &project_id, &tp, "Create a task").await
+            });
         }
     }
 
@@ -881,33 +2104,39 @@ impl TasksWidget {
 
         match cmd.command_type {
             AsyncCommandType::DeleteTask => {
-                let t = cmd.task.as_ref();
+                let t = cmd.task.clone();
                 let provider = self.providers_storage.read().await.provider(t.provider().as_str());
-                let mut p = provider.provider.write().await;
-                match p.delete(t).await {
-                    Ok(_) => {
-                        self.changed_tasks.retain(|c| !c.is_task(t));
+                self.spawn_provider_operation(format!("Delete the task in provider {}", provider.name), async move {
+                    let result = guarded(async {
+                        let mut p = provider.provider.write().await;
+                        p.delete(t.as_ref()).await?;
                         p.reload().await;
-                        self.load_tasks(&self.last_filter.clone()).await;
-                    }
-                    Err(e) => {
-                        tracing::error!(error=?e, task_name=?t.name(), task_id=t.id(), "Delete the task");
-                        self.error_logger.write().await.add_error(e.to_string().as_str());
+                        Ok(())
+                    })
+                    .await
+                    .and_then(|r| r);
+                    match result {
+                        Ok(()) => Message::TaskDeleted(t),
+                        Err(e) => {
+                            tracing::error!(error=?e, task_name=?t.name(), task_id=t.id(), "Delete the task");
+                            Message::Failed {
+                                provider: provider.name.clone(),
+                                message: error_message(&provider.name, &e),
+                            }
+                        }
                     }
-                }
+                });
             }
             AsyncCommandType::DuplicateTask => {
                 let t = cmd.task.as_ref();
                 if t.project().is_none() {
-                    self.error_logger
-                        .write()
-                        .await
-                        .add_error("I can't duplicate the task with empty project");
+                    self.events.publish(AppEvent::Error(
+                        "I can't duplicate the task with empty project".to_string(),
+                    ));
                     return;
                 }
                 let project = t.project().unwrap();
                 let provider = self.providers_storage.read().await.provider(t.provider().as_str());
-                let mut p = provider.provider.write().await;
 
                 let patch = TaskPatch {
                     task: None,
@@ -917,18 +2146,15 @@ impl TasksWidget {
                     scheduled: t.scheduled().map(|d| d.into()).into(),
                     priority: ValuePatch::Value(t.priority()),
                     state: ValuePatch::Value(State::Uncompleted),
+                    labels: ValuePatch::Value(t.labels()),
+                    parent: t.parent_id().into(),
+                    url: Some(t.url()).filter(|u| !u.is_empty()).into(),
                 };
 
-                match p.create(project.id().as_str(), &patch).await {
-                    Ok(_) => {
-                        p.reload().await;
-                        self.load_tasks(&self.last_filter.clone()).await;
-                    }
-                    Err(e) => {
-                        tracing::error!(error=?e, task_name=?t.name(), task_id=t.id(), "Duplicate the task");
-                        self.error_logger.write().await.add_error(e.to_string().as_str());
-                    }
-                }
+                self.spawn_provider_operation(
+                    format!("Duplicate the task in provider {}", provider.name),
+                    async move { create_task(&provider, &project.id(), &patch, "Duplicate the task").await },
+                );
             }
             _ => panic!("Wrong command type"),
         }
@@ -937,14 +2163,41 @@ impl TasksWidget {
     }
 
     fn available_tags(&self) -> Vec<String> {
-        self.all_tasks
+        self.store
+            .tasks()
             .iter()
-            .flat_map(|t| t.labels())
+            .flat_map(|t| self.tag_aliases.tags(&t.labels()))
             .unique()
             .sorted()
             .collect_vec()
     }
 
+    async fn show_description_dialog(&mut self) {
+        if let Some(t) = self.selected_task() {
+            let description = t.description().map(|d| d.display()).unwrap_or_default();
+            self.dialog = Some(Box::new(DescriptionDialog::new(&t.name().plain(), &description)));
+            self.is_global_dialog = true;
+        }
+    }
+
+    async fn switch_involvement_filter(&mut self) {
+        if !self
+            .providers_storage
+            .read()
+            .await
+            .iter()
+            .any(|p| p.capabilities.assignees)
+        {
+            self.events.publish(AppEvent::Error(
+                "None of the providers knows the task assignees".to_string(),
+            ));
+            return;
+        }
+        let involvement = self.filter_panel.involvement().next();
+        self.filter_panel.set_involvement(involvement);
+        self.filter_tasks().await;
+    }
+
     async fn show_filter_by_tag_dialog(&mut self) {
         let mut d = MultiSelectListDialog::new(&self.available_tags());
         d.set_selected(&self.filter_panel.tag_filter());
@@ -964,7 +2217,26 @@ impl MouseHandler for TasksWidget {
 
 #[async_trait]
 impl KeyboardHandler for TasksWidget {
+    async fn handle_paste(&mut self, text: &str) -> bool {
+        if let Some((_, e)) = &mut self.name_editor {
+            return e.handle_paste(text).await;
+        }
+        match &mut self.dialog {
+            Some(d) => d.handle_paste(text).await,
+            None => false,
+        }
+    }
+
     async fn handle_key(&mut self, key: KeyEvent) -> bool {
+        // the editor takes all keys until it's closed
+        if self.name_editor.is_some() {
+            if key.kind == KeyEventKind::Press {
+                self.handle_name_editor_key(key).await;
+                self.update_task_info_view().await;
+            }
+            return true;
+        }
+
         let mut handled = false;
 
         let mut need_to_update_view = false;
@@ -973,8 +2245,15 @@ impl KeyboardHandler for TasksWidget {
         let mut new_priority = None;
         let mut patches: Vec<Patch> = Vec::new();
         let mut add_another_one_task = false;
+        let mut add_another_one_parent = None;
         let mut create_task_dialog_state = None;
         let mut tag_filter = None;
+        let mut follow_up_task = None;
+        // the parent task and whether its subtasks are completed
+        let mut subtasks_answer = None;
+        let mut complete_blocked = None;
+        let mut blocker = None;
+        let mut commit = false;
 
         if let Some(d) = &mut self.dialog {
             need_to_update_view = true;
@@ -1006,6 +2285,12 @@ impl KeyboardHandler for TasksWidget {
                     new_priority = d.selected().cloned();
                 }
 
+                if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<ListDialog<BlockerItem>>()
+                    && d.accepted()
+                {
+                    blocker = d.selected().cloned();
+                }
+
                 if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<CreateUpdateTaskDialog>()
                     && d.accepted()
                 {
@@ -1022,7 +2307,15 @@ impl KeyboardHandler for TasksWidget {
                         })
                         .collect();
                     add_another_one_task = d.add_another_one();
+                    add_another_one_parent = d.parent();
                     create_task_dialog_state = Some(d.save().await);
+                    if d.is_task_creation() {
+                        self.last_destination = Some(d.destination().await);
+                    }
+                }
+
+                if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<CommitPreviewDialog>() {
+                    commit = d.accepted();
                 }
 
                 if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<MultiSelectListDialog<String>>()
@@ -1031,10 +2324,42 @@ impl KeyboardHandler for TasksWidget {
                     tag_filter = Some(d.selected().iter().cloned().collect_vec());
                 }
 
+                if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<ConfirmationDialog>()
+                    && !d.accepted()
+                    && let Some(c) = self.async_command.as_ref()
+                    && matches!(c.command_type, AsyncCommandType::CompleteSubtasks)
+                {
+                    subtasks_answer = Some((c.task.clone(), false));
+                }
+
                 if let Some(d) = DialogTrait::as_any(d.as_ref()).downcast_ref::<ConfirmationDialog>()
                     && d.accepted()
                 {
-                    self.on_async_command_confirmed().await;
+                    if d.dont_ask_again()
+                        && let Some(c) = self.async_command.as_ref().and_then(|c| c.command_type.confirmation())
+                        && let Err(e) = self
+                            .confirmation_settings
+                            .write()
+                            .await
+                            .set_confirmation_required(c, false)
+                    {
+                        self.events
+                            .publish(AppEvent::Error(format!("Save settings error: {e}")));
+                    }
+                    // the confirmation dialog is replaced by the task dialog after it's closed
+                    match self.async_command.as_ref() {
+                        Some(c) if matches!(c.command_type, AsyncCommandType::FollowUp) => {
+                            follow_up_task = Some(c.task.clone())
+                        }
+                        // the follow-up dialog can be shown after it
+                        Some(c) if matches!(c.command_type, AsyncCommandType::CompleteSubtasks) => {
+                            subtasks_answer = Some((c.task.clone(), true))
+                        }
+                        Some(c) if matches!(c.command_type, AsyncCommandType::CompleteBlocked) => {
+                            complete_blocked = Some(c.task.clone())
+                        }
+                        _ => self.on_async_command_confirmed().await,
+                    }
                 }
 
                 self.dialog = None;
@@ -1068,7 +2393,38 @@ impl KeyboardHandler for TasksWidget {
         }
 
         if add_another_one_task {
-            self.show_add_task_dialog(None, create_task_dialog_state, false).await;
+            match add_another_one_parent {
+                Some(p) => self.show_add_subtask_dialog(&p, create_task_dialog_state).await,
+                None => self.show_add_task_dialog(None, create_task_dialog_state, false).await,
+            }
+        }
+
+        if let Some(b) = &blocker {
+            self.toggle_blocker(b).await;
+        }
+
+        if let Some(t) = &complete_blocked {
+            self.async_command = None;
+            // the dialog is modal, so the blocked task is still under cursor
+            if self
+                .selected_task()
+                .is_some_and(|s| task_key(s.as_ref()) == task_key(t.as_ref()))
+            {
+                self.confirmed_blocked = Some(task_key(t.as_ref()));
+                self.change_check_state(Some(State::Completed)).await;
+            }
+        }
+
+        if let Some((t, complete)) = &subtasks_answer {
+            self.on_complete_subtasks_answered(t, *complete).await;
+        }
+
+        if let Some(t) = &follow_up_task {
+            self.show_follow_up_dialog(t).await;
+        }
+
+        if commit && !self.is_committing {
+            self.commit_changes().await;
         }
 
         if let Some(f) = &tag_filter {
@@ -1082,6 +2438,42 @@ impl KeyboardHandler for TasksWidget {
 
 impl TasksWidget {
     // rendering
+    fn render_tabs(&self, area: Rect, buf: &mut Buffer) {
+        let mut spans = Vec::new();
+        let names = std::iter::once("All".to_string()).chain(
+            self.query_tabs
+                .iter()
+                .map(|t| format!("{}: {}", t.provider, t.query.name)),
+        );
+        for (i, name) in names.enumerate() {
+            if i != 0 {
+                spans.push(Span::styled("│", default_style()));
+            }
+            let is_active = if i == 0 {
+                self.active_tab.is_none()
+            } else {
+                self.active_tab == Some(i - 1)
+            };
+            spans.push(Span::styled(
+                format!(" {name} "),
+                if is_active {
+                    selected_row_style()
+                } else {
+                    default_style()
+                },
+            ));
+        }
+        Line::from(spans).render(
+            Rect {
+                x: area.x + 1,
+                y: area.y,
+                width: area.width.saturating_sub(2),
+                height: 1,
+            },
+            buf,
+        );
+    }
+
     async fn render_scrollbar(&mut self, area: Rect, buf: &mut Buffer, pos: usize) {
         let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
             .begin_symbol(Some("↑"))
@@ -1100,29 +2492,37 @@ impl TasksWidget {
     }
 
     async fn render_tasks(&mut self, area: Rect, buf: &mut Buffer, selected: usize) {
-        let skip_count = {
-            let mut selected = selected;
-            let mut height = area.y;
-            while selected != 0 && height < area.height {
-                height += self.tasks[selected].size().height;
-                selected -= 1;
-            }
-
-            selected
-        };
+        if self.tasks.is_empty() {
+            self.offset = 0;
+            self.visible_rows = 0..0;
+            return;
+        }
 
-        let mut y = area.y;
+        let height = |i: usize| self.tasks[i].size().height;
+        self.offset = scroll_offset(self.offset, selected, area.height, height);
+        let visible_rows = self.offset..visible_end(self.offset, self.tasks.len(), area.height, height);
 
-        for (i, w) in self.tasks.iter_mut().enumerate() {
-            if i < skip_count || y > area.height {
+        // only the rows that were shown before are hidden, so the long lists aren't iterated on every frame
+        if visible_rows != self.visible_rows {
+            let len = self.tasks.len();
+            for w in &mut self.tasks[self.visible_rows.start.min(len)..self.visible_rows.end.min(len)] {
                 w.set_visible(false);
-                continue;
             }
+            self.visible_rows = visible_rows.clone();
+        }
 
+        let mut y = area.y;
+        let mut selected_y = None;
+
+        for i in visible_rows {
+            let w = &mut self.tasks[i];
             w.set_visible(true);
 
             let is_row_selected = selected == i;
             w.set_selected(is_row_selected);
+            if is_row_selected {
+                selected_y = Some(y);
+            }
 
             Text::from(if is_row_selected { ">" } else { " " })
                 .style(default_style())
@@ -1141,6 +2541,21 @@ impl TasksWidget {
             let size = w.size();
             y += size.height;
         }
+
+        // the editor's frame covers the neighbour rows, so the text is right in place of the row
+        if let Some(y) = selected_y
+            && let Some((_, e)) = &mut self.name_editor
+        {
+            let editor_area = Rect {
+                x: area.x + 1,
+                y: y.saturating_sub(1).max(area.y),
+                width: area.width.saturating_sub(2),
+                height: 3,
+            }
+            .intersection(*buf.area());
+            Clear {}.render(editor_area, buf);
+            e.render(editor_area, buf).await;
+        }
     }
 }
 
@@ -1148,11 +2563,15 @@ impl TasksWidget {
 impl WidgetTrait for TasksWidget {
     async fn render(&mut self, area: Rect, buf: &mut Buffer) {
         if self.list_state.selected().is_some_and(|idx| idx >= self.tasks.len()) {
-            self.list_state.select(Some(0));
+            self.list_state.select(self.tasks.len().checked_sub(1));
         }
 
         let changed = &self.changed_tasks;
-        let mut title = format!("Tasks ({})", self.tasks.len());
+        let mut title = if self.waiting_view {
+            format!("Waiting tasks ({}, use 'f'+'w' to show all)", self.tasks.len())
+        } else {
+            format!("Tasks ({})", self.tasks.len())
+        };
 
         if !changed.is_empty() {
             title.push_str(format!(" (uncommitted count {}, use 'c'+'c' to commit them)", changed.len()).as_str());
@@ -1164,6 +2583,12 @@ impl WidgetTrait for TasksWidget {
         let mut list_area = area;
         list_area.y += 1;
 
+        if !self.query_tabs.is_empty() {
+            self.render_tabs(list_area, buf);
+            list_area.y += 1;
+            list_area.height = list_area.height.saturating_sub(1);
+        }
+
         if !self.filter_panel.is_empty() {
             list_area.height -= self.filter_panel.size().height;
         }
@@ -1210,21 +2635,432 @@ impl WidgetTrait for TasksWidget {
     }
 }
 
+const LAST_DESTINATION_KEY: &str = "last_destination";
+
+#[async_trait]
+impl StatefulObject for TasksWidget {
+    async fn save(&self) -> ObjectState {
+        let mut result = ObjectState::default();
+        if let Some(s) = &self.last_destination {
+            result.insert_str(LAST_DESTINATION_KEY, s.clone().into());
+        }
+        result
+    }
+
+    async fn restore(&mut self, state: ObjectState) {
+        self.last_destination = state.get(LAST_DESTINATION_KEY).and_then(|v| state_from_str(v).ok());
+    }
+}
+
 impl std::fmt::Debug for TasksWidget {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "TasksWidget")
     }
 }
 
-fn project_name(t: &dyn TaskTrait) -> String {
-    t.project().map(|p| p.name()).unwrap_or_default()
+// The user can fix the authentication problems only in the settings, so they are pointed out explicitly
+async fn create_task(provider: &Provider, project_id: &str, tp: &TaskPatch, operation: &str) -> Message {
+    let result = guarded(async {
+        let mut p = provider.provider.write().await;
+        p.create(project_id, tp).await?;
+        p.reload().await;
+        Ok(())
+    })
+    .await
+    .and_then(|r| r);
+    match result {
+        Ok(()) => Message::TaskCreated,
+        Err(e) => {
+            tracing::error!(error=?e, task_name=?tp.name, operation, "Create a task");
+            Message::Failed {
+                provider: provider.name.clone(),
+                message: error_message(&provider.name, &e),
+            }
+        }
+    }
 }
 
-fn replace_if<T>(op: &mut ValuePatch<T>, other: &ValuePatch<T>)
-where
-    T: Clone,
-{
-    if other.is_set() {
-        *op = other.clone();
+// Returns the new order of all the ids after moving the task over its visible neighbour,
+// the hidden tasks keep their places
+fn moved_order(all: &[String], visible: &[String], id: &str, down: bool) -> Option<Vec<String>> {
+    let pos = visible.iter().position(|v| v == id)?;
+    let neighbour = if down {
+        visible.get(pos + 1)
+    } else {
+        visible.get(pos.checked_sub(1)?)
+    }?;
+
+    let mut result = all.iter().filter(|v| *v != id).cloned().collect::<Vec<String>>();
+    let neighbour_pos = result.iter().position(|v| v == neighbour)?;
+    result.insert(if down { neighbour_pos + 1 } else { neighbour_pos }, id.to_string());
+    Some(result)
+}
+
+// The provider and the id of the task
+type TaskKey = (String, String);
+
+fn task_key(t: &dyn TaskTrait) -> TaskKey {
+    (t.provider(), t.id())
+}
+
+// The uncompleted tasks of all the providers that are due soon, they're counted for the footer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct DueSoon {
+    pub next_hour: usize,
+    pub today: usize,
+}
+
+fn due_soon(tasks: &[Arc<dyn TaskTrait>]) -> DueSoon {
+    let now = chrono::Utc::now();
+    let mut result = DueSoon::default();
+    for t in tasks.iter().filter(|t| t.state() != State::Completed) {
+        let Some(due) = t.due() else {
+            continue;
+        };
+        if time::due_alarm(&due, &now) != DueAlarm::None {
+            result.next_hour += 1;
+        }
+        if task::due_group(&Some(due)) == Due::Today {
+            result.today += 1;
+        }
+    }
+    result
+}
+
+// The reminders and the due times of the uncompleted tasks that have passed in the period,
+// the dates without the time don't fire
+fn fired_reminders(tasks: &[Arc<dyn TaskTrait>], since: &DateTimeUtc, now: &DateTimeUtc) -> usize {
+    tasks
+        .iter()
+        .filter(|t| t.state() != State::Completed)
+        .filter(|t| {
+            t.reminders()
+                .into_iter()
+                .chain(t.due())
+                .any(|d| !time::is_date_only(&d) && since < &d && &d <= now)
+        })
+        .count()
+}
+
+// Returns the index of the previously selected task in the rebuilt list.
+// If the task is gone, the nearest task that is still in the list is selected: the following ones first, then the preceding ones.
+fn restore_selection(previous: &[TaskKey], selected: Option<usize>, current: &[TaskKey]) -> Option<usize> {
+    if current.is_empty() {
+        return None;
+    }
+
+    let selected = selected.unwrap_or_default();
+    let position = |k: &TaskKey| current.iter().position(|c| c == k);
+    if selected < previous.len() {
+        let following = previous[selected..].iter();
+        let preceding = previous[..selected].iter().rev();
+        if let Some(idx) = following.chain(preceding).find_map(position) {
+            return Some(idx);
+        }
+    }
+
+    Some(selected.min(current.len() - 1))
+}
+
+// Returns the first shown row, so the selected one is in the view and the view is scrolled as little as possible
+fn scroll_offset(offset: usize, selected: usize, view_height: u16, height: impl Fn(usize) -> u16) -> usize {
+    if selected <= offset {
+        return selected;
+    }
+
+    let mut offset = offset;
+    let mut rows_height: u32 = (offset..=selected).map(|i| height(i) as u32).sum();
+    while offset < selected && rows_height > view_height as u32 {
+        rows_height -= height(offset) as u32;
+        offset += 1;
+    }
+    offset
+}
+
+// Returns the end of the rows that fit the view starting from the offset
+fn visible_end(offset: usize, len: usize, view_height: u16, height: impl Fn(usize) -> u16) -> usize {
+    let mut end = offset;
+    let mut rows_height: u32 = 0;
+    while end < len && rows_height < view_height as u32 {
+        rows_height += height(end) as u32;
+        end += 1;
+    }
+    end
+}
+
+// The tabs are switched in a circle, the tab with all the tasks goes before the query tabs
+fn next_tab(current: Option<usize>, count: usize, forward: bool) -> Option<usize> {
+    match (current, forward) {
+        (None, true) => (count > 0).then_some(0),
+        (None, false) => count.checked_sub(1),
+        (Some(i), true) => (i + 1 < count).then_some(i + 1),
+        (Some(i), false) => i.checked_sub(1),
+    }
+}
+
+fn error_message(provider_name: &str, e: &ProviderError) -> String {
+    match e {
+        ProviderError::Auth(_) => {
+            format!("{e}. Check the credentials of the provider {provider_name} in the settings")
+        }
+        _ => e.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn moved_order_test() {
+        struct Case<'a> {
+            name: &'a str,
+            all: &'a [&'a str],
+            visible: &'a [&'a str],
+            id: &'a str,
+            down: bool,
+            expected: Option<&'a [&'a str]>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "down",
+                all: &["a", "b", "c"],
+                visible: &["a", "b", "c"],
+                id: "a",
+                down: true,
+                expected: Some(&["b", "a", "c"]),
+            },
+            Case {
+                name: "up",
+                all: &["a", "b", "c"],
+                visible: &["a", "b", "c"],
+                id: "c",
+                down: false,
+                expected: Some(&["a", "c", "b"]),
+            },
+            Case {
+                name: "the first one up",
+                all: &["a", "b"],
+                visible: &["a", "b"],
+                id: "a",
+                down: false,
+                expected: None,
+            },
+            Case {
+                name: "the last one down",
+                all: &["a", "b"],
+                visible: &["a", "b"],
+                id: "b",
+                down: true,
+                expected: None,
+            },
+            Case {
+                name: "over the hidden task",
+                all: &["a", "hidden", "b", "c"],
+                visible: &["a", "b", "c"],
+                id: "a",
+                down: true,
+                expected: Some(&["hidden", "b", "a", "c"]),
+            },
+        ];
+
+        for c in CASES {
+            let to_vec = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+            assert_eq!(
+                moved_order(&to_vec(c.all), &to_vec(c.visible), c.id, c.down),
+                c.expected.map(to_vec),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn restore_selection_test() {
+        struct Case<'a> {
+            name: &'a str,
+            previous: &'a [&'a str],
+            selected: Option<usize>,
+            current: &'a [&'a str],
+            expected: Option<usize>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "empty list",
+                previous: &["a", "b"],
+                selected: Some(1),
+                current: &[],
+                expected: None,
+            },
+            Case {
+                name: "the task is moved",
+                previous: &["a", "b", "c"],
+                selected: Some(1),
+                current: &["b", "c", "a"],
+                expected: Some(0),
+            },
+            Case {
+                name: "the following task is selected",
+                previous: &["a", "b", "c", "d"],
+                selected: Some(1),
+                current: &["d", "c", "a"],
+                expected: Some(1),
+            },
+            Case {
+                name: "the preceding task is selected",
+                previous: &["a", "b", "c"],
+                selected: Some(2),
+                current: &["b", "a"],
+                expected: Some(0),
+            },
+            Case {
+                name: "no known tasks",
+                previous: &["a", "b", "c"],
+                selected: Some(2),
+                current: &["d", "e"],
+                expected: Some(1),
+            },
+            Case {
+                name: "nothing was selected",
+                previous: &[],
+                selected: None,
+                current: &["a"],
+                expected: Some(0),
+            },
+        ];
+
+        let keys = |ids: &[&str]| {
+            ids.iter()
+                .map(|id| ("Home".to_string(), id.to_string()))
+                .collect::<Vec<TaskKey>>()
+        };
+        for c in CASES {
+            assert_eq!(
+                restore_selection(&keys(c.previous), c.selected, &keys(c.current)),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn scroll_offset_test() {
+        struct Case<'a> {
+            name: &'a str,
+            offset: usize,
+            selected: usize,
+            expected: usize,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "selected in the view",
+                offset: 2,
+                selected: 4,
+                expected: 2,
+            },
+            Case {
+                name: "selected above the view",
+                offset: 5,
+                selected: 3,
+                expected: 3,
+            },
+            Case {
+                name: "selected below the view",
+                offset: 0,
+                selected: 6,
+                expected: 4,
+            },
+            Case {
+                name: "selected right after the view",
+                offset: 2,
+                selected: 5,
+                expected: 3,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                scroll_offset(c.offset, c.selected, 3, |_| 1),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+
+        // the tall row is shown from the top even if it doesn't fit the view
+        assert_eq!(scroll_offset(0, 2, 3, |i| if i == 2 { 5 } else { 1 }), 2);
+    }
+
+    #[test]
+    fn visible_end_test() {
+        assert_eq!(visible_end(0, 10, 3, |_| 1), 3);
+        assert_eq!(visible_end(8, 10, 3, |_| 1), 10);
+        assert_eq!(visible_end(0, 10, 3, |i| if i == 0 { 2 } else { 1 }), 2);
+        assert_eq!(visible_end(0, 0, 3, |_| 1), 0);
+    }
+
+    #[test]
+    fn next_tab_test() {
+        struct Case<'a> {
+            name: &'a str,
+            current: Option<usize>,
+            count: usize,
+            forward: bool,
+            expected: Option<usize>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "from all to the first query",
+                current: None,
+                count: 2,
+                forward: true,
+                expected: Some(0),
+            },
+            Case {
+                name: "from all to the last query",
+                current: None,
+                count: 2,
+                forward: false,
+                expected: Some(1),
+            },
+            Case {
+                name: "from the last query to all",
+                current: Some(1),
+                count: 2,
+                forward: true,
+                expected: None,
+            },
+            Case {
+                name: "from the first query to all",
+                current: Some(0),
+                count: 2,
+                forward: false,
+                expected: None,
+            },
+            Case {
+                name: "to the next query",
+                current: Some(0),
+                count: 2,
+                forward: true,
+                expected: Some(1),
+            },
+            Case {
+                name: "no queries",
+                current: None,
+                count: 0,
+                forward: false,
+                expected: None,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                next_tab(c.current, c.count, c.forward),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
     }
 }
@@ -0,0 +1,333 @@
+// SPDX-License-Identifier: MIT
+
+// The optional terminal features are enabled only if the terminal supports them,
+// e.g. the Linux console doesn't understand the keyboard enhancement and the true colors
+
+use std::{io::Write, process::ExitStatus, sync::Mutex};
+
+use crossterm::{
+    event::{
+        DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyEventKind,
+        KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::EnterAlternateScreen,
+};
+use ratatui::DefaultTerminal;
+
+use super::style::{self, ColorSupport};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    pub keyboard_enhancement: bool,
+    pub mouse_capture: bool,
+    pub bracketed_paste: bool,
+    pub colors: ColorSupport,
+}
+
+// The variables of the environment that tell what the terminal is
+#[derive(Debug, Default)]
+struct Env {
+    term: Option<String>,
+    color_term: Option<String>,
+    in_tmux: bool,
+}
+
+impl Env {
+    fn current() -> Self {
+        Self {
+            term: std::env::var("TERM").ok(),
+            color_term: std::env::var("COLORTERM").ok(),
+            in_tmux: std::env::var_os("TMUX").is_some(),
+        }
+    }
+
+    fn is_multiplexer(&self) -> bool {
+        self.in_tmux
+            || self
+                .term
+                .as_deref()
+                .is_some_and(|t| t.starts_with("tmux") || t.starts_with("screen"))
+    }
+
+    fn is_basic_console(&self) -> bool {
+        matches!(
+            self.term.as_deref(),
+            None | Some("" | "dumb" | "linux" | "vt100" | "vt220" | "ansi" | "cons25")
+        )
+    }
+}
+
+// The terminal is asked about the keyboard enhancement only if it can answer,
+// the multiplexers and the consoles don't pass the request through or ignore it
+fn detect(env: &Env, supports_keyboard_enhancement: impl FnOnce() -> bool) -> Capabilities {
+    let basic = env.is_basic_console();
+
+    let colors = if basic {
+        ColorSupport::Basic
+    } else if env
+        .color_term
+        .as_deref()
+        .is_some_and(|c| c == "truecolor" || c == "24bit")
+    {
+        ColorSupport::TrueColor
+    } else {
+        ColorSupport::Indexed
+    };
+
+    Capabilities {
+        keyboard_enhancement: !basic && !env.is_multiplexer() && supports_keyboard_enhancement(),
+        mouse_capture: env.term.as_deref().is_some_and(|t| !matches!(t, "" | "dumb" | "linux")),
+        bracketed_paste: !basic,
+        colors,
+    }
+}
+
+static ENABLED: Mutex<Option<Capabilities>> = Mutex::new(None);
+
+// Detects the capabilities and enables the supported features, it should be called in the raw mode
+pub fn enable() -> Capabilities {
+    let mut caps = detect(&Env::current(), || {
+        crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false)
+    });
+    tracing::info!(target:"terminal", capabilities=?caps, "Detect the terminal capabilities");
+
+    style::set_color_support(caps.colors);
+
+    if caps.mouse_capture
+        && let Err(e) = execute!(std::io::stdout(), EnableMouseCapture)
+    {
+        tracing::error!(target:"terminal", error=?e, "Enable the mouse capture");
+        caps.mouse_capture = false;
+    }
+
+    if caps.bracketed_paste
+        && let Err(e) = execute!(std::io::stdout(), EnableBracketedPaste)
+    {
+        tracing::error!(target:"terminal", error=?e, "Enable the bracketed paste");
+        caps.bracketed_paste = false;
+    }
+
+    if caps.keyboard_enhancement
+        && let Err(e) = execute!(
+            std::io::stdout(),
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )
+    {
+        tracing::error!(target:"terminal", error=?e, "Enable the keyboard enhancement");
+        caps.keyboard_enhancement = false;
+    }
+
+    *ENABLED.lock().unwrap() = Some(caps);
+    caps
+}
+
+// Disables only the features that have been enabled, it's safe to call it several times
+pub fn restore() {
+    let Some(caps) = ENABLED.lock().map(|mut c| c.take()).unwrap_or_default() else {
+        return;
+    };
+
+    if caps.keyboard_enhancement {
+        let _ = execute!(std::io::stdout(), PopKeyboardEnhancementFlags);
+    }
+    if caps.bracketed_paste {
+        let _ = execute!(std::io::stdout(), DisableBracketedPaste);
+    }
+    if caps.mouse_capture {
+        let _ = execute!(std::io::stdout(), DisableMouseCapture);
+    }
+}
+
+// The terminal is given back to the shell while tatuin is suspended or runs a shell command
+pub fn leave() {
+    restore();
+    ratatui::restore();
+}
+
+pub fn enter(terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    execute!(std::io::stdout(), EnterAlternateScreen)?;
+    enable();
+    // the screen has been changed by the shell, so it's redrawn entirely
+    terminal.clear()
+}
+
+// Stops the process like Ctrl+Z does in the cooked mode, the function returns when the process is continued
+pub fn suspend() {
+    // SIGTSTP is handled by tatuin itself, so the process is stopped by the signal that can't be handled
+    #[cfg(unix)]
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+}
+
+// Runs the command in the user's shell and waits for a key press to show its output
+pub async fn run_shell_command(command: &str) -> std::io::Result<ExitStatus> {
+    let shell = std::env::var("SHELL").unwrap_or_else(|_| "sh".to_string());
+    let status = tokio::process::Command::new(shell)
+        .arg("-c")
+        .arg(command)
+        .status()
+        .await?;
+
+    print!("\n{status}. Press any key to return to Tatuin");
+    std::io::stdout().flush()?;
+    crossterm::terminal::enable_raw_mode()?;
+    tokio::task::spawn_blocking(|| {
+        while let Ok(e) = crossterm::event::read() {
+            if let Event::Key(k) = e
+                && k.kind == KeyEventKind::Press
+            {
+                break;
+            }
+        }
+    })
+    .await?;
+
+    Ok(status)
+}
+
+pub enum JobControl {
+    Suspend,
+    Resume,
+}
+
+// The job control signals that are sent to the process by the shell or by kill
+pub struct JobControlSignals {
+    #[cfg(unix)]
+    suspend: tokio::signal::unix::Signal,
+    #[cfg(unix)]
+    resume: tokio::signal::unix::Signal,
+}
+
+impl JobControlSignals {
+    pub fn new() -> std::io::Result<Self> {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{SignalKind, signal};
+            Ok(Self {
+                suspend: signal(SignalKind::from_raw(libc::SIGTSTP))?,
+                resume: signal(SignalKind::from_raw(libc::SIGCONT))?,
+            })
+        }
+        #[cfg(not(unix))]
+        Ok(Self {})
+    }
+
+    pub async fn recv(&mut self) -> JobControl {
+        #[cfg(unix)]
+        tokio::select! {
+            _ = self.suspend.recv() => JobControl::Suspend,
+            _ = self.resume.recv() => JobControl::Resume,
+        }
+        #[cfg(not(unix))]
+        std::future::pending().await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detect_test() {
+        struct Case<'a> {
+            name: &'a str,
+            term: Option<&'a str>,
+            color_term: Option<&'a str>,
+            in_tmux: bool,
+            keyboard: bool,
+            expected: Capabilities,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "modern terminal",
+                term: Some("xterm-kitty"),
+                color_term: Some("truecolor"),
+                in_tmux: false,
+                keyboard: true,
+                expected: Capabilities {
+                    keyboard_enhancement: true,
+                    mouse_capture: true,
+                    bracketed_paste: true,
+                    colors: ColorSupport::TrueColor,
+                },
+            },
+            Case {
+                name: "terminal without the keyboard enhancement",
+                term: Some("xterm-256color"),
+                color_term: None,
+                in_tmux: false,
+                keyboard: false,
+                expected: Capabilities {
+                    keyboard_enhancement: false,
+                    mouse_capture: true,
+                    bracketed_paste: true,
+                    colors: ColorSupport::Indexed,
+                },
+            },
+            Case {
+                name: "tmux",
+                term: Some("tmux-256color"),
+                color_term: Some("24bit"),
+                in_tmux: true,
+                keyboard: true,
+                expected: Capabilities {
+                    keyboard_enhancement: false,
+                    mouse_capture: true,
+                    bracketed_paste: true,
+                    colors: ColorSupport::TrueColor,
+                },
+            },
+            Case {
+                name: "screen",
+                term: Some("screen"),
+                color_term: None,
+                in_tmux: false,
+                keyboard: true,
+                expected: Capabilities {
+                    keyboard_enhancement: false,
+                    mouse_capture: true,
+                    bracketed_paste: true,
+                    colors: ColorSupport::Indexed,
+                },
+            },
+            Case {
+                name: "linux console",
+                term: Some("linux"),
+                color_term: Some("truecolor"),
+                in_tmux: false,
+                keyboard: true,
+                expected: Capabilities {
+                    keyboard_enhancement: false,
+                    mouse_capture: false,
+                    bracketed_paste: false,
+                    colors: ColorSupport::Basic,
+                },
+            },
+            Case {
+                name: "no TERM",
+                term: None,
+                color_term: None,
+                in_tmux: false,
+                keyboard: true,
+                expected: Capabilities {
+                    keyboard_enhancement: false,
+                    mouse_capture: false,
+                    bracketed_paste: false,
+                    colors: ColorSupport::Basic,
+                },
+            },
+        ];
+
+        for c in CASES {
+            let env = Env {
+                term: c.term.map(|t| t.to_string()),
+                color_term: c.color_term.map(|t| t.to_string()),
+                in_tmux: c.in_tmux,
+            };
+            assert_eq!(detect(&env, || c.keyboard), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: MIT
+
+// The text is edited by the grapheme clusters, so the emojis with modifiers and the combined characters
+// are one symbol, and laid out by the display width, so the wide characters take two cells
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+pub fn graphemes(s: &str) -> Vec<&str> {
+    s.graphemes(true).collect()
+}
+
+pub fn grapheme_count(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+pub fn width(s: &str) -> usize {
+    s.width()
+}
+
+// The byte offset of the grapheme, the length of the text for the position after the last one
+pub fn byte_index(s: &str, pos: usize) -> usize {
+    s.grapheme_indices(true).nth(pos).map(|(i, _)| i).unwrap_or(s.len())
+}
+
+// The number of the graphemes before the byte offset
+pub fn grapheme_index(s: &str, byte_idx: usize) -> usize {
+    s.grapheme_indices(true).take_while(|(i, _)| *i < byte_idx).count()
+}
+
+// The display width of the graphemes before the position
+pub fn column(graphemes: &[&str], pos: usize) -> usize {
+    graphemes.iter().take(pos).map(|g| g.width()).sum()
+}
+
+// The longest beginning of the text that fits the width
+pub fn truncate(s: &str, width: usize) -> &str {
+    let mut w = 0;
+    for (i, g) in s.grapheme_indices(true) {
+        w += g.width();
+        if w > width {
+            return &s[..i];
+        }
+    }
+    s
+}
+
+// Returns the first graphemes of the rows the line is wrapped to.
+// The line is broken after the last space that fits the width, the longer words are broken by the width.
+pub fn wrap(line: &str, width: usize) -> Vec<usize> {
+    let graphemes = graphemes(line);
+    let width = width.max(1);
+
+    let mut result = vec![0];
+    let mut start = 0;
+    loop {
+        let mut w = 0;
+        let mut end = start;
+        while end < graphemes.len() && w + graphemes[end].width() <= width {
+            w += graphemes[end].width();
+            end += 1;
+        }
+        if end == graphemes.len() {
+            break;
+        }
+
+        start = (start..end)
+            .rev()
+            .find(|i| graphemes[*i] == " ")
+            .map(|i| i + 1)
+            .unwrap_or(end.max(start + 1));
+        if start == graphemes.len() {
+            break;
+        }
+        result.push(start);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrap_test() {
+        struct Case<'a> {
+            name: &'a str,
+            line: &'a str,
+            width: usize,
+            expected: &'a [usize],
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "empty line",
+                line: "",
+                width: 10,
+                expected: &[0],
+            },
+            Case {
+                name: "short line",
+                line: "buy milk",
+                width: 10,
+                expected: &[0],
+            },
+            Case {
+                name: "break after the space",
+                line: "buy milk and bread",
+                width: 10,
+                expected: &[0, 9],
+            },
+            Case {
+                name: "break the long word",
+                line: "abcdefghijklmnopqrstuvwxyz",
+                width: 10,
+                expected: &[0, 10, 20],
+            },
+            Case {
+                name: "non-ascii",
+                line: "купить молоко",
+                width: 8,
+                expected: &[0, 7],
+            },
+            Case {
+                name: "wide characters",
+                line: "買い物リスト",
+                width: 5,
+                expected: &[0, 2, 4],
+            },
+            Case {
+                name: "the character is wider than the width",
+                line: "買い",
+                width: 1,
+                expected: &[0, 1],
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(wrap(c.line, c.width), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn graphemes_test() {
+        let s = "a👍🏽b\u{301}";
+        assert_eq!(graphemes(s), vec!["a", "👍🏽", "b\u{301}"]);
+        assert_eq!(grapheme_count(s), 3);
+        assert_eq!(width(s), 4);
+        assert_eq!(byte_index(s, 2), 9);
+        assert_eq!(byte_index(s, 3), s.len());
+        assert_eq!(grapheme_index(s, 9), 2);
+        assert_eq!(column(&graphemes(s), 2), 3);
+    }
+
+    #[test]
+    fn truncate_test() {
+        assert_eq!(truncate("buy milk", 3), "buy");
+        assert_eq!(truncate("買い物", 3), "買");
+        assert_eq!(truncate("買い物", 10), "買い物");
+        assert_eq!(truncate("👍🏽ok", 2), "👍🏽");
+    }
+}
@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: MIT
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Alignment, Rect, Size},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph, Widget},
+};
+use tatuin_core::types::ArcRwLockBlocked;
+
+use super::{
+    shortcut::{SharedData, keys_to_str},
+    style, text_layout,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Continuation {
+    keys: String,
+    name: String,
+}
+
+// Returns the shortcuts that can be completed after the entered keys
+fn continuations(prefix: &[char], shortcuts: &[ArcRwLockBlocked<SharedData>]) -> Vec<Continuation> {
+    let mut result = shortcuts
+        .iter()
+        .filter_map(|s| {
+            let d = s.read().unwrap();
+            if d.keys.len() > prefix.len() && d.keys.starts_with(prefix) {
+                Some(Continuation {
+                    keys: keys_to_str(&d.keys[prefix.len()..]),
+                    name: d.name.clone(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect::<Vec<Continuation>>();
+    result.sort_by(|l, r| l.keys.cmp(&r.keys).then_with(|| l.name.cmp(&r.name)));
+    result.dedup();
+    result
+}
+
+// The transient popup with the possible continuations of the partially entered key chord
+pub struct WhichKey {
+    prefix: String,
+    items: Vec<Continuation>,
+}
+
+impl WhichKey {
+    pub fn new(prefix: &[char], shortcuts: &[ArcRwLockBlocked<SharedData>]) -> Self {
+        Self {
+            prefix: keys_to_str(prefix),
+            items: continuations(prefix, shortcuts),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn size(&self) -> Size {
+        let width = self
+            .items
+            .iter()
+            .map(|c| text_layout::width(&c.keys) + text_layout::width(&c.name) + 3)
+            .max()
+            .unwrap_or_default()
+            .max(text_layout::width(&self.prefix) + 2);
+        Size::new(width as u16 + 2, self.items.len() as u16 + 2)
+    }
+}
+
+impl Widget for &WhichKey {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let b = Block::default()
+            .style(style::default_style())
+            .title_top(format!(" {} ", self.prefix))
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(style::border_color());
+
+        let lines = self
+            .items
+            .iter()
+            .map(|c| {
+                Line::from(vec![
+                    Span::styled(c.keys.clone(), style::key_help_value_fg()),
+                    Span::raw(" → "),
+                    Span::styled(c.name.clone(), style::key_help_name_fg()),
+                ])
+            })
+            .collect::<Vec<Line>>();
+
+        Paragraph::new(lines).block(b).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ui::shortcut::Shortcut;
+
+    #[test]
+    fn continuations_test() {
+        let shortcuts = [
+            Shortcut::new("Commit changes", &['c', 'c']),
+            Shortcut::new("Change due date of the task", &['c', 'd']),
+            Shortcut::new("Create a task", &['a']),
+            Shortcut::new("Change priority of the task", &['c', 'p']),
+        ]
+        .iter()
+        .map(|s| s.internal_data())
+        .collect::<Vec<ArcRwLockBlocked<SharedData>>>();
+
+        struct Case<'a> {
+            name: &'a str,
+            prefix: &'a [char],
+            expected: &'a [(&'a str, &'a str)],
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "chord prefix",
+                prefix: &['c'],
+                expected: &[
+                    ("c", "Commit changes"),
+                    ("d", "Change due date of the task"),
+                    ("p", "Change priority of the task"),
+                ],
+            },
+            Case {
+                name: "complete shortcut",
+                prefix: &['a'],
+                expected: &[],
+            },
+            Case {
+                name: "unknown prefix",
+                prefix: &['x'],
+                expected: &[],
+            },
+        ];
+
+        for c in CASES {
+            let expected = c
+                .expected
+                .iter()
+                .map(|(keys, name)| Continuation {
+                    keys: keys.to_string(),
+                    name: name.to_string(),
+                })
+                .collect::<Vec<Continuation>>();
+            assert_eq!(
+                continuations(c.prefix, &shortcuts),
+                expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -19,7 +19,7 @@ pub use filter_panel::Panel as FilterPanel;
 pub use hyperlink_widget::HyperlinkWidget;
 pub use line_edit::LineEdit;
 pub use markdown_view::{Config as MarkdownViewConfig, MarkdownView};
-pub use task_row::TaskRow;
+pub use task_row::{Marks, TaskRow, set_due_display};
 pub use text::Text;
 pub use text_edit::TextEdit;
 pub use widget::{WidgetState, WidgetStateTrait, WidgetTrait};
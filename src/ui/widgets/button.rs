@@ -20,6 +20,8 @@ pub struct Button {
     title: String,
     width: u16,
     tx: broadcast::Sender<()>,
+    // the destructive action is highlighted when the button is active
+    is_danger: bool,
     widget_state: WidgetState,
 }
 crate::impl_widget_state_trait!(Button);
@@ -31,6 +33,7 @@ impl Button {
             title: title.to_string(),
             width: 0,
             tx,
+            is_danger: false,
             widget_state: WidgetState::default(),
         };
         s.calculate_width();
@@ -45,6 +48,10 @@ impl Button {
         self.tx.subscribe()
     }
 
+    pub fn set_danger(&mut self, is_danger: bool) {
+        self.is_danger = is_danger;
+    }
+
     pub fn set_title(&mut self, title: &str) {
         self.title = title.to_string();
         self.calculate_width();
@@ -65,7 +72,9 @@ impl std::fmt::Debug for Button {
 #[async_trait]
 impl WidgetTrait for Button {
     async fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let s = if self.widget_state.is_active() {
+        let s = if self.widget_state.is_active() && self.is_danger {
+            style::danger_button_style()
+        } else if self.widget_state.is_active() {
             style::active_button_style()
         } else if self.widget_state.is_enabled() {
             style::enabled_button_style()
@@ -111,6 +111,7 @@ struct InternalData<T> {
     custom_widgets: Vec<Arc<dyn WidgetTrait>>,
     custom_widget_item_updaters: Vec<Arc<dyn CustomWidgetItemUpdater<T>>>,
     dialog: Option<ListDialog<String>>,
+    is_searchable: bool,
 }
 
 pub struct ComboBox<T> {
@@ -168,6 +169,7 @@ where
             custom_widgets: Vec::new(),
             custom_widget_item_updaters: Vec::new(),
             dialog: None,
+            is_searchable: false,
         }));
 
         tokio::spawn({
@@ -184,10 +186,13 @@ where
                             let mut data = internal_data.write().await;
                             let custom_widgets_count = data.custom_widgets.len();
                             let items = data.items.iter().take(data.items.len() - custom_widgets_count)
-                                        .map(|item| item.text.clone()).collect::<Vec<String>>();
+                                        .map(|item| item.display.clone()).collect::<Vec<String>>();
                             if !items.is_empty() {
-                                let selected = data.selected.as_ref().map(|item| item.text.clone()).unwrap_or_default();
-                                let mut d = ListDialog::new(&items, selected.as_str()).show_top_title(false).show_bottom_title(false);
+                                let selected = data.selected.as_ref().map(|item| item.display.clone()).unwrap_or_default();
+                                let mut d = ListDialog::new(&items, selected.as_str())
+                                    .show_top_title(false)
+                                    .show_bottom_title(false)
+                                    .searchable(data.is_searchable);
 
                                 // clear active flag
                                 for w in data.custom_widgets.iter_mut() {
@@ -237,6 +242,12 @@ where
         data.custom_widget_item_updaters.clear();
     }
 
+    // The items can be filtered by typing in the drop-down list
+    pub async fn searchable(self) -> Self {
+        self.internal_data.write().await.is_searchable = true;
+        self
+    }
+
     pub async fn current_item(self, item: &Item<T>) -> Self {
         let mut result = self;
         result.set_current_item(item).await;
@@ -263,6 +274,17 @@ where
         false
     }
 
+    // Selects the first item that satisfies the predicate
+    pub async fn set_current_item_if(&mut self, f: impl Fn(&Item<T>) -> bool) -> bool {
+        let mut data = self.internal_data.write().await;
+        if let Some(item) = data.items.iter().find(|i| f(i)) {
+            data.selected = Some(item.clone());
+            return true;
+        }
+
+        false
+    }
+
     pub async fn set_current_item_index(&self, index: &Option<usize>) -> bool {
         let mut data = self.internal_data.write().await;
         if let Some(idx) = index
@@ -3,15 +3,19 @@
 use std::any::Any;
 
 use async_trait::async_trait;
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
 use ratatui::{
     buffer::Buffer,
-    layout::{Constraint, Layout, Rect, Size},
-    style::Style,
-    text::Text,
+    layout::{Rect, Size},
+    style::Modifier,
+    text::{Line, Span, Text},
     widgets::Widget,
 };
-use tatuin_core::{task::DateTimeUtc, time::clear_time};
+use tatuin_core::{
+    task::DateTimeUtc,
+    time::{self, clear_time},
+};
 
 use super::WidgetTrait;
 use crate::ui::{
@@ -21,16 +25,55 @@ use crate::ui::{
     widgets::{WidgetState, WidgetStateTrait},
 };
 
-#[derive(PartialEq, Eq)]
-enum Element {
-    Year,
-    Month,
-    Day,
+const WEEK_HEADER: &str = "Wk Mo Tu We Th Fr Sa Su";
+const CALENDAR_HEIGHT: u16 = 8; // the date, the week header and up to 6 weeks
+
+// The week number and the days of the month, the days of the other months are None
+type Week = (u32, [Option<u32>; 7]);
+
+fn month_grid(date: NaiveDate) -> Vec<Week> {
+    let first = date.with_day(1).unwrap();
+    let mut day = first - Days::new(first.weekday().num_days_from_monday() as u64);
+
+    let mut result = Vec::new();
+    while day.month() == first.month() || day < first {
+        let mut week = (day.iso_week().week(), [None; 7]);
+        for d in week.1.iter_mut() {
+            if day.month() == first.month() {
+                *d = Some(day.day());
+            }
+            day = day + Days::new(1);
+        }
+        result.push(week);
+    }
+    result
+}
+
+// The day of the month is kept if it's possible, otherwise the last day is taken
+fn shift_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let m = Months::new(months.unsigned_abs());
+    let result = if months < 0 {
+        date.checked_sub_months(m)
+    } else {
+        date.checked_add_months(m)
+    };
+    result.unwrap_or(date)
 }
 
+fn shift_days(date: NaiveDate, days: i64) -> NaiveDate {
+    let d = Days::new(days.unsigned_abs());
+    let result = if days < 0 {
+        date.checked_sub_days(d)
+    } else {
+        date.checked_add_days(d)
+    };
+    result.unwrap_or(date)
+}
+
+// The calendar is shown while the editor is active:
+// h/l move by a day, j/k by a week, </> by a month, t selects today and Esc leaves the calendar
 pub struct DateEditor {
     dt: DateTimeUtc,
-    current_element: Element,
     widget_state: WidgetState,
 }
 crate::impl_widget_state_trait!(DateEditor);
@@ -38,8 +81,7 @@ crate::impl_widget_state_trait!(DateEditor);
 impl DateEditor {
     pub fn new(dt: Option<DateTimeUtc>) -> Self {
         Self {
-            dt: clear_time(&dt.unwrap_or(chrono::Local::now().to_utc())),
-            current_element: Element::Day,
+            dt: clear_time(&dt.unwrap_or(time::today())),
             widget_state: WidgetState::default(),
         }
     }
@@ -48,58 +90,67 @@ impl DateEditor {
         clear_time(&self.dt)
     }
 
-    fn style(&self, element: Element) -> Style {
-        if self.is_active() && self.current_element == element {
-            style::date_time_editor_active_element()
-        } else {
-            style::date_time_editor_inactive_element()
-        }
+    fn set_date(&mut self, date: NaiveDate) {
+        self.dt = date.and_time(self.dt.time()).and_utc();
     }
 
-    fn suffix(&self, element: Element) -> &str {
-        if self.is_active() && self.current_element == element {
-            return "↕";
-        }
+    fn week_line(&self, week: &Week) -> Line<'static> {
+        let today = time::today().date_naive();
+        let date = self.dt.date_naive();
+
+        let mut spans = vec![Span::styled(format!("{:>2} ", week.0), style::label_style())];
+        for (i, day) in week.1.iter().enumerate() {
+            let Some(day) = day else {
+                spans.push(Span::raw("   "));
+                continue;
+            };
 
-        if element == Element::Day { " " } else { "-" }
+            let mut s = style::date_time_editor_inactive_element();
+            if *day == date.day() {
+                s = style::date_time_editor_active_element();
+            }
+            if date.with_day(*day) == Some(today) {
+                s = s.add_modifier(Modifier::UNDERLINED);
+            }
+            if i >= Weekday::Sat.num_days_from_monday() as usize {
+                s = s.add_modifier(Modifier::BOLD);
+            }
+            spans.push(Span::styled(format!("{day:>2}"), s));
+            spans.push(Span::raw(" "));
+        }
+        Line::from(spans)
     }
 }
 
 #[async_trait]
 impl WidgetTrait for DateEditor {
     async fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        let [
-            year_area,
-            year_suffix_area,
-            month_area,
-            month_suffix_area,
-            day_area,
-            day_suffix_area,
-        ] = Layout::horizontal([
-            Constraint::Length(4),
-            Constraint::Length(1),
-            Constraint::Length(2),
-            Constraint::Length(1),
-            Constraint::Length(2),
-            Constraint::Length(1),
-        ])
-        .areas(area);
-
-        let suffix_style = style::date_time_editor_inactive_element();
-        Text::styled(format!("{}", self.dt.format("%Y")), self.style(Element::Year)).render(year_area, buf);
-        Text::styled(self.suffix(Element::Year), suffix_style).render(year_suffix_area, buf);
-
-        Text::styled(format!("{}", self.dt.format("%m")), self.style(Element::Month)).render(month_area, buf);
-        Text::styled(self.suffix(Element::Month), suffix_style).render(month_suffix_area, buf);
-
-        Text::styled(format!("{}", self.dt.format("%d")), self.style(Element::Day)).render(day_area, buf);
-        if self.is_active() && self.current_element == Element::Day {
-            Text::styled(self.suffix(Element::Day), suffix_style).render(day_suffix_area, buf);
+        if !self.is_active() {
+            Line::styled(
+                format!("{}", self.dt.format("%Y-%m-%d")),
+                style::date_time_editor_inactive_element(),
+            )
+            .render(area, buf);
+            return;
         }
+
+        let mut lines = vec![
+            Line::styled(
+                format!("{}", self.dt.format("%Y-%m-%d  < %B >")),
+                style::date_time_editor_active_element(),
+            ),
+            Line::styled(WEEK_HEADER, style::label_style()),
+        ];
+        lines.extend(month_grid(self.dt.date_naive()).iter().map(|w| self.week_line(w)));
+        Text::from(lines).render(area, buf);
     }
 
     fn size(&self) -> Size {
-        Size::new(Text::from("yyyy-mm-dd").width() as u16, 1)
+        if self.is_active() {
+            Size::new(Text::from(WEEK_HEADER).width() as u16, CALENDAR_HEIGHT)
+        } else {
+            Size::new(Text::from("yyyy-mm-dd").width() as u16, 1)
+        }
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -114,35 +165,16 @@ impl KeyboardHandler for DateEditor {
             return false;
         }
 
+        let date = self.dt.date_naive();
         match key.code {
-            KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => match self.current_element {
-                Element::Year => {
-                    return false;
-                }
-                Element::Month => self.current_element = Element::Year,
-                Element::Day => self.current_element = Element::Month,
-            },
-            KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => match self.current_element {
-                Element::Year => self.current_element = Element::Month,
-                Element::Month => self.current_element = Element::Day,
-                Element::Day => {
-                    return false;
-                }
-            },
-            KeyCode::Char('k') | KeyCode::Up => {
-                self.dt = match self.current_element {
-                    Element::Year => self.dt.checked_add_months(chrono::Months::new(12)).unwrap_or(self.dt),
-                    Element::Month => self.dt.checked_add_months(chrono::Months::new(1)).unwrap_or(self.dt),
-                    Element::Day => self.dt.checked_add_days(chrono::Days::new(1)).unwrap_or(self.dt),
-                }
-            }
-            KeyCode::Char('j') | KeyCode::Down => {
-                self.dt = match self.current_element {
-                    Element::Year => self.dt.checked_sub_months(chrono::Months::new(12)).unwrap_or(self.dt),
-                    Element::Month => self.dt.checked_sub_months(chrono::Months::new(1)).unwrap_or(self.dt),
-                    Element::Day => self.dt.checked_sub_days(chrono::Days::new(1)).unwrap_or(self.dt),
-                }
-            }
+            KeyCode::Char('h') | KeyCode::Left => self.set_date(shift_days(date, -1)),
+            KeyCode::Char('l') | KeyCode::Right => self.set_date(shift_days(date, 1)),
+            KeyCode::Char('k') | KeyCode::Up => self.set_date(shift_days(date, -7)),
+            KeyCode::Char('j') | KeyCode::Down => self.set_date(shift_days(date, 7)),
+            KeyCode::Char('<') | KeyCode::PageUp => self.set_date(shift_months(date, -1)),
+            KeyCode::Char('>') | KeyCode::PageDown => self.set_date(shift_months(date, 1)),
+            KeyCode::Char('t') => self.set_date(time::today().date_naive()),
+            KeyCode::Esc => self.set_active(false),
             _ => {
                 return false;
             }
@@ -155,3 +187,90 @@ impl KeyboardHandler for DateEditor {
 impl MouseHandler for DateEditor {
     async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn month_grid_test() {
+        // October 2026 starts on Thursday and ends on Saturday
+        let grid = month_grid(date("2026-10-15"));
+        assert_eq!(grid.len(), 5);
+        assert_eq!(grid[0], (40, [None, None, None, Some(1), Some(2), Some(3), Some(4)]));
+        assert_eq!(
+            grid[4],
+            (44, [Some(26), Some(27), Some(28), Some(29), Some(30), Some(31), None])
+        );
+
+        // February 2021 starts on Monday and takes exactly 4 weeks
+        let grid = month_grid(date("2021-02-01"));
+        assert_eq!(grid.len(), 4);
+        assert_eq!(grid[0].1[0], Some(1));
+        assert_eq!(grid[3].1[6], Some(28));
+
+        // the first days of January can belong to the last week of the previous year
+        assert_eq!(month_grid(date("2027-01-10"))[0].0, 53);
+    }
+
+    #[test]
+    fn shift_test() {
+        struct Case<'a> {
+            name: &'a str,
+            date: &'a str,
+            months: i32,
+            days: i64,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "next month",
+                date: "2026-10-15",
+                months: 1,
+                days: 0,
+                expected: "2026-11-15",
+            },
+            Case {
+                name: "the last day of the shorter month",
+                date: "2026-01-31",
+                months: 1,
+                days: 0,
+                expected: "2026-02-28",
+            },
+            Case {
+                name: "previous month through the year",
+                date: "2026-01-15",
+                months: -1,
+                days: 0,
+                expected: "2025-12-15",
+            },
+            Case {
+                name: "next week through the month",
+                date: "2026-10-29",
+                months: 0,
+                days: 7,
+                expected: "2026-11-05",
+            },
+            Case {
+                name: "previous day",
+                date: "2026-03-01",
+                months: 0,
+                days: -1,
+                expected: "2026-02-28",
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                shift_days(shift_months(date(c.date), c.months), c.days),
+                date(c.expected),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -2,6 +2,8 @@
 
 use std::any::Any;
 
+use tatuin_core::task_store::Involvement;
+
 use crate::ui::style;
 use async_trait::async_trait;
 use crossterm::event::{KeyEvent, MouseEvent};
@@ -20,6 +22,7 @@ use super::widget::WidgetTrait;
 
 pub struct Panel {
     tag_filter: Vec<String>,
+    involvement: Involvement,
     widget_state: WidgetState,
 }
 crate::impl_widget_state_trait!(Panel);
@@ -28,12 +31,13 @@ impl Panel {
     pub fn new() -> Self {
         Self {
             tag_filter: Vec::new(),
+            involvement: Involvement::default(),
             widget_state: WidgetState::default(),
         }
     }
 
     pub fn is_empty(&self) -> bool {
-        self.tag_filter.is_empty()
+        self.tag_filter.is_empty() && self.involvement == Involvement::All
     }
 
     pub fn tag_filter(&self) -> Vec<String> {
@@ -43,6 +47,14 @@ impl Panel {
     pub fn set_tag_filter(&mut self, filter: &[String]) {
         self.tag_filter = filter.to_vec()
     }
+
+    pub fn involvement(&self) -> Involvement {
+        self.involvement
+    }
+
+    pub fn set_involvement(&mut self, involvement: Involvement) {
+        self.involvement = involvement
+    }
 }
 
 #[async_trait]
@@ -79,6 +91,13 @@ impl WidgetTrait for Panel {
             lines.push(Line::from(spans));
         }
 
+        if self.involvement != Involvement::All {
+            lines.push(Line::from(Span::styled(
+                format!("👤 {}", self.involvement),
+                panel_style,
+            )));
+        }
+
         let mut r = Rect {
             x: area.x + 1,
             y: area.y + 1,
@@ -104,6 +123,9 @@ impl WidgetTrait for Panel {
         if !self.tag_filter.is_empty() {
             height += 1;
         }
+        if self.involvement != Involvement::All {
+            height += 1;
+        }
 
         Size::new(0, height)
     }
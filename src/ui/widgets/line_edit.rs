@@ -3,18 +3,24 @@
 use std::any::Any;
 
 use super::{WidgetState, WidgetStateTrait, WidgetTrait};
-use crate::ui::{
-    draw_helper::{CursorStyle, DrawHelper},
-    keyboard_handler::KeyboardHandler,
-    mouse_handler::MouseHandler,
-    style,
+use crate::{
+    spellcheck::SharedSpellchecker,
+    ui::{
+        draw_helper::{CursorStyle, DrawHelper},
+        keyboard_handler::KeyboardHandler,
+        mouse_handler::MouseHandler,
+        style,
+        text_layout::{self, byte_index, grapheme_count},
+    },
 };
 use async_trait::async_trait;
 use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use itertools::Itertools;
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect, Size},
-    text::Span,
+    style::Style,
+    text::Line,
     widgets::{Block, Paragraph, Widget},
 };
 use regex::Regex;
@@ -22,6 +28,7 @@ use regex::Regex;
 pub struct LineEdit {
     text: String,
     validator: Option<Regex>,
+    spellchecker: Option<SharedSpellchecker>,
     cursor_pos: u16,
     last_cursor_pos: Position,
     draw_helper: Option<DrawHelper>,
@@ -62,6 +69,7 @@ impl LineEdit {
         Self {
             text: String::new(),
             validator,
+            spellchecker: None,
             draw_helper: None,
             cursor_pos: 0,
             last_cursor_pos: Position::default(),
@@ -75,7 +83,7 @@ impl LineEdit {
 
     pub fn set_text(&mut self, text: &str) {
         self.text = text.to_string();
-        self.cursor_pos = text.chars().count() as u16;
+        self.cursor_pos = grapheme_count(text) as u16;
     }
 
     pub fn clear(&mut self) {
@@ -85,6 +93,10 @@ impl LineEdit {
     pub fn is_empty(&self) -> bool {
         self.text.is_empty()
     }
+
+    pub fn set_spellchecker(&mut self, sc: SharedSpellchecker) {
+        self.spellchecker = Some(sc);
+    }
 }
 
 #[async_trait]
@@ -94,26 +106,39 @@ impl WidgetTrait for LineEdit {
 
         let inner_area = b.inner(area);
 
-        let mut text = self.text.clone();
-        let mut cursor_pos = self.cursor_pos as usize;
+        let graphemes = text_layout::graphemes(&self.text);
+        let cursor_pos = self.cursor_pos as usize;
 
-        let mut s = Span::raw(text.clone());
-        while s.width() >= inner_area.width as usize {
-            if cursor_pos > 1 {
-                text.remove(0);
-                cursor_pos -= 1;
+        // the visible graphemes, the cursor is kept inside
+        let (mut start, mut end) = (0, graphemes.len());
+        while end > start && text_layout::column(&graphemes[start..end], end - start) >= inner_area.width as usize {
+            if cursor_pos > start + 1 {
+                start += 1;
             } else {
-                text.pop();
+                end -= 1;
             }
-            s.content = text.clone().into();
         }
 
-        Paragraph::new(text.as_str()).block(b).render(area, buf);
+        let misspelled = self
+            .spellchecker
+            .as_ref()
+            .map(|sc| sc.misspelled(&self.text))
+            .unwrap_or_default();
+        let spans = style::styled_spans(&graphemes[start..end], |i| {
+            if misspelled.iter().any(|(from, to)| (*from..*to).contains(&(i + start))) {
+                style::misspelled_word_style()
+            } else {
+                Style::default()
+            }
+        });
+
+        Paragraph::new(Line::from(spans)).block(b).render(area, buf);
 
         if let Some(dh) = &self.draw_helper
             && self.is_active()
         {
-            let pos = Position::new(inner_area.x + cursor_pos as u16, inner_area.y);
+            let column = text_layout::column(&graphemes[start..], cursor_pos - start);
+            let pos = Position::new(inner_area.x + column as u16, inner_area.y);
 
             if pos != self.last_cursor_pos {
                 dh.write().await.set_cursor_pos(pos, Some(CursorStyle::BlinkingBar));
@@ -138,8 +163,6 @@ impl WidgetTrait for LineEdit {
 #[async_trait]
 impl KeyboardHandler for LineEdit {
     async fn handle_key(&mut self, key: KeyEvent) -> bool {
-        let cursort_at_end = self.cursor_pos == self.text.chars().count() as u16;
-
         match key.code {
             KeyCode::Char(ch) => {
                 let validated = self
@@ -147,24 +170,17 @@ impl KeyboardHandler for LineEdit {
                     .as_ref()
                     .is_none_or(|v| v.is_match(format!("{}{ch}", self.text).as_str()));
                 if validated {
-                    if cursort_at_end {
-                        self.text.push(ch);
-                    } else {
-                        self.text
-                            .insert(self.text.char_indices().nth(self.cursor_pos as usize).unwrap().0, ch);
-                    }
-                    self.cursor_pos += 1;
+                    let idx = byte_index(&self.text, self.cursor_pos as usize);
+                    self.text.insert(idx, ch);
+                    // the character can be combined with the previous one into one grapheme
+                    self.cursor_pos = text_layout::grapheme_index(&self.text, idx + ch.len_utf8()) as u16;
                 }
             }
             KeyCode::Backspace => {
-                if cursort_at_end {
-                    if !self.text.is_empty() {
-                        self.text.pop();
-                        self.cursor_pos -= 1;
-                    }
-                } else if self.cursor_pos != 0 {
-                    self.text
-                        .remove(self.text.char_indices().nth(self.cursor_pos as usize - 1).unwrap().0);
+                if self.cursor_pos != 0 {
+                    let from = byte_index(&self.text, self.cursor_pos as usize - 1);
+                    let to = byte_index(&self.text, self.cursor_pos as usize);
+                    self.text.replace_range(from..to, "");
                     self.cursor_pos -= 1;
                 }
             }
@@ -174,7 +190,7 @@ impl KeyboardHandler for LineEdit {
                 }
             }
             KeyCode::Right => {
-                if self.cursor_pos != self.text.chars().count() as u16 {
+                if self.cursor_pos != grapheme_count(&self.text) as u16 {
                     self.cursor_pos += 1;
                 }
             }
@@ -184,9 +200,64 @@ impl KeyboardHandler for LineEdit {
         }
         true
     }
+
+    // The lines of the pasted text are joined, the line edit can't have several lines
+    async fn handle_paste(&mut self, text: &str) -> bool {
+        let text = text.lines().map(str::trim).filter(|l| !l.is_empty()).join(" ");
+        let idx = byte_index(&self.text, self.cursor_pos as usize);
+        let mut new_text = self.text.clone();
+        new_text.insert_str(idx, &text);
+        if self.validator.as_ref().is_none_or(|v| v.is_match(&new_text)) {
+            self.text = new_text;
+            self.cursor_pos = text_layout::grapheme_index(&self.text, idx + text.len()) as u16;
+        }
+        true
+    }
 }
 
 #[async_trait]
 impl MouseHandler for LineEdit {
     async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn graphemes_editing_test() {
+        let mut e = LineEdit::new(None);
+        e.set_text("a👍🏽");
+        assert_eq!(e.cursor_pos, 2);
+
+        for ch in ['e', '\u{301}'] {
+            e.handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE)).await;
+        }
+        assert_eq!(e.text(), "a👍🏽e\u{301}");
+        assert_eq!(e.cursor_pos, 3);
+
+        e.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE)).await;
+        e.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE))
+            .await;
+        assert_eq!(e.text(), "ae\u{301}");
+        assert_eq!(e.cursor_pos, 1);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn paste_test() {
+        let mut e = LineEdit::new(None);
+        e.set_text("buy ");
+        assert!(e.handle_paste("milk\n\n  and bread \n").await);
+        assert_eq!(e.text(), "buy milk and bread");
+        assert_eq!(e.cursor_pos, 18);
+
+        let mut e = LineEdit::new(Some(Regex::new(r"^\d*$").unwrap()));
+        e.handle_paste("12\n3").await;
+        assert_eq!(e.text(), "", "the text doesn't match the validator");
+        e.handle_paste("123").await;
+        assert_eq!(e.text(), "123");
+    }
+}
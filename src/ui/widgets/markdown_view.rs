@@ -129,10 +129,14 @@ impl WidgetTrait for MarkdownView {
         for line in self.lines.write().await.iter_mut() {
             let mut line_area = area;
             for w in line {
+                if line_area.width == 0 {
+                    break;
+                }
                 let size = w.size();
                 w.set_pos(Position::new(line_area.x, line_area.y));
                 w.render(line_area, buf).await;
                 line_area.x += size.width;
+                line_area.width = line_area.width.saturating_sub(size.width);
             }
             area.y += 1;
         }
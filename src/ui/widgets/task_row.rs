@@ -1,32 +1,91 @@
 // SPDX-License-Identifier: MIT
 
 use super::{MarkdownView, Text, WidgetState, WidgetStateTrait, WidgetTrait};
-use crate::ui::{keyboard_handler::KeyboardHandler, mouse_handler::MouseHandler, style, widgets::MarkdownViewConfig};
+use crate::{
+    settings::DueDisplay,
+    ui::{keyboard_handler::KeyboardHandler, mouse_handler::MouseHandler, style, widgets::MarkdownViewConfig},
+};
 use async_trait::async_trait;
-use chrono::{Local, NaiveTime};
+use chrono::Local;
 use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect, Size},
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
+};
+use std::{
+    any::Any,
+    cmp::Ordering,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering as AtomicOrdering},
+    },
 };
-use std::{any::Any, cmp::Ordering};
 use tatuin_core::{
-    task::{self, Task as TaskTrait},
+    filter::Due,
+    task::{self, DateTimeUtc, Priority, State, Task as TaskTrait},
     task_patch::TaskPatch,
+    time::{self, DueAlarm},
 };
 
-pub struct TaskRow {
-    task: Box<dyn TaskTrait>,
-    pos: Position,
-    children: Vec<Box<dyn WidgetTrait>>,
-    is_selected: bool,
-    widget_state: WidgetState,
+static RELATIVE_DUE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_due_display(d: DueDisplay) {
+    RELATIVE_DUE.store(d == DueDisplay::Relative, AtomicOrdering::Relaxed);
 }
-crate::impl_widget_state_trait!(TaskRow);
 
-impl TaskRow {
-    pub fn new(t: &dyn TaskTrait, changed_tasks: &[TaskPatch]) -> Self {
+fn is_relative_due() -> bool {
+    RELATIVE_DUE.load(AtomicOrdering::Relaxed)
+}
+
+fn due_text(due: &Option<DateTimeUtc>) -> String {
+    match due {
+        Some(d) if is_relative_due() => time::relative_due(d, &chrono::Utc::now(), &Local, time::day_start_hour()),
+        _ => task::datetime_to_str(*due, &Local::now().timezone()),
+    }
+}
+
+// The marks of the task that are kept locally, not by the provider
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct Marks {
+    pub starred: bool,
+    pub pinned: bool,
+    // the time the delegated task has started waiting
+    pub waiting_since: Option<DateTimeUtc>,
+    // the subtasks of the task are hidden
+    pub collapsed: bool,
+    // some of the tasks blocking it aren't completed
+    pub blocked: bool,
+}
+
+// Everything the row shows, so the row is rebuilt only when it changes
+#[derive(PartialEq)]
+struct Content {
+    name: String,
+    state: State,
+    due: Option<DateTimeUtc>,
+    // the relative due changes with the time, so the row is rebuilt when it does
+    due_text: String,
+    alarm: DueAlarm,
+    scheduled: Option<DateTimeUtc>,
+    priority: Priority,
+    has_description: bool,
+    place: String,
+    labels: Vec<String>,
+    assignee: Option<String>,
+    comment_count: u64,
+    last_activity: Option<DateTimeUtc>,
+    uncommitted: bool,
+    marks: Marks,
+    // the waiting time grows, so the row is rebuilt when it does
+    waiting_text: Option<String>,
+    fg_color: Color,
+    // the nesting level of the subtask
+    depth: usize,
+}
+
+impl Content {
+    fn new(t: &dyn TaskTrait, changed_tasks: &[TaskPatch], depth: usize, marks: Marks) -> Self {
         let mut name = t.name().display();
         let mut state = t.state();
         let mut due = t.due();
@@ -62,10 +121,10 @@ impl TaskRow {
             match task::planned_date(&scheduled, &due) {
                 Some(d) => {
                     let now = chrono::Utc::now();
-                    match d.date_naive().cmp(&now.date_naive()) {
-                        Ordering::Less => style::overdue_task_fg(),
-                        Ordering::Equal => {
-                            if d.time() == NaiveTime::default() {
+                    match task::due_group(&Some(*d)) {
+                        Due::Overdue => style::overdue_task_fg(),
+                        Due::Today => {
+                            if time::is_date_only(d) {
                                 style::today_task_fg()
                             } else {
                                 match d.cmp(&now) {
@@ -75,59 +134,167 @@ impl TaskRow {
                                 }
                             }
                         }
-                        Ordering::Greater => style::future_task_fg(),
+                        _ => style::future_task_fg(),
                     }
                 }
                 None => style::no_date_task_fg(),
             }
         };
 
+        Self {
+            name,
+            state,
+            due_text: due_text(&due),
+            alarm: match due {
+                Some(d) if state != State::Completed => time::due_alarm(&d, &chrono::Utc::now()),
+                _ => DueAlarm::None,
+            },
+            due,
+            scheduled,
+            priority,
+            has_description: !description.unwrap_or_default().is_empty(),
+            place: t.place(),
+            labels: t.labels(),
+            assignee: t.assignee(),
+            comment_count: t.comment_count().unwrap_or_default(),
+            last_activity: t.updated_at(),
+            uncommitted,
+            waiting_text: marks.waiting_since.map(|dt| time::ago(&dt, &chrono::Utc::now())),
+            marks,
+            fg_color,
+            depth,
+        }
+    }
+}
+
+pub struct TaskRow {
+    task: Arc<dyn TaskTrait>,
+    content: Content,
+    pos: Position,
+    children: Vec<Box<dyn WidgetTrait>>,
+    is_selected: bool,
+    widget_state: WidgetState,
+}
+crate::impl_widget_state_trait!(TaskRow);
+
+impl TaskRow {
+    pub fn new(t: &Arc<dyn TaskTrait>, changed_tasks: &[TaskPatch], depth: usize, marks: Marks) -> Self {
+        Self::from_content(t, Content::new(t.as_ref(), changed_tasks, depth, marks))
+    }
+
+    // Keeps the row's widgets if the shown content of the task isn't changed
+    pub fn update(mut self, t: &Arc<dyn TaskTrait>, changed_tasks: &[TaskPatch], depth: usize, marks: Marks) -> Self {
+        let content = Content::new(t.as_ref(), changed_tasks, depth, marks);
+        if content == self.content {
+            self.task = t.clone();
+            self
+        } else {
+            Self::from_content(t, content)
+        }
+    }
+
+    fn from_content(t: &Arc<dyn TaskTrait>, content: Content) -> Self {
         let tz = Local::now().timezone();
 
         let mut children: Vec<Box<dyn WidgetTrait>> = vec![
-            Box::new(Text::new(format!("[{state}] ").as_str())),
+            Box::new(Text::new(format!("[{}] ", content.state).as_str())),
             Box::new(
-                MarkdownView::new(name.as_str(), MarkdownViewConfig::default())
-                    .style(style::default_style().fg(fg_color)),
+                MarkdownView::new(content.name.as_str(), MarkdownViewConfig::default())
+                    .style(style::default_style().fg(content.fg_color)),
             ),
             Box::new(
-                Text::new(format!(" (due: {})", task::datetime_to_str(due, &tz)).as_str())
+                Text::new(format!(" (due: {})", content.due_text).as_str())
                     .style(style::default_style().fg(style::due_color())),
             ),
         ];
 
-        if scheduled.is_some() {
+        if content.marks.blocked {
+            children.insert(
+                0,
+                Box::new(Text::new("⛔ ").style(style::default_style().fg(style::overdue_task_fg()))),
+            );
+        }
+
+        if content.marks.collapsed {
+            children.insert(0, Box::new(Text::new("▸ ")));
+        }
+
+        if content.marks.starred {
+            children.insert(
+                0,
+                Box::new(Text::new("★ ").style(style::default_style().fg(style::starred_fg()))),
+            );
+        }
+
+        if let Some(w) = &content.waiting_text {
+            children.insert(
+                0,
+                Box::new(Text::new(format!("⏳{w} ").as_str()).style(style::default_style().fg(style::place_color()))),
+            );
+        }
+
+        if content.marks.pinned {
+            children.insert(0, Box::new(Text::new("📌 ")));
+        }
+
+        if content.depth > 0 {
+            children.insert(
+                0,
+                Box::new(Text::new(format!("{}↳ ", "  ".repeat(content.depth - 1)).as_str())),
+            );
+        }
+
+        if content.scheduled.is_some() {
             children.push(Box::new(
-                Text::new(format!(" (scheduled: {})", task::datetime_to_str(scheduled, &tz)).as_str())
+                Text::new(format!(" (scheduled: {})", task::datetime_to_str(content.scheduled, &tz)).as_str())
                     .style(style::default_style().fg(style::scheduled_color())),
             ));
         }
 
         children.push(Box::new(
-            Text::new(format!(" (Priority: {priority})").as_str())
-                .style(style::default_style().fg(style::priority_color(&priority))),
+            Text::new(format!(" (Priority: {})", content.priority).as_str())
+                .style(style::default_style().fg(style::priority_color(&content.priority))),
         ));
         children.push(Box::new(
-            Text::new(format!(" ({})", t.place()).as_str()).style(style::default_style().fg(style::place_color())),
+            Text::new(format!(" ({})", content.place).as_str()).style(style::default_style().fg(style::place_color())),
         ));
 
-        for l in t.labels() {
+        for l in &content.labels {
             children.push(Box::new(Text::new(" ")));
             children.push(Box::new(
                 Text::new(format!("{}{l}", style::tag_icon()).as_str()).style(style::label_style()),
             ));
         }
 
-        if !description.unwrap_or_default().is_empty() {
+        if let Some(a) = &content.assignee {
+            children.push(Box::new(
+                Text::new(format!(" 👤{a}").as_str()).style(style::default_style().fg(style::place_color())),
+            ));
+        }
+
+        // the discussed tasks show how long ago they were active
+        if content.comment_count > 0 {
+            let activity = content
+                .last_activity
+                .map(|dt| format!(" {}", time::ago(&dt, &chrono::Utc::now())))
+                .unwrap_or_default();
+            children.push(Box::new(
+                Text::new(format!(" 🗨{}{activity}", content.comment_count).as_str())
+                    .style(style::default_style().fg(style::place_color())),
+            ));
+        }
+
+        if content.has_description {
             children.push(Box::new(Text::new(" 💬")));
         }
 
-        if uncommitted {
+        if content.uncommitted {
             children.push(Box::new(Text::new(" 📤")));
         }
 
         Self {
-            task: t.clone_boxed(),
+            task: t.clone(),
+            content,
             children,
             pos: Position::default(),
             is_selected: false,
@@ -139,6 +306,18 @@ impl TaskRow {
         self.task.as_ref()
     }
 
+    pub fn shared_task(&self) -> &Arc<dyn TaskTrait> {
+        &self.task
+    }
+
+    pub fn depth(&self) -> usize {
+        self.content.depth
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.content.marks.pinned
+    }
+
     pub fn set_selected(&mut self, is_selected: bool) {
         self.is_selected = is_selected
     }
@@ -155,12 +334,17 @@ impl WidgetTrait for TaskRow {
         };
 
         let mut s = if self.is_selected {
-            buf.set_style(area, style::selected_row_style());
             style::selected_row_style()
+        } else if self.content.alarm != DueAlarm::None {
+            style::regular_row_style().bg(style::due_soon_row_bg())
         } else {
-            buf.set_style(area, style::regular_row_style());
             style::regular_row_style()
         };
+        // the task that is due in a few minutes blinks
+        if self.content.alarm == DueAlarm::Imminent {
+            s = s.add_modifier(Modifier::SLOW_BLINK);
+        }
+        buf.set_style(area, s);
         s.fg = None;
         for child in self.children.iter_mut() {
             let mut current_style = child.style();
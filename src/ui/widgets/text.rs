@@ -12,7 +12,7 @@ use ratatui::{
     widgets::Widget,
 };
 
-use crate::ui::{keyboard_handler::KeyboardHandler, mouse_handler::MouseHandler, style};
+use crate::ui::{keyboard_handler::KeyboardHandler, mouse_handler::MouseHandler, style, text_layout};
 
 use super::{WidgetState, WidgetStateTrait, WidgetTrait};
 
@@ -50,7 +50,9 @@ impl Text {
 #[async_trait]
 impl WidgetTrait for Text {
     async fn render(&mut self, area: Rect, buf: &mut Buffer) {
-        RatatuiText::styled(self.text.as_str(), self.style.add_modifier(self.modifier)).render(
+        // the wide characters that don't fit are dropped instead of being cut in half
+        let text = text_layout::truncate(self.text.as_str(), area.width as usize);
+        RatatuiText::styled(text, self.style.add_modifier(self.modifier)).render(
             Rect {
                 x: area.x,
                 y: area.y,
@@ -1,28 +1,100 @@
 // SPDX-License-Identifier: MIT
 
-use std::{any::Any, ops::Sub};
+use std::any::Any;
 
 use super::{WidgetState, WidgetStateTrait, WidgetTrait};
-use crate::ui::{
-    draw_helper::{CursorStyle, DrawHelper},
-    keyboard_handler::KeyboardHandler,
-    mouse_handler::MouseHandler,
-    style,
+use crate::{
+    spellcheck::SharedSpellchecker,
+    ui::{
+        draw_helper::{CursorStyle, DrawHelper},
+        keyboard_handler::KeyboardHandler,
+        mouse_handler::MouseHandler,
+        style,
+        text_layout::{self, byte_index, grapheme_count},
+    },
 };
 use async_trait::async_trait;
-use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
 use ratatui::{
     buffer::Buffer,
     layout::{Position, Rect, Size},
+    style::Style,
+    text::Line,
     widgets::{Block, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget, Widget},
 };
 
+const KILL_RING_SIZE: usize = 16;
+
+// The line and the grapheme in it
+type Pos = (usize, usize);
+
+// The part of the line that is rendered as one row after wrapping
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Row {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+fn is_word_char(g: &str) -> bool {
+    g.chars().next().is_some_and(|c| c.is_alphanumeric() || c == '_')
+}
+
+// The beginning of the next word or the end of the line
+fn next_word(line: &str, pos: usize) -> usize {
+    let chars = text_layout::graphemes(line);
+    let mut i = pos.min(chars.len());
+    while i < chars.len() && is_word_char(chars[i]) {
+        i += 1;
+    }
+    while i < chars.len() && !is_word_char(chars[i]) {
+        i += 1;
+    }
+    i
+}
+
+// The beginning of the current or the previous word
+fn previous_word(line: &str, pos: usize) -> usize {
+    let chars = text_layout::graphemes(line);
+    let mut i = pos.min(chars.len());
+    while i > 0 && !is_word_char(chars[i - 1]) {
+        i -= 1;
+    }
+    while i > 0 && is_word_char(chars[i - 1]) {
+        i -= 1;
+    }
+    i
+}
+
+// The selected graphemes of the row, the range is empty if nothing is selected there
+fn row_selection(row: &Row, from: Pos, to: Pos) -> (usize, usize) {
+    let clamp = |p: Pos| {
+        if p.0 < row.line {
+            row.start
+        } else if p.0 > row.line {
+            row.end
+        } else {
+            p.1.clamp(row.start, row.end)
+        }
+    };
+    let (start, end) = (clamp(from), clamp(to));
+    if start < end { (start, end) } else { (row.end, row.end) }
+}
+
+// The multi-line editor with word wrapping.
+// Home/End and Ctrl+Home/End move to the line and the text bounds, Ctrl+Left/Right move by words,
+// the moves with Shift select the text. Ctrl+C/X copy and cut the selection, Ctrl+K cuts the rest of the line,
+// Ctrl+V or Ctrl+Y paste the last cut text, Alt+Y right after pasting replaces it with the previous one.
 pub struct TextEdit {
     lines: Vec<String>,
-    current_line: usize,
-    pos_in_line: usize,
-    top_render_line: usize,
-    left_render_symbol: usize,
+    cursor: Pos,
+    selection_anchor: Option<Pos>,
+    kill_ring: Vec<String>,
+    spellchecker: Option<SharedSpellchecker>,
+    // The beginning of the pasted text and its index in the kill ring
+    last_yank: Option<(Pos, usize)>,
+    top_render_row: usize,
+    wrap_width: usize,
     last_cursor_pos: Position,
     draw_helper: Option<DrawHelper>,
     widget_state: WidgetState,
@@ -61,12 +133,15 @@ impl WidgetStateTrait for TextEdit {
 impl TextEdit {
     pub fn new() -> Self {
         Self {
-            lines: Vec::new(),
-            current_line: 0,
-            pos_in_line: 0,
+            lines: vec![String::new()],
+            cursor: (0, 0),
+            selection_anchor: None,
+            kill_ring: Vec::new(),
+            spellchecker: None,
+            last_yank: None,
             draw_helper: None,
-            top_render_line: 0,
-            left_render_symbol: 0,
+            top_render_row: 0,
+            wrap_width: 0,
             last_cursor_pos: Position::default(),
             widget_state: WidgetState::default(),
             size: Size::default(),
@@ -80,56 +155,282 @@ impl TextEdit {
     pub fn set_text(&mut self, text: &str) {
         self.clear();
         self.lines = text.split('\n').map(|s| s.to_string()).collect::<Vec<String>>();
-        self.current_line = self.lines.len().sub(1);
-        self.pos_in_line = self.end_of_current_line();
+        self.cursor = self.end_of_text();
     }
 
     pub fn clear(&mut self) {
-        self.lines.clear();
-        self.current_line = 0;
-        self.pos_in_line = 0;
-        self.top_render_line = 0;
-        self.left_render_symbol = 0;
+        self.lines = vec![String::new()];
+        self.cursor = (0, 0);
+        self.selection_anchor = None;
+        self.last_yank = None;
+        self.top_render_row = 0;
+    }
+
+    pub fn set_spellchecker(&mut self, sc: SharedSpellchecker) {
+        self.spellchecker = Some(sc);
+    }
+
+    pub fn selected_text(&self) -> Option<String> {
+        self.selection().map(|(from, to)| self.text_between(from, to))
     }
 }
 
 impl TextEdit {
-    fn current_line(&self) -> Option<&str> {
-        self.lines.get(self.current_line).map(|s| s.as_str())
+    fn line_size(&self, line: usize) -> usize {
+        self.lines.get(line).map(|l| grapheme_count(l)).unwrap_or_default()
     }
 
-    fn current_line_size(&self) -> usize {
-        self.current_line().map(|l| l.chars().count()).unwrap_or(0)
+    fn is_at_end_of_line(&self) -> bool {
+        self.cursor.1 == self.line_size(self.cursor.0)
     }
 
-    fn end_of_current_line(&self) -> usize {
-        self.current_line_size()
+    fn end_of_text(&self) -> Pos {
+        let line = self.lines.len() - 1;
+        (line, self.line_size(line))
     }
 
-    fn calculate_top_render_line(&mut self, line_count: usize) -> usize {
-        if self.top_render_line > self.current_line {
-            self.top_render_line = self.current_line;
+    fn previous_pos(&self) -> Pos {
+        match self.cursor {
+            (0, 0) => (0, 0),
+            (line, 0) => (line - 1, self.line_size(line - 1)),
+            (line, pos) => (line, pos - 1),
+        }
+    }
+
+    fn next_pos(&self) -> Pos {
+        let (line, pos) = self.cursor;
+        if pos < self.line_size(line) {
+            (line, pos + 1)
+        } else if line + 1 < self.lines.len() {
+            (line + 1, 0)
         } else {
-            self.top_render_line = self
-                .current_line
-                .checked_sub(line_count)
-                .map(|v| v + 1)
-                .unwrap_or_default();
+            self.cursor
         }
+    }
 
-        self.top_render_line
+    fn previous_word_pos(&self) -> Pos {
+        let (line, pos) = self.cursor;
+        if pos == 0 {
+            self.previous_pos()
+        } else {
+            (line, previous_word(&self.lines[line], pos))
+        }
     }
 
-    fn calculate_left_render_symbol(&mut self, max_count: usize) -> usize {
-        if self.left_render_symbol > self.pos_in_line {
-            self.left_render_symbol = self.pos_in_line;
-        } else if self.pos_in_line - self.left_render_symbol >= max_count {
-            self.left_render_symbol = self.pos_in_line - max_count + 1;
+    fn next_word_pos(&self) -> Pos {
+        let (line, pos) = self.cursor;
+        if self.is_at_end_of_line() {
+            self.next_pos()
         } else {
-            self.left_render_symbol = self.pos_in_line.checked_sub(max_count).map(|v| v + 1).unwrap_or(0);
+            (line, next_word(&self.lines[line], pos))
         }
+    }
 
-        self.left_render_symbol
+    fn visual_rows(&self) -> Vec<Row> {
+        let width = if self.wrap_width == 0 {
+            usize::MAX
+        } else {
+            self.wrap_width
+        };
+        let mut result = Vec::new();
+        for (line, s) in self.lines.iter().enumerate() {
+            let starts = text_layout::wrap(s, width);
+            for (i, start) in starts.iter().enumerate() {
+                let end = starts.get(i + 1).copied().unwrap_or_else(|| grapheme_count(s));
+                result.push(Row {
+                    line,
+                    start: *start,
+                    end,
+                });
+            }
+        }
+        result
+    }
+
+    fn cursor_row(&self, rows: &[Row]) -> usize {
+        rows.iter()
+            .rposition(|r| r.line == self.cursor.0 && r.start <= self.cursor.1)
+            .unwrap_or_default()
+    }
+
+    // The position on the row above or below with the same column
+    fn vertical_pos(&self, rows_delta: isize) -> Pos {
+        let rows = self.visual_rows();
+        let current = self.cursor_row(&rows);
+        let Some(target) = current.checked_add_signed(rows_delta).filter(|r| *r < rows.len()) else {
+            return if rows_delta < 0 { (0, 0) } else { self.end_of_text() };
+        };
+
+        let row = rows[target];
+        let is_last_row_of_line = rows.get(target + 1).is_none_or(|r| r.line != row.line);
+        let max_pos = if is_last_row_of_line {
+            row.end
+        } else {
+            row.end.saturating_sub(1).max(row.start)
+        };
+        let current_line = text_layout::graphemes(&self.lines[self.cursor.0]);
+        let column = text_layout::column(
+            &current_line[rows[current].start..],
+            self.cursor.1 - rows[current].start,
+        );
+
+        let line = text_layout::graphemes(&self.lines[row.line]);
+        let mut pos = row.start;
+        let mut width = 0;
+        while pos < max_pos && width + text_layout::width(line[pos]) <= column {
+            width += text_layout::width(line[pos]);
+            pos += 1;
+        }
+        (row.line, pos)
+    }
+
+    fn move_cursor(&mut self, pos: Pos, select: bool) {
+        if select {
+            self.selection_anchor.get_or_insert(self.cursor);
+        } else {
+            self.selection_anchor = None;
+        }
+        self.cursor = pos;
+    }
+
+    fn selection(&self) -> Option<(Pos, Pos)> {
+        self.selection_anchor
+            .filter(|a| *a != self.cursor)
+            .map(|a| (a.min(self.cursor), a.max(self.cursor)))
+    }
+
+    fn text_between(&self, from: Pos, to: Pos) -> String {
+        let (first, last) = (&self.lines[from.0], &self.lines[to.0]);
+        if from.0 == to.0 {
+            return first[byte_index(first, from.1)..byte_index(first, to.1)].to_string();
+        }
+
+        let mut parts = vec![first[byte_index(first, from.1)..].to_string()];
+        parts.extend(self.lines[from.0 + 1..to.0].iter().cloned());
+        parts.push(last[..byte_index(last, to.1)].to_string());
+        parts.join("\n")
+    }
+
+    fn delete_range(&mut self, from: Pos, to: Pos) {
+        let head = &self.lines[from.0][..byte_index(&self.lines[from.0], from.1)];
+        let tail = &self.lines[to.0][byte_index(&self.lines[to.0], to.1)..];
+        let joined = format!("{head}{tail}");
+        self.lines.splice(from.0..=to.0, [joined]);
+        self.cursor = from;
+        self.selection_anchor = None;
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        match self.selection() {
+            Some((from, to)) => {
+                self.delete_range(from, to);
+                true
+            }
+            None => {
+                self.selection_anchor = None;
+                false
+            }
+        }
+    }
+
+    // Replaces the selection with the text, the text can contain several lines
+    fn insert(&mut self, text: &str) {
+        self.delete_selection();
+
+        let (line, pos) = self.cursor;
+        let s = &self.lines[line];
+        let (head, tail) = s.split_at(byte_index(s, pos));
+        let (head, tail) = (head.to_string(), tail.to_string());
+
+        let mut new_lines = text.split('\n').map(|s| s.to_string()).collect::<Vec<String>>();
+        let last = new_lines.len() - 1;
+        new_lines[0].insert_str(0, &head);
+        // the inserted characters can be combined with the neighbours into one grapheme
+        let cursor = (
+            line + last,
+            text_layout::grapheme_index(&format!("{}{tail}", new_lines[last]), new_lines[last].len()),
+        );
+        new_lines[last].push_str(&tail);
+
+        self.lines.splice(line..=line, new_lines);
+        self.cursor = cursor;
+    }
+
+    fn kill(&mut self, text: String) {
+        self.kill_ring.push(text);
+        if self.kill_ring.len() > KILL_RING_SIZE {
+            self.kill_ring.remove(0);
+        }
+    }
+
+    fn copy(&mut self) {
+        if let Some(text) = self.selected_text() {
+            self.kill(text);
+        }
+    }
+
+    fn cut(&mut self) {
+        self.copy();
+        self.delete_selection();
+    }
+
+    fn kill_to_end_of_line(&mut self) {
+        let to = if self.is_at_end_of_line() {
+            self.next_pos()
+        } else {
+            (self.cursor.0, self.line_size(self.cursor.0))
+        };
+        if to != self.cursor {
+            self.kill(self.text_between(self.cursor, to));
+            self.delete_range(self.cursor, to);
+        }
+    }
+
+    fn yank(&mut self, idx: usize) {
+        let Some(text) = self.kill_ring.get(idx).cloned() else {
+            return;
+        };
+        self.delete_selection();
+        let start = self.cursor;
+        self.insert(&text);
+        self.last_yank = Some((start, idx));
+    }
+
+    fn yank_previous(&mut self, last_yank: Option<(Pos, usize)>) {
+        if let Some((start, idx)) = last_yank {
+            self.delete_range(start, self.cursor);
+            self.yank((idx + self.kill_ring.len() - 1) % self.kill_ring.len());
+        }
+    }
+
+    fn calculate_top_render_row(&mut self, cursor_row: usize, row_count: usize, visible_row_count: usize) -> usize {
+        self.top_render_row = self.top_render_row.min(row_count.saturating_sub(visible_row_count));
+        if self.top_render_row > cursor_row {
+            self.top_render_row = cursor_row;
+        } else if cursor_row >= self.top_render_row + visible_row_count {
+            self.top_render_row = cursor_row + 1 - visible_row_count;
+        }
+
+        self.top_render_row
+    }
+
+    fn render_row(&self, row: &Row, misspelled: &[(usize, usize)]) -> Line<'static> {
+        let (from, to) = self
+            .selection()
+            .map(|(from, to)| row_selection(row, from, to))
+            .unwrap_or((row.end, row.end));
+        let graphemes = text_layout::graphemes(&self.lines[row.line]);
+
+        Line::from(style::styled_spans(&graphemes[row.start..row.end], |i| {
+            let i = row.start + i;
+            if (from..to).contains(&i) {
+                style::selected_row_style()
+            } else if misspelled.iter().any(|(start, end)| (*start..*end).contains(&i)) {
+                style::misspelled_word_style()
+            } else {
+                Style::default()
+            }
+        }))
     }
 }
 
@@ -139,72 +440,35 @@ impl WidgetTrait for TextEdit {
         let b = Block::bordered().border_style(style::border_color());
 
         let inner_area = b.inner(area);
+        self.wrap_width = inner_area.width.max(1) as usize;
 
-        let lines_count = self.lines.len();
-        let possible_line_count = inner_area.height as usize;
-
-        let top_render_line = self.calculate_top_render_line(possible_line_count);
-        let max_symbol_count = inner_area.width as usize;
-        let left_render_symbol = self.calculate_left_render_symbol(max_symbol_count);
-
-        let visible_lines = self
-            .lines
-            .iter()
-            .skip(top_render_line)
-            .take(possible_line_count)
-            .map(|s| s.as_str())
-            .collect::<Vec<&str>>();
+        let rows = self.visual_rows();
+        let cursor_row = self.cursor_row(&rows);
+        let visible_row_count = (inner_area.height as usize).max(1);
+        let top_render_row = self.calculate_top_render_row(cursor_row, rows.len(), visible_row_count);
 
-        let lines = visible_lines
+        let lines = rows
             .iter()
-            .map(|s| {
-                s.chars()
-                    .skip(left_render_symbol)
-                    .take(max_symbol_count)
-                    .collect::<String>()
+            .skip(top_render_row)
+            .take(visible_row_count)
+            .map(|r| {
+                let misspelled = self
+                    .spellchecker
+                    .as_ref()
+                    .map(|sc| sc.misspelled(&self.lines[r.line]))
+                    .unwrap_or_default();
+                self.render_row(r, &misspelled)
             })
-            .collect::<Vec<String>>();
+            .collect::<Vec<Line>>();
 
-        Paragraph::new(lines.join("\n")).block(b).render(area, buf);
+        Paragraph::new(lines).block(b).render(area, buf);
 
-        if lines_count != lines.len() {
+        if rows.len() > visible_row_count {
             let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
                 .begin_symbol(Some("↑"))
                 .end_symbol(Some("↓"));
-            let mut scrollbar_state = ScrollbarState::new(lines_count).position(self.current_line);
-            scrollbar.render(
-                Rect {
-                    x: area.x,
-                    y: area.y,
-                    width: area.width,
-                    height: area.height,
-                },
-                buf,
-                &mut scrollbar_state,
-            );
-        }
-
-        let longest_line_size = visible_lines
-            .iter()
-            .map(|s| s.chars().count())
-            .max()
-            .unwrap_or_default();
-
-        if longest_line_size >= max_symbol_count {
-            let scrollbar = Scrollbar::new(ScrollbarOrientation::HorizontalBottom)
-                .begin_symbol(Some("←"))
-                .end_symbol(Some("→"));
-            let mut scrollbar_state = ScrollbarState::new(longest_line_size).position(self.pos_in_line);
-            scrollbar.render(
-                Rect {
-                    x: area.x + 1,
-                    y: area.y,
-                    width: area.width - 2,
-                    height: area.height,
-                },
-                buf,
-                &mut scrollbar_state,
-            );
+            let mut scrollbar_state = ScrollbarState::new(rows.len()).position(cursor_row);
+            scrollbar.render(area, buf, &mut scrollbar_state);
         }
 
         if let Some(dh) = &self.draw_helper
@@ -212,16 +476,17 @@ impl WidgetTrait for TextEdit {
         {
             let pos = Position::new(
                 std::cmp::min(
-                    inner_area.x + (self.pos_in_line - self.left_render_symbol) as u16,
-                    inner_area.x + inner_area.width - 1,
-                ),
-                std::cmp::min(
-                    inner_area.y + self.current_line.saturating_sub(self.top_render_line) as u16,
-                    inner_area.y + inner_area.height,
+                    inner_area.x
+                        + text_layout::column(
+                            &text_layout::graphemes(&self.lines[self.cursor.0])[rows[cursor_row].start..],
+                            self.cursor.1 - rows[cursor_row].start,
+                        ) as u16,
+                    inner_area.x + inner_area.width.saturating_sub(1),
                 ),
+                inner_area.y + (cursor_row - top_render_row) as u16,
             );
             if pos != self.last_cursor_pos {
-                let cursor_style = if self.pos_in_line == self.end_of_current_line() {
+                let cursor_style = if self.is_at_end_of_line() {
                     CursorStyle::BlinkingBlock
                 } else {
                     CursorStyle::BlinkingBar
@@ -258,111 +523,165 @@ impl WidgetTrait for TextEdit {
 #[async_trait]
 impl KeyboardHandler for TextEdit {
     async fn handle_key(&mut self, key: KeyEvent) -> bool {
-        let is_at_end_of_current_line = self.pos_in_line == self.end_of_current_line();
+        let is_at_end_of_current_line = self.is_at_end_of_line();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let last_yank = self.last_yank.take();
 
         match key.code {
-            KeyCode::Char(ch) => {
-                if self.lines.is_empty() {
-                    self.lines.push(String::new());
-                }
-
-                let end_of_current_line = self.end_of_current_line();
-                let s = self.lines.get_mut(self.current_line).unwrap();
-                if self.pos_in_line == end_of_current_line {
-                    s.push(ch);
-                } else {
-                    s.insert(s.char_indices().nth(self.pos_in_line).unwrap().0, ch);
-                }
-                self.pos_in_line += 1;
+            KeyCode::Char('c') if ctrl => self.copy(),
+            KeyCode::Char('x') if ctrl => self.cut(),
+            KeyCode::Char('k') if ctrl => self.kill_to_end_of_line(),
+            KeyCode::Char('v' | 'y') if ctrl => self.yank(self.kill_ring.len().wrapping_sub(1)),
+            KeyCode::Char('y') if alt => self.yank_previous(last_yank),
+            KeyCode::Char('a') if ctrl => {
+                self.selection_anchor = Some((0, 0));
+                self.cursor = self.end_of_text();
             }
-            KeyCode::Enter => match self.lines.get_mut(self.current_line) {
-                None => {
-                    self.lines.push(String::new());
-                    self.current_line = self.lines.len().sub(1);
-                    self.pos_in_line = 0;
-                }
-                Some(s) => {
-                    let new_str = if self.pos_in_line == s.chars().count() {
-                        String::new()
-                    } else {
-                        let ss = s.chars().skip(self.pos_in_line).collect();
-                        *s = s.chars().take(self.pos_in_line).collect();
-                        ss
-                    };
-                    self.current_line += 1;
-                    self.lines.insert(self.current_line, new_str);
-                    self.pos_in_line = 0;
-                }
-            },
-            KeyCode::Backspace if !self.lines.is_empty() => {
-                if self.pos_in_line == 0 && self.current_line != 0 {
-                    let current_line = self.current_line().unwrap().to_string();
-                    let previous_line_size = self
-                        .lines
-                        .get(self.current_line - 1)
-                        .map(|l| l.chars().count())
-                        .unwrap_or_default();
-                    self.lines
-                        .get_mut(self.current_line - 1)
-                        .unwrap()
-                        .push_str(&current_line);
-                    self.lines.remove(self.current_line);
-                    self.current_line -= 1;
-                    self.pos_in_line = previous_line_size;
-                } else if self.pos_in_line != 0 {
-                    let s = self.lines.get_mut(self.current_line).unwrap();
-                    if s.is_empty() {
-                        self.lines.pop();
-                        self.current_line = self.current_line.saturating_sub(1);
-                        self.pos_in_line = self.end_of_current_line();
-                    } else {
-                        s.remove(s.char_indices().nth(self.pos_in_line - 1).unwrap().0);
-                        self.pos_in_line = self.pos_in_line.saturating_sub(1);
-                    }
+            KeyCode::Char(ch) if !ctrl && !alt => self.insert(ch.to_string().as_str()),
+            KeyCode::Enter => self.insert("\n"),
+            KeyCode::Backspace => {
+                if !self.delete_selection() {
+                    self.delete_range(self.previous_pos(), self.cursor);
                 }
             }
-            KeyCode::Up => {
-                if self.current_line == 0 {
-                    self.pos_in_line = 0;
-                }
-                self.current_line = self.current_line.saturating_sub(1);
-                if self.pos_in_line > self.current_line_size() {
-                    self.pos_in_line = self.end_of_current_line();
-                }
-            }
-            KeyCode::Down => {
-                if self.current_line + 1 != self.lines.len() {
-                    self.current_line += 1;
-                    if self.pos_in_line > self.current_line_size() {
-                        self.pos_in_line = self.end_of_current_line();
-                    }
-                }
-            }
-            KeyCode::Left => {
-                self.pos_in_line = self.pos_in_line.saturating_sub(1);
-            }
-            KeyCode::Right => {
-                if let Some(l) = self.current_line()
-                    && self.pos_in_line != l.chars().count()
-                {
-                    self.pos_in_line += 1;
+            KeyCode::Delete => {
+                if !self.delete_selection() {
+                    self.delete_range(self.cursor, self.next_pos());
                 }
             }
+            KeyCode::Left if ctrl => self.move_cursor(self.previous_word_pos(), shift),
+            KeyCode::Left => self.move_cursor(self.previous_pos(), shift),
+            KeyCode::Right if ctrl => self.move_cursor(self.next_word_pos(), shift),
+            KeyCode::Right => self.move_cursor(self.next_pos(), shift),
+            KeyCode::Up => self.move_cursor(self.vertical_pos(-1), shift),
+            KeyCode::Down => self.move_cursor(self.vertical_pos(1), shift),
+            KeyCode::Home if ctrl => self.move_cursor((0, 0), shift),
+            KeyCode::Home => self.move_cursor((self.cursor.0, 0), shift),
+            KeyCode::End if ctrl => self.move_cursor(self.end_of_text(), shift),
+            KeyCode::End => self.move_cursor((self.cursor.0, self.line_size(self.cursor.0)), shift),
             _ => {
+                self.last_yank = last_yank;
                 return false;
             }
         }
 
-        if is_at_end_of_current_line != (self.pos_in_line == self.end_of_current_line()) {
+        if is_at_end_of_current_line != self.is_at_end_of_line() {
             // we should redraw the cursor style in this case
             self.last_cursor_pos = Position::default();
         }
 
         true
     }
+
+    async fn handle_paste(&mut self, text: &str) -> bool {
+        self.insert(&text.replace("\r\n", "\n").replace('\r', "\n"));
+        self.last_yank = None;
+        self.last_cursor_pos = Position::default();
+        true
+    }
 }
 
 #[async_trait]
 impl MouseHandler for TextEdit {
     async fn handle_mouse(&mut self, _ev: &MouseEvent) {}
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn word_test() {
+        let line = "buy milk, bread";
+        assert_eq!(next_word(line, 0), 4);
+        assert_eq!(next_word(line, 5), 10);
+        assert_eq!(next_word(line, 10), 15);
+        assert_eq!(previous_word(line, 15), 10);
+        assert_eq!(previous_word(line, 10), 4);
+        assert_eq!(previous_word(line, 2), 0);
+    }
+
+    #[test]
+    fn row_selection_test() {
+        let row = Row {
+            line: 1,
+            start: 5,
+            end: 10,
+        };
+        assert_eq!(row_selection(&row, (0, 3), (2, 0)), (5, 10));
+        assert_eq!(row_selection(&row, (1, 7), (1, 20)), (7, 10));
+        assert_eq!(row_selection(&row, (1, 0), (1, 3)), (10, 10));
+        assert_eq!(row_selection(&row, (2, 0), (2, 3)), (10, 10));
+    }
+
+    async fn press(e: &mut TextEdit, code: KeyCode, modifiers: KeyModifiers) {
+        e.handle_key(KeyEvent::new(code, modifiers)).await;
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn selection_and_kill_ring_test() {
+        let mut e = TextEdit::new();
+        e.set_text("buy milk\nand bread");
+
+        press(&mut e, KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::SHIFT).await;
+        assert_eq!(e.selected_text(), Some("bread".to_string()));
+        press(&mut e, KeyCode::Char('x'), KeyModifiers::CONTROL).await;
+        assert_eq!(e.text(), "buy milk\nand ");
+
+        press(&mut e, KeyCode::Home, KeyModifiers::CONTROL).await;
+        press(&mut e, KeyCode::Char('k'), KeyModifiers::CONTROL).await;
+        assert_eq!(e.text(), "\nand ");
+
+        press(&mut e, KeyCode::Char('v'), KeyModifiers::CONTROL).await;
+        assert_eq!(e.text(), "buy milk\nand ");
+        press(&mut e, KeyCode::Char('y'), KeyModifiers::ALT).await;
+        assert_eq!(e.text(), "bread\nand ");
+
+        press(&mut e, KeyCode::Char('a'), KeyModifiers::CONTROL).await;
+        press(&mut e, KeyCode::Char('c'), KeyModifiers::CONTROL).await;
+        press(&mut e, KeyCode::End, KeyModifiers::CONTROL).await;
+        press(&mut e, KeyCode::Char('y'), KeyModifiers::CONTROL).await;
+        assert_eq!(e.text(), "bread\nand bread\nand ");
+
+        press(&mut e, KeyCode::Up, KeyModifiers::SHIFT).await;
+        press(&mut e, KeyCode::Backspace, KeyModifiers::NONE).await;
+        assert_eq!(e.text(), "bread\nand ");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn wrapped_navigation_test() {
+        let mut e = TextEdit::new();
+        e.wrap_width = 10;
+        e.set_text("buy milk and bread\nok");
+        assert_eq!(e.cursor, (1, 2));
+
+        press(&mut e, KeyCode::Up, KeyModifiers::NONE).await;
+        assert_eq!(e.cursor, (0, 11));
+        press(&mut e, KeyCode::Up, KeyModifiers::NONE).await;
+        assert_eq!(e.cursor, (0, 2));
+        press(&mut e, KeyCode::End, KeyModifiers::NONE).await;
+        assert_eq!(e.cursor, (0, 18));
+        press(&mut e, KeyCode::Delete, KeyModifiers::NONE).await;
+        assert_eq!(e.text(), "buy milk and breadok");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn paste_test() {
+        let mut e = TextEdit::new();
+        e.set_text("buy\nbread");
+        press(&mut e, KeyCode::Up, KeyModifiers::NONE).await;
+
+        assert!(e.handle_paste(" milk\r\n  - 2 l\r\n").await);
+        assert_eq!(e.text(), "buy milk\n  - 2 l\n\nbread");
+        assert_eq!(e.cursor, (2, 0));
+
+        press(&mut e, KeyCode::Char('a'), KeyModifiers::CONTROL).await;
+        e.handle_paste("eggs").await;
+        assert_eq!(e.text(), "eggs");
+    }
+}
@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MIT
+
+// The waiting-for tasks are delegated to somebody else, they're marked locally or by the tag in the provider.
+// The time they have started waiting is kept to show the longest waiting ones first and to nag about them.
+
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use tatuin_core::task::DateTimeUtc;
+
+use crate::starred::TaskRef;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct WaitingTask {
+    #[serde(flatten)]
+    task: TaskRef,
+    since: DateTimeUtc,
+    // the tagged tasks stop waiting when the tag is removed, the marked ones when they're unmarked
+    #[serde(default)]
+    tagged: bool,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct WaitingFile {
+    #[serde(default)]
+    tasks: Vec<WaitingTask>,
+}
+
+#[derive(Default)]
+pub struct WaitingTasks {
+    // None keeps the waiting tasks only in memory
+    file_name: Option<PathBuf>,
+    tasks: Vec<WaitingTask>,
+}
+
+impl WaitingTasks {
+    pub fn load(file_name: &Path) -> Result<Self, Box<dyn Error>> {
+        let tasks = match std::fs::read_to_string(file_name) {
+            Ok(s) => toml::from_str::<WaitingFile>(&s)?.tasks,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            file_name: Some(file_name.to_path_buf()),
+            tasks,
+        })
+    }
+
+    fn find(&self, r: &TaskRef) -> Option<&WaitingTask> {
+        self.tasks.iter().find(|t| t.task == *r)
+    }
+
+    // The time the task has started waiting, None if it isn't waiting
+    pub fn since(&self, provider: &str, id: &str) -> Option<DateTimeUtc> {
+        self.find(&TaskRef::new(provider, id)).map(|t| t.since)
+    }
+
+    // Returns whether the task is marked as waiting now
+    pub fn toggle(&mut self, provider: &str, id: &str, now: &DateTimeUtc) -> Result<bool, Box<dyn Error>> {
+        let r = TaskRef::new(provider, id);
+        let waiting = match self.tasks.iter().position(|t| t.task == r) {
+            Some(i) => {
+                self.tasks.remove(i);
+                false
+            }
+            None => {
+                self.tasks.push(WaitingTask {
+                    task: r,
+                    since: *now,
+                    tagged: false,
+                });
+                true
+            }
+        };
+        self.save()?;
+        Ok(waiting)
+    }
+
+    // The loaded tasks of the provider are given as (id, has the tag).
+    // The tagged tasks start waiting when they're seen for the first time and stop when the tag is removed.
+    pub fn update_tagged(
+        &mut self,
+        provider: &str,
+        tasks: &[(String, bool)],
+        now: &DateTimeUtc,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut changed = false;
+        for (id, has_tag) in tasks {
+            let r = TaskRef::new(provider, id);
+            match self.tasks.iter().position(|t| t.task == r) {
+                Some(i) if !has_tag && self.tasks[i].tagged => {
+                    self.tasks.remove(i);
+                    changed = true;
+                }
+                None if *has_tag => {
+                    self.tasks.push(WaitingTask {
+                        task: r,
+                        since: *now,
+                        tagged: true,
+                    });
+                    changed = true;
+                }
+                _ => {}
+            }
+        }
+
+        if changed {
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    fn save(&self) -> Result<(), Box<dyn Error>> {
+        if let Some(f) = &self.file_name {
+            let s = toml::to_string(&WaitingFile {
+                tasks: self.tasks.clone(),
+            })?;
+            std::fs::write(f, s)?;
+        }
+        Ok(())
+    }
+}
+
+// Whether one more nag interval of the waiting has passed in the period, the zero interval never nags
+pub fn is_nag_due(since: &DateTimeUtc, checked_at: &DateTimeUtc, now: &DateTimeUtc, interval: Duration) -> bool {
+    let Some(interval) = interval.num_seconds().checked_abs().filter(|i| *i > 0) else {
+        return false;
+    };
+    let intervals = |dt: &DateTimeUtc| dt.signed_duration_since(since).num_seconds().max(0) / interval;
+    intervals(now) > intervals(checked_at)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn waiting_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let file_name = dir.path().join("waiting.toml");
+        let now = Utc.with_ymd_and_hms(2025, 1, 10, 9, 0, 0).unwrap();
+        let later = now + Duration::hours(1);
+
+        let mut w = WaitingTasks::load(&file_name).unwrap();
+        assert!(w.toggle("Todoist", "1", &now).unwrap());
+        w.update_tagged("GitHub", &[("1".to_string(), true), ("2".to_string(), false)], &now)
+            .unwrap();
+        // the tagged task keeps the time it was seen first
+        w.update_tagged("GitHub", &[("1".to_string(), true)], &later).unwrap();
+        // the marked task isn't removed by the missing tag
+        w.update_tagged("Todoist", &[("1".to_string(), false)], &later).unwrap();
+
+        let mut w = WaitingTasks::load(&file_name).unwrap();
+        assert_eq!(w.since("Todoist", "1"), Some(now));
+        assert_eq!(w.since("GitHub", "1"), Some(now));
+        assert_eq!(w.since("GitHub", "2"), None);
+
+        w.update_tagged("GitHub", &[("1".to_string(), false)], &later).unwrap();
+        assert!(!w.toggle("Todoist", "1", &later).unwrap());
+        let w = WaitingTasks::load(&file_name).unwrap();
+        assert_eq!(w.since("GitHub", "1"), None);
+        assert_eq!(w.since("Todoist", "1"), None);
+    }
+
+    #[test]
+    fn is_nag_due_test() {
+        let since = Utc.with_ymd_and_hms(2025, 1, 10, 9, 0, 0).unwrap();
+        let days = |d: i64, h: i64| since + Duration::days(d) + Duration::hours(h);
+
+        struct Case<'a> {
+            name: &'a str,
+            checked_at: DateTimeUtc,
+            now: DateTimeUtc,
+            interval: Duration,
+            expected: bool,
+        }
+        let cases = [
+            Case {
+                name: "the first interval isn't passed",
+                checked_at: days(2, 0),
+                now: days(2, 23),
+                interval: Duration::days(3),
+                expected: false,
+            },
+            Case {
+                name: "the first interval is passed",
+                checked_at: days(2, 23),
+                now: days(3, 0),
+                interval: Duration::days(3),
+                expected: true,
+            },
+            Case {
+                name: "already nagged",
+                checked_at: days(3, 1),
+                now: days(3, 2),
+                interval: Duration::days(3),
+                expected: false,
+            },
+            Case {
+                name: "the next interval",
+                checked_at: days(5, 23),
+                now: days(6, 1),
+                interval: Duration::days(3),
+                expected: true,
+            },
+            Case {
+                name: "zero interval",
+                checked_at: days(2, 0),
+                now: days(9, 0),
+                interval: Duration::zero(),
+                expected: false,
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                is_nag_due(&since, &c.checked_at, &c.now, c.interval),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
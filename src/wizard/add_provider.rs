@@ -4,19 +4,85 @@ use crate::settings;
 use std::collections::HashMap;
 use std::error::Error;
 use std::io::{self, Write};
+#[cfg(any(feature = "obsidian", feature = "org", feature = "markdown-dir"))]
 use std::path;
-use tatuin_providers::{caldav, github_issues, gitlab_todo, ical, obsidian, tatuin, todoist};
+#[cfg(feature = "caldav")]
+use tatuin_providers::caldav;
+#[cfg(feature = "deck")]
+use tatuin_providers::deck;
+#[cfg(feature = "github")]
+use tatuin_providers::github_issues;
+#[cfg(feature = "github")]
+use tatuin_providers::github_projects;
+#[cfg(feature = "gitlab")]
+use tatuin_providers::gitlab_issues;
+#[cfg(feature = "gitlab")]
+use tatuin_providers::gitlab_todo;
+#[cfg(feature = "ical")]
+use tatuin_providers::ical;
+#[cfg(feature = "inbox")]
+use tatuin_providers::inbox;
+#[cfg(feature = "jira")]
+use tatuin_providers::jira;
+#[cfg(feature = "markdown-dir")]
+use tatuin_providers::markdown_dir;
+#[cfg(feature = "mstodo")]
+use tatuin_providers::mstodo;
+#[cfg(feature = "notion")]
+use tatuin_providers::notion;
+#[cfg(feature = "obsidian")]
+use tatuin_providers::obsidian;
+#[cfg(feature = "org")]
+use tatuin_providers::org;
+#[cfg(feature = "taskwarrior")]
+use tatuin_providers::taskwarrior;
+#[cfg(feature = "tatuin-local")]
+use tatuin_providers::tatuin;
+#[cfg(feature = "todoist")]
+use tatuin_providers::todoist;
+#[cfg(feature = "todotxt")]
+use tatuin_providers::todotxt;
 
 pub const AVAILABLE_PROVIDERS: &[&str] = &[
+    #[cfg(feature = "tatuin-local")]
     tatuin::PROVIDER_NAME,
+    #[cfg(feature = "obsidian")]
     obsidian::PROVIDER_NAME,
+    #[cfg(feature = "todoist")]
     todoist::PROVIDER_NAME,
+    #[cfg(feature = "gitlab")]
     gitlab_todo::PROVIDER_NAME,
+    #[cfg(feature = "gitlab")]
+    gitlab_issues::PROVIDER_NAME,
+    #[cfg(feature = "github")]
     github_issues::PROVIDER_NAME,
+    #[cfg(feature = "github")]
+    github_projects::PROVIDER_NAME,
+    #[cfg(feature = "jira")]
+    jira::PROVIDER_NAME,
+    #[cfg(feature = "notion")]
+    notion::PROVIDER_NAME,
+    #[cfg(feature = "mstodo")]
+    mstodo::PROVIDER_NAME,
+    #[cfg(feature = "deck")]
+    deck::PROVIDER_NAME,
+    #[cfg(feature = "ical")]
     ical::PROVIDER_NAME,
+    #[cfg(feature = "caldav")]
     caldav::PROVIDER_NAME,
+    #[cfg(feature = "todotxt")]
+    todotxt::PROVIDER_NAME,
+    #[cfg(feature = "inbox")]
+    inbox::PROVIDER_NAME,
+    #[cfg(feature = "taskwarrior")]
+    taskwarrior::PROVIDER_NAME,
+    #[cfg(feature = "org")]
+    org::PROVIDER_NAME,
+    #[cfg(feature = "markdown-dir")]
+    markdown_dir::PROVIDER_NAME,
 ];
 
+#[cfg(feature = "caldav")]
 pub const CALDAV_AUTH_TYPES: &[caldav::AuthType] = &[caldav::AuthType::Basic, caldav::AuthType::Digest];
 
 pub struct AddProvider {}
@@ -72,16 +138,7 @@ impl AddProvider {
         let provider = AVAILABLE_PROVIDERS[provider_idx.unwrap() as usize];
         println!("Add provider {provider}");
 
-        let mut provider_cfg = match provider {
-            tatuin::PROVIDER_NAME => self.add_tatuin()?,
-            obsidian::PROVIDER_NAME => self.add_obsidian()?,
-            todoist::PROVIDER_NAME => self.add_todoist()?,
-            gitlab_todo::PROVIDER_NAME => self.add_gitlab_todo()?,
-            github_issues::PROVIDER_NAME => self.add_github_issues()?,
-            ical::PROVIDER_NAME => self.add_ical()?,
-            caldav::PROVIDER_NAME => self.add_caldav()?,
-            _ => panic!("Unknown provider {provider}"),
-        };
+        let mut provider_cfg = self.provider_config(provider)?;
         provider_cfg.insert("type".to_string(), provider.to_string());
 
         let provider_name = self.get_provider_name()?;
@@ -90,10 +147,56 @@ impl AddProvider {
         Ok(())
     }
 
+    fn provider_config(&self, provider: &str) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        match provider {
+            #[cfg(feature = "tatuin-local")]
+            tatuin::PROVIDER_NAME => self.add_tatuin(),
+            #[cfg(feature = "obsidian")]
+            obsidian::PROVIDER_NAME => self.add_obsidian(),
+            #[cfg(feature = "todoist")]
+            todoist::PROVIDER_NAME => self.add_todoist(),
+            #[cfg(feature = "gitlab")]
+            gitlab_todo::PROVIDER_NAME => self.add_gitlab_todo(),
+            #[cfg(feature = "gitlab")]
+            gitlab_issues::PROVIDER_NAME => self.add_gitlab_issues(),
+            #[cfg(feature = "github")]
+            github_issues::PROVIDER_NAME => self.add_github_issues(),
+            #[cfg(feature = "github")]
+            github_projects::PROVIDER_NAME => self.add_github_projects(),
+            #[cfg(feature = "jira")]
+            jira::PROVIDER_NAME => self.add_jira(),
+            #[cfg(feature = "notion")]
+            notion::PROVIDER_NAME => self.add_notion(),
+            #[cfg(feature = "mstodo")]
+            mstodo::PROVIDER_NAME => self.add_mstodo(),
+            #[cfg(feature = "deck")]
+            deck::PROVIDER_NAME => self.add_deck(),
+            #[cfg(feature = "ical")]
+            ical::PROVIDER_NAME => self.add_ical(),
+            #[cfg(feature = "caldav")]
+            caldav::PROVIDER_NAME => self.add_caldav(),
+            #[cfg(feature = "todotxt")]
+            todotxt::PROVIDER_NAME => self.add_todotxt(),
+            #[cfg(feature = "inbox")]
+            inbox::PROVIDER_NAME => self.add_inbox(),
+            #[cfg(feature = "taskwarrior")]
+            taskwarrior::PROVIDER_NAME => self.add_taskwarrior(),
+            #[cfg(feature = "org")]
+            org::PROVIDER_NAME => self.add_org(),
+            #[cfg(feature = "markdown-dir")]
+            markdown_dir::PROVIDER_NAME => self.add_markdown_dir(),
+            _ => Err(Box::<dyn std::error::Error>::from(format!(
+                "Unknown provider {provider}"
+            ))),
+        }
+    }
+
+    #[cfg(feature = "tatuin-local")]
     fn add_tatuin(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
         Ok(HashMap::new())
     }
 
+    #[cfg(feature = "obsidian")]
     fn add_obsidian(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
         print!("Please, provide a path to the vault> ");
         let _ = io::stdout().flush();
@@ -112,6 +215,7 @@ impl AddProvider {
         Ok(HashMap::from([("path".to_string(), input_line)]))
     }
 
+    #[cfg(feature = "todoist")]
     fn add_todoist(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
         print!("Please, provide an api key> ");
         let _ = io::stdout().flush();
@@ -124,6 +228,7 @@ impl AddProvider {
         Ok(HashMap::from([("api_key".to_string(), input_line)]))
     }
 
+    #[cfg(feature = "gitlab")]
     fn add_gitlab_todo(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
         print!("Please, provide a base url (aka https://gitlab.com)> ");
         let _ = io::stdout().flush();
@@ -147,8 +252,33 @@ impl AddProvider {
         ]))
     }
 
+    #[cfg(feature = "gitlab")]
+    fn add_gitlab_issues(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        let mut cfg = self.add_gitlab_todo()?;
+
+        print!("Please, provide the comma-separated groups, empty for all of them (aka my-group)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let groups = input_line.trim().to_string();
+
+        print!("Please, provide the comma-separated projects, empty for all of them (aka my-group/my-project)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let projects = input_line.trim().to_string();
+
+        cfg.extend([("groups".to_string(), groups), ("projects".to_string(), projects)]);
+        Ok(cfg)
+    }
+
+    #[cfg(feature = "github")]
     fn add_github_issues(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
-        print!("Please, provide a repository (aka panter-dsd/tatuin)> ");
+        print!("Please, provide the comma-separated repositories (aka panter-dsd/tatuin, rust-lang/*)> ");
         let _ = io::stdout().flush();
 
         let mut input_line = String::new();
@@ -170,6 +300,146 @@ impl AddProvider {
         ]))
     }
 
+    #[cfg(feature = "github")]
+    fn add_github_projects(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide the project owner, a user or an organization (aka panter-dsd)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let owner = input_line.trim().to_string();
+
+        print!("Please, provide the project number (aka 1 for github.com/users/panter-dsd/projects/1)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let number = input_line.trim().to_string();
+        if number.parse::<i64>().is_err() {
+            println!("The project number should be a number");
+            return Err(Box::<dyn std::error::Error>::from("Wrong project number"));
+        }
+
+        print!("Please, provide an api key with the project scope> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let api_key = input_line.trim().to_string();
+
+        Ok(HashMap::from([
+            ("owner".to_string(), owner),
+            ("number".to_string(), number),
+            ("api_key".to_string(), api_key),
+        ]))
+    }
+
+    #[cfg(feature = "jira")]
+    fn add_jira(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a base url (aka https://your-domain.atlassian.net)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let base_url = input_line.trim().to_string();
+
+        print!("Please, provide the email of the account> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let email = input_line.trim().to_string();
+
+        print!("Please, provide an api token> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let api_key = input_line.trim().to_string();
+
+        Ok(HashMap::from([
+            ("base_url".to_string(), base_url),
+            ("email".to_string(), email),
+            ("api_key".to_string(), api_key),
+        ]))
+    }
+
+    #[cfg(feature = "notion")]
+    fn add_notion(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide an integration secret> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let api_key = input_line.trim().to_string();
+
+        print!("Please, provide a database id (the part of the database url before ?v=)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let database_id = input_line.trim().to_string();
+
+        Ok(HashMap::from([
+            ("api_key".to_string(), api_key),
+            ("database_id".to_string(), database_id),
+        ]))
+    }
+
+    #[cfg(feature = "mstodo")]
+    fn add_mstodo(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide the application (client) id of the app registered in Microsoft Entra> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let client_id = input_line.trim().to_string();
+
+        print!(
+            "Please, provide a tenant, enter for default value ({})> ",
+            mstodo::auth::DEFAULT_TENANT
+        );
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let tenant = Some(input_line.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .unwrap_or(mstodo::auth::DEFAULT_TENANT.to_string());
+
+        // the wizard is synchronous, so the sign in is awaited in place
+        let auth = mstodo::auth::Auth::new(&tenant, &client_id);
+        let token = tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(async {
+                let code = auth.device_code().await?;
+                println!("{}", code.message);
+                println!("Waiting for the sign in...");
+                auth.wait_for_token(&code).await
+            })
+        })?;
+        let refresh_token = token
+            .refresh_token
+            .ok_or("Microsoft didn't return a refresh token, check the offline_access permission")?;
+        println!("Signed in");
+
+        Ok(HashMap::from([
+            ("client_id".to_string(), client_id),
+            ("tenant".to_string(), tenant),
+            ("api_key".to_string(), refresh_token),
+        ]))
+    }
+
+    #[cfg(feature = "ical")]
     fn add_ical(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
         print!("Please, provide a url (aka https://domain/file.ics)> ");
         let _ = io::stdout().flush();
@@ -182,6 +452,40 @@ impl AddProvider {
         Ok(HashMap::from([("url".to_string(), url)]))
     }
 
+    #[cfg(feature = "deck")]
+    fn add_deck(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a Nextcloud url (aka https://domain)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let url = input_line.trim().to_string();
+
+        print!("Please, provide a login> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let login = input_line.trim().to_string();
+
+        print!("Please, provide a password (an app password is recommended)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let password = input_line.trim().to_string();
+
+        Ok(HashMap::from([
+            ("url".to_string(), url),
+            ("login".to_string(), login),
+            ("password".to_string(), password),
+        ]))
+    }
+
+    #[cfg(feature = "caldav")]
     fn add_caldav(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
         print!("Please, provide a caldav url (aka https://domain/remote.php/dav/calendars/login/personal/)> ");
         let _ = io::stdout().flush();
@@ -228,6 +532,83 @@ impl AddProvider {
         ]))
     }
 
+    #[cfg(feature = "todotxt")]
+    fn add_todotxt(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a path to the todo.txt file> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let path = input_line.trim().to_string();
+
+        Ok(HashMap::from([("path".to_string(), path)]))
+    }
+
+    #[cfg(feature = "inbox")]
+    fn add_inbox(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a path to the inbox file (it's created on the first task)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let path = input_line.trim().to_string();
+
+        Ok(HashMap::from([("path".to_string(), path)]))
+    }
+
+    #[cfg(feature = "taskwarrior")]
+    fn add_taskwarrior(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a path to the Taskwarrior data (enter for the one from taskrc)> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let path = input_line.trim().to_string();
+
+        if path.is_empty() {
+            return Ok(HashMap::new());
+        }
+        Ok(HashMap::from([("data_location".to_string(), path)]))
+    }
+
+    #[cfg(feature = "org")]
+    fn add_org(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a path to the org directory or file> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        let path = input_line.trim().to_string();
+
+        if !path::Path::new(&path).exists() {
+            println!("The path doesn't exist");
+            return Err(Box::<dyn std::error::Error>::from("Wrong org path"));
+        }
+        Ok(HashMap::from([("path".to_string(), path)]))
+    }
+
+    #[cfg(feature = "markdown-dir")]
+    fn add_markdown_dir(&self) -> Result<HashMap<String, String>, Box<dyn std::error::Error>> {
+        print!("Please, provide a path to the notes directory> ");
+        let _ = io::stdout().flush();
+
+        let mut input_line = String::new();
+
+        io::stdin().read_line(&mut input_line).expect("Failed to read line");
+        input_line = input_line.trim().to_string();
+
+        if !path::Path::new(&input_line).is_dir() {
+            println!("The path is not a directory");
+            return Err(Box::<dyn std::error::Error>::from("Wrong notes path"));
+        }
+
+        Ok(HashMap::from([("path".to_string(), input_line)]))
+    }
+
     fn get_provider_name(&self) -> Result<String, Box<dyn std::error::Error>> {
         print!("Please, provide the new provider's unique name> ");
         let _ = io::stdout().flush();
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: MIT
+
+// The aggregation of the tasks is run on every reload and every filter change, run it with `cargo bench`
+
+use std::any::Any;
+
+use chrono::{Duration, Utc};
+use criterion::{Criterion, criterion_group, criterion_main};
+use tatuin_core::{
+    RichString,
+    filter::Filter,
+    project::Project as ProjectTrait,
+    task::{self, DateTimeUtc, Priority, State, Task as TaskTrait},
+    task_store::{Selection, SortOrder, merge_provider_tasks},
+};
+
+const TASKS_COUNT: usize = 10_000;
+const PROVIDERS: [&str; 4] = ["Todoist", "Obsidian", "GitHub", "Local"];
+
+#[derive(Debug, Clone)]
+struct BenchTask {
+    id: String,
+    name: String,
+    description: String,
+    provider: String,
+    priority: Priority,
+    state: State,
+    due: Option<DateTimeUtc>,
+    labels: Vec<String>,
+    parent_id: Option<String>,
+    order: i64,
+}
+
+impl TaskTrait for BenchTask {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+    fn name(&self) -> RichString {
+        RichString::new(&self.name)
+    }
+    fn description(&self) -> Option<RichString> {
+        Some(RichString::new(&self.description))
+    }
+    fn priority(&self) -> Priority {
+        self.priority
+    }
+    fn state(&self) -> State {
+        self.state
+    }
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+    fn labels(&self) -> Vec<String> {
+        self.labels.clone()
+    }
+    fn parent_id(&self) -> Option<String> {
+        self.parent_id.clone()
+    }
+    fn order(&self) -> Option<i64> {
+        Some(self.order)
+    }
+    fn provider(&self) -> String {
+        self.provider.clone()
+    }
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        None
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+}
+
+fn tasks(provider: &str, count: usize) -> Vec<Box<dyn TaskTrait>> {
+    let now = Utc::now();
+    let priorities = Priority::values();
+    (0..count)
+        .map(|i| {
+            Box::new(BenchTask {
+                id: format!("{provider}-{i}"),
+                name: format!("Task {i} with [the link](https://example.com/{i}) and more text"),
+                description: format!("The description of the task {i}\nhttps://example.com/description/{i}"),
+                provider: provider.to_string(),
+                priority: priorities[i % priorities.len()],
+                state: if i % 5 == 0 {
+                    State::Completed
+                } else {
+                    State::Uncompleted
+                },
+                due: (i % 3 != 0).then(|| now + Duration::days(i as i64 % 30 - 10)),
+                labels: vec![format!("tag{}", i % 20)],
+                parent_id: (i % 4 == 1).then(|| format!("{provider}-{}", i - 1)),
+                order: (count - i) as i64,
+            }) as Box<dyn TaskTrait>
+        })
+        .collect()
+}
+
+fn aggregation(c: &mut Criterion) {
+    let per_provider = TASKS_COUNT / PROVIDERS.len();
+    let loaded = PROVIDERS.iter().map(|p| tasks(p, per_provider)).collect::<Vec<_>>();
+
+    for order in [SortOrder::Priority, SortOrder::Native] {
+        c.bench_function(&format!("merge {TASKS_COUNT} tasks, {order:?} order"), |b| {
+            b.iter(|| {
+                let mut all = Vec::new();
+                for (p, t) in PROVIDERS.iter().zip(&loaded) {
+                    merge_provider_tasks(&mut all, p, t.iter().map(|t| t.clone_boxed()).collect(), order);
+                }
+                all
+            })
+        });
+    }
+
+    let mut all = Vec::new();
+    for (p, t) in PROVIDERS.iter().zip(&loaded) {
+        merge_provider_tasks(
+            &mut all,
+            p,
+            t.iter().map(|t| t.clone_boxed()).collect(),
+            SortOrder::Priority,
+        );
+    }
+
+    c.bench_function(&format!("reload one provider of {TASKS_COUNT} tasks"), |b| {
+        b.iter(|| {
+            let mut all = all.clone();
+            merge_provider_tasks(
+                &mut all,
+                PROVIDERS[0],
+                tasks(PROVIDERS[0], per_provider),
+                SortOrder::Priority,
+            );
+            all
+        })
+    });
+
+    let filter = Filter::full_filter();
+    let selection = Selection {
+        providers: vec![PROVIDERS[0].to_string(), PROVIDERS[2].to_string()],
+        tags: vec!["tag1".to_string(), "tag2".to_string()],
+        ..Selection::default()
+    };
+    c.bench_function(&format!("filter {TASKS_COUNT} tasks"), |b| {
+        b.iter(|| {
+            all.iter()
+                .map(|t| t.as_ref())
+                .filter(|t| filter.accept(*t) && selection.matches(*t))
+                .count()
+        })
+    });
+
+    let refs = all.iter().map(|t| t.as_ref()).collect::<Vec<&dyn TaskTrait>>();
+    c.bench_function(&format!("hierarchy of {TASKS_COUNT} tasks"), |b| {
+        b.iter(|| task::hierarchy(&refs))
+    });
+
+    c.bench_function(&format!("links of {TASKS_COUNT} tasks"), |b| {
+        b.iter(|| all.iter().map(|t| task::links(t.as_ref()).len()).sum::<usize>())
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // every iteration handles all the tasks, so the default sample is too long
+    config = Criterion::default().sample_size(10);
+    targets = aggregation
+}
+criterion_main!(benches);
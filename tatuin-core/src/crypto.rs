@@ -0,0 +1,191 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    sync::{Arc, Mutex},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ring::{
+    aead::{Aad, CHACHA20_POLY1305, LessSafeKey, NONCE_LEN, Nonce, UnboundKey},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+
+use crate::StringError;
+
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const FILE_MAGIC: &[u8] = b"TATUIN-ENC-V1\n";
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const PBKDF2_ITERATIONS: u32 = 100_000;
+
+type Salt = [u8; SALT_LEN];
+type Key = [u8; KEY_LEN];
+
+fn wrong_passphrase() -> StringError {
+    StringError::new("Can't decrypt the data: wrong passphrase or the data is corrupted")
+}
+
+// Passphrase based encryption (PBKDF2-HMAC-SHA256 + ChaCha20-Poly1305).
+// The encrypted data layout is salt | nonce | ciphertext | tag.
+#[derive(Clone)]
+pub struct Cipher {
+    passphrase: Arc<String>,
+    salt: Salt,
+    keys: Arc<Mutex<HashMap<Salt, Key>>>,
+}
+
+impl Cipher {
+    pub fn new(passphrase: &str) -> Result<Self, StringError> {
+        if passphrase.is_empty() {
+            return Err(StringError::new("The passphrase can't be empty"));
+        }
+
+        let mut salt = Salt::default();
+        SystemRandom::new()
+            .fill(&mut salt)
+            .map_err(|_| StringError::new("Can't generate the salt"))?;
+
+        Ok(Self {
+            passphrase: Arc::new(passphrase.to_string()),
+            salt,
+            keys: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    // The key derivation is slow, so the keys are cached by salt
+    fn key(&self, salt: &Salt) -> LessSafeKey {
+        let mut keys = self.keys.lock().unwrap();
+        let key = keys.entry(*salt).or_insert_with(|| {
+            let mut key = Key::default();
+            pbkdf2::derive(
+                pbkdf2::PBKDF2_HMAC_SHA256,
+                NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+                salt,
+                self.passphrase.as_bytes(),
+                &mut key,
+            );
+            key
+        });
+        LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).unwrap())
+    }
+
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>, StringError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| StringError::new("Can't generate the nonce"))?;
+
+        let mut in_out = data.to_vec();
+        self.key(&self.salt)
+            .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut in_out)
+            .map_err(|_| StringError::new("Can't encrypt the data"))?;
+
+        let mut result = Vec::with_capacity(SALT_LEN + NONCE_LEN + in_out.len());
+        result.extend_from_slice(&self.salt);
+        result.extend_from_slice(&nonce);
+        result.append(&mut in_out);
+        Ok(result)
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, StringError> {
+        if data.len() < SALT_LEN + NONCE_LEN {
+            return Err(wrong_passphrase());
+        }
+
+        let (salt, data) = data.split_at(SALT_LEN);
+        let (nonce, data) = data.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| wrong_passphrase())?;
+
+        let mut in_out = data.to_vec();
+        let len = self
+            .key(salt.try_into().unwrap())
+            .open_in_place(nonce, Aad::empty(), &mut in_out)
+            .map_err(|_| wrong_passphrase())?
+            .len();
+        in_out.truncate(len);
+        Ok(in_out)
+    }
+
+    pub fn encrypt_str(&self, s: &str) -> Result<String, StringError> {
+        Ok(format!(
+            "{ENCRYPTED_PREFIX}{}",
+            STANDARD.encode(self.encrypt(s.as_bytes())?)
+        ))
+    }
+
+    pub fn decrypt_str(&self, s: &str) -> Result<String, StringError> {
+        let data = s
+            .strip_prefix(ENCRYPTED_PREFIX)
+            .and_then(|s| STANDARD.decode(s).ok())
+            .ok_or_else(wrong_passphrase)?;
+        String::from_utf8(self.decrypt(&data)?).map_err(|_| wrong_passphrase())
+    }
+}
+
+pub fn is_encrypted_str(s: &str) -> bool {
+    s.starts_with(ENCRYPTED_PREFIX)
+}
+
+// Prepares the content to be stored on disk: it's encrypted only if the cipher is set
+pub fn encrypt_content(cipher: Option<&Cipher>, data: Vec<u8>) -> Result<Vec<u8>, StringError> {
+    match cipher {
+        Some(c) => Ok([FILE_MAGIC, c.encrypt(&data)?.as_slice()].concat()),
+        None => Ok(data),
+    }
+}
+
+// Restores the content stored on disk. The plain content is returned as is,
+// so the caches created before enabling the encryption are still readable.
+pub fn decrypt_content(cipher: Option<&Cipher>, data: Vec<u8>) -> Result<Vec<u8>, StringError> {
+    match data.strip_prefix(FILE_MAGIC) {
+        Some(encrypted) => match cipher {
+            Some(c) => c.decrypt(encrypted),
+            None => Err(StringError::new(
+                "The data is encrypted, but the encryption isn't configured",
+            )),
+        },
+        None => Ok(data),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn encrypt_decrypt_str_test() {
+        let c = Cipher::new("secret").unwrap();
+        let encrypted = c.encrypt_str("api key").unwrap();
+        assert!(is_encrypted_str(&encrypted));
+        assert_ne!(encrypted, c.encrypt_str("api key").unwrap());
+
+        // the new cipher has another salt but the same passphrase
+        assert_eq!(
+            Cipher::new("secret").unwrap().decrypt_str(&encrypted).unwrap(),
+            "api key"
+        );
+        assert!(Cipher::new("wrong").unwrap().decrypt_str(&encrypted).is_err());
+        assert!(c.decrypt_str("api key").is_err());
+        assert!(Cipher::new("").is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn encrypt_decrypt_content_test() {
+        let c = Cipher::new("secret").unwrap();
+        let data = b"BEGIN:VCALENDAR".to_vec();
+
+        let encrypted = encrypt_content(Some(&c), data.clone()).unwrap();
+        assert_ne!(encrypted, data);
+        assert_eq!(decrypt_content(Some(&c), encrypted.clone()).unwrap(), data);
+        assert!(decrypt_content(None, encrypted).is_err());
+
+        assert_eq!(encrypt_content(None, data.clone()).unwrap(), data);
+        assert_eq!(decrypt_content(Some(&c), data.clone()).unwrap(), data);
+    }
+}
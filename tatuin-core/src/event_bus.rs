@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::mpsc;
+
+use crate::filter::Filter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppEvent {
+    // The tasks should be (re)loaded with the filter
+    FilterChanged(Filter),
+    TasksLoaded {
+        provider: String,
+        count: usize,
+    },
+    PatchCommitted {
+        provider: String,
+        committed: usize,
+        failed: usize,
+    },
+    ProviderError {
+        provider: String,
+        message: String,
+    },
+    // The tasks of the provider have been changed outside the app
+    ProviderChanged {
+        provider: String,
+    },
+    // The task should be selected, e.g. by the open subcommand
+    OpenTask {
+        provider: String,
+        task_id: String,
+    },
+    Error(String),
+}
+
+// Delivers every published event to all subscribers.
+// The subscribers that dropped their receivers are removed on the next publishing.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<mpsc::UnboundedSender<AppEvent>>>>,
+}
+
+impl EventBus {
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<AppEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    pub fn publish(&self, e: AppEvent) {
+        self.subscribers.lock().unwrap().retain(|tx| tx.send(e.clone()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn publish_test() {
+        let bus = EventBus::default();
+        let mut first = bus.subscribe();
+        let second = bus.subscribe();
+        drop(second);
+
+        let e = AppEvent::TasksLoaded {
+            provider: "Home".to_string(),
+            count: 2,
+        };
+        bus.publish(e.clone());
+        assert_eq!(first.recv().await, Some(e));
+        assert_eq!(bus.subscribers.lock().unwrap().len(), 1);
+
+        let mut third = bus.subscribe();
+        bus.publish(AppEvent::Error("error".to_string()));
+        assert_eq!(first.recv().await, Some(AppEvent::Error("error".to_string())));
+        assert_eq!(third.recv().await, Some(AppEvent::Error("error".to_string())));
+    }
+}
@@ -1,10 +1,10 @@
 // SPDX-License-Identifier: MIT
 
 use super::task::{State, Task as TaskTrait, due_group};
-use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, PartialEq, Eq, ValueEnum, Debug, Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum FilterState {
     Completed,
     Todo,
@@ -40,7 +40,8 @@ impl From<State> for FilterState {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, ValueEnum, Ord, PartialOrd, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Ord, PartialOrd, Serialize, Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum Due {
     Overdue,
     Today,
@@ -72,7 +73,15 @@ impl Filter {
             return false;
         }
 
-        if !self.due.contains(&due_group(&t.due())) && !self.due.contains(&due_group(&t.scheduled())) {
+        // the tasks completed today are shown with the today's ones, the day start hour is respected
+        let is_completed_today = t.state() == State::Completed
+            && self.due.contains(&Due::Today)
+            && due_group(&t.completed_at()) == Due::Today;
+
+        if !self.due.contains(&due_group(&t.due()))
+            && !self.due.contains(&due_group(&t.scheduled()))
+            && !is_completed_today
+        {
             return false;
         }
 
@@ -0,0 +1,252 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+
+// The file access of the providers, so they can work with the local disk or with the files in memory
+#[async_trait]
+pub trait FileSystem: Send + Sync {
+    async fn read(&self, p: &Path) -> Result<Vec<u8>, Error>;
+    async fn write(&self, p: &Path, content: &[u8]) -> Result<(), Error>;
+    async fn remove(&self, p: &Path) -> Result<(), Error>;
+    async fn exists(&self, p: &Path) -> bool;
+    async fn rename(&self, from: &Path, to: &Path) -> Result<(), Error>;
+    async fn create_dir_all(&self, p: &Path) -> Result<(), Error>;
+    async fn is_dir(&self, p: &Path) -> bool;
+    // The files of the folder and its subfolders
+    async fn files(&self, dir: &Path) -> Result<Vec<PathBuf>, Error>;
+
+    async fn read_to_string(&self, p: &Path) -> Result<String, Error> {
+        String::from_utf8(self.read(p).await?).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+pub type SharedFileSystem = Arc<dyn FileSystem>;
+
+#[derive(Default)]
+pub struct LocalFileSystem {}
+
+impl LocalFileSystem {
+    pub fn shared() -> SharedFileSystem {
+        Arc::new(Self {})
+    }
+}
+
+#[async_trait]
+impl FileSystem for LocalFileSystem {
+    async fn read(&self, p: &Path) -> Result<Vec<u8>, Error> {
+        tokio::fs::read(p).await
+    }
+
+    // The content is synced, so the file isn't lost if the system is crashed right after the writing
+    async fn write(&self, p: &Path, content: &[u8]) -> Result<(), Error> {
+        let mut f = tokio::fs::File::create(p).await?;
+        f.write_all(content).await?;
+        f.sync_all().await
+    }
+
+    async fn remove(&self, p: &Path) -> Result<(), Error> {
+        tokio::fs::remove_file(p).await
+    }
+
+    async fn exists(&self, p: &Path) -> bool {
+        tokio::fs::try_exists(p).await.unwrap_or(false)
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<(), Error> {
+        tokio::fs::rename(from, to).await?;
+        // the directories can't be opened for syncing on some platforms, the rename is still done
+        if let Some(dir) = to.parent()
+            && let Ok(d) = tokio::fs::File::open(dir).await
+        {
+            let _ = d.sync_all().await;
+        }
+        Ok(())
+    }
+
+    async fn create_dir_all(&self, p: &Path) -> Result<(), Error> {
+        tokio::fs::create_dir_all(p).await
+    }
+
+    async fn is_dir(&self, p: &Path) -> bool {
+        tokio::fs::metadata(p).await.is_ok_and(|m| m.is_dir())
+    }
+
+    async fn files(&self, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        let mut result = Vec::new();
+        let mut dirs = vec![dir.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = tokio::fs::read_dir(&dir).await?;
+            while let Some(e) = entries.next_entry().await? {
+                if e.file_type().await?.is_dir() {
+                    dirs.push(e.path());
+                } else {
+                    result.push(e.path());
+                }
+            }
+        }
+        result.sort();
+        Ok(result)
+    }
+}
+
+// Keeps the files in memory. The directories aren't tracked, any path can be written
+// and the folder exists while it has any file.
+#[derive(Default)]
+pub struct MemoryFileSystem {
+    files: Mutex<HashMap<PathBuf, Vec<u8>>>,
+}
+
+impl MemoryFileSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_file(self, p: &Path, content: &str) -> Self {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(p.to_path_buf(), content.as_bytes().to_vec());
+        self
+    }
+
+    pub fn content(&self, p: &Path) -> Option<Vec<u8>> {
+        self.files.lock().unwrap().get(p).cloned()
+    }
+}
+
+fn has_files_under(files: &HashMap<PathBuf, Vec<u8>>, dir: &Path) -> bool {
+    files.keys().any(|f| f != dir && f.starts_with(dir))
+}
+
+fn not_found(p: &Path) -> Error {
+    Error::new(ErrorKind::NotFound, format!("the file {} is not found", p.display()))
+}
+
+#[async_trait]
+impl FileSystem for MemoryFileSystem {
+    async fn read(&self, p: &Path) -> Result<Vec<u8>, Error> {
+        self.content(p).ok_or_else(|| not_found(p))
+    }
+
+    async fn write(&self, p: &Path, content: &[u8]) -> Result<(), Error> {
+        self.files.lock().unwrap().insert(p.to_path_buf(), content.to_vec());
+        Ok(())
+    }
+
+    async fn remove(&self, p: &Path) -> Result<(), Error> {
+        self.files
+            .lock()
+            .unwrap()
+            .remove(p)
+            .map(|_| ())
+            .ok_or_else(|| not_found(p))
+    }
+
+    async fn exists(&self, p: &Path) -> bool {
+        let files = self.files.lock().unwrap();
+        files.contains_key(p) || has_files_under(&files, p)
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<(), Error> {
+        let mut files = self.files.lock().unwrap();
+        let content = files.remove(from).ok_or_else(|| not_found(from))?;
+        files.insert(to.to_path_buf(), content);
+        Ok(())
+    }
+
+    async fn create_dir_all(&self, _p: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+
+    async fn is_dir(&self, p: &Path) -> bool {
+        has_files_under(&self.files.lock().unwrap(), p)
+    }
+
+    async fn files(&self, dir: &Path) -> Result<Vec<PathBuf>, Error> {
+        let mut result = self
+            .files
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|f| *f != dir && f.starts_with(dir))
+            .cloned()
+            .collect::<Vec<_>>();
+        result.sort();
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn memory_file_system_test() {
+        let p = Path::new("/notes/todo.txt");
+        let fs = MemoryFileSystem::new().with_file(p, "task");
+        assert!(fs.exists(p).await);
+        assert_eq!(fs.read_to_string(p).await.unwrap(), "task");
+
+        fs.write(p, b"another task").await.unwrap();
+        assert_eq!(fs.read_to_string(p).await.unwrap(), "another task");
+
+        fs.remove(p).await.unwrap();
+        assert!(!fs.exists(p).await);
+        assert_eq!(fs.read(p).await.unwrap_err().kind(), ErrorKind::NotFound);
+        assert_eq!(fs.remove(p).await.unwrap_err().kind(), ErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn memory_file_system_folders_test() {
+        let fs = MemoryFileSystem::new()
+            .with_file(Path::new("/notes/work/todo.md"), "task")
+            .with_file(Path::new("/notes/daily.md"), "task")
+            .with_file(Path::new("/other.md"), "task");
+        assert!(fs.is_dir(Path::new("/notes/work")).await);
+        assert!(fs.exists(Path::new("/notes")).await);
+        assert!(!fs.is_dir(Path::new("/notes/daily.md")).await);
+        assert_eq!(
+            fs.files(Path::new("/notes")).await.unwrap(),
+            vec![PathBuf::from("/notes/daily.md"), PathBuf::from("/notes/work/todo.md")]
+        );
+
+        fs.rename(Path::new("/notes/daily.md"), Path::new("/notes/today.md"))
+            .await
+            .unwrap();
+        assert!(!fs.exists(Path::new("/notes/daily.md")).await);
+        assert_eq!(fs.read_to_string(Path::new("/notes/today.md")).await.unwrap(), "task");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn local_file_system_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let p = temp_dir.path().join("todo.txt");
+        let fs = LocalFileSystem::shared();
+        assert!(!fs.exists(&p).await);
+
+        fs.write(&p, b"task").await.unwrap();
+        assert!(fs.exists(&p).await);
+        assert_eq!(fs.read_to_string(&p).await.unwrap(), "task");
+
+        let dir = temp_dir.path().join("notes").join("work");
+        fs.create_dir_all(&dir).await.unwrap();
+        assert!(fs.is_dir(&dir).await);
+        fs.rename(&p, &dir.join("todo.txt")).await.unwrap();
+        assert!(!fs.exists(&p).await);
+        assert_eq!(fs.files(temp_dir.path()).await.unwrap(), vec![dir.join("todo.txt")]);
+
+        fs.remove(&dir.join("todo.txt")).await.unwrap();
+        assert!(fs.files(temp_dir.path()).await.unwrap().is_empty());
+    }
+}
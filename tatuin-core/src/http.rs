@@ -0,0 +1,334 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use base64::{Engine, prelude::BASE64_STANDARD};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{StringError, provider::ProviderError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+    // the listing of the WebDAV collection
+    Propfind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: &str) -> Self {
+        Self {
+            method,
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn get(url: &str) -> Self {
+        Self::new(Method::Get, url)
+    }
+
+    pub fn post(url: &str) -> Self {
+        Self::new(Method::Post, url)
+    }
+
+    pub fn put(url: &str) -> Self {
+        Self::new(Method::Put, url)
+    }
+
+    pub fn patch(url: &str) -> Self {
+        Self::new(Method::Patch, url)
+    }
+
+    pub fn delete(url: &str) -> Self {
+        Self::new(Method::Delete, url)
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: &[u8]) -> Self {
+        self.body = Some(body.to_vec());
+        self
+    }
+
+    pub fn with_bearer_auth(self, token: &str) -> Self {
+        self.with_header("Authorization", format!("Bearer {token}").as_str())
+    }
+
+    pub fn with_basic_auth(self, user: &str, password: &str) -> Self {
+        let credentials = BASE64_STANDARD.encode(format!("{user}:{password}"));
+        self.with_header("Authorization", format!("Basic {credentials}").as_str())
+    }
+
+    // The parameters are encoded and appended to the query of the url
+    pub fn with_query(mut self, params: &[(&str, &str)]) -> Self {
+        if params.is_empty() {
+            return self;
+        }
+
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .finish();
+        self.url.push(if self.url.contains('?') { '&' } else { '?' });
+        self.url.push_str(&query);
+        self
+    }
+
+    pub fn with_json<T: Serialize + ?Sized>(self, body: &T) -> Result<Self, ProviderError> {
+        let body = serde_json::to_vec(body).map_err(|e| ProviderError::Other(e.to_string()))?;
+        Ok(self.with_header("Content-Type", "application/json").with_body(&body))
+    }
+
+    pub fn with_form(self, params: &[(&str, &str)]) -> Self {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(params)
+            .finish();
+        self.with_header("Content-Type", "application/x-www-form-urlencoded")
+            .with_body(body.as_bytes())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn new(status: u16, body: &[u8]) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.to_vec(),
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    // The header names are case-insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, ProviderError> {
+        serde_json::from_slice(&self.body).map_err(|e| ProviderError::Other(format!("wrong response: {e}")))
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).to_string()
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    // Converts the unsuccessful status into the provider error
+    pub fn error_for_status(self, url: &str) -> Result<Self, ProviderError> {
+        if self.is_success() {
+            return Ok(self);
+        }
+
+        Err(ProviderError::from_http_status(
+            self.status,
+            self.header("retry-after").and_then(parse_retry_after),
+            format!("{} for url {url}", self.status).as_str(),
+        ))
+    }
+}
+
+// The header contains either the number of seconds or the HTTP date
+pub fn parse_retry_after(v: &str) -> Option<Duration> {
+    if let Ok(secs) = v.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    DateTime::parse_from_rfc2822(v.trim())
+        .ok()
+        .map(|dt| (dt.with_timezone(&Utc) - Utc::now()).to_std().unwrap_or_default())
+}
+
+// The tokens come from the config, so the line breaks and the other control characters
+// are reported on the client creation instead of the first request
+pub fn check_header_value(name: &str, value: &str) -> Result<(), StringError> {
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(StringError::new(
+            format!("the value of the header {name} contains the control characters").as_str(),
+        ));
+    }
+    Ok(())
+}
+
+// The HTTP transport of the providers. The errors are the transport ones only,
+// the unsuccessful statuses are returned as responses.
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    async fn send(&self, r: Request) -> Result<Response, ProviderError>;
+
+    // Sends the request and converts the unsuccessful status into the provider error
+    async fn send_checked(&self, r: Request) -> Result<Response, ProviderError> {
+        let url = r.url.clone();
+        self.send(r).await?.error_for_status(&url)
+    }
+}
+
+pub type SharedHttpClient = Arc<dyn HttpClient>;
+
+// Replies with the predefined responses and keeps the sent requests.
+// The requests without a predefined response get 404.
+#[derive(Default)]
+pub struct MockHttpClient {
+    responses: Vec<(Method, String, Response)>,
+    requests: Mutex<Vec<Request>>,
+}
+
+impl MockHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_response(mut self, method: Method, url: &str, r: Response) -> Self {
+        self.responses.push((method, url.to_string(), r));
+        self
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl HttpClient for MockHttpClient {
+    async fn send(&self, r: Request) -> Result<Response, ProviderError> {
+        let response = self
+            .responses
+            .iter()
+            .find(|(m, url, _)| *m == r.method && *url == r.url)
+            .map(|(_, _, response)| response.clone())
+            .unwrap_or_else(|| Response::new(404, b""));
+        self.requests.lock().unwrap().push(r);
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_retry_after_test() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::default())
+        );
+        let future = (Utc::now() + chrono::Duration::hours(1)).to_rfc2822();
+        assert!(parse_retry_after(&future).is_some_and(|d| d > Duration::from_secs(3500)));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn error_for_status_test() {
+        assert!(Response::new(204, b"").error_for_status("url").is_ok());
+        assert_eq!(
+            Response::new(404, b"").error_for_status("url"),
+            Err(ProviderError::NotFound("404 for url url".to_string()))
+        );
+        assert_eq!(
+            Response::new(429, b"")
+                .with_header("Retry-After", "30")
+                .error_for_status("url"),
+            Err(ProviderError::RateLimited {
+                retry_after: Some(Duration::from_secs(30))
+            })
+        );
+    }
+
+    #[test]
+    fn request_builder_test() {
+        let r = Request::get("https://example.com/tasks?limit=10").with_query(&[("filter", "a b&c")]);
+        assert_eq!(r.url, "https://example.com/tasks?limit=10&filter=a+b%26c");
+        assert_eq!(
+            Request::get("https://example.com").with_query(&[]).url,
+            "https://example.com"
+        );
+
+        let r = Request::post("https://example.com")
+            .with_basic_auth("user", "pass")
+            .with_json(&serde_json::json!({"name": "task"}))
+            .unwrap();
+        assert_eq!(
+            r.headers,
+            vec![
+                ("Authorization".to_string(), "Basic dXNlcjpwYXNz".to_string()),
+                ("Content-Type".to_string(), "application/json".to_string()),
+            ]
+        );
+        assert_eq!(r.body, Some(br#"{"name":"task"}"#.to_vec()));
+
+        let r = Request::post("https://example.com").with_form(&[("code", "a=b")]);
+        assert_eq!(r.body, Some(b"code=a%3Db".to_vec()));
+    }
+
+    #[test]
+    fn check_header_value_test() {
+        assert!(check_header_value("Authorization", "Bearer token").is_ok());
+        assert!(check_header_value("Authorization", "Bearer token\n").is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn send_checked_test() {
+        let c = MockHttpClient::new().with_response(Method::Get, "https://example.com", Response::new(200, b"{}"));
+        let r = c.send_checked(Request::get("https://example.com")).await.unwrap();
+        assert_eq!(r.json::<serde_json::Value>().unwrap(), serde_json::json!({}));
+        assert_eq!(
+            c.send_checked(Request::delete("https://example.com")).await,
+            Err(ProviderError::NotFound("404 for url https://example.com".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn mock_http_client_test() {
+        let c = MockHttpClient::new().with_response(Method::Get, "https://example.com", Response::new(200, b"ok"));
+        assert_eq!(
+            c.send(Request::get("https://example.com")).await,
+            Ok(Response::new(200, b"ok"))
+        );
+        assert_eq!(
+            c.send(Request::new(Method::Post, "https://example.com"))
+                .await
+                .unwrap()
+                .status,
+            404
+        );
+        assert_eq!(c.requests().len(), 2);
+    }
+}
@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT
+
+// The identical requests are coalesced: while one is running, the new ones aren't sent,
+// but the running one is repeated once after it's finished, so its result isn't older than the last request.
+// The different requests to one provider are limited, so the refresh and the dialogs don't flood it.
+
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::sync::Semaphore;
+
+// The number of the requests sent to one provider at the same time if the settings don't set it
+pub const DEFAULT_MAX_REQUESTS: usize = 4;
+
+#[derive(Debug, Default)]
+pub struct InFlight {
+    // the key of the running request and whether it should be repeated
+    running: HashMap<String, bool>,
+}
+
+impl InFlight {
+    // Returns true if the request should be sent
+    pub fn start(&mut self, key: &str) -> bool {
+        match self.running.get_mut(key) {
+            Some(repeat) => {
+                *repeat = true;
+                false
+            }
+            None => {
+                self.running.insert(key.to_string(), false);
+                true
+            }
+        }
+    }
+
+    // Returns true if the request should be repeated, it stays running in this case
+    pub fn finish(&mut self, key: &str) -> bool {
+        match self.running.get_mut(key) {
+            Some(repeat) if *repeat => {
+                *repeat = false;
+                true
+            }
+            _ => {
+                self.running.remove(key);
+                false
+            }
+        }
+    }
+
+    // The request is dropped without repeating
+    pub fn cancel(&mut self, key: &str) {
+        self.running.remove(key);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestLimit {
+    semaphore: Arc<Semaphore>,
+}
+
+impl RequestLimit {
+    // At least one request is allowed
+    pub fn new(max_requests: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_requests.max(1))),
+        }
+    }
+
+    // The request waits until one of the running ones is finished if the limit is reached
+    pub async fn run<F: Future>(&self, request: F) -> F::Output {
+        // the semaphore is never closed
+        let _permit = self.semaphore.acquire().await;
+        request.await
+    }
+}
+
+impl Default for RequestLimit {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_REQUESTS)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn in_flight_test() {
+        let mut f = InFlight::default();
+        assert!(f.start("tasks"));
+        assert!(f.start("projects"));
+        assert!(!f.start("tasks"));
+        assert!(!f.start("tasks"));
+
+        // the two requests are coalesced into one repeat
+        assert!(f.finish("tasks"));
+        assert!(!f.finish("tasks"));
+        assert!(f.start("tasks"));
+
+        assert!(!f.finish("projects"));
+        f.cancel("tasks");
+        assert!(f.start("tasks"));
+    }
+
+    #[tokio::test]
+    async fn request_limit_test() {
+        struct Case<'a> {
+            name: &'a str,
+            max_requests: usize,
+            expected: usize,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "limited",
+                max_requests: 2,
+                expected: 2,
+            },
+            Case {
+                name: "no limit in the settings",
+                max_requests: 0,
+                expected: 1,
+            },
+            Case {
+                name: "more than the requests",
+                max_requests: 10,
+                expected: 5,
+            },
+        ];
+
+        for c in CASES {
+            let limit = RequestLimit::new(c.max_requests);
+            let running = Arc::new(AtomicUsize::new(0));
+            let max_running = Arc::new(AtomicUsize::new(0));
+            let requests = (0..5).map(|_| {
+                let limit = limit.clone();
+                let running = running.clone();
+                let max_running = max_running.clone();
+                tokio::spawn(async move {
+                    limit
+                        .run(async {
+                            let n = running.fetch_add(1, Ordering::SeqCst) + 1;
+                            max_running.fetch_max(n, Ordering::SeqCst);
+                            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                            running.fetch_sub(1, Ordering::SeqCst);
+                        })
+                        .await
+                })
+            });
+            for r in requests.collect::<Vec<_>>() {
+                r.await.unwrap();
+            }
+            assert_eq!(
+                max_running.load(Ordering::SeqCst),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: MIT
+
+// The strings repeated in many tasks (the provider names, the projects and the labels) are kept once
+// and shared between the tasks, so the big aggregated lists don't hold thousands of equal copies
+
+use std::{
+    collections::HashSet,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+pub type IStr = Arc<str>;
+
+static POOL: LazyLock<Mutex<HashSet<IStr>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+pub fn intern(s: &str) -> IStr {
+    let mut pool = POOL.lock().unwrap();
+    match pool.get(s) {
+        Some(i) => i.clone(),
+        None => {
+            let i: IStr = Arc::from(s);
+            pool.insert(i.clone());
+            i
+        }
+    }
+}
+
+pub fn intern_all<S: AsRef<str>>(v: &[S]) -> Vec<IStr> {
+    v.iter().map(|s| intern(s.as_ref())).collect()
+}
+
+pub fn to_strings(v: &[IStr]) -> Vec<String> {
+    v.iter().map(|s| s.to_string()).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn intern_test() {
+        let a = intern("intern_test");
+        let b = intern(&String::from("intern_test"));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &intern("another")));
+        assert_eq!(&*a, "intern_test");
+
+        let all = intern_all(&["x", "y", "x"]);
+        assert!(Arc::ptr_eq(&all[0], &all[2]));
+        assert_eq!(to_strings(&all), vec!["x", "y", "x"]);
+    }
+}
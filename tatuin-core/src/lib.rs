@@ -1,21 +1,33 @@
 // SPDX-License-Identifier: MIT
 
+pub mod crypto;
 mod emoji_transformer;
+pub mod event_bus;
 pub mod filter;
 pub mod folders;
+pub mod fs;
+pub mod http;
+pub mod in_flight;
+pub mod intern;
 pub mod patched_task;
+pub mod placeholder;
 pub mod project;
 pub mod provider;
 mod raw_link_transformer;
+pub mod registry;
 mod rich_string;
 pub mod state;
 mod string_error;
+pub mod tag_alias;
 pub mod task;
 pub mod task_patch;
+pub mod task_store;
 pub mod time;
 pub mod types;
 pub mod utils;
+mod wiki_link_transformer;
 pub use emoji_transformer::EmojiTransformer;
 pub use raw_link_transformer::RawLinkTransformer;
 pub use rich_string::{RichString, Transformer as RichStringTransformerTrait};
 pub use string_error::StringError;
+pub use wiki_link_transformer::WikiLinkTransformer;
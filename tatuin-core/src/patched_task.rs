@@ -1,27 +1,28 @@
 // SPDX-License-Identifier: MIT
 
-use std::any::Any;
+use std::{any::Any, sync::Arc};
 
 use crate::RichString;
 
 use super::project::Project as ProjectTrait;
 use super::{
-    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, Recurrence, State, Task as TaskTrait},
     task_patch::TaskPatch,
 };
 
+#[derive(Clone)]
 pub struct PatchedTask {
-    task: Box<dyn TaskTrait>,
+    task: Arc<dyn TaskTrait>,
     patch: Option<TaskPatch>,
 }
 
 impl PatchedTask {
-    pub fn new(task: Box<dyn TaskTrait>, patch: Option<TaskPatch>) -> Self {
+    pub fn new(task: Arc<dyn TaskTrait>, patch: Option<TaskPatch>) -> Self {
         Self { task, patch }
     }
 
-    pub fn original_task(&self) -> Box<dyn TaskTrait> {
-        self.task.clone_boxed()
+    pub fn original_task(&self) -> Arc<dyn TaskTrait> {
+        self.task.clone()
     }
 }
 
@@ -98,6 +99,38 @@ impl TaskTrait for PatchedTask {
         self.task.url()
     }
 
+    fn parent_id(&self) -> Option<String> {
+        self.task.parent_id()
+    }
+
+    fn reminders(&self) -> Vec<DateTimeUtc> {
+        self.task.reminders()
+    }
+
+    fn order(&self) -> Option<i64> {
+        self.task.order()
+    }
+
+    fn recurrence(&self) -> Option<Recurrence> {
+        self.task.recurrence()
+    }
+
+    fn assignee(&self) -> Option<String> {
+        self.task.assignee()
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        self.task.is_assigned_to_me()
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        self.task.is_created_by_me()
+    }
+
+    fn comment_count(&self) -> Option<u64> {
+        self.task.comment_count()
+    }
+
     fn provider(&self) -> String {
         self.task.provider()
     }
@@ -119,12 +152,3 @@ impl TaskTrait for PatchedTask {
         self.task.const_patch_policy()
     }
 }
-
-impl Clone for PatchedTask {
-    fn clone(&self) -> Self {
-        Self {
-            task: self.task.clone_boxed(),
-            patch: self.patch.clone(),
-        }
-    }
-}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: MIT
+
+// The placeholders in the name and the description of the created task, e.g. "Weekly review {{week}}".
+// They're expanded once when the task is created:
+//   {{date}} or {{date:<chrono format>}} - the current day
+//   {{time}} - the current time
+//   {{week}} - the ISO week, e.g. 2025-W02
+//   {{weekday}} - the name of the current day of the week
+//   {{clipboard}} - the text in the clipboard
+// The unknown placeholders are kept as they are.
+
+use chrono::{
+    Local, TimeZone,
+    format::{Item, StrftimeItems},
+};
+
+use crate::{task::DateTimeUtc, time};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+pub fn expand(text: &str, clipboard: impl Fn() -> Option<String>) -> String {
+    expand_at(text, &chrono::Utc::now(), &Local, time::day_start_hour(), clipboard)
+}
+
+pub fn expand_at<Tz: TimeZone>(
+    text: &str,
+    now: &DateTimeUtc,
+    tz: &Tz,
+    day_start_hour: u32,
+    clipboard: impl Fn() -> Option<String>,
+) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + 2 + len + 2];
+        let key = rest[start + 2..start + 2 + len].trim();
+
+        result.push_str(&rest[..start]);
+        match value(key, now, tz, day_start_hour, &clipboard) {
+            Some(v) => result.push_str(&v),
+            None => result.push_str(placeholder),
+        }
+        rest = &rest[start + placeholder.len()..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn value<Tz: TimeZone>(
+    key: &str,
+    now: &DateTimeUtc,
+    tz: &Tz,
+    day_start_hour: u32,
+    clipboard: &impl Fn() -> Option<String>,
+) -> Option<String> {
+    let day = time::day(now, tz, day_start_hour);
+
+    let (name, format) = match key.split_once(':') {
+        Some((name, format)) => (name.trim(), Some(format)),
+        None => (key, None),
+    };
+
+    match (name, format) {
+        ("date", format) => {
+            let format = format.unwrap_or(DATE_FORMAT);
+            // the wrong format can't be displayed
+            if StrftimeItems::new(format).any(|i| matches!(i, Item::Error)) {
+                return None;
+            }
+            Some(day.format(format).to_string())
+        }
+        ("time", None) => Some(now.with_timezone(tz).naive_local().format("%H:%M").to_string()),
+        ("week", None) => Some(day.format("%G-W%V").to_string()),
+        ("weekday", None) => Some(day.format("%A").to_string()),
+        ("clipboard", None) => Some(
+            clipboard()
+                .unwrap_or_default()
+                .trim_end_matches(['\n', '\r'])
+                .to_string(),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    #[test]
+    fn expand_test() {
+        struct Case<'a> {
+            name: &'a str,
+            text: &'a str,
+            now: &'a str,
+            day_start_hour: u32,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no placeholders",
+                text: "Buy milk",
+                now: "2025-01-08T10:30:00Z",
+                day_start_hour: 0,
+                expected: "Buy milk",
+            },
+            Case {
+                name: "date",
+                text: "Report {{date}}",
+                now: "2025-01-08T10:30:00Z",
+                day_start_hour: 0,
+                expected: "Report 2025-01-08",
+            },
+            Case {
+                name: "date with format",
+                text: "Report {{ date:%d.%m }}",
+                now: "2025-01-08T10:30:00Z",
+                day_start_hour: 0,
+                expected: "Report 08.01",
+            },
+            Case {
+                name: "date before the day start",
+                text: "Report {{date}}",
+                now: "2025-01-08T02:30:00Z",
+                day_start_hour: 4,
+                expected: "Report 2025-01-07",
+            },
+            Case {
+                name: "time, week and weekday",
+                text: "{{weekday}} {{time}} {{week}}",
+                now: "2025-01-01T10:30:00Z",
+                day_start_hour: 0,
+                expected: "Wednesday 10:30 2025-W01",
+            },
+            Case {
+                name: "week of the previous year",
+                text: "Review {{week}}",
+                now: "2021-01-01T10:30:00Z",
+                day_start_hour: 0,
+                expected: "Review 2020-W53",
+            },
+            Case {
+                name: "clipboard",
+                text: "Read {{clipboard}}",
+                now: "2025-01-08T10:30:00Z",
+                day_start_hour: 0,
+                expected: "Read https://example.com",
+            },
+            Case {
+                name: "unknown and wrong placeholders are kept",
+                text: "{{unknown}} {{date:%Q}} {{time:%H}} {{date",
+                now: "2025-01-08T10:30:00Z",
+                day_start_hour: 0,
+                expected: "{{unknown}} {{date:%Q}} {{time:%H}} {{date",
+            },
+        ];
+
+        for c in CASES {
+            let now = c.now.parse::<DateTime<Utc>>().unwrap();
+            let result = expand_at(c.text, &now, &Utc, c.day_start_hour, || {
+                Some("https://example.com\n".to_string())
+            });
+            assert_eq!(result, c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn clipboard_is_read_only_when_needed_test() {
+        let now = "2025-01-08T10:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let result = expand_at("{{date}}", &now, &Utc, 0, || panic!("The clipboard is read"));
+        assert_eq!(result, "2025-01-08");
+        assert_eq!(expand_at("{{clipboard}}", &now, &Utc, 0, || None), "");
+    }
+}
@@ -3,15 +3,159 @@
 use super::{
     StringError, filter,
     project::Project as ProjectTrait,
-    task::{Priority, Task as TaskTrait},
+    task::{DateTimeUtc, Priority, Task as TaskTrait},
     task_patch::{PatchError, TaskPatch},
 };
 use async_trait::async_trait;
-use std::fmt::Debug;
+use std::{error::Error, fmt::Debug, path::PathBuf, time::Duration};
+use tokio::sync::broadcast;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProviderError {
+    Auth(String),
+    RateLimited { retry_after: Option<Duration> },
+    Network(String),
+    NotFound(String),
+    Conflict(String),
+    Other(String),
+}
+
+impl ProviderError {
+    pub fn other(message: &str) -> Self {
+        Self::Other(message.to_string())
+    }
+
+    pub fn wrong_task_type(task: &dyn TaskTrait, provider_type: &str) -> Self {
+        Self::Other(format!(
+            "the task id=`{}` name=`{}` isn't a task of the {provider_type} provider",
+            task.id(),
+            task.name().raw()
+        ))
+    }
+
+    pub fn from_http_status(status: u16, retry_after: Option<Duration>, message: &str) -> Self {
+        let message = message.to_string();
+        match status {
+            401 | 403 => Self::Auth(message),
+            404 | 410 => Self::NotFound(message),
+            409 | 412 => Self::Conflict(message),
+            429 => Self::RateLimited { retry_after },
+            503 if retry_after.is_some() => Self::RateLimited { retry_after },
+            502..=504 => Self::Network(message),
+            _ => Self::Other(message),
+        }
+    }
+}
+
+impl std::fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Auth(m) => write!(f, "authentication failed: {m}"),
+            Self::RateLimited { retry_after: Some(d) } => {
+                write!(f, "rate limit exceeded, retry after {}s", d.as_secs())
+            }
+            Self::RateLimited { retry_after: None } => write!(f, "rate limit exceeded"),
+            Self::Network(m) => write!(f, "network error: {m}"),
+            Self::NotFound(m) => write!(f, "not found: {m}"),
+            Self::Conflict(m) => write!(f, "conflict: {m}"),
+            Self::Other(m) => write!(f, "{m}"),
+        }
+    }
+}
+
+impl Error for ProviderError {}
+
+impl From<StringError> for ProviderError {
+    fn from(e: StringError) -> Self {
+        Self::Other(e.to_string())
+    }
+}
+
+impl From<ProviderError> for StringError {
+    fn from(e: ProviderError) -> Self {
+        StringError::new(e.to_string().as_str())
+    }
+}
+
+// The clients return boxed errors, so the already classified error is extracted back
+impl From<Box<dyn Error>> for ProviderError {
+    fn from(e: Box<dyn Error>) -> Self {
+        match e.downcast::<ProviderError>() {
+            Ok(e) => *e,
+            Err(e) => match e.downcast::<std::io::Error>() {
+                Ok(e) => (*e).into(),
+                Err(e) => Self::Other(e.to_string()),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for ProviderError {
+    fn from(e: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        let message = e.to_string();
+        match e.kind() {
+            ErrorKind::NotFound => Self::NotFound(message),
+            ErrorKind::PermissionDenied => Self::Auth(message),
+            ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::TimedOut => Self::Network(message),
+            _ => Self::Other(message),
+        }
+    }
+}
+
+// The named query of the provider, e.g. the GitHub search or the Todoist filter, shown as a tab above the tasks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub name: String,
+    pub query: String,
+}
+
+impl Query {
+    // Parses the "name = query; name = query" list from the settings
+    pub fn parse_list(s: &str) -> Result<Vec<Query>, StringError> {
+        s.split(';')
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(|q| match q.split_once('=') {
+                Some((name, query)) if !name.trim().is_empty() && !query.trim().is_empty() => Ok(Query {
+                    name: name.trim().to_string(),
+                    query: query.trim().to_string(),
+                }),
+                _ => Err(StringError::new(
+                    format!("Wrong query '{q}', it should be in format <name> = <query>").as_str(),
+                )),
+            })
+            .collect()
+    }
+}
 
 #[derive(Debug, Copy, Clone)]
 pub struct Capabilities {
     pub create_task: bool,
+    // the labels can be changed by the labels patch
+    pub labels: bool,
+    // the created tasks can have the parent by the parent patch
+    pub subtasks: bool,
+    // the tasks can be reordered by set_order
+    pub manual_order: bool,
+    // the tasks know their assignees and authors
+    pub assignees: bool,
+    // the tasks can be listed by the provider's own queries
+    pub queries: bool,
+    // the created tasks can have the link by the url patch
+    pub url: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SyncState {
+    pub cache_path: Option<PathBuf>,
+    pub cached_objects: usize,
+    pub last_sync: Option<DateTimeUtc>,
 }
 
 #[async_trait]
@@ -20,17 +164,25 @@ pub trait TaskProviderTrait {
         &mut self,
         project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError>;
-    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), StringError>;
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError>;
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError>;
     async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError>;
-    async fn delete(&mut self, _t: &dyn TaskTrait) -> Result<(), StringError> {
+    async fn delete(&mut self, _t: &dyn TaskTrait) -> Result<(), ProviderError> {
         unimplemented!()
     }
+    // Saves the manual order of the tasks, the ids are listed in the new order
+    async fn set_order(&mut self, _ids: &[String]) -> Result<(), ProviderError> {
+        Err(ProviderError::other("The manual order of the tasks isn't supported"))
+    }
+    // Lists the tasks by the query in the provider's own language, the results aren't cached
+    async fn query(&mut self, _query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        Err(ProviderError::other("The queries aren't supported"))
+    }
 }
 
 #[async_trait]
 pub trait ProjectProviderTrait {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError>;
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError>;
 }
 
 #[async_trait]
@@ -42,4 +194,160 @@ pub trait ProviderTrait: TaskProviderTrait + ProjectProviderTrait + Send + Sync
     fn supported_priorities(&self) -> Vec<Priority> {
         Priority::values()
     }
+    fn sync_state(&self) -> SyncState {
+        SyncState::default()
+    }
+    // Removes the cached data, so everything is downloaded again on the next list call
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.reload().await;
+        Ok(())
+    }
+    // Notifies when the tasks have been changed outside the app, e.g. the file edited by a script
+    fn subscribe_on_changes(&self) -> Option<broadcast::Receiver<()>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_queries_test() {
+        struct Case<'a> {
+            name: &'a str,
+            s: &'a str,
+            expected: Option<&'a [(&'a str, &'a str)]>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "empty",
+                s: " ",
+                expected: Some(&[]),
+            },
+            Case {
+                name: "two queries",
+                s: "Review requests = is:pr review-requested:@me; Assigned=assignee:@me;",
+                expected: Some(&[
+                    ("Review requests", "is:pr review-requested:@me"),
+                    ("Assigned", "assignee:@me"),
+                ]),
+            },
+            Case {
+                name: "equal sign in the query",
+                s: "Assigned = action=assigned&type=Issue",
+                expected: Some(&[("Assigned", "action=assigned&type=Issue")]),
+            },
+            Case {
+                name: "without name",
+                s: "assignee:@me",
+                expected: None,
+            },
+            Case {
+                name: "empty query",
+                s: "Assigned = ",
+                expected: None,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                Query::parse_list(c.s).ok(),
+                c.expected.map(|e| e
+                    .iter()
+                    .map(|(name, query)| Query {
+                        name: name.to_string(),
+                        query: query.to_string(),
+                    })
+                    .collect::<Vec<Query>>()),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn from_http_status_test() {
+        struct Case<'a> {
+            name: &'a str,
+            status: u16,
+            retry_after: Option<Duration>,
+            expected: ProviderError,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "unauthorized",
+                status: 401,
+                retry_after: None,
+                expected: ProviderError::Auth(String::new()),
+            },
+            Case {
+                name: "forbidden",
+                status: 403,
+                retry_after: None,
+                expected: ProviderError::Auth(String::new()),
+            },
+            Case {
+                name: "not found",
+                status: 404,
+                retry_after: None,
+                expected: ProviderError::NotFound(String::new()),
+            },
+            Case {
+                name: "precondition failed",
+                status: 412,
+                retry_after: None,
+                expected: ProviderError::Conflict(String::new()),
+            },
+            Case {
+                name: "too many requests",
+                status: 429,
+                retry_after: Some(Duration::from_secs(30)),
+                expected: ProviderError::RateLimited {
+                    retry_after: Some(Duration::from_secs(30)),
+                },
+            },
+            Case {
+                name: "unavailable with retry",
+                status: 503,
+                retry_after: Some(Duration::from_secs(5)),
+                expected: ProviderError::RateLimited {
+                    retry_after: Some(Duration::from_secs(5)),
+                },
+            },
+            Case {
+                name: "unavailable",
+                status: 503,
+                retry_after: None,
+                expected: ProviderError::Network(String::new()),
+            },
+            Case {
+                name: "bad request",
+                status: 400,
+                retry_after: None,
+                expected: ProviderError::Other(String::new()),
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                ProviderError::from_http_status(c.status, c.retry_after, ""),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn from_boxed_error_test() {
+        let e: Box<dyn Error> = Box::new(ProviderError::Conflict("etag".to_string()));
+        assert_eq!(ProviderError::from(e), ProviderError::Conflict("etag".to_string()));
+
+        let e: Box<dyn Error> = Box::new(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert!(matches!(ProviderError::from(e), ProviderError::NotFound(_)));
+
+        let e: Box<dyn Error> = Box::<dyn Error>::from("some error");
+        assert_eq!(ProviderError::from(e), ProviderError::Other("some error".to_string()));
+    }
 }
@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::{StringError, provider::ProviderTrait, types::ArcRwLock};
+
+pub type SharedProvider = ArcRwLock<Box<dyn ProviderTrait>>;
+
+/// The configured providers addressed by their unique names.
+///
+/// The providers are shared, so the registry can be cloned and used from several tasks.
+#[derive(Default, Clone)]
+pub struct ProviderRegistry {
+    providers: Vec<(String, SharedProvider)>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the provider and returns its shared handle. The provider's name must be unique.
+    pub fn add(&mut self, p: Box<dyn ProviderTrait>) -> Result<SharedProvider, StringError> {
+        let name = p.name();
+        let p = Arc::new(RwLock::new(p));
+        self.add_shared(&name, p.clone())?;
+        Ok(p)
+    }
+
+    /// Adds the provider that is shared with the app already, e.g. with its UI. The name must be unique.
+    pub fn add_shared(&mut self, name: &str, p: SharedProvider) -> Result<(), StringError> {
+        if self.get(name).is_some() {
+            return Err(StringError::new(
+                format!("The provider with name {name} already exists").as_str(),
+            ));
+        }
+
+        self.providers.push((name.to_string(), p));
+        self.providers.sort_by(|l, r| l.0.cmp(&r.0));
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<SharedProvider> {
+        self.providers.iter().find(|(n, _)| n == name).map(|(_, p)| p.clone())
+    }
+
+    /// The names of the providers in alphabetical order
+    pub fn names(&self) -> Vec<String> {
+        self.providers.iter().map(|(n, _)| n.clone()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &SharedProvider)> {
+        self.providers.iter().map(|(n, p)| (n.as_str(), p))
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
@@ -1,6 +1,12 @@
 // SPDX-License-Identifier: MIT
 
-use crate::{EmojiTransformer, RawLinkTransformer};
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{EmojiTransformer, RawLinkTransformer, WikiLinkTransformer};
+
+static MARKDOWN_LINK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\[([^\[\]]*)\]\(([^()\s]+)\)").unwrap());
 
 pub trait Transformer: std::fmt::Debug {
     fn transform(&self, s: &str) -> String;
@@ -34,6 +40,54 @@ impl RichString {
         for t in &self.transformers {
             s = t.transform(&s);
         }
-        s
+        // the wiki links that weren't resolved by the transformers are shown as text
+        WikiLinkTransformer {}.transform(&s)
+    }
+
+    // The displayed text with the markdown links replaced by their texts,
+    // for the places that can't show the hyperlinks
+    pub fn plain(&self) -> String {
+        MARKDOWN_LINK_RE.replace_all(&self.display(), "$1").to_string()
+    }
+
+    // The texts and the urls of the displayed links
+    pub fn links(&self) -> Vec<(String, String)> {
+        MARKDOWN_LINK_RE
+            .captures_iter(&self.display())
+            .map(|c| (c[1].to_string(), c[2].to_string()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug)]
+    struct ResolvingTransformer {}
+
+    impl Transformer for ResolvingTransformer {
+        fn transform(&self, s: &str) -> String {
+            s.replace("[[Books|books]]", "[books](obsidian://open?file=Books)")
+        }
+    }
+
+    #[test]
+    fn display_test() {
+        let s = RichString::new("Read [[Books|books]] from [[Library]] https://example.com")
+            .with_transformer(Box::new(ResolvingTransformer {}));
+        assert_eq!(
+            s.display(),
+            "Read [books](obsidian://open?file=Books) from Library [https://example.com](https://example.com)"
+        );
+        assert_eq!(s.plain(), "Read books from Library https://example.com");
+        assert_eq!(s.raw(), "Read [[Books|books]] from [[Library]] https://example.com");
+        assert_eq!(
+            s.links(),
+            vec![
+                ("books".to_string(), "obsidian://open?file=Books".to_string()),
+                ("https://example.com".to_string(), "https://example.com".to_string())
+            ]
+        );
     }
 }
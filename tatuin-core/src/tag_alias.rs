@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use crate::StringError;
+
+/// The equivalent labels of different providers, e.g. `wip = in-progress = doing`.
+/// The first label of the group is the one shown instead of the others, the labels are case-insensitive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagAliases {
+    canonical: HashMap<String, String>,
+}
+
+impl TagAliases {
+    pub fn parse(groups: &[String]) -> Result<Self, StringError> {
+        let mut canonical = HashMap::new();
+        for g in groups {
+            let tags = g.split('=').map(str::trim).collect::<Vec<&str>>();
+            if tags.len() < 2 || tags.iter().any(|t| t.is_empty()) {
+                return Err(StringError::new(
+                    format!("Wrong tag aliases '{g}', it should be in format <tag> = <alias> = <alias>").as_str(),
+                ));
+            }
+
+            for t in &tags {
+                if let Some(c) = canonical.insert(t.to_lowercase(), tags[0].to_string())
+                    && c != tags[0]
+                {
+                    return Err(StringError::new(
+                        format!("The tag '{t}' is an alias of both '{c}' and '{}'", tags[0]).as_str(),
+                    ));
+                }
+            }
+        }
+        Ok(Self { canonical })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
+
+    /// The first label of the group the tag belongs to or the tag itself
+    pub fn canonical(&self, tag: &str) -> String {
+        self.canonical
+            .get(&tag.to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| tag.to_string())
+    }
+
+    /// The labels with the aliases merged into one tag
+    pub fn tags(&self, labels: &[String]) -> Vec<String> {
+        let mut tags = Vec::new();
+        for t in labels.iter().map(|l| self.canonical(l)) {
+            if !tags.contains(&t) {
+                tags.push(t);
+            }
+        }
+        tags
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let aliases = TagAliases::parse(&["wip = in-progress = Doing".to_string(), "bug=defect".to_string()]).unwrap();
+
+        struct Case<'a> {
+            name: &'a str,
+            tag: &'a str,
+            expected: &'a str,
+        }
+        let cases = [
+            Case {
+                name: "canonical",
+                tag: "wip",
+                expected: "wip",
+            },
+            Case {
+                name: "alias",
+                tag: "in-progress",
+                expected: "wip",
+            },
+            Case {
+                name: "another case",
+                tag: "doing",
+                expected: "wip",
+            },
+            Case {
+                name: "another group",
+                tag: "Defect",
+                expected: "bug",
+            },
+            Case {
+                name: "unknown tag",
+                tag: "home",
+                expected: "home",
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(aliases.canonical(c.tag), c.expected, "Test '{}' was failed", c.name);
+        }
+
+        assert_eq!(
+            aliases.tags(&["doing".to_string(), "home".to_string(), "wip".to_string()]),
+            vec!["wip", "home"]
+        );
+        assert!(TagAliases::parse(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn wrong_aliases_test() {
+        for groups in [vec!["wip"], vec!["wip = "], vec!["wip = doing", "doing = in-progress"]] {
+            let groups = groups.iter().map(|g| g.to_string()).collect::<Vec<String>>();
+            assert!(TagAliases::parse(&groups).is_err(), "Test '{groups:?}' was failed");
+        }
+        assert!(TagAliases::parse(&["wip = WIP = doing".to_string()]).is_ok());
+    }
+}
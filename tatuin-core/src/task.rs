@@ -1,15 +1,16 @@
 // SPDX-License-Identifier: MIT
 
-use crate::RichString;
+use crate::{RichString, StringError};
 
 use super::{filter, project::Project as ProjectTrait, task_patch::DatePatchItem};
 use chrono::{DateTime, prelude::*};
-use colored::Colorize;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::{
     any::Any,
-    cmp::Ordering,
     fmt::{self, Write},
+    str::FromStr,
+    sync::LazyLock,
 };
 
 pub type DateTimeUtc = DateTime<Utc>;
@@ -64,7 +65,113 @@ impl fmt::Display for Priority {
     }
 }
 
-#[derive(Default, Debug, Clone)]
+pub const RECURRENCE_EMOJI: char = '🔁';
+
+static RECURRENCE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?i)(?:every\s+(?:(\d+)\s+)?(day|week|month|year)s?|(daily|weekly|monthly|yearly))\b").unwrap()
+});
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecurrenceUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+// The simplified recurrence like "every day" or "every 2 weeks"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Recurrence {
+    pub interval: u32,
+    pub unit: RecurrenceUnit,
+}
+
+impl Recurrence {
+    pub fn new(interval: u32, unit: RecurrenceUnit) -> Self {
+        Self {
+            interval: interval.max(1),
+            unit,
+        }
+    }
+
+    // The recurrence at the start of the text and the length of its text
+    fn parse_prefix(s: &str) -> Option<(Self, usize)> {
+        let caps = RECURRENCE_RE.captures(s)?;
+        let (interval, unit) = match caps.get(3) {
+            Some(adverb) => (1, adverb.as_str().to_lowercase()),
+            None => (
+                caps.get(1).map_or(Some(1), |n| n.as_str().parse::<u32>().ok())?,
+                caps[2].to_lowercase(),
+            ),
+        };
+        let unit = match unit.as_str() {
+            "day" | "daily" => RecurrenceUnit::Day,
+            "week" | "weekly" => RecurrenceUnit::Week,
+            "month" | "monthly" => RecurrenceUnit::Month,
+            _ => RecurrenceUnit::Year,
+        };
+        (interval > 0).then(|| (Self::new(interval, unit), caps[0].len()))
+    }
+
+    // Splits the text like "Pay rent 🔁 every month" into the text without the recurrence and the recurrence
+    pub fn extract(text: &str) -> (String, Option<Self>) {
+        let marker = format!(" {RECURRENCE_EMOJI} ");
+        if let Some(idx) = text.rfind(marker.as_str())
+            && let Some((r, len)) = Self::parse_prefix(&text[idx + marker.len()..])
+        {
+            let rest = &text[idx + marker.len() + len..];
+            return (format!("{}{rest}", &text[..idx]), Some(r));
+        }
+        (text.to_string(), None)
+    }
+
+    // The date of the next occurrence, the month end is kept in the shorter months, e.g. Jan 31 goes to Feb 28
+    pub fn next(&self, dt: &DateTimeUtc) -> DateTimeUtc {
+        let next = match self.unit {
+            RecurrenceUnit::Day => dt.checked_add_days(chrono::Days::new(self.interval.into())),
+            RecurrenceUnit::Week => dt.checked_add_days(chrono::Days::new(u64::from(self.interval) * 7)),
+            RecurrenceUnit::Month => dt.checked_add_months(chrono::Months::new(self.interval)),
+            RecurrenceUnit::Year => dt.checked_add_months(chrono::Months::new(self.interval.saturating_mul(12))),
+        };
+        next.unwrap_or(*dt)
+    }
+
+    // The due date of the next occurrence is counted from the current one or from today if the task has no due date
+    pub fn next_due(&self, due: &Option<DateTimeUtc>, today: &DateTimeUtc) -> DateTimeUtc {
+        self.next(due.as_ref().unwrap_or(today))
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            RecurrenceUnit::Day => "day",
+            RecurrenceUnit::Week => "week",
+            RecurrenceUnit::Month => "month",
+            RecurrenceUnit::Year => "year",
+        };
+        if self.interval == 1 {
+            write!(f, "every {unit}")
+        } else {
+            write!(f, "every {} {unit}s", self.interval)
+        }
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = StringError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::parse_prefix(s.trim()) {
+            Some((r, len)) if len == s.trim().len() => Ok(r),
+            _ => Err(StringError::new(
+                format!("Wrong recurrence '{s}', it should be like 'every day' or 'every 2 weeks'").as_str(),
+            )),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
 pub struct PatchPolicy {
     pub is_editable: bool,
     pub is_removable: bool,
@@ -120,6 +227,46 @@ pub trait Task: Send + Sync {
         Vec::new()
     }
 
+    // The id of the parent task of the same provider
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    // The position of the task in the provider's own order, the lower goes first
+    fn order(&self) -> Option<i64> {
+        None
+    }
+
+    // The times to remind about the task besides the due date
+    fn reminders(&self) -> Vec<DateTimeUtc> {
+        Vec::new()
+    }
+
+    // None if the task doesn't repeat
+    fn recurrence(&self) -> Option<Recurrence> {
+        None
+    }
+
+    // The name of the user the task is assigned to
+    fn assignee(&self) -> Option<String> {
+        None
+    }
+
+    // None if the provider doesn't know whom the task is assigned to
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        None
+    }
+
+    // None if the provider doesn't know who has created the task
+    fn is_created_by_me(&self) -> Option<bool> {
+        None
+    }
+
+    // None if the task can't be commented
+    fn comment_count(&self) -> Option<u64> {
+        None
+    }
+
     fn provider(&self) -> String;
 
     fn project(&self) -> Option<Box<dyn ProjectTrait>>;
@@ -165,28 +312,79 @@ where
     String::from("-")
 }
 
-pub fn format(t: &dyn Task) -> String {
-    format!(
-        "- [{}] {} ({}) ({})",
-        t.state(),
-        t.name().display(),
-        format!("due: {}", datetime_to_str(t.due(), &Local::now().timezone())).blue(),
-        t.place().green()
-    )
+// The texts and the urls of the links in the name and the description, without duplicates
+pub fn links(t: &dyn Task) -> Vec<(String, String)> {
+    let mut result: Vec<(String, String)> = Vec::new();
+    let description_links = t.description().map(|d| d.links()).unwrap_or_default();
+    for l in t.name().links().into_iter().chain(description_links) {
+        if !result.iter().any(|(_, url)| *url == l.1) {
+            result.push(l);
+        }
+    }
+    result
 }
 
-pub fn due_group(due: &Option<DateTimeUtc>) -> filter::Due {
-    match due {
-        Some(d) => {
-            let now = chrono::Utc::now().date_naive();
-            match d.date_naive().cmp(&now) {
-                Ordering::Less => filter::Due::Overdue,
-                Ordering::Equal => filter::Due::Today,
-                Ordering::Greater => filter::Due::Future,
+// Orders the tasks so the subtasks follow their parents, returns the task indexes with the nesting depths.
+// The subtasks whose parents aren't in the list are shown as top level tasks.
+pub fn hierarchy(tasks: &[&dyn Task]) -> Vec<(usize, usize)> {
+    let key = |t: &dyn Task| (t.provider(), t.id());
+    let parent = |t: &dyn Task| {
+        t.parent_id()
+            .map(|id| (t.provider(), id))
+            .filter(|k| *k != key(t) && tasks.iter().any(|p| key(*p) == *k))
+    };
+
+    fn add(idx: usize, depth: usize, children: &[Vec<usize>], visited: &mut [bool], result: &mut Vec<(usize, usize)>) {
+        if visited[idx] {
+            return;
+        }
+        visited[idx] = true;
+        result.push((idx, depth));
+        for c in &children[idx] {
+            add(*c, depth + 1, children, visited, result);
+        }
+    }
+
+    let mut children = vec![Vec::new(); tasks.len()];
+    let mut roots = Vec::new();
+    for (i, t) in tasks.iter().enumerate() {
+        match parent(*t).and_then(|k| tasks.iter().position(|p| key(*p) == k)) {
+            Some(p) => children[p].push(i),
+            None => roots.push(i),
+        }
+    }
+
+    let mut visited = vec![false; tasks.len()];
+    let mut result = Vec::with_capacity(tasks.len());
+    // the tasks in the parent cycles are reachable from none of the roots
+    for i in roots.into_iter().chain(0..tasks.len()) {
+        add(i, 0, &children, &mut visited, &mut result);
+    }
+    result
+}
+
+// The indexes of all the subtasks of the task in the list, the nested ones included
+pub fn subtasks(parent: &dyn Task, tasks: &[&dyn Task]) -> Vec<usize> {
+    let provider = parent.provider();
+    let mut result: Vec<usize> = Vec::new();
+    let mut parents = vec![parent.id()];
+    while let Some(id) = parents.pop() {
+        for (i, t) in tasks.iter().enumerate() {
+            if t.provider() == provider
+                && t.parent_id().is_some_and(|p| p == id)
+                && t.id() != parent.id()
+                && !result.contains(&i)
+            {
+                result.push(i);
+                parents.push(t.id());
             }
         }
-        None => filter::Due::NoDate,
     }
+    result
+}
+
+pub fn due_group(due: &Option<DateTimeUtc>) -> filter::Due {
+    crate::time::due_group(due)
 }
 
 pub fn planned_date<'a>(scheduled: &'a Option<DateTimeUtc>, due: &'a Option<DateTimeUtc>) -> &'a Option<DateTimeUtc> {
@@ -206,7 +404,8 @@ mod test {
 
     use crate::task::DateTimeUtc;
 
-    use super::planned_date;
+    use super::{Recurrence, RecurrenceUnit, Task, hierarchy, planned_date, subtasks};
+    use crate::{RichString, project::Project as ProjectTrait, task::State};
 
     fn dt(year: u16, month: u8, day: u8) -> Option<DateTimeUtc> {
         Some(DateTimeUtc::from_naive_utc_and_offset(
@@ -226,4 +425,237 @@ mod test {
         assert_eq!(&dt(2026, 1, 1), planned_date(&dt(2026, 1, 1), &dt(2026, 1, 2)));
         assert_eq!(&dt(2026, 1, 1), planned_date(&dt(2026, 1, 2), &dt(2026, 1, 1)));
     }
+
+    #[test]
+    fn recurrence_extract_test() {
+        struct Case<'a> {
+            name: &'a str,
+            text: &'a str,
+            expected: (&'a str, Option<Recurrence>),
+        }
+        let cases = [
+            Case {
+                name: "no recurrence",
+                text: "Pay rent",
+                expected: ("Pay rent", None),
+            },
+            Case {
+                name: "every month",
+                text: "Pay rent 🔁 every month",
+                expected: ("Pay rent", Some(Recurrence::new(1, RecurrenceUnit::Month))),
+            },
+            Case {
+                name: "interval and the text after",
+                text: "Water plants 🔁 Every 3 Days #home",
+                expected: ("Water plants #home", Some(Recurrence::new(3, RecurrenceUnit::Day))),
+            },
+            Case {
+                name: "adverb",
+                text: "Review 🔁 weekly",
+                expected: ("Review", Some(Recurrence::new(1, RecurrenceUnit::Week))),
+            },
+            Case {
+                name: "unsupported rule",
+                text: "Review 🔁 every weekday",
+                expected: ("Review 🔁 every weekday", None),
+            },
+            Case {
+                name: "zero interval",
+                text: "Review 🔁 every 0 years",
+                expected: ("Review 🔁 every 0 years", None),
+            },
+        ];
+
+        for c in cases {
+            let (text, r) = Recurrence::extract(c.text);
+            assert_eq!((text.as_str(), r), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn recurrence_next_test() {
+        struct Case<'a> {
+            name: &'a str,
+            recurrence: &'a str,
+            due: Option<DateTimeUtc>,
+            expected: Option<DateTimeUtc>,
+        }
+        let cases = [
+            Case {
+                name: "every day",
+                recurrence: "every day",
+                due: dt(2026, 2, 28),
+                expected: dt(2026, 3, 1),
+            },
+            Case {
+                name: "every 2 weeks",
+                recurrence: "every 2 weeks",
+                due: dt(2026, 1, 1),
+                expected: dt(2026, 1, 15),
+            },
+            Case {
+                name: "the month end",
+                recurrence: "monthly",
+                due: dt(2026, 1, 31),
+                expected: dt(2026, 2, 28),
+            },
+            Case {
+                name: "every year",
+                recurrence: "every year",
+                due: dt(2028, 2, 29),
+                expected: dt(2029, 2, 28),
+            },
+            Case {
+                name: "no due date",
+                recurrence: "every 3 days",
+                due: None,
+                expected: dt(2026, 1, 13),
+            },
+        ];
+
+        let today = dt(2026, 1, 10).unwrap();
+        for c in cases {
+            let r = c.recurrence.parse::<Recurrence>().unwrap();
+            assert_eq!(
+                Some(r.next_due(&c.due, &today)),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+
+        assert_eq!(Recurrence::new(2, RecurrenceUnit::Week).to_string(), "every 2 weeks");
+        assert_eq!(Recurrence::new(1, RecurrenceUnit::Day).to_string(), "every day");
+        assert!("every day or two".parse::<Recurrence>().is_err());
+    }
+
+    #[derive(Clone)]
+    struct TestTask {
+        id: &'static str,
+        provider: &'static str,
+        parent: Option<&'static str>,
+    }
+
+    impl Task for TestTask {
+        fn id(&self) -> String {
+            self.id.to_string()
+        }
+        fn name(&self) -> RichString {
+            RichString::new(self.id)
+        }
+        fn state(&self) -> State {
+            State::Uncompleted
+        }
+        fn parent_id(&self) -> Option<String> {
+            self.parent.map(|p| p.to_string())
+        }
+        fn provider(&self) -> String {
+            self.provider.to_string()
+        }
+        fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+            None
+        }
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+        fn clone_boxed(&self) -> Box<dyn Task> {
+            Box::new(self.clone())
+        }
+    }
+
+    const fn t(id: &'static str, provider: &'static str, parent: Option<&'static str>) -> TestTask {
+        TestTask { id, provider, parent }
+    }
+
+    #[test]
+    fn hierarchy_test() {
+        struct Case<'a> {
+            name: &'a str,
+            tasks: &'a [TestTask],
+            expected: &'a [(usize, usize)],
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no subtasks",
+                tasks: &[t("1", "p", None), t("2", "p", None)],
+                expected: &[(0, 0), (1, 0)],
+            },
+            Case {
+                name: "subtasks follow the parent",
+                tasks: &[
+                    t("child", "p", Some("parent")),
+                    t("other", "p", None),
+                    t("parent", "p", None),
+                    t("grandchild", "p", Some("child")),
+                ],
+                expected: &[(1, 0), (2, 0), (0, 1), (3, 2)],
+            },
+            Case {
+                name: "the parent isn't in the list",
+                tasks: &[t("child", "p", Some("parent")), t("other", "p", None)],
+                expected: &[(0, 0), (1, 0)],
+            },
+            Case {
+                name: "the parent is in another provider",
+                tasks: &[t("parent", "p1", None), t("child", "p2", Some("parent"))],
+                expected: &[(0, 0), (1, 0)],
+            },
+            Case {
+                name: "cycle",
+                tasks: &[t("1", "p", Some("2")), t("2", "p", Some("1")), t("3", "p", Some("3"))],
+                expected: &[(2, 0), (0, 0), (1, 1)],
+            },
+        ];
+
+        for c in CASES {
+            let tasks = c.tasks.iter().map(|t| t as &dyn Task).collect::<Vec<&dyn Task>>();
+            assert_eq!(hierarchy(&tasks), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn subtasks_test() {
+        struct Case<'a> {
+            name: &'a str,
+            parent: TestTask,
+            expected: &'a [usize],
+        }
+        let tasks = [
+            t("parent", "p", None),
+            t("child", "p", Some("parent")),
+            t("grandchild", "p", Some("child")),
+            t("other", "p", None),
+            t("stranger", "p2", Some("parent")),
+            t("second child", "p", Some("parent")),
+        ];
+        let cases = [
+            Case {
+                name: "children and grandchildren",
+                parent: t("parent", "p", None),
+                expected: &[1, 5, 2],
+            },
+            Case {
+                name: "no subtasks",
+                parent: t("other", "p", None),
+                expected: &[],
+            },
+            Case {
+                name: "the deepest subtask",
+                parent: t("grandchild", "p", Some("child")),
+                expected: &[],
+            },
+        ];
+
+        let tasks = tasks.iter().map(|t| t as &dyn Task).collect::<Vec<&dyn Task>>();
+        for c in cases {
+            assert_eq!(subtasks(&c.parent, &tasks), c.expected, "Test '{}' was failed", c.name);
+        }
+        let cyclic = [t("1", "p", Some("2")), t("2", "p", Some("1"))];
+        let cyclic = cyclic.iter().map(|t| t as &dyn Task).collect::<Vec<&dyn Task>>();
+        assert_eq!(
+            subtasks(cyclic[0], &cyclic),
+            vec![1],
+            "the parent isn't its own subtask"
+        );
+    }
 }
@@ -1,10 +1,13 @@
 // SPDX-License-Identifier: MIT
 
-use chrono::{Datelike, Local};
+use std::sync::Arc;
+
+use chrono::{Local, NaiveTime};
 use serde::{Deserialize, Serialize};
 
+use crate::provider::ProviderError;
 use crate::task::{DateTimeUtc, Priority, State, Task as TaskTrait, datetime_to_str};
-use crate::time::{add_days, clear_time};
+use crate::time::{self, add_days, clear_time};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum DatePatchItem {
@@ -41,11 +44,16 @@ impl DatePatchItem {
         let result = match self {
             DatePatchItem::Today => Some(*current_dt),
             DatePatchItem::Tomorrow => Some(add_days(current_dt, 1)),
-            DatePatchItem::ThisWeekend => match current_dt.weekday() {
-                chrono::Weekday::Sat | chrono::Weekday::Sun => Some(*current_dt),
-                wd => Some(add_days(current_dt, 5 - wd as u64)),
-            },
-            DatePatchItem::NextWeek => Some(add_days(current_dt, 7 - current_dt.weekday() as u64)),
+            DatePatchItem::ThisWeekend => Some(
+                time::weekend(current_dt.date_naive())
+                    .and_time(NaiveTime::MIN)
+                    .and_utc(),
+            ),
+            DatePatchItem::NextWeek => Some(
+                time::next_week_start(current_dt.date_naive())
+                    .and_time(NaiveTime::MIN)
+                    .and_utc(),
+            ),
             DatePatchItem::NoDate => None,
             DatePatchItem::Custom(dt) => Some(dt),
         };
@@ -66,13 +74,13 @@ impl DatePatchItem {
 
 impl From<DatePatchItem> for Option<DateTimeUtc> {
     fn from(value: DatePatchItem) -> Option<DateTimeUtc> {
-        value.to_date(&chrono::Utc::now())
+        value.to_date(&time::today())
     }
 }
 
 impl From<DateTimeUtc> for DatePatchItem {
     fn from(dt: DateTimeUtc) -> Self {
-        let now = clear_time(&chrono::Utc::now());
+        let now = time::today();
         match (dt - now).num_days() {
             0 => DatePatchItem::Today,
             1 => DatePatchItem::Tomorrow,
@@ -81,7 +89,7 @@ impl From<DateTimeUtc> for DatePatchItem {
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub enum ValuePatch<T> {
     #[default]
     NotSet,
@@ -161,29 +169,37 @@ mod test {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct TaskPatch {
-    pub task: Option<Box<dyn TaskTrait>>,
+    pub task: Option<Arc<dyn TaskTrait>>,
     pub name: ValuePatch<String>,
     pub description: ValuePatch<String>,
     pub due: ValuePatch<DatePatchItem>,
     pub scheduled: ValuePatch<DatePatchItem>,
     pub priority: ValuePatch<Priority>,
     pub state: ValuePatch<State>,
+    pub labels: ValuePatch<Vec<String>>,
+    // the id of the parent task, it's set for the created subtasks
+    pub parent: ValuePatch<String>,
+    // the link of the created task, it's kept by the providers with the url capability only
+    pub url: ValuePatch<String>,
 }
 
 impl std::fmt::Display for TaskPatch {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!(
-            "TaskPatch {{ task_id: {}, task_title: {} state: {:?}, due: {:?}, scheduled: {:?}, priority: {:?}, name: {:?}, description: {:?}",
+            "TaskPatch {{ task_id: {}, task_title: {} state: {:?}, due: {:?}, scheduled: {:?}, priority: {:?}, name: {:?}, description: {:?}, labels: {:?}, parent: {:?}, url: {:?}",
             self.task.as_ref().map(|t| t.id()).unwrap_or("-".to_string()),
-            self.task.as_ref().map(|t| t.name().display()).unwrap_or("-".to_string()),
+            self.task.as_ref().map(|t| t.name().plain()).unwrap_or("-".to_string()),
             self.state,
             self.due,
             self.scheduled,
             self.priority,
             self.name,
             self.description,
+            self.labels,
+            self.parent,
+            self.url,
         ))
     }
 }
@@ -201,7 +217,10 @@ impl TaskPatch {
             || self.due.is_set()
             || self.scheduled.is_set()
             || self.priority.is_set()
-            || self.state.is_set())
+            || self.state.is_set()
+            || self.labels.is_set()
+            || self.parent.is_set()
+            || self.url.is_set())
     }
 
     pub fn is_task(&self, task: &dyn TaskTrait) -> bool {
@@ -209,35 +228,79 @@ impl TaskPatch {
             .as_ref()
             .is_some_and(|t| t.id() == task.id() && t.provider() == task.provider())
     }
+
+    // Applies the fields that are set in the other patch
+    pub fn merge(&mut self, other: &TaskPatch) {
+        replace_if(&mut self.name, &other.name);
+        replace_if(&mut self.description, &other.description);
+        replace_if(&mut self.due, &other.due);
+        replace_if(&mut self.scheduled, &other.scheduled);
+        replace_if(&mut self.priority, &other.priority);
+        replace_if(&mut self.state, &other.state);
+        replace_if(&mut self.labels, &other.labels);
+        replace_if(&mut self.parent, &other.parent);
+        replace_if(&mut self.url, &other.url);
+    }
+
+    // Moves the url to the end of the description for the providers that can't keep it
+    pub fn with_url_in_description(mut self) -> Self {
+        if let Some(url) = self.url.value() {
+            self.description = ValuePatch::Value(match self.description.value().filter(|d| !d.is_empty()) {
+                Some(d) => format!("{d}\n\n{url}"),
+                None => url,
+            });
+            self.url = ValuePatch::NotSet;
+        }
+        self
+    }
 }
 
-impl Clone for TaskPatch {
-    fn clone(&self) -> Self {
-        Self {
-            task: if let Some(t) = self.task.as_ref() {
-                Some(t.clone_boxed())
-            } else {
-                None
-            },
-            name: self.name.clone(),
-            description: self.description.clone(),
-            due: self.due.clone(),
-            scheduled: self.scheduled.clone(),
-            priority: self.priority.clone(),
-            state: self.state.clone(),
+fn replace_if<T>(op: &mut ValuePatch<T>, other: &ValuePatch<T>)
+where
+    T: Clone,
+{
+    if other.is_set() {
+        *op = other.clone();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PatchErrorKind {
+    #[default]
+    Other,
+    // the change can be sent again when the network is back
+    Network,
+}
+
+impl PatchErrorKind {
+    // The provider errors are looked up in the boxed errors of the clients too
+    pub fn of(e: &(dyn std::error::Error + 'static)) -> Self {
+        match e.downcast_ref::<ProviderError>() {
+            Some(ProviderError::Network(_) | ProviderError::RateLimited { .. }) => Self::Network,
+            _ => Self::Other,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct PatchError {
-    pub task: Box<dyn TaskTrait>,
+    pub task: Arc<dyn TaskTrait>,
     pub error: String,
+    pub kind: PatchErrorKind,
 }
 
 impl PatchError {
     pub fn is_task(&self, task: &dyn TaskTrait) -> bool {
         self.task.id() == task.id() && self.task.provider() == task.provider()
     }
+
+    pub fn wrong_task_type(task: &dyn TaskTrait, provider_type: &str) -> Self {
+        Self {
+            task: task.clone_boxed().into(),
+            error: ProviderError::wrong_task_type(task, provider_type).to_string(),
+            kind: PatchErrorKind::Other,
+        }
+    }
 }
 
 impl std::fmt::Display for PatchError {
@@ -246,15 +309,6 @@ impl std::fmt::Display for PatchError {
     }
 }
 
-impl Clone for PatchError {
-    fn clone(&self) -> Self {
-        Self {
-            task: self.task.clone_boxed(),
-            error: self.error.clone(),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -346,4 +400,41 @@ mod tests {
             assert_eq!(result, c.result, "Test '{}' was failed", c.name);
         }
     }
+
+    #[test]
+    fn with_url_in_description_test() {
+        let tp = TaskPatch {
+            description: ValuePatch::Value("It fails on CI".to_string()),
+            url: ValuePatch::Value("https://chat/1".to_string()),
+            ..TaskPatch::default()
+        }
+        .with_url_in_description();
+        assert_eq!(
+            tp.description.value(),
+            Some("It fails on CI\n\nhttps://chat/1".to_string())
+        );
+        assert!(!tp.url.is_set());
+
+        let tp = TaskPatch {
+            url: ValuePatch::Value("https://chat/1".to_string()),
+            ..TaskPatch::default()
+        }
+        .with_url_in_description();
+        assert_eq!(tp.description.value(), Some("https://chat/1".to_string()));
+
+        let tp = TaskPatch::default().with_url_in_description();
+        assert!(!tp.description.is_set());
+    }
+
+    #[test]
+    fn patch_error_kind_test() {
+        let network: Box<dyn std::error::Error> = Box::new(ProviderError::Network("timeout".to_string()));
+        assert_eq!(PatchErrorKind::of(network.as_ref()), PatchErrorKind::Network);
+        let rate_limited = ProviderError::RateLimited { retry_after: None };
+        assert_eq!(PatchErrorKind::of(&rate_limited), PatchErrorKind::Network);
+        let not_found = ProviderError::NotFound("task".to_string());
+        assert_eq!(PatchErrorKind::of(&not_found), PatchErrorKind::Other);
+        let other: Box<dyn std::error::Error> = Box::from("network is unreachable");
+        assert_eq!(PatchErrorKind::of(other.as_ref()), PatchErrorKind::Other);
+    }
 }
@@ -0,0 +1,746 @@
+// SPDX-License-Identifier: MIT
+
+use std::{cmp::Ordering, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    StringError,
+    filter::Filter,
+    provider::{ProviderError, TaskProviderTrait},
+    registry::ProviderRegistry,
+    tag_alias::TagAliases,
+    task::{Task as TaskTrait, due_group},
+    task_patch::{PatchError, PatchErrorKind, TaskPatch},
+};
+
+/// How the aggregated tasks are sorted inside the due groups.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    /// By priority, due date, project and name, see [`compare_tasks`].
+    #[default]
+    Priority,
+    /// By provider and project, then in the provider's own order (e.g. Todoist's child order or the manual order of the local tasks).
+    /// The tasks without such order are sorted by priority.
+    Native,
+}
+
+/// The default order of the aggregated tasks: overdue first, then by priority, due date, project and name.
+pub fn compare_tasks(l: &dyn TaskTrait, r: &dyn TaskTrait) -> Ordering {
+    let project_name = |t: &dyn TaskTrait| t.project().map(|p| p.name()).unwrap_or_default();
+
+    due_group(&l.planned_date())
+        .cmp(&due_group(&r.planned_date()))
+        .then_with(|| r.priority().cmp(&l.priority()))
+        .then_with(|| l.due().cmp(&r.due()))
+        .then_with(|| project_name(l).cmp(&project_name(r)))
+        .then_with(|| l.name().plain().cmp(&r.name().plain()))
+}
+
+pub fn compare_tasks_by(order: SortOrder, l: &dyn TaskTrait, r: &dyn TaskTrait) -> Ordering {
+    match order {
+        SortOrder::Priority => compare_tasks(l, r),
+        SortOrder::Native => {
+            let project_name = |t: &dyn TaskTrait| t.project().map(|p| p.name()).unwrap_or_default();
+
+            due_group(&l.planned_date())
+                .cmp(&due_group(&r.planned_date()))
+                .then_with(|| l.provider().cmp(&r.provider()))
+                .then_with(|| project_name(l).cmp(&project_name(r)))
+                .then_with(|| l.order().cmp(&r.order()))
+                .then_with(|| compare_tasks(l, r))
+        }
+    }
+}
+
+/// Replaces the provider's tasks in the list sorted by [`compare_tasks_by`].
+/// Only the new tasks are sorted, then they are merged into the list, so the order of the equal tasks is kept.
+pub fn merge_provider_tasks(
+    tasks: &mut Vec<Arc<dyn TaskTrait>>,
+    provider: &str,
+    new: Vec<Box<dyn TaskTrait>>,
+    order: SortOrder,
+) {
+    let mut new = new.into_iter().map(Arc::from).collect::<Vec<Arc<dyn TaskTrait>>>();
+    new.sort_by(|l, r| compare_tasks_by(order, l.as_ref(), r.as_ref()));
+
+    let old = std::mem::take(tasks);
+    let mut old = old.into_iter().filter(|t| t.provider() != provider).peekable();
+    let mut new = new.into_iter().peekable();
+    loop {
+        let take_new = match (old.peek(), new.peek()) {
+            (Some(o), Some(n)) => compare_tasks_by(order, n.as_ref(), o.as_ref()) == Ordering::Less,
+            (Some(_), None) => false,
+            (None, Some(_)) => true,
+            (None, None) => break,
+        };
+        tasks.extend(if take_new { new.next() } else { old.next() });
+    }
+}
+
+/// Whose tasks are selected. The tasks of the providers that don't tell it apart are always selected.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Involvement {
+    #[default]
+    All,
+    AssignedToMe,
+    CreatedByMe,
+}
+
+impl Involvement {
+    pub fn next(&self) -> Self {
+        match self {
+            Involvement::All => Involvement::AssignedToMe,
+            Involvement::AssignedToMe => Involvement::CreatedByMe,
+            Involvement::CreatedByMe => Involvement::All,
+        }
+    }
+
+    pub fn matches(&self, t: &dyn TaskTrait) -> bool {
+        match self {
+            Involvement::All => true,
+            Involvement::AssignedToMe => t.is_assigned_to_me().unwrap_or(true),
+            Involvement::CreatedByMe => t.is_created_by_me().unwrap_or(true),
+        }
+    }
+}
+
+impl std::fmt::Display for Involvement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Involvement::All => write!(f, "All"),
+            Involvement::AssignedToMe => write!(f, "Assigned to me"),
+            Involvement::CreatedByMe => write!(f, "Created by me"),
+        }
+    }
+}
+
+/// Narrows the loaded tasks down. The empty list means no restriction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    pub providers: Vec<String>,
+    /// The providers whose tasks are hidden, e.g. the ones of the excluded groups.
+    pub excluded_providers: Vec<String>,
+    pub projects: Vec<String>,
+    pub tags: Vec<String>,
+    /// The aliases of the tags, the task matches the tag by any of them.
+    pub tag_aliases: TagAliases,
+    pub involvement: Involvement,
+}
+
+impl Selection {
+    pub fn matches(&self, t: &dyn TaskTrait) -> bool {
+        if !self.providers.is_empty() && !self.providers.contains(&t.provider()) {
+            return false;
+        }
+        if self.excluded_providers.contains(&t.provider()) {
+            return false;
+        }
+        if let Some(p) = t.project()
+            && !self.projects.is_empty()
+            && !self.projects.contains(&p.name())
+        {
+            return false;
+        }
+        if !self.involvement.matches(t) {
+            return false;
+        }
+        if self.tags.is_empty() {
+            return true;
+        }
+        let tags = self.tag_aliases.tags(&self.tags);
+        self.tag_aliases.tags(&t.labels()).iter().any(|l| tags.contains(l))
+    }
+}
+
+/// The tasks of all providers in one sorted list with the queue of the not committed changes.
+///
+/// The store doesn't depend on any UI, the changes are sent to the providers only by [`AggregatedTaskStore::commit`].
+#[derive(Default)]
+pub struct AggregatedTaskStore {
+    tasks: Vec<Arc<dyn TaskTrait>>,
+    patches: Vec<TaskPatch>,
+    sort_order: SortOrder,
+}
+
+impl AggregatedTaskStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_sort_order(mut self, order: SortOrder) -> Self {
+        self.sort_order = order;
+        self
+    }
+
+    /// The order is applied to the tasks loaded next time
+    pub fn set_sort_order(&mut self, order: SortOrder) {
+        self.sort_order = order;
+    }
+
+    pub fn tasks(&self) -> &[Arc<dyn TaskTrait>] {
+        &self.tasks
+    }
+
+    pub fn select(&self, s: &Selection) -> Vec<&dyn TaskTrait> {
+        self.tasks
+            .iter()
+            .map(|t| t.as_ref())
+            .filter(|t| s.matches(*t))
+            .collect()
+    }
+
+    /// Replaces the tasks of the provider.
+    /// The queued changes of the tasks that are gone or have changed their state are dropped.
+    pub fn set_provider_tasks(&mut self, provider: &str, tasks: Vec<Box<dyn TaskTrait>>) {
+        merge_provider_tasks(&mut self.tasks, provider, tasks, self.sort_order);
+
+        self.patches.retain(|tp| {
+            tp.task.as_ref().is_some_and(|task| {
+                self.tasks
+                    .iter()
+                    .any(|t| tp.is_task(t.as_ref()) && t.state() == task.state())
+            })
+        });
+    }
+
+    /// Drops the tasks of the provider, e.g. when it fails to load them
+    pub fn remove_provider_tasks(&mut self, provider: &str) {
+        self.tasks.retain(|t| t.provider() != provider);
+        self.patches
+            .retain(|tp| tp.task.as_ref().is_none_or(|t| t.provider() != provider));
+    }
+
+    /// Loads the tasks of all providers. The failed providers keep their previous tasks.
+    pub async fn load(&mut self, registry: &ProviderRegistry, f: &Filter) -> Vec<(String, ProviderError)> {
+        let mut errors = Vec::new();
+        for (name, p) in registry.iter() {
+            match TaskProviderTrait::list(p.write().await.as_mut(), None, f).await {
+                Ok(tasks) => self.set_provider_tasks(name, tasks),
+                Err(e) => errors.push((name.to_string(), e)),
+            }
+        }
+        errors
+    }
+
+    /// Queues the change of the existing task. It's merged with the already queued change of the same task.
+    pub fn queue_patch(&mut self, tp: TaskPatch) -> Result<(), StringError> {
+        let task = tp
+            .task
+            .as_ref()
+            .ok_or_else(|| StringError::new("The patch has no task to change"))?;
+        match self.patches.iter_mut().find(|p| p.is_task(task.as_ref())) {
+            Some(p) => p.merge(&tp),
+            None => self.patches.push(tp),
+        }
+        Ok(())
+    }
+
+    pub fn undo(&mut self, t: &dyn TaskTrait) {
+        self.patches.retain(|tp| !tp.is_task(t));
+    }
+
+    pub fn pending_patches(&self) -> &[TaskPatch] {
+        &self.patches
+    }
+
+    /// Sends the queued changes to the providers and reloads them.
+    /// The changes that have failed stay in the queue.
+    pub async fn commit(&mut self, registry: &ProviderRegistry) -> Vec<PatchError> {
+        let mut all_errors = Vec::new();
+
+        for name in self
+            .patches
+            .iter()
+            .filter_map(|tp| tp.task.as_ref().map(|t| t.provider()))
+            .collect::<std::collections::BTreeSet<String>>()
+        {
+            let patches = self
+                .patches
+                .iter()
+                .filter(|tp| tp.task.as_ref().is_some_and(|t| t.provider() == name))
+                .cloned()
+                .collect::<Vec<TaskPatch>>();
+
+            let errors = match registry.get(&name) {
+                Some(p) => {
+                    let mut p = p.write().await;
+                    let errors = p.update(&patches).await;
+                    p.reload().await;
+                    errors
+                }
+                None => patches
+                    .iter()
+                    .filter_map(|tp| tp.task.as_ref())
+                    .map(|t| PatchError {
+                        task: t.clone(),
+                        error: format!("The provider {name} is not found"),
+                        kind: PatchErrorKind::Other,
+                    })
+                    .collect(),
+            };
+
+            self.patches.retain(|tp| {
+                let committed = tp.task.as_ref().is_some_and(|t| t.provider() == name)
+                    && !errors
+                        .iter()
+                        .any(|pe| tp.task.as_ref().is_some_and(|t| pe.is_task(t.as_ref())));
+                !committed
+            });
+            all_errors.extend(errors);
+        }
+
+        all_errors
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        RichString,
+        project::Project as ProjectTrait,
+        provider::{Capabilities, ProjectProviderTrait, ProviderTrait},
+        task::{DateTimeUtc, Priority, State},
+        task_patch::ValuePatch,
+    };
+    use async_trait::async_trait;
+    use std::any::Any;
+
+    #[derive(Clone, Debug)]
+    struct TestTask {
+        id: String,
+        provider: String,
+        priority: Priority,
+        due: Option<DateTimeUtc>,
+        labels: Vec<String>,
+        order: Option<i64>,
+        assigned_to_me: Option<bool>,
+    }
+
+    impl TaskTrait for TestTask {
+        fn id(&self) -> String {
+            self.id.clone()
+        }
+        fn name(&self) -> RichString {
+            RichString::new(&self.id)
+        }
+        fn priority(&self) -> Priority {
+            self.priority
+        }
+        fn state(&self) -> State {
+            State::Uncompleted
+        }
+        fn due(&self) -> Option<DateTimeUtc> {
+            self.due
+        }
+        fn labels(&self) -> Vec<String> {
+            self.labels.clone()
+        }
+        fn order(&self) -> Option<i64> {
+            self.order
+        }
+        fn is_assigned_to_me(&self) -> Option<bool> {
+            self.assigned_to_me
+        }
+        fn provider(&self) -> String {
+            self.provider.clone()
+        }
+        fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+            None
+        }
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+        fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+            Box::new(self.clone())
+        }
+    }
+
+    fn task(id: &str, provider: &str, priority: Priority) -> Box<dyn TaskTrait> {
+        Box::new(TestTask {
+            id: id.to_string(),
+            provider: provider.to_string(),
+            priority,
+            due: None,
+            labels: Vec::new(),
+            order: None,
+            assigned_to_me: None,
+        })
+    }
+
+    #[derive(Debug)]
+    struct TestProvider {
+        name: String,
+        tasks: Vec<TestTask>,
+        failed_ids: Vec<String>,
+        updated: usize,
+    }
+
+    #[async_trait]
+    impl ProjectProviderTrait for TestProvider {
+        async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[async_trait]
+    impl TaskProviderTrait for TestProvider {
+        async fn list(
+            &mut self,
+            _project: Option<Box<dyn ProjectTrait>>,
+            _f: &Filter,
+        ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+            Ok(self.tasks.iter().map(|t| t.clone_boxed()).collect())
+        }
+        async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+            Ok(())
+        }
+        async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+            self.updated += patches.len();
+            patches
+                .iter()
+                .filter_map(|tp| tp.task.as_ref())
+                .filter(|t| self.failed_ids.contains(&t.id()))
+                .map(|t| PatchError {
+                    task: t.clone(),
+                    error: "failed".to_string(),
+                    kind: PatchErrorKind::Other,
+                })
+                .collect()
+        }
+    }
+
+    #[async_trait]
+    impl ProviderTrait for TestProvider {
+        fn name(&self) -> String {
+            self.name.clone()
+        }
+        fn type_name(&self) -> String {
+            "Test".to_string()
+        }
+        async fn reload(&mut self) {}
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                create_task: false,
+                labels: false,
+                subtasks: false,
+                manual_order: false,
+                assignees: false,
+                queries: false,
+                url: false,
+            }
+        }
+    }
+
+    fn patch(t: &dyn TaskTrait, priority: Priority) -> TaskPatch {
+        TaskPatch {
+            task: Some(t.clone_boxed().into()),
+            priority: ValuePatch::Value(priority),
+            ..TaskPatch::default()
+        }
+    }
+
+    #[test]
+    fn set_provider_tasks_test() {
+        let mut s = AggregatedTaskStore::new();
+        s.set_provider_tasks("Home", vec![task("low", "Home", Priority::Low)]);
+        s.set_provider_tasks("Work", vec![task("high", "Work", Priority::High)]);
+        let ids = |s: &AggregatedTaskStore| s.tasks().iter().map(|t| t.id()).collect::<Vec<String>>();
+        assert_eq!(ids(&s), vec!["high", "low"]);
+
+        s.queue_patch(patch(s.tasks()[1].as_ref(), Priority::Highest)).unwrap();
+        s.set_provider_tasks("Home", vec![task("medium", "Home", Priority::Medium)]);
+        assert_eq!(ids(&s), vec!["high", "medium"]);
+        assert!(s.pending_patches().is_empty());
+
+        s.queue_patch(patch(s.tasks()[0].as_ref(), Priority::Highest)).unwrap();
+        s.queue_patch(patch(s.tasks()[1].as_ref(), Priority::Highest)).unwrap();
+        s.remove_provider_tasks("Work");
+        assert_eq!(ids(&s), vec!["medium"]);
+        assert_eq!(s.pending_patches().len(), 1);
+    }
+
+    #[test]
+    fn merge_provider_tasks_test() {
+        let ids = |tasks: &[Arc<dyn TaskTrait>]| {
+            tasks
+                .iter()
+                .map(|t| format!("{}/{}", t.provider(), t.id()))
+                .collect::<Vec<String>>()
+        };
+
+        let mut tasks = Vec::new();
+        merge_provider_tasks(
+            &mut tasks,
+            "Home",
+            vec![task("b", "Home", Priority::Low), task("a", "Home", Priority::High)],
+            SortOrder::Priority,
+        );
+        assert_eq!(ids(&tasks), vec!["Home/a", "Home/b"]);
+
+        // the equal tasks of the other provider are placed after the existing ones
+        merge_provider_tasks(
+            &mut tasks,
+            "Work",
+            vec![task("c", "Work", Priority::Normal), task("a", "Work", Priority::High)],
+            SortOrder::Priority,
+        );
+        assert_eq!(ids(&tasks), vec!["Home/a", "Work/a", "Work/c", "Home/b"]);
+
+        merge_provider_tasks(
+            &mut tasks,
+            "Home",
+            vec![task("d", "Home", Priority::Highest)],
+            SortOrder::Priority,
+        );
+        assert_eq!(ids(&tasks), vec!["Home/d", "Work/a", "Work/c"]);
+
+        merge_provider_tasks(&mut tasks, "Work", Vec::new(), SortOrder::Priority);
+        assert_eq!(ids(&tasks), vec!["Home/d"]);
+    }
+
+    #[test]
+    fn sort_order_test() {
+        let ordered = |id: &str, provider: &str, priority: Priority, order: Option<i64>| -> Box<dyn TaskTrait> {
+            Box::new(TestTask {
+                id: id.to_string(),
+                provider: provider.to_string(),
+                priority,
+                due: None,
+                labels: Vec::new(),
+                order,
+                assigned_to_me: None,
+            })
+        };
+        let new_tasks = || {
+            [
+                (
+                    "Home",
+                    vec![
+                        ordered("a", "Home", Priority::High, Some(2)),
+                        ordered("b", "Home", Priority::Low, Some(1)),
+                    ],
+                ),
+                (
+                    "Work",
+                    vec![
+                        ordered("c", "Work", Priority::Low, None),
+                        ordered("d", "Work", Priority::High, None),
+                    ],
+                ),
+            ]
+        };
+
+        struct Case<'a> {
+            name: &'a str,
+            order: SortOrder,
+            expected: &'a [&'a str],
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "priority",
+                order: SortOrder::Priority,
+                expected: &["Home/a", "Work/d", "Home/b", "Work/c"],
+            },
+            Case {
+                name: "native",
+                order: SortOrder::Native,
+                expected: &["Home/b", "Home/a", "Work/d", "Work/c"],
+            },
+        ];
+
+        for c in CASES {
+            let mut tasks = Vec::new();
+            for (provider, new) in new_tasks() {
+                merge_provider_tasks(&mut tasks, provider, new, c.order);
+            }
+            assert_eq!(
+                tasks
+                    .iter()
+                    .map(|t| format!("{}/{}", t.provider(), t.id()))
+                    .collect::<Vec<String>>(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn queue_patch_test() {
+        let mut s = AggregatedTaskStore::new();
+        let t = task("id", "Home", Priority::Low);
+        s.queue_patch(patch(t.as_ref(), Priority::High)).unwrap();
+        s.queue_patch(TaskPatch {
+            task: Some(t.clone_boxed().into()),
+            name: ValuePatch::Value("New name".to_string()),
+            ..TaskPatch::default()
+        })
+        .unwrap();
+        assert_eq!(s.pending_patches().len(), 1);
+        assert_eq!(s.pending_patches()[0].priority.value(), Some(Priority::High));
+        assert_eq!(s.pending_patches()[0].name.value(), Some("New name".to_string()));
+
+        assert!(s.queue_patch(TaskPatch::default()).is_err());
+
+        s.undo(t.as_ref());
+        assert!(s.pending_patches().is_empty());
+    }
+
+    #[test]
+    fn selection_test() {
+        let mut tagged = TestTask {
+            id: "tagged".to_string(),
+            provider: "Home".to_string(),
+            priority: Priority::Normal,
+            due: None,
+            labels: vec!["home".to_string()],
+            order: None,
+            assigned_to_me: Some(false),
+        };
+        let mut s = AggregatedTaskStore::new();
+        s.set_provider_tasks(
+            "Home",
+            vec![tagged.clone_boxed(), task("plain", "Home", Priority::Normal)],
+        );
+        tagged.provider = "Work".to_string();
+        s.set_provider_tasks("Work", vec![tagged.clone_boxed()]);
+
+        struct Case<'a> {
+            name: &'a str,
+            selection: Selection,
+            expected: &'a [&'a str],
+        }
+        let cases = [
+            Case {
+                name: "everything",
+                selection: Selection::default(),
+                expected: &["Home/plain", "Home/tagged", "Work/tagged"],
+            },
+            Case {
+                name: "provider",
+                selection: Selection {
+                    providers: vec!["Work".to_string()],
+                    ..Selection::default()
+                },
+                expected: &["Work/tagged"],
+            },
+            Case {
+                name: "excluded provider",
+                selection: Selection {
+                    excluded_providers: vec!["Work".to_string()],
+                    ..Selection::default()
+                },
+                expected: &["Home/plain", "Home/tagged"],
+            },
+            Case {
+                name: "tag",
+                selection: Selection {
+                    tags: vec!["home".to_string()],
+                    ..Selection::default()
+                },
+                expected: &["Home/tagged", "Work/tagged"],
+            },
+            Case {
+                name: "tag alias",
+                selection: Selection {
+                    tags: vec!["house".to_string()],
+                    tag_aliases: TagAliases::parse(&["house = Home".to_string()]).unwrap(),
+                    ..Selection::default()
+                },
+                expected: &["Home/tagged", "Work/tagged"],
+            },
+            Case {
+                name: "assigned to me",
+                selection: Selection {
+                    involvement: Involvement::AssignedToMe,
+                    ..Selection::default()
+                },
+                expected: &["Home/plain"],
+            },
+            Case {
+                name: "created by me",
+                selection: Selection {
+                    involvement: Involvement::CreatedByMe,
+                    ..Selection::default()
+                },
+                expected: &["Home/plain", "Home/tagged", "Work/tagged"],
+            },
+        ];
+
+        for c in cases {
+            let mut selected = s
+                .select(&c.selection)
+                .iter()
+                .map(|t| format!("{}/{}", t.provider(), t.id()))
+                .collect::<Vec<String>>();
+            selected.sort();
+            assert_eq!(selected, c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn load_and_commit_test() {
+        let mut registry = ProviderRegistry::new();
+        let provider = registry
+            .add(Box::new(TestProvider {
+                name: "Home".to_string(),
+                tasks: vec![
+                    TestTask {
+                        id: "ok".to_string(),
+                        provider: "Home".to_string(),
+                        priority: Priority::Low,
+                        due: None,
+                        labels: Vec::new(),
+                        order: None,
+                        assigned_to_me: None,
+                    },
+                    TestTask {
+                        id: "fail".to_string(),
+                        provider: "Home".to_string(),
+                        priority: Priority::Low,
+                        due: None,
+                        labels: Vec::new(),
+                        order: None,
+                        assigned_to_me: None,
+                    },
+                ],
+                failed_ids: vec!["fail".to_string()],
+                updated: 0,
+            }))
+            .unwrap();
+        assert!(
+            registry
+                .add(Box::new(TestProvider {
+                    name: "Home".to_string(),
+                    tasks: Vec::new(),
+                    failed_ids: Vec::new(),
+                    updated: 0,
+                }))
+                .is_err()
+        );
+
+        let mut s = AggregatedTaskStore::new();
+        assert!(s.load(&registry, &Filter::default()).await.is_empty());
+        assert_eq!(s.tasks().len(), 2);
+
+        for t in s.tasks().iter().map(|t| t.clone_boxed()).collect::<Vec<_>>() {
+            s.queue_patch(patch(t.as_ref(), Priority::High)).unwrap();
+        }
+        s.queue_patch(patch(task("orphan", "Absent", Priority::Low).as_ref(), Priority::High))
+            .unwrap();
+
+        let errors = s.commit(&registry).await;
+        let mut failed = errors.iter().map(|e| e.task.id()).collect::<Vec<String>>();
+        failed.sort();
+        assert_eq!(failed, vec!["fail", "orphan"]);
+        assert_eq!(s.pending_patches().len(), 2);
+
+        let p = provider.read().await;
+        let p = format!("{p:?}");
+        assert!(p.contains("updated: 2"));
+    }
+}
@@ -1,8 +1,30 @@
 // SPDX-License-Identifier: MIT
 
-use chrono::NaiveTime;
+use std::{
+    cmp::Ordering,
+    sync::atomic::{AtomicU32, Ordering as AtomicOrdering},
+};
 
-use crate::task::DateTimeUtc;
+use chrono::{Datelike, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Weekday};
+
+use crate::{StringError, filter::Due, task::DateTimeUtc};
+
+// The hour the day starts at, e.g. with 3 the tasks due at 2am are still today's ones for the night owls
+static DAY_START_HOUR: AtomicU32 = AtomicU32::new(0);
+
+pub fn set_day_start_hour(hour: u32) -> Result<(), StringError> {
+    if hour > 23 {
+        return Err(StringError::new(
+            format!("Wrong day start hour {hour}, it should be from 0 to 23").as_str(),
+        ));
+    }
+    DAY_START_HOUR.store(hour, AtomicOrdering::Relaxed);
+    Ok(())
+}
+
+pub fn day_start_hour() -> u32 {
+    DAY_START_HOUR.load(AtomicOrdering::Relaxed)
+}
 
 pub fn clear_time(dt: &DateTimeUtc) -> DateTimeUtc {
     const NULL_TIME: NaiveTime = NaiveTime::from_hms_opt(0, 0, 0).unwrap();
@@ -12,3 +34,508 @@ pub fn clear_time(dt: &DateTimeUtc) -> DateTimeUtc {
 pub fn add_days(dt: &DateTimeUtc, days: u64) -> DateTimeUtc {
     dt.checked_add_days(chrono::Days::new(days)).unwrap()
 }
+
+// The providers store the dates without time as the midnight in UTC
+pub fn is_date_only(dt: &DateTimeUtc) -> bool {
+    dt.time() == NaiveTime::MIN
+}
+
+// The local day of the moment. The wall clock time is shifted by the day start hour,
+// so the day changes at the same hour before and after the DST transitions.
+pub fn day<Tz: TimeZone>(dt: &DateTimeUtc, tz: &Tz, day_start_hour: u32) -> NaiveDate {
+    let local = dt.with_timezone(tz).naive_local();
+    (local - TimeDelta::hours(day_start_hour.into())).date()
+}
+
+// The day of the due or scheduled date of the task
+pub fn task_day<Tz: TimeZone>(dt: &DateTimeUtc, tz: &Tz, day_start_hour: u32) -> NaiveDate {
+    if is_date_only(dt) {
+        dt.date_naive()
+    } else {
+        day(dt, tz, day_start_hour)
+    }
+}
+
+pub fn due_group_at<Tz: TimeZone>(due: &Option<DateTimeUtc>, now: &DateTimeUtc, tz: &Tz, day_start_hour: u32) -> Due {
+    match due {
+        Some(d) => match task_day(d, tz, day_start_hour).cmp(&day(now, tz, day_start_hour)) {
+            Ordering::Less => Due::Overdue,
+            Ordering::Equal => Due::Today,
+            Ordering::Greater => Due::Future,
+        },
+        None => Due::NoDate,
+    }
+}
+
+pub fn due_group(due: &Option<DateTimeUtc>) -> Due {
+    due_group_at(due, &chrono::Utc::now(), &Local, day_start_hour())
+}
+
+// The local today as the date without time
+pub fn today() -> DateTimeUtc {
+    day(&chrono::Utc::now(), &Local, day_start_hour())
+        .and_time(NaiveTime::MIN)
+        .and_utc()
+}
+
+// The weeks start on Monday
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - TimeDelta::days(date.weekday().num_days_from_monday().into())
+}
+
+pub fn next_week_start(date: NaiveDate) -> NaiveDate {
+    week_start(date) + TimeDelta::days(7)
+}
+
+// The Saturday of the week or the date itself if it's the weekend already
+pub fn weekend(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat | Weekday::Sun => date,
+        _ => week_start(date) + TimeDelta::days(5),
+    }
+}
+
+// The compact time passed since the moment, e.g. "5m" or "3d"
+pub fn ago(dt: &DateTimeUtc, now: &DateTimeUtc) -> String {
+    let d = now.signed_duration_since(dt);
+    if d.num_minutes() < 1 {
+        "now".to_string()
+    } else {
+        compact_duration(d)
+    }
+}
+
+// The due date relative to the moment, e.g. "in 2h 5m", "tomorrow" or "3d overdue".
+// The time left is counted down by minutes during the last day, the dates without time are compared by days.
+pub fn relative_due<Tz: TimeZone>(due: &DateTimeUtc, now: &DateTimeUtc, tz: &Tz, day_start_hour: u32) -> String {
+    if is_date_only(due) {
+        return match (task_day(due, tz, day_start_hour) - day(now, tz, day_start_hour)).num_days() {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            d if d > 0 => format!("in {d}d"),
+            d => format!("{}d overdue", -d),
+        };
+    }
+
+    let d = due.signed_duration_since(now);
+    if d.num_minutes() == 0 {
+        "now".to_string()
+    } else if d < TimeDelta::zero() {
+        format!("{} overdue", compact_duration(-d))
+    } else if d.num_hours() == 0 {
+        format!("in {}m", d.num_minutes())
+    } else if d.num_days() == 0 {
+        format!("in {}h {}m", d.num_hours(), d.num_minutes() % 60)
+    } else {
+        format!("in {}", compact_duration(d))
+    }
+}
+
+// How close the due time is, the dates without time and the overdue tasks have no alarm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueAlarm {
+    None,
+    // due within the next hour
+    Soon,
+    // due within the next 15 minutes
+    Imminent,
+}
+
+pub fn due_alarm(due: &DateTimeUtc, now: &DateTimeUtc) -> DueAlarm {
+    if is_date_only(due) {
+        return DueAlarm::None;
+    }
+
+    let left = due.signed_duration_since(now);
+    if left < TimeDelta::zero() || left > TimeDelta::hours(1) {
+        DueAlarm::None
+    } else if left <= TimeDelta::minutes(15) {
+        DueAlarm::Imminent
+    } else {
+        DueAlarm::Soon
+    }
+}
+
+fn compact_duration(d: TimeDelta) -> String {
+    if d.num_hours() < 1 {
+        format!("{}m", d.num_minutes())
+    } else if d.num_days() < 1 {
+        format!("{}h", d.num_hours())
+    } else if d.num_days() < 30 {
+        format!("{}d", d.num_days())
+    } else if d.num_days() < 365 {
+        format!("{}mo", d.num_days() / 30)
+    } else {
+        format!("{}y", d.num_days() / 365)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono_tz::{America::New_York, Europe::Berlin};
+
+    fn utc(s: &str) -> DateTimeUtc {
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn day_test() {
+        struct Case<'a> {
+            name: &'a str,
+            dt: &'a str,
+            day_start_hour: u32,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "the local day is later than the UTC one",
+                dt: "2025-10-25 22:30",
+                day_start_hour: 0,
+                expected: "2025-10-26",
+            },
+            Case {
+                name: "before the day start",
+                dt: "2025-06-10 00:30",
+                day_start_hour: 3,
+                expected: "2025-06-09",
+            },
+            Case {
+                name: "after the day start",
+                dt: "2025-06-10 01:30",
+                day_start_hour: 3,
+                expected: "2025-06-10",
+            },
+            Case {
+                name: "before the day start on the DST start",
+                dt: "2025-03-30 00:30",
+                day_start_hour: 3,
+                expected: "2025-03-29",
+            },
+            Case {
+                name: "after the day start on the DST start",
+                dt: "2025-03-30 01:30",
+                day_start_hour: 3,
+                expected: "2025-03-30",
+            },
+            Case {
+                name: "the repeated hour on the DST end",
+                dt: "2025-10-26 01:30",
+                day_start_hour: 3,
+                expected: "2025-10-25",
+            },
+            Case {
+                name: "after the day start on the DST end",
+                dt: "2025-10-26 02:30",
+                day_start_hour: 3,
+                expected: "2025-10-26",
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                day(&utc(c.dt), &Berlin, c.day_start_hour),
+                date(c.expected),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn due_group_test() {
+        struct Case<'a> {
+            name: &'a str,
+            due: Option<&'a str>,
+            now: &'a str,
+            day_start_hour: u32,
+            expected: Due,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no date",
+                due: None,
+                now: "2025-06-10 12:00",
+                day_start_hour: 0,
+                expected: Due::NoDate,
+            },
+            Case {
+                name: "the date without time is today in any time zone",
+                due: Some("2025-06-10 00:00"),
+                now: "2025-06-10 23:00",
+                day_start_hour: 0,
+                expected: Due::Today,
+            },
+            Case {
+                name: "the date without time is yesterday",
+                due: Some("2025-06-09 00:00"),
+                now: "2025-06-10 12:00",
+                day_start_hour: 0,
+                expected: Due::Overdue,
+            },
+            Case {
+                name: "the evening task is today in the local time",
+                due: Some("2025-06-11 01:00"),
+                now: "2025-06-10 20:00",
+                day_start_hour: 0,
+                expected: Due::Today,
+            },
+            Case {
+                name: "yesterday's task before the day start",
+                due: Some("2025-06-09 00:00"),
+                now: "2025-06-10 05:00",
+                day_start_hour: 3,
+                expected: Due::Today,
+            },
+            Case {
+                name: "tomorrow",
+                due: Some("2025-06-11 00:00"),
+                now: "2025-06-10 12:00",
+                day_start_hour: 0,
+                expected: Due::Future,
+            },
+            Case {
+                name: "the moment after midnight before the day start is yesterday",
+                due: Some("2025-06-10 06:30"),
+                now: "2025-06-10 15:00",
+                day_start_hour: 4,
+                expected: Due::Overdue,
+            },
+            Case {
+                name: "the DST start",
+                due: Some("2025-03-09 14:00"),
+                now: "2025-03-09 08:30",
+                day_start_hour: 0,
+                expected: Due::Today,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                due_group_at(&c.due.map(utc), &utc(c.now), &New_York, c.day_start_hour),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn week_test() {
+        // Monday, Friday, Saturday and Sunday
+        let days = ["2025-06-02", "2025-06-06", "2025-06-07", "2025-06-08"];
+        let expected = [
+            ("2025-06-02", "2025-06-09", "2025-06-07"),
+            ("2025-06-02", "2025-06-09", "2025-06-07"),
+            ("2025-06-02", "2025-06-09", "2025-06-07"),
+            ("2025-06-02", "2025-06-09", "2025-06-08"),
+        ];
+        for (d, (start, next, weekend_day)) in days.iter().zip(expected) {
+            let d = date(d);
+            assert_eq!(week_start(d), date(start), "Test '{d}' was failed");
+            assert_eq!(next_week_start(d), date(next), "Test '{d}' was failed");
+            assert_eq!(weekend(d), date(weekend_day), "Test '{d}' was failed");
+        }
+    }
+
+    #[test]
+    fn set_day_start_hour_test() {
+        assert!(set_day_start_hour(24).is_err());
+    }
+
+    #[test]
+    fn relative_due_test() {
+        struct Case<'a> {
+            name: &'a str,
+            due: &'a str,
+            now: &'a str,
+            day_start_hour: u32,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "now",
+                due: "2025-01-08 10:30",
+                now: "2025-01-08 10:30",
+                day_start_hour: 0,
+                expected: "now",
+            },
+            Case {
+                name: "minutes left",
+                due: "2025-01-08 10:30",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "in 25m",
+            },
+            Case {
+                name: "hours left",
+                due: "2025-01-08 12:30",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "in 2h 25m",
+            },
+            Case {
+                name: "days left",
+                due: "2025-01-11 12:30",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "in 3d",
+            },
+            Case {
+                name: "hours overdue",
+                due: "2025-01-08 07:30",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "2h overdue",
+            },
+            Case {
+                name: "days overdue",
+                due: "2025-01-05 07:30",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "3d overdue",
+            },
+            Case {
+                name: "date today",
+                due: "2025-01-08 00:00",
+                now: "2025-01-08 23:05",
+                day_start_hour: 0,
+                expected: "today",
+            },
+            Case {
+                name: "date tomorrow",
+                due: "2025-01-09 00:00",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "tomorrow",
+            },
+            Case {
+                name: "date is today before the day start",
+                due: "2025-01-08 00:00",
+                now: "2025-01-09 02:05",
+                day_start_hour: 3,
+                expected: "today",
+            },
+            Case {
+                name: "date overdue",
+                due: "2025-01-05 00:00",
+                now: "2025-01-08 10:05",
+                day_start_hour: 0,
+                expected: "3d overdue",
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                relative_due(&utc(c.due), &utc(c.now), &chrono::Utc, c.day_start_hour),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn due_alarm_test() {
+        struct Case<'a> {
+            name: &'a str,
+            due: &'a str,
+            expected: DueAlarm,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "date without time",
+                due: "2025-01-08 00:00",
+                expected: DueAlarm::None,
+            },
+            Case {
+                name: "overdue",
+                due: "2025-01-08 09:59",
+                expected: DueAlarm::None,
+            },
+            Case {
+                name: "now",
+                due: "2025-01-08 10:00",
+                expected: DueAlarm::Imminent,
+            },
+            Case {
+                name: "in 15 minutes",
+                due: "2025-01-08 10:15",
+                expected: DueAlarm::Imminent,
+            },
+            Case {
+                name: "in an hour",
+                due: "2025-01-08 11:00",
+                expected: DueAlarm::Soon,
+            },
+            Case {
+                name: "later",
+                due: "2025-01-08 11:01",
+                expected: DueAlarm::None,
+            },
+        ];
+
+        let now = utc("2025-01-08 10:00");
+        for c in CASES {
+            assert_eq!(due_alarm(&utc(c.due), &now), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn ago_test() {
+        struct Case<'a> {
+            name: &'a str,
+            seconds: i64,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "in the future",
+                seconds: -100,
+                expected: "now",
+            },
+            Case {
+                name: "seconds",
+                seconds: 59,
+                expected: "now",
+            },
+            Case {
+                name: "minutes",
+                seconds: 5 * 60 + 10,
+                expected: "5m",
+            },
+            Case {
+                name: "hours",
+                seconds: 3 * 3600,
+                expected: "3h",
+            },
+            Case {
+                name: "days",
+                seconds: 29 * 86400,
+                expected: "29d",
+            },
+            Case {
+                name: "months",
+                seconds: 65 * 86400,
+                expected: "2mo",
+            },
+            Case {
+                name: "years",
+                seconds: 800 * 86400,
+                expected: "2y",
+            },
+        ];
+
+        let now = chrono::Utc::now();
+        for c in CASES {
+            let dt = now - chrono::Duration::seconds(c.seconds);
+            assert_eq!(ago(&dt, &now), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+}
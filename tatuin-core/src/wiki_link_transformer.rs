@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::LazyLock;
+
+use regex::{Captures, Regex};
+
+use crate::RichStringTransformerTrait;
+
+static RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[\[([^\[\]|#]*)(?:#([^\[\]|]*))?(?:\|([^\[\]]*))?\]\]").unwrap());
+
+// Shows the wiki links ([[Note#Heading|Alias]]) as their aliases or the note names.
+// The providers that can resolve the links into urls do it before, so only the unresolved links are left here.
+#[derive(Debug)]
+pub struct WikiLinkTransformer {}
+
+impl RichStringTransformerTrait for WikiLinkTransformer {
+    fn transform(&self, s: &str) -> String {
+        RE.replace_all(s, |cap: &Captures| {
+            if let Some(alias) = cap.get(3).filter(|m| !m.is_empty()) {
+                return alias.as_str().to_string();
+            }
+
+            let note = cap.get(1).map(|m| m.as_str()).unwrap_or_default();
+            match cap.get(2).filter(|m| !m.is_empty()) {
+                Some(heading) if note.is_empty() => heading.as_str().to_string(),
+                Some(heading) => format!("{note} > {}", heading.as_str()),
+                None => note.to_string(),
+            }
+        })
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{RichStringTransformerTrait, WikiLinkTransformer};
+
+    #[test]
+    fn transform_test() {
+        struct Case<'a> {
+            name: &'a str,
+            text: &'a str,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no links",
+                text: "Buy milk [x]",
+                expected: "Buy milk [x]",
+            },
+            Case {
+                name: "note",
+                text: "Read [[Books]] list",
+                expected: "Read Books list",
+            },
+            Case {
+                name: "alias",
+                text: "Call [[People/John Smith|John]]",
+                expected: "Call John",
+            },
+            Case {
+                name: "heading",
+                text: "See [[Project#Goals]]",
+                expected: "See Project > Goals",
+            },
+            Case {
+                name: "heading of the same note",
+                text: "See [[#Goals]]",
+                expected: "See Goals",
+            },
+            Case {
+                name: "heading with alias",
+                text: "[[Project#Goals|goals]] and [[Plan]]",
+                expected: "goals and Plan",
+            },
+            Case {
+                name: "markdown link is kept",
+                text: "[Alias](obsidian://open?file=Note)",
+                expected: "[Alias](obsidian://open?file=Note)",
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                WikiLinkTransformer {}.transform(c.text),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
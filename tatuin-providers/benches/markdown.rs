@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: MIT
+
+// The markdown notes are parsed on every reload of the Obsidian and the markdown directory providers,
+// run it with `cargo bench`
+
+use std::path::Path;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use tatuin_core::{
+    filter::Filter,
+    provider::{ProjectProviderTrait, TaskProviderTrait},
+    task,
+};
+use tatuin_providers::{
+    config::Config,
+    markdown_dir::{Conventions, Provider, md_file::File},
+};
+
+const TASKS_COUNT: usize = 10_000;
+const FILES_COUNT: usize = 100;
+
+fn note(file: usize, count: usize) -> String {
+    let mut content = format!("---\nproject: Project {file}\n---\n# Note {file}\n\nSome text with a [[link]].\n\n");
+    for i in 0..count {
+        let state = if i % 5 == 0 { 'x' } else { ' ' };
+        content.push_str(&format!(
+            "- [{state}] Task {i} with [the link](https://example.com/{i}) #tag{} 📅 2025-01-{:02}\n",
+            i % 20,
+            i % 28 + 1
+        ));
+        if i % 4 == 0 {
+            content.push_str(&format!("  The description of the task {i}\n  - [ ] Subtask {i}\n"));
+        }
+        if i % 50 == 0 {
+            content.push_str("  ```\n  - [ ] not a task\n  ```\n");
+        }
+    }
+    content
+}
+
+fn provider(root: &Path) -> Provider {
+    Provider::new(Config::new("tatuin_bench", "markdown"), root, Conventions::default())
+}
+
+fn markdown(c: &mut Criterion) {
+    let content = note(0, TASKS_COUNT);
+    let file = File::new(Path::new("note.md"));
+    c.bench_function(&format!("parse {TASKS_COUNT} tasks of one note"), |b| {
+        b.iter(|| file.tasks_from_content(&content).unwrap())
+    });
+
+    let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+    let root = temp_dir.path();
+    for f in 0..FILES_COUNT {
+        std::fs::write(root.join(format!("note{f}.md")), note(f, TASKS_COUNT / FILES_COUNT)).unwrap();
+    }
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let filter = Filter::full_filter();
+
+    c.bench_function(&format!("load {TASKS_COUNT} tasks from {FILES_COUNT} notes"), |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let mut p = provider(root);
+                TaskProviderTrait::list(&mut p, None, &filter).await.unwrap()
+            })
+        })
+    });
+
+    c.bench_function(&format!("load {FILES_COUNT} projects"), |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let mut p = provider(root);
+                ProjectProviderTrait::list(&mut p).await.unwrap()
+            })
+        })
+    });
+
+    let tasks = rt.block_on(async {
+        let mut p = provider(root);
+        TaskProviderTrait::list(&mut p, None, &filter).await.unwrap()
+    });
+    c.bench_function(&format!("links of {} markdown tasks", tasks.len()), |b| {
+        b.iter(|| tasks.iter().map(|t| task::links(t.as_ref()).len()).sum::<usize>())
+    });
+}
+
+criterion_group! {
+    name = benches;
+    // every iteration parses all the notes, so the default sample is too long
+    config = Criterion::default().sample_size(10);
+    targets = markdown
+}
+criterion_main!(benches);
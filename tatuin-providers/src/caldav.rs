@@ -3,21 +3,23 @@
 mod client;
 mod fake_project;
 
-use std::error::Error;
+use std::{error::Error, sync::Arc};
 
 use async_trait::async_trait;
-use reqwest_dav::Auth;
 
 use super::ical::Task;
 use crate::config::Config as ProviderConfig;
 use client::{Client, Config};
 use strum::{Display, EnumString};
 use tatuin_core::{
-    StringError, filter,
+    filter,
+    fs::SharedFileSystem,
+    http::SharedHttpClient,
+    intern::intern,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
-    task::{Priority, State, Task as TaskTrait},
-    task_patch::{DatePatchItem, PatchError, TaskPatch},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, Priority, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
 };
 
 pub const PROVIDER_NAME: &str = "CalDav";
@@ -27,6 +29,7 @@ pub struct Provider {
 
     c: Client,
     tasks: Vec<Task>,
+    last_sync: Option<DateTimeUtc>,
 }
 
 impl Provider {
@@ -44,12 +47,24 @@ impl Provider {
             auth_type: auth_type.unwrap_or(AuthType::Basic),
         });
         c.set_cache_folder(&cfg.cache_path()?);
+        c.set_cipher(cfg.cipher());
         Ok(Self {
             cfg,
             c,
             tasks: Vec::new(),
+            last_sync: None,
         })
     }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.c = self.c.with_http_client(http);
+        self
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.c = self.c.with_file_system(fs);
+        self
+    }
 }
 
 impl std::fmt::Debug for Provider {
@@ -60,7 +75,7 @@ impl std::fmt::Debug for Provider {
 
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
         Ok(vec![Box::new(fake_project::Project::default())])
     }
 }
@@ -72,8 +87,9 @@ impl TaskProviderTrait for Provider {
         &mut self,
         _project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
         if self.tasks.is_empty() {
+            let name = intern(self.cfg.name().as_str());
             self.c.download().await?;
             self.tasks = self
                 .c
@@ -83,28 +99,32 @@ impl TaskProviderTrait for Provider {
                 .filter(|t| f.accept(*t))
                 .map(|t| {
                     let mut task = t.clone();
-                    task.set_provider(self.cfg.name().as_str());
+                    task.set_provider(name.clone());
                     task
                 })
                 .collect();
+            self.last_sync = Some(chrono::Utc::now());
         }
 
         return Ok(self.tasks.iter().map(|t| t.clone_boxed()).collect());
     }
 
-    async fn create(&mut self, _project_id: &str, tp: &TaskPatch) -> Result<(), StringError> {
+    async fn create(&mut self, _project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
         let t = Task {
-            provider: PROVIDER_NAME.to_string(),
+            provider: intern(PROVIDER_NAME),
             properties: Vec::new(),
             name: tp.name.value().unwrap(),
             description: tp.description.value(),
             due: tp.due.value().unwrap_or(DatePatchItem::NoDate).into(),
             priority: tp.priority.value().unwrap_or(Priority::Normal).into(),
+            categories: tp.labels.value().unwrap_or_default(),
+            parent: tp.parent.value(),
+            url: tp.url.value(),
             ..Task::default()
         };
         self.c.create_or_update(&t).await.map_err(|e| {
             tracing::error!(target:"caldav_provider",  error=?e, "Create a task");
-            StringError::new(e.to_string().as_str())
+            e.into()
         })
     }
 
@@ -126,6 +146,12 @@ impl TaskProviderTrait for Provider {
                     if let Some(p) = p.priority.value() {
                         t.priority = p.into();
                     }
+                    if p.labels.is_set() {
+                        t.categories = p.labels.value().unwrap_or_default();
+                    }
+                    if p.parent.is_set() {
+                        t.parent = p.parent.value();
+                    }
                     if let Some(s) = p.state.value() {
                         t.status = s.into();
                         if s == State::Completed {
@@ -137,27 +163,27 @@ impl TaskProviderTrait for Provider {
                     let r = self.c.create_or_update(&t).await.map_err(|e| {
                         tracing::error!(target:"caldav_provider",  error=?e, "Patch the task");
                         PatchError {
-                            task: t.clone_boxed(),
+                            task: Arc::new(t.clone()),
                             error: e.to_string(),
+                            kind: PatchErrorKind::Other,
                         }
                     });
                     if let Err(e) = r {
                         errors.push(e);
                     }
                 }
-                None => panic!(
-                    "Wrong casting the task id=`{}` name=`{}` to obsidian!",
-                    task.id(),
-                    task.name().display(),
-                ),
+                None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
             };
         }
 
         errors
     }
 
-    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), StringError> {
-        let t = t.as_any().downcast_ref::<Task>().expect("Wrong casting");
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let t = t
+            .as_any()
+            .downcast_ref::<Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
         self.c.delete(t).await.map_err(|e| {
             tracing::error!(error=?e, name=?t.name(), id=t.id(), "Delete the task");
             e.into()
@@ -179,8 +205,30 @@ impl ProviderTrait for Provider {
         self.tasks.clear();
     }
 
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: self.cfg.cache_path().ok(),
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.c.clear_cache().await?;
+        self.tasks.clear();
+        Ok(())
+    }
+
     fn capabilities(&self) -> Capabilities {
-        Capabilities { create_task: true }
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: true,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: true,
+        }
     }
 }
 
@@ -191,15 +239,6 @@ pub enum AuthType {
     Digest,
 }
 
-impl AuthType {
-    fn to_dav_auth(self, login: &str, password: &str) -> Auth {
-        match self {
-            AuthType::Basic => Auth::Basic(login.to_string(), password.to_string()),
-            AuthType::Digest => Auth::Digest(login.to_string(), password.to_string()),
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use std::str::FromStr;
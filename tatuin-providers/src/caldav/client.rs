@@ -1,27 +1,28 @@
 // SPDX-License-Identifier: MIT
 
-use chrono::TimeDelta;
+use chrono::{DateTime, TimeDelta, Utc};
+use digest_auth::{AuthContext, HttpMethod, WwwAuthenticateHeader};
 use ical::property::Property;
 use itertools::Itertools;
-use reqwest::{
-    StatusCode,
-    header::{HeaderMap, HeaderValue},
-};
-use reqwest_dav::{Client as WebDavClient, ClientBuilder, Depth, list_cmd::ListEntity, re_exports::reqwest};
 use serde::{Deserialize, Serialize};
 use std::{
     error::Error,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use tatuin_core::{
     StringError,
+    crypto::{Cipher, decrypt_content, encrypt_content},
+    fs::{LocalFileSystem, SharedFileSystem},
+    http::{Method, Request, Response, SharedHttpClient},
+    provider::ProviderError,
     task::{DateTimeUtc, PatchPolicy, Priority, State},
     task_patch::DatePatchItem,
 };
-use tokio::io::AsyncWriteExt;
 
 use crate::{
     caldav::AuthType,
+    http::ReqwestClient,
     ical::{Task, property_to_str},
 };
 
@@ -48,6 +49,89 @@ struct CachedFiles {
     files: Vec<CachedFile>,
 }
 
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<D:propfind xmlns:D="DAV:">
+    <D:allprop/>
+</D:propfind>"#;
+
+// The answer of the PROPFIND request, the namespaces of the elements are dropped by the parser
+#[derive(Deserialize, Debug)]
+struct MultiStatus {
+    #[serde(rename = "response", default)]
+    responses: Vec<PropResponse>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PropResponse {
+    href: String,
+    #[serde(rename = "propstat", default)]
+    prop_stats: Vec<PropStat>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PropStat {
+    status: String,
+    prop: Prop,
+}
+
+#[derive(Deserialize, Debug)]
+struct Prop {
+    #[serde(rename = "getlastmodified")]
+    last_modified: Option<String>,
+    #[serde(rename = "getetag")]
+    etag: Option<String>,
+    #[serde(rename = "resourcetype", default)]
+    resource_type: ResourceType,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct ResourceType {
+    collection: Option<()>,
+}
+
+#[derive(Debug, PartialEq)]
+struct RemoteFile {
+    href: String,
+    last_modified: DateTimeUtc,
+    etag: Option<String>,
+}
+
+// The files of the collection, the collection itself and the subfolders are skipped
+fn remote_files(content: &str) -> Result<Vec<RemoteFile>, Box<dyn Error>> {
+    let status = serde_xml_rs::from_str::<MultiStatus>(content)?;
+    Ok(status
+        .responses
+        .into_iter()
+        .filter_map(|r| {
+            let prop = r.prop_stats.into_iter().find(|p| p.status.contains(" 200 "))?.prop;
+            if prop.resource_type.collection.is_some() {
+                return None;
+            }
+            Some(RemoteFile {
+                href: r.href,
+                // the servers can skip the modification time, so only the etag is compared then
+                last_modified: prop
+                    .last_modified
+                    .and_then(|d| DateTime::parse_from_rfc2822(&d).ok())
+                    .map(|d| d.with_timezone(&Utc))
+                    .unwrap_or_default(),
+                etag: prop.etag,
+            })
+        })
+        .collect())
+}
+
+fn method_name(m: Method) -> &'static str {
+    match m {
+        Method::Get => "GET",
+        Method::Post => "POST",
+        Method::Put => "PUT",
+        Method::Patch => "PATCH",
+        Method::Delete => "DELETE",
+        Method::Propfind => "PROPFIND",
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Debug)]
 enum StorageType {
     Calendar,
@@ -57,8 +141,12 @@ enum StorageType {
 pub struct Client {
     cfg: Config,
     storage_type: Option<StorageType>,
-    c: Option<WebDavClient>,
+    // the challenge of the server, it's taken from the first unauthorized answer
+    digest: Option<WwwAuthenticateHeader>,
     cache_folder: PathBuf,
+    cipher: Option<Cipher>,
+    http: SharedHttpClient,
+    fs: SharedFileSystem,
 }
 
 impl Client {
@@ -66,45 +154,63 @@ impl Client {
         Self {
             cfg,
             storage_type: None,
-            c: None,
+            digest: None,
             cache_folder: tatuin_core::folders::temp_folder(),
+            cipher: None,
+            http: Arc::new(ReqwestClient::default()),
+            fs: LocalFileSystem::shared(),
         }
     }
 
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.fs = fs;
+        self
+    }
+
     pub fn set_cache_folder(&mut self, p: &Path) {
         self.cache_folder = p.to_path_buf()
     }
 
+    pub fn set_cipher(&mut self, cipher: Option<Cipher>) {
+        self.cipher = cipher;
+    }
+
     pub async fn download(&mut self) -> Result<(), Box<dyn Error>> {
-        let url = url::Url::parse(&self.cfg.url)?;
+        let url = self.cfg.url.clone();
         let mut current_cached_files = self.load_cached_files().await;
         let mut new_cached_files = CachedFiles::default();
 
-        tracing::debug!(uri = url.path(), "Get file list");
+        tracing::debug!(url = url, "Get file list");
 
-        let c = self.client()?;
-        let files = c.list(url.path(), Depth::Number(1)).await?;
+        let r = Request::new(Method::Propfind, &url)
+            .with_header("Depth", "1")
+            .with_header("Content-Type", "text/xml; charset=\"utf-8\"")
+            .with_body(PROPFIND_BODY.as_bytes());
+        let files = remote_files(&self.send(r).await?.error_for_status(&url)?.text())?;
 
         for f in files {
-            if let ListEntity::File(f) = f {
-                if let Some(pos) = current_cached_files.files.iter().position(|cf| cf.href == f.href) {
-                    let cached_file = current_cached_files.files.remove(pos);
-                    if cached_file.etag == f.tag && cached_file.last_modified == f.last_modified {
-                        tracing::debug!(href = f.href, "The file wasn't changed");
-                        new_cached_files.files.push(cached_file);
-                        continue;
-                    }
-
-                    tracing::debug!(href = f.href, "The file was changed");
+            if let Some(pos) = current_cached_files.files.iter().position(|cf| cf.href == f.href) {
+                let cached_file = current_cached_files.files.remove(pos);
+                if cached_file.etag == f.etag && cached_file.last_modified == f.last_modified {
+                    tracing::debug!(href = f.href, "The file wasn't changed");
+                    new_cached_files.files.push(cached_file);
+                    continue;
                 }
 
-                new_cached_files.files.push(CachedFile {
-                    file_name: self.download_and_save_file(f.href.as_str()).await?,
-                    href: f.href.to_string(),
-                    last_modified: f.last_modified,
-                    etag: f.tag,
-                });
+                tracing::debug!(href = f.href, "The file was changed");
             }
+
+            new_cached_files.files.push(CachedFile {
+                file_name: self.download_and_save_file(f.href.as_str()).await?,
+                href: f.href,
+                last_modified: f.last_modified,
+                etag: f.etag,
+            });
         }
 
         self.clean_missed_files(&current_cached_files.files).await;
@@ -146,30 +252,29 @@ impl Client {
 
     pub async fn create_or_update(&mut self, t: &Task) -> Result<(), Box<dyn Error>> {
         if let Some(st) = self.storage_type.clone() {
-            let r = self.send_create_or_update_request(&st, t).await?;
-            let st = r.status();
-            if st != StatusCode::CREATED && st != StatusCode::NO_CONTENT {
-                let response_text = r.text().await?;
-                tracing::error!(target:"caldav_client", response_text=?response_text, "Send create or update request");
-                return Err(StringError::new(format!("Wrong response status {st}").as_str()).into());
+            let (url, r) = self.send_create_or_update_request(&st, t).await?;
+            if r.status != 201 && r.status != 204 {
+                tracing::error!(target:"caldav_client", response_text=?r.text(), "Send create or update request");
+                return Err(ProviderError::from_http_status(
+                    r.status,
+                    None,
+                    format!("Wrong response status {}", r.status).as_str(),
+                )
+                .into());
             } else {
-                return r
-                    .error_for_status()
-                    .map(|_| ())
-                    .map_err(|e| Box::new(e) as Box<dyn Error>);
+                return r.error_for_status(&url).map(|_| ()).map_err(Box::<dyn Error>::from);
             }
         }
 
         tracing::info!(target:"caldav_client", "Try to detect storage type");
 
         // try to detect storage type
-        let mut r = self.send_create_or_update_request(&StorageType::Calendar, t).await?;
+        let (mut url, mut r) = self.send_create_or_update_request(&StorageType::Calendar, t).await?;
 
-        if r.status() == StatusCode::FORBIDDEN {
-            let response_text = r.text().await;
-            tracing::error!(target:"caldav_client", text=?response_text, "Wrong storage type");
+        if r.status == 403 {
+            tracing::error!(target:"caldav_client", text=?r.text(), "Wrong storage type");
             // wrong storage type
-            r = self.send_create_or_update_request(&StorageType::Calendar, t).await?;
+            (url, r) = self.send_create_or_update_request(&StorageType::Todo, t).await?;
             self.storage_type = Some(StorageType::Todo);
         } else {
             self.storage_type = Some(StorageType::Calendar);
@@ -177,8 +282,7 @@ impl Client {
 
         tracing::info!(target:"caldav_client", storage_type=?self.storage_type.as_ref().unwrap(), "The storage type has been detected");
 
-        let r = r.error_for_status();
-        r.map(|_| ()).map_err(|e| Box::new(e) as Box<dyn Error>)
+        r.error_for_status(&url).map(|_| ()).map_err(Box::<dyn Error>::from)
     }
 
     pub async fn delete(&mut self, t: &Task) -> Result<(), Box<dyn Error>> {
@@ -187,30 +291,62 @@ impl Client {
 }
 
 impl Client {
-    fn client(&mut self) -> Result<&WebDavClient, Box<dyn Error>> {
-        if self.c.is_none() {
-            let mut u = url::Url::parse(&self.cfg.url)?;
-            u.set_path("/");
-            tracing::debug!(
-                url = u.to_string(),
-                login = self.cfg.login,
-                password = self.cfg.password,
-                "Connect to the server"
-            );
-            self.c = Some(
-                ClientBuilder::new()
-                    .set_host(u.to_string())
-                    .set_auth(self.cfg.auth_type.to_dav_auth(&self.cfg.login, &self.cfg.password))
-                    .build()?,
-            );
+    pub async fn clear_cache(&self) -> Result<(), Box<dyn Error>> {
+        self.clean_missed_files(&self.load_cached_files().await.files).await;
+        let index = self.cache_folder.join(INDEX_FILE_NAME);
+        if self.fs.exists(&index).await {
+            self.fs.remove(&index).await?;
+        }
+        Ok(())
+    }
+
+    // The digest auth needs the challenge of the server, so the request is repeated with it once it's unauthorized
+    async fn send(&mut self, r: Request) -> Result<Response, ProviderError> {
+        let request = self.authorize(r.clone())?;
+        let response = self.http.send(request).await?;
+        if response.status != 401 || self.cfg.auth_type != AuthType::Digest {
+            return Ok(response);
         }
 
-        Ok(self.c.as_ref().unwrap())
+        let Some(challenge) = response.header("www-authenticate") else {
+            return Ok(response);
+        };
+        self.digest = Some(digest_auth::parse(challenge).map_err(|e| ProviderError::Auth(e.to_string()))?);
+        let request = self.authorize(r)?;
+        self.http.send(request).await
+    }
+
+    fn authorize(&mut self, r: Request) -> Result<Request, ProviderError> {
+        match self.cfg.auth_type {
+            AuthType::Basic => Ok(r.with_basic_auth(&self.cfg.login, &self.cfg.password)),
+            AuthType::Digest => {
+                let Some(digest) = self.digest.as_mut() else {
+                    return Ok(r);
+                };
+                let uri = url::Url::parse(&r.url)
+                    .map(|u| u.path().to_string())
+                    .unwrap_or_default();
+                let mut context = AuthContext::new(self.cfg.login.as_str(), self.cfg.password.as_str(), uri);
+                context.method = HttpMethod::from(method_name(r.method));
+                let header = digest
+                    .respond(&context)
+                    .map_err(|e| ProviderError::Auth(e.to_string()))?;
+                Ok(r.with_header("Authorization", &header.to_header_string()))
+            }
+        }
+    }
+
+    // The hrefs are the absolute paths on the server
+    fn url(&self, href: &str) -> Result<String, Box<dyn Error>> {
+        Ok(url::Url::parse(&self.cfg.url)?.join(href)?.to_string())
     }
 
     async fn load_cached_files(&self) -> CachedFiles {
-        if let Ok(s) = tokio::fs::read_to_string(self.cache_folder.join(INDEX_FILE_NAME)).await {
-            match toml::from_str(s.as_str()) {
+        if let Ok(data) = self.fs.read(&self.cache_folder.join(INDEX_FILE_NAME)).await {
+            let files = decrypt_content(self.cipher.as_ref(), data)
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|data| Ok(toml::from_slice(&data)?));
+            match files {
                 Ok(files) => return files,
                 Err(e) => tracing::error!(error=?e, "Load cached files"),
             }
@@ -221,36 +357,37 @@ impl Client {
 
     async fn save_cached_files(&self, files: &CachedFiles) -> Result<(), Box<dyn Error>> {
         let s = toml::to_string(files)?;
-        tokio::fs::write(self.cache_folder.join(INDEX_FILE_NAME), s).await?;
+        let data = encrypt_content(self.cipher.as_ref(), s.into_bytes())?;
+        self.fs.write(&self.cache_folder.join(INDEX_FILE_NAME), &data).await?;
         Ok(())
     }
 
     async fn clean_missed_files(&self, files: &[CachedFile]) {
         for f in files {
             tracing::debug!(href = f.href, file_name = f.file_name, "Remove cached file");
-            if let Err(e) = tokio::fs::remove_file(self.cache_folder.join(f.file_name.as_str())).await {
+            if let Err(e) = self.fs.remove(&self.cache_folder.join(f.file_name.as_str())).await {
                 tracing::error!(error=?e, path=?self.cache_folder, file=f.file_name, "Remove cached file");
             }
         }
     }
 
     async fn download_and_save_file(&mut self, href: &str) -> Result<String, Box<dyn Error>> {
-        let c = self.client()?;
-        let mut r = c.get(href).await?;
-
         let file_name = file_name_from_href(href)?;
         tracing::debug!(href = href, file_name = file_name, "Download the file");
 
-        let mut f = tokio::fs::File::create(self.cache_folder.join(file_name.as_str())).await?;
-        while let Some(chunk) = r.chunk().await? {
-            f.write_all(&chunk).await?;
-        }
+        let url = self.url(href)?;
+        let r = self.send(Request::get(&url)).await?.error_for_status(&url)?;
+        let content = encrypt_content(self.cipher.as_ref(), r.body)?;
+        self.fs
+            .write(&self.cache_folder.join(file_name.as_str()), &content)
+            .await?;
 
         Ok(file_name)
     }
 
     async fn parse_calendar(&self, file_name: &str) -> Result<Vec<Task>, Box<dyn Error>> {
-        crate::ical::parse_calendar(&self.cache_folder.join(file_name)).await
+        let data = self.fs.read(&self.cache_folder.join(file_name)).await?;
+        crate::ical::parse_calendar(data, self.cipher.as_ref()).await
     }
 
     fn create_or_update_request_body(&self, storage_type: &StorageType, t: &Task) -> String {
@@ -268,13 +405,19 @@ impl Client {
         };
 
         let properties: Vec<Property> = (&task).into();
+        let alarms = task.alarms.iter().map(|a| {
+            format!(
+                "BEGIN:VALARM\n{}\nEND:VALARM",
+                a.properties.iter().map(property_to_str).join("\n")
+            )
+        });
         format!(
             r#"BEGIN:VCALENDAR
 BEGIN:{task_type}
 {}
 END:{task_type}
 END:VCALENDAR"#,
-            properties.iter().map(property_to_str).join("\n")
+            properties.iter().map(property_to_str).chain(alarms).join("\n")
         )
     }
 
@@ -293,30 +436,20 @@ END:VCALENDAR"#,
         &mut self,
         storage_type: &StorageType,
         t: &Task,
-    ) -> Result<reqwest::Response, Box<dyn Error>> {
+    ) -> Result<(String, Response), Box<dyn Error>> {
         let body = self.create_or_update_request_body(storage_type, t);
         let href = self.task_href(t)?;
 
         tracing::debug!(body=?body, task=?&t, href=href, "Create or update a task");
 
-        let c = self.client()?;
-        c.start_request(reqwest::Method::PUT, &href)
-            .await?
-            .headers({
-                let mut map = HeaderMap::new();
-                map.insert(
-                    reqwest::header::CONTENT_TYPE,
-                    HeaderValue::from_str("text/calendar; charset=utf-8")?,
-                );
-                if let Some(etag) = &t.etag {
-                    map.insert(reqwest::header::IF_MATCH, HeaderValue::from_str(etag)?);
-                }
-                map
-            })
-            .body(body)
-            .send()
-            .await
-            .map_err(|e| Box::new(e) as Box<dyn Error>)
+        let url = self.url(&href)?;
+        let mut r = Request::put(&url)
+            .with_header("Content-Type", "text/calendar; charset=utf-8")
+            .with_body(body.as_bytes());
+        if let Some(etag) = &t.etag {
+            r = r.with_header("If-Match", etag);
+        }
+        Ok((url, self.send(r).await?))
     }
 
     async fn send_delete_request(&mut self, t: &Task) -> Result<(), Box<dyn Error>> {
@@ -325,19 +458,12 @@ END:VCALENDAR"#,
 
         tracing::debug!(etag=?etag, task=?&t, href=href, "Delete the task");
 
-        let c = self.client()?;
-        c.start_request(reqwest::Method::DELETE, &href)
-            .await?
-            .headers({
-                let mut map = HeaderMap::new();
-                map.insert(reqwest::header::IF_MATCH, HeaderValue::from_str(etag.as_str())?);
-                map
-            })
-            .send()
+        let url = self.url(&href)?;
+        self.send(Request::delete(&url).with_header("If-Match", etag))
             .await?
-            .error_for_status()
+            .error_for_status(&url)
             .map(|_| ())
-            .map_err(|e| Box::new(e) as Box<dyn Error>)
+            .map_err(Box::<dyn Error>::from)
     }
 }
 
@@ -353,7 +479,11 @@ fn file_name_from_href(href: &str) -> Result<String, StringError> {
 
 #[cfg(test)]
 mod test {
-    use super::file_name_from_href;
+    use super::*;
+    use tatuin_core::{
+        fs::MemoryFileSystem,
+        http::{MockHttpClient, Response},
+    };
 
     #[test]
     fn file_name_from_href_test() {
@@ -397,4 +527,140 @@ mod test {
             }
         }
     }
+
+    const MULTISTATUS: &str = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:cs="http://calendarserver.org/ns/">
+    <d:response>
+        <d:href>/dav/tasks/</d:href>
+        <d:propstat>
+            <d:prop><d:resourcetype><d:collection/></d:resourcetype></d:prop>
+            <d:status>HTTP/1.1 200 OK</d:status>
+        </d:propstat>
+    </d:response>
+    <d:response>
+        <d:href>/dav/tasks/1.ics</d:href>
+        <d:propstat>
+            <d:prop>
+                <d:getlastmodified>Wed, 21 Oct 2015 07:28:00 GMT</d:getlastmodified>
+                <d:getetag>"etag1"</d:getetag>
+                <d:resourcetype/>
+            </d:prop>
+            <d:status>HTTP/1.1 200 OK</d:status>
+        </d:propstat>
+        <d:propstat>
+            <d:prop><cs:getctag/></d:prop>
+            <d:status>HTTP/1.1 404 Not Found</d:status>
+        </d:propstat>
+    </d:response>
+</d:multistatus>"#;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:1
+SUMMARY:Task
+END:VTODO
+END:VCALENDAR
+";
+
+    fn config(auth_type: AuthType) -> Config {
+        Config {
+            url: "https://dav.example.com/dav/tasks/".to_string(),
+            login: "login".to_string(),
+            password: "password".to_string(),
+            auth_type,
+        }
+    }
+
+    #[test]
+    fn remote_files_test() {
+        assert_eq!(
+            remote_files(MULTISTATUS).unwrap(),
+            vec![RemoteFile {
+                href: "/dav/tasks/1.ics".to_string(),
+                last_modified: DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+                etag: Some("\"etag1\"".to_string()),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn download_test() {
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(
+                    Method::Propfind,
+                    "https://dav.example.com/dav/tasks/",
+                    Response::new(207, MULTISTATUS.as_bytes()),
+                )
+                .with_response(
+                    Method::Get,
+                    "https://dav.example.com/dav/tasks/1.ics",
+                    Response::new(200, CALENDAR.as_bytes()),
+                ),
+        );
+        let fs = Arc::new(MemoryFileSystem::new());
+        let mut c = Client::new(config(AuthType::Basic))
+            .with_http_client(http.clone())
+            .with_file_system(fs.clone());
+        c.set_cache_folder(Path::new("/cache"));
+
+        c.download().await.unwrap();
+        assert_eq!(fs.content(Path::new("/cache/1.ics")).unwrap(), CALENDAR.as_bytes());
+        let tasks = c.parse_calendars().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].href, "/dav/tasks/1.ics");
+
+        // the unchanged file isn't downloaded again
+        c.download().await.unwrap();
+        let requests = http.requests();
+        assert_eq!(
+            requests.iter().map(|r| r.method).collect::<Vec<_>>(),
+            vec![Method::Propfind, Method::Get, Method::Propfind]
+        );
+        assert_eq!(
+            requests[0].headers,
+            vec![
+                ("Depth".to_string(), "1".to_string()),
+                ("Content-Type".to_string(), "text/xml; charset=\"utf-8\"".to_string()),
+                ("Authorization".to_string(), "Basic bG9naW46cGFzc3dvcmQ=".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn digest_auth_test() {
+        let http = Arc::new(
+            MockHttpClient::new().with_response(
+                Method::Propfind,
+                "https://dav.example.com/dav/tasks/",
+                Response::new(401, b"").with_header(
+                    "WWW-Authenticate",
+                    r#"Digest realm="dav", qop="auth", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+                ),
+            ),
+        );
+        let mut c = Client::new(config(AuthType::Digest))
+            .with_http_client(http.clone())
+            .with_file_system(Arc::new(MemoryFileSystem::new()));
+
+        assert!(c.download().await.is_err());
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        let authorization = |r: &Request| {
+            r.headers
+                .iter()
+                .find(|(name, _)| name == "Authorization")
+                .map(|(_, value)| value.clone())
+        };
+        assert!(authorization(&requests[0]).is_none());
+        assert!(
+            authorization(&requests[1])
+                .is_some_and(|h| h.starts_with("Digest username=\"login\"") && h.contains("uri=\"/dav/tasks/\""))
+        );
+    }
 }
@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT
+
+pub mod matrix;
+pub mod slack;
+
+use async_trait::async_trait;
+use tatuin_core::{StringError, http::check_header_value};
+
+use crate::{config::Config, id_store::IdStore};
+
+const CAPTURED_FILE_NAME: &str = "captured.json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub id: String,
+    pub text: String,
+    pub permalink: String,
+}
+
+// The token comes from the settings, so it can contain the characters that aren't allowed in the header
+fn authorization(token: &str) -> Result<String, StringError> {
+    let value = format!("Bearer {token}");
+    check_header_value("Authorization", &value).map_err(|e| StringError::new(format!("Wrong token: {e}").as_str()))?;
+    Ok(value)
+}
+
+#[async_trait]
+pub trait SourceTrait: Send + Sync {
+    // The latest messages that have the reaction
+    async fn reacted_messages(&mut self) -> Result<Vec<Message>, StringError>;
+}
+
+pub struct Bridge {
+    cfg: Config,
+    source: Box<dyn SourceTrait>,
+    captured: Option<IdStore>,
+}
+
+impl Bridge {
+    pub fn new(cfg: Config, source: Box<dyn SourceTrait>) -> Self {
+        Self {
+            cfg,
+            source,
+            captured: None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn captured(&mut self) -> Result<&mut IdStore, StringError> {
+        if self.captured.is_none() {
+            self.captured = Some(IdStore::new(self.cfg.cache_path()?.join(CAPTURED_FILE_NAME)));
+        }
+        Ok(self.captured.as_mut().unwrap())
+    }
+
+    // Returns the messages that haven't been captured yet
+    pub async fn new_messages(&mut self) -> Result<Vec<Message>, StringError> {
+        let mut result = Vec::new();
+        for m in self.source.reacted_messages().await? {
+            if !self.captured()?.contains(&m.id)? {
+                result.push(m);
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn mark_captured(&mut self, m: &Message) -> Result<(), StringError> {
+        self.captured()?.insert(&m.id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct FakeSource {
+        messages: Vec<Message>,
+    }
+
+    #[async_trait]
+    impl SourceTrait for FakeSource {
+        async fn reacted_messages(&mut self) -> Result<Vec<Message>, StringError> {
+            Ok(self.messages.clone())
+        }
+    }
+
+    fn message(id: &str) -> Message {
+        Message {
+            id: id.to_string(),
+            text: format!("text {id}"),
+            permalink: format!("https://chat/{id}"),
+        }
+    }
+
+    fn bridge(cache_path: &std::path::Path) -> Bridge {
+        let mut cfg = Config::new("test_app", "test_name");
+        cfg.cache_path = cache_path.to_path_buf();
+        Bridge::new(
+            cfg,
+            Box::new(FakeSource {
+                messages: vec![message("1"), message("2")],
+            }),
+        )
+    }
+
+    #[test]
+    fn authorization_test() {
+        assert_eq!(authorization("token").unwrap(), "Bearer token");
+        assert!(authorization("token\n").is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn captured_messages_are_skipped() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+
+        let mut b = bridge(temp_dir.path());
+        let messages = b.new_messages().await.unwrap();
+        assert_eq!(messages.len(), 2);
+        b.mark_captured(&messages[0]).unwrap();
+        assert_eq!(b.new_messages().await.unwrap(), vec![message("2")]);
+
+        // the captured messages are stored between runs
+        let mut b = bridge(temp_dir.path());
+        assert_eq!(b.new_messages().await.unwrap(), vec![message("2")]);
+    }
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashSet, sync::Arc};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tatuin_core::{
+    StringError,
+    http::{Request, SharedHttpClient},
+};
+use urlencoding::encode;
+
+use super::{Message, SourceTrait, authorization};
+use crate::http::ReqwestClient;
+
+pub const SOURCE_NAME: &str = "Matrix";
+
+const MESSAGES_LIMIT: u16 = 200;
+const REACTION_EVENT_TYPE: &str = "m.reaction";
+const MESSAGE_EVENT_TYPE: &str = "m.room.message";
+
+#[derive(Deserialize, Debug)]
+struct Event {
+    event_id: String,
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    content: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct MessagesResponse {
+    #[serde(default)]
+    chunk: Vec<Event>,
+}
+
+pub struct Source {
+    homeserver: String,
+    room: String,
+    emoji: String,
+    authorization: String,
+    http: SharedHttpClient,
+}
+
+impl Source {
+    pub fn new(homeserver: &str, token: &str, room: &str, emoji: &str) -> Result<Self, StringError> {
+        Ok(Self {
+            homeserver: homeserver.trim_end_matches('/').to_string(),
+            room: room.to_string(),
+            emoji: emoji.to_string(),
+            authorization: authorization(token)?,
+            http: Arc::new(ReqwestClient::default()),
+        })
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+fn reacted(events: &[Event], room: &str, emoji: &str) -> Vec<Message> {
+    let reacted_ids = events
+        .iter()
+        .filter(|e| e.event_type == REACTION_EVENT_TYPE)
+        .filter_map(|e| {
+            let relation = &e.content["m.relates_to"];
+            (relation["key"].as_str() == Some(emoji)).then(|| relation["event_id"].as_str())?
+        })
+        .collect::<HashSet<&str>>();
+
+    events
+        .iter()
+        .filter(|e| e.event_type == MESSAGE_EVENT_TYPE && reacted_ids.contains(e.event_id.as_str()))
+        .map(|e| Message {
+            id: e.event_id.clone(),
+            text: e.content["body"].as_str().unwrap_or_default().to_string(),
+            permalink: format!("https://matrix.to/#/{room}/{}", e.event_id),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl SourceTrait for Source {
+    async fn reacted_messages(&mut self) -> Result<Vec<Message>, StringError> {
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/messages?dir=b&limit={MESSAGES_LIMIT}",
+            self.homeserver,
+            encode(&self.room)
+        );
+        let r = self
+            .http
+            .send(Request::get(&url).with_header("Authorization", &self.authorization))
+            .await?;
+        if !r.is_success() {
+            return Err(StringError::new(format!("Matrix API error: {}", r.text()).as_str()));
+        }
+
+        let r = r.json::<MessagesResponse>().map_err(|e| {
+            tracing::error!(target:"matrix_client", url=url, error=?e);
+            StringError::from(e)
+        })?;
+        Ok(reacted(&r.chunk, &self.room, &self.emoji))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[test]
+    fn reacted_messages_test() {
+        let data = r#"{
+            "chunk": [
+                {"event_id": "$r1", "type": "m.reaction", "content": {"m.relates_to": {"rel_type": "m.annotation", "event_id": "$m1", "key": "⭐"}}},
+                {"event_id": "$r2", "type": "m.reaction", "content": {"m.relates_to": {"rel_type": "m.annotation", "event_id": "$m2", "key": "👀"}}},
+                {"event_id": "$m1", "type": "m.room.message", "content": {"msgtype": "m.text", "body": "First"}},
+                {"event_id": "$m2", "type": "m.room.message", "content": {"msgtype": "m.text", "body": "Second"}},
+                {"event_id": "$s1", "type": "m.room.member", "content": {}}
+            ]
+        }"#;
+        let r: MessagesResponse = serde_json::from_str(data).unwrap();
+        assert_eq!(
+            reacted(&r.chunk, "!room:server", "⭐"),
+            vec![Message {
+                id: "$m1".to_string(),
+                text: "First".to_string(),
+                permalink: "https://matrix.to/#/!room:server/$m1".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn api_error_test() {
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Get,
+            "https://matrix.example.com/_matrix/client/v3/rooms/%21room%3Aserver/messages?dir=b&limit=200",
+            Response::new(403, br#"{"errcode": "M_FORBIDDEN"}"#),
+        ));
+        let mut s = Source::new("https://matrix.example.com/", "token", "!room:server", "⭐")
+            .unwrap()
+            .with_http_client(http.clone());
+
+        assert_eq!(
+            s.reacted_messages().await.unwrap_err().to_string(),
+            r#"Matrix API error: {"errcode": "M_FORBIDDEN"}"#
+        );
+        assert_eq!(
+            http.requests()[0].headers,
+            vec![("Authorization".to_string(), "Bearer token".to_string())]
+        );
+    }
+}
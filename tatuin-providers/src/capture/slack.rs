@@ -0,0 +1,195 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use tatuin_core::{
+    StringError,
+    http::{Request, SharedHttpClient},
+};
+
+use super::{Message, SourceTrait, authorization};
+use crate::http::ReqwestClient;
+
+pub const SOURCE_NAME: &str = "Slack";
+
+const BASE_URL: &str = "https://slack.com/api";
+const HISTORY_LIMIT: u16 = 200;
+
+#[derive(Deserialize, Debug)]
+struct Reaction {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct SlackMessage {
+    ts: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    reactions: Vec<Reaction>,
+}
+
+#[derive(Deserialize, Debug)]
+struct HistoryResponse {
+    ok: bool,
+    error: Option<String>,
+    #[serde(default)]
+    messages: Vec<SlackMessage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PermalinkResponse {
+    ok: bool,
+    error: Option<String>,
+    permalink: Option<String>,
+}
+
+fn api_error(error: Option<String>) -> StringError {
+    StringError::new(format!("Slack API error: {}", error.unwrap_or_default()).as_str())
+}
+
+pub struct Source {
+    channel: String,
+    emoji: String,
+    authorization: String,
+    http: SharedHttpClient,
+}
+
+impl Source {
+    pub fn new(token: &str, channel: &str, emoji: &str) -> Result<Self, StringError> {
+        Ok(Self {
+            channel: channel.to_string(),
+            emoji: emoji.trim_matches(':').to_string(),
+            authorization: authorization(token)?,
+            http: Arc::new(ReqwestClient::default()),
+        })
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn get<T>(&self, url: &str) -> Result<T, StringError>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let r = self
+            .http
+            .send(Request::get(url).with_header("Authorization", &self.authorization))
+            .await?;
+        r.json::<T>().map_err(|e| {
+            tracing::error!(target:"slack_client", url=url, error=?e);
+            StringError::from(e)
+        })
+    }
+
+    async fn permalink(&self, ts: &str) -> Result<String, StringError> {
+        let url = format!("{BASE_URL}/chat.getPermalink?channel={}&message_ts={ts}", self.channel);
+        let r = self.get::<PermalinkResponse>(&url).await?;
+        if !r.ok {
+            return Err(api_error(r.error));
+        }
+        Ok(r.permalink.unwrap_or_default())
+    }
+}
+
+fn reacted(messages: Vec<SlackMessage>, emoji: &str) -> Vec<SlackMessage> {
+    messages
+        .into_iter()
+        .filter(|m| m.reactions.iter().any(|r| r.name == emoji))
+        .collect()
+}
+
+#[async_trait]
+impl SourceTrait for Source {
+    async fn reacted_messages(&mut self) -> Result<Vec<Message>, StringError> {
+        let url = format!(
+            "{BASE_URL}/conversations.history?channel={}&limit={HISTORY_LIMIT}",
+            self.channel
+        );
+        let r = self.get::<HistoryResponse>(&url).await?;
+        if !r.ok {
+            return Err(api_error(r.error));
+        }
+
+        let mut result = Vec::new();
+        for m in reacted(r.messages, &self.emoji) {
+            result.push(Message {
+                id: m.ts.clone(),
+                permalink: self.permalink(&m.ts).await?,
+                text: m.text,
+            });
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[test]
+    fn parse_history_test() {
+        let data = r#"{
+            "ok": true,
+            "messages": [
+                {"type": "message", "ts": "1.1", "text": "First", "reactions": [{"name": "star", "count": 1}]},
+                {"type": "message", "ts": "1.2", "text": "Second"},
+                {"type": "message", "ts": "1.3", "text": "Third", "reactions": [{"name": "eyes", "count": 2}]}
+            ],
+            "has_more": false
+        }"#;
+        let r: HistoryResponse = serde_json::from_str(data).unwrap();
+        assert!(r.ok);
+        let messages = reacted(r.messages, "star");
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].ts, "1.1");
+        assert_eq!(messages[0].text, "First");
+
+        let r: HistoryResponse = serde_json::from_str(r#"{"ok": false, "error": "not_in_channel"}"#).unwrap();
+        assert!(!r.ok);
+        assert_eq!(api_error(r.error).to_string(), "Slack API error: not_in_channel");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn reacted_messages_test() {
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(
+                    Method::Get,
+                    "https://slack.com/api/conversations.history?channel=C1&limit=200",
+                    Response::new(
+                        200,
+                        br#"{"ok": true, "messages": [{"ts": "1.1", "text": "First", "reactions": [{"name": "star"}]}]}"#,
+                    ),
+                )
+                .with_response(
+                    Method::Get,
+                    "https://slack.com/api/chat.getPermalink?channel=C1&message_ts=1.1",
+                    Response::new(200, br#"{"ok": true, "permalink": "https://slack.com/archives/C1/p11"}"#),
+                ),
+        );
+        let mut s = Source::new("token", "C1", ":star:")
+            .unwrap()
+            .with_http_client(http.clone());
+
+        assert_eq!(
+            s.reacted_messages().await.unwrap(),
+            vec![Message {
+                id: "1.1".to_string(),
+                text: "First".to_string(),
+                permalink: "https://slack.com/archives/C1/p11".to_string(),
+            }]
+        );
+        assert!(
+            http.requests()
+                .iter()
+                .all(|r| r.headers == vec![("Authorization".to_string(), "Bearer token".to_string())])
+        );
+    }
+}
@@ -2,11 +2,12 @@
 
 use std::path::PathBuf;
 
-use tatuin_core::folders;
+use tatuin_core::{crypto::Cipher, folders};
 
 pub struct Config {
     app_name: String,
     name: String,
+    cipher: Option<Cipher>,
 
     #[cfg(test)]
     pub cache_path: PathBuf,
@@ -17,12 +18,22 @@ impl Config {
         Self {
             app_name: app_name.to_string(),
             name: name.to_string(),
+            cipher: None,
 
             #[cfg(test)]
             cache_path: PathBuf::default(),
         }
     }
 
+    pub fn with_cipher(mut self, cipher: Option<Cipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    pub fn cipher(&self) -> Option<Cipher> {
+        self.cipher.clone()
+    }
+
     pub fn name(&self) -> String {
         self.name.to_string()
     }
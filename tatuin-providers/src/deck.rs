@@ -0,0 +1,633 @@
+// SPDX-License-Identifier: MIT
+
+pub mod client;
+pub mod structs;
+
+use crate::config::Config;
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone, Utc};
+use client::Client;
+use serde_json::{Map, Value, json};
+use std::any::Any;
+use tatuin_core::{
+    RichString, filter,
+    http::SharedHttpClient,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
+    time,
+};
+
+use async_trait::async_trait;
+
+pub const PROVIDER_NAME: &str = "Nextcloud Deck";
+
+const CARD_TYPE: &str = "plain";
+
+// The new cards go to the bottom of the stack
+const NEW_CARD_ORDER: i64 = 999;
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    board: structs::Board,
+    provider: String,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.board.id.to_string()
+    }
+
+    fn name(&self) -> String {
+        self.board.title.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("The board {}", self.board.title)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        false
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    card: structs::Card,
+    stack: String,
+    due: Option<DateTimeUtc>,
+    url: String,
+    project: Project,
+}
+
+fn str_to_date(s: &str) -> Option<DateTimeUtc> {
+    DateTime::parse_from_rfc3339(s).ok().map(DateTimeUtc::from)
+}
+
+fn timestamp_to_date(ts: i64) -> Option<DateTimeUtc> {
+    if ts == 0 { None } else { DateTime::from_timestamp(ts, 0) }
+}
+
+// Deck has no dates without time, the local midnight is shown as the date only
+fn due_from<Tz: TimeZone>(due: &DateTimeUtc, tz: &Tz) -> DateTimeUtc {
+    let local = due.with_timezone(tz);
+    if local.time() == NaiveTime::MIN {
+        local.date_naive().and_time(NaiveTime::MIN).and_utc()
+    } else {
+        *due
+    }
+}
+
+fn due_to<Tz: TimeZone>(due: &DateTimeUtc, tz: &Tz) -> String {
+    let dt = if time::is_date_only(due) {
+        tz.from_local_datetime(&due.date_naive().and_time(NaiveTime::MIN))
+            .earliest()
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or(*due)
+    } else {
+        *due
+    };
+    dt.to_rfc3339()
+}
+
+// The labels to remove and the titles of the labels to add
+fn label_changes(current: &[structs::Label], wanted: &[String]) -> (Vec<i64>, Vec<String>) {
+    let remove = current
+        .iter()
+        .filter(|l| !wanted.contains(&l.title))
+        .map(|l| l.id)
+        .collect();
+    let add = wanted
+        .iter()
+        .filter(|w| !current.iter().any(|l| &l.title == *w))
+        .cloned()
+        .collect();
+    (remove, add)
+}
+
+// The full card with the patched fields, Deck clears the fields that aren't sent
+fn card_fields<Tz: TimeZone>(card: &structs::Card, tp: &TaskPatch, tz: &Tz) -> Result<Map<String, Value>, String> {
+    let mut result = Map::new();
+    result.insert("title".to_string(), json!(tp.name.ref_value().unwrap_or(&card.title)));
+    let card_type = if card.card_type.is_empty() {
+        CARD_TYPE
+    } else {
+        &card.card_type
+    };
+    result.insert("type".to_string(), json!(card_type));
+    result.insert("owner".to_string(), json!(card.owner_uid()));
+    result.insert("order".to_string(), json!(card.order));
+
+    let description = if tp.description.is_set() {
+        tp.description.value().unwrap_or_default()
+    } else {
+        card.description.to_string()
+    };
+    result.insert("description".to_string(), json!(description));
+
+    let due = if tp.due.is_set() {
+        let due: Option<DateTimeUtc> = tp.due.value().and_then(|d| d.into());
+        due.map(|d| due_to(&d, tz))
+    } else {
+        card.duedate.clone()
+    };
+    result.insert("duedate".to_string(), json!(due));
+
+    let done = match tp.state.ref_value() {
+        Some(State::Completed) => card.done.clone().or_else(|| Some(Utc::now().to_rfc3339())),
+        Some(State::Uncompleted) => None,
+        Some(s) => return Err(format!("The state {s} is unsupported")),
+        None => card.done.clone(),
+    };
+    result.insert("done".to_string(), json!(done));
+
+    Ok(result)
+}
+
+impl Task {
+    fn new(card: structs::Card, stack: &str, base_url: &str, project: &Project) -> Self {
+        let due = card
+            .duedate
+            .as_deref()
+            .and_then(str_to_date)
+            .map(|d| due_from(&d, &Local));
+        let url = format!(
+            "{base_url}/index.php/apps/deck/#/board/{}/card/{}",
+            project.board.id, card.id
+        );
+        Self {
+            card,
+            stack: stack.to_string(),
+            due,
+            url,
+            project: project.clone(),
+        }
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.card.id.to_string()
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(&self.card.title)
+    }
+
+    fn description(&self) -> Option<RichString> {
+        Some(self.card.description.trim())
+            .filter(|d| !d.is_empty())
+            .map(RichString::new)
+    }
+
+    fn state(&self) -> State {
+        if self.card.done.is_some() {
+            State::Completed
+        } else {
+            State::Uncompleted
+        }
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        timestamp_to_date(self.card.created_at)
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        timestamp_to_date(self.card.last_modified)
+    }
+
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        self.card.done.as_deref().and_then(str_to_date)
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+
+    fn place(&self) -> String {
+        format!("{} / {}", self.project.board.title, self.stack)
+    }
+
+    fn url(&self) -> String {
+        self.url.to_string()
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.card.labels.iter().map(|l| l.title.to_string()).collect()
+    }
+
+    fn provider(&self) -> String {
+        self.project.provider()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(self.project.clone_boxed())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: true,
+            is_removable: true,
+            available_states: vec![State::Uncompleted, State::Completed],
+            available_priorities: vec![Priority::Normal],
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+}
+
+pub struct Provider {
+    cfg: Config,
+    client: Client,
+    boards: Vec<Project>,
+    tasks: Vec<Task>,
+    last_sync: Option<DateTimeUtc>,
+}
+
+impl Provider {
+    // The login and the password are the same as the CalDav ones, the app password is better than the account one
+    pub fn new(cfg: Config, url: &str, login: &str, password: &str) -> Self {
+        Self {
+            cfg,
+            client: Client::new(url, login, password),
+            boards: Vec::new(),
+            tasks: Vec::new(),
+            last_sync: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    // The archived and the deleted boards are skipped
+    async fn boards(&mut self) -> Result<Vec<Project>, ProviderError> {
+        if self.boards.is_empty() {
+            let provider = self.name();
+            self.boards = self
+                .client
+                .boards()
+                .await?
+                .into_iter()
+                .filter(|b| !b.archived && b.deleted_at == 0)
+                .map(|board| Project {
+                    board,
+                    provider: provider.to_string(),
+                })
+                .collect();
+        }
+        Ok(self.boards.clone())
+    }
+
+    async fn board(&mut self, id: &str) -> Result<Project, ProviderError> {
+        self.boards()
+            .await?
+            .into_iter()
+            .find(|p| p.id() == id)
+            .ok_or_else(|| ProviderError::NotFound(format!("the board {id} is not found")))
+    }
+
+    async fn set_labels(
+        &mut self,
+        project: &Project,
+        card: &structs::Card,
+        wanted: &[String],
+    ) -> Result<(), ProviderError> {
+        let board_id = project.board.id;
+        let (remove, add) = label_changes(&card.labels, wanted);
+        for id in remove {
+            self.client.remove_label(board_id, card, id).await?;
+        }
+
+        for title in add {
+            let id = match project.board.labels.iter().find(|l| l.title == title) {
+                Some(l) => l.id,
+                None => {
+                    // the board labels are read again on the next request
+                    self.boards.clear();
+                    self.client.create_label(board_id, &title).await?.id
+                }
+            };
+            self.client.assign_label(board_id, card, id).await?;
+        }
+        Ok(())
+    }
+
+    async fn update_task(&mut self, t: &Task, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let fields = card_fields(&t.card, tp, &Local).map_err(|e| ProviderError::other(e.as_str()))?;
+        self.client.update_card(t.project.board.id, &t.card, fields).await?;
+
+        if let Some(labels) = tp.labels.ref_value() {
+            self.set_labels(&t.project, &t.card, labels).await?;
+        } else if tp.labels.is_set() {
+            self.set_labels(&t.project, &t.card, &[]).await?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        Ok(self.boards().await?.iter().map(|p| p.clone_boxed()).collect())
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        if self.tasks.is_empty() {
+            for p in self.boards().await? {
+                for s in self.client.stacks(p.board.id).await? {
+                    for c in s.cards.into_iter().filter(|c| !c.archived) {
+                        self.tasks.push(Task::new(c, &s.title, self.client.url(), &p));
+                    }
+                }
+            }
+            self.last_sync = Some(Utc::now());
+        }
+
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| project.as_ref().is_none_or(|p| p.id() == t.project.id()))
+            .filter(|t| f.accept(*t as &dyn TaskTrait))
+            .map(|t| t.clone_boxed())
+            .collect())
+    }
+
+    // The card is added to the first stack of the board
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let Some(name) = tp.name.ref_value().filter(|n| !n.is_empty()) else {
+            return Err(ProviderError::other("The task name is required"));
+        };
+
+        let project = self.board(project_id).await?;
+        let stack = self
+            .client
+            .stacks(project.board.id)
+            .await?
+            .into_iter()
+            .min_by_key(|s| s.order)
+            .ok_or(ProviderError::other("The board has no stacks"))?;
+
+        let mut fields = Map::new();
+        fields.insert("title".to_string(), json!(name));
+        fields.insert("type".to_string(), json!(CARD_TYPE));
+        fields.insert("order".to_string(), json!(NEW_CARD_ORDER));
+        fields.insert(
+            "description".to_string(),
+            json!(tp.description.value().unwrap_or_default()),
+        );
+        let due: Option<DateTimeUtc> = tp.due.value().and_then(|d| d.into());
+        fields.insert("duedate".to_string(), json!(due.map(|d| due_to(&d, &Local))));
+
+        let card = self.client.create_card(project.board.id, stack.id, fields).await?;
+        if let Some(labels) = tp.labels.ref_value() {
+            self.set_labels(&project, &card, labels).await?;
+        }
+        self.tasks.clear();
+        Ok(())
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        for p in patches {
+            tracing::debug!(target:"deck_patch_task", patch=p.to_string(), "Apply a patch");
+            let task = p.task.as_ref().unwrap();
+
+            let Some(t) = task.as_any().downcast_ref::<Task>() else {
+                errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                continue;
+            };
+
+            if let Err(e) = self.update_task(t, p).await {
+                errors.push(PatchError {
+                    task: task.clone(),
+                    error: e.to_string(),
+                    kind: PatchErrorKind::Other,
+                });
+            }
+        }
+
+        self.tasks.clear();
+
+        errors
+    }
+
+    // The card is archived, so it can be restored in Deck
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let task = t
+            .as_any()
+            .downcast_ref::<Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
+        self.client.archive_card(task.project.board.id, &task.card).await?;
+        self.tasks.clear();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        self.tasks.clear();
+        self.boards.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::FixedOffset;
+    use tatuin_core::task_patch::ValuePatch;
+
+    fn project() -> Project {
+        Project {
+            board: structs::Board {
+                id: 3,
+                title: "Home".to_string(),
+                archived: false,
+                deleted_at: 0,
+                labels: Vec::new(),
+            },
+            provider: "deck".to_string(),
+        }
+    }
+
+    fn card() -> structs::Card {
+        serde_json::from_value(json!({
+            "id": 42,
+            "title": "Fix the fence",
+            "description": " Buy the boards\n",
+            "stackId": 7,
+            "type": "plain",
+            "order": 2,
+            "duedate": "2025-01-08T13:30:00+00:00",
+            "labels": [{"id": 1, "title": "Garden", "color": "31CC7C"}, {"id": 2, "title": "Weekend", "color": "317CCC"}],
+            "archived": false,
+            "done": null,
+            "owner": {"primaryKey": "anna", "uid": "anna", "displayname": "Anna"},
+            "createdAt": 1735725600,
+            "lastModified": 1735812000
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn task_test() {
+        let t = Task::new(card(), "To do", "https://cloud.example.com", &project());
+        assert_eq!(t.id(), "42");
+        assert_eq!(t.name().raw(), "Fix the fence");
+        assert_eq!(t.description().map(|d| d.raw()), Some("Buy the boards".to_string()));
+        assert_eq!(t.state(), State::Uncompleted);
+        assert_eq!(t.due(), str_to_date("2025-01-08T13:30:00Z"));
+        assert_eq!(t.created_at(), str_to_date("2025-01-01T10:00:00Z"));
+        assert_eq!(t.labels(), vec!["Garden", "Weekend"]);
+        assert_eq!(t.place(), "Home / To do");
+        assert_eq!(
+            t.url(),
+            "https://cloud.example.com/index.php/apps/deck/#/board/3/card/42"
+        );
+        assert_eq!(t.card.owner_uid(), "anna");
+
+        let mut c = card();
+        c.done = Some("2025-01-09T10:00:00+00:00".to_string());
+        let t = Task::new(c, "Done", "https://cloud.example.com", &project());
+        assert_eq!(t.state(), State::Completed);
+        assert_eq!(t.completed_at(), str_to_date("2025-01-09T10:00:00Z"));
+    }
+
+    #[test]
+    fn due_test() {
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+
+        let midnight = str_to_date("2025-01-07T22:00:00Z").unwrap();
+        let date = str_to_date("2025-01-08T00:00:00Z").unwrap();
+        assert_eq!(due_from(&midnight, &tz), date);
+        assert_eq!(due_to(&date, &tz), "2025-01-07T22:00:00+00:00");
+
+        let dt = str_to_date("2025-01-08T13:30:00Z").unwrap();
+        assert_eq!(due_from(&dt, &tz), dt);
+        assert_eq!(due_to(&dt, &tz), "2025-01-08T13:30:00+00:00");
+    }
+
+    #[test]
+    fn card_fields_test() {
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Paint the fence".to_string()),
+            due: ValuePatch::Value(DatePatchItem::Custom(str_to_date("2025-01-10T00:00:00Z").unwrap())),
+            state: ValuePatch::Value(State::Completed),
+            ..TaskPatch::default()
+        };
+        let fields = card_fields(&card(), &tp, &Utc).unwrap();
+        let done = fields["done"].clone();
+        assert!(done.as_str().and_then(str_to_date).is_some());
+        assert_eq!(
+            Value::Object(fields),
+            json!({
+                "title": "Paint the fence",
+                "type": "plain",
+                "owner": "anna",
+                "order": 2,
+                "description": " Buy the boards\n",
+                "duedate": "2025-01-10T00:00:00+00:00",
+                "done": done,
+            })
+        );
+
+        let mut c = card();
+        c.done = Some("2025-01-09T10:00:00+00:00".to_string());
+        let tp = TaskPatch {
+            description: ValuePatch::Empty,
+            due: ValuePatch::Value(DatePatchItem::NoDate),
+            state: ValuePatch::Value(State::Uncompleted),
+            ..TaskPatch::default()
+        };
+        let fields = card_fields(&c, &tp, &Utc).unwrap();
+        assert_eq!(fields["description"], json!(""));
+        assert_eq!(fields["duedate"], Value::Null);
+        assert_eq!(fields["done"], Value::Null);
+
+        let tp = TaskPatch {
+            state: ValuePatch::Value(State::InProgress),
+            ..TaskPatch::default()
+        };
+        assert!(card_fields(&c, &tp, &Utc).is_err());
+    }
+
+    #[test]
+    fn label_changes_test() {
+        let (remove, add) = label_changes(&card().labels, &["Weekend".to_string(), "Tools".to_string()]);
+        assert_eq!(remove, vec![1]);
+        assert_eq!(add, vec!["Tools"]);
+
+        let (remove, add) = label_changes(&card().labels, &[]);
+        assert_eq!(remove, vec![1, 2]);
+        assert!(add.is_empty());
+    }
+}
@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MIT
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+use std::sync::Arc;
+use tatuin_core::{
+    http::{Method, Request, SharedHttpClient},
+    provider::ProviderError,
+};
+
+use super::structs::{Board, Card, Label, Stack};
+use crate::http::ReqwestClient;
+
+const API_PATH: &str = "index.php/apps/deck/api/v1.0";
+
+// The color of the labels created by tatuin
+const LABEL_COLOR: &str = "31CC7C";
+
+pub struct Client {
+    url: String,
+    login: String,
+    password: String,
+    http: SharedHttpClient,
+}
+
+impl Client {
+    // The url is the one of the Nextcloud server like https://cloud.example.com
+    pub fn new(url: &str, login: &str, password: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            login: login.to_string(),
+            password: password.to_string(),
+            http: Arc::new(ReqwestClient::default()),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn request(&self, method: Method, path: &str) -> Request {
+        Request::new(method, &format!("{}/{API_PATH}/{path}", self.url))
+            .with_basic_auth(&self.login, &self.password)
+            .with_header("OCS-APIRequest", "true")
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ProviderError> {
+        self.http
+            .send_checked(self.request(Method::Get, path))
+            .await?
+            .json::<T>()
+            .inspect_err(|e| tracing::error!(target:"deck_client", path=path, error=?e, "Parse the response"))
+    }
+
+    async fn send<T: DeserializeOwned>(&self, method: Method, path: &str, body: &Value) -> Result<T, ProviderError> {
+        self.http
+            .send_checked(self.request(method, path).with_json(body)?)
+            .await?
+            .json::<T>()
+    }
+
+    // The boards with their labels
+    pub async fn boards(&self) -> Result<Vec<Board>, ProviderError> {
+        self.get("boards?details=true").await
+    }
+
+    // The stacks of the board with their cards except the archived ones
+    pub async fn stacks(&self, board_id: i64) -> Result<Vec<Stack>, ProviderError> {
+        self.get(&format!("boards/{board_id}/stacks")).await
+    }
+
+    pub async fn create_card(
+        &self,
+        board_id: i64,
+        stack_id: i64,
+        fields: Map<String, Value>,
+    ) -> Result<Card, ProviderError> {
+        let path = format!("boards/{board_id}/stacks/{stack_id}/cards");
+        self.send(Method::Post, &path, &Value::Object(fields)).await
+    }
+
+    // Deck replaces the card, so the fields should be the full card
+    pub async fn update_card(
+        &self,
+        board_id: i64,
+        card: &Card,
+        fields: Map<String, Value>,
+    ) -> Result<(), ProviderError> {
+        let path = format!("boards/{board_id}/stacks/{}/cards/{}", card.stack_id, card.id);
+        self.send::<Value>(Method::Put, &path, &Value::Object(fields))
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn archive_card(&self, board_id: i64, card: &Card) -> Result<(), ProviderError> {
+        let path = format!("boards/{board_id}/stacks/{}/cards/{}/archive", card.stack_id, card.id);
+        self.send::<Value>(Method::Put, &path, &json!({})).await.map(|_| ())
+    }
+
+    pub async fn assign_label(&self, board_id: i64, card: &Card, label_id: i64) -> Result<(), ProviderError> {
+        let path = format!(
+            "boards/{board_id}/stacks/{}/cards/{}/assignLabel",
+            card.stack_id, card.id
+        );
+        self.http
+            .send_checked(
+                self.request(Method::Put, &path)
+                    .with_json(&json!({ "labelId": label_id }))?,
+            )
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn remove_label(&self, board_id: i64, card: &Card, label_id: i64) -> Result<(), ProviderError> {
+        let path = format!(
+            "boards/{board_id}/stacks/{}/cards/{}/removeLabel",
+            card.stack_id, card.id
+        );
+        self.http
+            .send_checked(
+                self.request(Method::Put, &path)
+                    .with_json(&json!({ "labelId": label_id }))?,
+            )
+            .await
+            .map(|_| ())
+    }
+
+    pub async fn create_label(&self, board_id: i64, title: &str) -> Result<Label, ProviderError> {
+        let path = format!("boards/{board_id}/labels");
+        self.send(Method::Post, &path, &json!({ "title": title, "color": LABEL_COLOR }))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{MockHttpClient, Response};
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn create_label_test() {
+        const URL: &str = "https://cloud.example.com/index.php/apps/deck/api/v1.0/boards/1/labels";
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Post,
+            URL,
+            Response::new(200, br#"{"id": 5, "title": "work", "color": "31CC7C"}"#),
+        ));
+
+        let c = Client::new("https://cloud.example.com/", "user", "password").with_http_client(http.clone());
+        let label = c.create_label(1, "work").await.unwrap();
+        assert_eq!(label.id, 5);
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("OCS-APIRequest".to_string(), "true".to_string()))
+        );
+        let body: Value = serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(body, json!({"title": "work", "color": LABEL_COLOR}));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn boards_test() {
+        let c = Client::new("https://cloud.example.com", "user", "password").with_http_client(Arc::new(
+            MockHttpClient::new().with_response(
+                Method::Get,
+                "https://cloud.example.com/index.php/apps/deck/api/v1.0/boards?details=true",
+                Response::new(401, b""),
+            ),
+        ));
+        assert!(matches!(c.boards().await, Err(ProviderError::Auth(_))));
+    }
+}
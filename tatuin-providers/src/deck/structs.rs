@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Board {
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub archived: bool,
+    // the deleted boards stay in the trash for a while
+    #[serde(rename = "deletedAt", default)]
+    pub deleted_at: i64,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Stack {
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub order: i64,
+    #[serde(default)]
+    pub cards: Vec<Card>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "stackId")]
+    pub stack_id: i64,
+    #[serde(rename = "type", default)]
+    pub card_type: String,
+    #[serde(default)]
+    pub order: i64,
+    // ISO 8601 like 2025-01-08T00:00:00+00:00
+    pub duedate: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+    #[serde(default)]
+    pub archived: bool,
+    // the time when the card has been marked as done
+    pub done: Option<String>,
+    // the user object in the responses, but the user id in the requests
+    #[serde(default)]
+    pub owner: Value,
+    #[serde(rename = "createdAt", default)]
+    pub created_at: i64,
+    #[serde(rename = "lastModified", default)]
+    pub last_modified: i64,
+}
+
+impl Card {
+    pub fn owner_uid(&self) -> String {
+        match &self.owner {
+            Value::String(s) => s.to_string(),
+            v => v.get("uid").and_then(|u| u.as_str()).unwrap_or_default().to_string(),
+        }
+    }
+}
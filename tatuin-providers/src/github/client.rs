@@ -2,33 +2,54 @@
 
 use tatuin_core::filter::FilterState;
 
-use super::structs::Issue;
+use super::{
+    projects::{GraphQlResponse, OwnerData, PROJECT_QUERY, Project, SET_STATUS_MUTATION},
+    structs::{Issue, Repository, User},
+};
+use crate::http::ReqwestClient;
 use itertools::Itertools;
-use reqwest::{Method, RequestBuilder, header::HeaderMap};
-use std::error::Error;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use std::{error::Error, sync::Arc};
+use tatuin_core::http::{Request, SharedHttpClient};
+use urlencoding::encode;
+
+#[derive(Debug, Serialize)]
+pub struct CreateIssueRequest<'a> {
+    pub title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub assignees: Vec<String>,
+}
 
 pub struct Client {
     base_url: String,
-    default_header: HeaderMap,
-    client: reqwest::Client,
+    api_key: String,
+    http: SharedHttpClient,
 }
 
 impl Client {
     pub fn new(api_key: &str) -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert("Authorization", format!("Bearer {api_key}").parse().unwrap());
-        headers.insert("X-GitHub-Api-Version", "2022-11-28".parse().unwrap());
-        headers.insert("Accept", "application/vnd.github+json".parse().unwrap());
-        headers.insert("User-Agent", "Tatuin".parse().unwrap());
         Self {
             base_url: "https://api.github.com".to_string(),
-            default_header: headers,
-            client: reqwest::Client::new(),
+            api_key: api_key.to_string(),
+            http: Arc::new(ReqwestClient::default()),
         }
     }
 
-    fn request(&self, method: Method, url: &str) -> RequestBuilder {
-        self.client.request(method, url).headers(self.default_header.clone())
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    fn request(&self, r: Request) -> Request {
+        r.with_bearer_auth(&self.api_key)
+            .with_header("X-GitHub-Api-Version", "2022-11-28")
+            .with_header("Accept", "application/vnd.github+json")
+            .with_header("User-Agent", "Tatuin")
     }
 
     pub async fn issues(&self, repo: &str, states: &[FilterState]) -> Result<Vec<Issue>, Box<dyn Error>> {
@@ -56,7 +77,12 @@ impl Client {
                 "{}/repos/{repo}/issues?page={page}&per_page={PER_PAGE}&{state_query}",
                 self.base_url
             );
-            match self.request(Method::GET, &url).send().await?.json::<Vec<Issue>>().await {
+            match self
+                .http
+                .send_checked(self.request(Request::get(&url)))
+                .await?
+                .json::<Vec<Issue>>()
+            {
                 Ok(mut r) => {
                     if r.is_empty() {
                         break;
@@ -74,4 +100,257 @@ impl Client {
 
         Ok(result)
     }
+
+    // The issues and the pull requests found by the search query
+    pub async fn search_issues(&self, query: &str) -> Result<Vec<Issue>, Box<dyn Error>> {
+        #[derive(serde::Deserialize)]
+        struct Response {
+            total_count: usize,
+            items: Vec<Issue>,
+        }
+
+        let mut result = Vec::new();
+
+        const PER_PAGE: i8 = 100;
+        let mut page = 1;
+
+        loop {
+            let url = format!(
+                "{}/search/issues?q={}&page={page}&per_page={PER_PAGE}",
+                self.base_url,
+                encode(query)
+            );
+            match self
+                .http
+                .send_checked(self.request(Request::get(&url)))
+                .await?
+                .json::<Response>()
+            {
+                Ok(mut r) => {
+                    if r.items.is_empty() {
+                        break;
+                    }
+
+                    result.append(&mut r.items);
+                    if result.len() >= r.total_count {
+                        break;
+                    }
+                    page += 1;
+                }
+                Err(e) => {
+                    tracing::error!(target:"github_client", url=url, error=?e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // The repositories of the organization or the user, the archived ones are skipped
+    pub async fn owner_repositories(&self, owner: &str) -> Result<Vec<Repository>, Box<dyn Error>> {
+        // the organization's list includes the private repositories of its members
+        let org_repos = self
+            .repositories(format!("{}/orgs/{owner}/repos", self.base_url).as_str())
+            .await
+            .inspect_err(
+                |e| tracing::debug!(target:"github_client", owner=owner, error=?e, "The owner isn't an organization"),
+            )
+            .ok();
+        match org_repos {
+            Some(r) => Ok(r),
+            None => {
+                self.repositories(format!("{}/users/{owner}/repos", self.base_url).as_str())
+                    .await
+            }
+        }
+    }
+
+    async fn repositories(&self, base_url: &str) -> Result<Vec<Repository>, Box<dyn Error>> {
+        let mut result = Vec::new();
+
+        const PER_PAGE: i8 = 100;
+        let mut page = 1;
+
+        loop {
+            let url = format!("{base_url}?page={page}&per_page={PER_PAGE}");
+            match self
+                .http
+                .send_checked(self.request(Request::get(&url)))
+                .await?
+                .json::<Vec<Repository>>()
+            {
+                Ok(r) => {
+                    if r.is_empty() {
+                        break;
+                    }
+
+                    result.extend(r.into_iter().filter(|r| !r.archived));
+                    page += 1;
+                }
+                Err(e) => {
+                    tracing::error!(target:"github_client", url=url, error=?e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // The authenticated user
+    pub async fn user(&self) -> Result<User, Box<dyn Error>> {
+        let url = format!("{}/user", self.base_url);
+        self.http
+            .send_checked(self.request(Request::get(&url)))
+            .await?
+            .json::<User>()
+            .map_err(|e| {
+                tracing::error!(target:"github_client", url=url, error=?e);
+                e.into()
+            })
+    }
+
+    pub async fn create_issue(&self, repo: &str, r: &CreateIssueRequest<'_>) -> Result<Issue, Box<dyn Error>> {
+        let url = format!("{}/repos/{repo}/issues", self.base_url);
+        self.http
+            .send_checked(self.request(Request::post(&url).with_json(r)?))
+            .await?
+            .json::<Issue>()
+            .map_err(|e| {
+                tracing::error!(target:"github_client", request=?r, error=?e, "Create the issue");
+                e.into()
+            })
+    }
+
+    async fn graphql<T: DeserializeOwned>(&self, query: &str, variables: Value) -> Result<T, Box<dyn Error>> {
+        let url = format!("{}/graphql", self.base_url);
+        let r = self
+            .http
+            .send_checked(
+                self.request(Request::post(&url).with_json(&json!({ "query": query, "variables": variables }))?),
+            )
+            .await?
+            .json::<GraphQlResponse<T>>()
+            .map_err(|e| {
+                tracing::error!(target:"github_client", url=url, error=?e, "Parse the GraphQL response");
+                e
+            })?;
+        // GraphQL answers with 200 OK to the wrong queries
+        if let Some(e) = r.errors.first() {
+            tracing::error!(target:"github_client", errors=?r.errors, "Run the GraphQL query");
+            return Err(e.message.clone().into());
+        }
+        r.data.ok_or_else(|| "The GraphQL response has no data".into())
+    }
+
+    // The project of the user or the organization with all its items
+    pub async fn project(&self, owner: &str, number: i64, status_field: &str) -> Result<Project, Box<dyn Error>> {
+        let mut result: Option<Project> = None;
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let data = self
+                .graphql::<OwnerData>(
+                    PROJECT_QUERY,
+                    json!({ "owner": owner, "number": number, "statusField": status_field, "cursor": cursor }),
+                )
+                .await?;
+            let p = data
+                .owner
+                .and_then(|o| o.project)
+                .ok_or_else(|| format!("The project {number} of {owner} isn't found"))?;
+            let page = p.items.page_info.clone();
+            match result.as_mut() {
+                Some(r) => r.items.nodes.extend(p.items.nodes),
+                None => result = Some(p),
+            }
+
+            if !page.has_next_page || page.end_cursor.is_none() {
+                break;
+            }
+            cursor = page.end_cursor;
+        }
+
+        result.ok_or_else(|| "The project isn't loaded".into())
+    }
+
+    // Moves the item to the option of the single select field, e.g. to the Done column
+    pub async fn set_item_status(
+        &self,
+        project_id: &str,
+        item_id: &str,
+        field_id: &str,
+        option_id: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        self.graphql::<Value>(
+            SET_STATUS_MUTATION,
+            json!({ "project": project_id, "item": item_id, "field": field_id, "option": option_id }),
+        )
+        .await
+        .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn owner_repositories_test() {
+        // the owner isn't an organization, so the repositories of the user are loaded
+        let repos = serde_json::json!([
+            {"full_name": "panter-dsd/tatuin"},
+            {"full_name": "panter-dsd/old", "archived": true},
+        ])
+        .to_string();
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(
+                    Method::Get,
+                    "https://api.github.com/users/panter-dsd/repos?page=1&per_page=100",
+                    Response::new(200, repos.as_bytes()),
+                )
+                .with_response(
+                    Method::Get,
+                    "https://api.github.com/users/panter-dsd/repos?page=2&per_page=100",
+                    Response::new(200, b"[]"),
+                ),
+        );
+
+        let c = Client::new("token").with_http_client(http.clone());
+        let repos = c.owner_repositories("panter-dsd").await.unwrap();
+        assert_eq!(
+            repos.iter().map(|r| r.full_name.as_str()).collect::<Vec<&str>>(),
+            ["panter-dsd/tatuin"]
+        );
+        assert_eq!(
+            http.requests()[0].url,
+            "https://api.github.com/orgs/panter-dsd/repos?page=1&per_page=100"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn graphql_error_test() {
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Post,
+            "https://api.github.com/graphql",
+            Response::new(200, br#"{"errors": [{"message": "Could not resolve to a node"}]}"#),
+        ));
+
+        let c = Client::new("token").with_http_client(http.clone());
+        let err = c
+            .set_item_status("project", "item", "field", "option")
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Could not resolve to a node");
+        assert!(
+            http.requests()[0]
+                .headers
+                .contains(&("User-Agent".to_string(), "Tatuin".to_string()))
+        );
+    }
 }
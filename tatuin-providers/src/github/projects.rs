@@ -0,0 +1,283 @@
+// SPDX-License-Identifier: MIT
+
+// The structures of the GitHub Projects (v2) GraphQL API
+
+use serde::{Deserialize, Serialize};
+
+// The owner is a user or an organization, the items are paged
+pub const PROJECT_QUERY: &str = r#"
+query($owner: String!, $number: Int!, $statusField: String!, $cursor: String) {
+  repositoryOwner(login: $owner) {
+    ... on ProjectV2Owner {
+      projectV2(number: $number) {
+        id
+        title
+        url
+        field(name: $statusField) {
+          ... on ProjectV2SingleSelectField { id name options { id name } }
+        }
+        items(first: 100, after: $cursor) {
+          pageInfo { hasNextPage endCursor }
+          nodes {
+            id
+            isArchived
+            createdAt
+            updatedAt
+            fieldValues(first: 50) {
+              nodes {
+                __typename
+                ... on ProjectV2ItemFieldSingleSelectValue { name optionId field { ... on ProjectV2FieldCommon { name } } }
+                ... on ProjectV2ItemFieldDateValue { date field { ... on ProjectV2FieldCommon { name } } }
+                ... on ProjectV2ItemFieldIterationValue { startDate duration field { ... on ProjectV2FieldCommon { name } } }
+              }
+            }
+            content {
+              __typename
+              ... on DraftIssue { title body }
+              ... on Issue {
+                title body url state
+                author { login }
+                labels(first: 20) { nodes { name } }
+                assignees(first: 10) { nodes { login } }
+              }
+              ... on PullRequest {
+                title body url state
+                author { login }
+                labels(first: 20) { nodes { name } }
+                assignees(first: 10) { nodes { login } }
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
+"#;
+
+pub const SET_STATUS_MUTATION: &str = r#"
+mutation($project: ID!, $item: ID!, $field: ID!, $option: String!) {
+  updateProjectV2ItemFieldValue(
+    input: { projectId: $project, itemId: $item, fieldId: $field, value: { singleSelectOptionId: $option } }
+  ) {
+    projectV2Item { id }
+  }
+}
+"#;
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQlError {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GraphQlResponse<T> {
+    pub data: Option<T>,
+    #[serde(default)]
+    pub errors: Vec<GraphQlError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OwnerData {
+    #[serde(rename = "repositoryOwner")]
+    pub owner: Option<Owner>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Owner {
+    #[serde(rename = "projectV2")]
+    pub project: Option<Project>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    // null if the project has no single select field with the name
+    pub field: Option<StatusField>,
+    pub items: Connection<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Connection<T> {
+    #[serde(rename = "pageInfo", default)]
+    pub page_info: PageInfo,
+    pub nodes: Vec<T>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct PageInfo {
+    #[serde(rename = "hasNextPage")]
+    pub has_next_page: bool,
+    #[serde(rename = "endCursor")]
+    pub end_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatusField {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub options: Vec<StatusOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusOption {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct FieldName {
+    #[serde(default)]
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum FieldValue {
+    #[serde(rename = "ProjectV2ItemFieldSingleSelectValue")]
+    SingleSelect {
+        name: Option<String>,
+        #[serde(rename = "optionId")]
+        option_id: Option<String>,
+        field: FieldName,
+    },
+    #[serde(rename = "ProjectV2ItemFieldDateValue")]
+    Date { date: Option<String>, field: FieldName },
+    // the iteration lasts the duration days since the start date
+    #[serde(rename = "ProjectV2ItemFieldIterationValue")]
+    Iteration {
+        #[serde(rename = "startDate")]
+        start_date: String,
+        duration: i64,
+        field: FieldName,
+    },
+    // the text, number, user and other values aren't used
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Login {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Name {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "__typename")]
+pub enum Content {
+    DraftIssue {
+        title: String,
+        body: Option<String>,
+    },
+    Issue(IssueContent),
+    PullRequest(IssueContent),
+    // the redacted items the token can't read
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct IssueContent {
+    pub title: String,
+    pub body: Option<String>,
+    pub url: String,
+    // OPEN, CLOSED or MERGED
+    pub state: String,
+    pub author: Option<Login>,
+    pub labels: Option<Connection<Name>>,
+    pub assignees: Connection<Login>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Item {
+    pub id: String,
+    #[serde(rename = "isArchived", default)]
+    pub is_archived: bool,
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: String,
+    #[serde(rename = "fieldValues")]
+    pub field_values: Connection<FieldValue>,
+    pub content: Option<Content>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_item_test() {
+        let item: Item = serde_json::from_value(serde_json::json!({
+            "id": "PVTI_1",
+            "isArchived": false,
+            "createdAt": "2025-01-01T10:00:00Z",
+            "updatedAt": "2025-01-02T10:00:00Z",
+            "fieldValues": {"nodes": [
+                {"__typename": "ProjectV2ItemFieldTextValue"},
+                {"__typename": "ProjectV2ItemFieldSingleSelectValue", "name": "In Progress", "optionId": "o2", "field": {"name": "Status"}},
+                {"__typename": "ProjectV2ItemFieldDateValue", "date": "2025-01-10", "field": {"name": "Due"}},
+                {"__typename": "ProjectV2ItemFieldIterationValue", "startDate": "2025-01-06", "duration": 14, "field": {"name": "Sprint"}},
+            ]},
+            "content": {
+                "__typename": "Issue",
+                "title": "Fix the build",
+                "body": null,
+                "url": "https://github.com/o/r/issues/1",
+                "state": "OPEN",
+                "author": {"login": "octocat"},
+                "labels": {"nodes": [{"name": "bug"}]},
+                "assignees": {"nodes": []},
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(
+            item.field_values.nodes,
+            vec![
+                FieldValue::Other,
+                FieldValue::SingleSelect {
+                    name: Some("In Progress".to_string()),
+                    option_id: Some("o2".to_string()),
+                    field: FieldName {
+                        name: "Status".to_string()
+                    },
+                },
+                FieldValue::Date {
+                    date: Some("2025-01-10".to_string()),
+                    field: FieldName {
+                        name: "Due".to_string()
+                    },
+                },
+                FieldValue::Iteration {
+                    start_date: "2025-01-06".to_string(),
+                    duration: 14,
+                    field: FieldName {
+                        name: "Sprint".to_string()
+                    },
+                },
+            ]
+        );
+        assert!(matches!(item.content, Some(Content::Issue(c)) if c.title == "Fix the build"));
+
+        let draft: Content =
+            serde_json::from_value(serde_json::json!({"__typename": "DraftIssue", "title": "Plan", "body": ""}))
+                .unwrap();
+        assert_eq!(
+            draft,
+            Content::DraftIssue {
+                title: "Plan".to_string(),
+                body: Some(String::new())
+            }
+        );
+    }
+}
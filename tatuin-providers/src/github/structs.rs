@@ -93,3 +93,10 @@ pub struct PullRequest {
     pub diff_url: String,
     pub patch_url: String,
 }
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Repository {
+    pub full_name: String,
+    #[serde(default)]
+    pub archived: bool,
+}
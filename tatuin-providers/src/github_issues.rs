@@ -2,13 +2,17 @@
 
 use crate::config::Config;
 
-use super::github::{client::Client, structs};
+use super::github::{
+    client::{Client, CreateIssueRequest},
+    structs,
+};
 use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use std::any::Any;
 use tatuin_core::{
-    RichString, StringError, filter,
+    RichString, filter,
+    http::SharedHttpClient,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
     task::{DateTimeUtc, PatchPolicy, State, Task as TaskTrait, due_group},
     task_patch::{PatchError, TaskPatch},
 };
@@ -17,10 +21,54 @@ use async_trait::async_trait;
 
 pub const PROVIDER_NAME: &str = "GitHub Issues";
 
+// Every repository is a project, the first one is the inbox
+#[derive(Clone, Debug)]
+pub struct Project {
+    repo: String,
+    provider: String,
+    is_inbox: bool,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.repo.to_string()
+    }
+
+    fn name(&self) -> String {
+        self.repo.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("Issues of the repository {}", self.repo)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        self.is_inbox
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
 #[derive(Clone)]
 pub struct Task {
     issue: structs::Issue,
-    provider: String,
+    project: Project,
+    // the login of the token owner
+    me: Option<String>,
 }
 
 fn str_to_date(s: &str) -> Option<DateTimeUtc> {
@@ -49,10 +97,18 @@ impl TaskTrait for Task {
         RichString::new(&self.issue.title)
     }
 
+    fn description(&self) -> Option<RichString> {
+        self.issue.body.as_ref().map(|b| RichString::new(b))
+    }
+
     fn created_at(&self) -> Option<DateTimeUtc> {
         str_to_date(self.issue.created_at.as_str())
     }
 
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        str_to_date(self.issue.updated_at.as_str())
+    }
+
     fn due(&self) -> Option<DateTimeUtc> {
         if let Some(m) = &self.issue.milestone
             && let Some(due) = &m.due_on
@@ -71,16 +127,43 @@ impl TaskTrait for Task {
         match self.issue.state.as_str() {
             "open" => State::Uncompleted,
             "closed" => State::Completed,
-            _ => panic!("Unknown state {}", self.issue.state),
+            _ => State::Unknown('?'),
         }
     }
 
     fn provider(&self) -> String {
-        self.provider.to_string()
+        self.project.provider.to_string()
     }
 
     fn project(&self) -> Option<Box<dyn ProjectTrait>> {
-        None
+        Some(self.project.clone_boxed())
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.issue.labels.iter().map(|l| l.name.to_string()).collect()
+    }
+
+    fn assignee(&self) -> Option<String> {
+        let assignees = self
+            .issue
+            .assignees
+            .iter()
+            .map(|a| a.login.as_str())
+            .collect::<Vec<&str>>();
+        (!assignees.is_empty()).then(|| assignees.join(", "))
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue.assignees.iter().any(|a| a.login == *me))
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        Some(self.issue.user.login == *self.me.as_ref()?)
+    }
+
+    fn comment_count(&self) -> Option<u64> {
+        u64::try_from(self.issue.comments).ok()
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -106,26 +189,130 @@ impl TaskTrait for Task {
     }
 }
 
+// The repository like panter-dsd/tatuin or all the repositories of the owner like panter-dsd/*
+#[derive(Clone, Debug, PartialEq)]
+enum Source {
+    Repository(String),
+    Owner(String),
+}
+
+impl Source {
+    fn search_qualifier(&self) -> String {
+        match self {
+            Self::Repository(r) => format!("repo:{r}"),
+            Self::Owner(o) => format!("user:{o}"),
+        }
+    }
+}
+
+// The comma-separated list of the repositories and the owners
+fn parse_sources(s: &str) -> Vec<Source> {
+    s.split(',')
+        .map(str::trim)
+        .filter(|r| !r.is_empty())
+        .map(|r| match r.strip_suffix("/*") {
+            Some(owner) => Source::Owner(owner.to_string()),
+            None => Source::Repository(r.to_string()),
+        })
+        .collect()
+}
+
+// The issue keeps the API url of its repository like https://api.github.com/repos/panter-dsd/tatuin
+fn issue_repository(issue: &structs::Issue) -> String {
+    issue
+        .repository_url
+        .split_once("/repos/")
+        .map(|(_, r)| r.to_string())
+        .unwrap_or_default()
+}
+
 pub struct Provider {
     cfg: Config,
-    repo: String,
+    sources: Vec<Source>,
+    // the sources with the owners resolved to their repositories
+    repos: Option<Vec<String>>,
     client: Client,
     tasks: Vec<Task>,
     last_filter: Option<filter::Filter>,
+    last_sync: Option<DateTimeUtc>,
+    // the login of the token owner, the created issues are assigned to it
+    login: Option<String>,
 }
 
 impl Provider {
-    pub fn new(cfg: Config, api_key: &str, repo: &str) -> Self {
+    pub fn new(cfg: Config, api_key: &str, repository: &str) -> Self {
         Self {
             cfg,
-            repo: repo.to_string(),
+            sources: parse_sources(repository),
+            repos: None,
             client: Client::new(api_key),
             tasks: Vec::new(),
             last_filter: None,
+            last_sync: None,
+            login: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    fn project(&self, repo: &str) -> Project {
+        Project {
+            repo: repo.to_string(),
+            provider: self.name(),
+            is_inbox: self.repos.as_ref().and_then(|r| r.first()).is_some_and(|r| r == repo),
+        }
+    }
+
+    async fn repositories(&mut self) -> Result<Vec<String>, ProviderError> {
+        if self.repos.is_none() {
+            let mut repos: Vec<String> = Vec::new();
+            for s in &self.sources {
+                let names = match s {
+                    Source::Repository(r) => vec![r.to_string()],
+                    Source::Owner(o) => self
+                        .client
+                        .owner_repositories(o)
+                        .await?
+                        .into_iter()
+                        .map(|r| r.full_name)
+                        .collect(),
+                };
+                for n in names {
+                    if !repos.contains(&n) {
+                        repos.push(n);
+                    }
+                }
+            }
+            self.repos = Some(repos);
+        }
+        Ok(self.repos.clone().unwrap_or_default())
+    }
+
+    async fn login(&mut self) -> Result<String, ProviderError> {
+        if self.login.is_none() {
+            self.login = Some(self.client.user().await?.login);
         }
+        Ok(self.login.clone().unwrap_or_default())
     }
 }
 
+fn create_issue_request<'a>(tp: &'a TaskPatch, login: &str) -> Result<CreateIssueRequest<'a>, ProviderError> {
+    let title = tp
+        .name
+        .ref_value()
+        .filter(|n| !n.is_empty())
+        .ok_or(ProviderError::other("The issue title is required"))?;
+    Ok(CreateIssueRequest {
+        title,
+        body: tp.description.ref_value().map(String::as_str).filter(|d| !d.is_empty()),
+        labels: tp.labels.value().unwrap_or_default(),
+        assignees: vec![login.to_string()],
+    })
+}
+
 impl std::fmt::Debug for Provider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Provider name={}", ProviderTrait::name(self))
@@ -134,8 +321,13 @@ impl std::fmt::Debug for Provider {
 
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
-        Ok(Vec::new())
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        Ok(self
+            .repositories()
+            .await?
+            .iter()
+            .map(|r| self.project(r).clone_boxed())
+            .collect())
     }
 }
 
@@ -143,9 +335,9 @@ impl ProjectProviderTrait for Provider {
 impl TaskProviderTrait for Provider {
     async fn list(
         &mut self,
-        _project: Option<Box<dyn ProjectTrait>>,
+        project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
         let mut should_clear = false;
         if let Some(last_filter) = self.last_filter.as_mut() {
             should_clear = last_filter != f;
@@ -156,18 +348,29 @@ impl TaskProviderTrait for Provider {
         }
 
         if self.tasks.is_empty() {
-            for t in self.client.issues(&self.repo, &f.states).await? {
-                self.tasks.push(Task {
-                    issue: t,
-                    provider: self.name(),
-                })
+            // the tasks are shown without the assignment info if the token can't read the user
+            let me = self
+                .login()
+                .await
+                .inspect_err(|e| tracing::warn!(target:"github_issues", error=?e, "Get the user"))
+                .ok();
+            for repo in self.repositories().await? {
+                for t in self.client.issues(&repo, &f.states).await? {
+                    self.tasks.push(Task {
+                        issue: t,
+                        project: self.project(&repo),
+                        me: me.clone(),
+                    })
+                }
             }
+            self.last_sync = Some(Utc::now());
         }
 
         let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
 
+        let repo = project.map(|p| p.id());
         for t in &self.tasks {
-            if f.due.contains(&due_group(&t.due())) {
+            if f.due.contains(&due_group(&t.due())) && repo.as_ref().is_none_or(|r| *r == t.project.repo) {
                 result.push(Box::new(t.clone()));
             }
         }
@@ -177,8 +380,47 @@ impl TaskProviderTrait for Provider {
         Ok(result)
     }
 
-    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), StringError> {
-        Err(StringError::new("Task creation is not supported"))
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let me = self.login().await.ok();
+        // the repositories are resolved to mark the inbox
+        self.repositories().await?;
+        let qualifiers = self
+            .sources
+            .iter()
+            .map(Source::search_qualifier)
+            .collect::<Vec<String>>();
+        Ok(self
+            .client
+            .search_issues(format!("{} {query}", qualifiers.join(" ")).as_str())
+            .await?
+            .into_iter()
+            .map(|issue| {
+                let project = self.project(&issue_repository(&issue));
+                Box::new(Task {
+                    issue,
+                    project,
+                    me: me.clone(),
+                }) as Box<dyn TaskTrait>
+            })
+            .collect())
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let repo = match project_id {
+            "" => self
+                .repositories()
+                .await?
+                .into_iter()
+                .next()
+                .ok_or(ProviderError::other("The provider has no repositories"))?,
+            id => id.to_string(),
+        };
+        let login = self.login().await?;
+        self.client
+            .create_issue(&repo, &create_issue_request(tp, &login)?)
+            .await?;
+        self.tasks.clear();
+        Ok(())
     }
 
     async fn update(&mut self, _patches: &[TaskPatch]) -> Vec<PatchError> {
@@ -198,9 +440,103 @@ impl ProviderTrait for Provider {
 
     async fn reload(&mut self) {
         self.tasks.clear();
+        self.repos = None;
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
     }
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities { create_task: false }
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: true,
+            queries: true,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::task_patch::ValuePatch;
+
+    #[test]
+    fn create_issue_request_test() {
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Fix the build".to_string()),
+            description: ValuePatch::Value("It fails on CI".to_string()),
+            labels: ValuePatch::Value(vec!["bug".to_string(), "ci".to_string()]),
+            ..TaskPatch::default()
+        };
+        let r = create_issue_request(&tp, "octocat").unwrap();
+        assert_eq!(
+            serde_json::to_value(&r).unwrap(),
+            serde_json::json!({
+                "title": "Fix the build",
+                "body": "It fails on CI",
+                "labels": ["bug", "ci"],
+                "assignees": ["octocat"],
+            })
+        );
+
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Fix the build".to_string()),
+            description: ValuePatch::Value(String::new()),
+            ..TaskPatch::default()
+        };
+        assert_eq!(
+            serde_json::to_value(create_issue_request(&tp, "octocat").unwrap()).unwrap(),
+            serde_json::json!({"title": "Fix the build", "assignees": ["octocat"]})
+        );
+
+        assert!(create_issue_request(&TaskPatch::default(), "octocat").is_err());
+    }
+
+    #[test]
+    fn parse_sources_test() {
+        struct Case<'a> {
+            name: &'a str,
+            s: &'a str,
+            expected: Vec<Source>,
+        }
+        let cases = [
+            Case {
+                name: "one repository",
+                s: "panter-dsd/tatuin",
+                expected: vec![Source::Repository("panter-dsd/tatuin".to_string())],
+            },
+            Case {
+                name: "list",
+                s: " panter-dsd/tatuin, rust-lang/* ,,",
+                expected: vec![
+                    Source::Repository("panter-dsd/tatuin".to_string()),
+                    Source::Owner("rust-lang".to_string()),
+                ],
+            },
+            Case {
+                name: "empty",
+                s: "",
+                expected: Vec::new(),
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(parse_sources(c.s), c.expected, "Test '{}' was failed", c.name);
+        }
+
+        let qualifiers = parse_sources("panter-dsd/tatuin,rust-lang/*")
+            .iter()
+            .map(Source::search_qualifier)
+            .collect::<Vec<String>>();
+        assert_eq!(qualifiers, vec!["repo:panter-dsd/tatuin", "user:rust-lang"]);
     }
 }
@@ -0,0 +1,589 @@
+// SPDX-License-Identifier: MIT
+
+use crate::config::Config;
+
+use super::github::{
+    client::Client,
+    projects::{self, Content, FieldValue, StatusField},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::{any::Any, sync::Arc};
+use tatuin_core::{
+    RichString, filter,
+    http::SharedHttpClient,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, State, Task as TaskTrait},
+    task_patch::{PatchError, PatchErrorKind, TaskPatch},
+    time,
+};
+
+use async_trait::async_trait;
+
+pub const PROVIDER_NAME: &str = "GitHub Projects";
+
+const DEFAULT_STATUS_FIELD: &str = "Status";
+const DEFAULT_TODO_STATUS: &str = "Todo";
+const DEFAULT_IN_PROGRESS_STATUS: &str = "In Progress";
+const DEFAULT_DONE_STATUS: &str = "Done";
+
+// The columns of the board, every task state is moved to its own column
+#[derive(Clone, Debug, PartialEq)]
+pub struct Statuses {
+    pub field: String,
+    pub todo: String,
+    pub in_progress: String,
+    pub done: String,
+}
+
+impl Default for Statuses {
+    fn default() -> Self {
+        Self {
+            field: DEFAULT_STATUS_FIELD.to_string(),
+            todo: DEFAULT_TODO_STATUS.to_string(),
+            in_progress: DEFAULT_IN_PROGRESS_STATUS.to_string(),
+            done: DEFAULT_DONE_STATUS.to_string(),
+        }
+    }
+}
+
+impl Statuses {
+    fn state(&self, status: Option<&str>) -> State {
+        match status {
+            Some(s) if s.eq_ignore_ascii_case(&self.done) => State::Completed,
+            Some(s) if s.eq_ignore_ascii_case(&self.in_progress) => State::InProgress,
+            _ => State::Uncompleted,
+        }
+    }
+
+    fn status(&self, state: &State) -> Option<&str> {
+        match state {
+            State::Uncompleted => Some(&self.todo),
+            State::InProgress => Some(&self.in_progress),
+            State::Completed => Some(&self.done),
+            State::Unknown(_) => None,
+        }
+    }
+}
+
+// The project board is the only project of the provider
+#[derive(Clone, Debug)]
+pub struct Project {
+    id: String,
+    title: String,
+    url: String,
+    provider: String,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn name(&self) -> String {
+        self.title.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("Items of the project {}", self.url)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        true
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    item: projects::Item,
+    project: Project,
+    // the name of the Status option of the item
+    status: Option<String>,
+    state: State,
+    due: Option<DateTimeUtc>,
+    // the login of the token owner
+    me: Option<String>,
+}
+
+fn str_to_date(s: &str) -> Option<DateTimeUtc> {
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return d.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+    }
+    DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc))
+}
+
+// The date of the date field or the last day of the iteration.
+// The field with the name is used if it's set, otherwise the first date or iteration field.
+fn due(values: &[FieldValue], due_field: Option<&str>) -> Option<DateTimeUtc> {
+    values
+        .iter()
+        .filter_map(|v| match v {
+            FieldValue::Date { date, field } => Some((field.name.as_str(), date.as_deref().and_then(str_to_date))),
+            FieldValue::Iteration {
+                start_date,
+                duration,
+                field,
+            } => Some((
+                field.name.as_str(),
+                str_to_date(start_date).map(|d| time::add_days(&d, duration.saturating_sub(1).max(0) as u64)),
+            )),
+            _ => None,
+        })
+        .find(|(name, _)| due_field.is_none_or(|f| f.eq_ignore_ascii_case(name)))
+        .and_then(|(_, d)| d)
+}
+
+fn status(values: &[FieldValue], status_field: &str) -> Option<String> {
+    values.iter().find_map(|v| match v {
+        FieldValue::SingleSelect { name, field, .. } if field.name.eq_ignore_ascii_case(status_field) => name.clone(),
+        _ => None,
+    })
+}
+
+impl Task {
+    fn issue(&self) -> Option<&projects::IssueContent> {
+        match &self.item.content {
+            Some(Content::Issue(c)) | Some(Content::PullRequest(c)) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.item.id.to_string()
+    }
+
+    fn name(&self) -> RichString {
+        match &self.item.content {
+            Some(Content::DraftIssue { title, .. }) => RichString::new(title),
+            Some(Content::Issue(c)) | Some(Content::PullRequest(c)) => RichString::new(&c.title),
+            _ => RichString::new("The item isn't available"),
+        }
+    }
+
+    fn description(&self) -> Option<RichString> {
+        match &self.item.content {
+            Some(Content::DraftIssue { body, .. }) => body.as_deref(),
+            Some(Content::Issue(c)) | Some(Content::PullRequest(c)) => c.body.as_deref(),
+            _ => None,
+        }
+        .filter(|b| !b.is_empty())
+        .map(RichString::new)
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        str_to_date(&self.item.created_at)
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        str_to_date(&self.item.updated_at)
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+
+    fn place(&self) -> String {
+        match &self.status {
+            Some(s) => format!("{} / {s}", self.project.title),
+            None => self.project.title.to_string(),
+        }
+    }
+
+    fn state(&self) -> State {
+        self.state
+    }
+
+    fn provider(&self) -> String {
+        self.project.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(self.project.clone_boxed())
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.issue()
+            .and_then(|c| c.labels.as_ref())
+            .map(|l| l.nodes.iter().map(|n| n.name.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    fn assignee(&self) -> Option<String> {
+        let assignees = self
+            .issue()?
+            .assignees
+            .nodes
+            .iter()
+            .map(|a| a.login.as_str())
+            .collect::<Vec<&str>>();
+        (!assignees.is_empty()).then(|| assignees.join(", "))
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue()?.assignees.nodes.iter().any(|a| a.login == *me))
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue()?.author.as_ref().is_some_and(|a| a.login == *me))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn url(&self) -> String {
+        self.issue()
+            .map(|c| c.url.to_string())
+            .unwrap_or_else(|| self.project.url.to_string())
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: false,
+            is_removable: false,
+            available_states: vec![State::Uncompleted, State::InProgress, State::Completed],
+            available_priorities: Vec::new(),
+            available_due_items: Vec::new(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+}
+
+pub struct Provider {
+    cfg: Config,
+    owner: String,
+    number: i64,
+    statuses: Statuses,
+    due_field: Option<String>,
+    client: Client,
+    tasks: Vec<Task>,
+    status_field: Option<StatusField>,
+    last_sync: Option<DateTimeUtc>,
+    // the login of the token owner
+    login: Option<String>,
+}
+
+impl Provider {
+    // The project is the one with the number of the user's or organization's projects, e.g. github.com/orgs/<owner>/projects/<number>
+    pub fn new(cfg: Config, api_key: &str, owner: &str, number: i64) -> Self {
+        Self {
+            cfg,
+            owner: owner.to_string(),
+            number,
+            statuses: Statuses::default(),
+            due_field: None,
+            client: Client::new(api_key),
+            tasks: Vec::new(),
+            status_field: None,
+            last_sync: None,
+            login: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    pub fn with_statuses(mut self, statuses: Statuses) -> Self {
+        self.statuses = statuses;
+        self
+    }
+
+    pub fn with_due_field(mut self, field: Option<&str>) -> Self {
+        self.due_field = field.map(|f| f.to_string());
+        self
+    }
+
+    async fn login(&mut self) -> Result<String, ProviderError> {
+        if self.login.is_none() {
+            self.login = Some(self.client.user().await?.login);
+        }
+        Ok(self.login.clone().unwrap_or_default())
+    }
+
+    async fn load(&mut self) -> Result<(), ProviderError> {
+        // the tasks are shown without the assignment info if the token can't read the user
+        let me = self
+            .login()
+            .await
+            .inspect_err(|e| tracing::warn!(target:"github_projects", error=?e, "Get the user"))
+            .ok();
+        let p = self
+            .client
+            .project(&self.owner, self.number, &self.statuses.field)
+            .await?;
+        let project = Project {
+            id: p.id.to_string(),
+            title: p.title.to_string(),
+            url: p.url.to_string(),
+            provider: self.name(),
+        };
+
+        self.tasks = p
+            .items
+            .nodes
+            .into_iter()
+            .filter(|i| !i.is_archived)
+            .map(|item| {
+                let status = status(&item.field_values.nodes, &self.statuses.field);
+                Task {
+                    state: self.statuses.state(status.as_deref()),
+                    due: due(&item.field_values.nodes, self.due_field.as_deref()),
+                    status,
+                    item,
+                    project: project.clone(),
+                    me: me.clone(),
+                }
+            })
+            .collect();
+        self.status_field = p.field.filter(|f| !f.id.is_empty());
+        self.last_sync = Some(Utc::now());
+        Ok(())
+    }
+
+    // Moves the item to the column of the state
+    async fn patch_task_state(&self, t: &Task, state: &State) -> Result<(), PatchError> {
+        let error = |e: String| PatchError {
+            task: Arc::new(t.clone()),
+            error: e,
+            kind: PatchErrorKind::Other,
+        };
+
+        let field = self.status_field.as_ref().ok_or_else(|| {
+            error(format!(
+                "The project has no single select field {}",
+                self.statuses.field
+            ))
+        })?;
+        let status = self
+            .statuses
+            .status(state)
+            .ok_or_else(|| error(format!("The state {state} is unsupported")))?;
+        let option = field
+            .options
+            .iter()
+            .find(|o| o.name.eq_ignore_ascii_case(status))
+            .ok_or_else(|| error(format!("The field {} has no option {status}", field.name)))?;
+        self.client
+            .set_item_status(&t.project.id, &t.item.id, &field.id, &option.id)
+            .await
+            .map_err(|e| PatchError {
+                kind: PatchErrorKind::of(e.as_ref()),
+                ..error(e.to_string())
+            })
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        if self.tasks.is_empty() {
+            self.load().await?;
+        }
+        Ok(self
+            .tasks
+            .first()
+            .map(|t| t.project.clone_boxed())
+            .into_iter()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        _project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        // the whole board is loaded at once, so the filter is applied locally
+        if self.tasks.is_empty() {
+            self.load().await?;
+        }
+
+        Ok(self
+            .tasks
+            .iter()
+            .filter(|t| f.accept(*t))
+            .map(|t| t.clone_boxed())
+            .collect())
+    }
+
+    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+        Err(ProviderError::other("Task creation is not supported"))
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        for p in patches {
+            let task = p.task.as_ref().unwrap();
+
+            let task = match task.as_any().downcast_ref::<Task>() {
+                Some(t) => t,
+                None => {
+                    errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                    continue;
+                }
+            };
+            if let Some(state) = &p.state.value()
+                && let Err(e) = self.patch_task_state(task, state).await
+            {
+                errors.push(e);
+            }
+        }
+
+        self.tasks.clear();
+
+        errors
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        self.tasks.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: false,
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: true,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use projects::FieldName;
+
+    fn field(name: &str) -> FieldName {
+        FieldName { name: name.to_string() }
+    }
+
+    #[test]
+    fn statuses_test() {
+        let s = Statuses::default();
+        assert_eq!(s.state(Some("done")), State::Completed);
+        assert_eq!(s.state(Some("In Progress")), State::InProgress);
+        assert_eq!(s.state(Some("Backlog")), State::Uncompleted);
+        assert_eq!(s.state(None), State::Uncompleted);
+        for state in [State::Uncompleted, State::InProgress, State::Completed] {
+            assert_eq!(s.state(s.status(&state)), state, "Test '{state:?}' was failed");
+        }
+        assert_eq!(s.status(&State::Unknown('?')), None);
+    }
+
+    #[test]
+    fn due_test() {
+        let values = vec![
+            FieldValue::SingleSelect {
+                name: Some("Todo".to_string()),
+                option_id: Some("o1".to_string()),
+                field: field("Status"),
+            },
+            FieldValue::Iteration {
+                start_date: "2025-01-06".to_string(),
+                duration: 14,
+                field: field("Sprint"),
+            },
+            FieldValue::Date {
+                date: Some("2025-01-10".to_string()),
+                field: field("Due"),
+            },
+        ];
+
+        struct Case<'a> {
+            name: &'a str,
+            due_field: Option<&'a str>,
+            expected: Option<&'a str>,
+        }
+        let cases = [
+            Case {
+                name: "the first field",
+                due_field: None,
+                expected: Some("2025-01-19"),
+            },
+            Case {
+                name: "the date field",
+                due_field: Some("due"),
+                expected: Some("2025-01-10"),
+            },
+            Case {
+                name: "the iteration field",
+                due_field: Some("Sprint"),
+                expected: Some("2025-01-19"),
+            },
+            Case {
+                name: "the absent field",
+                due_field: Some("Deadline"),
+                expected: None,
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                due(&values, c.due_field),
+                c.expected.and_then(str_to_date),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+
+        assert_eq!(status(&values, "status"), Some("Todo".to_string()));
+        assert_eq!(status(&values, "Priority"), None);
+    }
+}
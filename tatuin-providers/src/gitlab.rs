@@ -2,3 +2,24 @@
 
 pub mod client;
 pub mod structs;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use tatuin_core::task::DateTimeUtc;
+
+// The dates of the API are the plain dates or the times with or without the time zone
+pub fn str_to_date(s: &str) -> Option<DateTimeUtc> {
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let dt = d.and_hms_opt(0, 0, 0)?;
+        return Some(DateTimeUtc::from_naive_utc_and_offset(dt, Utc));
+    }
+
+    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
+        return Some(DateTimeUtc::from_naive_utc_and_offset(dt, Utc));
+    }
+
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(DateTimeUtc::from(dt));
+    }
+
+    None
+}
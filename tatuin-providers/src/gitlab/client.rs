@@ -1,20 +1,42 @@
 // SPDX-License-Identifier: MIT
 
-use super::structs::{Issue, Todo};
-use reqwest::header::HeaderMap;
+use super::structs::{Author, Issue, Milestone, Project, Todo};
+use crate::http::ReqwestClient;
 use serde::Serialize;
-use std::error::Error;
-use tatuin_core::filter::FilterState;
+use std::{error::Error, sync::Arc};
+use tatuin_core::{
+    filter::FilterState,
+    http::{Request, SharedHttpClient},
+};
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Debug, Default)]
 pub struct UpdateIssueRequest<'a> {
+    // the empty date removes the due date
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due_date: Option<&'a str>,
+    // close or reopen
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state_event: Option<&'a str>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CreateIssueRequest<'a> {
+    pub title: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'a str>,
+    // comma separated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub labels: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub milestone_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub due_date: Option<&'a str>,
 }
 
 pub struct Client {
     base_url: String,
-    default_header: HeaderMap,
-    client: reqwest::Client,
+    api_key: String,
+    http: SharedHttpClient,
 }
 
 impl std::fmt::Debug for Client {
@@ -25,15 +47,22 @@ impl std::fmt::Debug for Client {
 
 impl Client {
     pub fn new(base_url: &str, api_key: &str) -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert("Authorization", format!("Bearer {api_key}").parse().unwrap());
         Self {
             base_url: format!("{base_url}/api/v4"),
-            default_header: headers,
-            client: reqwest::Client::new(),
+            api_key: api_key.to_string(),
+            http: Arc::new(ReqwestClient::default()),
         }
     }
 
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    fn request(&self, r: Request) -> Request {
+        r.with_bearer_auth(&self.api_key)
+    }
+
     #[tracing::instrument(level = "info", target = "gitlab_client")]
     pub async fn todos(&self, state: &FilterState) -> Result<Vec<Todo>, Box<dyn Error>> {
         let mut result = Vec::new();
@@ -49,17 +78,13 @@ impl Client {
 
         loop {
             let r = self
-                .client
-                .get(format!(
+                .http
+                .send_checked(self.request(Request::get(&format!(
                     "{}/todos?page={page}&per_page={PER_PAGE}&{state_query}",
                     self.base_url
-                ))
-                .headers(self.default_header.clone())
-                .send()
+                ))))
                 .await?
-                .error_for_status()?
-                .json::<Vec<Todo>>()
-                .await;
+                .json::<Vec<Todo>>();
 
             match r {
                 Ok(mut v) => {
@@ -81,13 +106,10 @@ impl Client {
 
     pub async fn mark_todo_as_done(&self, id: &str) -> Result<(), Box<dyn Error>> {
         let _ = self
-            .client
-            .post(format!("{}/todos/{id}/mark_as_done", self.base_url))
-            .headers(self.default_header.clone())
-            .send()
+            .http
+            .send_checked(self.request(Request::post(&format!("{}/todos/{id}/mark_as_done", self.base_url))))
             .await?
-            .json::<Todo>()
-            .await?;
+            .json::<Todo>()?;
         Ok(())
     }
 
@@ -109,16 +131,13 @@ impl Client {
 
         loop {
             let r = self
-                .client
-                .get(format!(
+                .http
+                .send_checked(self.request(Request::get(&format!(
                     "{}/projects/{project_id}/issues?page={page}&per_page={PER_PAGE}&scope=all&{query}",
                     self.base_url
-                ))
-                .headers(self.default_header.clone())
-                .send()
+                ))))
                 .await?
-                .json::<Vec<Issue>>()
-                .await;
+                .json::<Vec<Issue>>();
 
             match r {
                 Ok(mut v) => {
@@ -148,16 +167,150 @@ impl Client {
         tracing::debug!(target:"gitlab_client", project_id=project_id, issue_iid=issue_iid, request=?r, "Patch issue");
 
         self
-            .client
-            .put(format!("{}/projects/{project_id}/issues/{issue_iid}", self.base_url))
-            .json(r)
-            .headers(self.default_header.clone())
-            .send()
+            .http
+            .send_checked(self.request(Request::put(&format!("{}/projects/{project_id}/issues/{issue_iid}", self.base_url)).with_json(r)?))
             .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"gitlab_client", project_id=project_id, issue_iid=issue_iid, request=?r, error=?e);
-                Box::<dyn Error>::from(e.to_string())
+                Box::<dyn Error>::from(e)
             })
     }
+
+    async fn get_pages<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<Vec<T>, Box<dyn Error>> {
+        let mut result = Vec::new();
+
+        const PER_PAGE: i8 = 100;
+        let mut page = 1;
+
+        loop {
+            let r = self
+                .http
+                .send_checked(self.request(Request::get(&format!(
+                    "{}/{path}&page={page}&per_page={PER_PAGE}",
+                    self.base_url
+                ))))
+                .await?
+                .json::<Vec<T>>();
+
+            match r {
+                Ok(mut v) => {
+                    if v.is_empty() {
+                        break;
+                    }
+                    result.append(&mut v);
+                    page += 1;
+                }
+                Err(e) => {
+                    tracing::error!(target:"gitlab_client", path=path, page=page, error=?e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // The todos by the parameters of the todos API, e.g. "action=review_requested&type=MergeRequest"
+    pub async fn todos_by_query(&self, query: &str) -> Result<Vec<Todo>, Box<dyn Error>> {
+        self.get_pages(format!("todos?{}", query.trim_start_matches('?')).as_str())
+            .await
+    }
+
+    // The issues assigned to the user, the prefix is empty for all of them or like "groups/<id>/" and "projects/<id>/"
+    pub async fn assigned_issues(&self, prefix: &str, state: &FilterState) -> Result<Vec<Issue>, Box<dyn Error>> {
+        let state_query = match state {
+            FilterState::Completed => "state=closed",
+            FilterState::Todo => "state=opened",
+            _ => return Ok(Vec::new()),
+        };
+        self.get_pages(format!("{prefix}issues?scope=assigned_to_me&{state_query}").as_str())
+            .await
+    }
+
+    // The authenticated user
+    pub async fn user(&self) -> Result<Author, Box<dyn Error>> {
+        Ok(self
+            .http
+            .send_checked(self.request(Request::get(&format!("{}/user", self.base_url))))
+            .await?
+            .json::<Author>()?)
+    }
+
+    // The projects the user is a member of and that have the issues enabled
+    pub async fn projects(&self) -> Result<Vec<Project>, Box<dyn Error>> {
+        self.get_pages("projects?membership=true&archived=false&with_issues_enabled=true&simple=true")
+            .await
+    }
+
+    pub async fn milestones(&self, project_id: i64) -> Result<Vec<Milestone>, Box<dyn Error>> {
+        self.get_pages(format!("projects/{project_id}/milestones?state=active").as_str())
+            .await
+    }
+
+    pub async fn create_issue(&self, project_id: i64, r: &CreateIssueRequest<'_>) -> Result<Issue, Box<dyn Error>> {
+        tracing::debug!(target:"gitlab_client", project_id=project_id, request=?r, "Create issue");
+
+        self.http
+            .send_checked(
+                self.request(Request::post(&format!("{}/projects/{project_id}/issues", self.base_url)).with_json(r)?),
+            )
+            .await?
+            .json::<Issue>()
+            .map_err(|e| {
+                tracing::error!(target:"gitlab_client", project_id=project_id, request=?r, error=?e);
+                e.into()
+            })
+    }
+
+    // Adds the issue to the todo list of the user
+    pub async fn add_issue_todo(&self, project_id: i64, issue_iid: i64) -> Result<(), Box<dyn Error>> {
+        self.http
+            .send_checked(self.request(Request::post(&format!("{}/projects/{project_id}/issues/{issue_iid}/todo", self.base_url))))
+            .await
+            .map(|_| ())
+            .map_err(|e| {
+                tracing::error!(target:"gitlab_client", project_id=project_id, issue_iid=issue_iid, error=?e, "Add the todo");
+                Box::<dyn Error>::from(e)
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn projects_test() {
+        const URL: &str =
+            "https://gitlab.com/api/v4/projects?membership=true&archived=false&with_issues_enabled=true&simple=true";
+        let project = serde_json::json!([{"id": 1, "name": "tatuin", "path": "tatuin"}]).to_string();
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(
+                    Method::Get,
+                    format!("{URL}&page=1&per_page=100").as_str(),
+                    Response::new(200, project.as_bytes()),
+                )
+                .with_response(
+                    Method::Get,
+                    format!("{URL}&page=2&per_page=100").as_str(),
+                    Response::new(200, b"[]"),
+                ),
+        );
+
+        let c = Client::new("https://gitlab.com", "token").with_http_client(http.clone());
+        let projects = c.projects().await.unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].name, "tatuin");
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[1].headers,
+            [("Authorization".to_string(), "Bearer token".to_string())]
+        );
+    }
 }
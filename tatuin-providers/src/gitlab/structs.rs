@@ -98,4 +98,19 @@ pub struct Issue {
     pub iid: i64,
     pub due_date: Option<String>,
     pub issue_type: String,
+    // the fields below are used by the issues provider, the todo provider needs only the due date
+    #[serde(default)]
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub web_url: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub milestone: Option<Milestone>,
+    #[serde(default)]
+    pub author: Option<Author>,
+    #[serde(default)]
+    pub assignees: Vec<Assignee>,
+    #[serde(default)]
+    pub user_notes_count: Option<i64>,
 }
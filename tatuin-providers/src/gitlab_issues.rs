@@ -0,0 +1,507 @@
+// SPDX-License-Identifier: MIT
+
+use crate::{
+    config::Config,
+    gitlab::{
+        client::{Client, UpdateIssueRequest},
+        str_to_date, structs,
+    },
+};
+use chrono::Utc;
+use std::{any::Any, sync::Arc};
+use tatuin_core::{
+    RichString, filter,
+    http::SharedHttpClient,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
+};
+use urlencoding::encode;
+
+use async_trait::async_trait;
+
+pub const PROVIDER_NAME: &str = "GitLab Issues";
+
+// The issues without a milestone are in the inbox project
+const NO_MILESTONE_ID: &str = "none";
+
+// The milestone of the issues is their project, the milestones of the groups and the projects are mixed
+#[derive(Clone, Debug)]
+pub struct Project {
+    milestone: Option<structs::Milestone>,
+    provider: String,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.milestone
+            .as_ref()
+            .and_then(|m| m.id)
+            .map_or(NO_MILESTONE_ID.to_string(), |id| id.to_string())
+    }
+
+    fn name(&self) -> String {
+        self.milestone
+            .as_ref()
+            .and_then(|m| m.title.clone())
+            .unwrap_or_else(|| "No milestone".to_string())
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        self.milestone
+            .as_ref()
+            .and_then(|m| m.description.clone())
+            .unwrap_or_default()
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        self.milestone.is_none()
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    issue: structs::Issue,
+    provider: String,
+    // the username of the token owner
+    me: Option<String>,
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.issue.id.to_string()
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(self.issue.title.as_deref().unwrap_or_default())
+    }
+
+    fn description(&self) -> Option<RichString> {
+        self.issue.description.as_deref().map(RichString::new)
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        str_to_date(self.issue.created_at.as_str())
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.issue.updated_at.as_deref().and_then(str_to_date)
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.issue.due_date.as_deref().and_then(str_to_date)
+    }
+
+    fn place(&self) -> String {
+        self.url()
+    }
+
+    fn state(&self) -> State {
+        match self.issue.state.as_deref() {
+            Some("opened") => State::Uncompleted,
+            Some("closed") => State::Completed,
+            _ => State::Unknown('?'),
+        }
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(Box::new(Project {
+            milestone: self.issue.milestone.clone(),
+            provider: self.provider.to_string(),
+        }))
+    }
+
+    fn url(&self) -> String {
+        self.issue.web_url.clone().unwrap_or_default()
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.issue.labels.clone()
+    }
+
+    fn assignee(&self) -> Option<String> {
+        let assignees = self
+            .issue
+            .assignees
+            .iter()
+            .filter_map(|a| a.name.clone().or(a.username.clone()))
+            .collect::<Vec<String>>();
+        (!assignees.is_empty()).then(|| assignees.join(", "))
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue.assignees.iter().any(|a| a.username.as_ref() == Some(me)))
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue.author.as_ref().and_then(|a| a.username.as_ref()) == Some(me))
+    }
+
+    fn comment_count(&self) -> Option<u64> {
+        u64::try_from(self.issue.user_notes_count?).ok()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: false,
+            is_removable: false,
+            available_states: vec![State::Uncompleted, State::Completed],
+            available_priorities: Vec::new(),
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+}
+
+// The comma-separated groups and projects, like "my-group, my-group/my-project" or their ids.
+// The issues of all the groups and projects are listed if both lists are empty.
+fn issue_prefixes(groups: &str, projects: &str) -> Vec<String> {
+    let items = |s: &str, kind: &str| {
+        s.split(',')
+            .map(str::trim)
+            .filter(|i| !i.is_empty())
+            .map(|i| format!("{kind}/{}/", encode(i)))
+            .collect::<Vec<String>>()
+    };
+    let mut result = items(groups, "groups");
+    result.extend(items(projects, "projects"));
+    if result.is_empty() {
+        result.push(String::new());
+    }
+    result
+}
+
+fn update_issue_request<'a>(state: Option<State>, due_date: Option<&'a str>) -> Result<UpdateIssueRequest<'a>, String> {
+    let state_event = match state {
+        Some(State::Completed) => Some("close"),
+        Some(State::Uncompleted) => Some("reopen"),
+        Some(s) => return Err(format!("The state {s} is unsupported")),
+        None => None,
+    };
+    Ok(UpdateIssueRequest { due_date, state_event })
+}
+
+pub struct Provider {
+    cfg: Config,
+    client: Client,
+    prefixes: Vec<String>,
+    tasks: Vec<Task>,
+    me: Option<String>,
+    last_filter: Option<filter::Filter>,
+    last_sync: Option<DateTimeUtc>,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, base_url: &str, api_key: &str, groups: &str, projects: &str) -> Self {
+        Self {
+            cfg,
+            client: Client::new(base_url, api_key),
+            prefixes: issue_prefixes(groups, projects),
+            tasks: Vec::new(),
+            me: None,
+            last_filter: None,
+            last_sync: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    async fn load_me(&mut self) {
+        if self.me.is_none() {
+            // the tasks are shown without the authorship info if the token can't read the user
+            match self.client.user().await {
+                Ok(u) => self.me = u.username,
+                Err(e) => tracing::warn!(target:"gitlab_issues", error=?e, "Get the user"),
+            }
+        }
+    }
+
+    async fn load_tasks(&mut self, states: &[filter::FilterState]) -> Result<(), ProviderError> {
+        self.load_me().await;
+        for st in states {
+            for prefix in &self.prefixes {
+                for issue in self.client.assigned_issues(prefix, st).await? {
+                    // the issue of the project is listed by its group too
+                    if self.tasks.iter().any(|t| t.issue.id == issue.id) {
+                        continue;
+                    }
+                    self.tasks.push(Task {
+                        issue,
+                        provider: self.cfg.name(),
+                        me: self.me.clone(),
+                    });
+                }
+            }
+        }
+        self.last_sync = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn patch_task(&mut self, t: &Task, p: &TaskPatch) -> Result<(), PatchError> {
+        let to_patch_error = |error: String| PatchError {
+            task: Arc::new(t.clone()),
+            error,
+            kind: PatchErrorKind::Other,
+        };
+
+        let due = p.due.value().map(|due| {
+            let dt: Option<DateTimeUtc> = due.into();
+            dt.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default()
+        });
+        let r = update_issue_request(p.state.value(), due.as_deref()).map_err(to_patch_error)?;
+        if r.due_date.is_none() && r.state_event.is_none() {
+            return Ok(());
+        }
+
+        self.client
+            .patch_issue(t.issue.project_id, t.issue.iid, &r)
+            .await
+            .map_err(|e| PatchError {
+                kind: PatchErrorKind::of(e.as_ref()),
+                ..to_patch_error(e.to_string())
+            })
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    // The milestones of the loaded issues, the open ones are loaded if nothing is loaded yet
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        if self.tasks.is_empty() {
+            self.load_tasks(&[filter::FilterState::Todo]).await?;
+        }
+
+        let mut projects: Vec<Box<dyn ProjectTrait>> = vec![Box::new(Project {
+            milestone: None,
+            provider: self.name(),
+        })];
+        for p in self.tasks.iter().filter_map(|t| t.project()) {
+            if !projects.iter().any(|existed| existed.id() == p.id()) {
+                projects.push(p);
+            }
+        }
+        Ok(projects)
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        if self.last_filter.as_ref().is_some_and(|last_filter| last_filter != f) {
+            self.tasks.clear();
+        }
+
+        if self.tasks.is_empty() {
+            self.load_tasks(&f.states).await?;
+        }
+
+        let project_id = project.map(|p| p.id());
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        for t in &self.tasks {
+            if f.accept(t)
+                && project_id
+                    .as_ref()
+                    .is_none_or(|id| t.project().is_some_and(|p| p.id() == *id))
+            {
+                result.push(Box::new(t.clone()));
+            }
+        }
+
+        self.last_filter = Some(f.clone());
+
+        Ok(result)
+    }
+
+    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+        Err(ProviderError::other(
+            "The issues can't be created without the GitLab project, use the GitLab TODO provider to create them",
+        ))
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        for p in patches {
+            tracing::debug!(target:"gitlab_issues_patch_task", patch=p.to_string(), "Apply a patch");
+            let task = p.task.as_ref().unwrap();
+
+            match task.as_any().downcast_ref::<Task>() {
+                Some(t) => {
+                    if let Err(e) = self.patch_task(t, p).await {
+                        errors.push(e);
+                    }
+                }
+                None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
+            }
+        }
+
+        self.tasks.clear();
+
+        errors
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        self.tasks.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: false,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: true,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn issue_prefixes_test() {
+        struct Case<'a> {
+            name: &'a str,
+            groups: &'a str,
+            projects: &'a str,
+            expected: Vec<&'a str>,
+        }
+        let cases = [
+            Case {
+                name: "all the issues",
+                groups: "",
+                projects: " ",
+                expected: vec![""],
+            },
+            Case {
+                name: "groups and projects",
+                groups: "my-group, 42",
+                projects: "my-group/sub/tatuin",
+                expected: vec!["groups/my-group/", "groups/42/", "projects/my-group%2Fsub%2Ftatuin/"],
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                issue_prefixes(c.groups, c.projects),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn update_issue_request_test() {
+        let r = update_issue_request(Some(State::Completed), Some("2025-01-02")).unwrap();
+        assert_eq!(
+            serde_json::to_value(&r).unwrap(),
+            serde_json::json!({"due_date": "2025-01-02", "state_event": "close"})
+        );
+        let r = update_issue_request(Some(State::Uncompleted), None).unwrap();
+        assert_eq!(
+            serde_json::to_value(&r).unwrap(),
+            serde_json::json!({"state_event": "reopen"})
+        );
+        let r = update_issue_request(None, Some("")).unwrap();
+        assert_eq!(serde_json::to_value(&r).unwrap(), serde_json::json!({"due_date": ""}));
+        assert!(update_issue_request(Some(State::InProgress), None).is_err());
+    }
+
+    #[test]
+    fn project_test() {
+        let milestone = |id: i64, title: &str| structs::Milestone {
+            id: Some(id),
+            title: Some(title.to_string()),
+            ..structs::Milestone::default()
+        };
+        let task = |m: Option<structs::Milestone>, state: &str| Task {
+            issue: structs::Issue {
+                state: Some(state.to_string()),
+                milestone: m,
+                ..structs::Issue::default()
+            },
+            provider: "gitlab".to_string(),
+            me: None,
+        };
+
+        let p = task(Some(milestone(7, "v1.0")), "opened").project().unwrap();
+        assert_eq!(p.id(), "7");
+        assert_eq!(p.name(), "v1.0");
+        assert!(!p.is_inbox());
+
+        let p = task(None, "closed").project().unwrap();
+        assert_eq!(p.id(), NO_MILESTONE_ID);
+        assert!(p.is_inbox());
+
+        assert_eq!(task(None, "opened").state(), State::Uncompleted);
+        assert_eq!(task(None, "closed").state(), State::Completed);
+        assert_eq!(task(None, "locked").state(), State::Unknown('?'));
+    }
+}
@@ -3,30 +3,44 @@
 use crate::{
     config::Config,
     gitlab::{
-        client::{Client, UpdateIssueRequest},
-        structs,
+        client::{Client, CreateIssueRequest, UpdateIssueRequest},
+        str_to_date, structs,
     },
 };
-use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use std::{any::Any, collections::HashMap, error::Error};
+use chrono::{NaiveTime, Utc};
+use std::{any::Any, collections::HashMap, error::Error, sync::Arc};
 use tatuin_core::{
-    RichString, StringError, filter,
+    RichString, filter,
+    http::SharedHttpClient,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
     task::{DateTimeUtc, PatchPolicy, State, Task as TaskTrait, due_group},
-    task_patch::{DatePatchItem, PatchError, TaskPatch},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
 };
 
 use async_trait::async_trait;
 
 pub const PROVIDER_NAME: &str = "GitLabTODO";
 
+// The membership project or its active milestone, the issues can be created in both of them
 #[derive(Clone)]
 pub struct Project {
     p: structs::Project,
+    milestone: Option<structs::Milestone>,
     provider: String,
 }
 
+const MILESTONE_SEPARATOR: char = '/';
+
+// The project id and the milestone id of the project or the milestone
+fn parse_project_id(id: &str) -> Result<(i64, Option<i64>), ProviderError> {
+    let wrong_id = |_| ProviderError::other(format!("Wrong project id '{id}'").as_str());
+    match id.split_once(MILESTONE_SEPARATOR) {
+        Some((p, m)) => Ok((p.parse().map_err(wrong_id)?, Some(m.parse().map_err(wrong_id)?))),
+        None => Ok((id.parse().map_err(wrong_id)?, None)),
+    }
+}
+
 impl std::fmt::Debug for Project {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -40,11 +54,17 @@ impl std::fmt::Debug for Project {
 
 impl ProjectTrait for Project {
     fn id(&self) -> String {
-        self.p.id.to_string()
+        match self.milestone.as_ref().and_then(|m| m.id) {
+            Some(m) => format!("{}{MILESTONE_SEPARATOR}{m}", self.p.id),
+            None => self.p.id.to_string(),
+        }
     }
 
     fn name(&self) -> String {
-        self.p.name.to_string()
+        match self.milestone.as_ref().and_then(|m| m.title.as_ref()) {
+            Some(m) => format!("{} / {m}", self.p.name),
+            None => self.p.name.to_string(),
+        }
     }
 
     fn provider(&self) -> String {
@@ -56,7 +76,7 @@ impl ProjectTrait for Project {
     }
 
     fn parent_id(&self) -> Option<String> {
-        None
+        self.milestone.as_ref().map(|_| self.p.id.to_string())
     }
 
     fn is_inbox(&self) -> bool {
@@ -77,23 +97,21 @@ pub struct Task {
     todo: structs::Todo,
     issue: Option<structs::Issue>,
     provider: String,
+    // the username of the token owner
+    me: Option<String>,
 }
 
-fn str_to_date(s: &str) -> Option<DateTimeUtc> {
-    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
-        let dt = d.and_hms_opt(0, 0, 0)?;
-        return Some(DateTimeUtc::from_naive_utc_and_offset(dt, Utc));
-    }
-
-    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f") {
-        return Some(DateTimeUtc::from_naive_utc_and_offset(dt, Utc));
-    }
-
-    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
-        return Some(DateTimeUtc::from(dt));
+impl Task {
+    fn is_in_project(&self, (project_id, milestone_id): (i64, Option<i64>)) -> bool {
+        self.todo.project.as_ref().is_some_and(|p| p.id == project_id)
+            && milestone_id.is_none_or(|m| {
+                self.todo
+                    .target
+                    .as_ref()
+                    .and_then(|t| t.milestone.as_ref())
+                    .is_some_and(|tm| tm.id == Some(m))
+            })
     }
-
-    None
 }
 
 impl TaskTrait for Task {
@@ -109,6 +127,15 @@ impl TaskTrait for Task {
         str_to_date(self.todo.created_at.as_str())
     }
 
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.todo
+            .target
+            .as_ref()
+            .and_then(|t| t.updated_at.as_ref())
+            .or(self.todo.updated_at.as_ref())
+            .and_then(|s| str_to_date(s))
+    }
+
     fn due(&self) -> Option<DateTimeUtc> {
         let _entered = tracing::span!(tracing::Level::TRACE, "gitlab_todo_task").entered();
 
@@ -133,7 +160,7 @@ impl TaskTrait for Task {
         match self.todo.state.as_str() {
             "pending" => State::Uncompleted,
             "done" => State::Completed,
-            _ => panic!("Unknown state {}", self.todo.state),
+            _ => State::Unknown('?'),
         }
     }
 
@@ -145,6 +172,7 @@ impl TaskTrait for Task {
         self.todo.project.as_ref().map(|p| {
             let result: Box<dyn ProjectTrait> = Box::new(Project {
                 p: p.clone(),
+                milestone: None,
                 provider: self.provider.to_string(),
             });
             result
@@ -155,6 +183,38 @@ impl TaskTrait for Task {
         self.todo.target_url.to_string()
     }
 
+    fn labels(&self) -> Vec<String> {
+        self.todo
+            .target
+            .as_ref()
+            .and_then(|t| t.labels.clone())
+            .unwrap_or_default()
+    }
+
+    fn assignee(&self) -> Option<String> {
+        let a = self.todo.target.as_ref()?.assignee.as_ref()?;
+        a.name.clone().or(a.username.clone())
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        if self.todo.action_name.as_deref() == Some("assigned") {
+            return Some(true);
+        }
+        let me = self.me.as_ref()?;
+        let target = self.todo.target.as_ref()?;
+        Some(target.assignee.as_ref().and_then(|a| a.username.as_ref()) == Some(me))
+    }
+
+    fn comment_count(&self) -> Option<u64> {
+        u64::try_from(self.todo.target.as_ref()?.user_notes_count?).ok()
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        let target = self.todo.target.as_ref()?;
+        Some(target.author.as_ref().and_then(|a| a.username.as_ref()) == Some(me))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -183,7 +243,10 @@ pub struct Provider {
     cfg: Config,
     client: Client,
     tasks: Vec<Task>,
+    projects: Vec<Project>,
+    me: Option<String>,
     last_filter: Option<filter::Filter>,
+    last_sync: Option<DateTimeUtc>,
 }
 
 impl Provider {
@@ -192,8 +255,50 @@ impl Provider {
             cfg,
             client: Client::new(base_url, api_key),
             tasks: Vec::new(),
+            projects: Vec::new(),
+            me: None,
             last_filter: None,
+            last_sync: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    async fn load_me(&mut self) {
+        if self.me.is_none() {
+            // the tasks are shown without the assignment info if the token can't read the user
+            match self.client.user().await {
+                Ok(u) => self.me = u.username,
+                Err(e) => tracing::warn!(target:"gitlab_todo", error=?e, "Get the user"),
+            }
+        }
+    }
+
+    async fn load_projects(&mut self) -> Result<(), Box<dyn Error>> {
+        if !self.projects.is_empty() {
+            return Ok(());
+        }
+
+        let projects = self.client.projects().await?;
+        for p in projects {
+            let milestones = self.client.milestones(p.id).await?;
+            self.projects.push(Project {
+                p: p.clone(),
+                milestone: None,
+                provider: self.name(),
+            });
+            for m in milestones {
+                self.projects.push(Project {
+                    p: p.clone(),
+                    milestone: Some(m),
+                    provider: self.name(),
+                });
+            }
         }
+        Ok(())
     }
 
     async fn load_todos_issues(&mut self, todos: &[structs::Todo]) -> Result<Vec<structs::Issue>, Box<dyn Error>> {
@@ -223,6 +328,28 @@ impl Provider {
         Ok(issues)
     }
 
+    async fn todos_to_tasks(&mut self, todos: Vec<structs::Todo>) -> Result<Vec<Task>, Box<dyn Error>> {
+        let issues = self.load_todos_issues(&todos).await?;
+
+        tracing::debug!(target:"gitlab_todo", issues=?issues, "Get Issues");
+        Ok(todos
+            .into_iter()
+            .map(|t| {
+                let id = t.target.as_ref().map(|t| t.id);
+                Task {
+                    todo: t,
+                    issue: if let Some(id) = id {
+                        issues.iter().find(|issue| issue.id == id).cloned()
+                    } else {
+                        None
+                    },
+                    provider: self.name(),
+                    me: self.me.clone(),
+                }
+            })
+            .collect())
+    }
+
     async fn patch_task_state(&mut self, t: &Task, state: &State) -> Result<(), PatchError> {
         match state {
             State::Completed => self
@@ -230,19 +357,22 @@ impl Provider {
                 .mark_todo_as_done(t.id().as_str())
                 .await
                 .map_err(|e| PatchError {
-                    task: t.clone_boxed(),
+                    task: Arc::new(t.clone()),
                     error: e.to_string(),
+                    kind: PatchErrorKind::of(e.as_ref()),
                 }),
             State::InProgress | State::Uncompleted | State::Unknown(_) => Err(PatchError {
-                task: t.clone_boxed(),
+                task: Arc::new(t.clone()),
                 error: format!("The state {state} is unsupported"),
+                kind: PatchErrorKind::Other,
             }),
         }
     }
     async fn patch_task_due(&mut self, t: &Task, due: &DatePatchItem) -> Result<(), PatchError> {
         let issue = t.issue.as_ref().ok_or(PatchError {
-            task: t.clone_boxed(),
+            task: Arc::new(t.clone()),
             error: "The task doesn't support due changing".to_string(),
+            kind: PatchErrorKind::Other,
         })?;
         let dt: Option<DateTimeUtc> = (*due).into();
         let d = dt.map(|d| d.format("%Y-%m-%d").to_string()).unwrap_or_default();
@@ -253,12 +383,14 @@ impl Provider {
                 issue.iid,
                 &UpdateIssueRequest {
                     due_date: Some(d.as_str()),
+                    ..UpdateIssueRequest::default()
                 },
             )
             .await
             .map_err(|e| PatchError {
-                task: t.clone_boxed(),
+                task: Arc::new(t.clone()),
                 error: e.to_string(),
+                kind: PatchErrorKind::of(e.as_ref()),
             })
     }
 }
@@ -269,10 +401,30 @@ impl std::fmt::Debug for Provider {
     }
 }
 
+fn create_issue_request<'a>(
+    tp: &'a TaskPatch,
+    milestone_id: Option<i64>,
+    due_date: Option<&'a str>,
+) -> Result<CreateIssueRequest<'a>, ProviderError> {
+    let title = tp
+        .name
+        .ref_value()
+        .filter(|n| !n.is_empty())
+        .ok_or(ProviderError::other("The issue title is required"))?;
+    Ok(CreateIssueRequest {
+        title,
+        description: tp.description.ref_value().map(String::as_str).filter(|d| !d.is_empty()),
+        labels: tp.labels.ref_value().filter(|l| !l.is_empty()).map(|l| l.join(",")),
+        milestone_id,
+        due_date,
+    })
+}
+
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
-        Ok(Vec::new())
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        self.load_projects().await?;
+        Ok(self.projects.iter().map(|p| p.clone_boxed()).collect())
     }
 }
 
@@ -280,9 +432,10 @@ impl ProjectProviderTrait for Provider {
 impl TaskProviderTrait for Provider {
     async fn list(
         &mut self,
-        _project: Option<Box<dyn ProjectTrait>>,
+        project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let project = project.map(|p| parse_project_id(p.id().as_str())).transpose()?;
         let mut should_clear = false;
         if let Some(last_filter) = self.last_filter.as_mut() {
             should_clear = last_filter != f;
@@ -293,30 +446,19 @@ impl TaskProviderTrait for Provider {
         }
 
         if self.tasks.is_empty() {
+            self.load_me().await;
             for st in &f.states {
                 let todos = self.client.todos(st).await?;
-                let issues = self.load_todos_issues(&todos).await?;
-
-                tracing::debug!(target:"gitlab_todo", issues=?issues, "Get Issues");
-                for t in todos {
-                    let id = t.target.as_ref().map(|t| t.id);
-                    self.tasks.push(Task {
-                        todo: t,
-                        issue: if let Some(id) = id {
-                            issues.iter().find(|issue| issue.id == id).cloned()
-                        } else {
-                            None
-                        },
-                        provider: self.name(),
-                    })
-                }
+                let mut tasks = self.todos_to_tasks(todos).await?;
+                self.tasks.append(&mut tasks);
             }
+            self.last_sync = Some(Utc::now());
         }
 
         let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
 
         for t in &self.tasks {
-            if f.due.contains(&due_group(&t.due())) {
+            if f.due.contains(&due_group(&t.due())) && project.is_none_or(|p| t.is_in_project(p)) {
                 result.push(Box::new(t.clone()));
             }
         }
@@ -326,8 +468,30 @@ impl TaskProviderTrait for Provider {
         Ok(result)
     }
 
-    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), StringError> {
-        Err(StringError::new("Task creation is not supported"))
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        self.load_me().await;
+        let todos = self.client.todos_by_query(query).await?;
+        Ok(self
+            .todos_to_tasks(todos)
+            .await?
+            .into_iter()
+            .map(|t| Box::new(t) as Box<dyn TaskTrait>)
+            .collect())
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let (project_id, milestone_id) = parse_project_id(project_id)?;
+        let due: Option<DateTimeUtc> = tp.due.value().and_then(|d| d.into());
+        let due = due.map(|d| d.format("%Y-%m-%d").to_string());
+
+        let issue = self
+            .client
+            .create_issue(project_id, &create_issue_request(tp, milestone_id, due.as_deref())?)
+            .await?;
+        // the issues created by the user aren't in the todo list
+        self.client.add_issue_todo(issue.project_id, issue.iid).await?;
+        self.tasks.clear();
+        Ok(())
     }
 
     async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
@@ -339,7 +503,10 @@ impl TaskProviderTrait for Provider {
 
             let task = match task.as_any().downcast_ref::<Task>() {
                 Some(t) => t,
-                None => panic!("Wrong casting!"),
+                None => {
+                    errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                    continue;
+                }
             };
             if let Some(state) = &p.state.value()
                 && let Err(e) = self.patch_task_state(task, state).await
@@ -371,9 +538,183 @@ impl ProviderTrait for Provider {
 
     async fn reload(&mut self) {
         self.tasks.clear();
+        self.projects.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
     }
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities { create_task: false }
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: true,
+            queries: true,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::task_patch::ValuePatch;
+
+    #[test]
+    fn state_test() {
+        struct Case<'a> {
+            name: &'a str,
+            state: &'a str,
+            expected: State,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "pending",
+                state: "pending",
+                expected: State::Uncompleted,
+            },
+            Case {
+                name: "done",
+                state: "done",
+                expected: State::Completed,
+            },
+            Case {
+                name: "new state from the API",
+                state: "snoozed",
+                expected: State::Unknown('?'),
+            },
+        ];
+
+        for c in CASES {
+            let t = Task {
+                todo: structs::Todo {
+                    state: c.state.to_string(),
+                    ..structs::Todo::default()
+                },
+                issue: None,
+                provider: String::new(),
+                me: None,
+            };
+            assert_eq!(t.state(), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn involvement_test() {
+        let user = |name: &str| structs::Author {
+            username: Some(name.to_string()),
+            ..structs::Author::default()
+        };
+        let task = |action: &str, me: Option<&str>| Task {
+            todo: structs::Todo {
+                action_name: Some(action.to_string()),
+                target: Some(structs::Target {
+                    author: Some(user("me")),
+                    assignee: Some(structs::Assignee {
+                        name: Some("Jane".to_string()),
+                        username: Some("jane".to_string()),
+                        ..structs::Assignee::default()
+                    }),
+                    ..structs::Target::default()
+                }),
+                ..structs::Todo::default()
+            },
+            issue: None,
+            provider: String::new(),
+            me: me.map(str::to_string),
+        };
+
+        let t = task("mentioned", Some("me"));
+        assert_eq!(t.assignee(), Some("Jane".to_string()));
+        assert_eq!(t.is_assigned_to_me(), Some(false));
+        assert_eq!(t.is_created_by_me(), Some(true));
+
+        assert_eq!(task("assigned", None).is_assigned_to_me(), Some(true));
+        assert_eq!(task("mentioned", None).is_assigned_to_me(), None);
+        assert_eq!(task("mentioned", Some("jane")).is_created_by_me(), Some(false));
+    }
+
+    #[test]
+    fn parse_project_id_test() {
+        struct Case<'a> {
+            name: &'a str,
+            id: &'a str,
+            expected: Option<(i64, Option<i64>)>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "project",
+                id: "42",
+                expected: Some((42, None)),
+            },
+            Case {
+                name: "milestone",
+                id: "42/7",
+                expected: Some((42, Some(7))),
+            },
+            Case {
+                name: "wrong project",
+                id: "group/project",
+                expected: None,
+            },
+            Case {
+                name: "wrong milestone",
+                id: "42/",
+                expected: None,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(parse_project_id(c.id).ok(), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn project_test() {
+        let p = structs::Project {
+            id: 42,
+            name: "tatuin".to_string(),
+            ..structs::Project::default()
+        };
+        let milestone = Project {
+            p: p.clone(),
+            milestone: Some(structs::Milestone {
+                id: Some(7),
+                title: Some("v1.0".to_string()),
+                ..structs::Milestone::default()
+            }),
+            provider: String::new(),
+        };
+        assert_eq!(milestone.id(), "42/7");
+        assert_eq!(milestone.name(), "tatuin / v1.0");
+        assert_eq!(milestone.parent_id(), Some("42".to_string()));
+        assert_eq!(parse_project_id(&milestone.id()).unwrap(), (42, Some(7)));
+    }
+
+    #[test]
+    fn create_issue_request_test() {
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Fix the build".to_string()),
+            description: ValuePatch::Value(String::new()),
+            labels: ValuePatch::Value(vec!["bug".to_string(), "ci".to_string()]),
+            ..TaskPatch::default()
+        };
+        assert_eq!(
+            serde_json::to_value(create_issue_request(&tp, Some(7), Some("2025-01-02")).unwrap()).unwrap(),
+            serde_json::json!({
+                "title": "Fix the build",
+                "labels": "bug,ci",
+                "milestone_id": 7,
+                "due_date": "2025-01-02",
+            })
+        );
+        assert!(create_issue_request(&TaskPatch::default(), None, None).is_err());
     }
 }
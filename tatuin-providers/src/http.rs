@@ -0,0 +1,54 @@
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use tatuin_core::http::{self, HttpClient, Method, Request};
+use tatuin_core::provider::ProviderError;
+
+pub fn from_reqwest(e: reqwest::Error) -> ProviderError {
+    let message = e.to_string();
+    if let Some(st) = e.status() {
+        ProviderError::from_http_status(st.as_u16(), None, &message)
+    } else if e.is_connect() || e.is_timeout() || e.is_request() {
+        ProviderError::Network(message)
+    } else {
+        ProviderError::Other(message)
+    }
+}
+
+// The HTTP client of the core traits over reqwest, the providers use it unless the other client is injected
+#[derive(Default)]
+pub struct ReqwestClient {
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl HttpClient for ReqwestClient {
+    async fn send(&self, r: Request) -> Result<http::Response, ProviderError> {
+        let method = match r.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+            Method::Propfind => reqwest::Method::from_bytes(b"PROPFIND").unwrap(),
+        };
+
+        let mut builder = self.client.request(method, &r.url);
+        for (name, value) in &r.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = r.body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await.map_err(from_reqwest)?;
+        let status = response.status().as_u16();
+        let headers = response
+            .headers()
+            .iter()
+            .filter_map(|(n, v)| v.to_str().ok().map(|v| (n.to_string(), v.to_string())))
+            .collect();
+        let body = response.bytes().await.map_err(from_reqwest)?.to_vec();
+        Ok(http::Response { status, headers, body })
+    }
+}
@@ -1,5 +1,6 @@
 // SPDX-License-Identifier: MIT
 
+mod alarm;
 mod client;
 mod priority;
 mod task;
@@ -11,11 +12,14 @@ use client::Client;
 pub use client::parse_calendar;
 pub use task::{Task, TaskType, property_to_str};
 use tatuin_core::{
-    StringError, filter,
+    filter,
+    fs::SharedFileSystem,
+    http::SharedHttpClient,
+    intern::intern,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
-    task::Task as TaskTrait,
-    task_patch::{PatchError, TaskPatch},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, Task as TaskTrait},
+    task_patch::{PatchError, PatchErrorKind, TaskPatch},
 };
 
 use crate::config::Config;
@@ -27,18 +31,31 @@ pub struct Provider {
 
     c: Client,
     tasks: Vec<Task>,
+    last_sync: Option<DateTimeUtc>,
 }
 
 impl Provider {
     pub fn new(cfg: Config, url: &str) -> Result<Self, Box<dyn Error>> {
         let mut c = Client::new(url);
         c.set_cache_folder(&cfg.cache_path()?);
+        c.set_cipher(cfg.cipher());
         Ok(Self {
             cfg,
             c,
             tasks: Vec::new(),
+            last_sync: None,
         })
     }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.c = self.c.with_http_client(http);
+        self
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.c = self.c.with_file_system(fs);
+        self
+    }
 }
 
 impl std::fmt::Debug for Provider {
@@ -49,8 +66,8 @@ impl std::fmt::Debug for Provider {
 
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
-        Err(StringError::new("not implemented"))
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        Err(ProviderError::other("not implemented"))
     }
 }
 
@@ -61,8 +78,9 @@ impl TaskProviderTrait for Provider {
         &mut self,
         _project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
         if self.tasks.is_empty() {
+            let name = intern(self.cfg.name().as_str());
             self.c.download_calendar().await?;
             self.tasks = self
                 .c
@@ -72,21 +90,30 @@ impl TaskProviderTrait for Provider {
                 .filter(|t| f.accept(*t))
                 .map(|t| {
                     let mut task = t.clone();
-                    task.set_provider(self.cfg.name().as_str());
+                    task.set_provider(name.clone());
                     task
                 })
                 .collect();
+            self.last_sync = Some(chrono::Utc::now());
         }
 
         return Ok(self.tasks.iter().map(|t| t.clone_boxed()).collect());
     }
 
-    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), StringError> {
-        panic!("Not implemented")
+    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+        Err(ProviderError::other("Task creation is not supported"))
     }
 
-    async fn update(&mut self, _patches: &[TaskPatch]) -> Vec<PatchError> {
-        panic!("Not implemented")
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        patches
+            .iter()
+            .filter_map(|p| p.task.as_ref())
+            .map(|t| PatchError {
+                task: t.clone(),
+                error: "Task updating is not supported".to_string(),
+                kind: PatchErrorKind::Other,
+            })
+            .collect()
     }
 }
 
@@ -104,7 +131,29 @@ impl ProviderTrait for Provider {
         self.tasks.clear();
     }
 
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: self.cfg.cache_path().ok(),
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    async fn clear_cache(&mut self) -> Result<(), ProviderError> {
+        self.c.clear_cache().await?;
+        self.tasks.clear();
+        Ok(())
+    }
+
     fn capabilities(&self) -> Capabilities {
-        Capabilities { create_task: false }
+        Capabilities {
+            create_task: false,
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
     }
 }
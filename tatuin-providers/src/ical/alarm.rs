@@ -0,0 +1,170 @@
+// SPDX-License-Identifier: MIT
+
+// The VALARM components of the events and the todos are turned into the reminder times
+
+use ical::{parser::ical::component::IcalAlarm, property::Property};
+use tatuin_core::task::DateTimeUtc;
+
+use super::task::{Task, dt_from_property, duration_from_str};
+
+fn param<'a>(p: &'a Property, name: &str) -> Option<&'a str> {
+    p.params
+        .as_ref()?
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .and_then(|(_, v)| v.first())
+        .map(String::as_str)
+}
+
+fn end(t: &Task) -> Option<DateTimeUtc> {
+    t.end
+        .or(t.due)
+        .or_else(|| t.start.zip(t.duration).and_then(|(s, d)| s.checked_add_signed(d)))
+}
+
+// The trigger is either an absolute time or a duration relative to the start (by default) or the end of the task
+fn trigger_time(trigger: &Property, t: &Task) -> Option<DateTimeUtc> {
+    if param(trigger, "VALUE").is_some_and(|v| v.eq_ignore_ascii_case("DATE-TIME")) {
+        return dt_from_property(trigger);
+    }
+
+    let value = trigger.value.as_ref()?.trim();
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(v) => (true, v),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let d = duration_from_str(value)?;
+
+    let anchor = if param(trigger, "RELATED").is_some_and(|r| r.eq_ignore_ascii_case("END")) {
+        end(t)
+    } else {
+        // the todos often have the due date only
+        t.start.or(t.due)
+    }?;
+
+    if negative {
+        anchor.checked_sub_signed(d)
+    } else {
+        anchor.checked_add_signed(d)
+    }
+}
+
+pub fn reminders(alarms: &[IcalAlarm], t: &Task) -> Vec<DateTimeUtc> {
+    let mut result = alarms
+        .iter()
+        .filter_map(|a| a.properties.iter().find(|p| p.name == "TRIGGER"))
+        .filter_map(|p| trigger_time(p, t))
+        .collect::<Vec<DateTimeUtc>>();
+    result.sort();
+    result.dedup();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn dt(s: &str) -> DateTimeUtc {
+        chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")
+            .unwrap()
+            .and_utc()
+    }
+
+    fn alarm(params: &[(&str, &str)], value: &str) -> IcalAlarm {
+        IcalAlarm {
+            properties: vec![
+                Property {
+                    name: "ACTION".to_string(),
+                    params: None,
+                    value: Some("DISPLAY".to_string()),
+                },
+                Property {
+                    name: "TRIGGER".to_string(),
+                    params: (!params.is_empty()).then(|| {
+                        params
+                            .iter()
+                            .map(|(n, v)| (n.to_string(), vec![v.to_string()]))
+                            .collect()
+                    }),
+                    value: Some(value.to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn reminders_test() {
+        struct Case<'a> {
+            name: &'a str,
+            start: Option<&'a str>,
+            due: Option<&'a str>,
+            alarms: Vec<IcalAlarm>,
+            expected: &'a [&'a str],
+        }
+        let cases = [
+            Case {
+                name: "no alarms",
+                start: None,
+                due: Some("2025-01-02 10:00"),
+                alarms: Vec::new(),
+                expected: &[],
+            },
+            Case {
+                name: "before the start",
+                start: Some("2025-01-02 10:00"),
+                due: Some("2025-01-03 10:00"),
+                alarms: vec![alarm(&[], "-PT15M")],
+                expected: &["2025-01-02 09:45"],
+            },
+            Case {
+                name: "relative to the due date",
+                start: Some("2025-01-02 10:00"),
+                due: Some("2025-01-03 10:00"),
+                alarms: vec![alarm(&[("RELATED", "END")], "-P1D"), alarm(&[], "PT1H")],
+                expected: &["2025-01-02 10:00", "2025-01-02 11:00"],
+            },
+            Case {
+                name: "todo without the start",
+                start: None,
+                due: Some("2025-01-03 10:00"),
+                alarms: vec![alarm(&[], "-PT30M")],
+                expected: &["2025-01-03 09:30"],
+            },
+            Case {
+                name: "absolute time",
+                start: None,
+                due: None,
+                alarms: vec![alarm(&[("VALUE", "DATE-TIME")], "20250101T080000Z")],
+                expected: &["2025-01-01 08:00"],
+            },
+            Case {
+                name: "no anchor",
+                start: None,
+                due: None,
+                alarms: vec![alarm(&[], "-PT15M")],
+                expected: &[],
+            },
+            Case {
+                name: "wrong trigger",
+                start: Some("2025-01-02 10:00"),
+                due: None,
+                alarms: vec![alarm(&[], "soon")],
+                expected: &[],
+            },
+        ];
+
+        for c in cases {
+            let t = Task {
+                start: c.start.map(dt),
+                due: c.due.map(dt),
+                ..Task::default()
+            };
+            assert_eq!(
+                reminders(&c.alarms, &t),
+                c.expected.iter().map(|s| dt(s)).collect::<Vec<DateTimeUtc>>(),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
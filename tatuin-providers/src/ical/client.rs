@@ -2,9 +2,9 @@
 
 use std::{
     error::Error,
-    fs::File,
     io::{BufReader, Cursor},
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
 use ical::{
@@ -12,7 +12,13 @@ use ical::{
     parser::ical::component::{IcalEvent, IcalTodo},
 };
 
-use crate::ical::task::TaskType;
+use tatuin_core::{
+    crypto::{Cipher, decrypt_content, encrypt_content},
+    fs::{LocalFileSystem, SharedFileSystem},
+    http::{Request, SharedHttpClient},
+};
+
+use crate::{http::ReqwestClient, ical::task::TaskType};
 
 use super::task::Task;
 
@@ -21,6 +27,9 @@ const FILE_NAME: &str = "calendar.ics";
 pub struct Client {
     url: String,
     file_name: PathBuf,
+    cipher: Option<Cipher>,
+    http: SharedHttpClient,
+    fs: SharedFileSystem,
 }
 
 impl Client {
@@ -28,28 +37,60 @@ impl Client {
         Self {
             url: url.to_string(),
             file_name: tatuin_core::folders::temp_folder().join(FILE_NAME),
+            cipher: None,
+            http: Arc::new(ReqwestClient::default()),
+            fs: LocalFileSystem::shared(),
         }
     }
 
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.fs = fs;
+        self
+    }
+
     pub fn set_cache_folder(&mut self, p: &Path) {
         self.file_name = p.join(FILE_NAME).to_path_buf();
     }
 
+    pub fn set_cipher(&mut self, cipher: Option<Cipher>) {
+        self.cipher = cipher;
+    }
+
     pub async fn download_calendar(&self) -> Result<(), Box<dyn Error>> {
-        let response = reqwest::get(&self.url).await?;
-        let mut file = std::fs::File::create(&self.file_name)?;
-        let mut content = Cursor::new(response.bytes().await?);
-        std::io::copy(&mut content, &mut file)?;
+        let response = self
+            .http
+            .send(Request::get(&self.url))
+            .await?
+            .error_for_status(&self.url)?;
+        let content = encrypt_content(self.cipher.as_ref(), response.body)?;
+        self.fs.write(&self.file_name, &content).await?;
         Ok(())
     }
 
     pub async fn parse_calendar(&self) -> Result<Vec<Task>, Box<dyn Error>> {
-        parse_calendar(&self.file_name).await
+        parse_calendar(self.fs.read(&self.file_name).await?, self.cipher.as_ref()).await
+    }
+
+    pub async fn clear_cache(&self) -> Result<(), std::io::Error> {
+        if self.fs.exists(&self.file_name).await {
+            self.fs.remove(&self.file_name).await?;
+        }
+        Ok(())
     }
 }
 
-pub async fn parse_calendar(file_path: &PathBuf) -> Result<Vec<Task>, Box<dyn Error>> {
-    let buf = BufReader::new(File::open(file_path)?);
+// The data is the cached calendar, it's encrypted if the cipher is set
+pub async fn parse_calendar(data: Vec<u8>, cipher: Option<&Cipher>) -> Result<Vec<Task>, Box<dyn Error>> {
+    parse_content(decrypt_content(cipher, data)?).await
+}
+
+async fn parse_content(content: Vec<u8>) -> Result<Vec<Task>, Box<dyn Error>> {
+    let buf = BufReader::new(Cursor::new(content));
     let reader = IcalParser::new(buf);
 
     let job = tokio::spawn(async move { read_tasks_from_calendar(reader) });
@@ -88,12 +129,14 @@ where
 fn event_to_task(ev: &IcalEvent) -> Task {
     let mut t = Task::from(&ev.properties);
     t.task_type = TaskType::Event;
+    t.alarms = ev.alarms.clone();
     t
 }
 
 fn todo_to_task(todo: &IcalTodo) -> Task {
     let mut t = Task::from(&todo.properties);
     t.task_type = TaskType::Todo;
+    t.alarms = todo.alarms.clone();
     t
 }
 
@@ -101,7 +144,11 @@ fn todo_to_task(todo: &IcalTodo) -> Task {
 mod test {
     use super::*;
     use crate::ical::task::TaskStatus;
-    use tatuin_core::task::{Priority, State, Task};
+    use tatuin_core::{
+        fs::MemoryFileSystem,
+        http::{Method, MockHttpClient, Response},
+        task::{Priority, State, Task},
+    };
 
     #[test]
     fn event_to_task_test() {
@@ -134,6 +181,10 @@ SUMMARY:Submit Quebec Income Tax Return for 2006
 CLASS:CONFIDENTIAL
 CATEGORIES:FAMILY,FINANCE
 STATUS:NEEDS-ACTION
+BEGIN:VALARM
+ACTION:DISPLAY
+TRIGGER;RELATED=END:-P1D
+END:VALARM
 END:VTODO
 END:VCALENDAR
 ";
@@ -179,5 +230,64 @@ END:VCALENDAR
         assert!(task.start.is_none());
         assert!(task.end.is_none());
         assert!(task.duration.is_none());
+
+        assert_eq!(
+            task.reminders().iter().map(|d| d.to_string()).collect::<Vec<String>>(),
+            vec!["2007-04-30 00:00:00 UTC"]
+        );
+        assert!(tasks[0].reminders().is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn clear_cache_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let mut c = Client::new("https://example.com/calendar.ics");
+        c.set_cache_folder(temp_dir.path());
+
+        let cache_file = temp_dir.path().join(FILE_NAME);
+        std::fs::write(&cache_file, "BEGIN:VCALENDAR").unwrap();
+        assert!(c.clear_cache().await.is_ok());
+        assert!(!cache_file.exists());
+
+        // the absent cache isn't an error
+        assert!(c.clear_cache().await.is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn download_calendar_test() {
+        const URL: &str = "https://example.com/calendar.ics";
+        const CALENDAR: &[u8] = b"BEGIN:VCALENDAR
+VERSION:2.0
+BEGIN:VTODO
+UID:1
+SUMMARY:Pay bills
+STATUS:NEEDS-ACTION
+END:VTODO
+END:VCALENDAR
+";
+
+        let fs = Arc::new(MemoryFileSystem::new());
+        let http = Arc::new(MockHttpClient::new().with_response(Method::Get, URL, Response::new(200, CALENDAR)));
+        let mut c = Client::new(URL)
+            .with_http_client(http.clone())
+            .with_file_system(fs.clone());
+        c.set_cache_folder(Path::new("/cache"));
+
+        c.download_calendar().await.unwrap();
+        assert_eq!(fs.content(&Path::new("/cache").join(FILE_NAME)).unwrap(), CALENDAR);
+        assert_eq!(http.requests(), vec![Request::get(URL)]);
+
+        let tasks = c.parse_calendar().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name().raw(), "Pay bills");
+
+        // the unsuccessful response doesn't replace the cache
+        let c = Client::new("https://example.com/absent.ics")
+            .with_http_client(http)
+            .with_file_system(fs.clone());
+        assert!(c.download_calendar().await.is_err());
+        assert!(fs.content(&Path::new("/cache").join(FILE_NAME)).is_some());
     }
 }
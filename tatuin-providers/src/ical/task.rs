@@ -3,11 +3,12 @@
 use std::str::FromStr;
 
 use chrono::{Duration, NaiveDate, NaiveDateTime};
-use ical::property::Property;
+use ical::{parser::ical::component::IcalAlarm, property::Property};
 
-use super::priority::TaskPriority;
+use super::{alarm, priority::TaskPriority};
 use tatuin_core::{
     RichString,
+    intern::IStr,
     project::Project as ProjectTrait,
     task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
 };
@@ -64,7 +65,7 @@ impl From<State> for TaskStatus {
 
 #[derive(Default, Clone)]
 pub struct Task {
-    pub provider: String,
+    pub provider: IStr,
     pub properties: Vec<ical::property::Property>,
     pub href: String,
     pub etag: Option<String>,
@@ -83,13 +84,18 @@ pub struct Task {
     pub created: Option<DateTimeUtc>,
     pub duration: Option<Duration>,
     pub categories: Vec<String>,
+    // the uid of the parent task from RELATED-TO
+    pub parent: Option<String>,
+    pub url: Option<String>,
+    // the VALARM components, they are written back as is
+    pub alarms: Vec<IcalAlarm>,
 }
 
 impl std::fmt::Debug for Task {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Task uuid={} name={} description={:?} status={:?} priority={} start={:?} end={:?} due={:?} completed={:?} created={:?} duration={:?} categories={:?} properties={:?} href={} etag={:?} type={:?}",
+            "Task uuid={} name={} description={:?} status={:?} priority={} start={:?} end={:?} due={:?} completed={:?} created={:?} duration={:?} categories={:?} parent={:?} url={:?} alarms={:?} properties={:?} href={} etag={:?} type={:?}",
             self.uid,
             self.name,
             self.description,
@@ -102,6 +108,9 @@ impl std::fmt::Debug for Task {
             self.created,
             self.duration,
             self.categories,
+            self.parent,
+            self.url,
+            self.alarms,
             self.properties,
             self.href,
             self.etag,
@@ -115,8 +124,8 @@ impl Task {
         !self.uid.is_empty() && !self.name.is_empty()
     }
 
-    pub fn set_provider(&mut self, p: &str) {
-        self.provider = p.to_string();
+    pub fn set_provider(&mut self, p: IStr) {
+        self.provider = p;
     }
 }
 
@@ -140,7 +149,7 @@ impl TaskTrait for Task {
     }
 
     fn provider(&self) -> String {
-        self.provider.clone()
+        self.provider.to_string()
     }
 
     fn project(&self) -> Option<Box<dyn ProjectTrait>> {
@@ -186,6 +195,18 @@ impl TaskTrait for Task {
     fn labels(&self) -> Vec<String> {
         self.categories.clone()
     }
+
+    fn parent_id(&self) -> Option<String> {
+        self.parent.clone()
+    }
+
+    fn url(&self) -> String {
+        self.url.clone().unwrap_or_default()
+    }
+
+    fn reminders(&self) -> Vec<DateTimeUtc> {
+        alarm::reminders(&self.alarms, self)
+    }
 }
 
 impl From<&Vec<Property>> for Task {
@@ -200,6 +221,7 @@ impl From<&Vec<Property>> for Task {
                 "UID" => t.uid = p.value.clone().unwrap_or_default(),
                 "SUMMARY" => t.name = p.value.clone().unwrap_or_default(),
                 "DESCRIPTION" => t.description = p.value.clone(),
+                "URL" => t.url = p.value.clone(),
                 "PRIORITY" => {
                     t.priority = p
                         .value
@@ -220,15 +242,15 @@ impl From<&Vec<Property>> for Task {
                 "COMPLETED" => t.completed = dt_from_property(p),
                 "CREATED" => t.created = dt_from_property(p),
                 "DURATION" => t.duration = duration_from_property(p),
+                // the categories can be split into several properties
                 "CATEGORIES" if p.value.is_some() => {
-                    t.categories = p
-                        .value
-                        .as_ref()
-                        .unwrap()
-                        .split(",")
-                        .map(|s| s.to_string())
-                        .collect::<Vec<String>>()
+                    for c in parse_categories(p.value.as_ref().unwrap()) {
+                        if !t.categories.contains(&c) {
+                            t.categories.push(c);
+                        }
+                    }
                 }
+                "RELATED-TO" if t.parent.is_none() && is_parent_relation(p) => t.parent = p.value.clone(),
                 _ => {}
             }
         }
@@ -237,8 +259,73 @@ impl From<&Vec<Property>> for Task {
     }
 }
 
+// The categories are separated by commas, the escaped commas belong to the names
+fn parse_categories(value: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut current = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            ',' => result.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    result.push(current);
+    result
+        .into_iter()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn categories_to_str(categories: &[String]) -> String {
+    categories
+        .iter()
+        .map(|c| c.replace('\\', "\\\\").replace(',', "\\,").replace(';', "\\;"))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+// RELATED-TO points to the parent task by default, the children and the siblings are kept as is
+fn is_parent_relation(p: &Property) -> bool {
+    p.name == "RELATED-TO"
+        && p.params.as_ref().is_none_or(|params| {
+            params
+                .iter()
+                .filter(|(n, _)| n.eq_ignore_ascii_case("RELTYPE"))
+                .all(|(_, v)| v.iter().all(|v| v.eq_ignore_ascii_case("PARENT")))
+        })
+}
+
+fn param_value_to_str(v: &str) -> String {
+    if v.contains([':', ';', ',']) {
+        format!("\"{v}\"")
+    } else {
+        v.to_string()
+    }
+}
+
 pub fn property_to_str(value: &Property) -> String {
-    format!("{}:{}", value.name, value.value.as_ref().unwrap_or(&String::new()))
+    let params = value
+        .params
+        .iter()
+        .flatten()
+        .map(|(n, v)| {
+            format!(
+                ";{n}={}",
+                v.iter()
+                    .map(|v| param_value_to_str(v))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            )
+        })
+        .collect::<String>();
+    format!(
+        "{}{params}:{}",
+        value.name,
+        value.value.as_ref().unwrap_or(&String::new())
+    )
 }
 
 fn make_property(name: &str, value: Option<String>) -> Property {
@@ -262,12 +349,17 @@ impl From<&Task> for Vec<Property> {
         let mut result = t.properties.clone();
         replace_or_add(&mut result, make_property("SUMMARY", Some(t.name.clone())));
         replace_or_add(&mut result, make_property("DESCRIPTION", t.description.clone()));
+        replace_or_add(&mut result, make_property("URL", t.url.clone()));
         replace_or_add(&mut result, make_property("STATUS", Some(t.status.to_string())));
         replace_or_add(&mut result, make_property("PRIORITY", Some(t.priority.to_string())));
         replace_or_add(
             &mut result,
             make_property("DUE", t.due.map(|d| d.format(DT_FORMAT).to_string())),
         );
+        replace_or_add(
+            &mut result,
+            make_property("CATEGORIES", Some(categories_to_str(&t.categories))),
+        );
         replace_or_add(
             &mut result,
             make_property("CREATED", Some(chrono::Utc::now().format(DT_FORMAT).to_string())),
@@ -280,6 +372,10 @@ impl From<&Task> for Vec<Property> {
             &mut result,
             make_property("DTEND", t.end.map(|d| d.format(DT_FORMAT).to_string())),
         );
+        result.retain(|p| !is_parent_relation(p));
+        if let Some(parent) = t.parent.as_ref().filter(|p| !p.is_empty()) {
+            result.push(make_property("RELATED-TO", Some(parent.clone())));
+        }
         result
     }
 }
@@ -299,7 +395,7 @@ fn tz_offset_from_property_params(params: &Option<Vec<(String, Vec<String>)>>) -
     None
 }
 
-fn dt_from_property(p: &Property) -> Option<DateTimeUtc> {
+pub(super) fn dt_from_property(p: &Property) -> Option<DateTimeUtc> {
     let s = p.value.as_ref()?;
 
     if let Ok(d) = NaiveDate::parse_from_str(s, "%Y%m%d") {
@@ -324,11 +420,137 @@ fn dt_from_property(p: &Property) -> Option<DateTimeUtc> {
 }
 
 fn duration_from_property(p: &Property) -> Option<Duration> {
-    if let Some(v) = &p.value
-        && let Ok(d) = v.parse::<iso8601_duration::Duration>()
-    {
-        return d.to_chrono();
+    duration_from_str(p.value.as_ref()?)
+}
+
+pub(super) fn duration_from_str(s: &str) -> Option<Duration> {
+    s.parse::<iso8601_duration::Duration>().ok()?.to_chrono()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn categories(p: &[Property]) -> Vec<String> {
+        p.iter()
+            .filter(|p| p.name == "CATEGORIES")
+            .filter_map(|p| p.value.clone())
+            .collect()
     }
 
-    None
+    #[test]
+    fn categories_test() {
+        let properties = vec![
+            make_property("UID", Some("1".to_string())),
+            make_property("SUMMARY", Some("Pay the bills".to_string())),
+            make_property("CATEGORIES", Some("FAMILY, FINANCE".to_string())),
+            make_property("CATEGORIES", Some("Home\\, sweet home,FAMILY".to_string())),
+        ];
+        let mut t = Task::from(&properties);
+        assert_eq!(t.labels(), vec!["FAMILY", "FINANCE", "Home, sweet home"]);
+
+        let p: Vec<Property> = (&t).into();
+        assert_eq!(categories(&p), vec!["FAMILY,FINANCE,Home\\, sweet home"]);
+        assert_eq!(Task::from(&p).labels(), t.labels());
+
+        t.categories.clear();
+        let p: Vec<Property> = (&t).into();
+        assert!(categories(&p).is_empty());
+    }
+
+    #[test]
+    fn related_to_test() {
+        let related_to = |value: &str, reltype: Option<&str>| Property {
+            name: "RELATED-TO".to_string(),
+            params: reltype.map(|r| vec![("RELTYPE".to_string(), vec![r.to_string()])]),
+            value: Some(value.to_string()),
+        };
+
+        struct Case<'a> {
+            name: &'a str,
+            relations: Vec<Property>,
+            expected: Option<&'a str>,
+        }
+        let cases = [
+            Case {
+                name: "no relations",
+                relations: Vec::new(),
+                expected: None,
+            },
+            Case {
+                name: "parent by default",
+                relations: vec![related_to("parent-uid", None)],
+                expected: Some("parent-uid"),
+            },
+            Case {
+                name: "explicit parent",
+                relations: vec![
+                    related_to("child-uid", Some("CHILD")),
+                    related_to("parent-uid", Some("parent")),
+                ],
+                expected: Some("parent-uid"),
+            },
+            Case {
+                name: "sibling",
+                relations: vec![related_to("sibling-uid", Some("SIBLING"))],
+                expected: None,
+            },
+        ];
+
+        for c in cases {
+            let mut properties = vec![
+                make_property("UID", Some("1".to_string())),
+                make_property("SUMMARY", Some("Buy milk".to_string())),
+            ];
+            properties.extend(c.relations);
+            assert_eq!(
+                Task::from(&properties).parent_id().as_deref(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn set_parent_test() {
+        let properties = vec![
+            make_property("UID", Some("1".to_string())),
+            make_property("SUMMARY", Some("Buy milk".to_string())),
+            make_property("RELATED-TO", Some("old-parent".to_string())),
+            Property {
+                name: "RELATED-TO".to_string(),
+                params: Some(vec![("RELTYPE".to_string(), vec!["CHILD".to_string()])]),
+                value: Some("child".to_string()),
+            },
+        ];
+        let mut t = Task::from(&properties);
+        t.parent = Some("new-parent".to_string());
+
+        let p: Vec<Property> = (&t).into();
+        let relations = p
+            .iter()
+            .filter(|p| p.name == "RELATED-TO")
+            .filter_map(|p| p.value.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(relations, vec!["child", "new-parent"]);
+        assert_eq!(Task::from(&p).parent_id().as_deref(), Some("new-parent"));
+    }
+
+    #[test]
+    fn property_to_str_test() {
+        let p = Property {
+            name: "TRIGGER".to_string(),
+            params: Some(vec![
+                ("RELATED".to_string(), vec!["END".to_string()]),
+                ("X-NOTE".to_string(), vec!["a:b".to_string(), "c".to_string()]),
+            ]),
+            value: Some("-PT15M".to_string()),
+        };
+        assert_eq!(property_to_str(&p), "TRIGGER;RELATED=END;X-NOTE=\"a:b\",c:-PT15M");
+        assert_eq!(
+            property_to_str(&make_property("SUMMARY", Some("Buy milk".to_string()))),
+            "SUMMARY:Buy milk"
+        );
+    }
 }
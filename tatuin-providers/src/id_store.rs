@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashSet, path::PathBuf};
+
+use tatuin_core::StringError;
+
+// Persistent set of the ids that have been already processed
+pub struct IdStore {
+    path: PathBuf,
+    ids: Option<HashSet<String>>,
+}
+
+impl IdStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path, ids: None }
+    }
+
+    fn ids(&mut self) -> Result<&mut HashSet<String>, StringError> {
+        if self.ids.is_none() {
+            let ids = match std::fs::read_to_string(&self.path) {
+                Ok(s) => serde_json::from_str(&s).map_err(|e| StringError::new(e.to_string().as_str()))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+                Err(e) => return Err(e.into()),
+            };
+            self.ids = Some(ids);
+        }
+
+        Ok(self.ids.as_mut().unwrap())
+    }
+
+    pub fn contains(&mut self, id: &str) -> Result<bool, StringError> {
+        Ok(self.ids()?.contains(id))
+    }
+
+    pub fn insert(&mut self, id: &str) -> Result<(), StringError> {
+        let ids = self.ids()?;
+        if !ids.insert(id.to_string()) {
+            return Ok(());
+        }
+
+        let s = serde_json::to_string(ids).map_err(|e| StringError::new(e.to_string().as_str()))?;
+        std::fs::write(&self.path, s)?;
+        Ok(())
+    }
+}
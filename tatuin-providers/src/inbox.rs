@@ -0,0 +1,319 @@
+// SPDX-License-Identifier: MIT
+
+mod client;
+mod project;
+mod task;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+use async_trait::async_trait;
+use tatuin_core::{
+    filter,
+    intern::intern,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::{DateTimeUtc, Priority, State, Task as TaskTrait},
+    task_patch::{PatchError, PatchErrorKind, TaskPatch},
+};
+use tokio::{sync::broadcast, task::JoinHandle};
+
+use crate::config::Config;
+
+pub const PROVIDER_NAME: &str = "Inbox";
+
+// How often the file is checked for the tasks added by other programs
+const WATCH_PERIOD: Duration = Duration::from_secs(1);
+
+pub struct Provider {
+    cfg: Config,
+    c: client::Client,
+    changes: broadcast::Sender<()>,
+    watcher: OnceLock<JoinHandle<()>>,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, path: &Path) -> Self {
+        let (changes, _) = broadcast::channel(1);
+        Self {
+            cfg,
+            c: client::Client::new(path),
+            changes,
+            watcher: OnceLock::new(),
+        }
+    }
+}
+
+impl Drop for Provider {
+    fn drop(&mut self) {
+        if let Some(w) = self.watcher.get() {
+            w.abort();
+        }
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+// The modification time and the size of the file, None if it doesn't exist
+async fn file_version(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    tokio::fs::metadata(path)
+        .await
+        .ok()
+        .map(|m| (m.modified().ok(), m.len()))
+}
+
+async fn watch(path: PathBuf, changes: broadcast::Sender<()>) {
+    let mut version = file_version(&path).await;
+    let mut interval = tokio::time::interval(WATCH_PERIOD);
+    loop {
+        interval.tick().await;
+        let v = file_version(&path).await;
+        if v != version {
+            version = v;
+            tracing::debug!(path=?path, "The inbox file has been changed");
+            // nobody listens when the app doesn't show the tasks
+            let _ = changes.send(());
+        }
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        Ok(vec![Box::new(project::Project::new(&self.name()))])
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        _project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let name = intern(&self.name());
+
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        for mut t in self.c.tasks().await? {
+            t.set_provider(name.clone());
+            if f.accept(&t) {
+                result.push(Box::new(t));
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create(&mut self, _project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let mut t = task::Task::default();
+        apply_patch(&mut t, tp);
+        self.c.append(&t).await
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut changes = Vec::new();
+        let mut errors = Vec::new();
+        for p in patches {
+            let task = p.task.as_ref().unwrap();
+
+            match task.as_any().downcast_ref::<task::Task>() {
+                Some(t) => {
+                    let mut new_task = t.clone();
+                    apply_patch(&mut new_task, p);
+                    changes.push((t, Some(new_task)));
+                }
+                None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
+            };
+        }
+
+        errors.extend(self.c.replace(&changes).await.into_iter().map(|e| PatchError {
+            task: Arc::new(e.task.clone()),
+            error: e.error,
+            kind: PatchErrorKind::Other,
+        }));
+        errors
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let t = t
+            .as_any()
+            .downcast_ref::<task::Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
+        match self.c.replace(&[(t, None)]).await.first() {
+            Some(e) => {
+                tracing::error!(error=?e.error, name=?t.name(), id=t.id(), "Delete the task");
+                Err(ProviderError::other(e.error.as_str()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        // the file is read on every list call
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+
+    // The file is watched since the first subscription
+    fn subscribe_on_changes(&self) -> Option<broadcast::Receiver<()>> {
+        self.watcher
+            .get_or_init(|| tokio::spawn(watch(self.c.path().to_path_buf(), self.changes.clone())));
+        Some(self.changes.subscribe())
+    }
+}
+
+fn apply_patch(t: &mut task::Task, tp: &TaskPatch) {
+    let e = &mut t.entry;
+
+    if let Some(n) = tp.name.value() {
+        e.name = n;
+    }
+
+    if tp.description.is_set() {
+        e.description = tp.description.value().filter(|d| !d.is_empty());
+    }
+
+    if tp.priority.is_set() {
+        e.priority = tp
+            .priority
+            .value()
+            .filter(|p| *p != Priority::Normal)
+            .map(|p| p.to_string());
+    }
+
+    if let Some(s) = tp.state.value() {
+        e.completed = s == State::Completed;
+    }
+
+    if tp.due.is_set() {
+        let due: Option<DateTimeUtc> = tp.due.value().and_then(|d| d.into());
+        e.due = due.as_ref().map(task::due_to_string);
+    }
+
+    if let Some(labels) = tp.labels.value() {
+        e.labels = labels;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use tatuin_core::{
+        filter::Filter,
+        task_patch::{DatePatchItem, ValuePatch},
+    };
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn list_and_change_tasks() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let path = temp_dir.path().join("inbox.txt");
+        std::fs::write(
+            &path,
+            "Buy milk\n\n{\"name\": \"Pay bills\", \"due\": \"2025-01-10\", \"priority\": \"high\", \"completed\": true}\n",
+        )
+        .unwrap();
+
+        let mut p = Provider::new(Config::new("test_app", "test_name"), &path);
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].name().raw(), "Buy milk");
+        assert_eq!(tasks[0].place(), "line 1");
+        assert_eq!(tasks[1].priority(), Priority::High);
+        assert_eq!(tasks[1].state(), State::Completed);
+        assert_eq!(tasks[1].due(), task::parse_due("2025-01-10"));
+        assert_eq!(tasks[1].place(), "line 3");
+
+        let tp = TaskPatch {
+            task: Some(tasks[0].clone_boxed().into()),
+            name: ValuePatch::Value("Buy milk and bread".to_string()),
+            labels: ValuePatch::Value(vec!["shop".to_string()]),
+            ..TaskPatch::default()
+        };
+        let tp2 = TaskPatch {
+            task: Some(tasks[1].clone_boxed().into()),
+            name: ValuePatch::Value("Pay bills".to_string()),
+            state: ValuePatch::Value(State::Uncompleted),
+            priority: ValuePatch::Value(Priority::Normal),
+            due: ValuePatch::Empty,
+            ..TaskPatch::default()
+        };
+        assert!(p.update(&[tp, tp2]).await.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"name\":\"Buy milk and bread\",\"labels\":[\"shop\"]}\n\nPay bills\n"
+        );
+
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Call mom".to_string()),
+            due: ValuePatch::Value(DatePatchItem::Custom(task::parse_due("2025-01-05").unwrap())),
+            ..TaskPatch::default()
+        };
+        p.create("", &tp).await.unwrap();
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Read a book".to_string()),
+            ..TaskPatch::default()
+        };
+        p.create("", &tp).await.unwrap();
+
+        // the task was loaded before the update, but the line is found by its content
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        assert!(p.delete(tasks[1].as_ref()).await.is_ok());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\"name\":\"Buy milk and bread\",\"labels\":[\"shop\"]}\n\n{\"name\":\"Call mom\",\"due\":\"2025-01-05\"}\nRead a book\n"
+        );
+        assert!(p.delete(tasks[1].as_ref()).await.is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn watch_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let path = temp_dir.path().join("inbox.txt");
+
+        let p = Provider::new(Config::new("test_app", "test_name"), &path);
+        let mut changes = p.subscribe_on_changes().unwrap();
+        // the watcher remembers the missing file first
+        tokio::time::sleep(WATCH_PERIOD / 2).await;
+
+        std::fs::write(&path, "Buy milk\n").unwrap();
+        tokio::time::timeout(WATCH_PERIOD * 5, changes.recv())
+            .await
+            .expect("The change hasn't been noticed")
+            .unwrap();
+    }
+}
@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT
+
+use std::path::{Path, PathBuf};
+
+use tatuin_core::{
+    fs::{LocalFileSystem, SharedFileSystem},
+    provider::ProviderError,
+};
+
+use super::task::Task;
+
+pub struct PatchError {
+    pub task: Task,
+    pub error: String,
+}
+
+pub struct Client {
+    path: PathBuf,
+    fs: SharedFileSystem,
+}
+
+impl Client {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            fs: LocalFileSystem::shared(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    async fn read_lines(&self) -> Result<Vec<String>, ProviderError> {
+        match self.fs.read_to_string(&self.path).await {
+            Ok(content) => Ok(content.lines().map(|l| l.to_string()).collect()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => {
+                tracing::error!(error=?e, path=?self.path, "Read the inbox file");
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn write_lines(&self, lines: &[String]) -> Result<(), ProviderError> {
+        let mut content = lines.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        self.fs.write(&self.path, content.as_bytes()).await.map_err(|e| {
+            tracing::error!(error=?e, path=?self.path, "Write the inbox file");
+            e.into()
+        })
+    }
+
+    pub async fn tasks(&self) -> Result<Vec<Task>, ProviderError> {
+        Ok(self
+            .read_lines()
+            .await?
+            .iter()
+            .enumerate()
+            .filter_map(|(i, l)| {
+                Task::parse(l).map(|mut t| {
+                    t.line = i;
+                    t
+                })
+            })
+            .collect())
+    }
+
+    pub async fn append(&self, t: &Task) -> Result<(), ProviderError> {
+        let mut lines = self.read_lines().await?;
+        lines.push(t.to_string());
+        self.write_lines(&lines).await
+    }
+
+    // Replaces the lines of the tasks by the new content or removes them if there is no new content.
+    // The line is looked up by the original text because the file could be changed since the loading.
+    pub async fn replace(&self, changes: &[(&Task, Option<Task>)]) -> Vec<PatchError> {
+        let mut lines = match self.read_lines().await {
+            Ok(lines) => lines,
+            Err(e) => {
+                return changes
+                    .iter()
+                    .map(|(t, _)| PatchError {
+                        task: (*t).clone(),
+                        error: e.to_string(),
+                    })
+                    .collect();
+            }
+        };
+
+        let mut errors = Vec::new();
+        let mut lines_to_remove = Vec::new();
+
+        for (old, new) in changes {
+            let idx = if lines.get(old.line).is_some_and(|l| *l == old.raw) {
+                Some(old.line)
+            } else {
+                lines.iter().position(|l| *l == old.raw)
+            };
+
+            match idx {
+                Some(idx) => match new {
+                    Some(t) => lines[idx] = t.to_string(),
+                    None => lines_to_remove.push(idx),
+                },
+                None => errors.push(PatchError {
+                    task: (*old).clone(),
+                    error: "The task has been changed outside".to_string(),
+                }),
+            }
+        }
+
+        lines_to_remove.sort_unstable();
+        lines_to_remove.dedup();
+        for idx in lines_to_remove.iter().rev() {
+            lines.remove(*idx);
+        }
+
+        if let Err(e) = self.write_lines(&lines).await {
+            return changes
+                .iter()
+                .map(|(t, _)| PatchError {
+                    task: (*t).clone(),
+                    error: e.to_string(),
+                })
+                .collect();
+        }
+
+        errors
+    }
+}
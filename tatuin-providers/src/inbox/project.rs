@@ -0,0 +1,59 @@
+// SPDX-License-Identifier: MIT
+
+use tatuin_core::project::Project as ProjectTrait;
+
+const INBOX_NAME: &str = "Inbox";
+
+#[derive(Clone)]
+pub struct Project {
+    provider: String,
+}
+
+impl Project {
+    // The inbox file is the only project of the provider
+    pub fn new(provider: &str) -> Self {
+        Self {
+            provider: provider.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Project {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Project provider={}", self.provider)
+    }
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        String::new()
+    }
+
+    fn name(&self) -> String {
+        INBOX_NAME.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        true
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,292 @@
+// SPDX-License-Identifier: MIT
+
+use std::any::Any;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use tatuin_core::{
+    RichString,
+    intern::IStr,
+    project::Project as ProjectTrait,
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task_patch::DatePatchItem,
+    time,
+};
+
+use super::project::Project;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// The line of the inbox file is either the plain task name or the JSON object like
+// {"name": "Pay bills", "due": "2025-01-10", "priority": "High", "labels": ["home"]}
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    // the date (2025-01-10) or the RFC 3339 time (2025-01-10T09:00:00Z)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub completed: bool,
+}
+
+pub fn parse_due(s: &str) -> Option<DateTimeUtc> {
+    match NaiveDate::parse_from_str(s, DATE_FORMAT) {
+        Ok(d) => d.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc()),
+        Err(_) => DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+pub fn due_to_string(dt: &DateTimeUtc) -> String {
+    if time::is_date_only(dt) {
+        dt.format(DATE_FORMAT).to_string()
+    } else {
+        dt.to_rfc3339()
+    }
+}
+
+pub fn parse_priority(s: &str) -> Option<Priority> {
+    Priority::values()
+        .into_iter()
+        .find(|p| p.to_string().eq_ignore_ascii_case(s.trim()))
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Task {
+    pub provider: IStr,
+    pub line: usize,
+    pub raw: String,
+    pub entry: Entry,
+}
+
+impl Task {
+    pub fn parse(line: &str) -> Option<Self> {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+
+        // the broken JSON is shown as is to be noticed and fixed
+        let entry = trimmed
+            .starts_with('{')
+            .then(|| serde_json::from_str::<Entry>(trimmed).ok())
+            .flatten()
+            .unwrap_or_else(|| Entry {
+                name: trimmed.to_string(),
+                ..Entry::default()
+            });
+
+        Some(Self {
+            raw: line.to_string(),
+            entry,
+            ..Task::default()
+        })
+    }
+
+    pub fn set_provider(&mut self, name: IStr) {
+        self.provider = name;
+    }
+}
+
+impl std::fmt::Display for Task {
+    // The task with the name only is written as the plain text
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let e = &self.entry;
+        let is_plain = e.description.is_none()
+            && e.due.is_none()
+            && e.priority.is_none()
+            && e.labels.is_empty()
+            && !e.completed
+            && !e.name.starts_with('{')
+            && !e.name.contains('\n');
+        if is_plain {
+            return write!(f, "{}", e.name);
+        }
+
+        write!(f, "{}", serde_json::to_string(e).map_err(|_| std::fmt::Error)?)
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        sha256::digest(format!("{}:{}", self.line, self.raw))
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(&self.entry.name)
+    }
+
+    fn description(&self) -> Option<RichString> {
+        self.entry.description.as_deref().map(RichString::new)
+    }
+
+    fn priority(&self) -> Priority {
+        self.entry
+            .priority
+            .as_deref()
+            .and_then(parse_priority)
+            .unwrap_or_default()
+    }
+
+    fn state(&self) -> State {
+        if self.entry.completed {
+            State::Completed
+        } else {
+            State::Uncompleted
+        }
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.entry.due.as_deref().and_then(parse_due)
+    }
+
+    fn place(&self) -> String {
+        format!("line {}", self.line + 1)
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.entry.labels.clone()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(Box::new(Project::new(&self.provider)))
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: true,
+            is_removable: true,
+            available_states: vec![State::Uncompleted, State::Completed],
+            available_priorities: Priority::values(),
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        struct Case<'a> {
+            name: &'a str,
+            line: &'a str,
+            expected: Option<Entry>,
+        }
+        let cases = [
+            Case {
+                name: "empty line",
+                line: "  ",
+                expected: None,
+            },
+            Case {
+                name: "plain text",
+                line: " Buy milk ",
+                expected: Some(Entry {
+                    name: "Buy milk".to_string(),
+                    ..Entry::default()
+                }),
+            },
+            Case {
+                name: "json",
+                line: r#"{"name": "Pay bills", "due": "2025-01-10", "priority": "high", "labels": ["home"], "extra": 1}"#,
+                expected: Some(Entry {
+                    name: "Pay bills".to_string(),
+                    due: Some("2025-01-10".to_string()),
+                    priority: Some("high".to_string()),
+                    labels: vec!["home".to_string()],
+                    ..Entry::default()
+                }),
+            },
+            Case {
+                name: "broken json",
+                line: r#"{"title": "Pay bills"}"#,
+                expected: Some(Entry {
+                    name: r#"{"title": "Pay bills"}"#.to_string(),
+                    ..Entry::default()
+                }),
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                Task::parse(c.line).map(|t| t.entry),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn to_string_test() {
+        struct Case<'a> {
+            name: &'a str,
+            line: &'a str,
+            expected: &'a str,
+        }
+        let cases = [
+            Case {
+                name: "plain text",
+                line: "Buy milk",
+                expected: "Buy milk",
+            },
+            Case {
+                name: "json with the name only",
+                line: r#"{"name": "Buy milk"}"#,
+                expected: "Buy milk",
+            },
+            Case {
+                name: "json",
+                line: r#"{"completed": true, "name": "Pay bills", "due": "2025-01-10"}"#,
+                expected: r#"{"name":"Pay bills","due":"2025-01-10","completed":true}"#,
+            },
+            Case {
+                name: "broken json is kept as json",
+                line: r#"{"title": "Pay bills"}"#,
+                expected: r#"{"name":"{\"title\": \"Pay bills\"}"}"#,
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                Task::parse(c.line).unwrap().to_string(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn due_test() {
+        for s in ["2025-01-10", "2025-01-10T09:30:00+00:00"] {
+            let dt = parse_due(s).unwrap();
+            assert_eq!(due_to_string(&dt), s, "Test '{s}' was failed");
+        }
+        assert_eq!(
+            parse_due("2025-01-10T09:30:00+02:00").map(|dt| due_to_string(&dt)),
+            Some("2025-01-10T07:30:00+00:00".to_string())
+        );
+        assert_eq!(parse_due("tomorrow"), None);
+    }
+}
@@ -0,0 +1,574 @@
+// SPDX-License-Identifier: MIT
+
+pub mod client;
+pub mod structs;
+
+use crate::config::Config;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use client::Client;
+use std::{any::Any, sync::Arc};
+use tatuin_core::{
+    RichString,
+    filter::{self, FilterState},
+    http::SharedHttpClient,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait, due_group},
+    task_patch::{PatchError, PatchErrorKind, TaskPatch},
+};
+
+use async_trait::async_trait;
+
+pub const PROVIDER_NAME: &str = "Jira";
+
+const STATUS_CATEGORY_TODO: &str = "new";
+const STATUS_CATEGORY_IN_PROGRESS: &str = "indeterminate";
+const STATUS_CATEGORY_DONE: &str = "done";
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    project: structs::Project,
+    provider: String,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.project.id.to_string()
+    }
+
+    fn name(&self) -> String {
+        self.project.name.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("Issues of the project {}", self.project.key)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        false
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    issue: structs::Issue,
+    base_url: String,
+    provider: String,
+    // the account id of the token owner
+    me: Option<String>,
+}
+
+fn str_to_date(s: &str) -> Option<DateTimeUtc> {
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        let dt = d.and_hms_opt(0, 0, 0)?;
+        return Some(DateTimeUtc::from_naive_utc_and_offset(dt, Utc));
+    }
+
+    // Jira writes the offset without the colon, e.g. 2025-01-08T10:30:00.000+0100
+    if let Ok(dt) = DateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f%z") {
+        return Some(DateTimeUtc::from(dt));
+    }
+
+    None
+}
+
+// The priority names of the default Jira schemes, the custom ones are shown as normal
+fn priority_from_name(name: &str) -> Priority {
+    match name.to_lowercase().as_str() {
+        "highest" | "blocker" => Priority::Highest,
+        "high" | "critical" => Priority::High,
+        "medium" | "major" => Priority::Medium,
+        "low" | "minor" => Priority::Low,
+        "lowest" | "trivial" => Priority::Lowest,
+        _ => Priority::Normal,
+    }
+}
+
+fn state_from_category(key: &str) -> State {
+    match key {
+        STATUS_CATEGORY_TODO => State::Uncompleted,
+        STATUS_CATEGORY_IN_PROGRESS => State::InProgress,
+        STATUS_CATEGORY_DONE => State::Completed,
+        _ => State::Unknown('?'),
+    }
+}
+
+fn category_from_state(state: &State) -> Option<&'static str> {
+    match state {
+        State::Uncompleted => Some(STATUS_CATEGORY_TODO),
+        State::InProgress => Some(STATUS_CATEGORY_IN_PROGRESS),
+        State::Completed => Some(STATUS_CATEGORY_DONE),
+        State::Unknown(_) => None,
+    }
+}
+
+// The plain text of the Atlassian Document Format, every block goes on its own line
+fn adf_to_text(node: &serde_json::Value) -> String {
+    if let Some(text) = node.get("text").and_then(|t| t.as_str()) {
+        return text.to_string();
+    }
+
+    let Some(content) = node.get("content").and_then(|c| c.as_array()) else {
+        return match node.get("type").and_then(|t| t.as_str()) {
+            Some("hardBreak") => "\n".to_string(),
+            _ => String::new(),
+        };
+    };
+
+    let inline = content
+        .iter()
+        .all(|n| n.get("text").is_some() || n.get("type").and_then(|t| t.as_str()) == Some("hardBreak"));
+    let separator = if inline { "" } else { "\n" };
+    content
+        .iter()
+        .map(adf_to_text)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<String>>()
+        .join(separator)
+}
+
+fn jql(states: &[FilterState]) -> String {
+    let categories = states
+        .iter()
+        .filter_map(|s| match s {
+            FilterState::Completed => Some(STATUS_CATEGORY_DONE),
+            FilterState::Todo => Some(STATUS_CATEGORY_TODO),
+            FilterState::InProgress => Some(STATUS_CATEGORY_IN_PROGRESS),
+            FilterState::Unknown => None,
+        })
+        .map(|c| format!("\"{c}\""))
+        .collect::<Vec<String>>();
+
+    let mut jql = "assignee = currentUser()".to_string();
+    if !states.is_empty() {
+        jql.push_str(format!(" AND statusCategory IN ({})", categories.join(", ")).as_str());
+    }
+    jql.push_str(" ORDER BY duedate ASC");
+    jql
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.issue.id.to_string()
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(format!("{} {}", self.issue.key, self.issue.fields.summary).as_str())
+    }
+
+    fn description(&self) -> Option<RichString> {
+        self.issue
+            .fields
+            .description
+            .as_ref()
+            .map(adf_to_text)
+            .filter(|d| !d.is_empty())
+            .map(|d| RichString::new(&d))
+    }
+
+    fn priority(&self) -> Priority {
+        self.issue
+            .fields
+            .priority
+            .as_ref()
+            .map(|p| priority_from_name(&p.name))
+            .unwrap_or_default()
+    }
+
+    fn state(&self) -> State {
+        state_from_category(self.issue.fields.status.status_category.key.as_str())
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        self.issue.fields.created.as_deref().and_then(str_to_date)
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.issue.fields.updated.as_deref().and_then(str_to_date)
+    }
+
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        self.issue.fields.resolutiondate.as_deref().and_then(str_to_date)
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.issue.fields.duedate.as_deref().and_then(str_to_date)
+    }
+
+    fn place(&self) -> String {
+        self.url()
+    }
+
+    fn url(&self) -> String {
+        format!("{}/browse/{}", self.base_url, self.issue.key)
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.issue.fields.labels.clone()
+    }
+
+    fn assignee(&self) -> Option<String> {
+        self.issue.fields.assignee.as_ref().map(|a| a.display_name.to_string())
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue.fields.assignee.as_ref().is_some_and(|a| a.account_id == *me))
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.issue.fields.reporter.as_ref().is_some_and(|r| r.account_id == *me))
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(Box::new(Project {
+            project: self.issue.fields.project.clone(),
+            provider: self.provider.to_string(),
+        }))
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: false,
+            is_removable: false,
+            available_states: vec![State::Uncompleted, State::InProgress, State::Completed],
+            available_priorities: Vec::new(),
+            available_due_items: Vec::new(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+}
+
+pub struct Provider {
+    cfg: Config,
+    base_url: String,
+    client: Client,
+    tasks: Vec<Task>,
+    last_filter: Option<filter::Filter>,
+    last_sync: Option<DateTimeUtc>,
+    // the account id of the token owner
+    account_id: Option<String>,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, base_url: &str, email: &str, api_token: &str) -> Self {
+        Self {
+            cfg,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::new(base_url, email, api_token),
+            tasks: Vec::new(),
+            last_filter: None,
+            last_sync: None,
+            account_id: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    async fn account_id(&mut self) -> Result<String, ProviderError> {
+        if self.account_id.is_none() {
+            self.account_id = Some(self.client.myself().await?.account_id);
+        }
+        Ok(self.account_id.clone().unwrap_or_default())
+    }
+
+    // The status is changed only through the workflow, so the first transition to the status category is used
+    async fn patch_task_state(&mut self, t: &Task, state: &State) -> Result<(), PatchError> {
+        let error = |e: String| PatchError {
+            task: Arc::new(t.clone()),
+            error: e,
+            kind: PatchErrorKind::Other,
+        };
+
+        let category = category_from_state(state).ok_or_else(|| error(format!("The state {state} is unsupported")))?;
+        let transitions = self
+            .client
+            .transitions(&t.issue.key)
+            .await
+            .map_err(|e| error(e.to_string()))?;
+        let transition = transitions
+            .iter()
+            .find(|tr| tr.to.status_category.key == category)
+            .ok_or_else(|| error(format!("The workflow has no transition to the state {state}")))?;
+        self.client
+            .transition(&t.issue.key, &transition.id)
+            .await
+            .map_err(|e| error(e.to_string()))
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let mut projects: Vec<Project> = Vec::new();
+        for t in &self.tasks {
+            if !projects.iter().any(|p| p.project.id == t.issue.fields.project.id) {
+                projects.push(Project {
+                    project: t.issue.fields.project.clone(),
+                    provider: self.name(),
+                });
+            }
+        }
+        Ok(projects.iter().map(|p| p.clone_boxed()).collect())
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let mut should_clear = false;
+        if let Some(last_filter) = self.last_filter.as_mut() {
+            should_clear = last_filter != f;
+        }
+
+        if should_clear {
+            self.tasks.clear();
+        }
+
+        if self.tasks.is_empty() {
+            // the tasks are shown without the assignment info if the token can't read the user
+            let me = self
+                .account_id()
+                .await
+                .inspect_err(|e| tracing::warn!(target:"jira", error=?e, "Get the user"))
+                .ok();
+            for issue in self.client.search(&jql(&f.states)).await? {
+                self.tasks.push(Task {
+                    issue,
+                    base_url: self.base_url.to_string(),
+                    provider: self.name(),
+                    me: me.clone(),
+                })
+            }
+            self.last_sync = Some(Utc::now());
+        }
+
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+
+        for t in &self.tasks {
+            if project.as_ref().is_some_and(|p| p.id() != t.issue.fields.project.id) {
+                continue;
+            }
+            if f.due.contains(&due_group(&t.due())) {
+                result.push(Box::new(t.clone()));
+            }
+        }
+
+        self.last_filter = Some(f.clone());
+
+        Ok(result)
+    }
+
+    async fn create(&mut self, _project_id: &str, _tp: &TaskPatch) -> Result<(), ProviderError> {
+        Err(ProviderError::other("Task creation is not supported"))
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        for p in patches {
+            tracing::debug!(target:"jira_patch_task", patch=p.to_string(), "Apply a patch");
+            let task = p.task.as_ref().unwrap();
+
+            let task = match task.as_any().downcast_ref::<Task>() {
+                Some(t) => t,
+                None => {
+                    errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                    continue;
+                }
+            };
+            if let Some(state) = &p.state.value()
+                && let Err(e) = self.patch_task_state(task, state).await
+            {
+                errors.push(e);
+            }
+        }
+
+        self.tasks.clear();
+
+        errors
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        self.tasks.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: false,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: true,
+            queries: false,
+            url: false,
+        }
+    }
+
+    fn supported_priorities(&self) -> Vec<Priority> {
+        vec![
+            Priority::Lowest,
+            Priority::Low,
+            Priority::Medium,
+            Priority::High,
+            Priority::Highest,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn jql_test() {
+        struct Case<'a> {
+            name: &'a str,
+            states: &'a [FilterState],
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "all states",
+                states: &[],
+                expected: "assignee = currentUser() ORDER BY duedate ASC",
+            },
+            Case {
+                name: "uncompleted",
+                states: &[FilterState::Todo, FilterState::InProgress],
+                expected: "assignee = currentUser() AND statusCategory IN (\"new\", \"indeterminate\") ORDER BY duedate ASC",
+            },
+            Case {
+                name: "completed",
+                states: &[FilterState::Completed, FilterState::Unknown],
+                expected: "assignee = currentUser() AND statusCategory IN (\"done\") ORDER BY duedate ASC",
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(jql(c.states), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn issue_test() {
+        let issue: structs::Issue = serde_json::from_value(serde_json::json!({
+            "id": "10001",
+            "key": "TAT-1",
+            "fields": {
+                "summary": "Fix the build",
+                "description": {
+                    "type": "doc",
+                    "version": 1,
+                    "content": [
+                        {"type": "paragraph", "content": [
+                            {"type": "text", "text": "It fails "},
+                            {"type": "text", "text": "on CI", "marks": [{"type": "strong"}]},
+                        ]},
+                        {"type": "paragraph", "content": [
+                            {"type": "text", "text": "See the log"},
+                            {"type": "hardBreak"},
+                            {"type": "text", "text": "of the last run"},
+                        ]},
+                    ],
+                },
+                "status": {"name": "In Review", "statusCategory": {"key": "indeterminate"}},
+                "priority": {"name": "High"},
+                "duedate": "2025-01-08",
+                "created": "2025-01-01T10:30:00.000+0100",
+                "updated": "2025-01-02T10:30:00.000+0000",
+                "labels": ["ci"],
+                "assignee": {"accountId": "me", "displayName": "Me"},
+                "reporter": {"accountId": "other", "displayName": "Other"},
+                "project": {"id": "1", "key": "TAT", "name": "Tatuin"},
+            },
+        }))
+        .unwrap();
+        let t = Task {
+            issue,
+            base_url: "https://tatuin.atlassian.net".to_string(),
+            provider: "jira".to_string(),
+            me: Some("me".to_string()),
+        };
+
+        assert_eq!(t.name().raw(), "TAT-1 Fix the build");
+        assert_eq!(
+            t.description().unwrap().raw(),
+            "It fails on CI\nSee the log\nof the last run"
+        );
+        assert_eq!(t.state(), State::InProgress);
+        assert_eq!(t.priority(), Priority::High);
+        assert_eq!(t.due(), str_to_date("2025-01-08"));
+        assert_eq!(
+            t.created_at(),
+            Some("2025-01-01T09:30:00Z".parse::<DateTimeUtc>().unwrap())
+        );
+        assert_eq!(t.url(), "https://tatuin.atlassian.net/browse/TAT-1");
+        assert_eq!(t.assignee(), Some("Me".to_string()));
+        assert_eq!(t.is_assigned_to_me(), Some(true));
+        assert_eq!(t.is_created_by_me(), Some(false));
+        assert_eq!(t.project().unwrap().name(), "Tatuin");
+    }
+}
@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MIT
+
+use super::structs::{Issue, Transition, User};
+use crate::http::ReqwestClient;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, sync::Arc};
+use tatuin_core::http::{Request, SharedHttpClient};
+use urlencoding::encode;
+
+const FIELDS: &str =
+    "summary,description,status,priority,duedate,created,updated,resolutiondate,labels,assignee,reporter,project";
+
+#[derive(Debug, Serialize)]
+struct TransitionRef<'a> {
+    id: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct TransitionRequest<'a> {
+    transition: TransitionRef<'a>,
+}
+
+pub struct Client {
+    base_url: String,
+    email: String,
+    api_token: String,
+    http: SharedHttpClient,
+}
+
+impl Client {
+    pub fn new(base_url: &str, email: &str, api_token: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            email: email.to_string(),
+            api_token: api_token.to_string(),
+            http: Arc::new(ReqwestClient::default()),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    fn request(&self, r: Request) -> Request {
+        r.with_basic_auth(&self.email, &self.api_token)
+            .with_header("Accept", "application/json")
+    }
+
+    // The issues found by the JQL query
+    pub async fn search(&self, jql: &str) -> Result<Vec<Issue>, Box<dyn Error>> {
+        #[derive(Deserialize)]
+        struct Response {
+            issues: Vec<Issue>,
+            #[serde(rename = "nextPageToken")]
+            next_page_token: Option<String>,
+        }
+
+        let mut result = Vec::new();
+
+        const PER_PAGE: i8 = 100;
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let mut url = format!(
+                "{}/rest/api/3/search/jql?jql={}&fields={FIELDS}&maxResults={PER_PAGE}",
+                self.base_url,
+                encode(jql)
+            );
+            if let Some(t) = &page_token {
+                url.push_str(format!("&nextPageToken={}", encode(t)).as_str());
+            }
+            match self
+                .http
+                .send_checked(self.request(Request::get(&url)))
+                .await?
+                .json::<Response>()
+            {
+                Ok(mut r) => {
+                    result.append(&mut r.issues);
+                    match r.next_page_token {
+                        Some(t) => page_token = Some(t),
+                        None => break,
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(target:"jira_client", url=url, error=?e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // The authenticated user
+    pub async fn myself(&self) -> Result<User, Box<dyn Error>> {
+        let url = format!("{}/rest/api/3/myself", self.base_url);
+        self.http
+            .send_checked(self.request(Request::get(&url)))
+            .await?
+            .json::<User>()
+            .map_err(|e| {
+                tracing::error!(target:"jira_client", url=url, error=?e);
+                e.into()
+            })
+    }
+
+    // The transitions that are available for the issue in its current status
+    pub async fn transitions(&self, issue_key: &str) -> Result<Vec<Transition>, Box<dyn Error>> {
+        #[derive(Deserialize)]
+        struct Response {
+            transitions: Vec<Transition>,
+        }
+
+        let url = format!("{}/rest/api/3/issue/{issue_key}/transitions", self.base_url);
+        self.http
+            .send_checked(self.request(Request::get(&url)))
+            .await?
+            .json::<Response>()
+            .map(|r| r.transitions)
+            .map_err(|e| {
+                tracing::error!(target:"jira_client", url=url, error=?e);
+                e.into()
+            })
+    }
+
+    pub async fn transition(&self, issue_key: &str, transition_id: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/rest/api/3/issue/{issue_key}/transitions", self.base_url);
+        let body = TransitionRequest {
+            transition: TransitionRef { id: transition_id },
+        };
+        self.http
+            .send_checked(self.request(Request::post(&url).with_json(&body)?))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn transition_test() {
+        const URL: &str = "https://example.atlassian.net/rest/api/3/issue/TT-1/transitions";
+        let http = Arc::new(MockHttpClient::new().with_response(Method::Post, URL, Response::new(204, b"")));
+
+        let c =
+            Client::new("https://example.atlassian.net/", "user@example.com", "token").with_http_client(http.clone());
+        c.transition("TT-1", "31").await.unwrap();
+        assert_eq!(
+            http.requests(),
+            vec![
+                Request::post(URL)
+                    .with_json(&serde_json::json!({"transition": {"id": "31"}}))
+                    .unwrap()
+                    .with_basic_auth("user@example.com", "token")
+                    .with_header("Accept", "application/json")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn myself_test() {
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Get,
+            "https://example.atlassian.net/rest/api/3/myself",
+            Response::new(200, br#"{"accountId": "1", "displayName": "User"}"#),
+        ));
+
+        let c = Client::new("https://example.atlassian.net", "user@example.com", "token").with_http_client(http);
+        assert_eq!(c.myself().await.unwrap().display_name, "User");
+
+        let c = Client::new("https://example.atlassian.net", "user@example.com", "token")
+            .with_http_client(Arc::new(MockHttpClient::new()));
+        assert!(c.myself().await.is_err());
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    pub id: String,
+    pub key: String,
+    pub fields: Fields,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fields {
+    pub summary: String,
+    // the Atlassian Document Format
+    pub description: Option<serde_json::Value>,
+    pub status: Status,
+    pub priority: Option<Priority>,
+    pub duedate: Option<String>,
+    pub created: Option<String>,
+    pub updated: Option<String>,
+    pub resolutiondate: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub assignee: Option<User>,
+    pub reporter: Option<User>,
+    pub project: Project,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Status {
+    pub name: String,
+    #[serde(rename = "statusCategory")]
+    pub status_category: StatusCategory,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusCategory {
+    // new, indeterminate or done
+    pub key: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Priority {
+    pub name: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct User {
+    #[serde(rename = "accountId")]
+    pub account_id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub id: String,
+    pub key: String,
+    pub name: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transition {
+    pub id: String,
+    pub name: String,
+    pub to: Status,
+}
@@ -1,12 +1,73 @@
 // SPDX-License-Identifier: MIT
 
+#[cfg(feature = "caldav")]
 pub mod caldav;
+#[cfg(feature = "capture")]
+pub mod capture;
 pub mod config;
+#[cfg(feature = "deck")]
+pub mod deck;
+#[cfg(feature = "github")]
 pub mod github;
+#[cfg(feature = "github")]
 pub mod github_issues;
+#[cfg(feature = "github")]
+pub mod github_projects;
+#[cfg(feature = "gitlab")]
 pub mod gitlab;
+#[cfg(feature = "gitlab")]
+pub mod gitlab_issues;
+#[cfg(feature = "gitlab")]
 pub mod gitlab_todo;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "ical")]
 pub mod ical;
+#[cfg(any(feature = "capture", feature = "notify"))]
+mod id_store;
+#[cfg(feature = "inbox")]
+pub mod inbox;
+#[cfg(feature = "jira")]
+pub mod jira;
+#[cfg(feature = "markdown-dir")]
+pub mod markdown_dir;
+#[cfg(feature = "mstodo")]
+pub mod mstodo;
+#[cfg(feature = "notify")]
+pub mod notify;
+#[cfg(feature = "notion")]
+pub mod notion;
+#[cfg(feature = "obsidian")]
 pub mod obsidian;
+#[cfg(feature = "org")]
+pub mod org;
+#[cfg(feature = "taskwarrior")]
+pub mod taskwarrior;
+#[cfg(feature = "tatuin-local")]
 pub mod tatuin;
+#[cfg(feature = "todoist")]
 pub mod todoist;
+#[cfg(feature = "todotxt")]
+pub mod todotxt;
+
+// The provider types that can be excluded from the build and the features that enable them
+pub const OPTIONAL_PROVIDERS: &[(&str, &str)] = &[
+    ("Obsidian", "obsidian"),
+    ("Todoist", "todoist"),
+    ("CalDav", "caldav"),
+    ("GitLabTODO", "gitlab"),
+    ("GitLab Issues", "gitlab"),
+    ("GitHub Issues", "github"),
+    ("GitHub Projects", "github"),
+    ("Jira", "jira"),
+    ("Notion", "notion"),
+    ("Microsoft To Do", "mstodo"),
+    ("Nextcloud Deck", "deck"),
+    ("iCal", "ical"),
+    ("Tatuin", "tatuin-local"),
+    ("MarkdownDir", "markdown-dir"),
+    ("TodoTxt", "todotxt"),
+    ("Inbox", "inbox"),
+    ("Taskwarrior", "taskwarrior"),
+    ("Org", "org"),
+];
@@ -0,0 +1,322 @@
+// SPDX-License-Identifier: MIT
+
+mod block;
+pub(crate) mod client;
+mod conventions;
+mod delete_mode;
+pub(crate) mod description;
+pub(crate) mod dialect;
+mod frontmatter;
+pub(crate) mod fs;
+mod indent;
+pub(crate) mod journal;
+pub mod md_file;
+mod patch;
+pub(crate) mod project;
+pub(crate) mod state;
+pub(crate) mod task;
+
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use description::Description;
+use state::State;
+use tatuin_core::{
+    filter,
+    fs::SharedFileSystem,
+    intern::intern,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::{Priority, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
+};
+
+use crate::config::Config;
+
+pub use conventions::Conventions;
+pub use delete_mode::DeleteMode;
+
+pub const PROVIDER_NAME: &str = "MarkdownDir";
+
+pub struct Provider {
+    cfg: Config,
+    c: client::Client,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, path: &Path, conventions: Conventions) -> Self {
+        Self {
+            cfg,
+            c: client::Client::new(path).with_conventions(conventions),
+        }
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.c = self.c.with_file_system(fs);
+        self
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let root = self.c.root_path();
+        let mut files = self.c.all_supported_files().await?;
+        files.sort();
+        Ok(files
+            .iter()
+            .map(|f| {
+                Box::new(project::Project::new(intern(self.cfg.name().as_str()), &root, f)) as Box<dyn ProjectTrait>
+            })
+            .collect())
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let root = self.c.root_path();
+        let tasks = self.c.tasks(f).await?;
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        let name = intern(&self.name());
+        for mut t in tasks {
+            if let Some(p) = &project
+                && fs::strip_root_str(&root, &t.file_path) != p.id()
+            {
+                continue;
+            }
+            t.set_provider(name.clone());
+            result.push(Box::new(t));
+        }
+        Ok(result)
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let t = new_task(tp);
+        let file_path = self.c.root_path().join(project_id);
+        self.c.add_task(&file_path, &t).await.map_err(|e| {
+            tracing::error!(error=?e, file=?file_path, "Add the task");
+            e.into()
+        })
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        update_tasks(&mut self.c, patches).await
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        delete_task(&mut self.c, t).await
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        // do nothing for now
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+pub(crate) fn new_task(tp: &TaskPatch) -> task::Task {
+    task::Task {
+        name: tp.name.value().unwrap(),
+        description: tp.description.value().map(|s| Description::from_str(s.as_str())),
+        state: State::Uncompleted,
+        due: tp.due.value().unwrap_or(DatePatchItem::NoDate).into(),
+        priority: tp.priority.value().unwrap_or(Priority::Normal),
+        ..task::Task::default()
+    }
+}
+
+pub(crate) async fn update_tasks(c: &mut client::Client, patches: &[TaskPatch]) -> Vec<PatchError> {
+    let mut client_patches = Vec::new();
+    let mut errors = Vec::new();
+    for p in patches.iter() {
+        let task = p.task.as_ref().unwrap();
+
+        match task.as_any().downcast_ref::<task::Task>() {
+            Some(t) => client_patches.push(patch_to_internal(t, p)),
+            None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
+        };
+    }
+
+    for e in c.patch_tasks(&client_patches).await {
+        errors.push(PatchError {
+            task: Arc::new(e.task.clone()),
+            error: e.error,
+            kind: PatchErrorKind::Other,
+        })
+    }
+
+    errors
+}
+
+pub(crate) async fn delete_task(c: &mut client::Client, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+    let t = t
+        .as_any()
+        .downcast_ref::<task::Task>()
+        .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
+    c.delete_task(t).await.map_err(|e| {
+        tracing::error!(error=?e, name=?t.name(), id=t.id(), "Delete the task");
+        e.into()
+    })
+}
+
+fn patch_to_internal<'a>(t: &'a task::Task, tp: &TaskPatch) -> patch::TaskPatch<'a> {
+    patch::TaskPatch {
+        task: t,
+        name: tp.name.clone(),
+        description: tp.description.clone(),
+        state: tp.state.clone().map(|s| s.into()),
+        due: tp.due.clone().into(),
+        scheduled: tp.scheduled.clone().into(),
+        priority: tp.priority.clone(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::{
+        fs::{FileSystem, MemoryFileSystem},
+        task_patch::ValuePatch,
+    };
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn custom_conventions() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let root = temp_dir.path().to_path_buf();
+        std::fs::create_dir(root.join("work")).unwrap();
+        std::fs::write(
+            root.join("notes.md"),
+            "# Notes\n* [ ] Buy milk due:2025-01-05 #home\n- [ ] Not a task\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("work").join("todo.md"), "* [x] Send report\n").unwrap();
+
+        let conventions =
+            Conventions::new(Some(r"^\s*\*\ \[(.)\]\ (.*)$"), Some("*"), Some("due:"), None, None).unwrap();
+        let mut p = Provider::new(Config::new("test_app", "test_name"), &root, conventions);
+
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(
+            projects.iter().map(|p| p.id()).collect::<Vec<_>>(),
+            vec!["notes.md", "work/todo.md"]
+        );
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &filter::Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+
+        let tasks = TaskProviderTrait::list(&mut p, Some(projects[0].clone_boxed()), &filter::Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        let t = &tasks[0];
+        assert_eq!(t.name().display(), "Buy milk");
+        assert_eq!(t.labels(), vec!["home"]);
+        assert_eq!(t.due().unwrap().format("%Y-%m-%d").to_string(), "2025-01-05");
+        assert!(t.url().starts_with("file://"));
+
+        let tp = TaskPatch {
+            task: Some(t.clone_boxed().into()),
+            name: ValuePatch::Value("Buy oat milk #home".to_string()),
+            priority: ValuePatch::Value(Priority::High),
+            ..TaskPatch::default()
+        };
+        assert!(p.update(&[tp]).await.is_empty());
+
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Plan the week".to_string()),
+            ..TaskPatch::default()
+        };
+        p.create("work/todo.md", &tp).await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(root.join("notes.md")).unwrap(),
+            "# Notes\n* [ ] Buy oat milk #home due:2025-01-05 ⏫\n- [ ] Not a task\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(root.join("work").join("todo.md")).unwrap(),
+            "* [x] Send report\n* [ ] Plan the week\n"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn archive_deleted_tasks() {
+        let root = Path::new("/vault");
+        let fs = Arc::new(MemoryFileSystem::new().with_file(
+            &root.join("daily.md"),
+            "# Today\n- [ ] Buy milk\n    oat\n- [ ] Call mom\n",
+        ));
+
+        let archive = root.join("archive").join("tasks.md");
+        let mut c = client::Client::new(root)
+            .with_delete_mode(DeleteMode::Archive(archive.clone()))
+            .with_file_system(fs.clone());
+        let tasks = c.tasks(&filter::Filter::full_filter()).await.unwrap();
+        assert_eq!(tasks.len(), 2);
+        c.delete_task(&tasks[0]).await.unwrap();
+
+        assert_eq!(
+            fs.read_to_string(&root.join("daily.md")).await.unwrap(),
+            "# Today\n- [ ] Call mom\n"
+        );
+        assert_eq!(fs.read_to_string(&archive).await.unwrap(), "- [ ] Buy milk\n    oat\n");
+
+        // the task deleted from the archive is removed
+        let tasks = c.tasks(&filter::Filter::full_filter()).await.unwrap();
+        let archived = tasks.iter().find(|t| t.file_path == archive).unwrap();
+        c.delete_task(archived).await.unwrap();
+        assert_eq!(fs.read_to_string(&archive).await.unwrap(), "");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn interrupted_write_is_recovered() {
+        let root = Path::new("/vault");
+        let fs = Arc::new(
+            MemoryFileSystem::new()
+                .with_file(&root.join("daily.md"), "- [ ] Buy milk\n")
+                .with_file(&root.join(".daily.md.tatuin-tmp"), "- [x] Bu"),
+        );
+        let mut p = Provider::new(Config::new("test_app", "test_name"), root, Conventions::default())
+            .with_file_system(fs.clone());
+
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(projects.iter().map(|p| p.id()).collect::<Vec<_>>(), vec!["daily.md"]);
+        assert_eq!(fs.files(root).await.unwrap(), vec![root.join("daily.md")]);
+    }
+}
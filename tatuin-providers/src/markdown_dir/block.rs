@@ -0,0 +1,133 @@
+// SPDX-License-Identifier: MIT
+
+// The note lines are grouped into blocks to tell apart the lines under a task:
+// only the indented text right after the task is its description,
+// the nested tasks and the code blocks that follow it are kept untouched when the task is patched
+
+use super::indent;
+
+const FENCES: [&str; 2] = ["```", "~~~"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    // a single task line
+    Task,
+    // the indented lines that are neither tasks nor code
+    Indented,
+    // a fenced code block with its fences, the tasks inside it aren't parsed
+    Code,
+    Text,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block<'a> {
+    pub kind: BlockKind,
+    // the char position of the first line in the content
+    pub pos: usize,
+    pub lines: Vec<&'a str>,
+}
+
+fn fence(line: &str) -> Option<&'static str> {
+    let l = indent::trim_str(line);
+    FENCES.into_iter().find(|f| l.starts_with(f))
+}
+
+pub fn blocks<'a>(content: &'a str, is_task: impl Fn(&str) -> bool) -> Vec<Block<'a>> {
+    let mut result: Vec<Block> = Vec::new();
+    let mut pos = 0;
+    let mut open_fence: Option<&str> = None;
+
+    for l in content.split('\n') {
+        let (kind, starts_block) = if let Some(f) = open_fence {
+            if fence(l) == Some(f) {
+                open_fence = None;
+            }
+            (BlockKind::Code, false)
+        } else if let Some(f) = fence(l) {
+            open_fence = Some(f);
+            (BlockKind::Code, true)
+        } else if is_task(l) {
+            (BlockKind::Task, true)
+        } else if indent::exists(l) {
+            (BlockKind::Indented, false)
+        } else {
+            (BlockKind::Text, false)
+        };
+
+        match result.last_mut() {
+            Some(b) if b.kind == kind && !starts_block => b.lines.push(l),
+            _ => result.push(Block {
+                kind,
+                pos,
+                lines: vec![l],
+            }),
+        }
+
+        pos += l.chars().count() + 1;
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(content: &str) -> Vec<(BlockKind, usize)> {
+        blocks(content, |l| indent::trim_str(l).starts_with("- ["))
+            .iter()
+            .map(|b| (b.kind, b.lines.len()))
+            .collect()
+    }
+
+    #[test]
+    fn blocks_test() {
+        struct Case<'a> {
+            name: &'a str,
+            content: &'a str,
+            expected: &'a [(BlockKind, usize)],
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "task with description",
+                content: "# Title\n- [ ] task\n  line 1\n  line 2",
+                expected: &[(BlockKind::Text, 1), (BlockKind::Task, 1), (BlockKind::Indented, 2)],
+            },
+            Case {
+                name: "nested tasks",
+                content: "- [ ] task\n  description\n  - [ ] subtask\n  - [x] another subtask",
+                expected: &[
+                    (BlockKind::Task, 1),
+                    (BlockKind::Indented, 1),
+                    (BlockKind::Task, 1),
+                    (BlockKind::Task, 1),
+                ],
+            },
+            Case {
+                name: "code block with a task inside",
+                content: "- [ ] task\n  ```md\n  - [ ] not a task\n\n  ```\n  text",
+                expected: &[(BlockKind::Task, 1), (BlockKind::Code, 4), (BlockKind::Indented, 1)],
+            },
+            Case {
+                name: "different fences",
+                content: "~~~\n```\n~~~\n```\n```",
+                expected: &[(BlockKind::Code, 3), (BlockKind::Code, 2)],
+            },
+            Case {
+                name: "unclosed code block",
+                content: "```\n- [ ] not a task",
+                expected: &[(BlockKind::Code, 2)],
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(kinds(c.content), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn positions_test() {
+        let b = blocks("тест\n- [ ] task\n  ```", |l| l.starts_with("- ["));
+        assert_eq!(b.iter().map(|b| b.pos).collect::<Vec<usize>>(), vec![0, 5, 16]);
+    }
+}
@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: MIT
+
+use super::{
+    conventions::Conventions,
+    delete_mode::DeleteMode,
+    dialect::Dialect,
+    fs, journal, md_file,
+    patch::{PatchError, TaskPatch},
+    task::Task,
+};
+use itertools::Itertools;
+use std::cmp::Ordering;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tatuin_core::{
+    filter,
+    fs::{LocalFileSystem, SharedFileSystem},
+    intern::intern,
+};
+use tokio::sync::{OnceCell, Semaphore};
+use tracing::Level;
+
+const SIMULTANEOUS_JOB_COUNT: usize = 10;
+
+fn note_project(
+    parser: &md_file::File,
+    root: &Path,
+    file: &Path,
+    project_keys: &[String],
+    folder_depth: usize,
+) -> Option<String> {
+    parser
+        .frontmatter_value(project_keys)
+        .or_else(|| fs::folder(root, file, folder_depth))
+}
+
+pub struct Client {
+    path: PathBuf,
+    conventions: Arc<Conventions>,
+    dialect: Option<Arc<dyn Dialect>>,
+    delete_mode: DeleteMode,
+    project_keys: Vec<String>,
+    folder_depth: usize,
+    fs: SharedFileSystem,
+    recovered: OnceCell<()>,
+}
+
+impl Client {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.into(),
+            conventions: Arc::new(Conventions::default()),
+            dialect: None,
+            delete_mode: DeleteMode::default(),
+            project_keys: Vec::new(),
+            folder_depth: 0,
+            fs: LocalFileSystem::shared(),
+            recovered: OnceCell::new(),
+        }
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    pub fn with_conventions(mut self, c: Conventions) -> Self {
+        self.conventions = Arc::new(c);
+        self
+    }
+
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub fn with_dialect(mut self, d: Arc<dyn Dialect>) -> Self {
+        self.dialect = Some(d);
+        self
+    }
+
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub fn with_delete_mode(mut self, m: DeleteMode) -> Self {
+        self.delete_mode = m;
+        self
+    }
+
+    // The tasks take their project from the first of the frontmatter keys that exists in their note
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub fn with_project_keys(mut self, keys: &[String]) -> Self {
+        self.project_keys = keys.to_vec();
+        self
+    }
+
+    // The tasks of the notes in the subfolders take the first folders of the path up to the depth as their project,
+    // the frontmatter project goes first
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub fn with_folder_depth(mut self, depth: usize) -> Self {
+        self.folder_depth = depth;
+        self
+    }
+
+    // The distinct frontmatter and folder projects of all the notes
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub async fn named_projects(&self) -> Result<Vec<String>, std::io::Error> {
+        if self.project_keys.is_empty() && self.folder_depth == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut result = Vec::new();
+        for f in self.all_supported_files().await? {
+            let mut parser = self.file(&f);
+            if parser.open().await.is_ok()
+                && let Some(p) = note_project(&parser, &self.path, &f, &self.project_keys, self.folder_depth)
+            {
+                result.push(p);
+            }
+        }
+        Ok(result.into_iter().sorted().dedup().collect())
+    }
+
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub fn conventions(&self) -> &Conventions {
+        &self.conventions
+    }
+
+    pub fn root_path(&self) -> PathBuf {
+        self.path.clone()
+    }
+
+    pub async fn all_supported_files(&self) -> Result<Vec<PathBuf>, std::io::Error> {
+        self.recover().await;
+        Ok(self
+            .fs
+            .files(&self.path)
+            .await?
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|ext| ext == "md"))
+            .collect())
+    }
+
+    // The writes interrupted by the previous run are finished before the first access to the files
+    async fn recover(&self) {
+        self.recovered
+            .get_or_init(|| async {
+                if let Err(e) = journal::recover(self.fs.as_ref(), &self.path).await {
+                    tracing::error!(error=?e, path=?self.path, "Recover the interrupted writes");
+                }
+            })
+            .await;
+    }
+
+    fn file(&self, p: &Path) -> md_file::File {
+        md_file::File::new(p)
+            .with_conventions(self.conventions.clone())
+            .with_file_system(self.fs.clone())
+    }
+
+    pub async fn tasks(&self, f: &filter::Filter) -> Result<Vec<Task>, Box<dyn Error>> {
+        let span = tracing::span!(Level::TRACE, "tasks", path=?self.path,  filter = ?&f, "Load tasks");
+        let _enter = span.enter();
+
+        let files = self.all_supported_files().await?;
+
+        let mut tasks: Vec<Task> = Vec::new();
+
+        let semaphore = Arc::new(Semaphore::new(SIMULTANEOUS_JOB_COUNT));
+
+        let mut jobs = Vec::new();
+
+        for f in files {
+            let semaphore = semaphore.clone();
+            let p = self.path.clone();
+            let conventions = self.conventions.clone();
+            let dialect = self.dialect.clone();
+            let project_keys = self.project_keys.clone();
+            let folder_depth = self.folder_depth;
+            let fs = self.fs.clone();
+
+            let job = tokio::spawn(async move {
+                let _permit = semaphore.acquire().await.unwrap();
+
+                let mut tasks = Vec::new();
+
+                let mut parser = md_file::File::new(&f)
+                    .with_conventions(conventions)
+                    .with_file_system(fs);
+                if parser.open().await.is_ok() {
+                    tasks = parser
+                        .tasks()
+                        .await
+                        .map_err(|e| format!("Parse the file {}: {e}", f.display()))?;
+                    let project = note_project(&parser, &p, &f, &project_keys, folder_depth).map(|p| intern(&p));
+                    for t in &mut tasks {
+                        t.set_root_path(&p);
+                        t.project = project.clone();
+                        if let Some(d) = &dialect {
+                            t.set_dialect(d.clone());
+                        }
+                    }
+                }
+                drop(_permit);
+                Ok::<Vec<Task>, String>(tasks)
+            });
+
+            jobs.push(job);
+        }
+
+        for job in jobs {
+            let mut response = job
+                .await?
+                .map_err(Box::<dyn Error>::from)?
+                .iter()
+                .filter(|t| f.accept(*t))
+                .cloned()
+                .collect::<Vec<Task>>();
+
+            tasks.append(&mut response);
+        }
+
+        drop(_enter);
+        Ok(tasks)
+    }
+
+    pub async fn patch_tasks<'a>(&mut self, patches: &'a [TaskPatch<'a>]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        let mut files: Vec<&'a Path> = Vec::new();
+        for p in patches {
+            files.push(&p.task.file_path);
+        }
+
+        for file in files.iter().unique() {
+            let mut f = self.file(file);
+            if let Err(e) = f.open().await {
+                errors.extend(
+                    patches
+                        .iter()
+                        .filter(|p| p.task.file_path.cmp(&file.to_path_buf()) == Ordering::Equal)
+                        .map(|p| PatchError {
+                            task: p.task.clone(),
+                            error: e.to_string(),
+                        }),
+                );
+                continue;
+            }
+            let mut file_patches = patches
+                .iter()
+                .filter(|p| p.task.file_path.cmp(&file.to_path_buf()) == Ordering::Equal)
+                .collect::<Vec<&'a TaskPatch>>();
+            file_patches.sort_by_key(|p| std::cmp::Reverse(p.task.start_pos));
+            for p in file_patches {
+                if let Err(e) = f.patch_task(p).await {
+                    errors.push(PatchError {
+                        task: p.task.clone(),
+                        error: e.to_string(),
+                    });
+                }
+            }
+            if let Err(e) = f.flush().await {
+                errors.extend(
+                    patches
+                        .iter()
+                        .filter(|p| p.task.file_path.cmp(&file.to_path_buf()) == Ordering::Equal)
+                        .map(|p| PatchError {
+                            task: p.task.clone(),
+                            error: e.to_string(),
+                        }),
+                );
+            }
+        }
+
+        errors
+    }
+
+    pub async fn add_task(&mut self, file_path: &Path, t: &Task) -> Result<(), Box<dyn Error>> {
+        let mut f = self.file(file_path);
+        if self.fs.exists(file_path).await {
+            f.open().await?;
+        }
+        f.append_task(t);
+        f.flush().await
+    }
+
+    pub async fn delete_task(&mut self, t: &Task) -> Result<(), Box<dyn Error>> {
+        let mut f = self.file(&t.file_path);
+        f.open().await?;
+        match &self.delete_mode {
+            DeleteMode::Remove => f.delete_task(t).await?,
+            DeleteMode::Comment => f.comment_task(t).await?,
+            // the archived tasks are removed from the archive itself with their descriptions
+            DeleteMode::Archive(p) if *p == t.file_path => {
+                f.take_task(t).await?;
+            }
+            DeleteMode::Archive(p) => {
+                let text = f.take_task(t).await?;
+                // the archive is written first to not lose the task if it fails
+                let mut archive = self.file(p);
+                if self.fs.exists(p).await {
+                    archive.open().await?;
+                } else if let Some(dir) = p.parent() {
+                    self.fs.create_dir_all(dir).await?;
+                }
+                archive.append_text(&text);
+                archive.flush().await?;
+            }
+        }
+        f.flush().await
+    }
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+
+use regex::Regex;
+use tatuin_core::StringError;
+
+pub const DUE_EMOJI: char = '📅';
+pub const SCHEDULED_EMOJI: char = '⏳';
+pub const COMPLETED_EMOJI: char = '✅';
+
+const DEFAULT_TASK_REGEX: &str = r"^\s*-\ \[(.)\]\ (.*)$";
+const DEFAULT_BULLET: &str = "-";
+
+// The markers are written right before the date, so they should contain the separator (e.g. "📅 " or "due:")
+#[derive(Debug, Clone)]
+pub struct Conventions {
+    pub task_re: Regex,
+    pub bullet: String,
+    pub due_marker: String,
+    pub scheduled_marker: String,
+    pub completed_marker: String,
+}
+
+impl Default for Conventions {
+    fn default() -> Self {
+        Self {
+            task_re: Regex::new(DEFAULT_TASK_REGEX).unwrap(),
+            bullet: DEFAULT_BULLET.to_string(),
+            due_marker: format!("{DUE_EMOJI} "),
+            scheduled_marker: format!("{SCHEDULED_EMOJI} "),
+            completed_marker: format!("{COMPLETED_EMOJI} "),
+        }
+    }
+}
+
+impl Conventions {
+    pub fn new(
+        task_regex: Option<&str>,
+        bullet: Option<&str>,
+        due_marker: Option<&str>,
+        scheduled_marker: Option<&str>,
+        completed_marker: Option<&str>,
+    ) -> Result<Self, StringError> {
+        let mut c = Self::default();
+
+        if let Some(re) = task_regex {
+            c.task_re = Regex::new(re).map_err(|e| StringError::new(e.to_string().as_str()))?;
+            // the first group is the state char and the second one is the task text
+            if c.task_re.captures_len() < 3 {
+                return Err(StringError::new(
+                    "The task regex should have two capture groups: the state and the text",
+                ));
+            }
+        }
+
+        let markers = [
+            (&mut c.bullet, bullet),
+            (&mut c.due_marker, due_marker),
+            (&mut c.scheduled_marker, scheduled_marker),
+            (&mut c.completed_marker, completed_marker),
+        ];
+        for (field, value) in markers {
+            if let Some(v) = value {
+                if v.trim().is_empty() {
+                    return Err(StringError::new("The markers can't be empty"));
+                }
+                *field = v.to_string();
+            }
+        }
+
+        Ok(c)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Conventions;
+
+    #[test]
+    fn new_conventions_test() {
+        let c = Conventions::new(None, None, None, None, None).unwrap();
+        assert_eq!(c.task_re.as_str(), Conventions::default().task_re.as_str());
+        assert_eq!(c.due_marker, "📅 ");
+
+        let c = Conventions::new(Some(r"^\s*\*\ \[(.)\]\ (.*)$"), Some("*"), Some("due:"), None, None).unwrap();
+        assert_eq!(c.bullet, "*");
+        assert_eq!(c.due_marker, "due:");
+        assert_eq!(c.scheduled_marker, "⏳ ");
+
+        assert!(Conventions::new(Some(r"^- \[.\] .*$"), None, None, None, None).is_err());
+        assert!(Conventions::new(Some(r"^- \[(.\] .*$"), None, None, None, None).is_err());
+        assert!(Conventions::new(None, None, Some(" "), None, None).is_err());
+    }
+}
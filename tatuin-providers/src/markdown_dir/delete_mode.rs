@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+
+use std::path::{Path, PathBuf};
+
+use tatuin_core::StringError;
+
+const DEFAULT_ARCHIVE_NOTE: &str = "Archive.md";
+
+// What happens with the task line (and its description) when the task is deleted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum DeleteMode {
+    #[default]
+    Remove,
+    // the lines are wrapped into `<!-- deleted: ... -->` and stay in the note
+    Comment,
+    // the lines are moved to the end of the note
+    Archive(PathBuf),
+}
+
+impl DeleteMode {
+    // The archive note is relative to the root folder
+    pub fn new(mode: Option<&str>, archive_note: Option<&str>, root: &Path) -> Result<Self, StringError> {
+        match mode.unwrap_or("remove") {
+            "remove" => Ok(Self::Remove),
+            "comment" => Ok(Self::Comment),
+            "archive" => Ok(Self::Archive(root.join(archive_note.unwrap_or(DEFAULT_ARCHIVE_NOTE)))),
+            m => Err(StringError::new(
+                format!("Unknown delete mode '{m}', it should be remove, comment or archive").as_str(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn new_delete_mode_test() {
+        let root = Path::new("/vault");
+
+        struct Case<'a> {
+            name: &'a str,
+            mode: Option<&'a str>,
+            archive_note: Option<&'a str>,
+            expected: Option<DeleteMode>,
+        }
+        let cases: &[Case] = &[
+            Case {
+                name: "default",
+                mode: None,
+                archive_note: None,
+                expected: Some(DeleteMode::Remove),
+            },
+            Case {
+                name: "comment",
+                mode: Some("comment"),
+                archive_note: Some("Done.md"),
+                expected: Some(DeleteMode::Comment),
+            },
+            Case {
+                name: "archive to the default note",
+                mode: Some("archive"),
+                archive_note: None,
+                expected: Some(DeleteMode::Archive(PathBuf::from("/vault/Archive.md"))),
+            },
+            Case {
+                name: "archive to the custom note",
+                mode: Some("archive"),
+                archive_note: Some("Tasks/Deleted.md"),
+                expected: Some(DeleteMode::Archive(PathBuf::from("/vault/Tasks/Deleted.md"))),
+            },
+            Case {
+                name: "unknown mode",
+                mode: Some("trash"),
+                archive_note: None,
+                expected: None,
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                DeleteMode::new(c.mode, c.archive_note, root).ok(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -0,0 +1,42 @@
+// SPDX-License-Identifier: MIT
+
+use tatuin_core::{RichString, RichStringTransformerTrait};
+
+use super::{md_file::TAG_RE, task::Task};
+
+// The hooks for the tools (e.g. Obsidian) that render the notes in their own way
+pub trait Dialect: Send + Sync + std::fmt::Debug {
+    fn name(&self, t: &Task) -> RichString;
+    fn description(&self, t: &Task) -> Option<RichString>;
+    fn url(&self, t: &Task) -> String;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TagsRemover {}
+
+impl RichStringTransformerTrait for TagsRemover {
+    fn transform(&self, s: &str) -> String {
+        clear_tags(s)
+    }
+}
+
+pub fn clear_tags(name: &str) -> String {
+    TAG_RE.replace_all(name, "").to_string()
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Plain {}
+
+impl Dialect for Plain {
+    fn name(&self, t: &Task) -> RichString {
+        RichString::new(&t.name).with_transformer(Box::new(TagsRemover {}))
+    }
+
+    fn description(&self, t: &Task) -> Option<RichString> {
+        t.description.as_ref().map(|d| RichString::new(&d.text))
+    }
+
+    fn url(&self, t: &Task) -> String {
+        format!("file://{}", t.file_path.display())
+    }
+}
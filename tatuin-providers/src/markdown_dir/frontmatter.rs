@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT
+
+use yaml_rust2::{Yaml, YamlLoader};
+
+const DELIMITER: &str = "---";
+
+// The YAML block between the `---` lines at the very beginning of the note
+fn parse(content: &str) -> Option<Yaml> {
+    let mut lines = content.lines();
+    if lines.next()?.trim_end() != DELIMITER {
+        return None;
+    }
+
+    let yaml = lines
+        .take_while(|l| l.trim_end() != DELIMITER)
+        .collect::<Vec<&str>>()
+        .join("\n");
+    YamlLoader::load_from_str(&yaml).ok()?.into_iter().next()
+}
+
+// The wiki links are replaced by their aliases or the note names
+fn plain(s: &str) -> String {
+    let s = s.trim();
+    match s.strip_prefix("[[").and_then(|s| s.strip_suffix("]]")) {
+        Some(link) => match link.split_once('|') {
+            Some((_, alias)) => alias.to_string(),
+            None => link.rsplit('/').next().unwrap_or(link).to_string(),
+        },
+        None => s.to_string(),
+    }
+}
+
+fn as_string(v: &Yaml) -> Option<String> {
+    match v {
+        Yaml::String(s) => Some(plain(s)),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Real(r) => Some(r.clone()),
+        // the first item is taken from the lists
+        Yaml::Array(a) => a.iter().find_map(as_string),
+        _ => None,
+    }
+    .filter(|s| !s.is_empty())
+}
+
+// Returns the value of the first key that exists in the frontmatter
+pub fn value(content: &str, keys: &[String]) -> Option<String> {
+    if keys.is_empty() {
+        return None;
+    }
+
+    let yaml = parse(content)?;
+    keys.iter().find_map(|k| as_string(&yaml[k.as_str()]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn value_test() {
+        let keys = ["project".to_string(), "area".to_string()];
+
+        struct Case<'a> {
+            name: &'a str,
+            content: &'a str,
+            expected: Option<&'a str>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "no frontmatter",
+                content: "# Note\n- [ ] task",
+                expected: None,
+            },
+            Case {
+                name: "project",
+                content: "---\nproject: Home\ntags: [a, b]\n---\n- [ ] task",
+                expected: Some("Home"),
+            },
+            Case {
+                name: "the first key wins",
+                content: "---\narea: Family\nproject: Home\n---\n",
+                expected: Some("Home"),
+            },
+            Case {
+                name: "the second key",
+                content: "---\narea: Family\n---\n",
+                expected: Some("Family"),
+            },
+            Case {
+                name: "wiki link",
+                content: "---\nproject: \"[[Projects/Home Renovation]]\"\n---\n",
+                expected: Some("Home Renovation"),
+            },
+            Case {
+                name: "wiki link with alias",
+                content: "---\nproject: \"[[Projects/Home Renovation|Renovation]]\"\n---\n",
+                expected: Some("Renovation"),
+            },
+            Case {
+                name: "list",
+                content: "---\nproject:\n  - Work\n  - Home\n---\n",
+                expected: Some("Work"),
+            },
+            Case {
+                name: "empty value",
+                content: "---\nproject:\narea: Family\n---\n",
+                expected: Some("Family"),
+            },
+            Case {
+                name: "not at the beginning",
+                content: "# Note\n---\nproject: Home\n---\n",
+                expected: None,
+            },
+            Case {
+                name: "broken yaml",
+                content: "---\nproject: [Home\n---\n",
+                expected: None,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                value(c.content, &keys).as_deref(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MIT
+
+use std::path::{Path, PathBuf};
+
+pub fn strip_root_str(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .map(|p| p.to_str().unwrap_or_default())
+        .unwrap_or_default()
+        .to_string()
+}
+
+// The first folders of the path relative to the root up to the depth, None for the files in the root
+pub fn folder(root: &Path, path: &Path, depth: usize) -> Option<String> {
+    let folders = path
+        .strip_prefix(root)
+        .ok()?
+        .parent()?
+        .components()
+        .take(depth)
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>();
+    (!folders.is_empty()).then(|| folders.join("/"))
+}
+
+/*
+* find file by name or by relative path
+*/
+#[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+pub fn find_file(p: &Path, name: &str) -> Result<PathBuf, std::io::Error> {
+    for e in std::fs::read_dir(p)? {
+        let entry = e?;
+        let path = entry.path();
+        if path.is_file() && path.ends_with(name) {
+            return Ok(path);
+        } else if path.is_dir()
+            && let Ok(f) = find_file(path.as_path(), name)
+        {
+            return Ok(f);
+        }
+    }
+
+    Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "requested file not found",
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn folder_test() {
+        let root = Path::new("/vault");
+
+        struct Case<'a> {
+            name: &'a str,
+            path: &'a str,
+            depth: usize,
+            expected: Option<&'a str>,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "turned off",
+                path: "/vault/Work/todo.md",
+                depth: 0,
+                expected: None,
+            },
+            Case {
+                name: "root note",
+                path: "/vault/daily.md",
+                depth: 1,
+                expected: None,
+            },
+            Case {
+                name: "top-level folder",
+                path: "/vault/Work/Reports/todo.md",
+                depth: 1,
+                expected: Some("Work"),
+            },
+            Case {
+                name: "two levels",
+                path: "/vault/Work/Reports/2025/todo.md",
+                depth: 2,
+                expected: Some("Work/Reports"),
+            },
+            Case {
+                name: "the depth is bigger than the path",
+                path: "/vault/Work/todo.md",
+                depth: 3,
+                expected: Some("Work"),
+            },
+            Case {
+                name: "outside of the root",
+                path: "/notes/Work/todo.md",
+                depth: 1,
+                expected: None,
+            },
+        ];
+
+        for c in CASES {
+            assert_eq!(
+                folder(root, Path::new(c.path), c.depth).as_deref(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MIT
+
+// The files are rewritten through a journal: the new content is written next to the file with its checksum
+// and synced, then it's written to a temp file that replaces the original one by renaming.
+// If the process is killed in the middle, the complete journals are applied on the next start
+// and the incomplete ones are dropped, so the file is either old or new, but never broken.
+
+use std::path::{Path, PathBuf};
+
+use tatuin_core::fs::FileSystem;
+
+const JOURNAL_EXT: &str = "tatuin-journal";
+const TEMP_EXT: &str = "tatuin-tmp";
+const HEADER_PREFIX: &str = "tatuin-journal v1 ";
+
+fn sibling(path: &Path, ext: &str) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(format!(".{name}.{ext}"))
+}
+
+// The original file of the journal or the temp file
+fn original(path: &Path, ext: &str) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let name = name.strip_prefix('.')?.strip_suffix(ext)?.strip_suffix('.')?;
+    Some(path.with_file_name(name))
+}
+
+// The file system syncs the written content and the renamed directory entry
+async fn replace(fs: &dyn FileSystem, path: &Path, content: &str) -> Result<(), std::io::Error> {
+    let temp = sibling(path, TEMP_EXT);
+    fs.write(&temp, content.as_bytes()).await?;
+    fs.rename(&temp, path).await
+}
+
+pub async fn write(fs: &dyn FileSystem, path: &Path, content: &str) -> Result<(), std::io::Error> {
+    let journal = sibling(path, JOURNAL_EXT);
+    fs.write(
+        &journal,
+        format!("{HEADER_PREFIX}{}\n{content}", sha256::digest(content)).as_bytes(),
+    )
+    .await?;
+
+    replace(fs, path, content).await?;
+    fs.remove(&journal).await
+}
+
+// Returns the content of the journal if it was written completely
+fn journal_content(journal: &str) -> Option<&str> {
+    let (header, content) = journal.split_once('\n')?;
+    let checksum = header.strip_prefix(HEADER_PREFIX)?;
+    (sha256::digest(content) == checksum).then_some(content)
+}
+
+// Finishes the interrupted writes in the folder and its subfolders, returns the restored files
+pub async fn recover(fs: &dyn FileSystem, root: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut result = Vec::new();
+
+    for path in fs.files(root).await? {
+        if path.strip_prefix(root).is_ok_and(|p| p.starts_with(".git")) {
+            continue;
+        }
+
+        if let Some(file) = original(&path, JOURNAL_EXT) {
+            let journal = fs.read_to_string(&path).await.unwrap_or_default();
+            match journal_content(&journal) {
+                Some(content) => {
+                    replace(fs, &file, content).await?;
+                    tracing::warn!(target:"journal", file=?file, "Restore the interrupted write");
+                    result.push(file);
+                }
+                None => tracing::warn!(target:"journal", file=?file, "Drop the incomplete journal"),
+            }
+            fs.remove(&path).await?;
+        } else if original(&path, TEMP_EXT).is_some() && fs.exists(&path).await {
+            fs.remove(&path).await?;
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::fs::{LocalFileSystem, MemoryFileSystem};
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn write_test() {
+        let p = Path::new("/vault/daily.md");
+        let fs = MemoryFileSystem::new().with_file(p, "- [ ] Buy milk\n");
+
+        write(&fs, p, "- [x] Buy milk\n").await.unwrap();
+        assert_eq!(fs.read_to_string(p).await.unwrap(), "- [x] Buy milk\n");
+        assert_eq!(fs.files(Path::new("/vault")).await.unwrap(), vec![p.to_path_buf()]);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn local_write_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let p = temp_dir.path().join("daily.md");
+        let fs = LocalFileSystem::shared();
+        fs.write(&p, b"- [ ] Buy milk\n").await.unwrap();
+
+        write(fs.as_ref(), &p, "- [x] Buy milk\n").await.unwrap();
+        assert_eq!(fs.read_to_string(&p).await.unwrap(), "- [x] Buy milk\n");
+        assert_eq!(fs.files(temp_dir.path()).await.unwrap(), vec![p]);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn recover_test() {
+        let root = Path::new("/vault");
+
+        // killed after the journal was written
+        let complete = root.join("notes").join("daily.md");
+        // killed while the journal was written
+        let incomplete = root.join("todo.md");
+        let fs = MemoryFileSystem::new()
+            .with_file(&complete, "- [ ] Buy milk\n")
+            .with_file(
+                &sibling(&complete, JOURNAL_EXT),
+                format!(
+                    "{HEADER_PREFIX}{}\n- [x] Buy milk\n",
+                    sha256::digest("- [x] Buy milk\n")
+                )
+                .as_str(),
+            )
+            .with_file(&sibling(&complete, TEMP_EXT), "- [x] Bu")
+            .with_file(&incomplete, "- [ ] Call mom\n")
+            .with_file(
+                &sibling(&incomplete, JOURNAL_EXT),
+                format!("{HEADER_PREFIX}{}\n- [x] Ca", sha256::digest("- [x] Call mom\n")).as_str(),
+            );
+
+        assert_eq!(recover(&fs, root).await.unwrap(), vec![complete.clone()]);
+        assert_eq!(fs.read_to_string(&complete).await.unwrap(), "- [x] Buy milk\n");
+        assert_eq!(fs.read_to_string(&incomplete).await.unwrap(), "- [ ] Call mom\n");
+        assert_eq!(fs.files(root).await.unwrap(), vec![complete, incomplete]);
+    }
+
+    #[test]
+    fn original_test() {
+        let p = Path::new("/vault/notes/daily.md");
+        assert_eq!(original(&sibling(p, JOURNAL_EXT), JOURNAL_EXT), Some(p.to_path_buf()));
+        assert_eq!(original(&sibling(p, TEMP_EXT), JOURNAL_EXT), None);
+        assert_eq!(original(p, JOURNAL_EXT), None);
+    }
+}
@@ -1,30 +1,36 @@
 // SPDX-License-Identifier: MIT
 
-use crate::obsidian::{description::Description, indent, state::State, task::Task};
+use super::{
+    block::{self, BlockKind},
+    conventions::Conventions,
+    description::Description,
+    frontmatter, indent, journal,
+    state::State,
+    task::Task,
+};
 use chrono::{NaiveDate, Utc};
 use regex::Regex;
-use std::fs;
 use std::path::Path;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock};
 use std::{error::Error, path::PathBuf};
 use tatuin_core::{
-    task::{DateTimeUtc, Priority},
+    fs::{LocalFileSystem, SharedFileSystem},
+    intern::{IStr, intern},
+    task::{DateTimeUtc, Priority, RECURRENCE_EMOJI, Recurrence},
     task_patch::ValuePatch,
+    time,
 };
 
 use super::patch::TaskPatch;
 
-static TASK_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*-\ \[(.)\]\ (.*)$").unwrap());
 pub(crate) static TAG_RE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"( #((?:[^\x00-\x7F]|\w)(?:[^\x00-\x7F]|\w|-|_|\/)+))").unwrap());
 
-const DUE_EMOJI: char = '📅';
-const SCHEDULED_EMOJI: char = '⏳';
-const COMPLETED_EMOJI: char = '✅';
-
 pub struct File {
     file_path: PathBuf,
     content: String,
+    conventions: Arc<Conventions>,
+    fs: SharedFileSystem,
 }
 
 impl File {
@@ -32,16 +38,28 @@ impl File {
         Self {
             file_path: file_path.into(),
             content: String::new(),
+            conventions: Arc::new(Conventions::default()),
+            fs: LocalFileSystem::shared(),
         }
     }
 
-    pub fn open(&mut self) -> Result<(), std::io::Error> {
-        self.content = fs::read_to_string(&self.file_path)?;
+    pub fn with_conventions(mut self, c: Arc<Conventions>) -> Self {
+        self.conventions = c;
+        self
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    pub async fn open(&mut self) -> Result<(), std::io::Error> {
+        self.content = self.fs.read_to_string(&self.file_path).await?;
         Ok(())
     }
 
-    pub fn flush(&mut self) -> Result<(), Box<dyn Error>> {
-        if let Err(err) = fs::write(&self.file_path, &self.content) {
+    pub async fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Err(err) = journal::write(self.fs.as_ref(), &self.file_path, &self.content).await {
             return Err(Box::new(err));
         }
 
@@ -52,6 +70,10 @@ impl File {
         self.tasks_from_content(&self.content)
     }
 
+    pub fn frontmatter_value(&self, keys: &[String]) -> Option<String> {
+        frontmatter::value(&self.content, keys)
+    }
+
     pub async fn patch_task(&mut self, p: &TaskPatch<'_>) -> Result<(), Box<dyn Error>> {
         self.content = self.patch_task_in_content(p, self.content.as_str())?;
         Ok(())
@@ -62,73 +84,94 @@ impl File {
         Ok(())
     }
 
+    pub async fn comment_task(&mut self, t: &Task) -> Result<(), Box<dyn Error>> {
+        self.content = self.comment_task_in_content(t, self.content.as_str())?;
+        Ok(())
+    }
+
+    // Removes the task with its description and returns their lines without the task indent
+    pub async fn take_task(&mut self, t: &Task) -> Result<String, Box<dyn Error>> {
+        let (content, text) = self.take_task_from_content(t, self.content.as_str())?;
+        self.content = content;
+        Ok(text)
+    }
+
+    pub fn append_text(&mut self, text: &str) {
+        if !self.content.is_empty() && !self.content.ends_with('\n') {
+            self.content.push('\n');
+        }
+        self.content.push_str(text);
+        self.content.push('\n');
+    }
+
+    pub fn append_task(&mut self, t: &Task) {
+        if !self.content.is_empty() && !self.content.ends_with('\n') {
+            self.content.push('\n');
+        }
+        self.content.push_str(task_to_string(t, "", &self.conventions).as_str());
+        self.content.push('\n');
+    }
+
     fn try_parse_task(&self, line: &str, pos: usize) -> Option<Task> {
-        let caps = TASK_RE.captures(line)?;
+        let caps = self.conventions.task_re.captures(line)?;
 
         let text = String::from(&caps[2]);
-        let (text, due) = extract_date_after_emoji(text.as_str(), DUE_EMOJI);
-        let (text, scheduled) = extract_date_after_emoji(text.as_str(), SCHEDULED_EMOJI);
-        let (text, completed_at) = extract_date_after_emoji(text.as_str(), COMPLETED_EMOJI);
+        let (text, due) = extract_date_after_marker(text.as_str(), &self.conventions.due_marker);
+        let (text, scheduled) = extract_date_after_marker(text.as_str(), &self.conventions.scheduled_marker);
+        let (text, completed_at) = extract_date_after_marker(text.as_str(), &self.conventions.completed_marker);
         let (text, priority) = extract_priority(text.as_str());
+        let (text, recurrence) = Recurrence::extract(text.as_str());
 
         let tags = TAG_RE
             .captures_iter(text.clone().as_str())
-            .map(|tag_cap| tag_cap[2].to_string())
-            .collect::<Vec<String>>();
+            .map(|tag_cap| intern(&tag_cap[2]))
+            .collect::<Vec<IStr>>();
+
+        // the custom task regex could match the line with an empty state
+        let Some(state) = caps[1].chars().next().map(State::new) else {
+            tracing::warn!(target:"md_file", line=line, "Skip the task with an empty state");
+            return None;
+        };
 
         Some(Task {
             file_path: self.file_path.clone(),
             start_pos: pos,
             end_pos: pos + line.chars().count(),
-            state: {
-                let cap: &str = &caps[1];
-                match cap.chars().next() {
-                    Some(x) => State::new(x),
-                    None => {
-                        panic!("Something wrong with regexp parsing of '{line}' because state shouldn't be empty")
-                    }
-                }
-            },
+            state,
             name: text.trim().into(),
             due,
             scheduled,
             priority,
+            recurrence,
             completed_at,
             tags,
             ..Default::default()
         })
     }
 
-    fn tasks_from_content(&self, content: &str) -> Result<Vec<Task>, Box<dyn Error>> {
-        const SPLIT_TERMINATOR: &str = "\n";
-
+    pub fn tasks_from_content(&self, content: &str) -> Result<Vec<Task>, Box<dyn Error>> {
         let mut result: Vec<Task> = Vec::new();
 
-        let mut pos: usize = 0;
-
         let mut task: Option<Task> = None;
 
-        for l in content.split(SPLIT_TERMINATOR) {
-            if let Some(t) = self.try_parse_task(l, pos) {
-                if let Some(previous_task) = task {
-                    result.push(previous_task);
+        for b in block::blocks(content, |l| self.conventions.task_re.is_match(l)) {
+            match b.kind {
+                BlockKind::Task => {
+                    result.extend(task.take());
+                    task = self.try_parse_task(b.lines[0], b.pos);
                 }
-                task = Some(t);
-            } else if let Some(t) = &mut task {
-                if indent::exists(l) {
-                    t.description = Some(t.description.clone().unwrap_or(Description::new(pos)).append(l));
-                } else {
-                    result.push(t.clone());
-                    task = None;
+                // only the text right after the task line is its description
+                BlockKind::Indented => {
+                    if let Some(mut t) = task.take() {
+                        t.description = Some(b.lines.iter().fold(Description::new(b.pos), |d, l| d.append(l)));
+                        result.push(t);
+                    }
                 }
+                BlockKind::Code | BlockKind::Text => result.extend(task.take()),
             }
-
-            pos += l.chars().count() + SPLIT_TERMINATOR.len();
         }
 
-        if let Some(t) = task {
-            result.push(t);
-        }
+        result.extend(task);
 
         Ok(result)
     }
@@ -197,10 +240,18 @@ impl File {
             .skip(current_task.start_pos)
             .take_while(indent::is_indent)
             .collect::<String>();
+        // the next occurrence of the completed recurring task is added above it like the Obsidian Tasks plugin does
+        let next_occurrence = if current_task.state != State::Completed && new_task.state == State::Completed {
+            next_occurrence(&new_task, &time::today())
+                .map(|t| format!("{}\n{indent}", task_to_string(&t, indent.as_str(), &self.conventions)))
+        } else {
+            None
+        };
         Ok([
             content.chars().take(current_task.start_pos).collect::<String>(),
             indent.clone(),
-            task_to_string(&new_task, indent.as_str()),
+            next_occurrence.unwrap_or_default(),
+            task_to_string(&new_task, indent.as_str(), &self.conventions),
             content
                 .chars()
                 .skip(
@@ -223,23 +274,89 @@ impl File {
         ]
         .join(""))
     }
+
+    fn comment_task_in_content(&self, t: &Task, content: &str) -> Result<String, Box<dyn Error>> {
+        self.check_task_was_not_changed(t, content)?;
+        let (start, end) = task_range(t);
+        let text = content.chars().skip(start).take(end - start).collect::<String>();
+        let indent = text.chars().take_while(indent::is_indent).collect::<String>();
+        Ok([
+            content.chars().take(start).collect::<String>(),
+            format!("{indent}<!-- deleted: {} -->", escape_comment(indent::trim_str(&text))),
+            content.chars().skip(end).collect::<String>(),
+        ]
+        .join(""))
+    }
+
+    fn take_task_from_content(&self, t: &Task, content: &str) -> Result<(String, String), Box<dyn Error>> {
+        self.check_task_was_not_changed(t, content)?;
+        let (start, end) = task_range(t);
+        let text = content.chars().skip(start).take(end - start).collect::<String>();
+        let indent = text.chars().take_while(indent::is_indent).collect::<String>();
+        let text = text
+            .split('\n')
+            .map(|l| l.strip_prefix(indent.as_str()).unwrap_or(l))
+            .collect::<Vec<&str>>()
+            .join("\n");
+        Ok((
+            [
+                content.chars().take(start).collect::<String>(),
+                content.chars().skip(end + 1).collect::<String>(),
+            ]
+            .join(""),
+            text,
+        ))
+    }
+}
+
+// The uncompleted copy of the recurring task, the scheduled date is moved by the same recurrence
+fn next_occurrence(t: &Task, today: &DateTimeUtc) -> Option<Task> {
+    let r = t.recurrence?;
+    Some(Task {
+        state: State::Uncompleted,
+        due: Some(r.next_due(&t.due, today)),
+        scheduled: t.scheduled.map(|d| r.next(&d)),
+        completed_at: None,
+        description: None,
+        ..t.clone()
+    })
+}
+
+// The comment ends on `--`, so a backslash is put between the dashes of the commented text
+fn escape_comment(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '-' && result.ends_with('-') {
+            result.push('\\');
+        }
+        result.push(c);
+    }
+    result
 }
 
-pub fn task_to_string(t: &Task, indent: &str) -> String {
+// The positions of the task line with its description, the line break after them isn't included
+fn task_range(t: &Task) -> (usize, usize) {
+    (t.start_pos, t.description.as_ref().map(|d| d.end).unwrap_or(t.end_pos))
+}
+
+pub fn task_to_string(t: &Task, indent: &str, c: &Conventions) -> String {
     let state_char: char = t.state.into();
-    let mut elements = vec![format!("- [{state_char}]"), t.name.clone()];
+    let mut elements = vec![format!("{} [{state_char}]", c.bullet), t.name.clone()];
+    if let Some(r) = &t.recurrence {
+        elements.push(format!("{RECURRENCE_EMOJI} {r}"))
+    }
     if let Some(d) = &t.due {
-        elements.push(format!("{DUE_EMOJI} {}", d.format("%Y-%m-%d")))
+        elements.push(format!("{}{}", c.due_marker, d.format("%Y-%m-%d")))
     }
     if let Some(d) = &t.scheduled {
-        elements.push(format!("{SCHEDULED_EMOJI} {}", d.format("%Y-%m-%d")))
+        elements.push(format!("{}{}", c.scheduled_marker, d.format("%Y-%m-%d")))
     }
     let priority_str = priority_to_str(&t.priority).to_string();
     if !priority_str.is_empty() {
         elements.push(priority_str);
     }
     if let Some(d) = &t.completed_at {
-        elements.push(format!("{COMPLETED_EMOJI} {}", d.format("%Y-%m-%d")))
+        elements.push(format!("{}{}", c.completed_marker, d.format("%Y-%m-%d")))
     }
     let mut s = elements.join(" ");
     if let Some(d) = &t.description {
@@ -252,8 +369,8 @@ pub fn task_to_string(t: &Task, indent: &str) -> String {
     s
 }
 
-fn extract_date_after_emoji(text: &str, emoji: char) -> (String, Option<DateTimeUtc>) {
-    let start = format!(" {emoji} ");
+fn extract_date_after_marker(text: &str, marker: &str) -> (String, Option<DateTimeUtc>) {
+    let start = format!(" {marker}");
     let idx = text.rfind(start.as_str());
     if idx.is_none() {
         return (text.to_string(), None);
@@ -338,16 +455,31 @@ fn extract_priority(text: &str) -> (String, Priority) {
 mod tests {
 
     use super::*;
+    use crate::markdown_dir::conventions::{COMPLETED_EMOJI, DUE_EMOJI, SCHEDULED_EMOJI};
     use tatuin_core::task::Task as TaskTrait;
 
     #[tokio::test]
     #[cfg_attr(miri, ignore)]
     async fn parse_not_exists_file_test() {
         let mut p = File::new(Path::new("/etc/file/not/exists"));
-        let err = p.open().unwrap_err();
+        let err = p.open().await.unwrap_err();
         assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
     }
 
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn open_and_flush_test() {
+        let path = Path::new("/vault/daily.md");
+        let fs = Arc::new(tatuin_core::fs::MemoryFileSystem::new().with_file(path, "- [ ] Buy milk"));
+        let mut f = File::new(path).with_file_system(fs.clone());
+        f.open().await.unwrap();
+        assert_eq!(f.tasks().await.unwrap().len(), 1);
+
+        f.append_text("- [ ] Call mom");
+        f.flush().await.unwrap();
+        assert_eq!(fs.content(path).unwrap(), b"- [ ] Buy milk\n- [ ] Call mom\n".to_vec());
+    }
+
     #[test]
     fn parse_content_test() {
         struct Case<'a> {
@@ -424,7 +556,7 @@ some another text
         let task = p.try_parse_task(text.as_str(), 0);
         assert!(task.is_some());
         let mut task = task.unwrap();
-        task.set_vault_path(Path::new("."));
+        task.set_root_path(Path::new("."));
 
         let task_trait: &dyn TaskTrait = &task;
         assert_eq!(task.name, "Some #tag task #группа/имя_tag-name123 text #tag_at_end");
@@ -436,7 +568,7 @@ some another text
         assert_eq!(task.scheduled.unwrap().format("%Y-%m-%d").to_string(), "2025-02-02");
         assert!(task.completed_at.is_some());
         assert_eq!(task.completed_at.unwrap().format("%Y-%m-%d").to_string(), "2025-01-01");
-        assert_eq!(task.tags, vec!["tag", "группа/имя_tag-name123", "tag_at_end"]);
+        assert_eq!(task.labels(), vec!["tag", "группа/имя_tag-name123", "tag_at_end"]);
     }
 
     #[test]
@@ -510,7 +642,7 @@ return indent back"
         ];
 
         for c in cases {
-            let (_, dt) = extract_date_after_emoji(c.line, DUE_EMOJI);
+            let (_, dt) = extract_date_after_marker(c.line, &format!("{DUE_EMOJI} "));
             assert_eq!(dt, c.expected, "Test {} was failed", c.name);
         }
     }
@@ -558,7 +690,7 @@ return indent back"
         ];
 
         for c in cases {
-            let (_, dt) = extract_date_after_emoji(&c.line, SCHEDULED_EMOJI);
+            let (_, dt) = extract_date_after_marker(&c.line, &format!("{SCHEDULED_EMOJI} "));
             assert_eq!(dt, c.expected, "Test {} was failed", c.name);
         }
     }
@@ -695,6 +827,71 @@ some text
         }
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn complete_recurring_task_in_content_test() {
+        let completed_string = format!(" ✅ {}", chrono::Utc::now().format("%Y-%m-%d"));
+        let content = format!(
+            "some text
+    - [ ] Pay rent 🔁 every month {DUE_EMOJI} 2025-01-31 {SCHEDULED_EMOJI} 2025-01-25
+        by card
+some text
+"
+        );
+
+        let p = File::new(Path::new(""));
+        let tasks = p.tasks_from_content(&content).unwrap();
+        assert_eq!(tasks[0].name, "Pay rent");
+        assert_eq!(
+            tasks[0].recurrence().map(|r| r.to_string()),
+            Some("every month".to_string())
+        );
+
+        let result = p
+            .patch_task_in_content(
+                &TaskPatch {
+                    task: &tasks[0],
+                    name: ValuePatch::NotSet,
+                    description: ValuePatch::NotSet,
+                    state: ValuePatch::Value(State::Completed),
+                    due: ValuePatch::NotSet,
+                    scheduled: ValuePatch::NotSet,
+                    priority: ValuePatch::NotSet,
+                },
+                &content,
+            )
+            .unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "some text
+    - [ ] Pay rent 🔁 every month {DUE_EMOJI} 2025-02-28 {SCHEDULED_EMOJI} 2025-02-25
+    - [x] Pay rent 🔁 every month {DUE_EMOJI} 2025-01-31 {SCHEDULED_EMOJI} 2025-01-25{completed_string}
+        by card
+some text
+"
+            )
+        );
+
+        // the task completed again isn't repeated
+        let tasks = p.tasks_from_content(&result).unwrap();
+        let result = p
+            .patch_task_in_content(
+                &TaskPatch {
+                    task: &tasks[1],
+                    name: ValuePatch::Value("Pay the rent".to_string()),
+                    description: ValuePatch::NotSet,
+                    state: ValuePatch::Value(State::Completed),
+                    due: ValuePatch::NotSet,
+                    scheduled: ValuePatch::NotSet,
+                    priority: ValuePatch::NotSet,
+                },
+                &result,
+            )
+            .unwrap();
+        assert_eq!(p.tasks_from_content(&result).unwrap().len(), 2);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn change_state_to_incomplete_in_content_test() {
@@ -1096,6 +1293,59 @@ Some another text";
                     priority: ValuePatch::NotSet,
                 },
             },
+            Case {
+                name: "change description followed by subtasks",
+                file_content_before: "- [ ] Parent
+    old description
+    - [ ] Subtask
+        subtask description
+"
+                .to_string(),
+                file_content_after: "- [ ] Parent
+    new description
+    - [ ] Subtask
+        subtask description
+"
+                .to_string(),
+                patch: TaskPatch {
+                    task: &Task::default(),
+                    name: ValuePatch::NotSet,
+                    description: ValuePatch::Value("new description".to_string()),
+                    state: ValuePatch::NotSet,
+                    due: ValuePatch::NotSet,
+                    scheduled: ValuePatch::NotSet,
+                    priority: ValuePatch::NotSet,
+                },
+            },
+            Case {
+                name: "add description before code block",
+                file_content_before: "- [ ] Parent
+    ```bash
+      cargo test
+    - [ ] not a task
+    ```
+    text after the code
+"
+                .to_string(),
+                file_content_after: "- [/] Parent
+    new description
+    ```bash
+      cargo test
+    - [ ] not a task
+    ```
+    text after the code
+"
+                .to_string(),
+                patch: TaskPatch {
+                    task: &Task::default(),
+                    name: ValuePatch::NotSet,
+                    description: ValuePatch::Value("new description".to_string()),
+                    state: ValuePatch::Value(State::InProgress),
+                    due: ValuePatch::NotSet,
+                    scheduled: ValuePatch::NotSet,
+                    priority: ValuePatch::NotSet,
+                },
+            },
         ];
 
         let p = File::new(Path::new(""));
@@ -1181,4 +1431,96 @@ Some another content
             assert_eq!(c.file_content_after, result, "Test '{}' was failed", c.name);
         }
     }
+
+    #[test]
+    fn soft_delete_task_test() {
+        struct Case<'a> {
+            name: &'a str,
+            file_content_before: &'a str,
+            commented: &'a str,
+            taken: &'a str,
+            taken_text: &'a str,
+        }
+        let cases: &[Case] = &[
+            Case {
+                name: "single task",
+                file_content_before: "- [ ] Buy milk #home",
+                commented: "<!-- deleted: - [ ] Buy milk #home -->",
+                taken: "",
+                taken_text: "- [ ] Buy milk #home",
+            },
+            Case {
+                name: "task with content",
+                file_content_before: "Some content
+- [ ] Buy milk #home
+Some another content
+",
+                commented: "Some content
+<!-- deleted: - [ ] Buy milk #home -->
+Some another content
+",
+                taken: "Some content
+Some another content
+",
+                taken_text: "- [ ] Buy milk #home",
+            },
+            Case {
+                name: "indented task with description",
+                file_content_before: "Some content
+  - [ ] Buy milk #home
+      oat or soy
+      2 liters
+Some another content
+",
+                commented: "Some content
+  <!-- deleted: - [ ] Buy milk #home
+      oat or soy
+      2 liters -->
+Some another content
+",
+                taken: "Some content
+Some another content
+",
+                taken_text: "- [ ] Buy milk #home
+    oat or soy
+    2 liters",
+            },
+            Case {
+                name: "task with the comment end",
+                file_content_before: "- [ ] Replace -> with --> or ---> in the docs
+- [ ] Buy milk #home
+",
+                commented: "<!-- deleted: - [ ] Replace -> with -\\-> or -\\-\\-> in the docs -->
+- [ ] Buy milk #home
+",
+                taken: "- [ ] Buy milk #home
+",
+                taken_text: "- [ ] Replace -> with --> or ---> in the docs",
+            },
+        ];
+
+        let p = File::new(Path::new(""));
+
+        for c in cases {
+            let tasks = p.tasks_from_content(c.file_content_before).unwrap();
+            let task = tasks[0].clone();
+
+            let r = p.comment_task_in_content(&task, c.file_content_before);
+            assert!(r.is_ok(), "{}: {}", c.name, r.unwrap_err());
+            let commented = r.unwrap();
+            assert_eq!(c.commented, commented, "Test '{}' was failed", c.name);
+            assert_eq!(
+                p.tasks_from_content(&commented).unwrap().len(),
+                tasks.len() - 1,
+                "Test '{}' was failed",
+                c.name
+            );
+
+            let r = p.take_task_from_content(&task, c.file_content_before);
+            assert!(r.is_ok(), "{}: {}", c.name, r.unwrap_err());
+            let (taken, taken_text) = r.unwrap();
+            assert_eq!(c.taken, taken, "Test '{}' was failed", c.name);
+            assert_eq!(c.taken_text, taken_text, "Test '{}' was failed", c.name);
+        }
+    }
 }
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+
+use std::path::{Path, PathBuf};
+
+use tatuin_core::{intern::IStr, project::Project as ProjectTrait};
+
+use super::fs;
+
+#[derive(Clone)]
+pub struct Project {
+    provider: IStr,
+    root_path: PathBuf,
+    file_path: PathBuf,
+    name: Option<IStr>,
+}
+
+impl Project {
+    pub fn new(provider: IStr, root_path: &Path, file_path: &Path) -> Self {
+        Self {
+            provider,
+            root_path: root_path.into(),
+            file_path: file_path.into(),
+            name: None,
+        }
+    }
+
+    // The named projects aren't bound to the notes, their names are their ids
+    #[cfg_attr(not(feature = "obsidian"), allow(dead_code))]
+    pub fn with_name(mut self, name: IStr) -> Self {
+        self.name = Some(name);
+        self
+    }
+}
+
+impl std::fmt::Debug for Project {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Project id={} name={}",
+            ProjectTrait::id(self),
+            ProjectTrait::name(self)
+        )
+    }
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        match &self.name {
+            Some(n) => n.to_string(),
+            None => fs::strip_root_str(&self.root_path, &self.file_path),
+        }
+    }
+
+    fn name(&self) -> String {
+        if let Some(n) = &self.name {
+            return n.to_string();
+        }
+
+        self.file_path
+            .file_name()
+            .unwrap_or_default()
+            .to_str()
+            .unwrap_or_default()
+            .strip_suffix(".md")
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        false
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
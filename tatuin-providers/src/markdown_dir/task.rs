@@ -1,23 +1,30 @@
 // SPDX-License-Identifier: MIT
 
-use crate::obsidian::internal_links_renderer::InternalLinksTransformer;
-
-use super::{description::Description, fs, project::Project, state::State};
+use super::{
+    description::Description,
+    dialect::{Dialect, Plain},
+    fs,
+    project::Project,
+    state::State,
+};
 use std::{
     any::Any,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use tatuin_core::{
     RichString,
+    intern::{IStr, to_strings},
     project::Project as ProjectTrait,
-    task::{DateTimeUtc, PatchPolicy, Priority, State as TaskState, Task as TaskTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, Recurrence, State as TaskState, Task as TaskTrait},
     task_patch::DatePatchItem,
 };
 
 #[derive(Debug, Clone, Default)]
 pub struct Task {
-    pub vault_path: PathBuf,
-    pub provider: String,
+    pub root_path: PathBuf,
+    pub provider: IStr,
+    pub dialect: Option<Arc<dyn Dialect>>,
 
     pub name: String,
     pub file_path: PathBuf,
@@ -29,7 +36,10 @@ pub struct Task {
     pub scheduled: Option<DateTimeUtc>,
     pub completed_at: Option<DateTimeUtc>,
     pub priority: Priority,
-    pub tags: Vec<String>,
+    pub recurrence: Option<Recurrence>,
+    pub tags: Vec<IStr>,
+    // the project from the note frontmatter, the note itself is the project otherwise
+    pub project: Option<IStr>,
 }
 
 impl PartialEq for Task {
@@ -42,6 +52,7 @@ impl PartialEq for Task {
             && self.due == o.due
             && self.scheduled == o.scheduled
             && self.priority == o.priority
+            && self.recurrence == o.recurrence
             && self.tags == o.tags
     }
 }
@@ -49,11 +60,19 @@ impl PartialEq for Task {
 impl Eq for Task {}
 
 impl Task {
-    pub fn set_vault_path(&mut self, p: &Path) {
-        self.vault_path = p.to_path_buf();
+    pub fn set_root_path(&mut self, p: &Path) {
+        self.root_path = p.to_path_buf();
     }
 
-    pub fn set_provider(&mut self, p: String) {
+    pub fn set_dialect(&mut self, d: Arc<dyn Dialect>) {
+        self.dialect = Some(d);
+    }
+
+    fn dialect(&self) -> Arc<dyn Dialect> {
+        self.dialect.clone().unwrap_or(Arc::new(Plain {}))
+    }
+
+    pub fn set_provider(&mut self, p: IStr) {
         self.provider = p;
     }
 }
@@ -67,15 +86,11 @@ impl TaskTrait for Task {
     }
 
     fn name(&self) -> RichString {
-        RichString::new(&self.name).with_transformer(Box::new(
-            InternalLinksTransformer::new(&self.vault_path).with_remove_tags(),
-        ))
+        self.dialect().name(self)
     }
 
     fn description(&self) -> Option<RichString> {
-        self.description.clone().map(|d| {
-            RichString::new(&d.text).with_transformer(Box::new(InternalLinksTransformer::new(&self.vault_path)))
-        })
+        self.dialect().description(self)
     }
 
     fn state(&self) -> TaskState {
@@ -85,7 +100,7 @@ impl TaskTrait for Task {
     fn place(&self) -> String {
         format!(
             "{}:{}",
-            fs::strip_root_str(&self.vault_path, &self.file_path),
+            fs::strip_root_str(&self.root_path, &self.file_path),
             self.start_pos,
         )
     }
@@ -107,23 +122,27 @@ impl TaskTrait for Task {
     }
 
     fn project(&self) -> Option<Box<dyn ProjectTrait>> {
-        Some(Box::new(Project::new(
-            &self.provider,
-            &self.vault_path,
-            &self.file_path,
-        )))
+        let p = Project::new(self.provider.clone(), &self.root_path, &self.file_path);
+        Some(Box::new(match &self.project {
+            Some(name) => p.with_name(name.clone()),
+            None => p,
+        }))
     }
 
     fn priority(&self) -> Priority {
         self.priority
     }
 
+    fn recurrence(&self) -> Option<Recurrence> {
+        self.recurrence
+    }
+
     fn url(&self) -> String {
-        fs::obsidian_url(&self.vault_path, &self.file_path)
+        self.dialect().url(self)
     }
 
     fn labels(&self) -> Vec<String> {
-        self.tags.clone()
+        to_strings(&self.tags)
     }
 
     fn const_patch_policy(&self) -> PatchPolicy {
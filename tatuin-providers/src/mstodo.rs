@@ -0,0 +1,672 @@
+// SPDX-License-Identifier: MIT
+
+pub mod auth;
+pub mod client;
+pub mod structs;
+
+use crate::config::Config;
+
+use auth::{Auth, TokenStore};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use client::Client;
+use serde_json::{Map, Value, json};
+use std::any::Any;
+use tatuin_core::{
+    RichString,
+    filter::{self, FilterState},
+    fs::SharedFileSystem,
+    http::SharedHttpClient,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch, ValuePatch},
+    time,
+};
+
+use async_trait::async_trait;
+
+pub const PROVIDER_NAME: &str = "Microsoft To Do";
+
+const TOKEN_FILE_NAME: &str = "token.toml";
+
+const STATUS_NOT_STARTED: &str = "notStarted";
+const STATUS_IN_PROGRESS: &str = "inProgress";
+const STATUS_COMPLETED: &str = "completed";
+const STATUS_WAITING: &str = "waitingOnOthers";
+const STATUS_DEFERRED: &str = "deferred";
+
+const DEFAULT_LIST: &str = "defaultList";
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    list: structs::TaskList,
+    provider: String,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.list.id.to_string()
+    }
+
+    fn name(&self) -> String {
+        self.list.display_name.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("The list {}", self.list.display_name)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        self.list.wellknown_list_name.as_deref() == Some(DEFAULT_LIST)
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    task: structs::Task,
+    due: Option<DateTimeUtc>,
+    project: Project,
+}
+
+fn str_to_date(s: &str) -> Option<DateTimeUtc> {
+    DateTime::parse_from_rfc3339(s).ok().map(DateTimeUtc::from)
+}
+
+fn naive_to_date(dt: &structs::DateTimeTimeZone) -> Option<DateTimeUtc> {
+    NaiveDateTime::parse_from_str(&dt.date_time, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|d| d.and_utc())
+}
+
+// To Do keeps the due dates as the midnight of the user's time zone, Graph returns them in UTC
+fn due_from<Tz: TimeZone>(dt: &structs::DateTimeTimeZone, tz: &Tz) -> Option<DateTimeUtc> {
+    let naive = NaiveDateTime::parse_from_str(&dt.date_time, "%Y-%m-%dT%H:%M:%S%.f").ok()?;
+    let date = if dt.time_zone == "UTC" {
+        naive.and_utc().with_timezone(tz).date_naive()
+    } else {
+        naive.date()
+    };
+    Some(date.and_hms_opt(0, 0, 0)?.and_utc())
+}
+
+fn due_to<Tz: TimeZone>(due: &DateTimeUtc, tz: &Tz) -> Option<Value> {
+    let date: NaiveDate = if time::is_date_only(due) {
+        due.date_naive()
+    } else {
+        due.with_timezone(tz).date_naive()
+    };
+    let midnight = tz
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()?
+        .with_timezone(&Utc);
+    Some(json!({
+        "dateTime": midnight.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        "timeZone": "UTC",
+    }))
+}
+
+fn state_from_status(status: &str) -> State {
+    match status {
+        STATUS_NOT_STARTED => State::Uncompleted,
+        STATUS_IN_PROGRESS => State::InProgress,
+        STATUS_COMPLETED => State::Completed,
+        _ => State::Unknown('?'),
+    }
+}
+
+fn status_from_state(state: &State) -> Option<&'static str> {
+    match state {
+        State::Uncompleted => Some(STATUS_NOT_STARTED),
+        State::InProgress => Some(STATUS_IN_PROGRESS),
+        State::Completed => Some(STATUS_COMPLETED),
+        State::Unknown(_) => None,
+    }
+}
+
+fn importance_from_priority(p: &Priority) -> &'static str {
+    match p {
+        Priority::Lowest | Priority::Low => "low",
+        Priority::Normal | Priority::Medium => "normal",
+        Priority::High | Priority::Highest => "high",
+    }
+}
+
+// The server side filter of the states, None if all the states are accepted
+fn status_filter(states: &[FilterState]) -> Option<String> {
+    if FilterState::values().iter().all(|s| states.contains(s)) {
+        return None;
+    }
+
+    let statuses = states
+        .iter()
+        .flat_map(|s| match s {
+            FilterState::Completed => vec![STATUS_COMPLETED],
+            FilterState::Todo => vec![STATUS_NOT_STARTED],
+            FilterState::InProgress => vec![STATUS_IN_PROGRESS],
+            FilterState::Unknown => vec![STATUS_WAITING, STATUS_DEFERRED],
+        })
+        .map(|s| format!("status eq '{s}'"))
+        .collect::<Vec<String>>();
+    if statuses.is_empty() {
+        // nothing is accepted, but the request still needs a filter
+        return Some(format!(
+            "status eq '{STATUS_COMPLETED}' and status ne '{STATUS_COMPLETED}'"
+        ));
+    }
+    Some(statuses.join(" or "))
+}
+
+// The fields of the task that are changed by the patch
+fn task_fields<Tz: TimeZone>(tp: &TaskPatch, tz: &Tz) -> Result<Map<String, Value>, String> {
+    let mut result = Map::new();
+
+    if let Some(name) = tp.name.ref_value() {
+        result.insert("title".to_string(), json!(name));
+    }
+
+    if tp.description.is_set() {
+        let description = tp.description.ref_value().map(String::as_str).unwrap_or_default();
+        result.insert(
+            "body".to_string(),
+            json!({ "content": description, "contentType": "text" }),
+        );
+    }
+
+    if let Some(p) = tp.priority.ref_value() {
+        result.insert("importance".to_string(), json!(importance_from_priority(p)));
+    }
+
+    if tp.due.is_set() {
+        let due: Option<DateTimeUtc> = match tp.due {
+            ValuePatch::Value(DatePatchItem::Custom(dt)) => Some(dt),
+            ValuePatch::Value(d) => d.into(),
+            ValuePatch::NotSet | ValuePatch::Empty => None,
+        };
+        result.insert("dueDateTime".to_string(), due.and_then(|d| due_to(&d, tz)).into());
+    }
+
+    if let Some(state) = tp.state.ref_value() {
+        let status = status_from_state(state).ok_or(format!("The state {state} is unsupported"))?;
+        result.insert("status".to_string(), json!(status));
+    }
+
+    Ok(result)
+}
+
+impl Task {
+    fn new(task: structs::Task, project: &Project) -> Self {
+        let due = task.due_date_time.as_ref().and_then(|d| due_from(d, &Local));
+        Self {
+            task,
+            due,
+            project: project.clone(),
+        }
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.task.id.to_string()
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(&self.task.title)
+    }
+
+    fn description(&self) -> Option<RichString> {
+        self.task
+            .body
+            .as_ref()
+            .map(|b| b.content.trim())
+            .filter(|c| !c.is_empty())
+            .map(RichString::new)
+    }
+
+    fn priority(&self) -> Priority {
+        match self.task.importance.as_str() {
+            "low" => Priority::Low,
+            "high" => Priority::High,
+            _ => Priority::Normal,
+        }
+    }
+
+    fn state(&self) -> State {
+        state_from_status(&self.task.status)
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        self.task.created_date_time.as_deref().and_then(str_to_date)
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.task.last_modified_date_time.as_deref().and_then(str_to_date)
+    }
+
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        self.task.completed_date_time.as_ref().and_then(naive_to_date)
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+
+    fn place(&self) -> String {
+        self.project.name()
+    }
+
+    fn url(&self) -> String {
+        format!("https://to-do.microsoft.com/tasks/id/{}/details", self.task.id)
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.task.categories.clone()
+    }
+
+    fn provider(&self) -> String {
+        self.project.provider()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(self.project.clone_boxed())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: true,
+            is_removable: true,
+            available_states: vec![State::Uncompleted, State::InProgress, State::Completed],
+            available_priorities: vec![Priority::Low, Priority::Normal, Priority::High],
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+}
+
+pub struct Provider {
+    cfg: Config,
+    client: Client,
+    lists: Vec<Project>,
+    tasks: Vec<Task>,
+    last_filter: Option<filter::Filter>,
+    last_sync: Option<DateTimeUtc>,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, tenant: Option<&str>, client_id: &str, refresh_token: &str) -> Self {
+        let store = TokenStore::new(
+            cfg.cache_path().ok().map(|p| p.join(TOKEN_FILE_NAME)),
+            cfg.cipher(),
+            refresh_token,
+        );
+        let auth = Auth::new(tenant.unwrap_or(auth::DEFAULT_TENANT), client_id);
+        Self {
+            cfg,
+            client: Client::new(auth, store, refresh_token),
+            lists: Vec::new(),
+            tasks: Vec::new(),
+            last_filter: None,
+            last_sync: None,
+        }
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.client = self.client.with_file_system(fs);
+        self
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    async fn lists(&mut self) -> Result<Vec<Project>, ProviderError> {
+        if self.lists.is_empty() {
+            let provider = self.name();
+            self.lists = self
+                .client
+                .lists()
+                .await?
+                .into_iter()
+                .map(|list| Project {
+                    list,
+                    provider: provider.to_string(),
+                })
+                .collect();
+        }
+        Ok(self.lists.clone())
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        Ok(self.lists().await?.iter().map(|p| p.clone_boxed()).collect())
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let mut should_clear = false;
+        if let Some(last_filter) = self.last_filter.as_mut() {
+            should_clear = last_filter != f;
+        }
+
+        if should_clear {
+            self.tasks.clear();
+        }
+
+        if self.tasks.is_empty() {
+            let filter = status_filter(&f.states);
+            for p in self.lists().await? {
+                for t in self.client.tasks(&p.list.id, filter.as_deref()).await? {
+                    self.tasks.push(Task::new(t, &p));
+                }
+            }
+            self.last_sync = Some(Utc::now());
+        }
+
+        let result = self
+            .tasks
+            .iter()
+            .filter(|t| project.as_ref().is_none_or(|p| p.id() == t.project.id()))
+            .filter(|t| f.accept(*t as &dyn TaskTrait))
+            .map(|t| t.clone_boxed())
+            .collect();
+
+        self.last_filter = Some(f.clone());
+
+        Ok(result)
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        if tp.name.ref_value().is_none_or(|n| n.is_empty()) {
+            return Err(ProviderError::other("The task name is required"));
+        }
+
+        let lists = self.lists().await?;
+        let list = lists
+            .iter()
+            .find(|p| p.id() == project_id)
+            .or_else(|| lists.iter().find(|p| p.is_inbox()))
+            .ok_or(ProviderError::other("There is no list for the task"))?;
+
+        let fields = task_fields(tp, &Local).map_err(|e| ProviderError::other(e.as_str()))?;
+        self.client.create_task(&list.list.id, fields).await?;
+        self.tasks.clear();
+        Ok(())
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        for p in patches {
+            tracing::debug!(target:"mstodo_patch_task", patch=p.to_string(), "Apply a patch");
+            let task = p.task.as_ref().unwrap();
+
+            let Some(t) = task.as_any().downcast_ref::<Task>() else {
+                errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                continue;
+            };
+
+            let result = match task_fields(p, &Local) {
+                Ok(fields) if fields.is_empty() => Ok(()),
+                Ok(fields) => self
+                    .client
+                    .update_task(&t.project.list.id, &t.task.id, fields)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                errors.push(PatchError {
+                    task: task.clone(),
+                    error: e,
+                    kind: PatchErrorKind::Other,
+                });
+            }
+        }
+
+        self.tasks.clear();
+
+        errors
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let task = t
+            .as_any()
+            .downcast_ref::<Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
+        self.client.delete_task(&task.project.list.id, &task.task.id).await?;
+        self.tasks.clear();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        self.tasks.clear();
+        self.lists.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn project() -> Project {
+        Project {
+            list: structs::TaskList {
+                id: "list".to_string(),
+                display_name: "Tasks".to_string(),
+                wellknown_list_name: Some(DEFAULT_LIST.to_string()),
+            },
+            provider: "todo".to_string(),
+        }
+    }
+
+    #[test]
+    fn task_test() {
+        let task: structs::Task = serde_json::from_value(json!({
+            "id": "t1",
+            "title": "Buy milk",
+            "status": "inProgress",
+            "importance": "high",
+            "body": {"content": " 2 liters\n", "contentType": "text"},
+            "dueDateTime": {"dateTime": "2025-01-08T00:00:00.0000000", "timeZone": "UTC"},
+            "createdDateTime": "2025-01-01T10:30:00.1234567Z",
+            "lastModifiedDateTime": "2025-01-02T10:30:00Z",
+            "categories": ["Shopping"],
+        }))
+        .unwrap();
+
+        let t = Task::new(task, &project());
+        assert_eq!(t.name().raw(), "Buy milk");
+        assert_eq!(t.description().map(|d| d.raw()), Some("2 liters".to_string()));
+        assert_eq!(t.state(), State::InProgress);
+        assert_eq!(t.priority(), Priority::High);
+        assert_eq!(t.created_at(), str_to_date("2025-01-01T10:30:00.1234567Z"));
+        assert_eq!(t.completed_at(), None);
+        assert_eq!(t.labels(), vec!["Shopping".to_string()]);
+        assert_eq!(t.place(), "Tasks");
+        assert!(project().is_inbox());
+
+        assert_eq!(state_from_status("deferred"), State::Unknown('?'));
+    }
+
+    #[test]
+    fn due_test() {
+        struct Case<'a> {
+            name: &'a str,
+            date_time: &'a str,
+            time_zone: &'a str,
+            offset_hours: i32,
+            expected: &'a str,
+        }
+        const CASES: &[Case] = &[
+            Case {
+                name: "midnight in UTC",
+                date_time: "2025-01-08T00:00:00.0000000",
+                time_zone: "UTC",
+                offset_hours: 0,
+                expected: "2025-01-08T00:00:00Z",
+            },
+            Case {
+                name: "midnight of the western time zone",
+                date_time: "2025-01-08T05:00:00.0000000",
+                time_zone: "UTC",
+                offset_hours: -5,
+                expected: "2025-01-08T00:00:00Z",
+            },
+            Case {
+                name: "midnight of the eastern time zone",
+                date_time: "2025-01-07T22:00:00.0000000",
+                time_zone: "UTC",
+                offset_hours: 2,
+                expected: "2025-01-08T00:00:00Z",
+            },
+            Case {
+                name: "other time zone",
+                date_time: "2025-01-08T00:00:00.0000000",
+                time_zone: "Pacific Standard Time",
+                offset_hours: 3,
+                expected: "2025-01-08T00:00:00Z",
+            },
+        ];
+
+        for c in CASES {
+            let tz = FixedOffset::east_opt(c.offset_hours * 3600).unwrap();
+            let dt = structs::DateTimeTimeZone {
+                date_time: c.date_time.to_string(),
+                time_zone: c.time_zone.to_string(),
+            };
+            let due = due_from(&dt, &tz);
+            assert_eq!(due, str_to_date(c.expected), "Test '{}' was failed", c.name);
+
+            if c.time_zone == "UTC" {
+                let written = due_to(&due.unwrap(), &tz).unwrap();
+                assert_eq!(
+                    written,
+                    json!({"dateTime": c.date_time.trim_end_matches(".0000000"), "timeZone": "UTC"}),
+                    "Test '{}' was failed",
+                    c.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn status_filter_test() {
+        assert_eq!(status_filter(&FilterState::values()), None);
+        assert_eq!(
+            status_filter(&[FilterState::Todo, FilterState::InProgress]),
+            Some("status eq 'notStarted' or status eq 'inProgress'".to_string())
+        );
+        assert_eq!(
+            status_filter(&[FilterState::Unknown]),
+            Some("status eq 'waitingOnOthers' or status eq 'deferred'".to_string())
+        );
+        assert!(status_filter(&[]).is_some());
+    }
+
+    #[test]
+    fn task_fields_test() {
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Buy milk".to_string()),
+            description: ValuePatch::Empty,
+            priority: ValuePatch::Value(Priority::Highest),
+            due: ValuePatch::Value(DatePatchItem::Custom(str_to_date("2025-01-08T00:00:00Z").unwrap())),
+            state: ValuePatch::Value(State::Completed),
+            ..TaskPatch::default()
+        };
+        assert_eq!(
+            Value::Object(task_fields(&tp, &Utc).unwrap()),
+            json!({
+                "title": "Buy milk",
+                "body": {"content": "", "contentType": "text"},
+                "importance": "high",
+                "dueDateTime": {"dateTime": "2025-01-08T00:00:00", "timeZone": "UTC"},
+                "status": "completed",
+            })
+        );
+
+        let tp = TaskPatch {
+            due: ValuePatch::Value(DatePatchItem::NoDate),
+            ..TaskPatch::default()
+        };
+        assert_eq!(
+            Value::Object(task_fields(&tp, &Utc).unwrap()),
+            json!({"dueDateTime": null})
+        );
+
+        let tp = TaskPatch {
+            state: ValuePatch::Value(State::Unknown('?')),
+            ..TaskPatch::default()
+        };
+        assert!(task_fields(&tp, &Utc).is_err());
+    }
+}
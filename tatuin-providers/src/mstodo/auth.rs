@@ -0,0 +1,267 @@
+// SPDX-License-Identifier: MIT
+
+// The OAuth 2.0 device code flow of the Microsoft identity platform.
+// The wizard shows the code to the user and keeps the refresh token in the config.
+// Microsoft rotates the refresh tokens, so the newer ones are kept in the cache folder of the provider.
+
+use crate::http::ReqwestClient;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, path::PathBuf, sync::Arc, time::Duration};
+use tatuin_core::{
+    crypto::{Cipher, decrypt_content, encrypt_content},
+    fs::{LocalFileSystem, SharedFileSystem},
+    http::{Request, Response, SharedHttpClient},
+    provider::ProviderError,
+};
+
+const AUTHORITY: &str = "https://login.microsoftonline.com";
+const SCOPE: &str = "Tasks.ReadWrite offline_access";
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+// Both the personal and the work or school accounts can sign in
+pub const DEFAULT_TENANT: &str = "common";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    pub interval: u64,
+    // the instruction for the user with the code and the url
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Token {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenError {
+    error: String,
+    error_description: Option<String>,
+}
+
+pub struct Auth {
+    tenant: String,
+    client_id: String,
+    http: SharedHttpClient,
+}
+
+impl Auth {
+    pub fn new(tenant: &str, client_id: &str) -> Self {
+        Self {
+            tenant: tenant.to_string(),
+            client_id: client_id.to_string(),
+            http: Arc::new(ReqwestClient::default()),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    async fn request(&self, endpoint: &str, params: &[(&str, &str)]) -> Result<Response, ProviderError> {
+        self.http
+            .send(Request::post(&format!("{AUTHORITY}/{}/oauth2/v2.0/{endpoint}", self.tenant)).with_form(params))
+            .await
+    }
+
+    pub async fn device_code(&self) -> Result<DeviceCode, ProviderError> {
+        let url = format!("{AUTHORITY}/{}/oauth2/v2.0/devicecode", self.tenant);
+        self.request("devicecode", &[("client_id", &self.client_id), ("scope", SCOPE)])
+            .await?
+            .error_for_status(&url)?
+            .json::<DeviceCode>()
+    }
+
+    // The token endpoint answers with an error until the user enters the code
+    pub async fn wait_for_token(&self, code: &DeviceCode) -> Result<Token, ProviderError> {
+        let mut interval = Duration::from_secs(code.interval.max(1));
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(code.expires_in);
+
+        while tokio::time::Instant::now() < deadline {
+            tokio::time::sleep(interval).await;
+
+            let r = self
+                .request(
+                    "token",
+                    &[
+                        ("grant_type", DEVICE_CODE_GRANT),
+                        ("client_id", &self.client_id),
+                        ("device_code", &code.device_code),
+                    ],
+                )
+                .await?;
+            if r.is_success() {
+                return r.json::<Token>();
+            }
+
+            let e = r.json::<TokenError>()?;
+            match e.error.as_str() {
+                "authorization_pending" => {}
+                "slow_down" => interval += Duration::from_secs(5),
+                _ => return Err(ProviderError::Auth(e.error_description.unwrap_or(e.error))),
+            }
+        }
+
+        Err(ProviderError::Auth("The code has expired".to_string()))
+    }
+
+    pub async fn refresh(&self, refresh_token: &str) -> Result<Token, ProviderError> {
+        let r = self
+            .request(
+                "token",
+                &[
+                    ("grant_type", "refresh_token"),
+                    ("client_id", &self.client_id),
+                    ("refresh_token", refresh_token),
+                    ("scope", SCOPE),
+                ],
+            )
+            .await?;
+        if r.is_success() {
+            return r.json::<Token>();
+        }
+
+        let e = r.json::<TokenError>()?;
+        Err(ProviderError::Auth(format!(
+            "{}, add the provider once again to sign in",
+            e.error_description.unwrap_or(e.error)
+        )))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredToken {
+    // the digest of the token in the config, the stored one is dropped when the config is changed
+    source: String,
+    refresh_token: String,
+}
+
+// The latest refresh token, it replaces the one from the config
+pub struct TokenStore {
+    file_name: Option<PathBuf>,
+    cipher: Option<Cipher>,
+    source: String,
+    fs: SharedFileSystem,
+}
+
+impl TokenStore {
+    pub fn new(file_name: Option<PathBuf>, cipher: Option<Cipher>, config_token: &str) -> Self {
+        Self {
+            file_name,
+            cipher,
+            source: sha256::digest(config_token),
+            fs: LocalFileSystem::shared(),
+        }
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    pub async fn load(&self) -> Option<String> {
+        let data = self.fs.read(self.file_name.as_ref()?).await.ok()?;
+        let data = decrypt_content(self.cipher.as_ref(), data)
+            .inspect_err(|e| tracing::warn!(target:"mstodo_auth", error=?e, "Decrypt the refresh token"))
+            .ok()?;
+        let stored = toml::from_str::<StoredToken>(&String::from_utf8(data).ok()?).ok()?;
+        (stored.source == self.source).then_some(stored.refresh_token)
+    }
+
+    pub async fn save(&self, refresh_token: &str) -> Result<(), Box<dyn Error>> {
+        let Some(file_name) = &self.file_name else {
+            return Ok(());
+        };
+        let s = toml::to_string(&StoredToken {
+            source: self.source.to_string(),
+            refresh_token: refresh_token.to_string(),
+        })?;
+        self.fs
+            .write(file_name, &encrypt_content(self.cipher.as_ref(), s.into_bytes())?)
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::Path;
+    use tatuin_core::{
+        fs::MemoryFileSystem,
+        http::{Method, MockHttpClient},
+    };
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn token_store_test() {
+        let file_name = Path::new("/cache/token.toml").to_path_buf();
+        let fs = Arc::new(MemoryFileSystem::new());
+
+        let store = TokenStore::new(Some(file_name.clone()), None, "config token").with_file_system(fs.clone());
+        assert_eq!(store.load().await, None);
+        store.save("rotated token").await.unwrap();
+        assert_eq!(store.load().await, Some("rotated token".to_string()));
+
+        let cipher = Cipher::new("passphrase").unwrap();
+        let store =
+            TokenStore::new(Some(file_name.clone()), Some(cipher.clone()), "config token").with_file_system(fs.clone());
+        store.save("encrypted token").await.unwrap();
+        assert!(!String::from_utf8_lossy(&fs.content(&file_name).unwrap_or_default()).contains("encrypted token"));
+        assert_eq!(store.load().await, Some("encrypted token".to_string()));
+
+        let store = TokenStore::new(Some(file_name), Some(cipher), "new config token").with_file_system(fs.clone());
+        assert_eq!(store.load().await, None);
+
+        let store = TokenStore::new(None, None, "config token").with_file_system(fs);
+        store.save("token").await.unwrap();
+        assert_eq!(store.load().await, None);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn refresh_test() {
+        const URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Post,
+            URL,
+            Response::new(
+                200,
+                br#"{"access_token": "access", "refresh_token": "rotated", "expires_in": 3600}"#,
+            ),
+        ));
+
+        let auth = Auth::new(DEFAULT_TENANT, "id").with_http_client(http.clone());
+        let token = auth.refresh("refresh").await.unwrap();
+        assert_eq!(token.access_token, "access");
+        assert_eq!(token.refresh_token.as_deref(), Some("rotated"));
+        assert_eq!(
+            http.requests()[0].body,
+            Some(
+                b"grant_type=refresh_token&client_id=id&refresh_token=refresh&scope=Tasks.ReadWrite+offline_access"
+                    .to_vec()
+            )
+        );
+
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Post,
+            URL,
+            Response::new(
+                400,
+                br#"{"error": "invalid_grant", "error_description": "The token has expired"}"#,
+            ),
+        ));
+        let auth = Auth::new(DEFAULT_TENANT, "id").with_http_client(http);
+        assert_eq!(
+            auth.refresh("refresh").await.unwrap_err(),
+            ProviderError::Auth("The token has expired, add the provider once again to sign in".to_string())
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// SPDX-License-Identifier: MIT
+
+use super::{
+    auth::{Auth, TokenStore},
+    structs::{Task, TaskList},
+};
+use crate::http::ReqwestClient;
+use chrono::Utc;
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use std::{error::Error, sync::Arc};
+use tatuin_core::{
+    fs::SharedFileSystem,
+    http::{Request, SharedHttpClient},
+    provider::ProviderError,
+    task::DateTimeUtc,
+};
+use urlencoding::encode;
+
+const BASE_URL: &str = "https://graph.microsoft.com/v1.0/me/todo";
+
+// The access token is renewed a bit before it expires
+const EXPIRATION_MARGIN_SECS: i64 = 60;
+
+pub struct Client {
+    auth: Auth,
+    store: TokenStore,
+    refresh_token: String,
+    // the stored token replaces the one from the config, it's read with the first request
+    stored_token_loaded: bool,
+    access_token: Option<(String, DateTimeUtc)>,
+    http: SharedHttpClient,
+}
+
+impl Client {
+    pub fn new(auth: Auth, store: TokenStore, refresh_token: &str) -> Self {
+        Self {
+            auth,
+            refresh_token: refresh_token.to_string(),
+            store,
+            stored_token_loaded: false,
+            access_token: None,
+            http: Arc::new(ReqwestClient::default()),
+        }
+    }
+
+    // The client is used by both the API and the sign in requests
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.auth = self.auth.with_http_client(http.clone());
+        self.http = http;
+        self
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.store = self.store.with_file_system(fs);
+        self
+    }
+
+    async fn access_token(&mut self) -> Result<String, ProviderError> {
+        if let Some((token, expires_at)) = &self.access_token
+            && Utc::now() < *expires_at
+        {
+            return Ok(token.to_string());
+        }
+
+        if !self.stored_token_loaded {
+            if let Some(t) = self.store.load().await {
+                self.refresh_token = t;
+            }
+            self.stored_token_loaded = true;
+        }
+
+        let token = self.auth.refresh(&self.refresh_token).await?;
+        if let Some(t) = token.refresh_token
+            && t != self.refresh_token
+        {
+            // the previous token is still valid for a while, so the failed saving isn't fatal
+            if let Err(e) = self.store.save(&t).await {
+                tracing::error!(target:"mstodo_client", error=?e, "Save the refresh token");
+            }
+            self.refresh_token = t;
+        }
+        let expires_at = Utc::now() + chrono::Duration::seconds(token.expires_in - EXPIRATION_MARGIN_SECS);
+        self.access_token = Some((token.access_token.to_string(), expires_at));
+        Ok(token.access_token)
+    }
+
+    async fn send(&mut self, r: Request) -> Result<tatuin_core::http::Response, ProviderError> {
+        let token = self.access_token().await?;
+        self.http.send_checked(r.with_bearer_auth(&token)).await
+    }
+
+    // Graph splits the collections into pages, the next one is linked from the current one
+    async fn collection<T: DeserializeOwned>(&mut self, url: &str) -> Result<Vec<T>, Box<dyn Error>> {
+        #[derive(Deserialize)]
+        struct Response<T> {
+            value: Vec<T>,
+            #[serde(rename = "@odata.nextLink")]
+            next_link: Option<String>,
+        }
+
+        let mut result = Vec::new();
+        let mut url = url.to_string();
+
+        loop {
+            match self.send(Request::get(&url)).await?.json::<Response<T>>() {
+                Ok(mut r) => {
+                    result.append(&mut r.value);
+                    match r.next_link {
+                        Some(l) => url = l,
+                        None => break,
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(target:"mstodo_client", url=url, error=?e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub async fn lists(&mut self) -> Result<Vec<TaskList>, Box<dyn Error>> {
+        self.collection(&format!("{BASE_URL}/lists")).await
+    }
+
+    // The tasks of the list, the filter is the OData one
+    pub async fn tasks(&mut self, list_id: &str, filter: Option<&str>) -> Result<Vec<Task>, Box<dyn Error>> {
+        let mut url = format!("{BASE_URL}/lists/{list_id}/tasks?$top=100");
+        if let Some(f) = filter {
+            url.push_str(format!("&$filter={}", encode(f)).as_str());
+        }
+        self.collection(&url).await
+    }
+
+    pub async fn create_task(&mut self, list_id: &str, fields: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        let url = format!("{BASE_URL}/lists/{list_id}/tasks");
+        self.send(Request::post(&url).with_json(&fields)?).await?;
+        Ok(())
+    }
+
+    pub async fn update_task(
+        &mut self,
+        list_id: &str,
+        task_id: &str,
+        fields: Map<String, Value>,
+    ) -> Result<(), Box<dyn Error>> {
+        let url = format!("{BASE_URL}/lists/{list_id}/tasks/{task_id}");
+        self.send(Request::patch(&url).with_json(&fields)?).await?;
+        Ok(())
+    }
+
+    pub async fn delete_task(&mut self, list_id: &str, task_id: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!("{BASE_URL}/lists/{list_id}/tasks/{task_id}");
+        self.send(Request::delete(&url)).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mstodo::auth::DEFAULT_TENANT;
+    use std::path::Path;
+    use tatuin_core::{
+        fs::MemoryFileSystem,
+        http::{Method, MockHttpClient, Response},
+    };
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn lists_test() {
+        const TOKEN_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/token";
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(
+                    Method::Post,
+                    TOKEN_URL,
+                    Response::new(200, br#"{"access_token": "access", "expires_in": 3600}"#),
+                )
+                .with_response(
+                    Method::Get,
+                    &format!("{BASE_URL}/lists"),
+                    Response::new(
+                        200,
+                        br#"{"value": [], "@odata.nextLink": "https://graph.microsoft.com/v1.0/me/todo/lists?skip=1"}"#,
+                    ),
+                )
+                .with_response(
+                    Method::Get,
+                    &format!("{BASE_URL}/lists?skip=1"),
+                    Response::new(200, br#"{"value": []}"#),
+                ),
+        );
+        let file_name = Path::new("/cache/token.toml");
+        let fs = Arc::new(MemoryFileSystem::new());
+        TokenStore::new(Some(file_name.to_path_buf()), None, "config token")
+            .with_file_system(fs.clone())
+            .save("stored token")
+            .await
+            .unwrap();
+
+        let mut c = Client::new(
+            Auth::new(DEFAULT_TENANT, "id"),
+            TokenStore::new(Some(file_name.to_path_buf()), None, "config token"),
+            "config token",
+        )
+        .with_http_client(http.clone())
+        .with_file_system(fs);
+        assert!(c.lists().await.unwrap().is_empty());
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 3);
+        // the stored token is newer than the one from the config
+        assert!(String::from_utf8_lossy(requests[0].body.as_ref().unwrap()).contains("refresh_token=stored+token"));
+        assert_eq!(
+            requests[2].headers,
+            [("Authorization".to_string(), "Bearer access".to_string())]
+        );
+    }
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: MIT
+
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskList {
+    pub id: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    // defaultList, flaggedEmails or none
+    #[serde(rename = "wellknownListName", default)]
+    pub wellknown_list_name: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DateTimeTimeZone {
+    // the time without the offset, e.g. 2025-01-08T00:00:00.0000000
+    #[serde(rename = "dateTime")]
+    pub date_time: String,
+    #[serde(rename = "timeZone")]
+    pub time_zone: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemBody {
+    pub content: String,
+    // text or html
+    #[serde(rename = "contentType")]
+    pub content_type: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub id: String,
+    pub title: String,
+    // notStarted, inProgress, completed, waitingOnOthers or deferred
+    pub status: String,
+    // low, normal or high
+    pub importance: String,
+    pub body: Option<ItemBody>,
+    #[serde(rename = "dueDateTime")]
+    pub due_date_time: Option<DateTimeTimeZone>,
+    #[serde(rename = "completedDateTime")]
+    pub completed_date_time: Option<DateTimeTimeZone>,
+    #[serde(rename = "createdDateTime")]
+    pub created_date_time: Option<String>,
+    #[serde(rename = "lastModifiedDateTime")]
+    pub last_modified_date_time: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
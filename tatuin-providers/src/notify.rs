@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT
+
+pub mod gotify;
+pub mod ntfy;
+
+use async_trait::async_trait;
+use tatuin_core::{StringError, task::Priority};
+
+use crate::{config::Config, id_store::IdStore};
+
+const SENT_FILE_NAME: &str = "sent.json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub url: String,
+    pub priority: Priority,
+}
+
+#[async_trait]
+pub trait PublisherTrait: Send + Sync {
+    async fn publish(&self, n: &Notification) -> Result<(), StringError>;
+}
+
+pub struct Notifier {
+    cfg: Config,
+    publisher: Box<dyn PublisherTrait>,
+    sent: Option<IdStore>,
+}
+
+impl Notifier {
+    pub fn new(cfg: Config, publisher: Box<dyn PublisherTrait>) -> Self {
+        Self {
+            cfg,
+            publisher,
+            sent: None,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn sent(&mut self) -> Result<&mut IdStore, StringError> {
+        if self.sent.is_none() {
+            self.sent = Some(IdStore::new(self.cfg.cache_path()?.join(SENT_FILE_NAME)));
+        }
+        Ok(self.sent.as_mut().unwrap())
+    }
+
+    // Publishes the notification if it hasn't been published yet.
+    // Returns true if the notification has been published.
+    pub async fn notify(&mut self, n: &Notification) -> Result<bool, StringError> {
+        if self.sent()?.contains(&n.id)? {
+            return Ok(false);
+        }
+
+        self.publisher.publish(n).await?;
+        self.sent()?.insert(&n.id)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    struct FakePublisher {
+        published: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl PublisherTrait for FakePublisher {
+        async fn publish(&self, n: &Notification) -> Result<(), StringError> {
+            self.published.lock().unwrap().push(n.id.clone());
+            Ok(())
+        }
+    }
+
+    fn notification(id: &str) -> Notification {
+        Notification {
+            id: id.to_string(),
+            title: "Title".to_string(),
+            message: "Message".to_string(),
+            url: String::new(),
+            priority: Priority::Normal,
+        }
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn notification_is_published_once() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let published = Arc::new(Mutex::new(Vec::new()));
+        let notifier = || {
+            let mut cfg = Config::new("test_app", "test_name");
+            cfg.cache_path = temp_dir.path().to_path_buf();
+            Notifier::new(
+                cfg,
+                Box::new(FakePublisher {
+                    published: published.clone(),
+                }),
+            )
+        };
+
+        let mut n = notifier();
+        assert!(n.notify(&notification("1")).await.unwrap());
+        assert!(!n.notify(&notification("1")).await.unwrap());
+        assert!(n.notify(&notification("2")).await.unwrap());
+
+        // the sent notifications are stored between runs
+        let mut n = notifier();
+        assert!(!n.notify(&notification("2")).await.unwrap());
+
+        assert_eq!(*published.lock().unwrap(), vec!["1", "2"]);
+    }
+}
@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+use tatuin_core::{
+    StringError,
+    http::{Request, SharedHttpClient},
+    task::Priority,
+};
+
+use super::{Notification, PublisherTrait};
+use crate::http::ReqwestClient;
+
+pub const PUBLISHER_NAME: &str = "Gotify";
+
+pub struct Publisher {
+    url: String,
+    token: String,
+    http: SharedHttpClient,
+}
+
+impl Publisher {
+    pub fn new(base_url: &str, token: &str) -> Self {
+        Self {
+            url: format!("{}/message", base_url.trim_end_matches('/')),
+            token: token.to_string(),
+            http: Arc::new(ReqwestClient::default()),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+fn priority(p: Priority) -> u8 {
+    match p {
+        Priority::Lowest => 1,
+        Priority::Low => 3,
+        Priority::Normal | Priority::Medium => 5,
+        Priority::High => 8,
+        Priority::Highest => 10,
+    }
+}
+
+fn body(n: &Notification) -> serde_json::Value {
+    let mut body = json!({
+        "title": n.title,
+        "message": n.message,
+        "priority": priority(n.priority),
+    });
+    if !n.url.is_empty() {
+        body["extras"] = json!({"client::notification": {"click": {"url": n.url}}});
+    }
+    body
+}
+
+#[async_trait]
+impl PublisherTrait for Publisher {
+    async fn publish(&self, n: &Notification) -> Result<(), StringError> {
+        let r = Request::post(&self.url)
+            .with_header("X-Gotify-Key", &self.token)
+            .with_json(&body(n))?;
+        let r = self.http.send(r).await?;
+
+        if !r.is_success() {
+            return Err(StringError::new(format!("Gotify error: {}", r.text()).as_str()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[test]
+    fn body_test() {
+        let mut n = Notification {
+            id: "1".to_string(),
+            title: "Title".to_string(),
+            message: "Message".to_string(),
+            url: String::new(),
+            priority: Priority::Highest,
+        };
+        assert_eq!(
+            body(&n),
+            json!({"title": "Title", "message": "Message", "priority": 10})
+        );
+
+        n.url = "https://example.com".to_string();
+        assert_eq!(
+            body(&n)["extras"]["client::notification"]["click"]["url"],
+            "https://example.com"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn publish_test() {
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Post,
+            "https://gotify.example.com/message",
+            Response::new(401, br#"{"error": "Unauthorized"}"#),
+        ));
+        let p = Publisher::new("https://gotify.example.com/", "token").with_http_client(http.clone());
+        let n = Notification {
+            id: "1".to_string(),
+            title: "Title".to_string(),
+            message: "Message".to_string(),
+            url: String::new(),
+            priority: Priority::Low,
+        };
+
+        assert_eq!(
+            p.publish(&n).await.unwrap_err().to_string(),
+            r#"Gotify error: {"error": "Unauthorized"}"#
+        );
+        let r = &http.requests()[0];
+        assert!(r.headers.contains(&("X-Gotify-Key".to_string(), "token".to_string())));
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(r.body.as_ref().unwrap()).unwrap(),
+            json!({"title": "Title", "message": "Message", "priority": 3})
+        );
+    }
+}
@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use tatuin_core::{
+    StringError,
+    http::{Request, SharedHttpClient, check_header_value},
+    task::Priority,
+};
+
+use super::{Notification, PublisherTrait};
+use crate::http::ReqwestClient;
+
+pub const PUBLISHER_NAME: &str = "Ntfy";
+
+pub struct Publisher {
+    url: String,
+    token: Option<String>,
+    http: SharedHttpClient,
+}
+
+impl Publisher {
+    pub fn new(base_url: &str, topic: &str, token: Option<&str>) -> Self {
+        Self {
+            url: format!("{}/{topic}", base_url.trim_end_matches('/')),
+            token: token.map(str::to_string),
+            http: Arc::new(ReqwestClient::default()),
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+}
+
+fn priority(p: Priority) -> u8 {
+    match p {
+        Priority::Lowest => 1,
+        Priority::Low => 2,
+        Priority::Normal | Priority::Medium => 3,
+        Priority::High => 4,
+        Priority::Highest => 5,
+    }
+}
+
+fn headers(n: &Notification, token: Option<&str>) -> Result<Vec<(String, String)>, StringError> {
+    let mut headers = Vec::new();
+    let mut insert = |key: &str, value: &str| -> Result<(), StringError> {
+        check_header_value(key, value)?;
+        headers.push((key.to_string(), value.to_string()));
+        Ok(())
+    };
+
+    // ntfy supports RFC 2047 encoded headers, so the non-ASCII titles aren't broken
+    insert(
+        "Title",
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(n.title.as_bytes())).as_str(),
+    )?;
+    insert("Priority", priority(n.priority).to_string().as_str())?;
+    if !n.url.is_empty() {
+        insert("Click", n.url.as_str())?;
+    }
+    if let Some(token) = token {
+        insert("Authorization", format!("Bearer {token}").as_str())?;
+    }
+    Ok(headers)
+}
+
+#[async_trait]
+impl PublisherTrait for Publisher {
+    async fn publish(&self, n: &Notification) -> Result<(), StringError> {
+        let r = headers(n, self.token.as_deref())?
+            .iter()
+            .fold(Request::post(&self.url), |r, (key, value)| r.with_header(key, value))
+            .with_body(n.message.as_bytes());
+        let r = self.http.send(r).await?;
+
+        if !r.is_success() {
+            return Err(StringError::new(format!("ntfy error: {}", r.text()).as_str()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    fn notification() -> Notification {
+        Notification {
+            id: "1".to_string(),
+            title: "foo".to_string(),
+            message: "Message".to_string(),
+            url: "https://example.com".to_string(),
+            priority: Priority::High,
+        }
+    }
+
+    #[test]
+    fn headers_test() {
+        let h = headers(&notification(), Some("token")).unwrap();
+        assert_eq!(
+            h,
+            [
+                ("Title", "=?UTF-8?B?Zm9v?="),
+                ("Priority", "4"),
+                ("Click", "https://example.com"),
+                ("Authorization", "Bearer token"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+        );
+        assert!(headers(&notification(), Some("token\n")).is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn publish_test() {
+        let http = Arc::new(MockHttpClient::new().with_response(
+            Method::Post,
+            "https://ntfy.sh/tasks",
+            Response::new(200, b"{}"),
+        ));
+        let p = Publisher::new("https://ntfy.sh/", "tasks", None).with_http_client(http.clone());
+
+        p.publish(&notification()).await.unwrap();
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].body, Some(b"Message".to_vec()));
+        assert_eq!(requests[0].headers.len(), 3);
+
+        let p = Publisher::new("https://ntfy.sh", "other", None).with_http_client(http);
+        assert_eq!(
+            p.publish(&notification()).await.unwrap_err().to_string(),
+            "ntfy error: "
+        );
+    }
+}
@@ -0,0 +1,789 @@
+// SPDX-License-Identifier: MIT
+
+pub mod client;
+pub mod structs;
+
+use crate::config::Config;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use client::Client;
+use serde_json::{Map, Value, json};
+use std::any::Any;
+use tatuin_core::{
+    RichString, StringError,
+    filter::{self, FilterState},
+    http::SharedHttpClient,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, PatchPolicy, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch, ValuePatch},
+    time,
+};
+
+use async_trait::async_trait;
+
+pub const PROVIDER_NAME: &str = "Notion";
+
+// Notion keeps up to 2000 characters in one piece of the rich text
+const RICH_TEXT_LIMIT: usize = 2000;
+
+// The names of the database properties the tasks are read from
+#[derive(Debug, Clone)]
+pub struct Properties {
+    state: String,
+    due: String,
+    description: String,
+}
+
+impl Default for Properties {
+    fn default() -> Self {
+        Self {
+            state: "Status".to_string(),
+            due: "Due".to_string(),
+            description: "Description".to_string(),
+        }
+    }
+}
+
+impl Properties {
+    pub fn new(state: Option<&str>, due: Option<&str>, description: Option<&str>) -> Self {
+        let d = Self::default();
+        Self {
+            state: state.map(str::to_string).unwrap_or(d.state),
+            due: due.map(str::to_string).unwrap_or(d.due),
+            description: description.map(str::to_string).unwrap_or(d.description),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum StateProperty {
+    Checkbox(String),
+    // the status options with the states of their groups
+    Status {
+        name: String,
+        options: Vec<(String, State)>,
+    },
+}
+
+// The properties of the database the provider works with, the due and the description are optional
+#[derive(Debug, Clone)]
+struct Schema {
+    title: String,
+    state: StateProperty,
+    due: Option<String>,
+    description: Option<String>,
+}
+
+fn group_state(group: &str) -> State {
+    match group {
+        "Complete" => State::Completed,
+        "In progress" => State::InProgress,
+        _ => State::Uncompleted,
+    }
+}
+
+impl Schema {
+    fn new(db: &structs::Database, p: &Properties) -> Result<Self, ProviderError> {
+        let property_of_type = |name: &str, kind: &str| db.properties.get(name).filter(|s| s.kind == kind);
+
+        let title = db
+            .properties
+            .iter()
+            .find(|(_, s)| s.kind == "title")
+            .map(|(name, _)| name.to_string())
+            .ok_or(ProviderError::other("The database has no title property"))?;
+
+        let state = match db.properties.get(&p.state) {
+            Some(s) if s.kind == "checkbox" => StateProperty::Checkbox(p.state.to_string()),
+            Some(s) if s.kind == "status" => {
+                let status = s.status.clone().unwrap_or_default();
+                let options = status
+                    .options
+                    .iter()
+                    .map(|o| {
+                        let group = status.groups.iter().find(|g| g.option_ids.contains(&o.id));
+                        (
+                            o.name.to_string(),
+                            group_state(group.map(|g| g.name.as_str()).unwrap_or_default()),
+                        )
+                    })
+                    .collect();
+                StateProperty::Status {
+                    name: p.state.to_string(),
+                    options,
+                }
+            }
+            _ => {
+                return Err(ProviderError::other(
+                    format!("The database property {} should be a checkbox or a status", p.state).as_str(),
+                ));
+            }
+        };
+
+        Ok(Self {
+            title,
+            state,
+            due: property_of_type(&p.due, "date").map(|_| p.due.to_string()),
+            description: property_of_type(&p.description, "rich_text").map(|_| p.description.to_string()),
+        })
+    }
+
+    fn states(&self) -> Vec<State> {
+        match &self.state {
+            StateProperty::Checkbox(_) => vec![State::Uncompleted, State::Completed],
+            StateProperty::Status { options, .. } => [State::Uncompleted, State::InProgress, State::Completed]
+                .into_iter()
+                .filter(|s| options.iter().any(|(_, o)| o == s))
+                .collect(),
+        }
+    }
+
+    fn state_value(&self, state: &State) -> Option<Value> {
+        match &self.state {
+            StateProperty::Checkbox(_) => match state {
+                State::Completed => Some(json!({ "checkbox": true })),
+                State::Uncompleted => Some(json!({ "checkbox": false })),
+                State::InProgress | State::Unknown(_) => None,
+            },
+            // the first option of the group is used, like Notion does for the new pages
+            StateProperty::Status { options, .. } => options
+                .iter()
+                .find(|(_, s)| s == state)
+                .map(|(name, _)| json!({ "status": { "name": name } })),
+        }
+    }
+
+    fn state_name(&self) -> &str {
+        match &self.state {
+            StateProperty::Checkbox(name) | StateProperty::Status { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Project {
+    id: String,
+    name: String,
+    provider: String,
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        format!("Notion database {}", self.name)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        true
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[derive(Clone)]
+pub struct Task {
+    id: String,
+    url: String,
+    name: String,
+    description: Option<String>,
+    state: State,
+    due: Option<DateTimeUtc>,
+    created_at: Option<DateTimeUtc>,
+    updated_at: Option<DateTimeUtc>,
+    available_states: Vec<State>,
+    has_due: bool,
+    project: Project,
+}
+
+fn str_to_date(s: &str) -> Option<DateTimeUtc> {
+    if let Ok(d) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Some(d.and_hms_opt(0, 0, 0)?.and_utc());
+    }
+
+    DateTime::parse_from_rfc3339(s).ok().map(DateTimeUtc::from)
+}
+
+fn date_to_str(dt: &DateTimeUtc) -> String {
+    if time::is_date_only(dt) {
+        dt.format("%Y-%m-%d").to_string()
+    } else {
+        dt.to_rfc3339()
+    }
+}
+
+fn rich_text(text: &str) -> Value {
+    let chars = text.chars().collect::<Vec<char>>();
+    Value::Array(
+        chars
+            .chunks(RICH_TEXT_LIMIT)
+            .map(|c| json!({ "text": { "content": c.iter().collect::<String>() } }))
+            .collect(),
+    )
+}
+
+fn task_from_page(page: structs::Page, schema: &Schema, project: &Project) -> Task {
+    use structs::{PropertyValue, plain_text};
+
+    let property = |name: &str| page.properties.get(name);
+
+    let name = match property(&schema.title) {
+        Some(PropertyValue::Title { title }) => plain_text(title),
+        _ => String::new(),
+    };
+    let description = schema
+        .description
+        .as_deref()
+        .and_then(|d| match property(d) {
+            Some(PropertyValue::RichText { rich_text }) => Some(plain_text(rich_text)),
+            _ => None,
+        })
+        .filter(|d| !d.is_empty());
+    let state = match (&schema.state, property(schema.state_name())) {
+        (_, Some(PropertyValue::Checkbox { checkbox })) => {
+            if *checkbox {
+                State::Completed
+            } else {
+                State::Uncompleted
+            }
+        }
+        (StateProperty::Status { options, .. }, Some(PropertyValue::Status { status })) => status
+            .as_ref()
+            .and_then(|s| options.iter().find(|(name, _)| *name == s.name))
+            .map(|(_, state)| *state)
+            .unwrap_or(State::Uncompleted),
+        _ => State::Unknown('?'),
+    };
+    let due = schema.due.as_deref().and_then(|d| match property(d) {
+        Some(PropertyValue::Date { date: Some(date) }) => str_to_date(&date.start),
+        _ => None,
+    });
+
+    Task {
+        id: page.id,
+        url: page.url,
+        name,
+        description,
+        state,
+        due,
+        created_at: str_to_date(&page.created_time),
+        updated_at: str_to_date(&page.last_edited_time),
+        available_states: schema.states(),
+        has_due: schema.due.is_some(),
+        project: project.clone(),
+    }
+}
+
+// The server side filter of the states, None if all the states are accepted
+fn query_filter(schema: &Schema, states: &[FilterState]) -> Option<Value> {
+    let accepted = |s: &State| {
+        states.iter().any(|f| match f {
+            FilterState::Completed => *s == State::Completed,
+            FilterState::Todo => *s == State::Uncompleted,
+            FilterState::InProgress => *s == State::InProgress,
+            FilterState::Unknown => false,
+        })
+    };
+
+    match &schema.state {
+        StateProperty::Checkbox(name) => match (accepted(&State::Completed), accepted(&State::Uncompleted)) {
+            (true, true) => None,
+            (completed, _) => Some(json!({ "property": name, "checkbox": { "equals": completed } })),
+        },
+        StateProperty::Status { name, options } => {
+            let names = options
+                .iter()
+                .filter(|(_, s)| accepted(s))
+                .map(|(o, _)| json!({ "property": name, "status": { "equals": o } }))
+                .collect::<Vec<Value>>();
+            (!names.is_empty() && names.len() != options.len()).then(|| json!({ "or": names }))
+        }
+    }
+}
+
+// The page properties of the patch, the fields that the database can't keep are errors
+fn page_properties(schema: &Schema, tp: &TaskPatch) -> Result<Map<String, Value>, String> {
+    let mut result = Map::new();
+
+    if let Some(name) = tp.name.ref_value() {
+        result.insert(schema.title.to_string(), json!({ "title": rich_text(name) }));
+    }
+
+    if tp.description.is_set() {
+        let property = schema
+            .description
+            .as_ref()
+            .ok_or("The database has no rich text property for the description")?;
+        let description = tp.description.ref_value().map(String::as_str).unwrap_or_default();
+        result.insert(property.to_string(), json!({ "rich_text": rich_text(description) }));
+    }
+
+    if tp.due.is_set() {
+        let property = schema
+            .due
+            .as_ref()
+            .ok_or("The database has no date property for the due")?;
+        let due: Option<DateTimeUtc> = match tp.due {
+            ValuePatch::Value(DatePatchItem::Custom(dt)) => Some(dt),
+            ValuePatch::Value(d) => d.into(),
+            ValuePatch::NotSet | ValuePatch::Empty => None,
+        };
+        let date = due.map(|d| json!({ "start": date_to_str(&d) }));
+        result.insert(property.to_string(), json!({ "date": date }));
+    }
+
+    if let Some(state) = tp.state.ref_value() {
+        let value = schema
+            .state_value(state)
+            .ok_or(format!("The state {state} is unsupported"))?;
+        result.insert(schema.state_name().to_string(), value);
+    }
+
+    Ok(result)
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(&self.name)
+    }
+
+    fn description(&self) -> Option<RichString> {
+        self.description.as_ref().map(|d| RichString::new(d))
+    }
+
+    fn state(&self) -> State {
+        self.state
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        self.created_at
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.updated_at
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.due
+    }
+
+    fn place(&self) -> String {
+        self.url.to_string()
+    }
+
+    fn url(&self) -> String {
+        self.url.to_string()
+    }
+
+    fn provider(&self) -> String {
+        self.project.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(self.project.clone_boxed())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: true,
+            is_removable: true,
+            available_states: self.available_states.clone(),
+            available_priorities: Vec::new(),
+            available_due_items: if self.has_due {
+                DatePatchItem::values()
+            } else {
+                Vec::new()
+            },
+            available_scheduled_items: Vec::new(),
+        }
+    }
+}
+
+pub struct Provider {
+    cfg: Config,
+    database_id: String,
+    properties: Properties,
+    client: Client,
+    schema: Option<(Schema, Project)>,
+    tasks: Vec<Task>,
+    last_filter: Option<filter::Filter>,
+    last_sync: Option<DateTimeUtc>,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, api_key: &str, database_id: &str, properties: Properties) -> Result<Self, StringError> {
+        Ok(Self {
+            cfg,
+            database_id: database_id.to_string(),
+            properties,
+            client: Client::new(api_key)?,
+            schema: None,
+            tasks: Vec::new(),
+            last_filter: None,
+            last_sync: None,
+        })
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.client = self.client.with_http_client(http);
+        self
+    }
+
+    // The database is read once, its properties are needed to read and write the pages
+    async fn schema(&mut self) -> Result<(Schema, Project), ProviderError> {
+        if self.schema.is_none() {
+            let db = self.client.database(&self.database_id).await?;
+            let project = Project {
+                id: db.id.to_string(),
+                name: Some(structs::plain_text(&db.title))
+                    .filter(|t| !t.is_empty())
+                    .unwrap_or_else(|| self.name()),
+                provider: self.name(),
+            };
+            self.schema = Some((Schema::new(&db, &self.properties)?, project));
+        }
+        Ok(self.schema.clone().unwrap())
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let (_, project) = self.schema().await?;
+        Ok(vec![project.clone_boxed()])
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        _project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let mut should_clear = false;
+        if let Some(last_filter) = self.last_filter.as_mut() {
+            should_clear = last_filter != f;
+        }
+
+        if should_clear {
+            self.tasks.clear();
+        }
+
+        if self.tasks.is_empty() {
+            let (schema, project) = self.schema().await?;
+            for page in self
+                .client
+                .query(&self.database_id, query_filter(&schema, &f.states))
+                .await?
+            {
+                self.tasks.push(task_from_page(page, &schema, &project));
+            }
+            self.last_sync = Some(Utc::now());
+        }
+
+        let result = self
+            .tasks
+            .iter()
+            .filter(|t| f.accept(*t as &dyn TaskTrait))
+            .map(|t| t.clone_boxed())
+            .collect();
+
+        self.last_filter = Some(f.clone());
+
+        Ok(result)
+    }
+
+    async fn create(&mut self, _project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        if tp.name.ref_value().is_none_or(|n| n.is_empty()) {
+            return Err(ProviderError::other("The task name is required"));
+        }
+        let (schema, _) = self.schema().await?;
+        let properties = page_properties(&schema, tp).map_err(|e| ProviderError::other(e.as_str()))?;
+        self.client.create_page(&self.database_id, properties).await?;
+        self.tasks.clear();
+        Ok(())
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        for p in patches {
+            tracing::debug!(target:"notion_patch_task", patch=p.to_string(), "Apply a patch");
+            let task = p.task.as_ref().unwrap();
+
+            if task.as_any().downcast_ref::<Task>().is_none() {
+                errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                continue;
+            }
+
+            let result = match self.schema().await {
+                Ok((schema, _)) => match page_properties(&schema, p) {
+                    Ok(properties) if properties.is_empty() => Ok(()),
+                    Ok(properties) => self
+                        .client
+                        .update_page(task.id().as_str(), properties)
+                        .await
+                        .map_err(|e| e.to_string()),
+                    Err(e) => Err(e),
+                },
+                Err(e) => Err(e.to_string()),
+            };
+            if let Err(e) = result {
+                errors.push(PatchError {
+                    task: task.clone(),
+                    error: e,
+                    kind: PatchErrorKind::Other,
+                });
+            }
+        }
+
+        self.tasks.clear();
+
+        errors
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        self.client.archive_page(t.id().as_str()).await?;
+        self.tasks.clear();
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        self.tasks.clear();
+        self.schema = None;
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn database() -> structs::Database {
+        serde_json::from_value(json!({
+            "id": "db",
+            "title": [{"plain_text": "Tasks"}],
+            "properties": {
+                "Name": {"id": "title", "type": "title", "title": {}},
+                "Status": {"id": "s", "type": "status", "status": {
+                    "options": [
+                        {"id": "1", "name": "Not started"},
+                        {"id": "2", "name": "Doing"},
+                        {"id": "3", "name": "Done"},
+                        {"id": "4", "name": "Archived"},
+                    ],
+                    "groups": [
+                        {"name": "To-do", "option_ids": ["1"]},
+                        {"name": "In progress", "option_ids": ["2"]},
+                        {"name": "Complete", "option_ids": ["3", "4"]},
+                    ],
+                }},
+                "Done": {"id": "d", "type": "checkbox", "checkbox": {}},
+                "Due": {"id": "due", "type": "date", "date": {}},
+                "Notes": {"id": "n", "type": "rich_text", "rich_text": {}},
+            },
+        }))
+        .unwrap()
+    }
+
+    fn project() -> Project {
+        Project {
+            id: "db".to_string(),
+            name: "Tasks".to_string(),
+            provider: "notion".to_string(),
+        }
+    }
+
+    #[test]
+    fn task_from_page_test() {
+        let schema = Schema::new(&database(), &Properties::new(None, None, Some("Notes"))).unwrap();
+        let page: structs::Page = serde_json::from_value(json!({
+            "id": "page",
+            "url": "https://www.notion.so/page",
+            "created_time": "2025-01-01T10:30:00.000Z",
+            "last_edited_time": "2025-01-02T10:30:00.000Z",
+            "properties": {
+                "Name": {"id": "title", "type": "title", "title": [{"plain_text": "Buy "}, {"plain_text": "milk"}]},
+                "Status": {"id": "s", "type": "status", "status": {"id": "2", "name": "Doing"}},
+                "Due": {"id": "due", "type": "date", "date": {"start": "2025-01-08", "end": null}},
+                "Notes": {"id": "n", "type": "rich_text", "rich_text": [{"plain_text": "2 liters"}]},
+                "Tags": {"id": "t", "type": "multi_select", "multi_select": []},
+            },
+        }))
+        .unwrap();
+
+        let t = task_from_page(page, &schema, &project());
+        assert_eq!(t.name().raw(), "Buy milk");
+        assert_eq!(t.description().unwrap().raw(), "2 liters");
+        assert_eq!(t.state(), State::InProgress);
+        assert_eq!(t.due(), str_to_date("2025-01-08"));
+        assert_eq!(
+            t.const_patch_policy().available_states,
+            vec![State::Uncompleted, State::InProgress, State::Completed]
+        );
+
+        let schema = Schema::new(&database(), &Properties::new(Some("Done"), Some("Deadline"), None)).unwrap();
+        let page: structs::Page = serde_json::from_value(json!({
+            "id": "page",
+            "url": "https://www.notion.so/page",
+            "created_time": "2025-01-01T10:30:00.000Z",
+            "last_edited_time": "2025-01-02T10:30:00.000Z",
+            "properties": {
+                "Name": {"id": "title", "type": "title", "title": [{"plain_text": "Buy milk"}]},
+                "Done": {"id": "d", "type": "checkbox", "checkbox": true},
+                "Due": {"id": "due", "type": "date", "date": {"start": "2025-01-08T10:00:00.000+01:00"}},
+            },
+        }))
+        .unwrap();
+        let t = task_from_page(page, &schema, &project());
+        assert_eq!(t.state(), State::Completed);
+        assert_eq!(t.due(), None);
+        assert!(t.const_patch_policy().available_due_items.is_empty());
+
+        assert!(Schema::new(&database(), &Properties::new(Some("Notes"), None, None)).is_err());
+    }
+
+    #[test]
+    fn query_filter_test() {
+        let status = Schema::new(&database(), &Properties::default()).unwrap();
+        let checkbox = Schema::new(&database(), &Properties::new(Some("Done"), None, None)).unwrap();
+
+        assert_eq!(query_filter(&status, &FilterState::values()), None);
+        assert_eq!(
+            query_filter(&status, &[FilterState::Completed]),
+            Some(json!({"or": [
+                {"property": "Status", "status": {"equals": "Done"}},
+                {"property": "Status", "status": {"equals": "Archived"}},
+            ]}))
+        );
+        assert_eq!(query_filter(&checkbox, &FilterState::values()), None);
+        assert_eq!(
+            query_filter(&checkbox, &[FilterState::Todo, FilterState::InProgress]),
+            Some(json!({"property": "Done", "checkbox": {"equals": false}}))
+        );
+    }
+
+    #[test]
+    fn page_properties_test() {
+        let schema = Schema::new(&database(), &Properties::new(None, None, Some("Notes"))).unwrap();
+
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Buy milk".to_string()),
+            description: ValuePatch::Value("2 liters".to_string()),
+            due: ValuePatch::Value(DatePatchItem::Custom(str_to_date("2025-01-08").unwrap())),
+            state: ValuePatch::Value(State::Completed),
+            ..TaskPatch::default()
+        };
+        assert_eq!(
+            Value::Object(page_properties(&schema, &tp).unwrap()),
+            json!({
+                "Name": {"title": [{"text": {"content": "Buy milk"}}]},
+                "Notes": {"rich_text": [{"text": {"content": "2 liters"}}]},
+                "Due": {"date": {"start": "2025-01-08"}},
+                "Status": {"status": {"name": "Done"}},
+            })
+        );
+
+        let tp = TaskPatch {
+            description: ValuePatch::Empty,
+            due: ValuePatch::Value(DatePatchItem::NoDate),
+            ..TaskPatch::default()
+        };
+        assert_eq!(
+            Value::Object(page_properties(&schema, &tp).unwrap()),
+            json!({"Notes": {"rich_text": []}, "Due": {"date": null}})
+        );
+
+        let long = "a".repeat(RICH_TEXT_LIMIT + 1);
+        assert_eq!(rich_text(&long).as_array().unwrap().len(), 2);
+
+        let checkbox = Schema::new(&database(), &Properties::new(Some("Done"), Some("Deadline"), None)).unwrap();
+        let tp = TaskPatch {
+            state: ValuePatch::Value(State::InProgress),
+            ..TaskPatch::default()
+        };
+        assert!(page_properties(&checkbox, &tp).is_err());
+        let tp = TaskPatch {
+            description: ValuePatch::Value("2 liters".to_string()),
+            ..TaskPatch::default()
+        };
+        assert!(page_properties(&checkbox, &tp).is_err());
+    }
+
+    #[test]
+    fn wrong_api_key_test() {
+        let properties = || Properties::new(None, None, None);
+        assert!(Provider::new(Config::new("test_app", "test_name"), "secret", "db", properties()).is_ok());
+        assert!(Provider::new(Config::new("test_app", "test_name"), "secret\n", "db", properties()).is_err());
+    }
+}
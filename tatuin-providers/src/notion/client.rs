@@ -0,0 +1,173 @@
+// SPDX-License-Identifier: MIT
+
+use super::structs::{Database, Page};
+use crate::http::ReqwestClient;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+use std::{error::Error, sync::Arc};
+use tatuin_core::{
+    StringError,
+    http::{Request, SharedHttpClient, check_header_value},
+};
+
+pub struct Client {
+    base_url: String,
+    api_key: String,
+    http: SharedHttpClient,
+}
+
+impl Client {
+    pub fn new(api_key: &str) -> Result<Self, StringError> {
+        check_header_value("Authorization", api_key)
+            .map_err(|e| StringError::new(format!("Wrong api key: {e}").as_str()))?;
+        Ok(Self {
+            base_url: "https://api.notion.com/v1".to_string(),
+            api_key: api_key.to_string(),
+            http: Arc::new(ReqwestClient::default()),
+        })
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    fn request(&self, r: Request) -> Request {
+        r.with_bearer_auth(&self.api_key)
+            .with_header("Notion-Version", "2022-06-28")
+    }
+
+    pub async fn database(&self, id: &str) -> Result<Database, Box<dyn Error>> {
+        let url = format!("{}/databases/{id}", self.base_url);
+        self.http
+            .send_checked(self.request(Request::get(&url)))
+            .await?
+            .json::<Database>()
+            .map_err(|e| {
+                tracing::error!(target:"notion_client", url=url, error=?e);
+                e.into()
+            })
+    }
+
+    // The pages of the database, the filter is the Notion one
+    pub async fn query(&self, database_id: &str, filter: Option<Value>) -> Result<Vec<Page>, Box<dyn Error>> {
+        #[derive(Deserialize)]
+        struct Response {
+            results: Vec<Page>,
+            has_more: bool,
+            next_cursor: Option<String>,
+        }
+
+        let mut result = Vec::new();
+
+        const PER_PAGE: i8 = 100;
+        let mut cursor: Option<String> = None;
+
+        let url = format!("{}/databases/{database_id}/query", self.base_url);
+        loop {
+            let mut body = json!({ "page_size": PER_PAGE });
+            if let Some(f) = &filter {
+                body["filter"] = f.clone();
+            }
+            if let Some(c) = &cursor {
+                body["start_cursor"] = json!(c);
+            }
+            match self
+                .http
+                .send_checked(self.request(Request::post(&url).with_json(&body)?))
+                .await?
+                .json::<Response>()
+            {
+                Ok(mut r) => {
+                    result.append(&mut r.results);
+                    match r.next_cursor.filter(|_| r.has_more) {
+                        Some(c) => cursor = Some(c),
+                        None => break,
+                    }
+                }
+                Err(e) => {
+                    tracing::error!(target:"notion_client", url=url, error=?e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    pub async fn create_page(&self, database_id: &str, properties: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/pages", self.base_url);
+        self.http
+            .send_checked(self.request(Request::post(&url).with_json(&json!({
+                "parent": { "database_id": database_id },
+                "properties": properties,
+            }))?))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn update_page(&self, page_id: &str, properties: Map<String, Value>) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/pages/{page_id}", self.base_url);
+        self.http
+            .send_checked(self.request(Request::patch(&url).with_json(&json!({ "properties": properties }))?))
+            .await?;
+        Ok(())
+    }
+
+    // The archived pages are moved to the trash of the workspace, so they can be restored there
+    pub async fn archive_page(&self, page_id: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/pages/{page_id}", self.base_url);
+        self.http
+            .send_checked(self.request(Request::patch(&url).with_json(&json!({ "archived": true }))?))
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[test]
+    fn new_test() {
+        assert!(Client::new("secret").is_ok());
+        assert!(Client::new("secret\n").is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn query_test() {
+        const URL: &str = "https://api.notion.com/v1/databases/db/query";
+        let page = json!({
+            "id": "page",
+            "url": "https://www.notion.so/page",
+            "created_time": "2025-01-01T00:00:00.000Z",
+            "last_edited_time": "2025-01-01T00:00:00.000Z",
+            "properties": {},
+        });
+        let http = Arc::new(
+            MockHttpClient::new().with_response(
+                Method::Post,
+                URL,
+                Response::new(
+                    200,
+                    json!({"results": [page], "has_more": false, "next_cursor": "next"})
+                        .to_string()
+                        .as_bytes(),
+                ),
+            ),
+        );
+
+        let c = Client::new("secret").unwrap().with_http_client(http.clone());
+        let filter = json!({"property": "Done", "checkbox": {"equals": false}});
+        let pages = c.query("db", Some(filter.clone())).await.unwrap();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(pages[0].id, "page");
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        let body: Value = serde_json::from_slice(requests[0].body.as_ref().unwrap()).unwrap();
+        assert_eq!(body, json!({"page_size": 100, "filter": filter}));
+    }
+}
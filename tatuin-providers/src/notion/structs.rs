@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Database {
+    pub id: String,
+    #[serde(default)]
+    pub title: Vec<RichText>,
+    pub properties: HashMap<String, PropertySchema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PropertySchema {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub status: Option<StatusSchema>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct StatusSchema {
+    pub options: Vec<SelectOption>,
+    pub groups: Vec<StatusGroup>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct SelectOption {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct StatusGroup {
+    // To-do, In progress or Complete
+    pub name: String,
+    pub option_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Page {
+    pub id: String,
+    pub url: String,
+    pub created_time: String,
+    pub last_edited_time: String,
+    pub properties: HashMap<String, PropertyValue>,
+}
+
+// The value of the page property, the types that the provider doesn't use are skipped
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PropertyValue {
+    Title {
+        title: Vec<RichText>,
+    },
+    RichText {
+        rich_text: Vec<RichText>,
+    },
+    Checkbox {
+        checkbox: bool,
+    },
+    Status {
+        status: Option<SelectOption>,
+    },
+    Date {
+        date: Option<DateValue>,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct RichText {
+    pub plain_text: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Deserialize)]
+pub struct DateValue {
+    pub start: String,
+}
+
+pub fn plain_text(text: &[RichText]) -> String {
+    text.iter().map(|t| t.plain_text.as_str()).collect()
+}
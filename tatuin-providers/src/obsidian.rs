@@ -1,52 +1,106 @@
 // SPDX-License-Identifier: MIT
 
-mod client;
-mod description;
+mod dialect;
 mod fs;
-mod indent;
+mod git;
 mod internal_links_renderer;
 mod markdown;
-mod md_file;
-mod patch;
-mod project;
 mod rest;
-mod state;
-mod task;
 
-use std::path::Path;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use async_trait::async_trait;
-use description::Description;
-use md_file::task_to_string;
-use state::State;
 use tatuin_core::{
-    StringError, filter,
+    filter,
+    fs::SharedFileSystem,
+    http::SharedHttpClient,
+    intern::intern,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
-    task::{Priority, Task as TaskTrait},
-    task_patch::{DatePatchItem, PatchError, TaskPatch},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::Task as TaskTrait,
+    task_patch::{PatchError, TaskPatch},
 };
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    markdown_dir::{self, DeleteMode, client, md_file::task_to_string, project, task},
+};
 
 pub const PROVIDER_NAME: &str = "Obsidian";
 
+const DEFAULT_PROJECT_KEYS: [&str; 2] = ["project", "area"];
+
 pub struct Provider {
     cfg: Config,
     c: client::Client,
     rest: rest::Client,
+    git: Option<git::Git>,
 }
 
 impl Provider {
     pub fn new(cfg: Config, path: &Path) -> Self {
         Self {
             cfg,
-            c: client::Client::new(path),
+            c: client::Client::new(path)
+                .with_dialect(Arc::new(dialect::Dialect {}))
+                .with_project_keys(&DEFAULT_PROJECT_KEYS.map(String::from)),
             rest: rest::Client::new(path),
+            git: None,
+        }
+    }
+
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.rest = self.rest.with_http_client(http);
+        self
+    }
+
+    // The notes are read and written through the file system, the REST API config and the internal links
+    // of the rendered descriptions are still taken from the disk
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.c = self.c.with_file_system(fs);
+        self
+    }
+
+    pub fn with_delete_mode(mut self, m: DeleteMode) -> Self {
+        self.c = self.c.with_delete_mode(m);
+        self
+    }
+
+    // The frontmatter keys that give the project of the note tasks, the empty list turns them off
+    pub fn with_project_keys(mut self, keys: &[String]) -> Self {
+        self.c = self.c.with_project_keys(keys);
+        self
+    }
+
+    // The folders of the notes up to the depth are the projects, 0 turns them off
+    pub fn with_folder_depth(mut self, depth: usize) -> Self {
+        self.c = self.c.with_folder_depth(depth);
+        self
+    }
+
+    pub fn with_git_commit(mut self, message_template: Option<&str>) -> Self {
+        self.git = Some(git::Git::new(&self.c.root_path(), message_template));
+        self
+    }
+
+    // The write operation has already succeeded, so the commit errors are only logged
+    async fn commit(&self, action: &str, tasks: &[String], paths: &[PathBuf]) {
+        if let Some(g) = &self.git
+            && let Err(e) = g.commit(action, tasks, paths).await
+        {
+            tracing::error!(error=?e, action=action, "Commit the vault changes");
         }
     }
 }
 
+// The note of the task, the other tasks aren't written by the provider
+fn file_path(t: &dyn TaskTrait) -> Option<PathBuf> {
+    t.as_any().downcast_ref::<task::Task>().map(|t| t.file_path.clone())
+}
+
 impl std::fmt::Debug for Provider {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Provider name={}", ProviderTrait::name(self))
@@ -55,12 +109,20 @@ impl std::fmt::Debug for Provider {
 
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
-        Ok(vec![Box::new(project::Project::new(
-            self.cfg.name().as_str(),
-            &self.c.root_path(),
-            &self.c.root_path().join("daily.md"),
-        ))])
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let root = self.c.root_path();
+        let provider = intern(self.cfg.name().as_str());
+        let mut result: Vec<Box<dyn ProjectTrait>> = vec![Box::new(project::Project::new(
+            provider.clone(),
+            &root,
+            &root.join("daily.md"),
+        ))];
+        for name in self.c.named_projects().await? {
+            result.push(Box::new(
+                project::Project::new(provider.clone(), &root, &root).with_name(intern(&name)),
+            ));
+        }
+        Ok(result)
     }
 }
 
@@ -68,62 +130,67 @@ impl ProjectProviderTrait for Provider {
 impl TaskProviderTrait for Provider {
     async fn list(
         &mut self,
-        _project: Option<Box<dyn ProjectTrait>>,
+        project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
         let tasks = self.c.tasks(f).await?;
         let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        let name = intern(&self.name());
         for mut t in tasks {
-            t.set_provider(self.name());
+            if let Some(p) = &project
+                && t.project().is_some_and(|tp| tp.id() != p.id())
+            {
+                continue;
+            }
+            t.set_provider(name.clone());
             result.push(Box::new(t));
         }
         Ok(result)
     }
 
-    async fn create(&mut self, _project_id: &str, tp: &TaskPatch) -> Result<(), StringError> {
-        let t = task::Task {
-            name: tp.name.value().unwrap(),
-            description: tp.description.value().map(|s| Description::from_str(s.as_str())),
-            state: State::Uncompleted,
-            due: tp.due.value().unwrap_or(DatePatchItem::NoDate).into(),
-            priority: tp.priority.value().unwrap_or(Priority::Normal),
-            ..task::Task::default()
-        };
-        self.rest.add_text_to_daily_note(task_to_string(&t, "").as_str()).await
+    async fn create(&mut self, _project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let t = markdown_dir::new_task(tp);
+        self.rest
+            .add_text_to_daily_note(task_to_string(&t, "", self.c.conventions()).as_str())
+            .await?;
+        if self.git.is_some() {
+            match self.rest.daily_note_path().await {
+                Ok(path) => {
+                    let path = self.c.root_path().join(path);
+                    self.commit("create", std::slice::from_ref(&t.name), &[path]).await;
+                }
+                Err(e) => tracing::error!(error=?e, "Get the path of the daily note to commit it"),
+            }
+        }
+        Ok(())
     }
 
     async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
-        let mut client_patches = Vec::new();
-        let mut errors = Vec::new();
-        for p in patches.iter() {
-            let task = p.task.as_ref().unwrap();
-
-            match task.as_any().downcast_ref::<task::Task>() {
-                Some(t) => client_patches.push(patch_to_internal(t, p)),
-                None => panic!(
-                    "Wrong casting the task id=`{}` name=`{}` to obsidian!",
-                    task.id(),
-                    task.name().raw(),
-                ),
-            };
+        let errors = markdown_dir::update_tasks(&mut self.c, patches).await;
+        if errors.len() < patches.len() {
+            let tasks = patches
+                .iter()
+                .filter_map(|p| p.task.as_ref())
+                .filter(|t| !errors.iter().any(|e| e.task.id() == t.id()))
+                .collect::<Vec<_>>();
+            let names = tasks.iter().map(|t| t.name().plain()).collect::<Vec<String>>();
+            let mut paths = tasks
+                .iter()
+                .filter_map(|t| file_path(t.as_ref()))
+                .collect::<Vec<PathBuf>>();
+            paths.sort();
+            paths.dedup();
+            self.commit("update", &names, &paths).await;
         }
-
-        for e in self.c.patch_tasks(&client_patches).await {
-            errors.push(PatchError {
-                task: e.task.clone_boxed(),
-                error: e.error,
-            })
-        }
-
         errors
     }
 
-    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), StringError> {
-        let t = t.as_any().downcast_ref::<task::Task>().expect("Wrong casting");
-        self.c.delete_task(t).await.map_err(|e| {
-            tracing::error!(error=?e, name=?t.name(), id=t.id(), "Delete the task");
-            e.into()
-        })
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let name = t.name().plain();
+        let path = file_path(t);
+        markdown_dir::delete_task(&mut self.c, t).await?;
+        self.commit("delete", &[name], path.as_slice()).await;
+        Ok(())
     }
 }
 
@@ -144,18 +211,93 @@ impl ProviderTrait for Provider {
     fn capabilities(&self) -> Capabilities {
         Capabilities {
             create_task: self.rest.is_available(),
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
         }
     }
 }
 
-fn patch_to_internal<'a>(t: &'a task::Task, tp: &TaskPatch) -> patch::TaskPatch<'a> {
-    patch::TaskPatch {
-        task: t,
-        name: tp.name.clone(),
-        description: tp.description.clone(),
-        state: tp.state.clone().map(|s| s.into()),
-        due: tp.due.clone().into(),
-        scheduled: tp.scheduled.clone().into(),
-        priority: tp.priority.clone(),
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::fs::MemoryFileSystem;
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn frontmatter_projects() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let root = temp_dir.path().to_path_buf();
+        std::fs::write(root.join("daily.md"), "- [ ] Call mom\n").unwrap();
+        std::fs::write(
+            root.join("repair.md"),
+            "---\nproject: Home\n---\n- [ ] Paint the wall\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("garden.md"),
+            "---\narea: \"[[Areas/Home]]\"\n---\n- [ ] Cut the grass\n",
+        )
+        .unwrap();
+
+        let mut p = Provider::new(Config::new("test_app", "test_name"), &root);
+
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(
+            projects.iter().map(|p| p.name()).collect::<Vec<_>>(),
+            vec!["daily", "Home"]
+        );
+
+        let mut tasks =
+            TaskProviderTrait::list(&mut p, Some(projects[1].clone_boxed()), &filter::Filter::full_filter())
+                .await
+                .unwrap()
+                .iter()
+                .map(|t| t.name().raw())
+                .collect::<Vec<_>>();
+        tasks.sort();
+        assert_eq!(tasks, vec!["Cut the grass", "Paint the wall"]);
+
+        let mut p = p.with_project_keys(&[]);
+        assert_eq!(ProjectProviderTrait::list(&mut p).await.unwrap().len(), 1);
+        let tasks = TaskProviderTrait::list(&mut p, None, &filter::Filter::full_filter())
+            .await
+            .unwrap();
+        assert!(tasks.iter().all(|t| t.project().unwrap().name() != "Home"));
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn folder_projects() {
+        let root = Path::new("/vault");
+        let fs = MemoryFileSystem::new()
+            .with_file(&root.join("daily.md"), "- [ ] Call mom\n")
+            .with_file(
+                &root.join("Work").join("Reports").join("q1.md"),
+                "- [ ] Send the report\n",
+            )
+            .with_file(
+                &root.join("Home").join("repair.md"),
+                "---\nproject: Renovation\n---\n- [ ] Paint the wall\n",
+            );
+
+        let mut p = Provider::new(Config::new("test_app", "test_name"), root)
+            .with_folder_depth(1)
+            .with_file_system(Arc::new(fs));
+
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(
+            projects.iter().map(|p| p.name()).collect::<Vec<_>>(),
+            vec!["daily", "Renovation", "Work"]
+        );
+
+        let tasks = TaskProviderTrait::list(&mut p, Some(projects[2].clone_boxed()), &filter::Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].name().raw(), "Send the report");
     }
 }
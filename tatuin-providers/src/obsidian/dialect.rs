@@ -0,0 +1,28 @@
+// SPDX-License-Identifier: MIT
+
+use tatuin_core::RichString;
+
+use crate::{
+    markdown_dir::{dialect::Dialect as DialectTrait, task::Task},
+    obsidian::{fs, internal_links_renderer::InternalLinksTransformer},
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct Dialect {}
+
+impl DialectTrait for Dialect {
+    fn name(&self, t: &Task) -> RichString {
+        RichString::new(&t.name)
+            .with_transformer(Box::new(InternalLinksTransformer::new(&t.root_path).with_remove_tags()))
+    }
+
+    fn description(&self, t: &Task) -> Option<RichString> {
+        t.description
+            .as_ref()
+            .map(|d| RichString::new(&d.text).with_transformer(Box::new(InternalLinksTransformer::new(&t.root_path))))
+    }
+
+    fn url(&self, t: &Task) -> String {
+        fs::obsidian_url(&t.root_path, &t.file_path)
+    }
+}
@@ -0,0 +1,136 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+use tatuin_core::StringError;
+use tokio::process::Command;
+
+const DEFAULT_MESSAGE_TEMPLATE: &str = "tatuin: {action} {task}";
+
+// Commits the notes changed by tatuin after every write operation, the other changes of the vault are left as they are.
+// The template can contain {action} (create, update or delete) and {task} (the task names)
+pub struct Git {
+    path: PathBuf,
+    message_template: String,
+}
+
+impl Git {
+    pub fn new(path: &Path, message_template: Option<&str>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            message_template: message_template.unwrap_or(DEFAULT_MESSAGE_TEMPLATE).to_string(),
+        }
+    }
+
+    fn message(&self, action: &str, tasks: &[String]) -> String {
+        self.message_template
+            .replace("{action}", action)
+            .replace("{task}", tasks.join(", ").as_str())
+    }
+
+    async fn git<S: AsRef<OsStr>>(&self, args: &[S]) -> Result<bool, StringError> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.path)
+            .args(args)
+            .output()
+            .await
+            .map_err(|e| StringError::new(format!("can't run git: {e}").as_str()))?;
+        if !output.status.success() && !output.stderr.is_empty() {
+            return Err(StringError::new(
+                format!(
+                    "git {}: {}",
+                    args[0].as_ref().to_string_lossy(),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )
+                .as_str(),
+            ));
+        }
+        Ok(output.status.success())
+    }
+
+    // Only the paths are staged and committed, the changes staged by the user stay staged
+    pub async fn commit(&self, action: &str, tasks: &[String], paths: &[PathBuf]) -> Result<(), StringError> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let with_paths = |args: &[&str]| -> Vec<OsString> {
+            args.iter()
+                .map(OsString::from)
+                .chain(std::iter::once(OsString::from("--")))
+                .chain(paths.iter().map(|p| p.as_os_str().to_os_string()))
+                .collect()
+        };
+
+        self.git(&with_paths(&["add"])).await?;
+        // the quiet diff fails when there are staged changes
+        if self.git(&with_paths(&["diff", "--cached", "--quiet"])).await? {
+            return Ok(());
+        }
+        let message = self.message(action, tasks);
+        self.git(&with_paths(&["commit", "--quiet", "--message", message.as_str()]))
+            .await
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn message_test() {
+        let tasks = ["Buy milk".to_string(), "Call mom".to_string()];
+        assert_eq!(
+            Git::new(Path::new(""), None).message("update", &tasks),
+            "tatuin: update Buy milk, Call mom"
+        );
+        assert_eq!(
+            Git::new(Path::new(""), Some("[{action}] {task} via tatuin")).message("delete", &tasks[..1]),
+            "[delete] Buy milk via tatuin"
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn commit_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let g = Git::new(temp_dir.path(), None);
+        g.git(&["init", "--quiet"]).await.unwrap();
+        g.git(&["config", "user.name", "test"]).await.unwrap();
+        g.git(&["config", "user.email", "test@example.com"]).await.unwrap();
+
+        let daily = temp_dir.path().join("daily.md");
+        std::fs::write(&daily, "- [ ] Buy milk\n").unwrap();
+        // the note edited by the user isn't committed
+        std::fs::write(temp_dir.path().join("ideas.md"), "Some ideas\n").unwrap();
+        g.commit("create", &["Buy milk".to_string()], std::slice::from_ref(&daily))
+            .await
+            .unwrap();
+        // nothing to commit
+        g.commit("update", &["Buy milk".to_string()], std::slice::from_ref(&daily))
+            .await
+            .unwrap();
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["log", "--format=%s"])
+            .output()
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "tatuin: create Buy milk\n");
+
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(temp_dir.path())
+            .args(["status", "--porcelain"])
+            .output()
+            .await
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "?? ideas.md\n");
+    }
+}
@@ -4,7 +4,10 @@ use std::path::{Path, PathBuf};
 
 use tatuin_core::RichStringTransformerTrait;
 
-use crate::obsidian::{fs, markdown, md_file::TAG_RE};
+use crate::{
+    markdown_dir::{dialect::clear_tags, fs::find_file},
+    obsidian::{fs, markdown},
+};
 
 #[derive(Debug, Clone, Default)]
 pub struct InternalLinksTransformer {
@@ -39,10 +42,6 @@ impl RichStringTransformerTrait for InternalLinksTransformer {
     }
 }
 
-fn clear_tags(name: &str) -> String {
-    TAG_RE.replace_all(name, "").to_string()
-}
-
 fn fix_wiki_links(text: &str, vault_path: &Path) -> String {
     let mut result = text.to_string();
 
@@ -50,7 +49,7 @@ fn fix_wiki_links(text: &str, vault_path: &Path) -> String {
         let file_name = format!("{}.md", l.link);
 
         let link = if let Ok(file_name) = urlencoding::decode(&file_name)
-            && let Ok(f) = fs::find_file(vault_path, &file_name)
+            && let Ok(f) = find_file(vault_path, &file_name)
         {
             fs::obsidian_url(vault_path, &f)
         } else {
@@ -75,7 +74,7 @@ fn fix_regular_links(text: &str, vault_path: &Path) -> String {
 
     for l in markdown::find_regular_links(text).iter().rev() {
         if let Ok(file_name) = urlencoding::decode(l.link)
-            && let Ok(f) = fs::find_file(vault_path, &file_name)
+            && let Ok(f) = find_file(vault_path, &file_name)
         {
             result.replace_range(
                 l.start..l.end,
@@ -1,12 +1,17 @@
 // SPDX-License-Identifier: MIT
 
-use reqwest::StatusCode;
 use serde::Deserialize;
 use std::fs;
 use std::path;
 use std::path::Path;
+use std::sync::Arc;
 
-use tatuin_core::StringError;
+use tatuin_core::{
+    http::{Request, SharedHttpClient},
+    provider::ProviderError,
+};
+
+use crate::http::ReqwestClient;
 
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -17,15 +22,21 @@ struct Config {
     api_key: String,
 }
 
+// The note's metadata, the path is relative to the vault
+#[derive(Deserialize)]
+struct NoteJson {
+    path: String,
+}
+
 const CONFIG_PATH: &str = ".obsidian/plugins/obsidian-local-rest-api/data.json";
 
-fn not_connected_err() -> StringError {
-    StringError::new("the vault doesn't contain the obsidian-local-rest-api plugin")
+fn not_connected_err() -> ProviderError {
+    ProviderError::other("the vault doesn't contain the obsidian-local-rest-api plugin")
 }
 
 pub struct Client {
     cfg: Option<Config>,
-    client: reqwest::Client,
+    http: SharedHttpClient,
 }
 
 impl std::fmt::Debug for Client {
@@ -46,20 +57,25 @@ impl Client {
     pub fn new(vault_path: &Path) -> Self {
         Self {
             cfg: read_config(vault_path.join(CONFIG_PATH)),
-            client: reqwest::Client::new(),
+            http: Arc::new(ReqwestClient::default()),
         }
     }
 
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
     pub fn is_available(&self) -> bool {
         self.cfg.is_some()
     }
 
-    fn token(&self) -> Result<String, StringError> {
+    fn token(&self) -> Result<String, ProviderError> {
         let cfg = self.cfg.as_ref().ok_or(not_connected_err())?;
         Ok(cfg.api_key.clone())
     }
 
-    fn url(&self, uri: &str) -> Result<String, StringError> {
+    fn url(&self, uri: &str) -> Result<String, ProviderError> {
         let cfg = self.cfg.as_ref().ok_or(not_connected_err())?;
         let (transport, port) = if cfg.enable_insecure_server {
             ("http", cfg.insecure_port)
@@ -69,44 +85,95 @@ impl Client {
         Ok(format!("{transport}://localhost:{port}{uri}"))
     }
 
+    // The path of the daily note relative to the vault, it's set by the Obsidian settings
+    pub async fn daily_note_path(&self) -> Result<String, ProviderError> {
+        let url = self.url("/periodic/daily/")?;
+        let note = self
+            .http
+            .send_checked(
+                Request::get(&url)
+                    .with_bearer_auth(&self.token()?)
+                    .with_header("Accept", "application/vnd.olrapi.note+json"),
+            )
+            .await?
+            .json::<NoteJson>()?;
+        Ok(note.path)
+    }
+
     #[tracing::instrument(level = "info", target = "obsidian_rest_client")]
-    pub async fn add_text_to_daily_note(&self, data: &str) -> Result<(), StringError> {
+    pub async fn add_text_to_daily_note(&self, data: &str) -> Result<(), ProviderError> {
         let url = self.url("/periodic/daily/")?;
         let token = self.token()?;
 
-        if let Ok(r) = self.client.get(&url).bearer_auth(&token).send().await
-            && r.status() == StatusCode::NOT_FOUND
+        if let Ok(r) = self.http.send(Request::get(&url).with_bearer_auth(&token)).await
+            && r.status == 404
         {
             // Sometimes, when the user have any templating plugin that rewrites all created files with
             // template, the daily note creates with no task. So, we create the daily note first
             // and then add a small delay.
             tracing::info!("Create daily note");
 
-            self.client
-            .post(&url)
-            .bearer_auth(&token)
-            .header(reqwest::header::CONTENT_TYPE, "text/markdown")
-            .send()
+            self.http
+            .send_checked(Request::post(&url).with_bearer_auth(&token).with_header("Content-Type", "text/markdown"))
             .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"obsidian_rest_client", data=?data, cfg=?self.cfg, error=?e, "Create daily note");
-                StringError::new(e.to_string().as_str())
+                e
             })?;
             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
         }
 
-        self.client
-            .post(&url)
-            .bearer_auth(&token)
-            .header(reqwest::header::CONTENT_TYPE, "text/markdown")
-            .body(reqwest::Body::wrap(data.to_string()))
-            .send()
+        self.http
+            .send_checked(
+                Request::post(&url)
+                    .with_bearer_auth(&token)
+                    .with_header("Content-Type", "text/markdown")
+                    .with_body(data.as_bytes()),
+            )
             .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"obsidian_rest_client", data=?data, cfg=?self.cfg, error=?e, "Add text to daily note");
-                StringError::new(e.to_string().as_str())
+                e
             })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::http::{Method, MockHttpClient, Response};
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn add_text_to_daily_note_test() {
+        const URL: &str = "http://localhost:27123/periodic/daily/";
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let cfg_path = temp_dir.path().join(CONFIG_PATH);
+        std::fs::create_dir_all(cfg_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &cfg_path,
+            r#"{"port": 27124, "insecurePort": 27123, "enableInsecureServer": true, "apiKey": "key"}"#,
+        )
+        .unwrap();
+
+        // the daily note exists, so it isn't created
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(Method::Get, URL, Response::new(200, b""))
+                .with_response(Method::Post, URL, Response::new(204, b"")),
+        );
+        let c = Client::new(temp_dir.path()).with_http_client(http.clone());
+        assert!(c.is_available());
+        c.add_text_to_daily_note("- [ ] Buy milk").await.unwrap();
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].body, Some(b"- [ ] Buy milk".to_vec()));
+
+        let c = Client::new(Path::new("/vault/not/exists")).with_http_client(http);
+        assert!(!c.is_available());
+        assert!(c.add_text_to_daily_note("- [ ] Buy milk").await.is_err());
+    }
+}
@@ -0,0 +1,479 @@
+// SPDX-License-Identifier: MIT
+
+// The TODO headings of the org files. The headings and their planning lines are patched in place,
+// the rest of the files is kept as Emacs wrote it.
+
+mod file;
+mod project;
+mod task;
+mod timestamp;
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use chrono::{Local, TimeZone, Utc};
+use tatuin_core::{
+    filter,
+    fs::{LocalFileSystem, SharedFileSystem},
+    intern::intern,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::{DateTimeUtc, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch, ValuePatch},
+};
+
+use crate::{config::Config, markdown_dir::journal};
+use file::{Heading, Keywords, Planning};
+use timestamp::Timestamp;
+use tokio::sync::OnceCell;
+
+pub const PROVIDER_NAME: &str = "Org";
+
+const FILE_EXTENSION: &str = "org";
+
+pub struct Provider {
+    cfg: Config,
+    // the folder with the org files or the single file
+    path: PathBuf,
+    fs: SharedFileSystem,
+    recovered: OnceCell<()>,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, path: &Path) -> Self {
+        Self {
+            cfg,
+            path: path.into(),
+            fs: LocalFileSystem::shared(),
+            recovered: OnceCell::new(),
+        }
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    async fn is_dir(&self) -> bool {
+        self.fs.is_dir(&self.path).await
+    }
+
+    // The writes interrupted by the previous run are finished before the first access to the files
+    async fn recover(&self) {
+        self.recovered
+            .get_or_init(|| async {
+                if self.is_dir().await
+                    && let Err(e) = journal::recover(self.fs.as_ref(), &self.path).await
+                {
+                    tracing::error!(error=?e, path=?self.path, "Recover the interrupted writes");
+                }
+            })
+            .await;
+    }
+
+    // The hidden folders like .git are skipped
+    async fn files(&self) -> Result<Vec<PathBuf>, ProviderError> {
+        self.recover().await;
+        if !self.is_dir().await {
+            return Ok(vec![self.path.clone()]);
+        }
+
+        Ok(self
+            .fs
+            .files(&self.path)
+            .await?
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|ext| ext == FILE_EXTENSION) && !is_hidden(&self.path, p))
+            .collect())
+    }
+
+    async fn file_path(&self, project_id: &str) -> PathBuf {
+        if self.is_dir().await {
+            self.path.join(project_id)
+        } else {
+            self.path.clone()
+        }
+    }
+
+    async fn read(&self, file_path: &Path) -> Result<String, std::io::Error> {
+        self.recover().await;
+        self.fs.read_to_string(file_path).await
+    }
+
+    async fn tasks(&self, file_path: &Path) -> Result<Vec<task::Task>, ProviderError> {
+        let content = self.read(file_path).await?;
+        let keywords = Arc::new(Keywords::parse(&content));
+        let name = intern(&self.name());
+        Ok(file::parse(&content, &keywords)
+            .into_iter()
+            .map(|e| task::Task::new(name.clone(), &self.path, file_path, e, keywords.clone()))
+            .collect())
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+fn is_hidden(root: &Path, p: &Path) -> bool {
+    p.strip_prefix(root)
+        .is_ok_and(|p| p.components().any(|c| c.as_os_str().to_string_lossy().starts_with('.')))
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let name = intern(&self.name());
+        Ok(self
+            .files()
+            .await?
+            .iter()
+            .map(|f| Box::new(project::Project::new(name.clone(), &self.path, f)) as Box<dyn ProjectTrait>)
+            .collect())
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let files = match project {
+            Some(p) => vec![self.file_path(&p.id()).await],
+            None => self.files().await?,
+        };
+
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        for file_path in files {
+            for t in self.tasks(&file_path).await? {
+                if f.accept(&t) {
+                    result.push(Box::new(t));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let file_path = self.file_path(project_id).await;
+        let content = self.read(&file_path).await?;
+        let keywords = Keywords::parse(&content);
+
+        let mut h = Heading::new(&keywords.active[0], "");
+        let mut p = Planning::default();
+        apply_patch(&mut h, &mut p, &keywords, tp, &Local);
+        let description = tp.description.value().unwrap_or_default();
+
+        journal::write(
+            self.fs.as_ref(),
+            &file_path,
+            &file::append(&content, &h, &p, &description),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!(error=?e, file=?file_path, "Add the task");
+            e.into()
+        })
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        let mut files: BTreeMap<PathBuf, Vec<(&task::Task, &TaskPatch)>> = BTreeMap::new();
+        for p in patches {
+            let task = p.task.as_ref().unwrap();
+            match task.as_any().downcast_ref::<task::Task>() {
+                Some(t) => files.entry(t.file_path.clone()).or_default().push((t, p)),
+                None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
+            }
+        }
+
+        for (file_path, mut patches) in files {
+            let error = |tp: &TaskPatch, e: &str| PatchError {
+                task: tp.task.clone().unwrap(),
+                error: e.to_string(),
+                kind: PatchErrorKind::Other,
+            };
+
+            let mut content = match self.read(&file_path).await {
+                Ok(c) => c,
+                Err(e) => {
+                    errors.extend(patches.iter().map(|(_, tp)| error(tp, &e.to_string())));
+                    continue;
+                }
+            };
+            let keywords = Keywords::parse(&content);
+
+            // the lower headings go first, so the planning lines added or removed don't shift the upper ones
+            patches.sort_by_key(|(t, _)| std::cmp::Reverse(t.entry.line));
+            let mut changed = Vec::new();
+            for (t, tp) in patches {
+                let (mut h, mut p) = (t.entry.heading.clone(), t.entry.planning.clone());
+                apply_patch(&mut h, &mut p, &keywords, tp, &Local);
+                match file::replace(&content, t.entry.line, &t.entry.raw, &h, &p) {
+                    Ok(c) => {
+                        content = c;
+                        changed.push(tp);
+                    }
+                    Err(e) => errors.push(error(tp, &e)),
+                }
+            }
+
+            if changed.is_empty() {
+                continue;
+            }
+            if let Err(e) = journal::write(self.fs.as_ref(), &file_path, &content).await {
+                tracing::error!(error=?e, file=?file_path, "Write the tasks");
+                errors.extend(changed.into_iter().map(|tp| error(tp, &e.to_string())));
+            }
+        }
+
+        errors
+    }
+
+    async fn delete(&mut self, _t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        Err(ProviderError::other(
+            "The org headings can't be deleted, close them instead",
+        ))
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        // do nothing, the files are read on every request
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+// The repeater of the changed date is kept
+fn date_patch<Tz: TimeZone>(current: Option<Timestamp>, p: &ValuePatch<DatePatchItem>, tz: &Tz) -> Option<Timestamp> {
+    let dt: Option<DateTimeUtc> = p.value().and_then(|d| d.into());
+    let suffix = current.map(|ts| ts.suffix).unwrap_or_default();
+    dt.map(|d| Timestamp::from_datetime(&d, tz, true).with_suffix(&suffix))
+}
+
+fn apply_patch<Tz: TimeZone>(h: &mut Heading, p: &mut Planning, keywords: &Keywords, tp: &TaskPatch, tz: &Tz) {
+    if let Some(n) = tp.name.value() {
+        h.title = n;
+    }
+
+    if tp.priority.is_set() {
+        h.priority = tp.priority.value().and_then(task::priority_to_char);
+    }
+
+    if let Some(s) = tp.state.value()
+        && s != task::state(&h.keyword, keywords)
+    {
+        match s {
+            State::Completed => {
+                h.keyword = keywords.done[0].clone();
+                p.closed = Some(Timestamp::from_datetime(&Utc::now(), tz, false));
+            }
+            State::Uncompleted => {
+                h.keyword = keywords.active[0].clone();
+                p.closed = None;
+            }
+            State::InProgress => {
+                if let Some(k) = keywords.active.get(1) {
+                    h.keyword = k.clone();
+                    p.closed = None;
+                }
+            }
+            State::Unknown(_) => {}
+        }
+    }
+
+    if tp.due.is_set() {
+        p.deadline = date_patch(p.deadline.take(), &tp.due, tz);
+    }
+
+    if tp.scheduled.is_set() {
+        p.scheduled = date_patch(p.scheduled.take(), &tp.scheduled, tz);
+    }
+
+    if tp.labels.is_set() {
+        h.tags = tp.labels.value().unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::{
+        filter::Filter,
+        fs::{FileSystem, MemoryFileSystem},
+        task::Priority,
+    };
+
+    const CONTENT: &str = "#+TODO: TODO NEXT | DONE
+* TODO Call mom :phone:
+  DEADLINE: <2025-01-05 Sun +1w>
+About the trip
+* DONE Pay bills
+CLOSED: [2025-01-03 Fri 09:00]
+";
+
+    fn provider(root: &Path) -> Provider {
+        Provider::new(Config::new("test_app", "test_name"), root)
+    }
+
+    #[test]
+    fn apply_patch_test() {
+        let keywords = Keywords::parse(CONTENT);
+        let e = file::parse(CONTENT, &keywords).remove(0);
+        let (mut h, mut p) = (e.heading, e.planning);
+
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Call dad".to_string()),
+            priority: ValuePatch::Value(Priority::Highest),
+            state: ValuePatch::Value(State::InProgress),
+            due: ValuePatch::Value(DatePatchItem::Custom(
+                Timestamp::parse("<2025-01-06>").unwrap().to_datetime(&Utc),
+            )),
+            scheduled: ValuePatch::Value(DatePatchItem::Custom(
+                Timestamp::parse("<2025-01-04>").unwrap().to_datetime(&Utc),
+            )),
+            labels: ValuePatch::Value(vec!["phone".to_string(), "family".to_string()]),
+            ..TaskPatch::default()
+        };
+        apply_patch(&mut h, &mut p, &keywords, &tp, &Utc);
+        assert_eq!(h.to_string(), "* NEXT [#A] Call dad :phone:family:");
+        assert_eq!(
+            p.to_string(),
+            "  DEADLINE: <2025-01-06 Mon +1w> SCHEDULED: <2025-01-04 Sat>"
+        );
+
+        let tp = TaskPatch {
+            priority: ValuePatch::Empty,
+            state: ValuePatch::Value(State::Completed),
+            due: ValuePatch::Empty,
+            scheduled: ValuePatch::Empty,
+            labels: ValuePatch::Empty,
+            ..TaskPatch::default()
+        };
+        apply_patch(&mut h, &mut p, &keywords, &tp, &Utc);
+        assert_eq!(h.to_string(), "* DONE Call dad");
+        assert!(p.closed.is_some());
+        assert!(p.deadline.is_none() && p.scheduled.is_none());
+
+        let tp = TaskPatch {
+            state: ValuePatch::Value(State::Uncompleted),
+            ..TaskPatch::default()
+        };
+        apply_patch(&mut h, &mut p, &keywords, &tp, &Utc);
+        assert_eq!(h.to_string(), "* TODO Call dad");
+        assert!(p.is_empty());
+    }
+
+    #[tokio::test]
+    async fn provider_test() {
+        let root = Path::new("/notes");
+        let fs = Arc::new(
+            MemoryFileSystem::new()
+                .with_file(&root.join("home/family.org"), CONTENT)
+                .with_file(&root.join("inbox.org"), "")
+                .with_file(&root.join(".git/hidden.org"), CONTENT)
+                .with_file(&root.join("notes.md"), CONTENT),
+        );
+
+        let mut p = provider(root).with_file_system(fs.clone());
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(
+            projects.iter().map(|p| p.id()).collect::<Vec<String>>(),
+            vec!["home/family.org", "inbox.org"]
+        );
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].name().raw(), "Call mom");
+        assert_eq!(tasks[1].state(), State::Completed);
+
+        let errors = p
+            .update(&[TaskPatch {
+                task: Some(tasks[0].clone_boxed().into()),
+                state: ValuePatch::Value(State::InProgress),
+                due: ValuePatch::Empty,
+                ..TaskPatch::default()
+            }])
+            .await;
+        assert!(errors.is_empty());
+        assert_eq!(
+            fs.read_to_string(&root.join("home/family.org")).await.unwrap(),
+            CONTENT.replace(
+                "* TODO Call mom :phone:\n  DEADLINE: <2025-01-05 Sun +1w>\n",
+                "* NEXT Call mom :phone:\n"
+            )
+        );
+
+        // the task is read before the change
+        let errors = p
+            .update(&[TaskPatch {
+                task: Some(tasks[0].clone_boxed().into()),
+                state: ValuePatch::Value(State::Completed),
+                ..TaskPatch::default()
+            }])
+            .await;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error, "The task has been changed outside");
+
+        p.create(
+            "inbox.org",
+            &TaskPatch {
+                name: ValuePatch::Value("Buy milk".to_string()),
+                priority: ValuePatch::Value(Priority::Low),
+                ..TaskPatch::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            fs.read_to_string(&root.join("inbox.org")).await.unwrap(),
+            "* TODO [#C] Buy milk\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn single_file_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let path = temp_dir.path().join("todo.org");
+        std::fs::write(&path, CONTENT).unwrap();
+
+        let mut p = provider(&path);
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(projects.len(), 1);
+        let tasks = TaskProviderTrait::list(&mut p, Some(projects[0].clone_boxed()), &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+    }
+}
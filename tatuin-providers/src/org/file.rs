@@ -0,0 +1,478 @@
+// SPDX-License-Identifier: MIT
+
+// The parser of the org headings with the TODO keywords and their planning lines.
+// The lines are patched in place, the rest of the file is kept as it is.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::timestamp::Timestamp;
+
+const DEFAULT_ACTIVE_KEYWORD: &str = "TODO";
+const DEFAULT_DONE_KEYWORD: &str = "DONE";
+const KEYWORD_SETTINGS: &[&str] = &["#+todo:", "#+seq_todo:", "#+typ_todo:"];
+
+static PLANNING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(CLOSED|DEADLINE|SCHEDULED):\s*([<\[][^>\]]+[>\]])").unwrap());
+static TAGS_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(\s+)(:[\w@#%:]+:)\s*$").unwrap());
+
+// The keywords of the file, they're set by the #+TODO: lines like `#+TODO: TODO NEXT | DONE CANCELED`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Keywords {
+    pub active: Vec<String>,
+    pub done: Vec<String>,
+}
+
+impl Default for Keywords {
+    fn default() -> Self {
+        Self {
+            active: vec![DEFAULT_ACTIVE_KEYWORD.to_string()],
+            done: vec![DEFAULT_DONE_KEYWORD.to_string()],
+        }
+    }
+}
+
+impl Keywords {
+    pub fn parse(content: &str) -> Self {
+        let mut result = Self {
+            active: Vec::new(),
+            done: Vec::new(),
+        };
+
+        for l in content.lines() {
+            let lower = l.trim_start().to_lowercase();
+            let Some(prefix) = KEYWORD_SETTINGS.iter().find(|p| lower.starts_with(**p)) else {
+                continue;
+            };
+            // the fast access keys like TODO(t) aren't the parts of the keywords
+            let words = l.trim_start()[prefix.len()..]
+                .split_whitespace()
+                .map(|w| w.split('(').next().unwrap_or_default().to_string())
+                .filter(|w| !w.is_empty())
+                .collect::<Vec<String>>();
+            match words.iter().position(|w| w == "|") {
+                Some(i) => {
+                    result.active.extend_from_slice(&words[..i]);
+                    result.done.extend_from_slice(&words[i + 1..]);
+                }
+                // without the separator the last keyword is the done one
+                None => {
+                    if let Some((last, rest)) = words.split_last() {
+                        result.active.extend_from_slice(rest);
+                        result.done.push(last.to_string());
+                    }
+                }
+            }
+        }
+
+        if result.active.is_empty() || result.done.is_empty() {
+            Self::default()
+        } else {
+            result
+        }
+    }
+
+    pub fn contains(&self, w: &str) -> bool {
+        self.active.iter().chain(self.done.iter()).any(|k| k == w)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heading {
+    pub level: usize,
+    pub keyword: String,
+    pub priority: Option<char>,
+    pub title: String,
+    pub tags: Vec<String>,
+    // the alignment of the tags is kept
+    tags_gap: String,
+}
+
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    (level > 0 && line[level..].starts_with(' ')).then_some(level)
+}
+
+impl Heading {
+    pub fn new(keyword: &str, title: &str) -> Self {
+        Self {
+            level: 1,
+            keyword: keyword.to_string(),
+            title: title.to_string(),
+            ..Self::default()
+        }
+    }
+
+    // The headings without the keywords aren't the tasks
+    pub fn parse(line: &str, keywords: &Keywords) -> Option<Self> {
+        let level = heading_level(line)?;
+        let rest = line[level..].trim_start();
+        let (keyword, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+        if !keywords.contains(keyword) {
+            return None;
+        }
+
+        let mut rest = rest.trim_start();
+        let mut priority = None;
+        if let Some(p) = rest.strip_prefix("[#")
+            && let Some(c) = p.chars().next()
+            && p[c.len_utf8()..].starts_with(']')
+        {
+            priority = Some(c);
+            rest = p[c.len_utf8() + 1..].trim_start();
+        }
+
+        let (mut title, mut tags, mut tags_gap) = (rest.trim_end().to_string(), Vec::new(), String::new());
+        if let Some(c) = TAGS_RE.captures(rest) {
+            let m = c.get(0).unwrap();
+            title = rest[..m.start()].to_string();
+            tags_gap = c[1].to_string();
+            tags = c[2]
+                .split(':')
+                .filter(|t| !t.is_empty())
+                .map(|t| t.to_string())
+                .collect();
+        }
+
+        Some(Self {
+            level,
+            keyword: keyword.to_string(),
+            priority,
+            title,
+            tags,
+            tags_gap,
+        })
+    }
+}
+
+impl std::fmt::Display for Heading {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", "*".repeat(self.level), self.keyword)?;
+        if let Some(p) = self.priority {
+            write!(f, " [#{p}]")?;
+        }
+        if !self.title.is_empty() {
+            write!(f, " {}", self.title)?;
+        }
+        if !self.tags.is_empty() {
+            let gap = if self.tags_gap.is_empty() { " " } else { &self.tags_gap };
+            write!(f, "{gap}:{}:", self.tags.join(":"))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Planning {
+    pub closed: Option<Timestamp>,
+    pub deadline: Option<Timestamp>,
+    pub scheduled: Option<Timestamp>,
+    indent: String,
+}
+
+impl Planning {
+    pub fn parse(line: &str) -> Option<Self> {
+        if !PLANNING_RE.is_match(line) || !PLANNING_RE.replace_all(line, "").trim().is_empty() {
+            return None;
+        }
+
+        let mut result = Self {
+            indent: line[..line.len() - line.trim_start().len()].to_string(),
+            ..Self::default()
+        };
+        for c in PLANNING_RE.captures_iter(line) {
+            let ts = Timestamp::parse(&c[2]);
+            match &c[1] {
+                "CLOSED" => result.closed = ts,
+                "DEADLINE" => result.deadline = ts,
+                _ => result.scheduled = ts,
+            }
+        }
+        Some(result)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.closed.is_none() && self.deadline.is_none() && self.scheduled.is_none()
+    }
+}
+
+impl std::fmt::Display for Planning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = [
+            ("CLOSED", &self.closed),
+            ("DEADLINE", &self.deadline),
+            ("SCHEDULED", &self.scheduled),
+        ]
+        .iter()
+        .filter_map(|(k, ts)| ts.as_ref().map(|ts| format!("{k}: {ts}")))
+        .collect::<Vec<String>>();
+        write!(f, "{}{}", self.indent, parts.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    // the index of the heading line
+    pub line: usize,
+    pub raw: String,
+    pub heading: Heading,
+    pub planning: Planning,
+    pub description: String,
+}
+
+fn is_drawer_start(line: &str) -> bool {
+    let l = line.trim();
+    l.len() > 2 && l.starts_with(':') && l.ends_with(':') && l != ":END:" && !l[1..l.len() - 1].contains(':')
+}
+
+// The text under the heading without the planning line and the drawers
+fn description(lines: &[&str]) -> String {
+    let mut result = Vec::new();
+    let mut in_drawer = false;
+    for l in lines {
+        if in_drawer {
+            in_drawer = l.trim() != ":END:";
+        } else if is_drawer_start(l) {
+            in_drawer = true;
+        } else {
+            result.push(l.trim());
+        }
+    }
+    result.join("\n").trim().to_string()
+}
+
+pub fn parse(content: &str, keywords: &Keywords) -> Vec<Entry> {
+    let lines = content.lines().collect::<Vec<&str>>();
+    let headings = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| heading_level(l).is_some())
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+
+    let mut result = Vec::new();
+    for (n, i) in headings.iter().enumerate() {
+        let Some(heading) = Heading::parse(lines[*i], keywords) else {
+            continue;
+        };
+        let end = headings.get(n + 1).copied().unwrap_or(lines.len());
+
+        let mut body = i + 1;
+        let mut planning = Planning::default();
+        if let Some(p) = lines.get(body).filter(|_| body < end).and_then(|l| Planning::parse(l)) {
+            planning = p;
+            body += 1;
+        }
+
+        result.push(Entry {
+            line: *i,
+            raw: lines[*i].to_string(),
+            heading,
+            planning,
+            description: description(&lines[body.min(end)..end]),
+        });
+    }
+    result
+}
+
+// Replaces the heading and its planning line, the heading should be the same as it was read
+pub fn replace(content: &str, line: usize, raw: &str, h: &Heading, p: &Planning) -> Result<String, String> {
+    let mut lines = content.split('\n').map(|l| l.to_string()).collect::<Vec<String>>();
+    if lines.get(line).map(|l| l.trim_end_matches('\r')) != Some(raw) {
+        return Err("The task has been changed outside".to_string());
+    }
+
+    lines[line] = h.to_string();
+    let has_planning = lines.get(line + 1).and_then(|l| Planning::parse(l)).is_some();
+    match (has_planning, p.is_empty()) {
+        (true, true) => {
+            lines.remove(line + 1);
+        }
+        (true, false) => lines[line + 1] = p.to_string(),
+        (false, false) => lines.insert(line + 1, p.to_string()),
+        (false, true) => {}
+    }
+    Ok(lines.join("\n"))
+}
+
+pub fn append(content: &str, h: &Heading, p: &Planning, description: &str) -> String {
+    let mut result = content.to_string();
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    result.push_str(&h.to_string());
+    result.push('\n');
+    if !p.is_empty() {
+        result.push_str(&p.to_string());
+        result.push('\n');
+    }
+    for l in description.lines() {
+        result.push_str(l);
+        result.push('\n');
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONTENT: &str = "#+TITLE: Home
+#+TODO: TODO NEXT(n) | DONE(d) CANCELED
+
+* Family
+** TODO [#A] Call mom                                               :phone:family:
+   DEADLINE: <2025-01-05 Sun> SCHEDULED: <2025-01-04 Sat 10:00 +1w>
+   :PROPERTIES:
+   :ID: 42
+   :END:
+   About the trip
+   and the dog
+** NEXT Buy a present
+** DONE Pay bills
+CLOSED: [2025-01-03 Fri 09:00]
+* Notes without a keyword
+TODO is just a word here
+";
+
+    #[test]
+    fn keywords_test() {
+        assert_eq!(
+            Keywords::parse(CONTENT),
+            Keywords {
+                active: vec!["TODO".to_string(), "NEXT".to_string()],
+                done: vec!["DONE".to_string(), "CANCELED".to_string()],
+            }
+        );
+        assert_eq!(
+            Keywords::parse("#+SEQ_TODO: TODO WAIT DONE\n"),
+            Keywords {
+                active: vec!["TODO".to_string(), "WAIT".to_string()],
+                done: vec!["DONE".to_string()],
+            }
+        );
+        assert_eq!(Keywords::parse("* TODO Call mom\n"), Keywords::default());
+    }
+
+    #[test]
+    fn parse_test() {
+        let entries = parse(CONTENT, &Keywords::parse(CONTENT));
+        assert_eq!(entries.len(), 3);
+
+        let e = &entries[0];
+        assert_eq!(e.line, 4);
+        assert_eq!(e.heading.level, 2);
+        assert_eq!(e.heading.keyword, "TODO");
+        assert_eq!(e.heading.priority, Some('A'));
+        assert_eq!(e.heading.title, "Call mom");
+        assert_eq!(e.heading.tags, vec!["phone", "family"]);
+        assert_eq!(e.planning.deadline, Timestamp::parse("<2025-01-05 Sun>"));
+        assert_eq!(e.planning.scheduled, Timestamp::parse("<2025-01-04 Sat 10:00 +1w>"));
+        assert_eq!(e.description, "About the trip\nand the dog");
+        // the heading is written back as it was
+        assert_eq!(e.heading.to_string(), e.raw);
+        assert_eq!(e.planning.to_string(), CONTENT.lines().nth(5).unwrap());
+
+        assert_eq!(entries[1].heading.keyword, "NEXT");
+        assert_eq!(entries[1].heading.title, "Buy a present");
+        assert!(entries[1].planning.is_empty());
+        assert_eq!(entries[1].description, "");
+
+        assert_eq!(entries[2].planning.closed, Timestamp::parse("[2025-01-03 Fri 09:00]"));
+    }
+
+    #[test]
+    fn heading_test() {
+        struct Case<'a> {
+            name: &'a str,
+            line: &'a str,
+            expected: Option<(&'a str, Option<char>, &'a str, Vec<&'a str>)>,
+        }
+        let cases = [
+            Case {
+                name: "only keyword",
+                line: "* TODO",
+                expected: Some(("TODO", None, "", vec![])),
+            },
+            Case {
+                name: "title with colons",
+                line: "* DONE Meeting at 10:00",
+                expected: Some(("DONE", None, "Meeting at 10:00", vec![])),
+            },
+            Case {
+                name: "priority and tag",
+                line: "*** TODO [#C] Read a book :books:",
+                expected: Some(("TODO", Some('C'), "Read a book", vec!["books"])),
+            },
+            Case {
+                name: "unknown keyword",
+                line: "* WAIT Call mom",
+                expected: None,
+            },
+            Case {
+                name: "bold text isn't a heading",
+                line: "*TODO* Call mom",
+                expected: None,
+            },
+        ];
+
+        for c in cases {
+            let h = Heading::parse(c.line, &Keywords::default());
+            assert_eq!(
+                h.as_ref().map(|h| (
+                    h.keyword.as_str(),
+                    h.priority,
+                    h.title.as_str(),
+                    h.tags.iter().map(|t| t.as_str()).collect()
+                )),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+            if let Some(h) = h {
+                assert_eq!(h.to_string(), c.line, "Test '{}' was failed", c.name);
+            }
+        }
+    }
+
+    #[test]
+    fn replace_test() {
+        let keywords = Keywords::parse(CONTENT);
+        let entries = parse(CONTENT, &keywords);
+
+        // the planning line is removed
+        let mut h = entries[0].heading.clone();
+        h.keyword = "DONE".to_string();
+        let content = replace(CONTENT, entries[0].line, &entries[0].raw, &h, &Planning::default()).unwrap();
+        assert!(content.contains(
+            "** DONE [#A] Call mom                                               :phone:family:\n   :PROPERTIES:"
+        ));
+
+        // the planning line is added
+        let p = Planning {
+            deadline: Timestamp::parse("<2025-01-06 Mon>"),
+            ..Planning::default()
+        };
+        let content = replace(CONTENT, entries[1].line, &entries[1].raw, &entries[1].heading, &p).unwrap();
+        assert!(content.contains("** NEXT Buy a present\nDEADLINE: <2025-01-06 Mon>\n** DONE Pay bills"));
+        assert!(content.ends_with("a word here\n"));
+
+        assert_eq!(
+            replace(CONTENT, entries[1].line, "** NEXT Buy a gift", &entries[1].heading, &p),
+            Err("The task has been changed outside".to_string())
+        );
+    }
+
+    #[test]
+    fn append_test() {
+        let h = Heading::new("TODO", "Call mom");
+        let mut p = Planning::default();
+        assert_eq!(append("", &h, &p, ""), "* TODO Call mom\n");
+        p.scheduled = Timestamp::parse("<2025-01-06 Mon>");
+        assert_eq!(
+            append("* DONE Pay bills", &h, &p, "About the trip"),
+            "* DONE Pay bills\n* TODO Call mom\nSCHEDULED: <2025-01-06 Mon>\nAbout the trip\n"
+        );
+    }
+}
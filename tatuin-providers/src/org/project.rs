@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+
+use std::path::{Path, PathBuf};
+
+use tatuin_core::{intern::IStr, project::Project as ProjectTrait};
+
+use crate::markdown_dir::fs;
+
+// Every org file is a project
+#[derive(Clone)]
+pub struct Project {
+    provider: IStr,
+    root_path: PathBuf,
+    file_path: PathBuf,
+}
+
+impl Project {
+    pub fn new(provider: IStr, root_path: &Path, file_path: &Path) -> Self {
+        Self {
+            provider,
+            root_path: root_path.into(),
+            file_path: file_path.into(),
+        }
+    }
+}
+
+impl std::fmt::Debug for Project {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Project id={} name={}",
+            ProjectTrait::id(self),
+            ProjectTrait::name(self)
+        )
+    }
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        let id = fs::strip_root_str(&self.root_path, &self.file_path);
+        // the single file is the root itself
+        if id.is_empty() {
+            self.file_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default()
+        } else {
+            id
+        }
+    }
+
+    fn name(&self) -> String {
+        self.file_path
+            .file_stem()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        None
+    }
+
+    fn is_inbox(&self) -> bool {
+        false
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn project_test() {
+        let p = Project::new(
+            IStr::default(),
+            Path::new("/notes"),
+            Path::new("/notes/home/family.org"),
+        );
+        assert_eq!(p.id(), "home/family.org");
+        assert_eq!(p.name(), "family");
+
+        let p = Project::new(
+            IStr::default(),
+            Path::new("/notes/todo.org"),
+            Path::new("/notes/todo.org"),
+        );
+        assert_eq!(p.id(), "todo.org");
+        assert_eq!(p.name(), "todo");
+    }
+}
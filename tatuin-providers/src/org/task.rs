@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MIT
+
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use chrono::Local;
+use tatuin_core::{
+    RichString,
+    intern::IStr,
+    project::Project as ProjectTrait,
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task_patch::DatePatchItem,
+};
+
+use super::{
+    file::{Entry, Keywords},
+    project::Project,
+};
+
+// Org has three priorities, the highest and the lowest ones of tatuin are merged into them
+pub fn priority_from_char(c: Option<char>) -> Priority {
+    match c {
+        Some('A') => Priority::High,
+        Some('B') => Priority::Medium,
+        Some('C') => Priority::Low,
+        _ => Priority::Normal,
+    }
+}
+
+pub fn priority_to_char(p: Priority) -> Option<char> {
+    match p {
+        Priority::Highest | Priority::High => Some('A'),
+        Priority::Medium => Some('B'),
+        Priority::Low | Priority::Lowest => Some('C'),
+        Priority::Normal => None,
+    }
+}
+
+// The first active keyword is the new task, the other active ones are the tasks in progress
+pub fn state(keyword: &str, keywords: &Keywords) -> State {
+    if keywords.done.iter().any(|k| k == keyword) {
+        State::Completed
+    } else if keywords.active.first().is_some_and(|k| k == keyword) {
+        State::Uncompleted
+    } else {
+        State::InProgress
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Task {
+    pub provider: IStr,
+    pub root_path: PathBuf,
+    pub file_path: PathBuf,
+    pub entry: Entry,
+    pub keywords: Arc<Keywords>,
+}
+
+impl Task {
+    pub fn new(provider: IStr, root_path: &Path, file_path: &Path, entry: Entry, keywords: Arc<Keywords>) -> Self {
+        Self {
+            provider,
+            root_path: root_path.into(),
+            file_path: file_path.into(),
+            entry,
+            keywords,
+        }
+    }
+
+    fn project_(&self) -> Project {
+        Project::new(self.provider.clone(), &self.root_path, &self.file_path)
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        let id = format!("{}:{}:{}", self.project_().id(), self.entry.line, self.entry.raw);
+        sha256::digest(id)
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(&self.entry.heading.title)
+    }
+
+    fn description(&self) -> Option<RichString> {
+        if self.entry.description.is_empty() {
+            None
+        } else {
+            Some(RichString::new(&self.entry.description))
+        }
+    }
+
+    fn priority(&self) -> Priority {
+        priority_from_char(self.entry.heading.priority)
+    }
+
+    fn state(&self) -> State {
+        state(&self.entry.heading.keyword, &self.keywords)
+    }
+
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        self.entry.planning.closed.as_ref().map(|ts| ts.to_datetime(&Local))
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.entry.planning.deadline.as_ref().map(|ts| ts.to_datetime(&Local))
+    }
+
+    fn scheduled(&self) -> Option<DateTimeUtc> {
+        self.entry.planning.scheduled.as_ref().map(|ts| ts.to_datetime(&Local))
+    }
+
+    fn place(&self) -> String {
+        format!("{}:{}", self.file_path.display(), self.entry.line + 1)
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.entry.heading.tags.clone()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(Box::new(self.project_()))
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        let mut available_states = vec![State::Uncompleted, State::Completed];
+        // the progress needs the second active keyword like NEXT or STARTED
+        if self.keywords.active.len() > 1 {
+            available_states.insert(1, State::InProgress);
+        }
+
+        PatchPolicy {
+            is_editable: true,
+            is_removable: false,
+            available_states,
+            available_priorities: vec![Priority::Normal, Priority::Low, Priority::Medium, Priority::High],
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: DatePatchItem::values(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::org::file;
+
+    #[test]
+    fn task_test() {
+        let content =
+            "#+TODO: TODO NEXT | DONE\n* NEXT [#B] Call mom :phone:\nDEADLINE: <2025-01-05 Sun>\nAbout the trip\n";
+        let keywords = Arc::new(Keywords::parse(content));
+        let entry = file::parse(content, &keywords).remove(0);
+        let t = Task::new(
+            IStr::default(),
+            Path::new("/notes"),
+            Path::new("/notes/home/family.org"),
+            entry,
+            keywords,
+        );
+        assert_eq!(t.name().raw(), "Call mom");
+        assert_eq!(t.description().unwrap().raw(), "About the trip");
+        assert_eq!(t.priority(), Priority::Medium);
+        assert_eq!(t.state(), State::InProgress);
+        assert_eq!(t.labels(), vec!["phone"]);
+        assert_eq!(
+            t.due(),
+            chrono::NaiveDate::from_ymd_opt(2025, 1, 5).map(|d| d.and_time(chrono::NaiveTime::MIN).and_utc())
+        );
+        assert_eq!(t.place(), "/notes/home/family.org:2");
+        assert_eq!(t.project().unwrap().id(), "home/family.org");
+        assert_eq!(
+            t.const_patch_policy().available_states,
+            vec![State::Uncompleted, State::InProgress, State::Completed]
+        );
+    }
+
+    #[test]
+    fn state_test() {
+        let keywords = Keywords::parse("#+TODO: TODO NEXT WAIT | DONE CANCELED\n");
+        assert_eq!(state("TODO", &keywords), State::Uncompleted);
+        assert_eq!(state("NEXT", &keywords), State::InProgress);
+        assert_eq!(state("WAIT", &keywords), State::InProgress);
+        assert_eq!(state("DONE", &keywords), State::Completed);
+        assert_eq!(state("CANCELED", &keywords), State::Completed);
+    }
+
+    #[test]
+    fn priority_conversion_test() {
+        for p in [Priority::Normal, Priority::Low, Priority::Medium, Priority::High] {
+            assert_eq!(priority_from_char(priority_to_char(p)), p);
+        }
+        assert_eq!(priority_to_char(Priority::Highest), Some('A'));
+        assert_eq!(priority_to_char(Priority::Lowest), Some('C'));
+        assert_eq!(priority_from_char(Some('D')), Priority::Normal);
+    }
+}
@@ -0,0 +1,205 @@
+// SPDX-License-Identifier: MIT
+
+// The org timestamps like <2025-01-05 Sun>, <2025-01-05 Sun 10:00 +1w> and [2025-01-05 Sun 10:00]
+
+use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+use tatuin_core::{task::DateTimeUtc, time};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timestamp {
+    pub date: NaiveDate,
+    pub time: Option<NaiveTime>,
+    // the repeater and the warning period like +1w or -2d, they're kept on changing the date
+    pub suffix: String,
+    // the inactive timestamps are in the square brackets
+    pub active: bool,
+}
+
+impl Timestamp {
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (active, inner) = if let Some(inner) = s.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+            (true, inner)
+        } else {
+            (false, s.strip_prefix('[')?.strip_suffix(']')?)
+        };
+
+        let mut words = inner.split_whitespace().peekable();
+        let date = NaiveDate::parse_from_str(words.next()?, DATE_FORMAT).ok()?;
+        // the day name depends on the locale of Emacs, it isn't checked
+        if words.peek().is_some_and(|w| w.chars().all(char::is_alphabetic)) {
+            words.next();
+        }
+        // the time range like 10:00-11:00 is cut to its start
+        let time = words
+            .peek()
+            .and_then(|w| NaiveTime::parse_from_str(w.split('-').next().unwrap_or_default(), TIME_FORMAT).ok());
+        if time.is_some() {
+            words.next();
+        }
+
+        Some(Self {
+            date,
+            time,
+            suffix: words.collect::<Vec<&str>>().join(" "),
+            active,
+        })
+    }
+
+    // The date without time is the midnight of UTC like the other providers have
+    pub fn to_datetime<Tz: TimeZone>(&self, tz: &Tz) -> DateTimeUtc {
+        let midnight = self.date.and_time(NaiveTime::MIN).and_utc();
+        match self.time {
+            Some(t) => tz
+                .from_local_datetime(&self.date.and_time(t))
+                .earliest()
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or(midnight),
+            None => midnight,
+        }
+    }
+
+    pub fn from_datetime<Tz: TimeZone>(dt: &DateTimeUtc, tz: &Tz, active: bool) -> Self {
+        let (date, time) = if time::is_date_only(dt) {
+            (dt.date_naive(), None)
+        } else {
+            let local = dt.with_timezone(tz).naive_local();
+            (local.date(), Some(local.time()))
+        };
+        Self {
+            date,
+            time,
+            suffix: String::new(),
+            active,
+        }
+    }
+
+    pub fn with_suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+}
+
+impl std::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![
+            self.date.format(DATE_FORMAT).to_string(),
+            self.date.format("%a").to_string(),
+        ];
+        if let Some(t) = self.time {
+            parts.push(t.format(TIME_FORMAT).to_string());
+        }
+        if !self.suffix.is_empty() {
+            parts.push(self.suffix.to_string());
+        }
+        let (open, close) = if self.active { ('<', '>') } else { ('[', ']') };
+        write!(f, "{open}{}{close}", parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, DATE_FORMAT).unwrap()
+    }
+
+    fn time(s: &str) -> Option<NaiveTime> {
+        NaiveTime::parse_from_str(s, TIME_FORMAT).ok()
+    }
+
+    #[test]
+    fn parse_test() {
+        struct Case<'a> {
+            name: &'a str,
+            s: &'a str,
+            expected: Option<Timestamp>,
+        }
+        let cases = [
+            Case {
+                name: "date",
+                s: "<2025-01-05 Sun>",
+                expected: Some(Timestamp {
+                    date: date("2025-01-05"),
+                    time: None,
+                    suffix: String::new(),
+                    active: true,
+                }),
+            },
+            Case {
+                name: "date with time and repeater",
+                s: "<2025-01-05 Sun 10:00 +1w -2d>",
+                expected: Some(Timestamp {
+                    date: date("2025-01-05"),
+                    time: time("10:00"),
+                    suffix: "+1w -2d".to_string(),
+                    active: true,
+                }),
+            },
+            Case {
+                name: "inactive with the time range and without the day name",
+                s: "[2025-01-05 10:00-11:30]",
+                expected: Some(Timestamp {
+                    date: date("2025-01-05"),
+                    time: time("10:00"),
+                    suffix: String::new(),
+                    active: false,
+                }),
+            },
+            Case {
+                name: "localized day name",
+                s: "<2025-01-05 Вс>",
+                expected: Some(Timestamp {
+                    date: date("2025-01-05"),
+                    time: None,
+                    suffix: String::new(),
+                    active: true,
+                }),
+            },
+            Case {
+                name: "wrong brackets",
+                s: "<2025-01-05 Sun]",
+                expected: None,
+            },
+            Case {
+                name: "wrong date",
+                s: "<tomorrow>",
+                expected: None,
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(Timestamp::parse(c.s), c.expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn to_string_test() {
+        for s in [
+            "<2025-01-05 Sun>",
+            "<2025-01-05 Sun 10:00 +1w>",
+            "[2025-01-05 Sun 10:00]",
+        ] {
+            assert_eq!(Timestamp::parse(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn datetime_test() {
+        let tz = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+
+        let ts = Timestamp::parse("<2025-01-05 Sun>").unwrap();
+        let dt = ts.to_datetime(&tz);
+        assert_eq!(dt, date("2025-01-05").and_time(NaiveTime::MIN).and_utc());
+        assert_eq!(Timestamp::from_datetime(&dt, &tz, true), ts);
+
+        let ts = Timestamp::parse("<2025-01-05 Sun 10:00>").unwrap();
+        let dt = ts.to_datetime(&tz);
+        assert_eq!(dt, date("2025-01-05").and_time(time("08:00").unwrap()).and_utc());
+        assert_eq!(Timestamp::from_datetime(&dt, &tz, true), ts);
+    }
+}
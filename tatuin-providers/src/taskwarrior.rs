@@ -0,0 +1,564 @@
+// SPDX-License-Identifier: MIT
+
+// The tasks of Taskwarrior are read and written by its command line tool (task export and task import),
+// so the data files of any Taskwarrior version are supported.
+
+mod client;
+mod project;
+mod task;
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    path::Path,
+};
+
+use async_trait::async_trait;
+use chrono::{Local, TimeZone};
+use serde_json::{Map, Value, json};
+use tatuin_core::{
+    filter,
+    intern::intern,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::{DateTimeUtc, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch, ValuePatch},
+};
+
+use crate::config::Config;
+
+pub const PROVIDER_NAME: &str = "Taskwarrior";
+
+const CHANGED_OUTSIDE_ERROR: &str = "The task has been changed outside";
+
+pub struct Provider {
+    cfg: Config,
+    c: client::Client,
+}
+
+impl Provider {
+    // The command is `task` from PATH by default, the data location is the one from taskrc by default
+    pub fn new(cfg: Config, command: Option<&str>, data_location: Option<&Path>) -> Self {
+        Self {
+            cfg,
+            c: client::Client::new(command, data_location.map(|p| p.to_path_buf())),
+        }
+    }
+
+    // The deleted tasks and the templates of the recurring ones are skipped
+    async fn tasks(&self) -> Result<Vec<task::Task>, ProviderError> {
+        Ok(self
+            .c
+            .export()
+            .await?
+            .into_iter()
+            .map(task::Task::new)
+            .filter(|t| ![task::STATUS_DELETED, task::STATUS_RECURRING].contains(&t.status()))
+            .collect())
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+// The project contains the tasks of its subprojects like Taskwarrior's project filter does
+fn is_in_project(t: &task::Task, project_id: &str) -> bool {
+    match t.project_name() {
+        None => project_id.is_empty(),
+        Some(p) => {
+            !project_id.is_empty()
+                && p.strip_prefix(project_id)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        }
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let name = self.name();
+        let paths = self
+            .tasks()
+            .await?
+            .iter()
+            .filter_map(|t| t.project_name())
+            .flat_map(project::with_parents)
+            .collect::<BTreeSet<String>>();
+
+        let mut result: Vec<Box<dyn ProjectTrait>> = vec![Box::new(project::Project::new(&name, None))];
+        for p in paths {
+            result.push(Box::new(project::Project::new(&name, Some(&p))));
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let name = intern(&self.name());
+        let project_id = project.map(|p| p.id());
+
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        for mut t in self.tasks().await? {
+            if let Some(id) = &project_id
+                && !is_in_project(&t, id)
+            {
+                continue;
+            }
+
+            t.set_provider(name.clone());
+            if f.accept(&t) {
+                result.push(Box::new(t));
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let mut raw = Map::new();
+        raw.insert("uuid".to_string(), json!(uuid::Uuid::new_v4().to_string()));
+        raw.insert("status".to_string(), json!(task::STATUS_PENDING));
+        raw.insert("entry".to_string(), json!(task::now_str()));
+        raw.insert("description".to_string(), json!(""));
+        if !project_id.is_empty() {
+            raw.insert("project".to_string(), json!(project_id));
+        }
+
+        apply_patch(&mut raw, tp, &Local);
+        self.c.import(&[raw]).await
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut errors = Vec::new();
+
+        // the patch is applied to the current task, so the fields changed outside tatuin aren't lost
+        let current = match self.tasks().await {
+            Ok(tasks) => tasks
+                .into_iter()
+                .map(|t| (t.id(), t))
+                .collect::<HashMap<String, task::Task>>(),
+            Err(e) => {
+                return patches
+                    .iter()
+                    .filter_map(|p| p.task.as_ref())
+                    .map(|t| PatchError {
+                        task: t.clone(),
+                        error: e.to_string(),
+                        kind: PatchErrorKind::Other,
+                    })
+                    .collect();
+            }
+        };
+
+        let mut changed = Vec::new();
+        let mut changed_tasks = Vec::new();
+        for p in patches {
+            let task = p.task.as_ref().unwrap();
+
+            let Some(t) = task.as_any().downcast_ref::<task::Task>() else {
+                errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME));
+                continue;
+            };
+
+            match current.get(&t.id()) {
+                Some(c) if c.modified() == t.modified() => {
+                    let mut raw = c.raw.clone();
+                    apply_patch(&mut raw, p, &Local);
+                    changed.push(raw);
+                    changed_tasks.push(task.clone());
+                }
+                _ => errors.push(PatchError {
+                    task: task.clone(),
+                    error: CHANGED_OUTSIDE_ERROR.to_string(),
+                    kind: PatchErrorKind::Other,
+                }),
+            }
+        }
+
+        if changed.is_empty() {
+            return errors;
+        }
+
+        if let Err(e) = self.c.import(&changed).await {
+            errors.extend(changed_tasks.into_iter().map(|t| PatchError {
+                task: t,
+                error: e.to_string(),
+                kind: PatchErrorKind::Other,
+            }));
+        }
+        errors
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let t = t
+            .as_any()
+            .downcast_ref::<task::Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
+
+        let Some(current) = self.tasks().await?.into_iter().find(|c| c.id() == t.id()) else {
+            return Err(ProviderError::NotFound(format!("the task {} is not found", t.id())));
+        };
+        if current.modified() != t.modified() {
+            return Err(ProviderError::Conflict(CHANGED_OUTSIDE_ERROR.to_string()));
+        }
+
+        let mut raw = current.raw;
+        raw.insert("status".to_string(), json!(task::STATUS_DELETED));
+        raw.insert("end".to_string(), json!(task::now_str()));
+        self.c.import(&[raw]).await
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        // do nothing, the tasks are exported on every request
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: true,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+fn set_or_remove(raw: &mut Map<String, Value>, key: &str, v: Option<Value>) {
+    match v {
+        Some(v) => raw.insert(key.to_string(), v),
+        None => raw.remove(key),
+    };
+}
+
+fn date_patch<Tz: TimeZone>(p: &ValuePatch<DatePatchItem>, tz: &Tz) -> Option<Value> {
+    let dt: Option<DateTimeUtc> = p.value().and_then(|d| d.into());
+    dt.map(|d| json!(task::date_to_str(&task::date_to(&d, tz))))
+}
+
+// Every line of the description is an annotation, the entry dates of the unchanged ones are kept
+fn annotations(current: Option<&Value>, description: &str) -> Option<Value> {
+    let entries = current
+        .and_then(|v| v.as_array())
+        .map(|a| {
+            a.iter()
+                .filter_map(|a| Some((a.get("description")?.as_str()?, a.get("entry")?.clone())))
+                .collect::<HashMap<&str, Value>>()
+        })
+        .unwrap_or_default();
+
+    let result = description
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let entry = entries.get(l).cloned().unwrap_or_else(|| json!(task::now_str()));
+            json!({ "entry": entry, "description": l })
+        })
+        .collect::<Vec<Value>>();
+
+    if result.is_empty() { None } else { Some(json!(result)) }
+}
+
+fn apply_patch<Tz: TimeZone>(raw: &mut Map<String, Value>, tp: &TaskPatch, tz: &Tz) {
+    if let Some(n) = tp.name.value() {
+        raw.insert("description".to_string(), json!(n));
+    }
+
+    if tp.description.is_set() {
+        let a = annotations(raw.get("annotations"), &tp.description.value().unwrap_or_default());
+        set_or_remove(raw, "annotations", a);
+    }
+
+    if tp.priority.is_set() {
+        let p = tp.priority.value().and_then(task::priority_to_str).map(|p| json!(p));
+        set_or_remove(raw, "priority", p);
+    }
+
+    if let Some(s) = tp.state.value() {
+        let is_completed = raw.get("status").and_then(|v| v.as_str()) == Some(task::STATUS_COMPLETED);
+        match s {
+            State::Completed => {
+                raw.insert("status".to_string(), json!(task::STATUS_COMPLETED));
+                raw.insert("end".to_string(), json!(task::now_str()));
+                raw.remove("start");
+            }
+            State::Uncompleted | State::InProgress => {
+                // the waiting task stays waiting
+                if is_completed {
+                    raw.insert("status".to_string(), json!(task::STATUS_PENDING));
+                    raw.remove("end");
+                }
+                if s == State::InProgress {
+                    raw.entry("start").or_insert_with(|| json!(task::now_str()));
+                } else {
+                    raw.remove("start");
+                }
+            }
+            State::Unknown(_) => {}
+        }
+    }
+
+    if tp.due.is_set() {
+        set_or_remove(raw, "due", date_patch(&tp.due, tz));
+    }
+
+    if tp.scheduled.is_set() {
+        set_or_remove(raw, "scheduled", date_patch(&tp.scheduled, tz));
+    }
+
+    if tp.labels.is_set() {
+        let tags = tp.labels.value().unwrap_or_default();
+        set_or_remove(raw, "tags", if tags.is_empty() { None } else { Some(json!(tags)) });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::Utc;
+    use tatuin_core::{filter::Filter, task::Priority};
+
+    fn raw(v: Value) -> Map<String, Value> {
+        v.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn apply_patch_test() {
+        let mut r = raw(json!({
+            "uuid": "1",
+            "description": "Call mom",
+            "status": "completed",
+            "end": "20250103T090000Z",
+            "priority": "L",
+            "tags": ["phone"],
+            "annotations": [{"entry": "20250101T100000Z", "description": "About the trip"}],
+            "udaestimate": "2h"
+        }));
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Call dad".to_string()),
+            description: ValuePatch::Value("About the trip\n\nAnd the dog".to_string()),
+            priority: ValuePatch::Value(Priority::Highest),
+            state: ValuePatch::Value(State::InProgress),
+            due: ValuePatch::Value(DatePatchItem::Custom(task::parse_date("20250105T000000Z").unwrap())),
+            labels: ValuePatch::Value(vec!["phone".to_string(), "family".to_string()]),
+            ..TaskPatch::default()
+        };
+        apply_patch(&mut r, &tp, &Utc);
+
+        let start = r.get("start").cloned().unwrap();
+        let dog_entry = r["annotations"][1]["entry"].clone();
+        assert_eq!(
+            Value::Object(r.clone()),
+            json!({
+                "uuid": "1",
+                "description": "Call dad",
+                "status": "pending",
+                "start": start,
+                "priority": "H",
+                "due": "20250105T000000Z",
+                "tags": ["phone", "family"],
+                "annotations": [
+                    {"entry": "20250101T100000Z", "description": "About the trip"},
+                    {"entry": dog_entry, "description": "And the dog"}
+                ],
+                "udaestimate": "2h"
+            })
+        );
+
+        let tp = TaskPatch {
+            description: ValuePatch::Empty,
+            priority: ValuePatch::Value(Priority::Normal),
+            state: ValuePatch::Value(State::Completed),
+            due: ValuePatch::Empty,
+            labels: ValuePatch::Empty,
+            ..TaskPatch::default()
+        };
+        apply_patch(&mut r, &tp, &Utc);
+        let end = r.get("end").cloned().unwrap();
+        assert_eq!(
+            Value::Object(r),
+            json!({
+                "uuid": "1",
+                "description": "Call dad",
+                "status": "completed",
+                "end": end,
+                "udaestimate": "2h"
+            })
+        );
+    }
+
+    #[test]
+    fn is_in_project_test() {
+        struct Case<'a> {
+            name: &'a str,
+            project: Option<&'a str>,
+            project_id: &'a str,
+            expected: bool,
+        }
+        let cases = [
+            Case {
+                name: "inbox",
+                project: None,
+                project_id: "",
+                expected: true,
+            },
+            Case {
+                name: "the task with a project isn't in the inbox",
+                project: Some("Home"),
+                project_id: "",
+                expected: false,
+            },
+            Case {
+                name: "same project",
+                project: Some("Home"),
+                project_id: "Home",
+                expected: true,
+            },
+            Case {
+                name: "subproject",
+                project: Some("Home.Garden"),
+                project_id: "Home",
+                expected: true,
+            },
+            Case {
+                name: "the project with the same prefix",
+                project: Some("Homework"),
+                project_id: "Home",
+                expected: false,
+            },
+        ];
+
+        for c in cases {
+            let mut r = raw(json!({"uuid": "1"}));
+            if let Some(p) = c.project {
+                r.insert("project".to_string(), json!(p));
+            }
+            assert_eq!(
+                is_in_project(&task::Task::new(r), c.project_id),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    // The fake task command exports the tasks.json file and saves the imported tasks to the imported.json one
+    #[cfg(unix)]
+    fn fake_command(dir: &Path) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join("task");
+        std::fs::write(
+            &path,
+            format!(
+                "#!/bin/sh\ncase \"$*\" in\n*\" export\") cat {0}/tasks.json ;;\n*\" import -\") cat > {0}/imported.json ;;\n*) exit 1 ;;\nesac\n",
+                dir.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn export_and_import() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let dir = temp_dir.path();
+        std::fs::write(
+            dir.join("tasks.json"),
+            json!([
+                {"uuid": "1", "description": "Call mom", "status": "pending", "project": "Home.Family", "modified": "20250101T100000Z"},
+                {"uuid": "2", "description": "Pay bills", "status": "completed", "project": "Home"},
+                {"uuid": "3", "description": "Old task", "status": "deleted"},
+                {"uuid": "4", "description": "Water plants", "status": "recurring"},
+                {"uuid": "5", "description": "Read a book", "status": "waiting"}
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let command = fake_command(dir);
+        let mut p = Provider::new(Config::new("test_app", "test_name"), command.to_str(), Some(dir));
+
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(
+            projects.iter().map(|p| p.id()).collect::<Vec<_>>(),
+            vec!["", "Home", "Home.Family"]
+        );
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id()).collect::<Vec<_>>(), vec!["1", "2", "5"]);
+
+        let home_tasks = TaskProviderTrait::list(&mut p, Some(projects[1].clone_boxed()), &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(home_tasks.len(), 2);
+
+        let tp = TaskPatch {
+            task: Some(tasks[0].clone_boxed().into()),
+            state: ValuePatch::Value(State::Completed),
+            ..TaskPatch::default()
+        };
+        assert!(p.update(&[tp]).await.is_empty());
+        let imported: Vec<Map<String, Value>> =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("imported.json")).unwrap()).unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0]["uuid"], "1");
+        assert_eq!(imported[0]["status"], "completed");
+        assert_eq!(imported[0]["project"], "Home.Family");
+
+        assert!(p.delete(tasks[2].as_ref()).await.is_ok());
+        let imported: Vec<Map<String, Value>> =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("imported.json")).unwrap()).unwrap();
+        assert_eq!(imported[0]["uuid"], "5");
+        assert_eq!(imported[0]["status"], "deleted");
+
+        // the task was modified after it had been loaded
+        std::fs::write(
+            dir.join("tasks.json"),
+            json!([{"uuid": "1", "description": "Call dad", "status": "pending", "modified": "20250102T100000Z"}])
+                .to_string(),
+        )
+        .unwrap();
+        let tp = TaskPatch {
+            task: Some(tasks[0].clone_boxed().into()),
+            name: ValuePatch::Value("Call grandma".to_string()),
+            ..TaskPatch::default()
+        };
+        let errors = p.update(&[tp]).await;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error, CHANGED_OUTSIDE_ERROR);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn failed_command() {
+        let mut p = Provider::new(Config::new("test_app", "test_name"), Some("/nonexistent/task"), None);
+        let r = TaskProviderTrait::list(&mut p, None, &Filter::full_filter()).await;
+        assert!(matches!(r, Err(ProviderError::Other(_))));
+    }
+}
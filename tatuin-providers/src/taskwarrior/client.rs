@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+
+use std::{path::PathBuf, process::Stdio};
+
+use serde_json::{Map, Value};
+use tatuin_core::provider::ProviderError;
+use tokio::{io::AsyncWriteExt, process::Command};
+
+const DEFAULT_COMMAND: &str = "task";
+
+// The overrides that make the output stable whatever the user's taskrc is
+const RC_OVERRIDES: &[&str] = &["rc.confirmation=off", "rc.verbose=nothing", "rc.json.array=on"];
+
+// The tasks are read and written by the task command, so the hooks and the sync settings of the user keep working
+pub struct Client {
+    command: String,
+    data_location: Option<PathBuf>,
+}
+
+impl Client {
+    pub fn new(command: Option<&str>, data_location: Option<PathBuf>) -> Self {
+        Self {
+            command: command.unwrap_or(DEFAULT_COMMAND).to_string(),
+            data_location,
+        }
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut c = Command::new(&self.command);
+        c.args(RC_OVERRIDES);
+        if let Some(p) = &self.data_location {
+            c.arg(format!("rc.data.location={}", p.display()));
+        }
+        c.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        c
+    }
+
+    fn check(&self, action: &str, output: std::process::Output) -> Result<Vec<u8>, ProviderError> {
+        if output.status.success() {
+            return Ok(output.stdout);
+        }
+        let e = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        tracing::error!(target:"taskwarrior_client", action=action, error=e, "Run the task command");
+        Err(ProviderError::Other(format!("{} {action}: {e}", self.command)))
+    }
+
+    fn spawn_error(&self, e: std::io::Error) -> ProviderError {
+        ProviderError::Other(format!("can't run {}: {e}", self.command))
+    }
+
+    // All the tasks as the raw objects, the fields that tatuin doesn't know (UDAs, annotations) are kept on writing
+    pub async fn export(&self) -> Result<Vec<Map<String, Value>>, ProviderError> {
+        let output = self
+            .command(&["export"])
+            .output()
+            .await
+            .map_err(|e| self.spawn_error(e))?;
+        let data = self.check("export", output)?;
+        serde_json::from_slice::<Vec<Map<String, Value>>>(&data).map_err(|e| {
+            tracing::error!(target:"taskwarrior_client", error=?e, "Parse the exported tasks");
+            ProviderError::Other(format!("wrong export of {}: {e}", self.command))
+        })
+    }
+
+    // The tasks with the known uuid are replaced, the others are created
+    pub async fn import(&self, tasks: &[Map<String, Value>]) -> Result<(), ProviderError> {
+        let data = serde_json::to_vec(tasks).map_err(|e| ProviderError::Other(e.to_string()))?;
+        let mut child = self
+            .command(&["import", "-"])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.spawn_error(e))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&data).await?;
+        }
+        let output = child.wait_with_output().await.map_err(|e| self.spawn_error(e))?;
+        self.check("import", output).map(|_| ())
+    }
+}
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: MIT
+
+use tatuin_core::project::Project as ProjectTrait;
+
+const INBOX_NAME: &str = "Inbox";
+const SEPARATOR: char = '.';
+
+#[derive(Clone)]
+pub struct Project {
+    provider: String,
+    // the full name like Home.Garden, the task without a project lives in the inbox
+    path: Option<String>,
+}
+
+impl Project {
+    pub fn new(provider: &str, path: Option<&str>) -> Self {
+        Self {
+            provider: provider.to_string(),
+            path: path.map(|s| s.to_string()),
+        }
+    }
+}
+
+// The project with its parents, Taskwarrior creates the parents implicitly
+pub fn with_parents(path: &str) -> Vec<String> {
+    path.match_indices(SEPARATOR)
+        .map(|(i, _)| path[..i].to_string())
+        .chain(std::iter::once(path.to_string()))
+        .collect()
+}
+
+impl std::fmt::Debug for Project {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Project id={} name={}",
+            ProjectTrait::id(self),
+            ProjectTrait::name(self)
+        )
+    }
+}
+
+impl ProjectTrait for Project {
+    fn id(&self) -> String {
+        self.path.clone().unwrap_or_default()
+    }
+
+    fn name(&self) -> String {
+        match &self.path {
+            Some(p) => p.rsplit(SEPARATOR).next().unwrap_or(p).to_string(),
+            None => INBOX_NAME.to_string(),
+        }
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn description(&self) -> String {
+        String::new()
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        self.path
+            .as_ref()
+            .and_then(|p| p.rsplit_once(SEPARATOR))
+            .map(|(parent, _)| parent.to_string())
+    }
+
+    fn is_inbox(&self) -> bool {
+        self.path.is_none()
+    }
+
+    fn is_favorite(&self) -> bool {
+        false
+    }
+
+    fn clone_boxed(&self) -> Box<dyn ProjectTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn project_test() {
+        let p = Project::new("tw", Some("Home.Garden.Roses"));
+        assert_eq!(p.id(), "Home.Garden.Roses");
+        assert_eq!(p.name(), "Roses");
+        assert_eq!(p.parent_id(), Some("Home.Garden".to_string()));
+        assert!(!p.is_inbox());
+
+        let p = Project::new("tw", Some("Home"));
+        assert_eq!(p.name(), "Home");
+        assert_eq!(p.parent_id(), None);
+
+        let p = Project::new("tw", None);
+        assert_eq!(p.id(), "");
+        assert_eq!(p.name(), "Inbox");
+        assert!(p.is_inbox());
+    }
+
+    #[test]
+    fn with_parents_test() {
+        assert_eq!(
+            with_parents("Home.Garden.Roses"),
+            vec!["Home", "Home.Garden", "Home.Garden.Roses"]
+        );
+        assert_eq!(with_parents("Work"), vec!["Work"]);
+    }
+}
@@ -0,0 +1,314 @@
+// SPDX-License-Identifier: MIT
+
+use std::any::Any;
+
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde_json::{Map, Value};
+use tatuin_core::{
+    RichString,
+    intern::IStr,
+    project::Project as ProjectTrait,
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task_patch::DatePatchItem,
+    time,
+};
+
+use super::project::Project;
+
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_COMPLETED: &str = "completed";
+pub const STATUS_DELETED: &str = "deleted";
+// the template of the recurring tasks, its instances are the pending ones
+pub const STATUS_RECURRING: &str = "recurring";
+
+pub fn parse_date(s: &str) -> Option<DateTimeUtc> {
+    NaiveDateTime::parse_from_str(s, DATE_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+pub fn date_to_str(dt: &DateTimeUtc) -> String {
+    dt.format(DATE_FORMAT).to_string()
+}
+
+pub fn now_str() -> String {
+    date_to_str(&Utc::now())
+}
+
+// Taskwarrior keeps the dates as the local midnight, tatuin shows the midnight of UTC as the date without time
+pub fn date_from<Tz: TimeZone>(dt: &DateTimeUtc, tz: &Tz) -> DateTimeUtc {
+    let local = dt.with_timezone(tz);
+    if local.time() == NaiveTime::MIN {
+        local.date_naive().and_time(NaiveTime::MIN).and_utc()
+    } else {
+        *dt
+    }
+}
+
+pub fn date_to<Tz: TimeZone>(dt: &DateTimeUtc, tz: &Tz) -> DateTimeUtc {
+    if !time::is_date_only(dt) {
+        return *dt;
+    }
+    tz.from_local_datetime(&dt.date_naive().and_time(NaiveTime::MIN))
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or(*dt)
+}
+
+// Taskwarrior has three priorities, the highest and the lowest ones of tatuin are merged into them
+pub fn priority_from_str(s: Option<&str>) -> Priority {
+    match s {
+        Some("H") => Priority::High,
+        Some("M") => Priority::Medium,
+        Some("L") => Priority::Low,
+        _ => Priority::Normal,
+    }
+}
+
+pub fn priority_to_str(p: Priority) -> Option<&'static str> {
+    match p {
+        Priority::Highest | Priority::High => Some("H"),
+        Priority::Medium => Some("M"),
+        Priority::Low | Priority::Lowest => Some("L"),
+        Priority::Normal => None,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Task {
+    pub provider: IStr,
+    // the exported object, it's imported back with the changed fields
+    pub raw: Map<String, Value>,
+}
+
+impl Task {
+    pub fn new(raw: Map<String, Value>) -> Self {
+        Self {
+            provider: IStr::default(),
+            raw,
+        }
+    }
+
+    pub fn set_provider(&mut self, name: IStr) {
+        self.provider = name;
+    }
+
+    fn str(&self, key: &str) -> Option<&str> {
+        self.raw.get(key).and_then(|v| v.as_str())
+    }
+
+    fn date(&self, key: &str) -> Option<DateTimeUtc> {
+        self.str(key).and_then(parse_date)
+    }
+
+    pub fn status(&self) -> &str {
+        self.str("status").unwrap_or(STATUS_PENDING)
+    }
+
+    pub fn project_name(&self) -> Option<&str> {
+        self.str("project").filter(|p| !p.is_empty())
+    }
+
+    pub fn modified(&self) -> Option<&str> {
+        self.str("modified")
+    }
+
+    pub fn annotations(&self) -> Vec<String> {
+        self.raw
+            .get("annotations")
+            .and_then(|v| v.as_array())
+            .map(|a| {
+                a.iter()
+                    .filter_map(|a| a.get("description").and_then(|d| d.as_str()))
+                    .map(|d| d.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        self.str("uuid").unwrap_or_default().to_string()
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(self.str("description").unwrap_or_default())
+    }
+
+    fn description(&self) -> Option<RichString> {
+        let a = self.annotations();
+        if a.is_empty() {
+            None
+        } else {
+            Some(RichString::new(&a.join("\n")))
+        }
+    }
+
+    fn priority(&self) -> Priority {
+        priority_from_str(self.str("priority"))
+    }
+
+    fn state(&self) -> State {
+        match self.status() {
+            STATUS_COMPLETED => State::Completed,
+            // the started task is the one in progress
+            _ if self.raw.contains_key("start") => State::InProgress,
+            _ => State::Uncompleted,
+        }
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        self.date("entry")
+    }
+
+    fn updated_at(&self) -> Option<DateTimeUtc> {
+        self.date("modified")
+    }
+
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        if self.status() == STATUS_COMPLETED {
+            self.date("end")
+        } else {
+            None
+        }
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        self.date("due").map(|d| date_from(&d, &Local))
+    }
+
+    fn scheduled(&self) -> Option<DateTimeUtc> {
+        self.date("scheduled").map(|d| date_from(&d, &Local))
+    }
+
+    fn place(&self) -> String {
+        match self.raw.get("id").and_then(|v| v.as_u64()).filter(|id| *id != 0) {
+            Some(id) => format!("task {id}"),
+            None => format!("task {}", self.id().chars().take(8).collect::<String>()),
+        }
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.raw
+            .get("tags")
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().filter_map(|t| t.as_str()).map(|t| t.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(Box::new(Project::new(&self.provider, self.project_name())))
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: true,
+            is_removable: true,
+            available_states: vec![State::Uncompleted, State::InProgress, State::Completed],
+            available_priorities: vec![Priority::Normal, Priority::Low, Priority::Medium, Priority::High],
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: DatePatchItem::values(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    fn task(v: Value) -> Task {
+        Task::new(v.as_object().unwrap().clone())
+    }
+
+    #[test]
+    fn task_test() {
+        let t = task(json!({
+            "id": 3,
+            "uuid": "9d1a3c5e-8b0f-4a52-a1f4-7c43f0e5d3b1",
+            "description": "Call mom",
+            "status": "pending",
+            "entry": "20250101T100000Z",
+            "modified": "20250102T100000Z",
+            "due": "20250105T220000Z",
+            "project": "Home.Family",
+            "tags": ["phone", "next"],
+            "priority": "H",
+            "annotations": [
+                {"entry": "20250101T100000Z", "description": "About the trip"},
+                {"entry": "20250101T110000Z", "description": "And the dog"}
+            ],
+            "urgency": 12.5
+        }));
+        assert_eq!(t.id(), "9d1a3c5e-8b0f-4a52-a1f4-7c43f0e5d3b1");
+        assert_eq!(t.name().raw(), "Call mom");
+        assert_eq!(t.description().unwrap().raw(), "About the trip\nAnd the dog");
+        assert_eq!(t.priority(), Priority::High);
+        assert_eq!(t.state(), State::Uncompleted);
+        assert_eq!(t.created_at(), parse_date("20250101T100000Z"));
+        assert_eq!(t.date("due"), parse_date("20250105T220000Z"));
+        assert_eq!(t.completed_at(), None);
+        assert_eq!(t.labels(), vec!["phone", "next"]);
+        assert_eq!(t.place(), "task 3");
+        assert_eq!(t.project().unwrap().id(), "Home.Family");
+
+        let t = task(json!({
+            "id": 0,
+            "uuid": "0b7e14c2-2f58-4f0e-9d1c-6a7d0c2b9e44",
+            "description": "Pay bills",
+            "status": "completed",
+            "end": "20250103T090000Z"
+        }));
+        assert_eq!(t.state(), State::Completed);
+        assert_eq!(t.completed_at(), parse_date("20250103T090000Z"));
+        assert_eq!(t.priority(), Priority::Normal);
+        assert!(t.description().is_none());
+        assert_eq!(t.place(), "task 0b7e14c2");
+        assert!(t.project().unwrap().is_inbox());
+
+        let t = task(json!({"uuid": "1", "description": "Write the report", "start": "20250103T090000Z"}));
+        assert_eq!(t.state(), State::InProgress);
+    }
+
+    #[test]
+    fn priority_conversion_test() {
+        for p in [Priority::Normal, Priority::Low, Priority::Medium, Priority::High] {
+            assert_eq!(priority_from_str(priority_to_str(p)), p);
+        }
+        assert_eq!(priority_to_str(Priority::Highest), Some("H"));
+        assert_eq!(priority_to_str(Priority::Lowest), Some("L"));
+        assert_eq!(priority_from_str(Some("X")), Priority::Normal);
+    }
+
+    #[test]
+    fn date_test() {
+        let dt = parse_date("20250105T220000Z").unwrap();
+        assert_eq!(date_to_str(&dt), "20250105T220000Z");
+        assert_eq!(parse_date("2025-01-05"), None);
+
+        let tz = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let midnight = parse_date("20250105T220000Z").unwrap();
+        let date = parse_date("20250106T000000Z").unwrap();
+        assert_eq!(date_from(&midnight, &tz), date);
+        assert_eq!(date_to(&date, &tz), midnight);
+
+        let dt = parse_date("20250105T130000Z").unwrap();
+        assert_eq!(date_from(&dt, &tz), dt);
+        assert_eq!(date_to(&dt, &tz), dt);
+    }
+}
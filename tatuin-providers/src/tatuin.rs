@@ -13,9 +13,10 @@ use task::Task;
 use tatuin_core::{
     StringError, filter,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
-    task::{Priority, Task as TaskTrait},
-    task_patch::{DatePatchItem, PatchError, TaskPatch},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::{Priority, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
+    time,
 };
 
 use crate::config::Config;
@@ -49,7 +50,7 @@ impl std::fmt::Debug for Provider {
 
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
         self.c
             .projects(self.cfg.name().as_str())
             .await
@@ -67,7 +68,7 @@ impl TaskProviderTrait for Provider {
         &mut self,
         project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
         let project_id = if let Some(p) = project {
             Some(parse_uuid(p.id().as_str())?)
         } else {
@@ -92,7 +93,7 @@ impl TaskProviderTrait for Provider {
             .collect::<Vec<Box<dyn TaskTrait>>>())
     }
 
-    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), StringError> {
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
         let mut t = task::Task::default();
         t.id = uuid::Uuid::new_v4();
         t.name = tp.name.value().unwrap();
@@ -100,6 +101,8 @@ impl TaskProviderTrait for Provider {
         t.due = tp.due.value().unwrap_or(DatePatchItem::NoDate).into();
         t.priority = tp.priority.value().unwrap_or(Priority::Normal);
         t.project_id = parse_uuid(project_id)?;
+        t.parent_id = tp.parent.value().map(|id| parse_uuid(&id)).transpose()?;
+        t.url = tp.url.value();
         t.created_at = Utc::now();
         t.updated_at = Utc::now();
         self.c.create_task(t).await.map_err(|e| {
@@ -109,12 +112,58 @@ impl TaskProviderTrait for Provider {
     }
 
     async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
-        let tasks = patches.iter().map(task_patch_to_task).collect::<Vec<Task>>();
-        self.c.patch_tasks(&tasks).await
+        let mut tasks = Vec::new();
+        let mut completed = Vec::new();
+        let mut errors = Vec::new();
+        for tp in patches {
+            let Some(task) = tp.task.as_ref() else {
+                continue;
+            };
+            match task.as_any().downcast_ref::<Task>() {
+                Some(t) => {
+                    let new_task = task_patch_to_task(t, tp);
+                    if t.state != State::Completed && new_task.state == State::Completed {
+                        completed.push(new_task.clone());
+                    }
+                    tasks.push(new_task)
+                }
+                None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
+            }
+        }
+        errors.extend(self.c.patch_tasks(&tasks).await);
+
+        // the next occurrences of the completed recurring tasks are created
+        let (today, now) = (time::today(), Utc::now());
+        for t in completed {
+            if errors.iter().any(|e| e.task.id() == t.id()) {
+                continue;
+            }
+            if let Some(next) = t.next_occurrence(&today, &now)
+                && let Err(e) = self.c.create_task(next).await
+            {
+                tracing::error!(error=?e, task_id=t.id(), "Create the next occurrence of the recurring task");
+                errors.push(PatchError {
+                    task: t.clone_boxed().into(),
+                    error: format!("The next occurrence isn't created: {e}"),
+                    kind: PatchErrorKind::Other,
+                });
+            }
+        }
+        errors
+    }
+
+    async fn set_order(&mut self, ids: &[String]) -> Result<(), ProviderError> {
+        self.c.set_order(ids).await.map_err(|e| {
+            tracing::error!(error=?e, "Save the order of the tasks into database");
+            e.into()
+        })
     }
 
-    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), StringError> {
-        let t = t.as_any().downcast_ref::<Task>().expect("Wrong casting");
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let t = t
+            .as_any()
+            .downcast_ref::<Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
         self.c.delete_task(t).await.map_err(|e| {
             tracing::error!(error=?e, "Delete the task from database");
             e.into()
@@ -137,19 +186,20 @@ impl ProviderTrait for Provider {
     }
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities { create_task: true }
+        Capabilities {
+            create_task: true,
+            labels: false,
+            subtasks: true,
+            manual_order: true,
+            assignees: false,
+            queries: false,
+            url: true,
+        }
     }
 }
 
-fn task_patch_to_task(tp: &TaskPatch) -> Task {
-    let mut t = tp
-        .task
-        .as_ref()
-        .expect("Task in patch should be exist")
-        .as_any()
-        .downcast_ref::<Task>()
-        .expect("The task should have right type")
-        .clone();
+fn task_patch_to_task(t: &Task, tp: &TaskPatch) -> Task {
+    let mut t = t.clone();
 
     if let Some(n) = &tp.name.value() {
         t.name = n.clone();
@@ -272,6 +322,9 @@ mod test {
             } else {
                 ValuePatch::NotSet
             },
+            labels: ValuePatch::NotSet,
+            parent: ValuePatch::NotSet,
+            url: ValuePatch::NotSet,
         }
     }
 
@@ -347,13 +400,16 @@ mod test {
         let complete_patches = tasks
             .iter()
             .map(|t| TaskPatch {
-                task: Some(t.clone_boxed()),
+                task: Some(t.clone_boxed().into()),
                 name: ValuePatch::NotSet,
                 description: ValuePatch::NotSet,
                 due: ValuePatch::NotSet,
                 scheduled: ValuePatch::NotSet,
                 priority: ValuePatch::NotSet,
                 state: ValuePatch::Value(State::Completed),
+                labels: ValuePatch::NotSet,
+                parent: ValuePatch::NotSet,
+                url: ValuePatch::NotSet,
             })
             .collect::<Vec<TaskPatch>>();
         let patch_errors = p.update(&complete_patches).await;
@@ -377,4 +433,93 @@ mod test {
             );
         }
     }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn complete_recurring_task_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+
+        let p: &mut dyn ProviderTrait = &mut Provider::new(config(temp_dir.path().to_path_buf())).unwrap();
+        let project = &ProjectProviderTrait::list(p).await.unwrap()[0];
+
+        let due = chrono::NaiveDate::from_ymd_opt(2026, 1, 31)
+            .unwrap()
+            .and_time(chrono::NaiveTime::MIN)
+            .and_utc();
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Pay rent 🔁 every month".to_string()),
+            due: ValuePatch::Value(DatePatchItem::Custom(due)),
+            ..generate_task_patch(1)
+        };
+        p.create(project.id().as_str(), &tp).await.unwrap();
+
+        let tasks = TaskProviderTrait::list(p, None, &Filter::full_filter()).await.unwrap();
+        assert_eq!(
+            tasks[0].recurrence().map(|r| r.to_string()),
+            Some("every month".to_string())
+        );
+        let complete_patch = TaskPatch {
+            task: Some(tasks[0].clone_boxed().into()),
+            name: ValuePatch::NotSet,
+            state: ValuePatch::Value(State::Completed),
+            ..generate_task_patch(1)
+        };
+        assert!(p.update(&[complete_patch]).await.is_empty());
+
+        let tasks = TaskProviderTrait::list(p, None, &Filter::full_filter()).await.unwrap();
+        assert_eq!(tasks.len(), 2);
+        let next = tasks.iter().find(|t| t.state() == State::Uncompleted).unwrap();
+        assert_eq!(next.name().raw(), "Pay rent 🔁 every month");
+        assert_eq!(
+            next.due().map(|d| d.date_naive()),
+            chrono::NaiveDate::from_ymd_opt(2026, 2, 28)
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn set_order_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+
+        let p: &mut dyn ProviderTrait = &mut Provider::new(config(temp_dir.path().to_path_buf())).unwrap();
+        let project = &ProjectProviderTrait::list(p).await.unwrap()[0];
+        generate_items(p, 3, project.id().as_str()).await.unwrap();
+
+        let tasks = TaskProviderTrait::list(p, None, &Filter::full_filter()).await.unwrap();
+        assert!(tasks.iter().all(|t| t.order() == Some(i64::MAX)));
+
+        let mut ids = tasks.iter().map(|t| t.id()).collect::<Vec<String>>();
+        ids.reverse();
+        p.set_order(&ids).await.unwrap();
+
+        let mut tasks = TaskProviderTrait::list(p, None, &Filter::full_filter()).await.unwrap();
+        tasks.sort_by_key(|t| t.order());
+        assert_eq!(tasks.iter().map(|t| t.id()).collect::<Vec<String>>(), ids);
+        assert_eq!(
+            tasks.iter().map(|t| t.order()).collect::<Vec<Option<i64>>>(),
+            vec![Some(0), Some(1), Some(2)]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn create_subtask_test() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+
+        let p: &mut dyn ProviderTrait = &mut Provider::new(config(temp_dir.path().to_path_buf())).unwrap();
+        let project = &ProjectProviderTrait::list(p).await.unwrap()[0];
+        p.create(project.id().as_str(), &generate_task_patch(1)).await.unwrap();
+        let parent = TaskProviderTrait::list(p, None, &Filter::full_filter()).await.unwrap()[0].id();
+
+        let tp = TaskPatch {
+            parent: ValuePatch::Value(parent.clone()),
+            url: ValuePatch::NotSet,
+            ..generate_task_patch(2)
+        };
+        p.create(project.id().as_str(), &tp).await.unwrap();
+
+        let tasks = TaskProviderTrait::list(p, None, &Filter::full_filter()).await.unwrap();
+        let subtask = tasks.iter().find(|t| t.id() != parent).unwrap();
+        assert_eq!(subtask.parent_id(), Some(parent));
+    }
 }
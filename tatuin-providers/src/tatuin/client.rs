@@ -4,12 +4,13 @@ use redb::{Database, ReadableDatabase, ReadableTable, ReadableTableMetadata, Tab
 use std::{
     error::Error,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 use tatuin_core::{
     filter::{Filter, FilterState},
     project::Project as ProjectTrait,
     task::{State, Task as TaskTrait, due_group},
-    task_patch::PatchError,
+    task_patch::{PatchError, PatchErrorKind},
 };
 
 use super::{
@@ -72,6 +73,14 @@ impl Client {
         }
     }
 
+    pub async fn set_order(&self, ids: &[String]) -> Result<(), Box<dyn Error>> {
+        let db = Database::create(self.path.join(DB_FILE_NAME))?;
+        let ids = ids.to_vec();
+        tokio::task::spawn_blocking(move || set_order(&db, &ids))
+            .await?
+            .map_err(|e| e as Box<dyn Error>)
+    }
+
     pub async fn delete_task(&self, t: &Task) -> Result<(), Box<dyn Error>> {
         let db = Database::create(self.path.join(DB_FILE_NAME))?;
         let t = t.clone();
@@ -158,6 +167,22 @@ fn create_task(db: &Database, t: Task) -> Result<(), SyncedError> {
     Ok(())
 }
 
+fn set_order(db: &Database, ids: &[String]) -> Result<(), SyncedError> {
+    let tx = db.begin_write()?;
+    for td in [TASKS_TABLE, COMPLETED_TASKS_TABLE] {
+        let mut table = tx.open_table(td)?;
+        for (i, id) in ids.iter().enumerate() {
+            let t = table.get(id.as_str())?.map(|v| v.value());
+            if let Some(mut t) = t {
+                t.order = Some(i as i64);
+                table.insert(id.as_str(), t)?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
 fn delete_task(db: &Database, t: &Task) -> Result<(), SyncedError> {
     let tx = db.begin_write()?;
     {
@@ -179,8 +204,9 @@ fn fill_global_error(errors: Vec<PatchError>, tasks: &[Task], error: &str) -> Ve
         tasks
             .iter()
             .map(|t| PatchError {
-                task: t.clone_boxed(),
+                task: Arc::new(t.clone()),
                 error: error.to_string(),
+                kind: PatchErrorKind::Other,
             })
             .collect::<Vec<PatchError>>(),
     ]
@@ -226,8 +252,9 @@ fn patch_tasks(db: &Database, tasks: &[Task]) -> Vec<PatchError> {
             if let Err(e) = result {
                 tracing::error!(error=?e, id=id, "Add the task to database");
                 errors.push(PatchError {
-                    task: t.clone_boxed(),
+                    task: Arc::new(t.clone()),
                     error: e.to_string(),
+                    kind: PatchErrorKind::Other,
                 });
             }
         }
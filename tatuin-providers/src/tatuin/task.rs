@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 use tatuin_core::{
     RichString,
     project::Project as ProjectTrait,
-    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task::{DateTimeUtc, PatchPolicy, Priority, Recurrence, State, Task as TaskTrait},
     task_patch::DatePatchItem,
 };
 
@@ -23,6 +23,13 @@ pub struct Task {
     pub completed_at: Option<DateTimeUtc>,
     pub due: Option<DateTimeUtc>,
     pub project_id: uuid::Uuid,
+    // the manual order, the tasks without it go last
+    #[serde(default)]
+    pub order: Option<i64>,
+    #[serde(default)]
+    pub parent_id: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub url: Option<String>,
 
     #[serde(skip_serializing, skip_deserializing)]
     provider: String,
@@ -38,6 +45,20 @@ impl Task {
     pub fn set_project(&mut self, p: Project) {
         self.project = Some(p)
     }
+
+    // The uncompleted copy of the recurring task that is due on the next date
+    pub fn next_occurrence(&self, today: &DateTimeUtc, now: &DateTimeUtc) -> Option<Task> {
+        let r = self.recurrence()?;
+        let mut t = self.clone();
+        t.id = uuid::Uuid::new_v4();
+        t.state = State::Uncompleted;
+        t.due = Some(r.next_due(&self.due, today));
+        t.created_at = *now;
+        t.updated_at = *now;
+        t.completed_at = None;
+        t.order = None;
+        Some(t)
+    }
 }
 
 impl TaskTrait for Task {
@@ -45,10 +66,22 @@ impl TaskTrait for Task {
         self.id.to_string()
     }
 
+    fn order(&self) -> Option<i64> {
+        Some(self.order.unwrap_or(i64::MAX))
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        self.parent_id.map(|u| u.to_string())
+    }
+
     fn name(&self) -> RichString {
         RichString::new(&self.name)
     }
 
+    fn url(&self) -> String {
+        self.url.clone().unwrap_or_default()
+    }
+
     fn description(&self) -> Option<RichString> {
         self.description.as_ref().map(|s| RichString::new(s))
     }
@@ -81,6 +114,11 @@ impl TaskTrait for Task {
         "db".to_string()
     }
 
+    // The recurrence is kept in the name like "Pay rent 🔁 every month", so it's edited with the name
+    fn recurrence(&self) -> Option<Recurrence> {
+        Recurrence::extract(&self.name).1
+    }
+
     fn labels(&self) -> Vec<String> {
         self.labels.clone()
     }
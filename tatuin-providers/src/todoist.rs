@@ -4,13 +4,14 @@ pub mod client;
 mod project;
 mod task;
 
-use std::{cmp::Ordering, error::Error, fmt::Debug};
+use std::{cmp::Ordering, collections::HashMap, error::Error, fmt::Debug};
 use tatuin_core::{
-    StringError, filter,
+    filter,
+    http::SharedHttpClient,
     project::Project as ProjectTrait,
-    provider::{Capabilities, ProjectProviderTrait, ProviderTrait, TaskProviderTrait},
-    task::{Priority, State, Task as TaskTrait},
-    task_patch::{DatePatchItem, PatchError, TaskPatch},
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, SyncState, TaskProviderTrait},
+    task::{DateTimeUtc, Priority, State, Task as TaskTrait},
+    task_patch::{DatePatchItem, PatchError, PatchErrorKind, TaskPatch},
 };
 
 use async_trait::async_trait;
@@ -24,8 +25,13 @@ pub struct Provider {
     c: client::Client,
     projects: Vec<project::Project>,
     tasks: Vec<task::Task>,
+    // the id of the token owner
+    me: Option<String>,
+    // the collaborator names by their ids of the shared projects
+    collaborators: HashMap<String, HashMap<String, String>>,
     last_filter: Option<filter::Filter>,
     last_project: Option<Box<dyn ProjectTrait>>,
+    last_sync: Option<DateTimeUtc>,
 }
 
 impl Provider {
@@ -35,11 +41,19 @@ impl Provider {
             c: client::Client::new(api_key),
             projects: Vec::new(),
             tasks: Vec::new(),
+            me: None,
+            collaborators: HashMap::new(),
             last_filter: None,
             last_project: None,
+            last_sync: None,
         }
     }
 
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.c = self.c.with_http_client(http);
+        self
+    }
+
     async fn load_projects(&mut self) -> Result<(), Box<dyn Error>> {
         if self.projects.is_empty() {
             self.projects = self.c.projects().await?;
@@ -50,6 +64,51 @@ impl Provider {
         Ok(())
     }
 
+    async fn load_me(&mut self) {
+        if self.me.is_none() {
+            // the tasks are shown without the assignment info if the token can't read the user
+            match self.c.user().await {
+                Ok(u) => self.me = Some(u.id),
+                Err(e) => tracing::warn!(error=?e, "Get the user"),
+            }
+        }
+    }
+
+    async fn collaborator_name(&mut self, project_id: &str, uid: &str) -> Option<String> {
+        if !self.collaborators.contains_key(project_id) {
+            let collaborators = match self.c.collaborators(project_id).await {
+                Ok(c) => c.into_iter().map(|c| (c.id, c.name)).collect(),
+                Err(e) => {
+                    tracing::warn!(error=?e, project_id=project_id, "Get the collaborators");
+                    HashMap::new()
+                }
+            };
+            self.collaborators.insert(project_id.to_string(), collaborators);
+        }
+        self.collaborators.get(project_id)?.get(uid).cloned()
+    }
+
+    // Fills the projects and the assignees of the loaded tasks
+    async fn boxed_tasks(&mut self, tasks: Vec<task::Task>) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+
+        self.load_me().await;
+        for mut t in tasks {
+            let project = self.project_by_id(t.project_id.as_str()).await?;
+            if let Some(uid) = &t.responsible_uid
+                && project.is_shared
+            {
+                t.assignee_name = self.collaborator_name(project.id.as_str(), uid).await;
+            }
+            t.project = Some(project);
+            t.provider = Some(self.name());
+            t.me = self.me.clone();
+            result.push(Box::new(t));
+        }
+
+        Ok(result)
+    }
+
     pub async fn project_by_id(&mut self, id: &str) -> Result<project::Project, Box<dyn Error>> {
         self.load_projects().await?;
         let project = self.projects.iter().find(|p| p.id() == id);
@@ -71,7 +130,7 @@ impl Debug for Provider {
 
 #[async_trait]
 impl ProjectProviderTrait for Provider {
-    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, StringError> {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
         self.load_projects().await?;
         let mut result: Vec<Box<dyn ProjectTrait>> = Vec::new();
         for p in &self.projects {
@@ -89,7 +148,7 @@ impl TaskProviderTrait for Provider {
         &mut self,
         project: Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
-    ) -> Result<Vec<Box<dyn TaskTrait>>, StringError> {
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
         let mut should_clear = false;
         if let Some(last_filter) = self.last_filter.as_mut() {
             should_clear = last_filter != f;
@@ -135,26 +194,27 @@ impl TaskProviderTrait for Provider {
                 }
             }
             self.last_project = project;
+            self.last_sync = Some(chrono::Utc::now());
         }
 
-        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
-
-        for t in &mut self.tasks.to_vec() {
-            t.project = Some(self.project_by_id(t.project_id.as_str()).await?);
-            t.provider = Some(self.name());
-            result.push(Box::new(t.clone()));
-        }
+        let result = self.boxed_tasks(self.tasks.to_vec()).await?;
 
         self.last_filter = Some(f.clone());
 
         Ok(result)
     }
 
-    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), StringError> {
+    async fn query(&mut self, query: &str) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let tasks = self.c.tasks_by_query(query).await?;
+        self.boxed_tasks(tasks).await
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
         let mut due_custom_dt = String::new();
 
         let name = tp.name.value().unwrap();
         let description = tp.description.value();
+        let parent_id = tp.parent.value();
         let r = client::CreateTaskRequest {
             content: name.as_str(),
             description: description.as_deref(),
@@ -171,6 +231,7 @@ impl TaskProviderTrait for Provider {
                 }
             }),
             priority: tp.priority.value().map(|p| task::priority_to_int(&p)),
+            parent_id: parent_id.as_deref(),
         };
         self.c.create_task(&r).await.map_err(|e| e.into())
     }
@@ -186,19 +247,22 @@ impl TaskProviderTrait for Provider {
                     State::Completed => match self.c.close_task(task.id().as_str()).await {
                         Ok(_) => self.tasks.clear(),
                         Err(e) => errors.push(PatchError {
-                            task: task.clone_boxed(),
+                            task: task.clone(),
                             error: e.to_string(),
+                            kind: PatchErrorKind::of(e.as_ref()),
                         }),
                     },
                     State::InProgress | State::Unknown(_) => errors.push(PatchError {
-                        task: task.clone_boxed(),
+                        task: task.clone(),
                         error: format!("The state {state} is unsupported"),
+                        kind: PatchErrorKind::Other,
                     }),
                     State::Uncompleted => match self.c.reopen_task(task.id().as_str()).await {
                         Ok(_) => self.tasks.clear(),
                         Err(e) => errors.push(PatchError {
-                            task: task.clone_boxed(),
+                            task: task.clone(),
                             error: e.to_string(),
+                            kind: PatchErrorKind::of(e.as_ref()),
                         }),
                     },
                 }
@@ -228,8 +292,9 @@ impl TaskProviderTrait for Provider {
                 match self.c.update_task(task.id().as_str(), &r).await {
                     Ok(_) => self.tasks.clear(),
                     Err(e) => errors.push(PatchError {
-                        task: task.clone_boxed(),
+                        task: task.clone(),
                         error: e.to_string(),
+                        kind: PatchErrorKind::of(e.as_ref()),
                     }),
                 }
             }
@@ -238,9 +303,65 @@ impl TaskProviderTrait for Provider {
         errors
     }
 
-    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), StringError> {
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
         self.c.delete_task(t.id().as_str()).await.map_err(|e| e.into())
     }
+
+    async fn set_order(&mut self, ids: &[String]) -> Result<(), ProviderError> {
+        let mut tasks = Vec::new();
+        for id in ids {
+            match self.tasks.iter().find(|t| t.id == *id) {
+                Some(t) => tasks.push(t),
+                None => return Err(ProviderError::other(format!("The task {id} isn't loaded").as_str())),
+            }
+        }
+
+        let orders = reordered(&tasks);
+        if orders.is_empty() {
+            return Ok(());
+        }
+        self.c.reorder_tasks(&orders).await?;
+        self.tasks.clear();
+        Ok(())
+    }
+}
+
+// Returns the changed child orders of the tasks listed in the new order.
+// The child order is kept per section and parent in Todoist, so the tasks exchange
+// their order values only with the tasks of the same section and parent.
+fn reordered(tasks: &[&task::Task]) -> Vec<(String, i32)> {
+    let mut result = Vec::new();
+    let mut groups: Vec<(&Option<String>, &Option<String>)> = Vec::new();
+    for t in tasks {
+        let key = (&t.section_id, &t.parent_id);
+        if !groups.contains(&key) {
+            groups.push(key);
+        }
+    }
+
+    for key in groups {
+        let members = tasks
+            .iter()
+            .filter(|t| (&t.section_id, &t.parent_id) == key)
+            .collect::<Vec<_>>();
+        let mut orders = members
+            .iter()
+            .map(|t| t.child_order.unwrap_or_default())
+            .collect::<Vec<i32>>();
+        orders.sort();
+        // the equal orders are spread to make the new order strict
+        for i in 1..orders.len() {
+            orders[i] = orders[i].max(orders[i - 1] + 1);
+        }
+
+        for (t, order) in members.iter().zip(orders) {
+            if t.child_order != Some(order) {
+                result.push((t.id.clone(), order));
+            }
+        }
+    }
+
+    result
 }
 
 #[async_trait]
@@ -256,13 +377,100 @@ impl ProviderTrait for Provider {
     async fn reload(&mut self) {
         self.projects.clear();
         self.tasks.clear();
+        self.collaborators.clear();
+    }
+
+    fn sync_state(&self) -> SyncState {
+        SyncState {
+            cache_path: None,
+            cached_objects: self.tasks.len(),
+            last_sync: self.last_sync,
+        }
     }
 
     fn capabilities(&self) -> Capabilities {
-        Capabilities { create_task: true }
+        Capabilities {
+            create_task: true,
+            labels: false,
+            subtasks: true,
+            manual_order: true,
+            assignees: true,
+            queries: true,
+            url: false,
+        }
     }
 
     fn supported_priorities(&self) -> Vec<Priority> {
         task::SUPPORTED_PRIORITIES.into()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn task(id: &str, section_id: Option<&str>, child_order: i32) -> task::Task {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "user_id": "user",
+            "project_id": "project",
+            "section_id": section_id,
+            "content": id,
+            "child_order": child_order,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn reordered_test() {
+        struct Case<'a> {
+            name: &'a str,
+            tasks: Vec<task::Task>,
+            expected: &'a [(&'a str, i32)],
+        }
+        let cases = [
+            Case {
+                name: "the same order",
+                tasks: vec![task("a", None, 1), task("b", None, 2)],
+                expected: &[],
+            },
+            Case {
+                name: "swapped neighbours",
+                tasks: vec![task("b", None, 2), task("a", None, 1), task("c", None, 3)],
+                expected: &[("b", 1), ("a", 2)],
+            },
+            Case {
+                name: "the gaps are kept",
+                tasks: vec![task("c", None, 7), task("a", None, 2), task("b", None, 5)],
+                expected: &[("c", 2), ("a", 5), ("b", 7)],
+            },
+            Case {
+                name: "equal orders",
+                tasks: vec![task("b", None, 0), task("a", None, 0)],
+                expected: &[("a", 1)],
+            },
+            Case {
+                name: "different sections",
+                tasks: vec![
+                    task("b", Some("s1"), 2),
+                    task("x", Some("s2"), 1),
+                    task("a", Some("s1"), 1),
+                ],
+                expected: &[("b", 1), ("a", 2)],
+            },
+        ];
+
+        for c in cases {
+            let tasks = c.tasks.iter().collect::<Vec<&task::Task>>();
+            assert_eq!(
+                reordered(&tasks),
+                c.expected
+                    .iter()
+                    .map(|(id, o)| (id.to_string(), *o))
+                    .collect::<Vec<(String, i32)>>(),
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+}
@@ -1,13 +1,14 @@
 // SPDX-License-Identifier: MIT
 
 use super::{project::Project, task::Task};
-use reqwest::header::HeaderMap;
+use crate::http::ReqwestClient;
 use serde::{Deserialize, Serialize};
-use std::error::Error;
-use tatuin_core::{filter, project::Project as ProjectTrait};
-use url::Url;
-use url_builder::URLBuilder;
-use urlencoding::encode;
+use std::{error::Error, sync::Arc};
+use tatuin_core::{
+    filter,
+    http::{Request, SharedHttpClient},
+    project::Project as ProjectTrait,
+};
 
 const BASE_URL: &str = "https://todoist.com/api/v1";
 
@@ -18,6 +19,8 @@ pub struct CreateTaskRequest<'a> {
     pub project_id: Option<&'a str>,
     pub due_string: Option<&'a str>,
     pub priority: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<&'a str>,
 }
 
 #[derive(Debug, Serialize)]
@@ -28,22 +31,71 @@ pub struct UpdateTaskRequest<'a> {
     pub priority: Option<i32>,
 }
 
+#[derive(Debug, Serialize)]
+struct ReorderItem<'a> {
+    id: &'a str,
+    child_order: i32,
+}
+
+#[derive(Debug, Serialize)]
+struct ReorderArgs<'a> {
+    items: Vec<ReorderItem<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct SyncCommand<'a> {
+    #[serde(rename = "type")]
+    type_name: &'a str,
+    uuid: String,
+    args: ReorderArgs<'a>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SyncResponse {
+    #[serde(default)]
+    sync_status: std::collections::HashMap<String, serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct User {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Collaborator {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct CollaboratorResponse {
+    pub results: Vec<Collaborator>,
+    pub next_cursor: Option<String>,
+}
+
 pub struct Client {
-    default_header: HeaderMap,
-    client: reqwest::Client,
+    api_key: String,
+    http: SharedHttpClient,
 }
 
 #[allow(dead_code)]
 impl Client {
     pub fn new(api_key: &str) -> Self {
-        let mut headers = HeaderMap::new();
-        headers.insert("Authorization", format!("Bearer {api_key}").parse().unwrap());
         Self {
-            default_header: headers,
-            client: reqwest::Client::new(),
+            api_key: api_key.to_string(),
+            http: Arc::new(ReqwestClient::default()),
         }
     }
 
+    pub fn with_http_client(mut self, http: SharedHttpClient) -> Self {
+        self.http = http;
+        self
+    }
+
+    fn request(&self, r: Request) -> Request {
+        r.with_bearer_auth(&self.api_key)
+    }
+
     pub async fn completed_tasks(
         &self,
         project_id: &Option<String>,
@@ -51,28 +103,24 @@ impl Client {
     ) -> Result<Vec<Task>, Box<dyn Error>> {
         let mut result: Vec<Task> = Vec::new();
 
-        let mut cursor = None;
-
-        let query = {
-            let mut v = vec![
-                String::from("limit=200"),
-                format!(
-                    "since={}",
-                    chrono::Utc::now()
-                        .checked_sub_days(chrono::Days::new(7))
-                        .unwrap()
-                        .format("%Y-%m-%dT%H:%M:%SZ")
-                ),
-                format!("until={}", chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")),
-                format!("filter_query={}", filter_to_query(&None, f)),
-            ];
-
-            if let Some(p) = project_id {
-                v.push(format!("project_id={p}"));
-            }
+        let mut cursor: Option<String> = None;
 
-            v
-        };
+        let since = chrono::Utc::now()
+            .checked_sub_days(chrono::Days::new(7))
+            .unwrap()
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        let until = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string();
+        let filter_query = filter_to_query(&None, f);
+        let mut query = vec![
+            ("limit", "200"),
+            ("since", since.as_str()),
+            ("until", until.as_str()),
+            ("filter_query", filter_query.as_str()),
+        ];
+        if let Some(p) = project_id {
+            query.push(("project_id", p.as_str()));
+        }
 
         #[derive(Deserialize)]
         struct Response {
@@ -82,17 +130,14 @@ impl Client {
 
         loop {
             let mut q = query.clone();
-            if let Some(c) = cursor {
-                q.push(format!("cursor={c}"));
+            if let Some(c) = &cursor {
+                q.push(("cursor", c.as_str()));
             }
             let mut resp = self
-                .client
-                .get(format!("{BASE_URL}/tasks/completed?{}", &q.join("&")))
-                .headers(self.default_header.clone())
-                .send()
+                .http
+                .send_checked(self.request(Request::get(&format!("{BASE_URL}/tasks/completed")).with_query(&q)))
                 .await?
-                .json::<Response>()
-                .await?;
+                .json::<Response>()?;
 
             result.append(&mut resp.items);
 
@@ -111,16 +156,24 @@ impl Client {
         project: &Option<Box<dyn ProjectTrait>>,
         f: &filter::Filter,
     ) -> Result<Vec<Task>, Box<dyn Error>> {
-        let mut result: Vec<Task> = Vec::new();
-
-        let u = Url::parse(BASE_URL).unwrap();
-        let mut cursor: Option<String> = None;
-
         let mut project_name = None;
         if let Some(p) = project {
             project_name = Some(p.name())
         }
 
+        self.filtered_tasks(filter_to_query(&project_name, f).as_str()).await
+    }
+
+    // The tasks by the query in the Todoist filter language
+    pub async fn tasks_by_query(&self, query: &str) -> Result<Vec<Task>, Box<dyn Error>> {
+        self.filtered_tasks(query).await
+    }
+
+    async fn filtered_tasks(&self, query: &str) -> Result<Vec<Task>, Box<dyn Error>> {
+        let mut result: Vec<Task> = Vec::new();
+
+        let mut cursor: Option<String> = None;
+
         #[derive(Deserialize, Debug)]
         struct Response {
             pub results: Vec<Task>,
@@ -128,27 +181,16 @@ impl Client {
         }
 
         loop {
-            let mut url = URLBuilder::new();
-            url.set_protocol(u.scheme())
-                .set_host(u.host_str().unwrap_or_default())
-                .set_port(u.port().unwrap_or_default())
-                .add_route("api/v1/tasks/filter")
-                .add_param("limit", "200")
-                .add_param("query", filter_to_query(&project_name, f).as_str());
-
-            if let Some(c) = cursor {
-                url.add_param("cursor", c.as_str());
+            let mut q = vec![("limit", "200"), ("query", query)];
+            if let Some(c) = &cursor {
+                q.push(("cursor", c.as_str()));
             }
-            let built_url = url.build();
 
             let mut resp = self
-                .client
-                .get(built_url)
-                .headers(self.default_header.clone())
-                .send()
+                .http
+                .send_checked(self.request(Request::get(&format!("{BASE_URL}/tasks/filter")).with_query(&q)))
                 .await?
-                .json::<Response>()
-                .await?;
+                .json::<Response>()?;
 
             result.append(&mut resp.results);
 
@@ -165,22 +207,19 @@ impl Client {
     pub async fn projects(&self) -> Result<Vec<Project>, Box<dyn Error>> {
         let mut result: Vec<Project> = Vec::new();
 
-        let mut cursor = None;
+        let mut cursor: Option<String> = None;
 
         loop {
-            let mut query: String = String::from("?limit=200");
-            if let Some(c) = cursor {
-                query.push_str(format!("&cursor={c}").as_str());
+            let mut query = vec![("limit", "200")];
+            if let Some(c) = &cursor {
+                query.push(("cursor", c.as_str()));
             }
 
             let mut resp = self
-                .client
-                .get(format!("{BASE_URL}/projects{query}"))
-                .headers(self.default_header.clone())
-                .send()
+                .http
+                .send_checked(self.request(Request::get(&format!("{BASE_URL}/projects")).with_query(&query)))
                 .await?
-                .json::<ProjectResponse>()
-                .await?;
+                .json::<ProjectResponse>()?;
 
             result.append(&mut resp.results);
 
@@ -195,86 +234,136 @@ impl Client {
     }
 
     pub async fn project(&self, id: &str) -> Result<Project, Box<dyn Error>> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/projects/{id}"))
-            .headers(self.default_header.clone())
-            .send()
+        Ok(self
+            .http
+            .send_checked(self.request(Request::get(&format!("{BASE_URL}/projects/{id}"))))
             .await?
-            .json::<Project>()
-            .await?;
+            .json::<Project>()?)
+    }
+
+    pub async fn user(&self) -> Result<User, Box<dyn Error>> {
+        Ok(self
+            .http
+            .send_checked(self.request(Request::get(&format!("{BASE_URL}/user"))))
+            .await?
+            .json::<User>()?)
+    }
+
+    // The users of the shared project
+    pub async fn collaborators(&self, project_id: &str) -> Result<Vec<Collaborator>, Box<dyn Error>> {
+        let mut result = Vec::new();
+
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = vec![("limit", "200")];
+            if let Some(c) = &cursor {
+                query.push(("cursor", c.as_str()));
+            }
+
+            let mut resp = self
+                .http
+                .send_checked(self.request(
+                    Request::get(&format!("{BASE_URL}/projects/{project_id}/collaborators")).with_query(&query),
+                ))
+                .await?
+                .json::<CollaboratorResponse>()?;
+
+            result.append(&mut resp.results);
+
+            if resp.next_cursor.is_none() {
+                break;
+            }
+
+            cursor = resp.next_cursor;
+        }
 
-        Ok(resp)
+        Ok(result)
     }
 
     pub async fn close_task(&self, task_id: &str) -> Result<(), Box<dyn Error>> {
-        let resp = self
-            .client
-            .post(format!("{BASE_URL}/tasks/{task_id}/close"))
-            .headers(self.default_header.clone())
-            .send()
+        self.http
+            .send_checked(self.request(Request::post(&format!("{BASE_URL}/tasks/{task_id}/close"))))
             .await?;
-        if resp.status().is_success() {
-            return Ok(());
-        }
-        Err(Box::<dyn Error>::from(format!(
-            "wrong status: {}",
-            resp.status().as_str()
-        )))
+        Ok(())
     }
 
     pub async fn reopen_task(&self, task_id: &str) -> Result<(), Box<dyn Error>> {
-        self.client
-            .post(format!("{BASE_URL}/tasks/{task_id}/reopen"))
-            .headers(self.default_header.clone())
-            .send()
+        self.http
+            .send_checked(self.request(Request::post(&format!("{BASE_URL}/tasks/{task_id}/reopen"))))
             .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"todoist_client", task_id=?task_id, error=?e, "Reopen the task");
-                Box::<dyn Error>::from(e.to_string())
+                Box::<dyn Error>::from(e)
             })
     }
 
     pub async fn update_task(&self, task_id: &str, r: &UpdateTaskRequest<'_>) -> Result<(), Box<dyn Error>> {
-        self.client
-            .post(format!("{BASE_URL}/tasks/{task_id}"))
-            .json(r)
-            .headers(self.default_header.clone())
-            .send()
+        self.http
+            .send_checked(self.request(Request::post(&format!("{BASE_URL}/tasks/{task_id}")).with_json(r)?))
             .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"todoist_client", request=?r, error=?e, "Update the task");
-                Box::<dyn Error>::from(e.to_string())
+                Box::<dyn Error>::from(e)
             })
     }
 
     pub async fn create_task(&self, r: &CreateTaskRequest<'_>) -> Result<(), Box<dyn Error>> {
-        self.client
-            .post(format!("{BASE_URL}/tasks"))
-            .json(r)
-            .headers(self.default_header.clone())
-            .send()
+        self.http
+            .send_checked(self.request(Request::post(&format!("{BASE_URL}/tasks")).with_json(r)?))
             .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"todoist_client", request=?r, error=?e, "Create the task");
-                Box::<dyn Error>::from(e.to_string())
+                Box::<dyn Error>::from(e)
             })
     }
 
-    pub async fn delete_task(&self, task_id: &str) -> Result<(), Box<dyn Error>> {
-        self.client
-            .delete(format!("{BASE_URL}/tasks/{task_id}"))
-            .headers(self.default_header.clone())
-            .send()
+    // The REST API can't change the order, so the item_reorder command of the sync API is used
+    pub async fn reorder_tasks(&self, orders: &[(String, i32)]) -> Result<(), Box<dyn Error>> {
+        let commands = [SyncCommand {
+            type_name: "item_reorder",
+            uuid: uuid::Uuid::new_v4().to_string(),
+            args: ReorderArgs {
+                items: orders
+                    .iter()
+                    .map(|(id, child_order)| ReorderItem {
+                        id: id.as_str(),
+                        child_order: *child_order,
+                    })
+                    .collect(),
+            },
+        }];
+        let commands = serde_json::to_string(&commands)?;
+
+        let resp = self
+            .http
+            .send_checked(
+                self.request(Request::post(&format!("{BASE_URL}/sync")).with_form(&[("commands", &commands)])),
+            )
             .await?
-            .error_for_status()
+            .json::<SyncResponse>()?;
+
+        // the sync API answers with 200 and reports the failed commands in the status
+        match resp.sync_status.values().find(|v| v.as_str() != Some("ok")) {
+            Some(e) => {
+                tracing::error!(target:"todoist_client", orders=?orders, error=?e, "Reorder the tasks");
+                Err(format!("Reorder the tasks: {e}").into())
+            }
+            None => Ok(()),
+        }
+    }
+
+    pub async fn delete_task(&self, task_id: &str) -> Result<(), Box<dyn Error>> {
+        self.http
+            .send_checked(self.request(Request::delete(&format!("{BASE_URL}/tasks/{task_id}"))))
+            .await
             .map(|_| ())
             .map_err(|e| {
                 tracing::error!(target:"todoist_client", task_id=task_id, error=?e, "Delete the task");
-                Box::<dyn Error>::from(e.to_string())
+                Box::<dyn Error>::from(e)
             })
     }
 }
@@ -317,5 +406,76 @@ fn filter_to_query(project_name: &Option<String>, f: &filter::Filter) -> String
         and_filter.push(format!("#{p}"));
     }
 
-    encode(and_filter.join("&").as_str()).into_owned()
+    and_filter.join("&")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tatuin_core::{
+        http::{Method, MockHttpClient, Response},
+        provider::ProviderError,
+    };
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn tasks_by_query_test() {
+        const FIRST_URL: &str = "https://todoist.com/api/v1/tasks/filter?limit=200&query=today+%7C+%23Work";
+        const SECOND_URL: &str =
+            "https://todoist.com/api/v1/tasks/filter?limit=200&query=today+%7C+%23Work&cursor=next";
+        let task = |id: &str| {
+            serde_json::json!({
+                "id": id,
+                "user_id": "user",
+                "project_id": "project",
+                "content": id,
+                "child_order": 1,
+            })
+        };
+        let http = Arc::new(
+            MockHttpClient::new()
+                .with_response(
+                    Method::Get,
+                    FIRST_URL,
+                    Response::new(
+                        200,
+                        serde_json::json!({"results": [task("1")], "next_cursor": "next"})
+                            .to_string()
+                            .as_bytes(),
+                    ),
+                )
+                .with_response(
+                    Method::Get,
+                    SECOND_URL,
+                    Response::new(
+                        200,
+                        serde_json::json!({"results": [task("2")], "next_cursor": null})
+                            .to_string()
+                            .as_bytes(),
+                    ),
+                ),
+        );
+
+        let c = Client::new("token").with_http_client(http.clone());
+        let tasks = c.tasks_by_query("today | #Work").await.unwrap();
+        assert_eq!(tasks.iter().map(|t| t.id.as_str()).collect::<Vec<&str>>(), ["1", "2"]);
+
+        let requests = http.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0].headers,
+            [("Authorization".to_string(), "Bearer token".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn close_task_test() {
+        let c = Client::new("token").with_http_client(Arc::new(MockHttpClient::new()));
+        let err = ProviderError::from(c.close_task("1").await.unwrap_err());
+        assert_eq!(
+            err,
+            ProviderError::NotFound("404 for url https://todoist.com/api/v1/tasks/1/close".to_string())
+        );
+    }
 }
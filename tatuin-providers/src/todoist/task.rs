@@ -61,6 +61,12 @@ pub struct Task {
 
     pub project: Option<Project>,
     pub provider: Option<String>,
+    // the name of the responsible collaborator
+    #[serde(skip)]
+    pub assignee_name: Option<String>,
+    // the id of the token owner
+    #[serde(skip)]
+    pub me: Option<String>,
 }
 
 fn str_to_date(s: &str) -> Option<DateTimeUtc> {
@@ -85,6 +91,14 @@ impl TaskTrait for Task {
         self.id.to_string()
     }
 
+    fn order(&self) -> Option<i64> {
+        self.child_order.map(i64::from)
+    }
+
+    fn parent_id(&self) -> Option<String> {
+        self.parent_id.clone()
+    }
+
     fn name(&self) -> RichString {
         RichString::new(&self.content)
     }
@@ -166,6 +180,29 @@ impl TaskTrait for Task {
         self.labels.clone().unwrap_or_default()
     }
 
+    fn assignee(&self) -> Option<String> {
+        let uid = self.responsible_uid.as_ref()?;
+        Some(self.assignee_name.clone().unwrap_or(uid.to_string()))
+    }
+
+    fn is_assigned_to_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        // all the tasks of the personal projects are mine
+        if !self.project.as_ref().is_some_and(|p| p.is_shared) {
+            return Some(true);
+        }
+        Some(self.responsible_uid.as_ref() == Some(me))
+    }
+
+    fn comment_count(&self) -> Option<u64> {
+        Some(u64::try_from(self.note_count.unwrap_or_default()).unwrap_or_default())
+    }
+
+    fn is_created_by_me(&self) -> Option<bool> {
+        let me = self.me.as_ref()?;
+        Some(self.added_by_uid.as_ref().is_none_or(|uid| uid == me))
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
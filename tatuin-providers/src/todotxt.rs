@@ -0,0 +1,360 @@
+// SPDX-License-Identifier: MIT
+
+mod client;
+mod project;
+mod task;
+
+use std::{path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::Local;
+use itertools::Itertools;
+use tatuin_core::{
+    filter,
+    fs::SharedFileSystem,
+    intern::intern,
+    project::Project as ProjectTrait,
+    provider::{Capabilities, ProjectProviderTrait, ProviderError, ProviderTrait, TaskProviderTrait},
+    task::{DateTimeUtc, State, Task as TaskTrait},
+    task_patch::{PatchError, PatchErrorKind, TaskPatch},
+};
+
+use crate::config::Config;
+
+pub const PROVIDER_NAME: &str = "TodoTxt";
+
+pub struct Provider {
+    cfg: Config,
+    c: client::Client,
+}
+
+impl Provider {
+    pub fn new(cfg: Config, path: &Path) -> Self {
+        Self {
+            cfg,
+            c: client::Client::new(path),
+        }
+    }
+
+    pub fn with_file_system(mut self, fs: SharedFileSystem) -> Self {
+        self.c = self.c.with_file_system(fs);
+        self
+    }
+}
+
+impl std::fmt::Debug for Provider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Provider name={}", ProviderTrait::name(self))
+    }
+}
+
+#[async_trait]
+impl ProjectProviderTrait for Provider {
+    async fn list(&mut self) -> Result<Vec<Box<dyn ProjectTrait>>, ProviderError> {
+        let name = self.name();
+        let mut result: Vec<Box<dyn ProjectTrait>> = vec![Box::new(project::Project::new(&name, None))];
+        for p in self
+            .c
+            .tasks()
+            .await?
+            .iter()
+            .flat_map(|t| t.projects())
+            .unique()
+            .sorted()
+        {
+            result.push(Box::new(project::Project::new(&name, Some(&p))));
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl TaskProviderTrait for Provider {
+    async fn list(
+        &mut self,
+        project: Option<Box<dyn ProjectTrait>>,
+        f: &filter::Filter,
+    ) -> Result<Vec<Box<dyn TaskTrait>>, ProviderError> {
+        let name = intern(&self.name());
+        let project_id = project.map(|p| p.id());
+
+        let mut result: Vec<Box<dyn TaskTrait>> = Vec::new();
+        for mut t in self.c.tasks().await? {
+            if let Some(id) = &project_id {
+                let projects = t.projects();
+                if (id.is_empty() && !projects.is_empty()) || (!id.is_empty() && !projects.contains(id)) {
+                    continue;
+                }
+            }
+
+            t.set_provider(name.clone());
+            if f.accept(&t) {
+                result.push(Box::new(t));
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create(&mut self, project_id: &str, tp: &TaskPatch) -> Result<(), ProviderError> {
+        let mut t = task::Task {
+            creation_date: Some(Local::now().date_naive()),
+            ..task::Task::default()
+        };
+
+        apply_patch(&mut t, tp);
+
+        if !project_id.is_empty() && !t.projects().iter().any(|p| p == project_id) {
+            t.text.push_str(format!(" +{project_id}").as_str());
+        }
+
+        self.c.append(&t).await
+    }
+
+    async fn update(&mut self, patches: &[TaskPatch]) -> Vec<PatchError> {
+        let mut changes = Vec::new();
+        let mut errors = Vec::new();
+        for p in patches {
+            let task = p.task.as_ref().unwrap();
+
+            match task.as_any().downcast_ref::<task::Task>() {
+                Some(t) => {
+                    let mut new_task = t.clone();
+                    apply_patch(&mut new_task, p);
+                    changes.push((t, Some(new_task)));
+                }
+                None => errors.push(PatchError::wrong_task_type(task.as_ref(), PROVIDER_NAME)),
+            };
+        }
+
+        errors.extend(self.c.replace(&changes).await.into_iter().map(|e| PatchError {
+            task: Arc::new(e.task.clone()),
+            error: e.error,
+            kind: PatchErrorKind::Other,
+        }));
+        errors
+    }
+
+    async fn delete(&mut self, t: &dyn TaskTrait) -> Result<(), ProviderError> {
+        let t = t
+            .as_any()
+            .downcast_ref::<task::Task>()
+            .ok_or_else(|| ProviderError::wrong_task_type(t, PROVIDER_NAME))?;
+        match self.c.replace(&[(t, None)]).await.first() {
+            Some(e) => {
+                tracing::error!(error=?e.error, name=?t.name(), id=t.id(), "Delete the task");
+                Err(ProviderError::other(e.error.as_str()))
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl ProviderTrait for Provider {
+    fn name(&self) -> String {
+        self.cfg.name()
+    }
+
+    fn type_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    async fn reload(&mut self) {
+        // do nothing for now
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            create_task: true,
+            labels: false,
+            subtasks: false,
+            manual_order: false,
+            assignees: false,
+            queries: false,
+            url: false,
+        }
+    }
+}
+
+fn apply_patch(t: &mut task::Task, tp: &TaskPatch) {
+    if let Some(n) = tp.name.value() {
+        t.text = n;
+    }
+
+    if let Some(p) = tp.priority.value() {
+        t.priority = task::priority_to_char(p);
+    }
+
+    if let Some(s) = tp.state.value() {
+        t.completed = s == State::Completed;
+        t.completion_date = if t.completed {
+            Some(Local::now().date_naive())
+        } else {
+            None
+        };
+    }
+
+    if tp.due.is_set() {
+        let due: Option<DateTimeUtc> = tp.due.value().and_then(|d| d.into());
+        t.due = due.map(|d| d.date_naive());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::Config;
+    use std::sync::Arc;
+    use tatuin_core::{
+        filter::Filter,
+        fs::{FileSystem, MemoryFileSystem},
+        task::Priority,
+        task_patch::{DatePatchItem, ValuePatch},
+    };
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn list_projects_and_tasks() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let path = temp_dir.path().join("todo.txt");
+        std::fs::write(
+            &path,
+            "(A) Call mom +Family @phone\nx 2025-01-01 Pay bills +Home\n\nBuy milk +Home +Family\nRead a book\n",
+        )
+        .unwrap();
+
+        let mut p = Provider::new(Config::new("test_app", "test_name"), &path);
+
+        let projects = ProjectProviderTrait::list(&mut p).await.unwrap();
+        assert_eq!(
+            projects.iter().map(|p| p.name()).collect::<Vec<_>>(),
+            vec!["Inbox", "Family", "Home"]
+        );
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 4);
+        assert_eq!(tasks[0].priority(), Priority::Highest);
+        assert_eq!(tasks[0].labels(), vec!["phone"]);
+        assert_eq!(tasks[1].state(), State::Completed);
+        assert_eq!(tasks[3].project().unwrap().name(), "Inbox");
+
+        let home_tasks = TaskProviderTrait::list(&mut p, Some(projects[2].clone_boxed()), &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(home_tasks.len(), 2);
+
+        let inbox_tasks = TaskProviderTrait::list(&mut p, Some(projects[0].clone_boxed()), &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(inbox_tasks.len(), 1);
+        assert_eq!(inbox_tasks[0].name().raw(), "Read a book");
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn create_update_delete_tasks() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let path = temp_dir.path().join("todo.txt");
+
+        let mut p = Provider::new(Config::new("test_app", "test_name"), &path);
+
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Call mom".to_string()),
+            priority: ValuePatch::Value(Priority::High),
+            due: ValuePatch::Value(DatePatchItem::Custom(
+                chrono::NaiveDate::from_ymd_opt(2025, 1, 5)
+                    .unwrap()
+                    .and_hms_opt(0, 0, 0)
+                    .unwrap()
+                    .and_utc(),
+            )),
+            ..TaskPatch::default()
+        };
+        p.create("Family", &tp).await.unwrap();
+        let tp = TaskPatch {
+            name: ValuePatch::Value("Read a book".to_string()),
+            ..TaskPatch::default()
+        };
+        p.create("", &tp).await.unwrap();
+
+        let today = Local::now().date_naive().format("%Y-%m-%d");
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("(B) {today} Call mom +Family due:2025-01-05\n{today} Read a book\n")
+        );
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        let tp = TaskPatch {
+            task: Some(tasks[0].clone_boxed().into()),
+            name: ValuePatch::Value("Call dad +Family".to_string()),
+            state: ValuePatch::Value(State::Completed),
+            due: ValuePatch::Empty,
+            ..TaskPatch::default()
+        };
+        assert!(p.update(&[tp]).await.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("x {today} {today} Call dad +Family pri:B\n{today} Read a book\n")
+        );
+
+        // the task was loaded before the update, but the line is found by its content
+        assert!(p.delete(tasks[1].as_ref()).await.is_ok());
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("x {today} {today} Call dad +Family pri:B\n")
+        );
+
+        assert!(p.delete(tasks[1].as_ref()).await.is_err());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn changed_outside() {
+        let path = Path::new("/notes/todo.txt");
+        let fs = Arc::new(MemoryFileSystem::new().with_file(path, "Call mom\nRead a book\n"));
+        let mut p = Provider::new(Config::new("test_app", "test_name"), path).with_file_system(fs.clone());
+
+        let tasks = TaskProviderTrait::list(&mut p, None, &Filter::full_filter())
+            .await
+            .unwrap();
+        assert_eq!(tasks.len(), 2);
+
+        fs.write(path, b"Call dad\nRead a book\n").await.unwrap();
+
+        let tp = TaskPatch {
+            task: Some(tasks[0].clone_boxed().into()),
+            name: ValuePatch::Value("Call grandma".to_string()),
+            ..TaskPatch::default()
+        };
+        let errors = p.update(&[tp]).await;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error, "The task has been changed outside");
+
+        assert!(p.delete(tasks[1].as_ref()).await.is_ok());
+        assert_eq!(fs.content(path).unwrap(), b"Call dad\n");
+    }
+
+    #[cfg(feature = "ical")]
+    #[tokio::test]
+    #[cfg_attr(miri, ignore)]
+    async fn wrong_task_type() {
+        let temp_dir = tempfile::tempdir().expect("Can't create a temp dir");
+        let mut p = Provider::new(Config::new("test_app", "test_name"), &temp_dir.path().join("todo.txt"));
+
+        let foreign = crate::ical::Task::default();
+        let tp = TaskPatch {
+            task: Some(foreign.clone_boxed().into()),
+            name: ValuePatch::Value("Foreign task".to_string()),
+            ..TaskPatch::default()
+        };
+        let errors = p.update(&[tp]).await;
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].error.contains(PROVIDER_NAME));
+
+        assert!(matches!(p.delete(&foreign).await, Err(ProviderError::Other(_))));
+    }
+}
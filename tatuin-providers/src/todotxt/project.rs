@@ -1,24 +1,21 @@
 // SPDX-License-Identifier: MIT
 
-use std::path::{Path, PathBuf};
-
 use tatuin_core::project::Project as ProjectTrait;
 
-use crate::obsidian::fs;
+const INBOX_NAME: &str = "Inbox";
 
 #[derive(Clone)]
 pub struct Project {
     provider: String,
-    vault_path: PathBuf,
-    file_path: PathBuf,
+    name: Option<String>,
 }
 
 impl Project {
-    pub fn new(provider: &str, vault_path: &Path, file_path: &Path) -> Self {
+    // The task without any +project lives in the inbox
+    pub fn new(provider: &str, name: Option<&str>) -> Self {
         Self {
             provider: provider.to_string(),
-            vault_path: vault_path.into(),
-            file_path: file_path.into(),
+            name: name.map(|s| s.to_string()),
         }
     }
 }
@@ -36,18 +33,11 @@ impl std::fmt::Debug for Project {
 
 impl ProjectTrait for Project {
     fn id(&self) -> String {
-        fs::strip_root_str(&self.vault_path, &self.file_path)
+        self.name.clone().unwrap_or_default()
     }
 
     fn name(&self) -> String {
-        self.file_path
-            .file_name()
-            .unwrap_or_default()
-            .to_str()
-            .unwrap_or_default()
-            .strip_suffix(".md")
-            .unwrap_or_default()
-            .to_string()
+        self.name.clone().unwrap_or(INBOX_NAME.to_string())
     }
 
     fn provider(&self) -> String {
@@ -63,7 +53,7 @@ impl ProjectTrait for Project {
     }
 
     fn is_inbox(&self) -> bool {
-        false
+        self.name.is_none()
     }
 
     fn is_favorite(&self) -> bool {
@@ -0,0 +1,380 @@
+// SPDX-License-Identifier: MIT
+
+use std::any::Any;
+
+use chrono::NaiveDate;
+use tatuin_core::{
+    RichString,
+    intern::IStr,
+    project::Project as ProjectTrait,
+    task::{DateTimeUtc, PatchPolicy, Priority, State, Task as TaskTrait},
+    task_patch::DatePatchItem,
+};
+
+use super::project::Project;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DUE_KEY: &str = "due:";
+const PRIORITY_KEY: &str = "pri:";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Task {
+    pub provider: IStr,
+    pub line: usize,
+    pub raw: String,
+
+    pub completed: bool,
+    pub priority: Option<char>,
+    pub completion_date: Option<NaiveDate>,
+    pub creation_date: Option<NaiveDate>,
+    pub text: String,
+    pub due: Option<NaiveDate>,
+}
+
+fn parse_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s, DATE_FORMAT).ok()
+}
+
+fn date_to_datetime(d: &Option<NaiveDate>) -> Option<DateTimeUtc> {
+    d.and_then(|d| d.and_hms_opt(0, 0, 0)).map(|dt| dt.and_utc())
+}
+
+fn parse_priority(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next(), chars.next(), chars.next()) {
+        (Some('('), Some(c), Some(')'), None) if c.is_ascii_uppercase() => Some(c),
+        _ => None,
+    }
+}
+
+pub fn priority_from_char(c: Option<char>) -> Priority {
+    match c {
+        Some('A') => Priority::Highest,
+        Some('B') => Priority::High,
+        Some('C') => Priority::Medium,
+        Some('D') => Priority::Low,
+        Some(_) => Priority::Lowest,
+        None => Priority::Normal,
+    }
+}
+
+pub fn priority_to_char(p: Priority) -> Option<char> {
+    match p {
+        Priority::Highest => Some('A'),
+        Priority::High => Some('B'),
+        Priority::Medium => Some('C'),
+        Priority::Low => Some('D'),
+        Priority::Lowest => Some('E'),
+        Priority::Normal => None,
+    }
+}
+
+impl Task {
+    pub fn parse(line: &str) -> Option<Self> {
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        let mut t = Task {
+            raw: line.to_string(),
+            ..Task::default()
+        };
+
+        let mut words = line.split_whitespace().peekable();
+
+        if words.peek() == Some(&"x") {
+            t.completed = true;
+            words.next();
+            if let Some(d) = words.peek().and_then(|w| parse_date(w)) {
+                t.completion_date = Some(d);
+                words.next();
+            }
+        } else if let Some(p) = words.peek().and_then(|w| parse_priority(w)) {
+            t.priority = Some(p);
+            words.next();
+        }
+
+        if let Some(d) = words.peek().and_then(|w| parse_date(w)) {
+            t.creation_date = Some(d);
+            words.next();
+        }
+
+        let mut text = Vec::new();
+        for w in words {
+            if let Some(d) = w.strip_prefix(DUE_KEY).and_then(parse_date) {
+                t.due = Some(d);
+            } else if t.completed
+                && t.priority.is_none()
+                && let Some(p) = w.strip_prefix(PRIORITY_KEY).and_then(|p| p.chars().next())
+                && p.is_ascii_uppercase()
+            {
+                // the priority of completed tasks is kept as a key to be restored on uncompleting
+                t.priority = Some(p);
+            } else {
+                text.push(w);
+            }
+        }
+        t.text = text.join(" ");
+
+        Some(t)
+    }
+
+    pub fn projects(&self) -> Vec<String> {
+        self.text
+            .split_whitespace()
+            .filter_map(|w| w.strip_prefix('+'))
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    pub fn contexts(&self) -> Vec<String> {
+        self.text
+            .split_whitespace()
+            .filter_map(|w| w.strip_prefix('@'))
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    pub fn set_provider(&mut self, name: IStr) {
+        self.provider = name;
+    }
+}
+
+impl std::fmt::Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+
+        if self.completed {
+            parts.push("x".to_string());
+            if let Some(d) = self.completion_date {
+                parts.push(d.format(DATE_FORMAT).to_string());
+            }
+        } else if let Some(p) = self.priority {
+            parts.push(format!("({p})"));
+        }
+
+        if let Some(d) = self.creation_date {
+            parts.push(d.format(DATE_FORMAT).to_string());
+        }
+
+        if !self.text.is_empty() {
+            parts.push(self.text.clone());
+        }
+
+        if let Some(d) = self.due {
+            parts.push(format!("{DUE_KEY}{}", d.format(DATE_FORMAT)));
+        }
+
+        if self.completed
+            && let Some(p) = self.priority
+        {
+            parts.push(format!("{PRIORITY_KEY}{p}"));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl TaskTrait for Task {
+    fn id(&self) -> String {
+        sha256::digest(format!("{}:{}", self.line, self.raw))
+    }
+
+    fn name(&self) -> RichString {
+        RichString::new(&self.text)
+    }
+
+    fn priority(&self) -> Priority {
+        priority_from_char(self.priority)
+    }
+
+    fn state(&self) -> State {
+        if self.completed {
+            State::Completed
+        } else {
+            State::Uncompleted
+        }
+    }
+
+    fn created_at(&self) -> Option<DateTimeUtc> {
+        date_to_datetime(&self.creation_date)
+    }
+
+    fn completed_at(&self) -> Option<DateTimeUtc> {
+        date_to_datetime(&self.completion_date)
+    }
+
+    fn due(&self) -> Option<DateTimeUtc> {
+        date_to_datetime(&self.due)
+    }
+
+    fn place(&self) -> String {
+        format!("line {}", self.line + 1)
+    }
+
+    fn labels(&self) -> Vec<String> {
+        self.contexts()
+    }
+
+    fn provider(&self) -> String {
+        self.provider.to_string()
+    }
+
+    fn project(&self) -> Option<Box<dyn ProjectTrait>> {
+        Some(Box::new(Project::new(
+            &self.provider,
+            self.projects().first().map(String::as_str),
+        )))
+    }
+
+    fn const_patch_policy(&self) -> PatchPolicy {
+        PatchPolicy {
+            is_editable: true,
+            is_removable: true,
+            available_states: vec![State::Uncompleted, State::Completed],
+            available_priorities: Priority::values(),
+            available_due_items: DatePatchItem::values(),
+            available_scheduled_items: Vec::new(),
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn clone_boxed(&self) -> Box<dyn TaskTrait> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(s: &str) -> Option<NaiveDate> {
+        parse_date(s)
+    }
+
+    #[test]
+    fn parse_test() {
+        struct Case<'a> {
+            name: &'a str,
+            line: &'a str,
+            expected: Option<Task>,
+        }
+        let cases = [
+            Case {
+                name: "empty line",
+                line: "  ",
+                expected: None,
+            },
+            Case {
+                name: "only text",
+                line: "Call mom",
+                expected: Some(Task {
+                    text: "Call mom".to_string(),
+                    ..Task::default()
+                }),
+            },
+            Case {
+                name: "priority, creation date, project, context and due",
+                line: "(A) 2025-01-01 Call mom +Family @phone due:2025-01-05",
+                expected: Some(Task {
+                    priority: Some('A'),
+                    creation_date: date("2025-01-01"),
+                    text: "Call mom +Family @phone".to_string(),
+                    due: date("2025-01-05"),
+                    ..Task::default()
+                }),
+            },
+            Case {
+                name: "completed with dates and stored priority",
+                line: "x 2025-01-03 2025-01-01 Pay bills pri:B",
+                expected: Some(Task {
+                    completed: true,
+                    priority: Some('B'),
+                    completion_date: date("2025-01-03"),
+                    creation_date: date("2025-01-01"),
+                    text: "Pay bills".to_string(),
+                    ..Task::default()
+                }),
+            },
+            Case {
+                name: "wrong priority and due are kept in text",
+                line: "(a) Task due:tomorrow",
+                expected: Some(Task {
+                    text: "(a) Task due:tomorrow".to_string(),
+                    ..Task::default()
+                }),
+            },
+            Case {
+                name: "x inside the text",
+                line: "xylophone lesson",
+                expected: Some(Task {
+                    text: "xylophone lesson".to_string(),
+                    ..Task::default()
+                }),
+            },
+        ];
+
+        for c in cases {
+            let expected = c.expected.map(|mut t| {
+                t.raw = c.line.to_string();
+                t
+            });
+            assert_eq!(Task::parse(c.line), expected, "Test '{}' was failed", c.name);
+        }
+    }
+
+    #[test]
+    fn to_string_test() {
+        struct Case<'a> {
+            name: &'a str,
+            line: &'a str,
+            expected: &'a str,
+        }
+        let cases = [
+            Case {
+                name: "only text",
+                line: "Call mom",
+                expected: "Call mom",
+            },
+            Case {
+                name: "all fields",
+                line: "(A) 2025-01-01 Call due:2025-01-05 mom +Family @phone",
+                expected: "(A) 2025-01-01 Call mom +Family @phone due:2025-01-05",
+            },
+            Case {
+                name: "completed",
+                line: "x 2025-01-03 2025-01-01 Pay bills pri:B",
+                expected: "x 2025-01-03 2025-01-01 Pay bills pri:B",
+            },
+        ];
+
+        for c in cases {
+            assert_eq!(
+                Task::parse(c.line).unwrap().to_string(),
+                c.expected,
+                "Test '{}' was failed",
+                c.name
+            );
+        }
+    }
+
+    #[test]
+    fn projects_and_contexts_test() {
+        let t = Task::parse("Call +Family mom @phone +Work @ + @home").unwrap();
+        assert_eq!(t.projects(), vec!["Family", "Work"]);
+        assert_eq!(t.contexts(), vec!["phone", "home"]);
+    }
+
+    #[test]
+    fn priority_conversion_test() {
+        for p in Priority::values() {
+            assert_eq!(priority_from_char(priority_to_char(p)), p);
+        }
+        assert_eq!(priority_from_char(Some('Z')), Priority::Lowest);
+    }
+}